@@ -0,0 +1,37 @@
+import { test } from "node:test";
+import assert from "node:assert/strict";
+
+import { estimate, toFiat, toXLM } from "./amount.js";
+
+test("converts at exact rates", () => {
+  assert.equal(toXLM("0.12", "100", "down"), "12");
+  assert.equal(toXLM("3/25", "100", "down"), "12");
+  assert.equal(toXLM("12.5", "0.01", "down"), "0.125");
+  assert.equal(toXLM("10", "0", "down"), "0");
+  // 9,007,199,254.7409921 XLM is past what a float carries to the stroop.
+  assert.equal(toXLM("1", "9007199254.7409921", "down"), "9007199254.7409921");
+  assert.equal(toFiat("0.12", "1", "down"), "8.33");
+  assert.equal(toFiat("0.12", "1", "half-up"), "8.33");
+  assert.equal(toFiat("11", "1", "half-up"), "0.09");
+  assert.equal(toFiat("10", "1", "down"), "0.10");
+});
+
+test("rounds the way the caller says", () => {
+  // 0.005 of a cent either side of the half
+  assert.equal(toFiat("200", "1", "down"), "0.00");
+  assert.equal(toFiat("200", "1", "half-up"), "0.01");
+  assert.equal(toFiat("200", "1", "half-even"), "0.00");
+  assert.equal(toFiat("200", "3", "half-even"), "0.02");
+  assert.equal(toFiat("3", "0.0000001", "up"), "0.01");
+});
+
+test("refuses amounts that are not plain decimals", () => {
+  for (const amount of [String(1e21), "-1", "NaN", "", "1.5.0"]) {
+    assert.throws(() => toXLM("10", amount, "down"), RangeError);
+  }
+  assert.throws(() => toFiat("10", "0.00000001", "down"), RangeError);
+  assert.throws(() => toXLM("10", "1000000000000000000", "down"), RangeError);
+  assert.throws(() => toXLM("0", "1", "down"), RangeError);
+  assert.equal(estimate(() => toXLM("10", "-1", "down")), null);
+  assert.equal(estimate(() => toXLM("10", "0", "down")), "0");
+});
@@ -0,0 +1,125 @@
+/**
+ * StellrFlow - Exact fiat conversions
+ *
+ * `Rate::to_xlm` / `Rate::to_fiat` from crates/stellrflow-amount, in
+ * BigInt: rates are exact fractions, amounts are integers of their
+ * smallest unit, and every conversion is rounded once, the way the caller
+ * says, instead of going through float multiplication.
+ *
+ * Amounts come back as decimal strings, so nothing is lost on the way to
+ * a transaction. An amount that is negative, not a plain decimal (such
+ * as `String(1e21)`, "1e+21") or more than a payment can carry throws a
+ * RangeError.
+ *
+ * @module anchor/amount
+ */
+
+/** How a conversion that does not come out exact is rounded. */
+export type Rounding = 'down' | 'up' | 'half-up' | 'half-even';
+
+/** Decimal places of an XLM amount (stroops). */
+const XLM_DECIMALS = 7;
+/** Decimal places of a fiat payout, as the anchor's cents. */
+const FIAT_DECIMALS = 2;
+/** The most decimal places a fiat amount or rate can have. */
+const MAX_DECIMALS = 18;
+/** The most stroops a payment can carry (an int64). */
+const MAX_STROOPS = 2n ** 63n - 1n;
+
+/** `text` as an integer of 10^-`decimals` units; refuses more places. */
+function parseDecimal(text: string, decimals: number): bigint {
+  const match = /^(\d+)(?:\.(\d*))?$/.exec(text.trim());
+  const fraction = match?.[2]?.replace(/0+$/, '') ?? '';
+  if (!match || fraction.length > decimals) {
+    throw new RangeError(`${text} is not an amount with at most ${decimals} decimals`);
+  }
+  return BigInt(match[1] + fraction.padEnd(decimals, '0'));
+}
+
+/** The places `text` is written with, past any trailing zeros. */
+function placesOf(text: string): number {
+  const fraction = text.trim().split('.')[1] ?? '';
+  return Math.min(fraction.replace(/0+$/, '').length, MAX_DECIMALS);
+}
+
+/** `value` units of 10^-`decimals`, with every place if `fixed`. */
+function formatDecimal(value: bigint, decimals: number, fixed: boolean): string {
+  const digits = value.toString().padStart(decimals + 1, '0');
+  const whole = digits.slice(0, digits.length - decimals);
+  let fraction = digits.slice(digits.length - decimals);
+  if (!fixed) fraction = fraction.replace(/0+$/, '');
+  return fraction ? `${whole}.${fraction}` : whole;
+}
+
+/** A rate written as a decimal (`0.12`) or a fraction (`3/25`). */
+function parseRate(rate: string): [bigint, bigint] {
+  const parts = rate.split('/');
+  const places = placesOf(rate);
+  const [numerator, denominator] = parts.length === 2
+    ? parts.map(part => parseDecimal(part, 0))
+    : [parseDecimal(rate, places), 10n ** BigInt(places)];
+  if (parts.length > 2 || numerator === 0n || denominator === 0n) {
+    throw new RangeError(`${rate} is not an exchange rate`);
+  }
+  return [numerator, denominator];
+}
+
+/** `numerator / denominator`, both non-negative, rounded to an integer. */
+function divide(numerator: bigint, denominator: bigint, rounding: Rounding): bigint {
+  const quotient = numerator / denominator;
+  const remainder = numerator % denominator;
+  if (remainder === 0n) return quotient;
+  const twice = remainder * 2n;
+  switch (rounding) {
+    case 'down':
+      return quotient;
+    case 'up':
+      return quotient + 1n;
+    case 'half-up':
+      return twice >= denominator ? quotient + 1n : quotient;
+    case 'half-even':
+      if (twice === denominator) return quotient % 2n === 0n ? quotient : quotient + 1n;
+      return twice > denominator ? quotient + 1n : quotient;
+  }
+}
+
+/** The XLM that `fiatAmount` buys at `rate` XLM per unit, to 7 decimals. */
+export function toXLM(rate: string, fiatAmount: string, rounding: Rounding): string {
+  const [numerator, denominator] = parseRate(rate);
+  const places = placesOf(fiatAmount);
+  const minor = parseDecimal(fiatAmount, places);
+  const stroops = divide(
+    minor * numerator * 10n ** BigInt(XLM_DECIMALS),
+    denominator * 10n ** BigInt(places),
+    rounding,
+  );
+  if (stroops > MAX_STROOPS) {
+    throw new RangeError(`${fiatAmount} buys more XLM than a payment can carry`);
+  }
+  return formatDecimal(stroops, XLM_DECIMALS, false);
+}
+
+/**
+ * `convert()`, or null if it refuses the amount: for estimates shown
+ * before an amount has been checked.
+ */
+export function estimate(convert: () => string): string | null {
+  try {
+    return convert();
+  } catch (err) {
+    if (err instanceof RangeError) return null;
+    throw err;
+  }
+}
+
+/** What `xlmAmount` is worth at `rate` XLM per unit, to the cent. */
+export function toFiat(rate: string, xlmAmount: string, rounding: Rounding): string {
+  const [numerator, denominator] = parseRate(rate);
+  const stroops = parseDecimal(xlmAmount, XLM_DECIMALS);
+  const cents = divide(
+    stroops * denominator * 10n ** BigInt(FIAT_DECIMALS),
+    numerator * 10n ** BigInt(XLM_DECIMALS),
+    rounding,
+  );
+  return formatDecimal(cents, FIAT_DECIMALS, true);
+}
@@ -30,6 +30,7 @@ export {
   getUserWithdrawals,
   getWithdrawalEstimate,
   cancelWithdrawal,
+  getTreasuryAddress,
   type WithdrawalRecord,
   type WithdrawalResult,
   type WithdrawalStatus,
@@ -31,17 +31,20 @@
  *  @module anchor/mockAnchor
  */
 
+import { toFiat, toXLM } from './amount.js';
+
 // ───────────────────────────────────────────
 //  Demo exchange rates  (fiat → XLM)
 // ───────────────────────────────────────────
 //  In production these come from a market feed.
-//  Here they are constant for reproducible demos.
+//  Here they are constant for reproducible demos, and kept as
+//  exact decimals: conversions go through ./amount.ts.
 
-const RATES: Record<string, number> = {
-  USD: 10,        // 1 USD  →  10    XLM
-  EUR: 11,        // 1 EUR  →  11    XLM
-  INR: 0.12,      // 1 INR  →   0.12 XLM  (₹100 ≈ 12 XLM)
-  GBP: 12.5,      // 1 GBP  →  12.5  XLM
+const RATES: Record<string, string> = {
+  USD: '10',      // 1 USD  →  10    XLM
+  EUR: '11',      // 1 EUR  →  11    XLM
+  INR: '0.12',    // 1 INR  →   0.12 XLM  (₹100 ≈ 12 XLM)
+  GBP: '12.5',    // 1 GBP  →  12.5  XLM
 };
 
 // Processing delays — short enough for a live demo,
@@ -69,7 +72,7 @@ export interface AnchorDepositResponse {
   status: AnchorTxStatus;
   fiatAmount: number;
   fiatCurrency: string;
-  creditedXLM: number;
+  creditedXLM: string;
   exchangeRate: number;
   message: string;
   createdAt: Date;
@@ -81,9 +84,9 @@ export interface AnchorWithdrawResponse {
   transactionId: string;
   status: AnchorTxStatus;
   xlmAmount: number;
-  fiatPayout: number;
+  fiatPayout: string;
   fiatCurrency: string;
-  exchangeRate: number;
+  exchangeRate: string;
   eta: string;
   message: string;
   createdAt: Date;
@@ -110,9 +113,40 @@ function nextTxId(prefix: string): string {
  * Returns the USD rate as fallback.
  */
 export function getRate(currency: string): number {
+  return Number(exactRate(currency));
+}
+
+/**
+ * The exact fiat → XLM rate, as ./amount.ts reads it.
+ */
+function exactRate(currency: string): string {
   return RATES[currency.toUpperCase()] ?? RATES['USD'];
 }
 
+/**
+ * The XLM `fiatAmount` buys, rounded down: the anchor never credits more
+ * than the fiat paid for. Throws a RangeError for an amount that cannot be
+ * converted, such as a negative one.
+ */
+export function fiatToXLM(fiatAmount: number, currency: string = 'USD'): string {
+  return toXLM(exactRate(currency), String(fiatAmount), 'down');
+}
+
+/**
+ * What `xlmAmount`, to the stroop, pays out in fiat, rounded down to the
+ * cent. Throws a RangeError like fiatToXLM.
+ */
+export function xlmToFiat(xlmAmount: number, currency: string = 'USD'): string {
+  return toFiat(exactRate(currency), xlmAmount.toFixed(7), 'down');
+}
+
+/**
+ * What one XLM is worth in fiat, to the nearest cent, for display.
+ */
+export function fiatPerXLM(currency: string = 'USD'): string {
+  return toFiat(exactRate(currency), '1', 'half-up');
+}
+
 /**
  * Structured rate response used by the rest of the module.
  */
@@ -158,7 +192,7 @@ export async function simulateFiatDeposit(
   await sleep(DEPOSIT_DELAY_MS);
 
   const rate = getRate(currency);
-  const creditedXLM = roundXLM(fiatAmount * rate);
+  const creditedXLM = fiatToXLM(fiatAmount, currency);
   const txId = nextTxId('DEP');
   const now = new Date();
 
@@ -195,8 +229,7 @@ export async function simulateFiatWithdrawal(
 ): Promise<AnchorWithdrawResponse> {
   await sleep(WITHDRAW_DELAY_MS);
 
-  const rate = getRate(currency);
-  const fiatPayout = roundFiat(xlmAmount / rate);
+  const fiatPayout = xlmToFiat(xlmAmount, currency);
   const txId = nextTxId('WDR');
   const now = new Date();
 
@@ -206,7 +239,7 @@ export async function simulateFiatWithdrawal(
     xlmAmount,
     fiatPayout,
     fiatCurrency: currency.toUpperCase(),
-    exchangeRate: roundFiat(1 / rate),  // fiat-per-XLM
+    exchangeRate: fiatPerXLM(currency),
     eta: '5–10 min (simulated)',
     message: `Anchor payout: ${xlmAmount} XLM → ${fiatPayout} ${currency.toUpperCase()}`,
     createdAt: now,
@@ -218,14 +251,6 @@ export async function simulateFiatWithdrawal(
 //  Internal utilities
 // ───────────────────────────────────────────
 
-function roundXLM(n: number): number {
-  return Math.round(n * 1e7) / 1e7;        // Stellar 7-decimal precision
-}
-
-function roundFiat(n: number): number {
-  return Math.round(n * 100) / 100;
-}
-
 function sleep(ms: number): Promise<void> {
   return new Promise(r => setTimeout(r, ms));
 }
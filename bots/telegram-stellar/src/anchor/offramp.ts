@@ -13,7 +13,7 @@
  *  Lifecycle states:
  *    created → pending → processing → completed | failed | cancelled
  *
- *  For Telegram wallets (bot holds secret key) the debit is
+ *  For Telegram wallets (the bot can sign for them) the debit is
  *  executed on-chain automatically.
  *
  *  For Freighter wallets (user holds key) a production system
@@ -23,9 +23,11 @@
  *  @module anchor/offramp
  */
 
+import { estimate } from './amount.js';
 import {
   simulateFiatWithdrawal,
-  getExchangeRate,
+  xlmToFiat,
+  fiatPerXLM,
 } from './mockAnchor.js';
 import {
   getBalance,
@@ -49,13 +51,13 @@ export interface WithdrawalRecord {
   withdrawalId: string;
   userId: string;
   xlmAmount: number;
-  estimatedFiat: number;
+  estimatedFiat: string;
   currency: string;
-  exchangeRate: number;          // fiat-per-XLM
+  exchangeRate: string;          // fiat-per-XLM
   walletAddress: string | null;
   status: WithdrawalStatus;
   stellarTxHash: string | null;
-  actualFiatPayout: number;
+  actualFiatPayout: string | null;
   eta: string;
   createdAt: Date;
   completedAt: Date | null;
@@ -65,7 +67,7 @@ export interface WithdrawalResult {
   success: boolean;
   withdrawalId: string;
   xlmDebited: number;
-  fiatPayout: number;
+  fiatPayout: string | null;
   currency: string;
   stellarTxHash: string | null;
   eta: string;
@@ -94,6 +96,9 @@ function nextWdrId(): string {
  * If `walletAddress` is provided we pre-validate that the
  * on-chain balance is sufficient.  This prevents wasting the
  * user's time on withdrawals that will inevitably fail.
+ *
+ * Rejects with a RangeError for an amount that cannot be converted
+ * to fiat.
  */
 export async function createWithdrawal(
   userId: string,
@@ -121,9 +126,8 @@ export async function createWithdrawal(
   }
   */
 
-  const { rate } = getExchangeRate(currency);
-  const fiatRate = roundFiat(1 / rate);               // XLM → fiat direction
-  const estimatedFiat = roundFiat(xlmAmount * fiatRate);
+  const fiatRate = fiatPerXLM(currency);              // XLM → fiat direction
+  const estimatedFiat = xlmToFiat(xlmAmount, currency);
   const wdrId = nextWdrId();
   const now = new Date();
 
@@ -137,7 +141,7 @@ export async function createWithdrawal(
     walletAddress: walletAddress ?? null,
     status: 'created',
     stellarTxHash: null,
-    actualFiatPayout: 0,
+    actualFiatPayout: null,
     eta: '5–10 min',
     createdAt: now,
     completedAt: null,
@@ -157,10 +161,11 @@ export async function createWithdrawal(
 
 /**
  * Execute the withdrawal:
- *   1. Debit XLM from user's wallet (if we have the secret key)
+ *   1. Debit XLM from user's wallet (if we can sign for it)
  *   2. Simulate fiat payout via mock anchor
  *
- * @param walletSecret  — secret key of the user's Telegram wallet.
+ * @param walletAccount — the user's Telegram wallet, which the
+ *                         submission service signs for.
  *                         Pass `undefined` for Freighter wallets
  *                         (the debit step is simulated).
  * @param anchorAddress — Stellar address the anchor uses to receive
@@ -169,7 +174,7 @@ export async function createWithdrawal(
 export async function confirmWithdrawal(
   withdrawalId: string,
   walletAddress: string,
-  walletSecret?: string,
+  walletAccount?: string,
   anchorAddress?: string,
 ): Promise<WithdrawalResult> {
   const rec = withdrawals.get(withdrawalId);
@@ -192,11 +197,11 @@ export async function confirmWithdrawal(
   let transfer: TransferResult;
 
   // Get treasury public key to receive the XLM
-  const treasuryPublicKey = process.env.ANCHOR_TREASURY_PUBLIC || 'GBCWLQYUSY4K4W7T23IK5F6DPIAXWJ3WKYGVFFYGU7GOG3K2X3GHAQ4D';
+  const treasuryPublicKey = getTreasuryAddress();
 
-  if (walletSecret) {
-    // Telegram wallet — we hold the key, can transfer to treasury
-    transfer = await sendXLM(walletSecret, treasuryPublicKey, rec.xlmAmount);
+  if (walletAccount) {
+    // Telegram wallet — the submission service signs the transfer to treasury
+    transfer = await sendXLM(walletAccount, treasuryPublicKey, rec.xlmAmount.toFixed(7));
   } else {
     // Freighter wallet — can't sign server-side, simulate for demo
     transfer = { success: true, hash: `FREIGHTER_DEBIT_${Date.now().toString(36).toUpperCase()}` };
@@ -237,11 +242,11 @@ export async function quickWithdrawal(
   xlmAmount: number,
   currency: string,
   walletAddress: string,
-  walletSecret?: string,
+  walletAccount?: string,
 ): Promise<WithdrawalResult> {
   try {
     const rec = await createWithdrawal(userId, xlmAmount, currency, walletAddress);
-    return confirmWithdrawal(rec.withdrawalId, walletAddress, walletSecret);
+    return confirmWithdrawal(rec.withdrawalId, walletAddress, walletAccount);
   } catch (err: any) {
     return wdrFail('N/A', err.message ?? 'Withdrawal creation failed');
   }
@@ -251,6 +256,11 @@ export async function quickWithdrawal(
 //  Query helpers
 // ───────────────────────────────────────────
 
+/** The account withdrawals pay their XLM into. */
+export function getTreasuryAddress(): string {
+  return process.env.ANCHOR_TREASURY_PUBLIC || 'GBCWLQYUSY4K4W7T23IK5F6DPIAXWJ3WKYGVFFYGU7GOG3K2X3GHAQ4D';
+}
+
 export function getWithdrawal(withdrawalId: string): WithdrawalRecord | null {
   return withdrawals.get(withdrawalId) ?? null;
 }
@@ -264,14 +274,12 @@ export function getUserWithdrawals(userId: string): WithdrawalRecord[] {
 export function getWithdrawalEstimate(
   xlmAmount: number,
   currency: string = 'USD',
-): { xlmAmount: number; estimatedFiat: number; currency: string; rate: number } {
-  const { rate } = getExchangeRate(currency);
-  const fiatRate = roundFiat(1 / rate);
+): { xlmAmount: number; estimatedFiat: string | null; currency: string; rate: string } {
   return {
     xlmAmount,
-    estimatedFiat: roundFiat(xlmAmount * fiatRate),
+    estimatedFiat: estimate(() => xlmToFiat(xlmAmount, currency)),
     currency: currency.toUpperCase(),
-    rate: fiatRate,
+    rate: fiatPerXLM(currency),
   };
 }
 
@@ -286,16 +294,12 @@ export function cancelWithdrawal(withdrawalId: string): boolean {
 //  Internal
 // ───────────────────────────────────────────
 
-function roundFiat(n: number): number {
-  return Math.round(n * 100) / 100;
-}
-
 function wdrFail(withdrawalId: string, message: string): WithdrawalResult {
   return {
     success: false,
     withdrawalId,
     xlmDebited: 0,
-    fiatPayout: 0,
+    fiatPayout: null,
     currency: 'N/A',
     stellarTxHash: null,
     eta: 'N/A',
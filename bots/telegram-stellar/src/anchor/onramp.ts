@@ -6,7 +6,8 @@
  * @module anchor/onramp
  */
 
-import { simulateFiatDeposit, getExchangeRate } from './mockAnchor.js';
+import { estimate } from './amount.js';
+import { simulateFiatDeposit, getExchangeRate, fiatToXLM } from './mockAnchor.js';
 import { sendXLM, fundWithFriendbot, type TransferResult } from './stellarService.js';
 
 // Types
@@ -18,13 +19,13 @@ export interface DepositRecord {
   userId: string;
   fiatAmount: number;
   currency: string;
-  estimatedXLM: number;
+  estimatedXLM: string;
   exchangeRate: number;
   walletAddress: string | null;
   status: DepositStatus;
   paymentLink: string;
   stellarTxHash: string | null;
-  creditedXLM: number;
+  creditedXLM: string | null;
   createdAt: Date;
   completedAt: Date | null;
 }
@@ -32,7 +33,7 @@ export interface DepositRecord {
 export interface DepositResult {
   success: boolean;
   depositId: string;
-  creditedXLM: number;
+  creditedXLM: string | null;
   stellarTxHash: string | null;
   message: string;
 }
@@ -48,7 +49,8 @@ function nextDepositId(): string {
 }
 
 /**
- * Create a deposit request with a mock payment link.
+ * Create a deposit request with a mock payment link. Throws a RangeError
+ * for an amount that cannot be converted to XLM.
  */
 export function createDeposit(
   userId: string,
@@ -57,7 +59,7 @@ export function createDeposit(
   walletAddress: string | null = null,
 ): DepositRecord {
   const { rate } = getExchangeRate(currency);
-  const estimatedXLM = roundXLM(fiatAmount * rate);
+  const estimatedXLM = fiatToXLM(fiatAmount, currency);
   const depositId = nextDepositId();
   const now = new Date();
 
@@ -72,7 +74,7 @@ export function createDeposit(
     status: 'created',
     paymentLink: `https://stellrflow-anchor.demo/pay/${depositId}?amt=${fiatAmount}&cur=${currency.toUpperCase()}`,
     stellarTxHash: null,
-    creditedXLM: 0,
+    creditedXLM: null,
     createdAt: now,
     completedAt: null,
   };
@@ -87,11 +89,15 @@ export function createDeposit(
 
 /**
  * Confirm a deposit: simulate fiat, then credit XLM on-chain.
+ *
+ * @param treasury — the account the XLM is credited from, signed for by
+ *                   the submission service. Without one, the wallet is
+ *                   funded by Friendbot instead.
  */
 export async function confirmDeposit(
   depositId: string,
   walletAddress: string,
-  sourceSecret?: string,
+  treasury?: string,
 ): Promise<DepositResult> {
   const rec = deposits.get(depositId);
   if (!rec) return fail(depositId, 'Deposit not found');
@@ -110,8 +116,8 @@ export async function confirmDeposit(
 
   // 2. Credit XLM on-chain
   let transfer: TransferResult;
-  if (sourceSecret) {
-    transfer = await sendXLM(sourceSecret, walletAddress, anchor.creditedXLM);
+  if (treasury) {
+    transfer = await sendXLM(treasury, walletAddress, anchor.creditedXLM);
   } else {
     transfer = await fundWithFriendbot(walletAddress);
   }
@@ -143,10 +149,14 @@ export async function quickDeposit(
   fiatAmount: number,
   currency: string,
   walletAddress: string,
-  sourceSecret?: string,
+  treasury?: string,
 ): Promise<DepositResult> {
-  const rec = createDeposit(userId, fiatAmount, currency, walletAddress);
-  return confirmDeposit(rec.depositId, walletAddress, sourceSecret);
+  try {
+    const rec = createDeposit(userId, fiatAmount, currency, walletAddress);
+    return confirmDeposit(rec.depositId, walletAddress, treasury);
+  } catch (err: any) {
+    return fail('N/A', err.message ?? 'Deposit creation failed');
+  }
 }
 
 // Query helpers
@@ -164,12 +174,12 @@ export function getUserDeposits(userId: string): DepositRecord[] {
 export function getDepositEstimate(
   fiatAmount: number,
   currency: string = 'USD',
-): { fiatAmount: number; currency: string; estimatedXLM: number; rate: number } {
+): { fiatAmount: number; currency: string; estimatedXLM: string | null; rate: number } {
   const { rate } = getExchangeRate(currency);
   return {
     fiatAmount,
     currency: currency.toUpperCase(),
-    estimatedXLM: roundXLM(fiatAmount * rate),
+    estimatedXLM: estimate(() => fiatToXLM(fiatAmount, currency)),
     rate,
   };
 }
@@ -183,10 +193,6 @@ export function cancelDeposit(depositId: string): boolean {
 
 // Internal
 
-function roundXLM(n: number): number {
-  return Math.round(n * 1e7) / 1e7;
-}
-
 function fail(depositId: string, message: string): DepositResult {
-  return { success: false, depositId, creditedXLM: 0, stellarTxHash: null, message };
+  return { success: false, depositId, creditedXLM: null, stellarTxHash: null, message };
 }
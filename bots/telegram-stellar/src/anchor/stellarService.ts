@@ -12,7 +12,7 @@
  *  RESPONSIBILITIES
  *  ----------------
  *  • Check account balance
- *  • Send XLM payment  (payment / createAccount)
+ *  • Send XLM payment  (through stellrflow-submit)
  *  • Fund via Friendbot (testnet)
  *  • Build unsigned XDR for Freighter signing
  *  • Log transactions to an in-memory ledger
@@ -25,20 +25,13 @@
  *      ↓
  *  stellarService.ts  ← you are here
  *      ↓
- *  Stellar Horizon / Testnet
+ *  stellrflow-submit (payments) / Stellar Horizon / Testnet
  *
  *  @module anchor/stellarService
  */
 
-import {
-  Horizon,
-  Keypair,
-  Networks,
-  TransactionBuilder,
-  Operation,
-  Asset,
-  BASE_FEE,
-} from '@stellar/stellar-sdk';
+import { Horizon } from '@stellar/stellar-sdk';
+import { sendNative } from '../submit.js';
 
 // ───────────────────────────────────────────
 //  Configuration
@@ -51,9 +44,6 @@ const HORIZON_URL =
     ? 'https://horizon-testnet.stellar.org'
     : 'https://horizon.stellar.org');
 
-const NETWORK_PASSPHRASE =
-  STELLAR_NETWORK === 'testnet' ? Networks.TESTNET : Networks.PUBLIC;
-
 const horizon = new Horizon.Server(HORIZON_URL);
 
 // ───────────────────────────────────────────
@@ -78,7 +68,7 @@ export interface TxLogEntry {
   type: 'credit' | 'debit' | 'friendbot';
   from: string;
   to: string;
-  xlmAmount: number;
+  xlmAmount: string;
   hash: string | null;
   status: 'ok' | 'failed';
   timestamp: Date;
@@ -146,68 +136,39 @@ export async function getBalance(address: string): Promise<BalanceInfo> {
 // ───────────────────────────────────────────
 
 /**
- * Send `amount` XLM from a source keypair to a destination address.
+ * Send `amount` XLM, a decimal string of at most 7 places, from the
+ * `source` account to a destination address. The submission service
+ * signs for the source: the anchor treasury or a Telegram wallet.
  *
- * • If destination exists → uses `Operation.payment`
- * • If destination does NOT exist → uses `Operation.createAccount`
+ * • If destination exists → a payment
+ * • If destination does NOT exist → a createAccount
  *   (requires amount ≥ 1 XLM for the base reserve)
  */
 export async function sendXLM(
-  sourceSecret: string,
+  source: string,
   destination: string,
-  amount: number,
+  amount: string,
 ): Promise<TransferResult> {
   try {
-    const kp = Keypair.fromSecret(sourceSecret);
-    const source = await horizon.loadAccount(kp.publicKey());
-
-    const destExists = await accountExists(destination);
-
-    let tx;
-    if (destExists) {
-      tx = new TransactionBuilder(source, { fee: BASE_FEE, networkPassphrase: NETWORK_PASSPHRASE })
-        .addOperation(Operation.payment({
-          destination,
-          asset: Asset.native(),
-          amount: amount.toFixed(7),
-        }))
-        .setTimeout(60)
-        .build();
-    } else {
-      // createAccount needs at least 1 XLM on testnet
-      const startBal = Math.max(amount, 1);
-      tx = new TransactionBuilder(source, { fee: BASE_FEE, networkPassphrase: NETWORK_PASSPHRASE })
-        .addOperation(Operation.createAccount({
-          destination,
-          startingBalance: startBal.toFixed(7),
-        }))
-        .setTimeout(60)
-        .build();
-    }
-
-    tx.sign(kp);
-    const res = await horizon.submitTransaction(tx);
+    const res = await sendNative(source, destination, amount);
 
     log({
       type: 'credit',
-      from: kp.publicKey(),
+      from: source,
       to: destination,
       xlmAmount: amount,
       hash: res.hash,
       status: 'ok',
-      note: destExists ? 'payment' : 'createAccount',
+      note: res.createdAccount ? 'createAccount' : 'payment',
     });
 
-    return { success: true, hash: res.hash, ledger: (res as any).ledger };
+    return { success: true, hash: res.hash, ledger: res.ledger };
   } catch (err: any) {
-    const msg =
-      err?.response?.data?.extras?.result_codes
-        ? JSON.stringify(err.response.data.extras.result_codes)
-        : err.message || 'sendXLM failed';
+    const msg = err.message || 'sendXLM failed';
 
     log({
       type: 'credit',
-      from: '(source)',
+      from: source,
       to: destination,
       xlmAmount: amount,
       hash: null,
@@ -250,7 +211,7 @@ export async function fundWithFriendbot(address: string): Promise<TransferResult
       type: 'friendbot',
       from: 'friendbot',
       to: address,
-      xlmAmount: 10_000,
+      xlmAmount: '10000',
       hash,
       status: 'ok',
       note: 'testnet funding',
@@ -266,15 +227,6 @@ export async function fundWithFriendbot(address: string): Promise<TransferResult
 //  Helpers
 // ───────────────────────────────────────────
 
-async function accountExists(address: string): Promise<boolean> {
-  try {
-    await horizon.loadAccount(address);
-    return true;
-  } catch {
-    return false;
-  }
-}
-
 /** Expose the network name for display purposes. */
 export function getNetworkName(): string {
   return STELLAR_NETWORK;
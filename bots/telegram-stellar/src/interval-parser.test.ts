@@ -0,0 +1,20 @@
+import { test } from "node:test";
+import assert from "node:assert/strict";
+
+import { formatIntervalForDisplay, parseIntervalFormat } from "./interval-parser.js";
+
+test("parses single and compound intervals", () => {
+  assert.equal(parseIntervalFormat("1500"), 1500);
+  assert.equal(parseIntervalFormat("1.5h"), 5_400_000);
+  assert.equal(parseIntervalFormat("1h 30m"), 5_400_000);
+  assert.equal(parseIntervalFormat("2 weeks 1 day"), 1_296_000_000);
+  assert.equal(parseIntervalFormat("1m30s"), 90_000);
+  assert.throws(() => parseIntervalFormat("1h 30"), /Invalid interval format/);
+  assert.throws(() => parseIntervalFormat("1h 2y"), /Unknown time unit: y/);
+});
+
+test("displayed intervals read back as the same interval", () => {
+  for (const ms of [0, 1, 999, 1000, 5_400_000, 90_061_001, 694_861_001, 2 ** 40]) {
+    assert.equal(parseIntervalFormat(formatIntervalForDisplay(ms)), ms);
+  }
+});
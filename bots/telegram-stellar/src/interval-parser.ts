@@ -2,9 +2,40 @@
  * Interval Parser Utility
  *
  * Parses custom interval formats into milliseconds for AutoPay scheduling
- * Supports: "3600s", "1h", "30m", "1d", "5w", or raw milliseconds
+ * Supports: "3600s", "1h", "30m", "1d", "5w", several at once as in
+ * "1h 30m", or raw milliseconds
  */
 
+const UNIT_MS: Record<string, number> = {
+  // Milliseconds
+  ms: 1,
+  // Seconds
+  s: 1000,
+  sec: 1000,
+  second: 1000,
+  seconds: 1000,
+  // Minutes
+  m: 60 * 1000,
+  min: 60 * 1000,
+  minute: 60 * 1000,
+  minutes: 60 * 1000,
+  // Hours
+  h: 60 * 60 * 1000,
+  hr: 60 * 60 * 1000,
+  hour: 60 * 60 * 1000,
+  hours: 60 * 60 * 1000,
+  // Days
+  d: 24 * 60 * 60 * 1000,
+  day: 24 * 60 * 60 * 1000,
+  days: 24 * 60 * 60 * 1000,
+  // Weeks
+  w: 7 * 24 * 60 * 60 * 1000,
+  week: 7 * 24 * 60 * 60 * 1000,
+  weeks: 7 * 24 * 60 * 60 * 1000,
+};
+
+const PART = /(\d+\.?\d*)\s*([a-zA-Z]+)/g;
+
 export function parseIntervalFormat(input: string): number {
   const trimmed = input.trim();
 
@@ -13,76 +44,50 @@ export function parseIntervalFormat(input: string): number {
     return parseInt(trimmed, 10);
   }
 
-  // Parse with unit suffix
-  const match = trimmed.match(/^(\d+\.?\d*)\s*([a-zA-Z]+)$/);
-  if (!match) throw new Error(`Invalid interval format: ${input}`);
-
-  const [, numStr, unit] = match;
-  const num = parseFloat(numStr);
-
-  const unitMap: Record<string, number> = {
-    // Milliseconds
-    ms: 1,
-    // Seconds
-    s: 1000,
-    sec: 1000,
-    second: 1000,
-    seconds: 1000,
-    // Minutes
-    m: 60 * 1000,
-    min: 60 * 1000,
-    minute: 60 * 1000,
-    minutes: 60 * 1000,
-    // Hours
-    h: 60 * 60 * 1000,
-    hr: 60 * 60 * 1000,
-    hour: 60 * 60 * 1000,
-    hours: 60 * 60 * 1000,
-    // Days
-    d: 24 * 60 * 60 * 1000,
-    day: 24 * 60 * 60 * 1000,
-    days: 24 * 60 * 60 * 1000,
-    // Weeks
-    w: 7 * 24 * 60 * 60 * 1000,
-    week: 7 * 24 * 60 * 60 * 1000,
-    weeks: 7 * 24 * 60 * 60 * 1000,
-  };
+  // One or more numbers with a unit suffix, as in "1h 30m"
+  if (!/^(\d+\.?\d*\s*[a-zA-Z]+\s*)+$/.test(trimmed)) {
+    throw new Error(`Invalid interval format: ${input}`);
+  }
 
-  const multiplier = unitMap[unit.toLowerCase()];
-  if (!multiplier) throw new Error(`Unknown time unit: ${unit}`);
+  let total = 0;
+  for (const [, numStr, unit] of trimmed.matchAll(PART)) {
+    const multiplier = UNIT_MS[unit.toLowerCase()];
+    if (!multiplier) throw new Error(`Unknown time unit: ${unit}`);
+    total += parseFloat(numStr) * multiplier;
+  }
 
-  return Math.floor(num * multiplier);
+  return Math.floor(total);
 }
 
 /**
  * Format milliseconds into human-readable duration string
  *
+ * Every unit is kept, so parseIntervalFormat reads the result back as the
+ * same interval.
+ *
  * @param ms Milliseconds to format
- * @returns Formatted string (e.g., "1h", "30m", "5000s")
+ * @returns Formatted string (e.g., "1h", "1h 30m", "1s 500ms")
  */
 export function formatIntervalForDisplay(ms: number): string {
-  // Less than a minute - show seconds
-  if (ms < 60000) {
-    return `${Math.round(ms / 1000)}s`;
-  }
-
-  // Less than an hour - show minutes
-  if (ms < 3600000) {
-    return `${Math.round(ms / 60000)}m`;
-  }
-
-  // Less than a day - show hours
-  if (ms < 86400000) {
-    return `${Math.round(ms / 3600000)}h`;
-  }
+  const units: [string, number][] = [
+    ["w", 604800000],
+    ["d", 86400000],
+    ["h", 3600000],
+    ["m", 60000],
+    ["s", 1000],
+    ["ms", 1],
+  ];
 
-  // Less than a week - show days
-  if (ms < 604800000) {
-    return `${Math.round(ms / 86400000)}d`;
+  let rest = Math.max(0, Math.floor(ms));
+  const parts: string[] = [];
+  for (const [name, size] of units) {
+    if (rest >= size) {
+      parts.push(`${Math.floor(rest / size)}${name}`);
+      rest %= size;
+    }
   }
 
-  // Show weeks
-  return `${Math.round(ms / 604800000)}w`;
+  return parts.length > 0 ? parts.join(" ") : "0ms";
 }
 
 /**
@@ -0,0 +1,135 @@
+/**
+ * StellrFlow - Submission service client
+ *
+ * Payments from Telegram wallets and the anchor treasury are made by
+ * stellrflow-submit (crates/stellrflow-submit) at SUBMIT_URL. It signs them
+ * with the keystore or the signing server and sends each account's
+ * transactions one at a time with a sequence number it keeps, so two
+ * payments from one account at once no longer fail with `tx_bad_seq`.
+ * Each offers the fee the service's FEE_PERCENTILE picks from recent
+ * ledgers, and the anchor treasury fee-bumps any that get stuck. /pay's
+ * path payments are quoted and bounded by the service too.
+ *
+ * @module submit
+ */
+
+const SUBMIT_URL = (process.env.SUBMIT_URL || 'http://localhost:3007').replace(/\/+$/, '');
+
+/** What the service did with a payment. */
+export interface Sent {
+  hash: string;
+  ledger: number;
+  /** The destination did not exist and was created with the payment. */
+  createdAccount: boolean;
+  /** The fee offered per operation, in stroops. */
+  fee: number;
+  /** What the network charged, in stroops. */
+  feeCharged: number;
+  /** Set when the transaction got stuck and a sponsor bumped its fee. */
+  feeBump: { feeSource: string; fee: number; innerHash: string } | null;
+}
+
+/**
+ * Why the service, or Horizon behind it, refused a transaction. A
+ * rejected submission's message is its result codes, as JSON.
+ */
+export class SubmitError extends Error {
+  constructor(message: string, readonly resultCodes?: unknown) {
+    super(message);
+    this.name = 'SubmitError';
+  }
+}
+
+/** POSTs `body` to `route`, or GETs it without one. */
+async function call<T>(route: string, body?: object): Promise<T> {
+  let response: Response;
+  try {
+    response = await fetch(`${SUBMIT_URL}/api/submit/${route}`, body ? {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(body),
+    } : undefined);
+  } catch (err: any) {
+    throw new SubmitError(`Submission service unavailable: ${err.message}`);
+  }
+  const result: any = await response.json().catch(() => ({}));
+  if (!response.ok || !result.success) {
+    throw new SubmitError(
+      result.error || `Submission service answered ${response.status}`,
+      result.resultCodes,
+    );
+  }
+  return result as T;
+}
+
+/**
+ * Send `amount` XLM, a decimal string of at most 7 places, from the
+ * `source` account to `destination`: a payment, or a createAccount of at
+ * least 1 XLM if the destination does not exist yet.
+ */
+export function sendNative(source: string, destination: string, amount: string): Promise<Sent> {
+  return call('send', { source, destination, amount });
+}
+
+/**
+ * The fee per operation, in stroops, for transactions the bot builds
+ * itself: what the service would offer now, as a string for
+ * TransactionBuilder.
+ */
+export async function suggestedFee(): Promise<string> {
+  const { fee } = await call<{ fee: number }>('fee');
+  return String(fee);
+}
+
+/**
+ * A strict-receive path payment: exactly `amount` of `destAsset`
+ * ("CODE:ISSUER"), paid in `sendAsset` (XLM unless given) at most
+ * `slippage` percent (1 unless given) above the quote.
+ */
+export interface PathRequest {
+  sendAsset?: string;
+  destAsset: string;
+  amount: string;
+  slippage?: string;
+}
+
+/** The best path for a payment. Amounts are decimal strings. */
+export interface Quote {
+  sendAsset: string;
+  destAsset: string;
+  sourceAmount: string;
+  destinationAmount: string;
+  /** The quote plus the slippage: the most the payment may send. */
+  mostSent: string;
+  /** How much of `destAsset` one unit of `sendAsset` buys. */
+  rate: string;
+  /** The assets in between, as "CODE:ISSUER". */
+  path: string[];
+}
+
+/** What a path payment did. */
+export interface PathPaid {
+  hash: string;
+  ledger: number;
+  /** What the source actually sent. */
+  sourceAmount: string;
+  destinationAmount: string;
+  /** The rate it actually got. */
+  rate: string;
+  quotedRate: string;
+  path: string[];
+}
+
+/** Quote `payment`, to check its `mostSent` against the policy. */
+export function quotePayment(payment: PathRequest): Promise<Quote> {
+  return call('quote', payment);
+}
+
+/**
+ * Make `payment` from the `source` account to `destination`, which must
+ * exist. It is quoted again as it is made, and bounded by that quote plus
+ * the slippage.
+ */
+export function pathPayment(source: string, destination: string, payment: PathRequest): Promise<PathPaid> {
+  return call('path-payment', { source, destination, ...payment });
+}
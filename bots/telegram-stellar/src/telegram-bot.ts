@@ -14,7 +14,7 @@ import cors from "cors";
 import dotenv from "dotenv";
 import path from "path";
 import { fileURLToPath } from "url";
-import { Horizon, Networks, Keypair, TransactionBuilder, Operation, Asset, BASE_FEE } from "@stellar/stellar-sdk";
+import { Horizon, Networks, Keypair, TransactionBuilder, Operation, Asset } from "@stellar/stellar-sdk";
 
 // Anchor module — on/off ramp + Stellar helpers
 import {
@@ -22,6 +22,7 @@ import {
   quickWithdrawal,
   getDepositEstimate,
   getWithdrawalEstimate,
+  getTreasuryAddress,
   getDeposit,
   getWithdrawal,
   getUserDeposits,
@@ -31,6 +32,7 @@ import {
   getLogForAddress,
 } from "./anchor/index.js";
 import { answerStellarQuestion } from "./sdk-chatbot.js";
+import { pathPayment, quotePayment, sendNative, suggestedFee } from "./submit.js";
 import {
   parseIntervalFormat,
   formatIntervalForDisplay,
@@ -58,14 +60,40 @@ const HORIZON_URL =
 // Optional: Stellar secret key for /send (bot-funded payments)
 const STELLAR_SECRET_KEY = process.env.STELLAR_SECRET_KEY || "";
 
-// Anchor Treasury: funded wallet for real XLM credits on deposit
+// Anchor Treasury: funded wallet for real XLM credits on deposit. The
+// submission service signs with it; the bot only needs its address.
 const ANCHOR_TREASURY_SECRET = process.env.ANCHOR_TREASURY_SECRET || "";
+const ANCHOR_TREASURY = ANCHOR_TREASURY_SECRET
+  ? Keypair.fromSecret(ANCHOR_TREASURY_SECRET).publicKey()
+  : "";
+
+// Optional: the stellrflow-scheduler service that persists and pays AutoPay
+// schedules. Without it, schedules are only kept in memory and never paid.
+const AUTOPAY_SCHEDULER_URL = (process.env.AUTOPAY_SCHEDULER_URL || "").replace(/\/+$/, "");
+
+// Optional: keep Telegram wallet secrets encrypted with the
+// stellrflow-keystore binary (`id:secret`). Without it, wallets.json holds
+// every secret key in plaintext.
+const KEYSTORE_MASTER_KEY = process.env.KEYSTORE_MASTER_KEY || "";
+const KEYSTORE_BIN = process.env.KEYSTORE_BIN || "stellrflow-keystore";
+
+// Optional: the stellrflow-policy service that every payment is checked
+// against before it is signed. Without it, any payment is signed as asked.
+const POLICY_URL = (process.env.POLICY_URL || "").replace(/\/+$/, "");
+
+// How far /pay may stray from its quote, in percent (0 to 100, default 1).
+// It caps the XLM a path payment may spend at the quote plus this much.
+const PAY_SLIPPAGE_PERCENT = (process.env.PAY_SLIPPAGE_PERCENT || "1").replace(/%$/, "");
 
 if (!TELEGRAM_BOT_TOKEN) {
   console.error("TELEGRAM_BOT_TOKEN is not defined in .env");
   process.exit(1);
 }
 
+if (!KEYSTORE_MASTER_KEY) {
+  console.warn("Telegram wallet payments need KEYSTORE_MASTER_KEY: stellrflow-submit signs them from the keystore");
+}
+
 const bot = new TelegramBot(TELEGRAM_BOT_TOKEN, { polling: true });
 const userChatIds = new Map<string, string>();
 
@@ -79,6 +107,7 @@ const activeSessions = new Map<string, {
 // Persistent Wallet Storage (JSON file-based)
 // ═══════════════════════════════════════════════════════════════════════════
 import * as fs from 'fs';
+import { execFileSync } from 'child_process';
 
 const WALLETS_FILE = path.join(__dirname, '../data/wallets.json');
 
@@ -93,8 +122,55 @@ if (!fs.existsSync(dataDir)) {
   fs.mkdirSync(dataDir, { recursive: true });
 }
 
+// Run a stellrflow-keystore command on the wallets file. The master key
+// reaches it through the inherited environment.
+function keystore(command: string, input?: string): string {
+  return execFileSync(KEYSTORE_BIN, [command, WALLETS_FILE], {
+    input,
+    encoding: 'utf-8',
+    stdio: ['pipe', 'pipe', 'inherit'],
+  });
+}
+
+// Keystore files carry a format version; the plaintext file does not
+function walletsEncrypted(): boolean {
+  try {
+    return JSON.parse(fs.readFileSync(WALLETS_FILE, 'utf-8')).version !== undefined;
+  } catch {
+    return false;
+  }
+}
+
 // Load wallets from disk
 function loadWallets(): WalletData {
+  const encrypted = walletsEncrypted();
+
+  if (KEYSTORE_MASTER_KEY) {
+    // A keystore that cannot be read must stop the bot: saving an empty
+    // wallet list over it would lose every wallet.
+    try {
+      if (fs.existsSync(WALLETS_FILE) && !encrypted) {
+        keystore('migrate');
+        console.log(`Encrypted the wallets in ${WALLETS_FILE}`);
+      }
+      // stellrflow-submit signs for the wallets; secretKey is left out of
+      // each one.
+      const data = JSON.parse(keystore('public'));
+      for (const wallet of Object.values(data.telegramWallets ?? {}) as any[]) {
+        wallet.secretKey ??= '';
+      }
+      return data;
+    } catch (err) {
+      console.error('Failed to load wallets from the keystore:', err);
+      process.exit(1);
+    }
+  }
+
+  if (encrypted) {
+    console.error(`${WALLETS_FILE} is encrypted; set KEYSTORE_MASTER_KEY to load it`);
+    process.exit(1);
+  }
+
   try {
     if (fs.existsSync(WALLETS_FILE)) {
       const data = fs.readFileSync(WALLETS_FILE, 'utf-8');
@@ -125,7 +201,13 @@ function saveWallets(): void {
         }])
       ),
     };
-    fs.writeFileSync(WALLETS_FILE, JSON.stringify(data, null, 2));
+    if (KEYSTORE_MASTER_KEY) {
+      // Wallets saved without their secret keep the one already stored.
+      keystore('import', JSON.stringify(data));
+      for (const wallet of userWallets.values()) wallet.secretKey = '';
+    } else {
+      fs.writeFileSync(WALLETS_FILE, JSON.stringify(data, null, 2));
+    }
     console.log(`Wallets saved to ${WALLETS_FILE}`);
   } catch (err) {
     console.error('Failed to save wallets:', err);
@@ -167,6 +249,76 @@ console.log(`Loaded ${userWallets.size} Telegram wallets, ${freighterWallets.siz
 // Stellar Horizon client (for balance queries)
 const horizon = new Horizon.Server(HORIZON_URL);
 
+// ═══════════════════════════════════════════════════════════════════════════
+// Spending Policy
+// ═══════════════════════════════════════════════════════════════════════════
+
+type PaymentSource = "manual" | "workflow" | "autopay" | "offramp";
+
+interface PolicyCheck {
+  allowed: boolean;
+  // "allow", "deny" or "requireMultisig"
+  outcome: string;
+  reason: string;
+  decisionId?: number;
+}
+
+// Ask the policy service whether a payment may be signed. A service that
+// cannot be reached denies everything rather than letting payments through.
+async function checkPolicy(
+  chatId: string,
+  destination: string,
+  amount: number,
+  source: PaymentSource
+): Promise<PolicyCheck> {
+  if (!POLICY_URL) {
+    return { allowed: true, outcome: "allow", reason: "no spending policy configured" };
+  }
+  try {
+    const response = await fetch(`${POLICY_URL}/api/policy/check`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ chatId, destination, amount, source }),
+    });
+    const result: any = await response.json();
+    if (!response.ok || !result.success) {
+      return { allowed: false, outcome: "deny", reason: result.error || "Policy check failed" };
+    }
+    return {
+      allowed: result.allowed,
+      outcome: result.decision.outcome,
+      reason: result.decision.reason,
+      decisionId: result.decision.id,
+    };
+  } catch (err: any) {
+    return {
+      allowed: false,
+      outcome: "deny",
+      reason: `Spending policy service unavailable: ${err.message}`,
+    };
+  }
+}
+
+// Tell the policy service an allowed payment was not made, so it stops
+// counting towards the wallet's limits.
+async function releasePolicy(check: PolicyCheck): Promise<void> {
+  if (!POLICY_URL || check.decisionId === undefined) return;
+  try {
+    await fetch(`${POLICY_URL}/api/policy/decisions/${check.decisionId}/release`, {
+      method: "POST",
+    });
+  } catch (err) {
+    console.error("Failed to release policy decision:", err);
+  }
+}
+
+// The chat message for a payment the policy did not allow.
+function policyMessage(check: PolicyCheck): string {
+  return check.outcome === "requireMultisig"
+    ? `🔐 **Multisig Approval Required**\n\n${check.reason}`
+    : `🚫 **Payment Blocked by Spending Policy**\n\n${check.reason}`;
+}
+
 function initBot() {
   console.log("Initializing StellrFlow Telegram Bot (Stellar)...");
 
@@ -252,6 +404,7 @@ function initBot() {
         "/mybalance - Check your wallet balance\n" +
         "/mywallet - Show your wallet address\n" +
         "/send <address> <amount> - Send XLM\n" +
+        "/pay <amount> <CODE:ISSUER> to <address> - Pay in any asset from XLM\n" +
         "/disconnect - Disconnect your wallet\n";
 
       // Only show fundwallet for Telegram wallets
@@ -455,12 +608,13 @@ function initBot() {
     const publicKey = keypair.publicKey();
     const secretKey = keypair.secret();
 
-    // Store wallet
+    // Store wallet; the submission service signs for it from the saved file
     userWallets.set(chatId, {
       publicKey,
       secretKey,
       createdAt: new Date(),
     });
+    saveWallets();
 
     bot.sendMessage(
       chatId,
@@ -603,7 +757,7 @@ function initBot() {
         chatId,
         `⏳ **Processing Deposit...**\n\n` +
         `**Amount:** ${amount} ${currency}\n` +
-        `**Est. XLM:** ~${estimate.estimatedXLM.toFixed(4)} XLM\n` +
+        `**Est. XLM:** ~${estimate.estimatedXLM ?? '—'} XLM\n` +
         `**Rate:** 1 ${currency} = ${estimate.rate} XLM`,
         { parse_mode: "Markdown" }
       );
@@ -616,7 +770,7 @@ function initBot() {
           chatId,
           `✅ **Deposit Successful!**\n\n` +
           `**Deposited:** ${amount} ${currency}\n` +
-          `**Credited:** ${result.creditedXLM.toFixed(4)} XLM\n` +
+          `**Credited:** ${result.creditedXLM} XLM\n` +
           `**Deposit ID:** \`${result.depositId}\`\n` +
           (result.stellarTxHash ? `**Tx:** \`${result.stellarTxHash.slice(0, 12)}...\`\n` : '') +
           `\nUse /mybalance to check your updated balance.`,
@@ -689,6 +843,12 @@ function initBot() {
       return;
     }
 
+    const policy = await checkPolicy(chatId, getTreasuryAddress(), xlmAmount, "offramp");
+    if (!policy.allowed) {
+      bot.sendMessage(chatId, policyMessage(policy), { parse_mode: "Markdown" });
+      return;
+    }
+
     try {
       // Check balance first
       const account = await horizon.loadAccount(wallet.publicKey);
@@ -696,6 +856,7 @@ function initBot() {
       const balance = xlmBalance && "balance" in xlmBalance ? parseFloat(xlmBalance.balance) : 0;
 
       if (balance < xlmAmount) {
+        await releasePolicy(policy);
         bot.sendMessage(
           chatId,
           `❌ **Insufficient Balance**\n\n` +
@@ -714,7 +875,7 @@ function initBot() {
         chatId,
         `⏳ **Processing Withdrawal...**\n\n` +
         `**XLM Amount:** ${xlmAmount} XLM\n` +
-        `**Est. Payout:** ~${estimate.estimatedFiat} ${currency}`,
+        `**Est. Payout:** ~${estimate.estimatedFiat ?? '—'} ${currency}`,
         { parse_mode: "Markdown" }
       );
 
@@ -734,6 +895,7 @@ function initBot() {
           { parse_mode: "Markdown" }
         );
       } else {
+        await releasePolicy(policy);
         bot.sendMessage(
           chatId,
           `❌ **Withdrawal Failed**\n\n${result.message}`,
@@ -741,6 +903,7 @@ function initBot() {
         );
       }
     } catch (err: any) {
+      await releasePolicy(policy);
       if (err?.response?.status === 404) {
         bot.sendMessage(
           chatId,
@@ -811,7 +974,7 @@ function initBot() {
       text += "**Deposits (On-Ramp):**\n";
       for (const d of deps.slice(-5)) {
         const icon = d.status === 'completed' ? '✅' : d.status === 'failed' ? '❌' : '⏳';
-        text += `${icon} \`${d.depositId}\` — ${d.fiatAmount} ${d.currency} → ${d.creditedXLM || d.estimatedXLM} XLM (${d.status})\n`;
+        text += `${icon} \`${d.depositId}\` — ${d.fiatAmount} ${d.currency} → ${d.creditedXLM ?? d.estimatedXLM} XLM (${d.status})\n`;
       }
       text += "\n";
     }
@@ -820,7 +983,7 @@ function initBot() {
       text += "**Withdrawals (Off-Ramp):**\n";
       for (const w of wdrs.slice(-5)) {
         const icon = w.status === 'completed' ? '✅' : w.status === 'failed' ? '❌' : '⏳';
-        text += `${icon} \`${w.withdrawalId}\` — ${w.xlmAmount} XLM → ${w.actualFiatPayout || w.estimatedFiat} ${w.currency} (${w.status})\n`;
+        text += `${icon} \`${w.withdrawalId}\` — ${w.xlmAmount} XLM → ${w.actualFiatPayout ?? w.estimatedFiat} ${w.currency} (${w.status})\n`;
       }
     }
 
@@ -850,7 +1013,7 @@ function initBot() {
       `**ID:** \`${d.depositId}\`\n` +
       `**Status:** ${d.status}\n` +
       `**Amount:** ${d.fiatAmount} ${d.currency}\n` +
-      `**XLM Credited:** ${d.creditedXLM || '—'}\n` +
+      `**XLM Credited:** ${d.creditedXLM ?? '—'}\n` +
       `**Rate:** 1 ${d.currency} = ${d.exchangeRate} XLM\n` +
       (d.stellarTxHash ? `**Stellar Tx:** \`${d.stellarTxHash.slice(0, 16)}...\`\n` : '') +
       `**Created:** ${d.createdAt.toISOString()}`,
@@ -881,7 +1044,7 @@ function initBot() {
       `**ID:** \`${w.withdrawalId}\`\n` +
       `**Status:** ${w.status}\n` +
       `**XLM Debited:** ${w.xlmAmount}\n` +
-      `**Fiat Payout:** ${w.actualFiatPayout || w.estimatedFiat} ${w.currency}\n` +
+      `**Fiat Payout:** ${w.actualFiatPayout ?? w.estimatedFiat} ${w.currency}\n` +
       `**ETA:** ${w.eta}\n` +
       (w.stellarTxHash ? `**Stellar Tx:** \`${w.stellarTxHash.slice(0, 16)}...\`\n` : '') +
       `**Created:** ${w.createdAt.toISOString()}`,
@@ -917,6 +1080,12 @@ function initBot() {
         return;
       }
 
+      const policy = await checkPolicy(chatId, destAddress, amount, "manual");
+      if (!policy.allowed) {
+        bot.sendMessage(chatId, policyMessage(policy), { parse_mode: "Markdown" });
+        return;
+      }
+
       // Generate link to send-transaction page
       const sendUrl = `http://localhost:3000/send-transaction?chatId=${chatId}&destination=${encodeURIComponent(destAddress)}&amount=${amount}&network=${freighterWallet.network}`;
 
@@ -958,68 +1127,17 @@ function initBot() {
       return;
     }
 
+    const policy = await checkPolicy(chatId, destAddress, amount, "manual");
+    if (!policy.allowed) {
+      bot.sendMessage(chatId, policyMessage(policy), { parse_mode: "Markdown" });
+      return;
+    }
+
     try {
       bot.sendMessage(chatId, "⏳ Processing transaction...");
 
-      // Load sender account
-      const sourceKeypair = Keypair.fromSecret(wallet.secretKey);
-      const sourceAccount = await horizon.loadAccount(wallet.publicKey);
-
-      // Check if destination exists
-      let destinationExists = true;
-      try {
-        await horizon.loadAccount(destAddress);
-      } catch {
-        destinationExists = false;
-      }
-
-      // Build transaction
-      const networkPassphrase = STELLAR_NETWORK === "testnet"
-        ? Networks.TESTNET
-        : Networks.PUBLIC;
-
-      let transaction;
-      if (destinationExists) {
-        // Regular payment
-        transaction = new TransactionBuilder(sourceAccount, {
-          fee: BASE_FEE,
-          networkPassphrase,
-        })
-          .addOperation(
-            Operation.payment({
-              destination: destAddress,
-              asset: Asset.native(),
-              amount: amount.toFixed(7),
-            })
-          )
-          .setTimeout(30)
-          .build();
-      } else {
-        // Create account operation for new accounts
-        if (amount < 1) {
-          bot.sendMessage(
-            chatId,
-            "❌ Destination account doesn't exist. Minimum 1 XLM required to create it."
-          );
-          return;
-        }
-        transaction = new TransactionBuilder(sourceAccount, {
-          fee: BASE_FEE,
-          networkPassphrase,
-        })
-          .addOperation(
-            Operation.createAccount({
-              destination: destAddress,
-              startingBalance: amount.toFixed(7),
-            })
-          )
-          .setTimeout(30)
-          .build();
-      }
-
-      // Sign and submit
-      transaction.sign(sourceKeypair);
-      const result = await horizon.submitTransaction(transaction);
+      // A payment, or a createAccount if the destination does not exist yet.
+      const result = await sendNative(wallet.publicKey, destAddress, amount.toFixed(7));
 
       bot.sendMessage(
         chatId,
@@ -1030,14 +1148,100 @@ function initBot() {
         { parse_mode: "Markdown" }
       );
     } catch (err: any) {
-      const errorMsg = err?.response?.data?.extras?.result_codes
-        ? JSON.stringify(err.response.data.extras.result_codes)
-        : err.message || "Transaction failed";
+      await releasePolicy(policy);
+      bot.sendMessage(
+        chatId,
+        `❌ Transaction failed: ${err.message || "Transaction failed"}`,
+        { parse_mode: "Markdown" }
+      );
+    }
+  });
+
+  // /pay <amount> <asset> to <address>: deliver exactly <amount> of <asset>,
+  // paid in XLM converted over the cheapest path on the SDEX.
+  bot.onText(/\/pay\b(?:\s+(\S+)\s+(\S+)\s+to\s+(\S+))?/i, async (msg, match) => {
+    const chatId = msg.chat.id.toString();
+    const wallet = userWallets.get(chatId);
+
+    if (!wallet) {
+      bot.sendMessage(
+        chatId,
+        freighterWallets.has(chatId)
+          ? "❌ /pay signs with a Telegram wallet. With Freighter, use /send."
+          : "❌ No wallet connected. Connect one via StellrFlow workflow.",
+        { parse_mode: "Markdown" }
+      );
+      return;
+    }
+
+    const amountStr = match?.[1]?.trim();
+    const assetStr = match?.[2]?.trim();
+    const destAddress = match?.[3]?.trim();
+    if (!amountStr || !assetStr || !destAddress) {
+      bot.sendMessage(
+        chatId,
+        "**Usage:** /pay <amount> <CODE:ISSUER> to <address>\n\n" +
+        "**Example:** /pay 10 USDC:GA5Z...KZVN to GABC...XYZ\n\n" +
+        "The destination receives exactly 10 USDC, paid from your XLM at the best rate " +
+        `on the network (up to ${PAY_SLIPPAGE_PERCENT}% above the quote).`,
+        { parse_mode: "Markdown" }
+      );
+      return;
+    }
+
+    if (!/^\d+(\.\d{1,7})?$/.test(amountStr) || !/[1-9]/.test(amountStr)) {
+      bot.sendMessage(chatId, "❌ Invalid amount. Please enter a positive number with at most 7 decimals.");
+      return;
+    }
+    if (/^(native|xlm)$/i.test(assetStr)) {
+      bot.sendMessage(chatId, "💡 To pay in XLM, use /send <address> <amount>.");
+      return;
+    }
+    const code = assetStr.split(":")[0];
+
+    // Path payments cannot create accounts.
+    try {
+      await horizon.loadAccount(destAddress);
+    } catch {
+      bot.sendMessage(chatId, "❌ Destination account doesn't exist. Fund it with /send first.");
+      return;
+    }
+
+    // The cheapest way to deliver the amount from XLM, and the most it may
+    // cost.
+    const payment = { destAsset: assetStr, amount: amountStr, slippage: PAY_SLIPPAGE_PERCENT };
+    let quote;
+    try {
+      quote = await quotePayment(payment);
+    } catch (err: any) {
+      bot.sendMessage(chatId, `❌ Could not quote the payment: ${err.message}`);
+      return;
+    }
+
+    const policy = await checkPolicy(chatId, destAddress, Number(quote.mostSent), "manual");
+    if (!policy.allowed) {
+      bot.sendMessage(chatId, policyMessage(policy), { parse_mode: "Markdown" });
+      return;
+    }
+
+    try {
+      bot.sendMessage(chatId, `⏳ Paying ${amountStr} ${code} for about ${quote.sourceAmount} XLM...`);
+
+      const paid = await pathPayment(wallet.publicKey, destAddress, payment);
+
       bot.sendMessage(
         chatId,
-        `❌ Transaction failed: ${errorMsg}`,
+        `✅ **Payment Successful!**\n\n` +
+        `**Received:** ${paid.destinationAmount} ${code}\n` +
+        `**Paid:** ${paid.sourceAmount} XLM\n` +
+        `**Rate:** 1 XLM = ${paid.rate} ${code} (quoted ${paid.quotedRate})\n` +
+        `**To:** \`${destAddress.slice(0, 8)}...${destAddress.slice(-8)}\`\n\n` +
+        `🔗 [View on Explorer](https://stellar.expert/explorer/${STELLAR_NETWORK}/tx/${paid.hash})`,
         { parse_mode: "Markdown" }
       );
+    } catch (err: any) {
+      await releasePolicy(policy);
+      bot.sendMessage(chatId, `❌ Payment failed: ${err.message}`);
     }
   });
 
@@ -1388,6 +1592,7 @@ app.post("/api/wallet/create", (req, res) => {
       secretKey: keypair.secret(),
       createdAt: new Date(),
     });
+    saveWallets();
 
     return res.json({
       success: true,
@@ -1522,9 +1727,12 @@ app.post("/api/wallet/:chatId/fund", async (req, res) => {
 
 // Send XLM from wallet
 app.post("/api/wallet/:chatId/send", async (req, res) => {
+  let policy: PolicyCheck | undefined;
   try {
     const { chatId } = req.params;
     const { destination, amount } = req.body;
+    // The scheduler marks AutoPay payments; anything else came from a workflow.
+    const source: PaymentSource = req.body.source === "autopay" ? "autopay" : "workflow";
     const wallet = userWallets.get(chatId);
 
     if (!wallet) {
@@ -1549,60 +1757,16 @@ app.post("/api/wallet/:chatId/send", async (req, res) => {
       });
     }
 
-    // Load sender account
-    const sourceKeypair = Keypair.fromSecret(wallet.secretKey);
-    const sourceAccount = await horizon.loadAccount(wallet.publicKey);
-
-    // Check if destination exists
-    let destinationExists = true;
-    try {
-      await horizon.loadAccount(destination);
-    } catch {
-      destinationExists = false;
-    }
-
-    const networkPassphrase = STELLAR_NETWORK === "testnet"
-      ? Networks.TESTNET
-      : Networks.PUBLIC;
-
-    let transaction;
-    if (destinationExists) {
-      transaction = new TransactionBuilder(sourceAccount, {
-        fee: BASE_FEE,
-        networkPassphrase,
-      })
-        .addOperation(
-          Operation.payment({
-            destination,
-            asset: Asset.native(),
-            amount: amountNum.toFixed(7),
-          })
-        )
-        .setTimeout(30)
-        .build();
-    } else {
-      if (amountNum < 1) {
-        return res.status(400).json({
-          success: false,
-          error: "Minimum 1 XLM required to create new account",
-        });
-      }
-      transaction = new TransactionBuilder(sourceAccount, {
-        fee: BASE_FEE,
-        networkPassphrase,
-      })
-        .addOperation(
-          Operation.createAccount({
-            destination,
-            startingBalance: amountNum.toFixed(7),
-          })
-        )
-        .setTimeout(30)
-        .build();
+    policy = await checkPolicy(chatId, destination, amountNum, source);
+    if (!policy.allowed) {
+      return res.status(403).json({
+        success: false,
+        error: policy.reason,
+        policy: policy.outcome,
+      });
     }
 
-    transaction.sign(sourceKeypair);
-    const result = await horizon.submitTransaction(transaction);
+    const result = await sendNative(wallet.publicKey, destination, amountNum.toFixed(7));
 
     return res.json({
       success: true,
@@ -1612,12 +1776,10 @@ app.post("/api/wallet/:chatId/send", async (req, res) => {
       explorerUrl: `https://stellar.expert/explorer/${STELLAR_NETWORK}/tx/${result.hash}`,
     });
   } catch (error: any) {
-    const errorMsg = error?.response?.data?.extras?.result_codes
-      ? JSON.stringify(error.response.data.extras.result_codes)
-      : error.message || "Transaction failed";
+    if (policy) await releasePolicy(policy);
     return res.status(500).json({
       success: false,
-      error: errorMsg,
+      error: error.message || "Transaction failed",
     });
   }
 });
@@ -1790,12 +1952,15 @@ app.post("/api/transaction/build", async (req, res) => {
     const networkPassphrase = (network || STELLAR_NETWORK) === "testnet"
       ? Networks.TESTNET
       : Networks.PUBLIC;
+    // What the submission service would offer, so a busy network does not
+    // leave the signed transaction stuck.
+    const fee = await suggestedFee();
 
     let transaction;
     if (destinationExists) {
       // Regular payment
       transaction = new TransactionBuilder(sourceAccount, {
-        fee: BASE_FEE,
+        fee,
         networkPassphrase,
       })
         .addOperation(
@@ -1816,7 +1981,7 @@ app.post("/api/transaction/build", async (req, res) => {
         });
       }
       transaction = new TransactionBuilder(sourceAccount, {
-        fee: BASE_FEE,
+        fee,
         networkPassphrase,
       })
         .addOperation(
@@ -1898,13 +2063,14 @@ app.post("/api/anchor/deposit", async (req, res) => {
         createdAt: new Date(),
       };
       userWallets.set(String(chatId), newWallet);
+      saveWallets();
       wallet = newWallet;
       console.log(`Auto-created wallet for anchor deposit: ${chatId}`);
     }
 
-    // Pass treasury secret for real XLM transfer (or undefined for Friendbot fallback)
-    const sourceSecret = ANCHOR_TREASURY_SECRET || undefined;
-    const result = await quickDeposit(String(chatId), parseFloat(amount), currency || 'USD', wallet.publicKey, sourceSecret);
+    // Credit from the treasury for real XLM transfer (or undefined for Friendbot fallback)
+    const treasury = ANCHOR_TREASURY || undefined;
+    const result = await quickDeposit(String(chatId), parseFloat(amount), currency || 'USD', wallet.publicKey, treasury);
     return res.json(result);
   } catch (err: any) {
     return res.status(500).json({ success: false, error: err.message });
@@ -1930,13 +2096,20 @@ app.post("/api/anchor/withdraw", async (req, res) => {
         createdAt: new Date(),
       };
       userWallets.set(String(chatId), newWallet);
+      saveWallets();
       wallet = newWallet;
       console.log(`Auto-created wallet for anchor withdrawal: ${chatId}`);
     }
 
-    const telegramWallet = userWallets.get(String(chatId));
-    const secret = telegramWallet?.secretKey;
-    const result = await quickWithdrawal(String(chatId), parseFloat(xlmAmount), currency || 'USD', wallet.publicKey, secret);
+    const policy = await checkPolicy(String(chatId), getTreasuryAddress(), parseFloat(xlmAmount), "offramp");
+    if (!policy.allowed) {
+      return res.status(403).json({ success: false, error: policy.reason, policy: policy.outcome });
+    }
+
+    // Only a Telegram wallet's XLM can be debited; the submission service signs for it.
+    const telegramWallet = userWallets.get(String(chatId))?.publicKey;
+    const result = await quickWithdrawal(String(chatId), parseFloat(xlmAmount), currency || 'USD', wallet.publicKey, telegramWallet);
+    if (!result.success) await releasePolicy(policy);
     return res.json(result);
   } catch (err: any) {
     return res.status(500).json({ success: false, error: err.message });
@@ -1981,7 +2154,7 @@ interface AutoPaySchedule {
   destination: string;
   amount: number;
   interval: string;
-  duration: number;
+  duration: number; // days
   createdAt: Date;
   nextPayment: Date;
   isActive: boolean;
@@ -1990,8 +2163,46 @@ interface AutoPaySchedule {
 const autoPaySchedules = new Map<string, AutoPaySchedule>();
 let scheduleCounter = 1;
 
+const HOUR_MS = 60 * 60 * 1000;
+const DAY_MS = 24 * HOUR_MS;
+
+/**
+ * A `duration` or `timeout` counted in units of `unitMs`: a bare number is
+ * already in those units, as older configs sent it, and anything else is an
+ * interval such as "2w" or "12h". Throws on anything else.
+ */
+function inUnits(name: string, value: unknown, unitMs: number, fallback: number): number {
+  const text = value === undefined || value === null ? "" : String(value).trim();
+  if (!text) return fallback;
+  const units = /^\d+(\.\d+)?$/.test(text) ? Number(text) : parseIntervalFormat(text) / unitMs;
+  if (!(units > 0)) throw new Error(`${name} must be positive, got "${text}"`);
+  return units;
+}
+
+// Relay an AutoPay request to the scheduler service and pass its answer back.
+async function forwardToScheduler(
+  res: express.Response,
+  method: string,
+  path: string,
+  body?: unknown
+) {
+  try {
+    const response = await fetch(`${AUTOPAY_SCHEDULER_URL}${path}`, {
+      method,
+      headers: { "Content-Type": "application/json" },
+      body: body === undefined ? undefined : JSON.stringify(body),
+    });
+    return res.status(response.status).json(await response.json());
+  } catch (err: any) {
+    return res.status(502).json({
+      success: false,
+      error: `AutoPay scheduler unavailable: ${err.message}`,
+    });
+  }
+}
+
 // POST /api/autopay/create — Create scheduled payment
-app.post("/api/autopay/create", (req, res) => {
+app.post("/api/autopay/create", async (req, res) => {
   try {
     const { chatId, destination, amount, interval, duration } = req.body;
 
@@ -2002,6 +2213,18 @@ app.post("/api/autopay/create", (req, res) => {
       });
     }
 
+    if (AUTOPAY_SCHEDULER_URL) {
+      // The scheduler pays through /api/wallet/:chatId/send, which signs
+      // with the Telegram wallet.
+      if (!userWallets.get(String(chatId))) {
+        return res.status(404).json({
+          success: false,
+          error: "AutoPay pays from your Telegram wallet. Create one with /createwallet first."
+        });
+      }
+      return forwardToScheduler(res, "POST", "/api/autopay/create", req.body);
+    }
+
     const wallet = freighterWallets.get(String(chatId)) || userWallets.get(String(chatId));
     if (!wallet) {
       return res.status(404).json({
@@ -2010,6 +2233,13 @@ app.post("/api/autopay/create", (req, res) => {
       });
     }
 
+    let days: number;
+    try {
+      days = inUnits("duration", duration, DAY_MS, 30);
+    } catch (err: any) {
+      return res.status(400).json({ success: false, error: err.message });
+    }
+
     const scheduleId = `AP-${Date.now()}-${scheduleCounter++}`;
     const now = new Date();
 
@@ -2031,7 +2261,7 @@ app.post("/api/autopay/create", (req, res) => {
       destination,
       amount: parseFloat(amount),
       interval: interval || "daily",
-      duration: parseInt(duration) || 30,
+      duration: days,
       createdAt: now,
       nextPayment,
       isActive: true,
@@ -2057,16 +2287,22 @@ app.post("/api/autopay/create", (req, res) => {
 });
 
 // GET /api/autopay/:chatId — Get user's schedules
-app.get("/api/autopay/:chatId", (req, res) => {
+app.get("/api/autopay/:chatId", async (req, res) => {
   const { chatId } = req.params;
+  if (AUTOPAY_SCHEDULER_URL) {
+    return forwardToScheduler(res, "GET", `/api/autopay/${encodeURIComponent(chatId)}`);
+  }
   const schedules = Array.from(autoPaySchedules.values())
     .filter(s => s.chatId === chatId);
   return res.json({ success: true, schedules });
 });
 
 // DELETE /api/autopay/:scheduleId — Cancel a schedule
-app.delete("/api/autopay/:scheduleId", (req, res) => {
+app.delete("/api/autopay/:scheduleId", async (req, res) => {
   const { scheduleId } = req.params;
+  if (AUTOPAY_SCHEDULER_URL) {
+    return forwardToScheduler(res, "DELETE", `/api/autopay/${encodeURIComponent(scheduleId)}`);
+  }
   const schedule = autoPaySchedules.get(scheduleId);
 
   if (!schedule) {
@@ -2086,7 +2322,7 @@ interface MultisigConfig {
   chatId: string;
   threshold: number;
   signers: string[];
-  timeout: number;
+  timeout: number; // hours
   createdAt: Date;
   isActive: boolean;
 }
@@ -2123,6 +2359,13 @@ app.post("/api/multisig/create", (req, res) => {
       });
     }
 
+    let hours: number;
+    try {
+      hours = inUnits("timeout", timeout, HOUR_MS, 24);
+    } catch (err: any) {
+      return res.status(400).json({ success: false, error: err.message });
+    }
+
     const multisigId = `MS-${Date.now()}-${multisigCounter++}`;
     const now = new Date();
 
@@ -2131,7 +2374,7 @@ app.post("/api/multisig/create", (req, res) => {
       chatId: String(chatId),
       threshold: parseInt(threshold),
       signers: signerList,
-      timeout: parseInt(timeout) || 24,
+      timeout: hours,
       createdAt: now,
       isActive: true,
     };
@@ -0,0 +1,378 @@
+//! StellrFlow — AutoPay Escrow
+//!
+//! On-chain replacement for the in-memory `autoPaySchedules` map behind
+//! `POST /api/autopay/create`.
+//!
+//! Lifecycle:
+//!   1. `create` — the payer locks `amount × payments` of a token (XLM via
+//!      its Stellar Asset Contract, or any SEP-41 token) into the contract
+//!   2. `execute_due` — anyone (a keeper, the bot, the destination) releases
+//!      every payment whose due time has passed
+//!   3. `cancel` — the payer stops the schedule; payments already due still
+//!      go to the destination, the rest is refunded
+//!
+//! Payment `n` (1-based) is due at `start + n × interval`, matching the bot
+//! which schedules the first payment one interval after creation.
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, Env, Vec,
+};
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const SCHEDULE_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const SCHEDULE_TTL_EXTEND_TO: u32 = 60 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_EXTEND_TO: u32 = 60 * DAY_IN_LEDGERS;
+
+/// Schedule IDs per page of a payer's index, so no entry outgrows the
+/// ledger's size limit however many schedules a payer creates.
+pub const SCHEDULES_PER_PAGE: u32 = 100;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// `amount` must be positive.
+    InvalidAmount = 1,
+    /// `interval` must be at least one second.
+    InvalidInterval = 2,
+    /// `duration` must cover at least one interval, and end before the
+    /// largest ledger timestamp.
+    InvalidDuration = 3,
+    /// No schedule with this ID.
+    NotFound = 4,
+    /// The schedule has completed or been cancelled.
+    NotActive = 5,
+    /// `amount × payments` does not fit in an i128.
+    BudgetOverflow = 6,
+}
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Status {
+    Active = 0,
+    Completed = 1,
+    Cancelled = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Schedule {
+    pub payer: Address,
+    pub destination: Address,
+    pub token: Address,
+    /// Amount released per payment, in the token's base units (stroops for XLM).
+    pub amount: i128,
+    /// Seconds between payments.
+    pub interval: u64,
+    /// Ledger timestamp the schedule was created at.
+    pub start: u64,
+    pub total_payments: u32,
+    pub payments_made: u32,
+    pub status: Status,
+}
+
+impl Schedule {
+    /// Timestamp at which the next unpaid payment becomes due.
+    fn next_payment(&self) -> Option<u64> {
+        if self.status != Status::Active || self.payments_made >= self.total_payments {
+            return None;
+        }
+        (self.payments_made as u64 + 1)
+            .checked_mul(self.interval)
+            .and_then(|offset| self.start.checked_add(offset))
+    }
+
+    /// Number of payments that are due at `now` but not yet released.
+    fn due_at(&self, now: u64) -> u32 {
+        let elapsed = now.saturating_sub(self.start) / self.interval;
+        let due = elapsed.min(self.total_payments as u64) as u32;
+        due.saturating_sub(self.payments_made)
+    }
+
+    fn remaining_budget(&self) -> i128 {
+        self.amount * (self.total_payments - self.payments_made) as i128
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    NextId,
+    Schedule(u64),
+    /// How many schedules a payer has created.
+    PayerScheduleCount(Address),
+    /// Page `n` of a payer's schedule IDs, oldest first.
+    PayerSchedules(Address, u32),
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduleCreated {
+    #[topic]
+    pub schedule_id: u64,
+    #[topic]
+    pub payer: Address,
+    pub destination: Address,
+    pub amount: i128,
+    pub interval: u64,
+    pub total_payments: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentsReleased {
+    #[topic]
+    pub schedule_id: u64,
+    pub count: u32,
+    pub amount: i128,
+    pub payments_made: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduleCompleted {
+    #[topic]
+    pub schedule_id: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScheduleCancelled {
+    #[topic]
+    pub schedule_id: u64,
+    pub refunded: i128,
+}
+
+#[contract]
+pub struct AutoPay;
+
+#[contractimpl]
+impl AutoPay {
+    /// Lock `amount × (duration / interval)` of `token` from `payer` and start
+    /// paying `destination` every `interval` seconds. Returns the schedule ID.
+    pub fn create(
+        env: Env,
+        payer: Address,
+        destination: Address,
+        token: Address,
+        amount: i128,
+        interval: u64,
+        duration: u64,
+    ) -> Result<u64, Error> {
+        payer.require_auth();
+        extend_instance(&env);
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        if interval == 0 {
+            return Err(Error::InvalidInterval);
+        }
+        let total_payments = duration / interval;
+        let start = env.ledger().timestamp();
+        if total_payments == 0
+            || total_payments > u32::MAX as u64
+            || start.checked_add(duration).is_none()
+        {
+            return Err(Error::InvalidDuration);
+        }
+        let total_payments = total_payments as u32;
+        let budget = amount
+            .checked_mul(total_payments as i128)
+            .ok_or(Error::BudgetOverflow)?;
+
+        token::Client::new(&env, &token).transfer(&payer, env.current_contract_address(), &budget);
+
+        let schedule_id = next_id(&env);
+        let schedule = Schedule {
+            payer: payer.clone(),
+            destination: destination.clone(),
+            token,
+            amount,
+            interval,
+            start,
+            total_payments,
+            payments_made: 0,
+            status: Status::Active,
+        };
+        write_schedule(&env, schedule_id, &schedule);
+        index_schedule(&env, &payer, schedule_id);
+
+        ScheduleCreated {
+            schedule_id,
+            payer,
+            destination,
+            amount,
+            interval,
+            total_payments,
+        }
+        .publish(&env);
+        Ok(schedule_id)
+    }
+
+    /// Release every payment of `schedule_id` that is due by the current
+    /// ledger timestamp. Callable by anyone; returns how many were released.
+    pub fn execute_due(env: Env, schedule_id: u64) -> Result<u32, Error> {
+        extend_instance(&env);
+        let mut schedule = read_schedule(&env, schedule_id)?;
+        if schedule.status != Status::Active {
+            return Err(Error::NotActive);
+        }
+
+        let released = release_due(&env, schedule_id, &mut schedule);
+        if schedule.payments_made == schedule.total_payments {
+            schedule.status = Status::Completed;
+            ScheduleCompleted { schedule_id }.publish(&env);
+        }
+        write_schedule(&env, schedule_id, &schedule);
+        Ok(released)
+    }
+
+    /// Stop `schedule_id`. Payments already due are released to the
+    /// destination first; the remaining budget is refunded to the payer.
+    /// Returns the refunded amount.
+    pub fn cancel(env: Env, schedule_id: u64) -> Result<i128, Error> {
+        let mut schedule = read_schedule(&env, schedule_id)?;
+        schedule.payer.require_auth();
+        extend_instance(&env);
+        if schedule.status != Status::Active {
+            return Err(Error::NotActive);
+        }
+
+        release_due(&env, schedule_id, &mut schedule);
+
+        let refunded = schedule.remaining_budget();
+        if refunded > 0 {
+            token::Client::new(&env, &schedule.token).transfer(
+                &env.current_contract_address(),
+                &schedule.payer,
+                &refunded,
+            );
+        }
+        schedule.status = Status::Cancelled;
+        write_schedule(&env, schedule_id, &schedule);
+
+        ScheduleCancelled {
+            schedule_id,
+            refunded,
+        }
+        .publish(&env);
+        Ok(refunded)
+    }
+
+    /// Look up a schedule.
+    pub fn schedule(env: Env, schedule_id: u64) -> Option<Schedule> {
+        read_schedule(&env, schedule_id).ok()
+    }
+
+    /// How many schedules `payer` has created.
+    pub fn schedule_count(env: Env, payer: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PayerScheduleCount(payer))
+            .unwrap_or(0)
+    }
+
+    /// Page `page` of the IDs of the schedules `payer` has created, oldest
+    /// first: up to [`SCHEDULES_PER_PAGE`] of them.
+    pub fn schedules_of(env: Env, payer: Address, page: u32) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PayerSchedules(payer, page))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Timestamp of the next payment, or `None` once the schedule is done.
+    pub fn next_payment(env: Env, schedule_id: u64) -> Result<Option<u64>, Error> {
+        Ok(read_schedule(&env, schedule_id)?.next_payment())
+    }
+
+    /// Number of payments a keeper could release right now.
+    pub fn due_count(env: Env, schedule_id: u64) -> Result<u32, Error> {
+        let schedule = read_schedule(&env, schedule_id)?;
+        if schedule.status != Status::Active {
+            return Ok(0);
+        }
+        Ok(schedule.due_at(env.ledger().timestamp()))
+    }
+}
+
+fn release_due(env: &Env, schedule_id: u64, schedule: &mut Schedule) -> u32 {
+    let count = schedule.due_at(env.ledger().timestamp());
+    if count == 0 {
+        return 0;
+    }
+
+    let amount = schedule.amount * count as i128;
+    token::Client::new(env, &schedule.token).transfer(
+        &env.current_contract_address(),
+        &schedule.destination,
+        &amount,
+    );
+    schedule.payments_made += count;
+
+    PaymentsReleased {
+        schedule_id,
+        count,
+        amount,
+        payments_made: schedule.payments_made,
+    }
+    .publish(env);
+    count
+}
+
+/// Keep the contract instance, and the `NextId` counter stored with it,
+/// from being archived while the contract is in use.
+fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+}
+
+fn next_id(env: &Env) -> u64 {
+    let id: u64 = env.storage().instance().get(&DataKey::NextId).unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextId, &(id + 1));
+    id
+}
+
+/// Add `schedule_id` to the last page of `payer`'s index, starting a new
+/// page once that one is full.
+fn index_schedule(env: &Env, payer: &Address, schedule_id: u64) {
+    let count_key = DataKey::PayerScheduleCount(payer.clone());
+    let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    let key = DataKey::PayerSchedules(payer.clone(), count / SCHEDULES_PER_PAGE);
+    let mut ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or_else(|| Vec::new(env));
+    ids.push_back(schedule_id);
+    env.storage().persistent().set(&key, &ids);
+    env.storage().persistent().set(&count_key, &(count + 1));
+    for key in [key, count_key] {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, SCHEDULE_TTL_THRESHOLD, SCHEDULE_TTL_EXTEND_TO);
+    }
+}
+
+fn read_schedule(env: &Env, schedule_id: u64) -> Result<Schedule, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Schedule(schedule_id))
+        .ok_or(Error::NotFound)
+}
+
+fn write_schedule(env: &Env, schedule_id: u64, schedule: &Schedule) {
+    let key = DataKey::Schedule(schedule_id);
+    env.storage().persistent().set(&key, schedule);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, SCHEDULE_TTL_THRESHOLD, SCHEDULE_TTL_EXTEND_TO);
+}
+
+#[cfg(test)]
+mod test;
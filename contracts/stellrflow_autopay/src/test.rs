@@ -0,0 +1,304 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{storage::Instance as _, Address as _, EnvTestConfig, Events as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    Event,
+};
+
+const HOUR: u64 = 3_600;
+const DAY: u64 = 24 * HOUR;
+const START: u64 = 1_700_000_000;
+/// 10 XLM in stroops.
+const AMOUNT: i128 = 100_000_000;
+
+struct Setup {
+    env: Env,
+    payer: Address,
+    destination: Address,
+    token: TokenClient<'static>,
+    client: AutoPayClient<'static>,
+}
+
+fn setup() -> Setup {
+    setup_in(Env::default())
+}
+
+fn setup_in(env: Env) -> Setup {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(START);
+
+    let issuer = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(issuer);
+    let payer = Address::generate(&env);
+    StellarAssetClient::new(&env, &sac.address()).mint(&payer, &(1_000 * AMOUNT));
+
+    let contract_id = env.register(AutoPay, ());
+    Setup {
+        payer,
+        destination: Address::generate(&env),
+        token: TokenClient::new(&env, &sac.address()),
+        client: AutoPayClient::new(&env, &contract_id),
+        env,
+    }
+}
+
+fn advance(env: &Env, seconds: u64) {
+    env.ledger().with_mut(|l| l.timestamp += seconds);
+}
+
+impl Setup {
+    /// Hourly payments of AMOUNT for one day.
+    fn create_hourly(&self) -> u64 {
+        self.client.create(
+            &self.payer,
+            &self.destination,
+            &self.token.address,
+            &AMOUNT,
+            &HOUR,
+            &DAY,
+        )
+    }
+}
+
+#[test]
+fn create_locks_budget() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    assert_eq!(id, 1);
+    assert_eq!(s.token.balance(&s.client.address), 24 * AMOUNT);
+    assert_eq!(s.token.balance(&s.payer), 976 * AMOUNT);
+
+    let schedule = s.client.schedule(&id).unwrap();
+    assert_eq!(schedule.total_payments, 24);
+    assert_eq!(schedule.payments_made, 0);
+    assert_eq!(schedule.start, START);
+    assert_eq!(schedule.status, Status::Active);
+    assert_eq!(s.client.next_payment(&id), Some(START + HOUR));
+    assert_eq!(
+        s.client.schedules_of(&s.payer, &0),
+        soroban_sdk::vec![&s.env, 1]
+    );
+    assert_eq!(s.client.schedule_count(&s.payer), 1);
+}
+
+#[test]
+fn create_rejects_bad_parameters() {
+    let s = setup();
+    let (payer, dest, token) = (&s.payer, &s.destination, &s.token.address);
+
+    assert_eq!(
+        s.client.try_create(payer, dest, token, &0, &HOUR, &DAY),
+        Err(Ok(Error::InvalidAmount))
+    );
+    assert_eq!(
+        s.client.try_create(payer, dest, token, &AMOUNT, &0, &DAY),
+        Err(Ok(Error::InvalidInterval))
+    );
+    assert_eq!(
+        s.client
+            .try_create(payer, dest, token, &AMOUNT, &DAY, &HOUR),
+        Err(Ok(Error::InvalidDuration))
+    );
+    assert_eq!(
+        s.client
+            .try_create(payer, dest, token, &i128::MAX, &HOUR, &DAY),
+        Err(Ok(Error::BudgetOverflow))
+    );
+    // The last payment would fall after the largest timestamp.
+    assert_eq!(
+        s.client
+            .try_create(payer, dest, token, &AMOUNT, &(u64::MAX / 2), &u64::MAX),
+        Err(Ok(Error::InvalidDuration))
+    );
+}
+
+#[test]
+fn the_longest_interval_does_not_overflow() {
+    let s = setup();
+    let interval = u64::MAX - START;
+    let id = s.client.create(
+        &s.payer,
+        &s.destination,
+        &s.token.address,
+        &AMOUNT,
+        &interval,
+        &interval,
+    );
+    assert_eq!(s.client.next_payment(&id), Some(u64::MAX));
+    assert_eq!(s.client.due_count(&id), 0);
+}
+
+#[test]
+fn a_payers_schedules_are_indexed_in_pages() {
+    // A hundred schedules make too big a snapshot to keep.
+    let s = setup_in(Env::new_with_config(EnvTestConfig {
+        capture_snapshot_at_drop: false,
+    }));
+    let ids: std::vec::Vec<u64> = (0..=SCHEDULES_PER_PAGE)
+        .map(|_| {
+            s.client
+                .create(&s.payer, &s.destination, &s.token.address, &1, &HOUR, &HOUR)
+        })
+        .collect();
+
+    assert_eq!(s.client.schedule_count(&s.payer), SCHEDULES_PER_PAGE + 1);
+    let first = s.client.schedules_of(&s.payer, &0);
+    assert_eq!(first.len(), SCHEDULES_PER_PAGE);
+    assert_eq!(first.get(0), Some(ids[0]));
+    assert_eq!(
+        s.client.schedules_of(&s.payer, &1),
+        soroban_sdk::vec![&s.env, ids[SCHEDULES_PER_PAGE as usize]]
+    );
+    assert!(s.client.schedules_of(&s.payer, &2).is_empty());
+}
+
+#[test]
+fn nothing_is_due_before_first_interval() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    advance(&s.env, HOUR - 1);
+    assert_eq!(s.client.due_count(&id), 0);
+    assert_eq!(s.client.execute_due(&id), 0);
+    assert_eq!(s.token.balance(&s.destination), 0);
+}
+
+#[test]
+fn execute_due_releases_exactly_the_due_payments() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    advance(&s.env, HOUR);
+    assert_eq!(s.client.execute_due(&id), 1);
+    assert_eq!(s.token.balance(&s.destination), AMOUNT);
+
+    // Calling again in the same ledger releases nothing.
+    assert_eq!(s.client.execute_due(&id), 0);
+
+    // A keeper that was down for 3.5 hours catches up on the 3 missed payments.
+    advance(&s.env, 3 * HOUR + HOUR / 2);
+    assert_eq!(s.client.due_count(&id), 3);
+    assert_eq!(s.client.execute_due(&id), 3);
+    assert_eq!(
+        s.env.events().all().filter_by_contract(&s.client.address),
+        [PaymentsReleased {
+            schedule_id: id,
+            count: 3,
+            amount: 3 * AMOUNT,
+            payments_made: 4,
+        }
+        .to_xdr(&s.env, &s.client.address)]
+    );
+    assert_eq!(s.token.balance(&s.destination), 4 * AMOUNT);
+    assert_eq!(s.client.next_payment(&id), Some(START + 5 * HOUR));
+}
+
+#[test]
+fn schedule_completes_after_duration() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    advance(&s.env, 10 * DAY);
+    assert_eq!(s.client.execute_due(&id), 24);
+    assert_eq!(s.token.balance(&s.destination), 24 * AMOUNT);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+
+    let schedule = s.client.schedule(&id).unwrap();
+    assert_eq!(schedule.status, Status::Completed);
+    assert_eq!(s.client.next_payment(&id), None);
+    assert_eq!(s.client.try_execute_due(&id), Err(Ok(Error::NotActive)));
+}
+
+#[test]
+fn cancel_pays_due_and_refunds_remainder() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    advance(&s.env, 2 * HOUR + 1);
+    let refunded = s.client.cancel(&id);
+
+    assert_eq!(refunded, 22 * AMOUNT);
+    assert_eq!(s.token.balance(&s.destination), 2 * AMOUNT);
+    assert_eq!(s.token.balance(&s.payer), 998 * AMOUNT);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+    assert_eq!(s.client.schedule(&id).unwrap().status, Status::Cancelled);
+
+    advance(&s.env, DAY);
+    assert_eq!(s.client.try_execute_due(&id), Err(Ok(Error::NotActive)));
+    assert_eq!(s.client.try_cancel(&id), Err(Ok(Error::NotActive)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn cancel_requires_payer_auth() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    s.env.set_auths(&[]);
+    s.client.cancel(&id);
+}
+
+#[test]
+fn execute_due_needs_no_auth() {
+    let s = setup();
+    let id = s.create_hourly();
+
+    advance(&s.env, HOUR);
+    s.env.set_auths(&[]);
+    assert_eq!(s.client.execute_due(&id), 1);
+    assert!(s.env.auths().is_empty());
+}
+
+#[test]
+fn unknown_schedule() {
+    let s = setup();
+
+    assert_eq!(s.client.schedule(&7), None);
+    assert_eq!(s.client.try_execute_due(&7), Err(Ok(Error::NotFound)));
+    assert_eq!(s.client.try_cancel(&7), Err(Ok(Error::NotFound)));
+}
+
+#[test]
+fn schedules_are_independent() {
+    let s = setup();
+    let hourly = s.create_hourly();
+    let daily = s.client.create(
+        &s.payer,
+        &s.destination,
+        &s.token.address,
+        &(2 * AMOUNT),
+        &DAY,
+        &(7 * DAY),
+    );
+
+    advance(&s.env, DAY);
+    assert_eq!(s.client.execute_due(&daily), 1);
+    assert_eq!(s.client.execute_due(&hourly), 24);
+    assert_eq!(s.token.balance(&s.destination), 26 * AMOUNT);
+    assert_eq!(
+        s.client.schedules_of(&s.payer, &0),
+        soroban_sdk::vec![&s.env, hourly, daily]
+    );
+}
+
+#[test]
+fn calls_keep_the_instance_alive() {
+    let s = setup();
+    let id = s.create_hourly();
+    let ttl = || {
+        s.env
+            .as_contract(&s.client.address, || s.env.storage().instance().get_ttl())
+    };
+    assert_eq!(ttl(), INSTANCE_TTL_EXTEND_TO);
+
+    // Within a week of expiring, a keeper's call extends it again.
+    let elapsed = INSTANCE_TTL_EXTEND_TO - INSTANCE_TTL_THRESHOLD + 1;
+    s.env.ledger().with_mut(|l| l.sequence_number += elapsed);
+    assert_eq!(ttl(), INSTANCE_TTL_THRESHOLD - 1);
+    assert_eq!(s.client.execute_due(&id), 0);
+    assert_eq!(ttl(), INSTANCE_TTL_EXTEND_TO);
+}
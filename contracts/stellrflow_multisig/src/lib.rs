@@ -0,0 +1,329 @@
+//! StellrFlow — Multisig Approval Vault
+//!
+//! On-chain counterpart of the bot's `MultisigConfig` / `PendingApproval`
+//! records. The vault holds tokens; moving them out takes `threshold`
+//! approvals from the configured signers within `timeout` seconds.
+//!
+//! Flow:
+//!   1. `propose` — a signer proposes a transfer (this counts as their
+//!      approval)
+//!   2. `approve` — other signers approve with `require_auth`
+//!   3. the approval that reaches the threshold executes the transfer
+//!
+//! A proposal that has not reached the threshold by `created_at + timeout`
+//! is expired and can no longer be approved or executed.
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, token, Address, Env, Vec,
+};
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const PROPOSAL_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const PROPOSAL_TTL_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_EXTEND_TO: u32 = 60 * DAY_IN_LEDGERS;
+
+/// The longest approval window, in seconds: as long as a proposal is kept
+/// alive without being touched.
+pub const MAX_TIMEOUT: u64 = 30 * 24 * 60 * 60;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Threshold must be between 1 and the number of signers.
+    InvalidThreshold = 1,
+    /// A signer appears more than once.
+    DuplicateSigner = 2,
+    /// Timeout must be between one second and [`MAX_TIMEOUT`].
+    InvalidTimeout = 3,
+    /// The caller is not one of the configured signers.
+    NotSigner = 4,
+    /// No proposal with this ID.
+    NotFound = 5,
+    /// This signer has already approved the proposal.
+    AlreadyApproved = 6,
+    /// The approval window has closed.
+    Expired = 7,
+    /// The proposal has already been executed.
+    AlreadyExecuted = 8,
+    /// Transfer amount must be positive.
+    InvalidAmount = 9,
+}
+
+/// Signer policy, mirroring the bot's `MultisigConfig`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+    /// Seconds a proposal stays open for approvals.
+    pub timeout: u64,
+}
+
+/// The transfer a proposal will perform once approved.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transfer {
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Status {
+    Pending = 0,
+    Executed = 1,
+    Expired = 2,
+}
+
+/// A proposed transfer, mirroring the bot's `PendingApproval`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub transfer: Transfer,
+    pub proposer: Address,
+    pub approvals: Vec<Address>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub executed: bool,
+}
+
+impl Proposal {
+    fn status(&self, now: u64) -> Status {
+        if self.executed {
+            Status::Executed
+        } else if now >= self.expires_at {
+            Status::Expired
+        } else {
+            Status::Pending
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Config,
+    NextId,
+    Proposal(u64),
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposed {
+    #[topic]
+    pub proposal_id: u64,
+    #[topic]
+    pub proposer: Address,
+    pub transfer: Transfer,
+    pub expires_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Approved {
+    #[topic]
+    pub proposal_id: u64,
+    #[topic]
+    pub signer: Address,
+    pub approvals: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Executed {
+    #[topic]
+    pub proposal_id: u64,
+    pub transfer: Transfer,
+}
+
+#[contract]
+pub struct MultisigVault;
+
+#[contractimpl]
+impl MultisigVault {
+    /// Deploy-time setup of the signer policy.
+    pub fn __constructor(
+        env: Env,
+        signers: Vec<Address>,
+        threshold: u32,
+        timeout: u64,
+    ) -> Result<(), Error> {
+        if threshold == 0 || threshold > signers.len() {
+            return Err(Error::InvalidThreshold);
+        }
+        if timeout == 0 || timeout > MAX_TIMEOUT {
+            return Err(Error::InvalidTimeout);
+        }
+        for (i, signer) in signers.iter().enumerate() {
+            if signers.first_index_of(&signer) != Some(i as u32) {
+                return Err(Error::DuplicateSigner);
+            }
+        }
+
+        env.storage().instance().set(
+            &DataKey::Config,
+            &Config {
+                signers,
+                threshold,
+                timeout,
+            },
+        );
+        extend_instance(&env);
+        Ok(())
+    }
+
+    /// The signer policy.
+    pub fn config(env: Env) -> Config {
+        read_config(&env)
+    }
+
+    /// Propose `transfer` out of the vault. The proposer's approval is
+    /// counted immediately, so a 1-of-N vault executes right away.
+    pub fn propose(env: Env, proposer: Address, transfer: Transfer) -> Result<u64, Error> {
+        proposer.require_auth();
+        extend_instance(&env);
+        let config = read_config(&env);
+        if !config.signers.contains(&proposer) {
+            return Err(Error::NotSigner);
+        }
+        if transfer.amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let proposal_id = next_id(&env);
+        let now = env.ledger().timestamp();
+        let mut proposal = Proposal {
+            transfer: transfer.clone(),
+            proposer: proposer.clone(),
+            approvals: Vec::new(&env),
+            created_at: now,
+            expires_at: now.saturating_add(config.timeout),
+            executed: false,
+        };
+
+        Proposed {
+            proposal_id,
+            proposer: proposer.clone(),
+            transfer,
+            expires_at: proposal.expires_at,
+        }
+        .publish(&env);
+
+        record_approval(&env, &config, proposal_id, &mut proposal, proposer);
+        write_proposal(&env, proposal_id, &proposal);
+        Ok(proposal_id)
+    }
+
+    /// Approve `proposal_id` as `signer`. Returns `true` if this approval
+    /// reached the threshold and the transfer was executed.
+    pub fn approve(env: Env, signer: Address, proposal_id: u64) -> Result<bool, Error> {
+        signer.require_auth();
+        extend_instance(&env);
+        let config = read_config(&env);
+        if !config.signers.contains(&signer) {
+            return Err(Error::NotSigner);
+        }
+
+        let mut proposal = read_proposal(&env, proposal_id)?;
+        match proposal.status(env.ledger().timestamp()) {
+            Status::Executed => return Err(Error::AlreadyExecuted),
+            Status::Expired => return Err(Error::Expired),
+            Status::Pending => {}
+        }
+        if proposal.approvals.contains(&signer) {
+            return Err(Error::AlreadyApproved);
+        }
+
+        record_approval(&env, &config, proposal_id, &mut proposal, signer);
+        write_proposal(&env, proposal_id, &proposal);
+        Ok(proposal.executed)
+    }
+
+    /// Look up a proposal.
+    pub fn proposal(env: Env, proposal_id: u64) -> Option<Proposal> {
+        read_proposal(&env, proposal_id).ok()
+    }
+
+    /// Current status of a proposal, taking expiry into account.
+    pub fn status(env: Env, proposal_id: u64) -> Result<Status, Error> {
+        Ok(read_proposal(&env, proposal_id)?.status(env.ledger().timestamp()))
+    }
+}
+
+/// Add `signer` to the approvals and execute the transfer if that reaches
+/// the threshold.
+fn record_approval(
+    env: &Env,
+    config: &Config,
+    proposal_id: u64,
+    proposal: &mut Proposal,
+    signer: Address,
+) {
+    proposal.approvals.push_back(signer.clone());
+    Approved {
+        proposal_id,
+        signer,
+        approvals: proposal.approvals.len(),
+    }
+    .publish(env);
+
+    if proposal.approvals.len() >= config.threshold {
+        let transfer = &proposal.transfer;
+        token::Client::new(env, &transfer.token).transfer(
+            &env.current_contract_address(),
+            &transfer.to,
+            &transfer.amount,
+        );
+        proposal.executed = true;
+
+        Executed {
+            proposal_id,
+            transfer: transfer.clone(),
+        }
+        .publish(env);
+    }
+}
+
+/// Keep the vault's instance, which holds its `Config` and `NextId`, from
+/// being archived while it is in use.
+fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+}
+
+fn read_config(env: &Env) -> Config {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .expect("config is set by the constructor")
+}
+
+fn next_id(env: &Env) -> u64 {
+    let id: u64 = env.storage().instance().get(&DataKey::NextId).unwrap_or(1);
+    env.storage().instance().set(&DataKey::NextId, &(id + 1));
+    id
+}
+
+fn read_proposal(env: &Env, proposal_id: u64) -> Result<Proposal, Error> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Proposal(proposal_id))
+        .ok_or(Error::NotFound)
+}
+
+fn write_proposal(env: &Env, proposal_id: u64, proposal: &Proposal) {
+    let key = DataKey::Proposal(proposal_id);
+    env.storage().persistent().set(&key, proposal);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, PROPOSAL_TTL_THRESHOLD, PROPOSAL_TTL_EXTEND_TO);
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,267 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{storage::Instance as _, Address as _, Events as _, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Event,
+};
+
+const HOUR: u64 = 3_600;
+const START: u64 = 1_700_000_000;
+const AMOUNT: i128 = 250_000_000;
+
+struct Setup {
+    env: Env,
+    signers: [Address; 3],
+    recipient: Address,
+    token: TokenClient<'static>,
+    client: MultisigVaultClient<'static>,
+}
+
+/// A funded 2-of-3 vault with a 24h approval window.
+fn setup() -> Setup {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(START);
+
+    let signers = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let contract_id = env.register(
+        MultisigVault,
+        (
+            vec![
+                &env,
+                signers[0].clone(),
+                signers[1].clone(),
+                signers[2].clone(),
+            ],
+            2_u32,
+            24 * HOUR,
+        ),
+    );
+
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &(10 * AMOUNT));
+
+    Setup {
+        signers,
+        recipient: Address::generate(&env),
+        token: TokenClient::new(&env, &sac.address()),
+        client: MultisigVaultClient::new(&env, &contract_id),
+        env,
+    }
+}
+
+impl Setup {
+    fn transfer(&self) -> Transfer {
+        Transfer {
+            token: self.token.address.clone(),
+            to: self.recipient.clone(),
+            amount: AMOUNT,
+        }
+    }
+}
+
+#[test]
+fn config_is_stored() {
+    let s = setup();
+    let config = s.client.config();
+
+    assert_eq!(config.threshold, 2);
+    assert_eq!(config.signers.len(), 3);
+    assert_eq!(config.timeout, 24 * HOUR);
+}
+
+#[test]
+fn threshold_approvals_execute_transfer() {
+    let s = setup();
+    let id = s.client.propose(&s.signers[0], &s.transfer());
+
+    let proposal = s.client.proposal(&id).unwrap();
+    assert_eq!(proposal.approvals, vec![&s.env, s.signers[0].clone()]);
+    assert_eq!(proposal.expires_at, START + 24 * HOUR);
+    assert_eq!(s.client.status(&id), Status::Pending);
+    assert_eq!(s.token.balance(&s.recipient), 0);
+
+    assert!(s.client.approve(&s.signers[2], &id));
+    assert_eq!(
+        s.env.events().all().filter_by_contract(&s.client.address),
+        [
+            Approved {
+                proposal_id: id,
+                signer: s.signers[2].clone(),
+                approvals: 2,
+            }
+            .to_xdr(&s.env, &s.client.address),
+            Executed {
+                proposal_id: id,
+                transfer: s.transfer(),
+            }
+            .to_xdr(&s.env, &s.client.address),
+        ]
+    );
+
+    assert_eq!(s.token.balance(&s.recipient), AMOUNT);
+    assert_eq!(s.token.balance(&s.client.address), 9 * AMOUNT);
+    assert_eq!(s.client.status(&id), Status::Executed);
+    assert_eq!(
+        s.client.try_approve(&s.signers[1], &id),
+        Err(Ok(Error::AlreadyExecuted))
+    );
+}
+
+#[test]
+fn one_of_n_executes_on_propose() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let signer = Address::generate(&env);
+    let contract_id = env.register(MultisigVault, (vec![&env, signer.clone()], 1_u32, HOUR));
+    let client = MultisigVaultClient::new(&env, &contract_id);
+
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    StellarAssetClient::new(&env, &sac.address()).mint(&contract_id, &AMOUNT);
+    let recipient = Address::generate(&env);
+
+    let id = client.propose(
+        &signer,
+        &Transfer {
+            token: sac.address(),
+            to: recipient.clone(),
+            amount: AMOUNT,
+        },
+    );
+    assert_eq!(client.status(&id), Status::Executed);
+    assert_eq!(
+        TokenClient::new(&env, &sac.address()).balance(&recipient),
+        AMOUNT
+    );
+}
+
+#[test]
+fn approvals_are_counted_once() {
+    let s = setup();
+    let id = s.client.propose(&s.signers[0], &s.transfer());
+
+    assert_eq!(
+        s.client.try_approve(&s.signers[0], &id),
+        Err(Ok(Error::AlreadyApproved))
+    );
+    assert_eq!(s.token.balance(&s.recipient), 0);
+}
+
+#[test]
+fn strangers_cannot_propose_or_approve() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
+
+    assert_eq!(
+        s.client.try_propose(&stranger, &s.transfer()),
+        Err(Ok(Error::NotSigner))
+    );
+
+    let id = s.client.propose(&s.signers[0], &s.transfer());
+    assert_eq!(
+        s.client.try_approve(&stranger, &id),
+        Err(Ok(Error::NotSigner))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn approve_requires_signer_auth() {
+    let s = setup();
+    let id = s.client.propose(&s.signers[0], &s.transfer());
+
+    s.env.set_auths(&[]);
+    s.client.approve(&s.signers[1], &id);
+}
+
+#[test]
+fn proposals_expire_after_timeout() {
+    let s = setup();
+    let id = s.client.propose(&s.signers[0], &s.transfer());
+
+    s.env.ledger().with_mut(|l| l.timestamp += 24 * HOUR - 1);
+    assert_eq!(s.client.status(&id), Status::Pending);
+
+    s.env.ledger().with_mut(|l| l.timestamp += 1);
+    assert_eq!(s.client.status(&id), Status::Expired);
+    assert_eq!(
+        s.client.try_approve(&s.signers[1], &id),
+        Err(Ok(Error::Expired))
+    );
+    assert_eq!(s.token.balance(&s.recipient), 0);
+}
+
+#[test]
+fn rejects_non_positive_amount() {
+    let s = setup();
+    let mut transfer = s.transfer();
+    transfer.amount = 0;
+
+    assert_eq!(
+        s.client.try_propose(&s.signers[0], &transfer),
+        Err(Ok(Error::InvalidAmount))
+    );
+}
+
+#[test]
+fn calls_keep_the_instance_alive() {
+    let s = setup();
+    let ttl = || {
+        s.env
+            .as_contract(&s.client.address, || s.env.storage().instance().get_ttl())
+    };
+    assert_eq!(ttl(), INSTANCE_TTL_EXTEND_TO);
+
+    // Within a week of expiring, a proposal extends it again.
+    let elapsed = INSTANCE_TTL_EXTEND_TO - INSTANCE_TTL_THRESHOLD + 1;
+    s.env.ledger().with_mut(|l| l.sequence_number += elapsed);
+    assert_eq!(ttl(), INSTANCE_TTL_THRESHOLD - 1);
+    s.client.propose(&s.signers[0], &s.transfer());
+    assert_eq!(ttl(), INSTANCE_TTL_EXTEND_TO);
+}
+
+#[test]
+fn unknown_proposal() {
+    let s = setup();
+
+    assert_eq!(s.client.proposal(&9), None);
+    assert_eq!(s.client.try_status(&9), Err(Ok(Error::NotFound)));
+    assert_eq!(
+        s.client.try_approve(&s.signers[1], &9),
+        Err(Ok(Error::NotFound))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1)")]
+fn constructor_rejects_threshold_above_signers() {
+    let env = Env::default();
+    let signer = Address::generate(&env);
+    env.register(MultisigVault, (vec![&env, signer], 2_u32, HOUR));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn constructor_rejects_duplicate_signers() {
+    let env = Env::default();
+    let signer = Address::generate(&env);
+    env.register(
+        MultisigVault,
+        (vec![&env, signer.clone(), signer], 1_u32, HOUR),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #3)")]
+fn constructor_rejects_timeouts_past_the_maximum() {
+    let env = Env::default();
+    let signer = Address::generate(&env);
+    env.register(MultisigVault, (vec![&env, signer], 1_u32, MAX_TIMEOUT + 1));
+}
@@ -0,0 +1,182 @@
+//! StellrFlow — Telegram Chat Registry
+//!
+//! Binds a Telegram chat ID (the one `/register` and
+//! `POST /api/session/register` hand out) to a Stellar address, so
+//! workflows and other contracts can resolve "who is this chat" on-chain.
+//!
+//! A binding needs two signatures:
+//!   • the bot admin, attesting that the chat ID really belongs to the
+//!     Telegram user who asked for it
+//!   • the Stellar address being bound, proving the user controls it
+//!
+//! Either the bound address or the admin can remove a binding again.
+#![no_std]
+
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Env,
+};
+
+/// Bindings are looked up on every workflow run, so keep them alive for
+/// roughly 30 days (5s ledgers) and bump them whenever they are touched.
+const DAY_IN_LEDGERS: u32 = 17_280;
+const BINDING_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const BINDING_TTL_EXTEND_TO: u32 = 30 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_THRESHOLD: u32 = 7 * DAY_IN_LEDGERS;
+const INSTANCE_TTL_EXTEND_TO: u32 = 60 * DAY_IN_LEDGERS;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// The chat ID is already bound to an address.
+    AlreadyRegistered = 1,
+    /// The chat ID has no binding.
+    NotRegistered = 2,
+    /// Telegram never hands out chat ID 0.
+    InvalidChatId = 3,
+    /// The caller is neither the bound address nor the admin.
+    Unauthorized = 4,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Chat(i64),
+}
+
+/// A chat/address binding as stored on-chain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Binding {
+    pub address: Address,
+    /// Ledger timestamp (unix seconds) at which the binding was created.
+    pub registered_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Registered {
+    #[topic]
+    pub chat_id: i64,
+    pub address: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unregistered {
+    #[topic]
+    pub chat_id: i64,
+    pub address: Address,
+}
+
+#[contract]
+pub struct TelegramBotRegistry;
+
+#[contractimpl]
+impl TelegramBotRegistry {
+    /// Deploy-time setup: `admin` is the bot's own Stellar account.
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        extend_instance(&env);
+    }
+
+    /// Return the bot admin address.
+    pub fn admin(env: Env) -> Address {
+        read_admin(&env)
+    }
+
+    /// Bind `chat_id` to `address`.
+    ///
+    /// Fails with `AlreadyRegistered` if the chat is bound to any address
+    /// (including this one) — call `unregister` first to move a chat.
+    pub fn register(env: Env, chat_id: i64, address: Address) -> Result<Binding, Error> {
+        if chat_id == 0 {
+            return Err(Error::InvalidChatId);
+        }
+        read_admin(&env).require_auth();
+        address.require_auth();
+        extend_instance(&env);
+
+        let key = DataKey::Chat(chat_id);
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyRegistered);
+        }
+
+        let binding = Binding {
+            address: address.clone(),
+            registered_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &binding);
+        extend_binding(&env, &key);
+
+        Registered { chat_id, address }.publish(&env);
+        Ok(binding)
+    }
+
+    /// Remove the binding for `chat_id`.
+    ///
+    /// `caller` must be either the bound address or the bot admin.
+    pub fn unregister(env: Env, chat_id: i64, caller: Address) -> Result<(), Error> {
+        let key = DataKey::Chat(chat_id);
+        let binding: Binding = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NotRegistered)?;
+
+        if caller != binding.address && caller != read_admin(&env) {
+            return Err(Error::Unauthorized);
+        }
+        caller.require_auth();
+        extend_instance(&env);
+
+        env.storage().persistent().remove(&key);
+
+        Unregistered {
+            chat_id,
+            address: binding.address,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Resolve `chat_id` to its bound address, if any.
+    pub fn lookup(env: Env, chat_id: i64) -> Option<Address> {
+        Self::binding(env, chat_id).map(|b| b.address)
+    }
+
+    /// Full binding record for `chat_id`, if any.
+    pub fn binding(env: Env, chat_id: i64) -> Option<Binding> {
+        let key = DataKey::Chat(chat_id);
+        let binding: Option<Binding> = env.storage().persistent().get(&key);
+        if binding.is_some() {
+            extend_binding(&env, &key);
+        }
+        binding
+    }
+}
+
+/// Keep the instance, which holds `DataKey::Admin`, from being archived
+/// while the registry is in use.
+fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL_EXTEND_TO);
+}
+
+fn read_admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .expect("admin is set by the constructor")
+}
+
+fn extend_binding(env: &Env, key: &DataKey) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, BINDING_TTL_THRESHOLD, BINDING_TTL_EXTEND_TO);
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,190 @@
+#![cfg(test)]
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{
+        storage::Instance as _, Address as _, AuthorizedFunction, AuthorizedInvocation,
+        Events as _, Ledger,
+    },
+    Event, IntoVal, Symbol,
+};
+
+const CHAT_ID: i64 = 123_456_789;
+
+fn setup() -> (Env, Address, TelegramBotRegistryClient<'static>) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let contract_id = env.register(TelegramBotRegistry, (&admin,));
+    let client = TelegramBotRegistryClient::new(&env, &contract_id);
+    (env, admin, client)
+}
+
+#[test]
+fn register_and_lookup() {
+    let (env, admin, client) = setup();
+    env.ledger().set_timestamp(1_700_000_000);
+    let user = Address::generate(&env);
+
+    let binding = client.register(&CHAT_ID, &user);
+    assert_eq!(binding.address, user);
+    assert_eq!(binding.registered_at, 1_700_000_000);
+
+    // Both the bot admin and the user must have signed.
+    let auths = env.auths();
+    assert_eq!(auths.len(), 2);
+    let expected = AuthorizedInvocation {
+        function: AuthorizedFunction::Contract((
+            client.address.clone(),
+            Symbol::new(&env, "register"),
+            (CHAT_ID, user.clone()).into_val(&env),
+        )),
+        sub_invocations: std::vec![],
+    };
+    assert_eq!(auths[0], (admin, expected.clone()));
+    assert_eq!(auths[1], (user.clone(), expected));
+
+    assert_eq!(client.lookup(&CHAT_ID), Some(user.clone()));
+    assert_eq!(client.binding(&CHAT_ID), Some(binding));
+    assert_eq!(client.lookup(&42), None);
+}
+
+#[test]
+fn register_emits_event() {
+    let (env, _admin, client) = setup();
+    let user = Address::generate(&env);
+
+    client.register(&CHAT_ID, &user);
+
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Registered {
+            chat_id: CHAT_ID,
+            address: user,
+        }
+        .to_xdr(&env, &client.address)]
+    );
+}
+
+#[test]
+fn group_chats_have_negative_ids() {
+    let (env, _admin, client) = setup();
+    let user = Address::generate(&env);
+
+    client.register(&-1_001_234_567_890, &user);
+    assert_eq!(client.lookup(&-1_001_234_567_890), Some(user));
+}
+
+#[test]
+fn register_rejects_duplicate_chat() {
+    let (env, _admin, client) = setup();
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+
+    client.register(&CHAT_ID, &first);
+
+    assert_eq!(
+        client.try_register(&CHAT_ID, &second),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+    assert_eq!(
+        client.try_register(&CHAT_ID, &first),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+    assert_eq!(client.lookup(&CHAT_ID), Some(first));
+}
+
+#[test]
+fn register_rejects_zero_chat_id() {
+    let (env, _admin, client) = setup();
+    let user = Address::generate(&env);
+
+    assert_eq!(
+        client.try_register(&0, &user),
+        Err(Ok(Error::InvalidChatId))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn register_requires_auth() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(TelegramBotRegistry, (&admin,));
+    let client = TelegramBotRegistryClient::new(&env, &contract_id);
+
+    client.register(&CHAT_ID, &Address::generate(&env));
+}
+
+#[test]
+fn unregister_by_owner() {
+    let (env, _admin, client) = setup();
+    let user = Address::generate(&env);
+    client.register(&CHAT_ID, &user);
+
+    client.unregister(&CHAT_ID, &user);
+
+    assert_eq!(
+        env.events().all().filter_by_contract(&client.address),
+        [Unregistered {
+            chat_id: CHAT_ID,
+            address: user.clone(),
+        }
+        .to_xdr(&env, &client.address)]
+    );
+    assert_eq!(client.lookup(&CHAT_ID), None);
+
+    // The chat is free to be bound again.
+    let other = Address::generate(&env);
+    client.register(&CHAT_ID, &other);
+    assert_eq!(client.lookup(&CHAT_ID), Some(other));
+}
+
+#[test]
+fn unregister_by_admin() {
+    let (env, admin, client) = setup();
+    let user = Address::generate(&env);
+    client.register(&CHAT_ID, &user);
+
+    client.unregister(&CHAT_ID, &admin);
+    assert_eq!(client.lookup(&CHAT_ID), None);
+}
+
+#[test]
+fn unregister_rejects_strangers() {
+    let (env, _admin, client) = setup();
+    let user = Address::generate(&env);
+    client.register(&CHAT_ID, &user);
+
+    assert_eq!(
+        client.try_unregister(&CHAT_ID, &Address::generate(&env)),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(client.lookup(&CHAT_ID), Some(user));
+}
+
+#[test]
+fn unregister_unknown_chat() {
+    let (_env, admin, client) = setup();
+
+    assert_eq!(
+        client.try_unregister(&CHAT_ID, &admin),
+        Err(Ok(Error::NotRegistered))
+    );
+}
+
+#[test]
+fn calls_keep_the_instance_alive() {
+    let (env, _admin, client) = setup();
+    let ttl = || env.as_contract(&client.address, || env.storage().instance().get_ttl());
+    assert_eq!(ttl(), INSTANCE_TTL_EXTEND_TO);
+
+    // Within a week of expiring, a registration extends it again.
+    let elapsed = INSTANCE_TTL_EXTEND_TO - INSTANCE_TTL_THRESHOLD + 1;
+    env.ledger().with_mut(|l| l.sequence_number += elapsed);
+    assert_eq!(ttl(), INSTANCE_TTL_THRESHOLD - 1);
+    client.register(&CHAT_ID, &Address::generate(&env));
+    assert_eq!(ttl(), INSTANCE_TTL_EXTEND_TO);
+}
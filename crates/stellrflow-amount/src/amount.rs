@@ -0,0 +1,143 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::decimal;
+use crate::error::AmountError;
+
+/// Decimal places in an XLM (or any Stellar asset) amount.
+pub const DECIMALS: u32 = 7;
+
+/// Stroops in one XLM.
+pub const STROOPS_PER_XLM: i64 = 10_000_000;
+
+/// An amount of XLM, or of any Stellar asset, held exactly as stroops: the
+/// 64-bit integer of ten-millionths that transactions carry.
+///
+/// It reads `"10"`, `"2.5"` and `toFixed(7)`'s `"2.5000000"` alike, and
+/// writes the shortest form, `2.5`; [`Amount::to_fixed`] writes the seven
+/// decimals Horizon uses. Arithmetic is checked: an overflow is `None`
+/// rather than a wrapped or rounded amount.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    /// One XLM.
+    pub const ONE: Amount = Amount(STROOPS_PER_XLM);
+    /// 922,337,203,685.4775807, the most an account or trustline can hold.
+    pub const MAX: Amount = Amount(i64::MAX);
+
+    pub const fn from_stroops(stroops: i64) -> Self {
+        Amount(stroops)
+    }
+
+    pub const fn stroops(self) -> i64 {
+        self.0
+    }
+
+    /// A whole number of XLM.
+    pub fn from_xlm(xlm: i64) -> Option<Self> {
+        xlm.checked_mul(STROOPS_PER_XLM).map(Amount)
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    /// The amount `times` times over, e.g. every payment of a schedule.
+    pub fn checked_mul(self, times: i64) -> Option<Amount> {
+        self.0.checked_mul(times).map(Amount)
+    }
+
+    /// With all seven decimals, as `toFixed(7)` and Horizon write it.
+    pub fn to_fixed(self) -> String {
+        decimal::format(i128::from(self.0), DECIMALS, true)
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&decimal::format(i128::from(self.0), DECIMALS, false))
+    }
+}
+
+impl FromStr for Amount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let stroops = decimal::parse(s, DECIMALS)?;
+        i64::try_from(stroops)
+            .map(Amount)
+            .map_err(|_| AmountError::Overflow)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Also reads a JSON number, as the bot sends them: it must be written
+/// with at most seven decimals, so `0.1 + 0.2` is refused rather than
+/// rounded.
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(Parsed::<Amount>::new("an XLM amount"))
+    }
+}
+
+/// Deserializes a [`FromStr`] value from a string or a JSON number.
+pub(crate) struct Parsed<T> {
+    expecting: &'static str,
+    parsed: std::marker::PhantomData<T>,
+}
+
+impl<T> Parsed<T> {
+    pub(crate) fn new(expecting: &'static str) -> Self {
+        Parsed {
+            expecting,
+            parsed: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> Visitor<'_> for Parsed<T>
+where
+    T: FromStr<Err = AmountError>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    /// `f64`'s `Display` is the shortest text that reads back as the same
+    /// number, and never uses an exponent.
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<T, E> {
+        self.visit_str(&v.to_string())
+    }
+}
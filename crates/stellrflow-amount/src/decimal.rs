@@ -0,0 +1,68 @@
+//! Fixed-point decimals as integers of their smallest unit.
+
+use crate::error::AmountError;
+
+/// `10^exp`, for the scales used here (at most 18 decimals).
+pub(crate) fn pow10(exp: u32) -> i128 {
+    10i128.pow(exp)
+}
+
+/// Reads `-12.5` as `-125 * 10^(decimals - 1)`. Trailing zeros past
+/// `decimals` are allowed, since they change nothing; other digits are not.
+pub(crate) fn parse(text: &str, decimals: u32) -> Result<i128, AmountError> {
+    let invalid = || AmountError::Invalid(text.to_string());
+    let trimmed = text.trim();
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if int.is_empty()
+        || !int.bytes().all(|b| b.is_ascii_digit())
+        || !frac.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let frac = frac.trim_end_matches('0');
+    if frac.len() > decimals as usize {
+        return Err(AmountError::TooPrecise {
+            amount: trimmed.to_string(),
+            decimals,
+        });
+    }
+
+    let mut value: i128 = 0;
+    for digit in int.bytes().chain(frac.bytes()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(i128::from(digit - b'0')))
+            .ok_or(AmountError::Overflow)?;
+    }
+    let value = value
+        .checked_mul(pow10(decimals - frac.len() as u32))
+        .ok_or(AmountError::Overflow)?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Writes `value` smallest units with `decimals` places, dropping
+/// trailing zeros unless `fixed`.
+pub(crate) fn format(value: i128, decimals: u32, fixed: bool) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let scale = pow10(decimals).unsigned_abs();
+    let magnitude = value.unsigned_abs();
+    let int = magnitude / scale;
+    if decimals == 0 {
+        return format!("{sign}{int}");
+    }
+    let frac = format!("{:0width$}", magnitude % scale, width = decimals as usize);
+    let frac = if fixed {
+        frac.as_str()
+    } else {
+        frac.trim_end_matches('0')
+    };
+    if frac.is_empty() {
+        format!("{sign}{int}")
+    } else {
+        format!("{sign}{int}.{frac}")
+    }
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+/// Why an amount or rate could not be read or computed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AmountError {
+    /// Not a plain decimal: signs other than a leading `-`, exponents,
+    /// separators and `NaN` are all rejected.
+    #[error("invalid amount `{0}`")]
+    Invalid(String),
+    /// More decimal places than the unit has: 7 for XLM, 2 for fiat.
+    #[error("`{amount}` has more than {decimals} decimal places")]
+    TooPrecise { amount: String, decimals: u32 },
+    #[error("amount is out of range")]
+    Overflow,
+    #[error("invalid rate `{0}`: use a positive decimal or a fraction such as `3/25`")]
+    InvalidRate(String),
+}
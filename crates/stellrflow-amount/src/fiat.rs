@@ -0,0 +1,227 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::amount::{Parsed, DECIMALS};
+use crate::decimal::{self, pow10};
+use crate::error::AmountError;
+use crate::rounding::Rounding;
+use crate::Amount;
+
+/// Decimal places in the anchor's fiat amounts, as its `roundFiat`.
+pub const FIAT_DECIMALS: u32 = 2;
+
+/// The most decimal places a fiat amount can have.
+const MAX_DECIMALS: u32 = 18;
+
+/// An amount of fiat money in minor units (cents) at a fixed number of
+/// decimal places, e.g. `12.30` USD is 1230 at 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fiat {
+    minor: i64,
+    decimals: u32,
+}
+
+impl Fiat {
+    /// `minor` units at `decimals` places (at most 18).
+    pub fn new(minor: i64, decimals: u32) -> Self {
+        assert!(decimals <= MAX_DECIMALS, "at most 18 decimal places");
+        Fiat { minor, decimals }
+    }
+
+    /// Reads `text` at exactly `decimals` places: `"12.3"` at 2 is `12.30`,
+    /// and `"12.345"` at 2 is refused.
+    pub fn parse(text: &str, decimals: u32) -> Result<Self, AmountError> {
+        let decimals = decimals.min(MAX_DECIMALS);
+        let minor = decimal::parse(text, decimals)?;
+        let minor = i64::try_from(minor).map_err(|_| AmountError::Overflow)?;
+        Ok(Fiat::new(minor, decimals))
+    }
+
+    pub fn minor(self) -> i64 {
+        self.minor
+    }
+
+    pub fn decimals(self) -> u32 {
+        self.decimals
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.minor > 0
+    }
+
+    /// The same amount at `decimals` places, rounded if that drops digits.
+    pub fn rescale(self, decimals: u32, rounding: Rounding) -> Result<Self, AmountError> {
+        let decimals = decimals.min(MAX_DECIMALS);
+        let minor = i128::from(self.minor);
+        let minor = if decimals >= self.decimals {
+            minor
+                .checked_mul(pow10(decimals - self.decimals))
+                .ok_or(AmountError::Overflow)?
+        } else {
+            rounding.divide(minor, pow10(self.decimals - decimals))
+        };
+        let minor = i64::try_from(minor).map_err(|_| AmountError::Overflow)?;
+        Ok(Fiat::new(minor, decimals))
+    }
+}
+
+impl fmt::Display for Fiat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&decimal::format(
+            i128::from(self.minor),
+            self.decimals,
+            true,
+        ))
+    }
+}
+
+/// Keeps as many decimal places as `s` has.
+impl FromStr for Fiat {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let places = s
+            .trim()
+            .split_once('.')
+            .map_or(0, |(_, frac)| frac.len() as u32);
+        Fiat::parse(s, places)
+    }
+}
+
+impl Serialize for Fiat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Fiat {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(Parsed::<Fiat>::new("a fiat amount"))
+    }
+}
+
+/// How much XLM one unit of a fiat currency buys, as an exact fraction:
+/// the anchor's INR rate of 0.12 is 3/25, so conversions never go through
+/// a float.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rate {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Rate {
+    /// `numerator / denominator` XLM per unit, in lowest terms.
+    pub fn new(numerator: u64, denominator: u64) -> Result<Self, AmountError> {
+        if numerator == 0 || denominator == 0 {
+            return Err(AmountError::InvalidRate(format!(
+                "{numerator}/{denominator}"
+            )));
+        }
+        let divisor = gcd(numerator, denominator);
+        Ok(Rate {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    pub fn numerator(self) -> u64 {
+        self.numerator
+    }
+
+    pub fn denominator(self) -> u64 {
+        self.denominator
+    }
+
+    /// The XLM that `fiat` buys.
+    pub fn to_xlm(self, fiat: Fiat, rounding: Rounding) -> Result<Amount, AmountError> {
+        // minor × n × 10^7 / (d × 10^decimals)
+        let numerator = i128::from(fiat.minor)
+            .checked_mul(i128::from(self.numerator))
+            .and_then(|v| v.checked_mul(pow10(DECIMALS)))
+            .ok_or(AmountError::Overflow)?;
+        let denominator = i128::from(self.denominator)
+            .checked_mul(pow10(fiat.decimals))
+            .ok_or(AmountError::Overflow)?;
+        let stroops = rounding.divide(numerator, denominator);
+        i64::try_from(stroops)
+            .map(Amount::from_stroops)
+            .map_err(|_| AmountError::Overflow)
+    }
+
+    /// What `xlm` is worth in the currency, at `decimals` places.
+    pub fn to_fiat(
+        self,
+        xlm: Amount,
+        decimals: u32,
+        rounding: Rounding,
+    ) -> Result<Fiat, AmountError> {
+        // stroops × d × 10^decimals / (n × 10^7)
+        let decimals = decimals.min(MAX_DECIMALS);
+        let numerator = i128::from(xlm.stroops())
+            .checked_mul(i128::from(self.denominator))
+            .and_then(|v| v.checked_mul(pow10(decimals)))
+            .ok_or(AmountError::Overflow)?;
+        let denominator = i128::from(self.numerator) * pow10(DECIMALS);
+        let minor = rounding.divide(numerator, denominator);
+        let minor = i64::try_from(minor).map_err(|_| AmountError::Overflow)?;
+        Ok(Fiat::new(minor, decimals))
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            d => write!(f, "{}/{d}", self.numerator),
+        }
+    }
+}
+
+/// Reads a decimal (`10`, `0.12`) or a fraction (`3/25`).
+impl FromStr for Rate {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AmountError::InvalidRate(s.trim().to_string());
+        let whole = |text: &str| {
+            let text = text.trim();
+            if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            text.parse::<u64>().map_err(|_| invalid())
+        };
+        if let Some((numerator, denominator)) = s.split_once('/') {
+            return Rate::new(whole(numerator)?, whole(denominator)?).map_err(|_| invalid());
+        }
+        let fraction = s.trim().split_once('.').map_or("", |(_, frac)| frac);
+        let places = fraction.trim_end_matches('0').len() as u32;
+        if places > MAX_DECIMALS || s.trim().starts_with('-') {
+            return Err(invalid());
+        }
+        let scaled = decimal::parse(s, places).map_err(|_| invalid())?;
+        let numerator = u64::try_from(scaled).map_err(|_| invalid())?;
+        let denominator = u64::try_from(pow10(places)).map_err(|_| invalid())?;
+        Rate::new(numerator, denominator).map_err(|_| invalid())
+    }
+}
+
+impl Serialize for Rate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rate {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(Parsed::<Rate>::new("an exchange rate"))
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
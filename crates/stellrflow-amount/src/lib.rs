@@ -0,0 +1,27 @@
+//! Exact money amounts for StellrFlow.
+//!
+//! The bot handles money as JavaScript numbers: `parseFloat` on the way
+//! in, `toFixed(7)` on the way out, and `roundXLM`/`roundFiat` after the
+//! mock anchor multiplies floats by its rates. Here each kind of amount is
+//! an integer of its smallest unit, so nothing drifts:
+//!
+//! - [`Amount`]: XLM (or any Stellar asset) as stroops, the `i64` that
+//!   transactions carry. Parsing refuses an eighth decimal instead of
+//!   rounding it away, and arithmetic is checked.
+//! - [`Fiat`]: a fiat amount in minor units at a fixed number of decimals.
+//! - [`Rate`]: XLM per unit of a currency as an exact fraction, converting
+//!   either way with an explicit [`Rounding`].
+//!
+//! All three read the strings the bot and Horizon write (`"10"`, `"2.5"`,
+//! `"2.5000000"`) and JSON numbers, and serialize as strings.
+
+mod amount;
+mod decimal;
+mod error;
+mod fiat;
+mod rounding;
+
+pub use amount::{Amount, DECIMALS, STROOPS_PER_XLM};
+pub use error::AmountError;
+pub use fiat::{Fiat, Rate, FIAT_DECIMALS};
+pub use rounding::Rounding;
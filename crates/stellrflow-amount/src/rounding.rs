@@ -0,0 +1,39 @@
+/// How a conversion that does not come out exact is rounded. There is no
+/// default: each caller says whether it favours the payer or the payee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// Toward zero: never pays out more than the exact amount.
+    Down,
+    /// Away from zero: never collects less than the exact amount.
+    Up,
+    /// To the nearest, halves away from zero, as `Math.round` does for the
+    /// bot's positive amounts.
+    HalfUp,
+    /// To the nearest, halves to the even neighbour (banker's rounding).
+    HalfEven,
+}
+
+impl Rounding {
+    /// `numerator / denominator` rounded to an integer. `denominator` must
+    /// be positive.
+    pub(crate) fn divide(self, numerator: i128, denominator: i128) -> i128 {
+        debug_assert!(denominator > 0);
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        if remainder == 0 {
+            return quotient;
+        }
+        let away = quotient + numerator.signum();
+        // Compare the remainder with half the denominator without dividing.
+        let twice = remainder.unsigned_abs() * 2;
+        let denominator = denominator.unsigned_abs();
+        match self {
+            Rounding::Down => quotient,
+            Rounding::Up => away,
+            Rounding::HalfUp if twice >= denominator => away,
+            Rounding::HalfEven if twice > denominator => away,
+            Rounding::HalfEven if twice == denominator && quotient % 2 != 0 => away,
+            Rounding::HalfUp | Rounding::HalfEven => quotient,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use proptest::prelude::*;
+use serde_json::json;
+use stellrflow_amount::{Amount, AmountError, Fiat, Rate, Rounding, FIAT_DECIMALS};
+
+fn xlm(text: &str) -> Amount {
+    text.parse().unwrap()
+}
+
+fn fiat(text: &str) -> Fiat {
+    Fiat::parse(text, FIAT_DECIMALS).unwrap()
+}
+
+#[test]
+fn amounts_parse_exactly_or_not_at_all() {
+    assert_eq!(xlm("10"), Amount::from_xlm(10).unwrap());
+    assert_eq!(xlm("2.5000000"), xlm("2.5"));
+    assert_eq!(xlm(" 0.0000001 ").stroops(), 1);
+    // Zeros past the seventh decimal change nothing.
+    assert_eq!(xlm("1.000000000"), Amount::ONE);
+    assert_eq!(xlm("922337203685.4775807"), Amount::MAX);
+
+    assert_eq!(xlm("2.5").to_string(), "2.5");
+    assert_eq!(xlm("2.5").to_fixed(), "2.5000000");
+    assert_eq!(xlm("-0.05").to_string(), "-0.05");
+    assert_eq!(Amount::ZERO.to_fixed(), "0.0000000");
+
+    assert_eq!(
+        "0.00000001".parse::<Amount>(),
+        Err(AmountError::TooPrecise {
+            amount: "0.00000001".into(),
+            decimals: 7
+        })
+    );
+    assert_eq!(
+        "0.00000001".parse::<Amount>().unwrap_err().to_string(),
+        "`0.00000001` has more than 7 decimal places"
+    );
+    for text in [
+        "", "1e3", "+1", ".5", "1,000", "NaN", "0x10", "1.2.3", "--1",
+    ] {
+        assert_eq!(
+            text.parse::<Amount>(),
+            Err(AmountError::Invalid(text.into())),
+            "{text:?}"
+        );
+    }
+    assert_eq!(
+        "922337203685.4775808".parse::<Amount>(),
+        Err(AmountError::Overflow)
+    );
+}
+
+#[test]
+fn arithmetic_is_checked() {
+    let fee = Amount::from_stroops(100);
+    assert_eq!(xlm("5").checked_add(fee), Some(xlm("5.00001")));
+    assert_eq!(xlm("5").checked_sub(xlm("7.5")), Some(xlm("-2.5")));
+    assert_eq!(xlm("2.5").checked_mul(12), Some(xlm("30")));
+    assert_eq!(Amount::MAX.checked_add(fee), None);
+    assert_eq!(Amount::MAX.checked_mul(2), None);
+    assert_eq!(Amount::from_xlm(i64::MAX), None);
+    assert!(!Amount::ZERO.is_positive());
+    assert!(xlm("5") > xlm("4.9999999"));
+}
+
+#[test]
+fn serde_reads_the_bots_strings_and_numbers() {
+    let read = |value| serde_json::from_value::<Amount>(value);
+    assert_eq!(read(json!("2.5000000")).unwrap(), xlm("2.5"));
+    assert_eq!(read(json!(2.5)).unwrap(), xlm("2.5"));
+    assert_eq!(read(json!(10)).unwrap(), xlm("10"));
+    assert_eq!(read(json!(0.0000001)).unwrap().stroops(), 1);
+    // 0.30000000000000004: refused rather than rounded.
+    let err = read(json!(0.1 + 0.2)).unwrap_err();
+    assert!(
+        err.to_string().contains("more than 7 decimal places"),
+        "{err}"
+    );
+    assert!(read(json!(true)).is_err());
+    assert_eq!(serde_json::to_value(xlm("2.5")).unwrap(), json!("2.5"));
+
+    let payout: Fiat = serde_json::from_value(json!(0.64)).unwrap();
+    assert_eq!(payout, fiat("0.64"));
+    assert_eq!(serde_json::to_value(fiat("12.3")).unwrap(), json!("12.30"));
+    let rate: Rate = serde_json::from_value(json!(0.12)).unwrap();
+    assert_eq!(serde_json::to_value(rate).unwrap(), json!("3/25"));
+}
+
+#[test]
+fn rates_convert_like_the_mock_anchor_without_floats() {
+    // mockAnchor's RATES: XLM per unit of fiat.
+    let usd: Rate = "10".parse().unwrap();
+    let eur: Rate = "11".parse().unwrap();
+    let inr: Rate = "0.12".parse().unwrap();
+    let gbp: Rate = "12.5".parse().unwrap();
+    assert_eq!(inr, Rate::new(3, 25).unwrap());
+    assert_eq!("3/25".parse::<Rate>().unwrap(), inr);
+    assert_eq!((gbp.numerator(), gbp.denominator()), (25, 2));
+    assert_eq!(inr.to_string(), "3/25");
+    assert_eq!(usd.to_string(), "10");
+
+    // simulateFiatDeposit: `roundXLM(fiatAmount * rate)`.
+    let deposit = |rate: Rate, amount| rate.to_xlm(fiat(amount), Rounding::HalfUp).unwrap();
+    assert_eq!(deposit(usd, "100"), xlm("1000"));
+    assert_eq!(deposit(inr, "100"), xlm("12"));
+    assert_eq!(deposit(inr, "0.01"), xlm("0.0012"));
+    assert_eq!(deposit(gbp, "19.99"), xlm("249.875"));
+
+    // simulateFiatWithdrawal: `roundFiat(xlmAmount / rate)`.
+    let payout = |rounding| eur.to_fiat(xlm("7"), FIAT_DECIMALS, rounding).unwrap();
+    assert_eq!(payout(Rounding::HalfUp), fiat("0.64"));
+    assert_eq!(payout(Rounding::Down), fiat("0.63"));
+    assert_eq!(payout(Rounding::HalfUp).to_string(), "0.64");
+    // The quoted fiat-per-XLM rate, `roundFiat(1 / rate)`.
+    let per_xlm = eur.to_fiat(Amount::ONE, FIAT_DECIMALS, Rounding::HalfUp);
+    assert_eq!(per_xlm.unwrap().to_string(), "0.09");
+    // An eighth decimal of XLM is rounded explicitly, never silently.
+    let third = Rate::new(1, 3).unwrap();
+    assert_eq!(
+        third.to_xlm(fiat("1"), Rounding::Down).unwrap().to_fixed(),
+        "0.3333333"
+    );
+    assert_eq!(
+        third.to_xlm(fiat("2"), Rounding::Up).unwrap().to_fixed(),
+        "0.6666667"
+    );
+
+    for text in ["0", "-1", "1/0", "0/5", "abc", "1.5/2", "", "1e2"] {
+        assert_eq!(
+            text.parse::<Rate>(),
+            Err(AmountError::InvalidRate(text.into())),
+            "{text:?}"
+        );
+    }
+    assert_eq!(
+        Rate::new(1, 1)
+            .unwrap()
+            .to_xlm(Fiat::new(i64::MAX, 0), Rounding::Down),
+        Err(AmountError::Overflow)
+    );
+}
+
+#[test]
+fn rounding_modes_and_fiat_scales() {
+    let cases = [
+        ("0.125", Rounding::Down, "0.12"),
+        ("0.125", Rounding::Up, "0.13"),
+        ("0.125", Rounding::HalfUp, "0.13"),
+        ("0.125", Rounding::HalfEven, "0.12"),
+        ("0.135", Rounding::HalfEven, "0.14"),
+        ("0.1251", Rounding::HalfEven, "0.13"),
+        ("-0.125", Rounding::Down, "-0.12"),
+        ("-0.125", Rounding::Up, "-0.13"),
+        ("-0.125", Rounding::HalfUp, "-0.13"),
+    ];
+    for (amount, rounding, expected) in cases {
+        let scaled = amount
+            .parse::<Fiat>()
+            .unwrap()
+            .rescale(2, rounding)
+            .unwrap();
+        assert_eq!(scaled.to_string(), expected, "{amount} {rounding:?}");
+    }
+
+    assert_eq!(fiat("12.3").minor(), 1230);
+    assert_eq!(fiat("12").to_string(), "12.00");
+    assert_eq!("12.30".parse::<Fiat>().unwrap().decimals(), 2);
+    assert_eq!(
+        fiat("5").rescale(0, Rounding::Down).unwrap().to_string(),
+        "5"
+    );
+    assert_eq!(
+        Fiat::parse("12.345", FIAT_DECIMALS),
+        Err(AmountError::TooPrecise {
+            amount: "12.345".into(),
+            decimals: 2
+        })
+    );
+}
+
+proptest! {
+    #[test]
+    fn amounts_read_back_from_their_text(stroops in any::<i64>()) {
+        let amount = Amount::from_stroops(stroops);
+        prop_assert_eq!(amount.to_string().parse::<Amount>(), Ok(amount));
+        prop_assert_eq!(amount.to_fixed().parse::<Amount>(), Ok(amount));
+        let json = serde_json::to_value(amount).unwrap();
+        prop_assert_eq!(serde_json::from_value::<Amount>(json).unwrap(), amount);
+    }
+
+    #[test]
+    fn rounding_down_never_pays_out_more(
+        cents in 0i64..1_000_000_000,
+        numerator in 1u64..1_000_000,
+        denominator in 1u64..1_000_000,
+    ) {
+        let rate = Rate::new(numerator, denominator).unwrap();
+        let paid = Fiat::new(cents, FIAT_DECIMALS);
+        let credited = rate.to_xlm(paid, Rounding::Down).unwrap();
+        let back = rate.to_fiat(credited, FIAT_DECIMALS, Rounding::Down).unwrap();
+        prop_assert!(back.minor() <= cents);
+        let up = rate.to_xlm(paid, Rounding::Up).unwrap();
+        prop_assert!(up >= credited);
+        prop_assert!(up.stroops() - credited.stroops() <= 1);
+    }
+}
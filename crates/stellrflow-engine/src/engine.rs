@@ -0,0 +1,295 @@
+//! Graph traversal.
+//!
+//! Unlike the recursive `executeNode` in the browser, the engine walks the
+//! graph once in topological order, so a node with several parents runs
+//! once with their merged outputs instead of once per incoming edge.
+//!
+//! Payload rules follow the frontend: a node receives the payload its
+//! parents received, overlaid with their outputs; when parents disagree on a
+//! key the later edge wins. A node whose parents all failed (or never ran)
+//! is not run either.
+//!
+//! Nodes with named outputs (`condition`) pick one handle per run, and only
+//! edges drawn from that handle carry the payload on. A node that none of
+//! its parents fed, although they all finished without error, was routed
+//! around and ends up [`NodeStatus::Skipped`]; below a failure nodes stay
+//! `Pending`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::error::EngineError;
+use crate::executor::{ExecutorRegistry, NodeContext};
+use crate::graph::{Payload, Workflow};
+use crate::validate::{self, Diagnostic, DiagnosticCode, NodeRules, Validation};
+
+/// Mirrors the values of `nodeExecutionState` in the frontend store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeStatus {
+    Pending,
+    Running,
+    Success,
+    Error,
+    /// Not run because the branch leading to it was not taken.
+    Skipped,
+}
+
+/// Emitted every time a node changes status.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionEvent {
+    pub node_id: String,
+    pub status: NodeStatus,
+    /// Set on `Success`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Set on `Error`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Receives [`ExecutionEvent`]s while a run is in progress.
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: ExecutionEvent);
+}
+
+/// Discards events.
+impl EventSink for () {
+    fn emit(&self, _event: ExecutionEvent) {}
+}
+
+/// Forwards events to a channel; a closed receiver is ignored.
+impl EventSink for UnboundedSender<ExecutionEvent> {
+    fn emit(&self, event: ExecutionEvent) {
+        let _ = self.send(event);
+    }
+}
+
+/// Final state of a run, shaped like the frontend's `nodeExecutionState`
+/// and `nodeResults`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub node_execution_state: BTreeMap<String, NodeStatus>,
+    pub node_results: BTreeMap<String, Value>,
+    pub node_errors: BTreeMap<String, String>,
+}
+
+impl RunReport {
+    /// `true` if no node failed.
+    pub fn is_success(&self) -> bool {
+        self.node_errors.is_empty()
+    }
+
+    pub fn status(&self, node_id: &str) -> Option<NodeStatus> {
+        self.node_execution_state.get(node_id).copied()
+    }
+}
+
+pub struct Engine {
+    registry: ExecutorRegistry,
+    rules: NodeRules,
+}
+
+impl Engine {
+    /// An engine validating against [`NodeRules::builtin`].
+    pub fn new(registry: ExecutorRegistry) -> Self {
+        Self {
+            registry,
+            rules: NodeRules::builtin(),
+        }
+    }
+
+    /// Replace the rules used by [`validate`](Self::validate).
+    pub fn with_rules(mut self, rules: NodeRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn registry(&self) -> &ExecutorRegistry {
+        &self.registry
+    }
+
+    pub fn rules(&self) -> &NodeRules {
+        &self.rules
+    }
+
+    /// Everything [`validate::validate`] reports, plus an error for each node
+    /// whose type has no registered executor.
+    pub fn validate(&self, workflow: &Workflow) -> Validation {
+        let mut validation = validate::validate(workflow, &self.rules);
+        for node in &workflow.nodes {
+            if !self.registry.contains(&node.data.node_type) {
+                validation.push(
+                    Diagnostic::error(
+                        DiagnosticCode::UnknownNodeType,
+                        format!(
+                            "no executor registered for node type `{}` (node `{}`)",
+                            node.data.node_type, node.id
+                        ),
+                    )
+                    .nodes([node.id.as_str()]),
+                );
+            }
+        }
+        validation
+    }
+
+    /// A copy of `workflow` with every config that has a schema rewritten
+    /// into its canonical form, so executors only ever see current shapes.
+    fn normalize(&self, workflow: &Workflow) -> Workflow {
+        let mut workflow = workflow.clone();
+        for node in &mut workflow.nodes {
+            let data = &mut node.data;
+            let Some(schema) = self.rules.get(&data.node_type).and_then(|r| r.schema) else {
+                continue;
+            };
+            if let Ok(config) = schema.normalize(&data.config, data.config_version) {
+                data.config = config;
+                data.config_version = Some(schema.version);
+            }
+        }
+        workflow
+    }
+
+    /// Run `workflow` to completion without observing progress.
+    pub async fn run(&self, workflow: &Workflow) -> Result<RunReport, EngineError> {
+        self.run_with(workflow, &()).await
+    }
+
+    /// Run `workflow` to completion, reporting every status change to `sink`.
+    ///
+    /// Returns `Err` only if [`validate`](Self::validate) reports an error;
+    /// node failures are recorded in the report and stop their own branch.
+    pub async fn run_with(
+        &self,
+        workflow: &Workflow,
+        sink: &dyn EventSink,
+    ) -> Result<RunReport, EngineError> {
+        let validation = self.validate(workflow);
+        if validation.has_errors() {
+            return Err(EngineError::Invalid(validation));
+        }
+        let normalized = self.normalize(workflow);
+        let workflow = &normalized;
+        let order = workflow
+            .topological_order()
+            .expect("validated to be acyclic");
+
+        let mut report = RunReport::default();
+        for node in &workflow.nodes {
+            set_status(&mut report, sink, &node.id, NodeStatus::Pending, None, None);
+        }
+
+        // Payload each successful node hands to its children, and the
+        // handle it leaves from.
+        let mut handoff: HashMap<&str, (Payload, Option<String>)> = HashMap::new();
+
+        for node_id in order {
+            let node = workflow.node(node_id).expect("ids come from the workflow");
+
+            let mut input = Payload::new();
+            let mut has_parents = false;
+            let mut fed = false;
+            let mut routed_around = true;
+            for edge in workflow.incoming(node_id) {
+                has_parents = true;
+                match handoff.get(edge.source.as_str()) {
+                    Some((payload, handle))
+                        if handle.is_none() || *handle == edge.source_handle =>
+                    {
+                        input.extend(payload.clone());
+                        fed = true;
+                    }
+                    _ => {
+                        routed_around &= matches!(
+                            report.status(&edge.source),
+                            Some(NodeStatus::Success | NodeStatus::Skipped)
+                        );
+                    }
+                }
+            }
+            if has_parents && !fed {
+                if routed_around {
+                    set_status(&mut report, sink, node_id, NodeStatus::Skipped, None, None);
+                }
+                continue;
+            }
+
+            let downstream_types: Vec<&str> = workflow
+                .outgoing(node_id)
+                .filter_map(|e| workflow.node(&e.target))
+                .map(|n| n.data.node_type.as_str())
+                .collect();
+            let executor = self
+                .registry
+                .get(&node.data.node_type)
+                .expect("validated to have an executor");
+
+            set_status(&mut report, sink, node_id, NodeStatus::Running, None, None);
+            let ctx = NodeContext {
+                node,
+                input: &input,
+                downstream_types: &downstream_types,
+            };
+            match executor.execute(ctx).await {
+                Ok(output) => {
+                    let result = Value::Object(output.value.clone());
+                    report
+                        .node_results
+                        .insert(node_id.to_string(), result.clone());
+                    set_status(
+                        &mut report,
+                        sink,
+                        node_id,
+                        NodeStatus::Success,
+                        Some(result),
+                        None,
+                    );
+
+                    input.extend(output.value);
+                    handoff.insert(node_id, (input, output.handle));
+                }
+                Err(err) => {
+                    let message = err.to_string();
+                    report
+                        .node_errors
+                        .insert(node_id.to_string(), message.clone());
+                    set_status(
+                        &mut report,
+                        sink,
+                        node_id,
+                        NodeStatus::Error,
+                        None,
+                        Some(message),
+                    );
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+fn set_status(
+    report: &mut RunReport,
+    sink: &dyn EventSink,
+    node_id: &str,
+    status: NodeStatus,
+    result: Option<Value>,
+    error: Option<String>,
+) {
+    report
+        .node_execution_state
+        .insert(node_id.to_string(), status);
+    sink.emit(ExecutionEvent {
+        node_id: node_id.to_string(),
+        status,
+        result,
+        error,
+    });
+}
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+use crate::validate::Validation;
+
+/// Why a workflow could not be started.
+#[derive(Debug, Error)]
+pub enum EngineError {
+    /// Validation reported at least one error; the full report, warnings
+    /// included, is attached.
+    #[error("workflow is invalid: {0}")]
+    Invalid(Validation),
+}
+
+/// Why a single node failed.
+#[derive(Debug, Error)]
+pub enum NodeError {
+    /// A required config value is missing or malformed.
+    #[error("{0}")]
+    Config(String),
+    /// The node ran but the operation failed; the message is user-facing.
+    #[error("{0}")]
+    Failed(String),
+    #[error("request to bot API failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
@@ -0,0 +1,151 @@
+//! The extension point for node types.
+//!
+//! Every `NodeData.type` the engine can run has a [`NodeExecutor`]
+//! registered under that name. The engine owns graph traversal and status
+//! bookkeeping; an executor only turns `(config, input)` into an output.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use stellrflow_nodes::NodeConfig;
+
+use crate::error::NodeError;
+use crate::graph::{Node, Payload};
+
+/// Everything an executor gets to see about the node it is running.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeContext<'a> {
+    pub node: &'a Node,
+    /// Merged outputs of every upstream node that fed this one.
+    pub input: &'a Payload,
+    /// `NodeData.type` of each directly connected downstream node.
+    pub downstream_types: &'a [&'a str],
+}
+
+impl<'a> NodeContext<'a> {
+    pub fn config(&self) -> &'a Payload {
+        &self.node.data.config
+    }
+
+    /// The config parsed as `T`. The engine hands executors configs already
+    /// migrated to `T::VERSION`; this also covers nodes run outside it.
+    pub fn parse_config<T: NodeConfig>(&self) -> Result<T, NodeError> {
+        T::parse(self.config(), self.node.data.config_version.unwrap_or(1))
+            .map_err(|err| NodeError::Config(err.to_string()))
+    }
+
+    /// A config value as a trimmed, non-empty string. Numbers are accepted
+    /// too, since the properties panel stores some fields either way.
+    pub fn config_str(&self, key: &str) -> Option<String> {
+        value_str(self.config().get(key))
+    }
+
+    /// An input value as a trimmed, non-empty string.
+    pub fn input_str(&self, key: &str) -> Option<String> {
+        value_str(self.input.get(key))
+    }
+
+    /// The Telegram chat this node should talk to: its own `chatId` config,
+    /// falling back to the one handed down by the trigger.
+    pub fn chat_id(&self) -> Option<String> {
+        self.config_str("chatId")
+            .or_else(|| self.input_str("chatId"))
+    }
+
+    /// Like [`chat_id`](Self::chat_id), but a missing chat is a node error.
+    pub fn require_chat_id(&self) -> Result<String, NodeError> {
+        self.chat_id().ok_or_else(|| {
+            NodeError::Config(
+                "Chat ID is required. Connect to a Telegram trigger first.".to_string(),
+            )
+        })
+    }
+}
+
+fn value_str(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => {
+            let s = s.trim();
+            (!s.is_empty()).then(|| s.to_string())
+        }
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// What a node hands to its downstream nodes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeOutput {
+    /// Recorded as the node's result and merged into the payload passed on.
+    pub value: Payload,
+    /// For node types with named outputs: the handle the payload leaves
+    /// from. Only edges drawn from it carry the payload on; `None` feeds
+    /// every outgoing edge.
+    pub handle: Option<String>,
+}
+
+impl NodeOutput {
+    pub fn new(value: Payload) -> Self {
+        Self {
+            value,
+            handle: None,
+        }
+    }
+
+    /// Route the payload along the edges leaving `handle` only.
+    pub fn on(mut self, handle: impl Into<String>) -> Self {
+        self.handle = Some(handle.into());
+        self
+    }
+}
+
+impl From<Payload> for NodeOutput {
+    fn from(value: Payload) -> Self {
+        Self::new(value)
+    }
+}
+
+#[async_trait]
+pub trait NodeExecutor: Send + Sync {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError>;
+}
+
+/// Maps `NodeData.type` strings to executors.
+#[derive(Clone, Default)]
+pub struct ExecutorRegistry {
+    executors: HashMap<String, Arc<dyn NodeExecutor>>,
+}
+
+impl ExecutorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `executor` for `node_type`, replacing any previous one.
+    pub fn register(
+        &mut self,
+        node_type: impl Into<String>,
+        executor: impl NodeExecutor + 'static,
+    ) -> &mut Self {
+        self.executors.insert(node_type.into(), Arc::new(executor));
+        self
+    }
+
+    pub fn get(&self, node_type: &str) -> Option<&Arc<dyn NodeExecutor>> {
+        self.executors.get(node_type)
+    }
+
+    pub fn contains(&self, node_type: &str) -> bool {
+        self.executors.contains_key(node_type)
+    }
+
+    /// Registered node types, sorted.
+    pub fn node_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.executors.keys().map(String::as_str).collect();
+        types.sort_unstable();
+        types
+    }
+}
@@ -0,0 +1,141 @@
+//! The workflow graph as the builder saves it.
+//!
+//! These types deserialize the `{ nodes, edges }` JSON produced by
+//! `useWorkflowStore` (ReactFlow nodes carrying a `NodeData` payload).
+//! Fields the engine does not need — `position`, `animated`, `style`, … —
+//! are ignored on input.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Free-form JSON object passed between nodes and used for node config.
+pub type Payload = Map<String, Value>;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub id: String,
+    pub data: NodeData,
+}
+
+/// Mirrors `NodeData` in `frontend/lib/stores/workflow-store.ts`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeData {
+    #[serde(default)]
+    pub label: String,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(default)]
+    pub icon: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub config: Payload,
+    /// Schema version `config` was saved at; absent means 1. See
+    /// `stellrflow_nodes::NodeConfig::VERSION`.
+    #[serde(
+        default,
+        rename = "configVersion",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub config_version: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Edge {
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub source_handle: Option<String>,
+    #[serde(default)]
+    pub target_handle: Option<String>,
+}
+
+impl Workflow {
+    /// Parse the builder's `{ nodes, edges }` JSON.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    /// Edges leaving `node_id`, in the order they were drawn.
+    pub fn outgoing<'a>(&'a self, node_id: &'a str) -> impl Iterator<Item = &'a Edge> + 'a {
+        self.edges.iter().filter(move |e| e.source == node_id)
+    }
+
+    /// Edges entering `node_id`, in the order they were drawn.
+    pub fn incoming<'a>(&'a self, node_id: &'a str) -> impl Iterator<Item = &'a Edge> + 'a {
+        self.edges.iter().filter(move |e| e.target == node_id)
+    }
+
+    /// Nodes with no incoming edge. This is how `startWorkflow` picks the
+    /// nodes to fire first.
+    pub fn roots(&self) -> Vec<&Node> {
+        self.nodes
+            .iter()
+            .filter(|n| self.incoming(&n.id).next().is_none())
+            .collect()
+    }
+
+    /// Node IDs in topological order (Kahn's algorithm, ties broken by node
+    /// order in the document). Returns `Err` with the IDs that could not be
+    /// ordered if the graph contains a cycle.
+    pub fn topological_order(&self) -> Result<Vec<&str>, Vec<&str>> {
+        let mut in_degree: HashMap<&str, usize> =
+            self.nodes.iter().map(|n| (n.id.as_str(), 0)).collect();
+        for edge in &self.edges {
+            if let Some(d) = in_degree.get_mut(edge.target.as_str()) {
+                *d += 1;
+            }
+        }
+
+        let position: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.as_str(), i))
+            .collect();
+        let mut ready: BTreeMap<usize, &str> = self
+            .nodes
+            .iter()
+            .filter(|n| in_degree[n.id.as_str()] == 0)
+            .map(|n| (position[n.id.as_str()], n.id.as_str()))
+            .collect();
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some((_, id)) = ready.pop_first() {
+            order.push(id);
+            for edge in self.outgoing(id) {
+                if let Some(d) = in_degree.get_mut(edge.target.as_str()) {
+                    *d -= 1;
+                    if *d == 0 {
+                        ready.insert(position[edge.target.as_str()], edge.target.as_str());
+                    }
+                }
+            }
+        }
+
+        if order.len() == self.nodes.len() {
+            Ok(order)
+        } else {
+            Err(self
+                .nodes
+                .iter()
+                .map(|n| n.id.as_str())
+                .filter(|id| !order.contains(id))
+                .collect())
+        }
+    }
+}
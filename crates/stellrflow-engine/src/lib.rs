@@ -0,0 +1,36 @@
+//! Server-side execution engine for StellrFlow workflows.
+//!
+//! The builder in `frontend/` saves a workflow as ReactFlow `{ nodes, edges }`
+//! JSON. This crate parses that document ([`Workflow`]), checks it, and runs
+//! it: every node type is handled by a [`NodeExecutor`] looked up in an
+//! [`ExecutorRegistry`], and the [`Engine`] takes care of ordering, passing
+//! payloads along edges and tracking per-node status.
+//!
+//! ```ignore
+//! let engine = Engine::new(ExecutorRegistry::builtin(BotClient::default()));
+//! let report = engine.run(&Workflow::from_json(&json)?).await?;
+//! println!("{}", serde_json::to_string(&report)?);
+//! ```
+//!
+//! Before running, the engine checks the graph ([`validate`]): cycles,
+//! nodes no trigger can reach, missing or duplicate triggers, missing
+//! required config and edges leaving a `condition` from neither branch are
+//! reported as [`Diagnostic`]s carrying node IDs.
+//!
+//! Status changes can be observed while a run is in progress by passing an
+//! [`EventSink`] to [`Engine::run_with`]; the final [`RunReport`] serializes
+//! to the same `nodeExecutionState` / `nodeResults` shape the frontend store
+//! keeps.
+
+mod engine;
+mod error;
+mod executor;
+mod graph;
+pub mod nodes;
+pub mod validate;
+
+pub use engine::{Engine, EventSink, ExecutionEvent, NodeStatus, RunReport};
+pub use error::{EngineError, NodeError};
+pub use executor::{ExecutorRegistry, NodeContext, NodeExecutor, NodeOutput};
+pub use graph::{Edge, Node, NodeData, Payload, Workflow};
+pub use validate::{Diagnostic, DiagnosticCode, NodeRule, NodeRules, Severity, Validation};
@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use stellrflow_amount::{Amount, Fiat, Rounding, FIAT_DECIMALS, STROOPS_PER_XLM};
+use stellrflow_nodes::{AnchorOffRampConfig, AnchorOnRampConfig};
+
+use super::{display, BotClient};
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+/// `anchor-onramp`: deposits `config.amount` (default 100) of
+/// `config.fiatCurrency` through the bot's mock anchor.
+#[derive(Debug, Clone)]
+pub struct AnchorOnRamp {
+    bot: BotClient,
+}
+
+impl AnchorOnRamp {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for AnchorOnRamp {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: AnchorOnRampConfig = ctx.parse_config()?;
+        let amount = match config.amount.trim() {
+            "" => Fiat::new(100, 0),
+            text => Fiat::parse(text, FIAT_DECIMALS)
+                .map_err(|err| NodeError::Config(err.to_string()))?,
+        };
+        let currency = config.fiat_currency;
+
+        let response = self
+            .bot
+            .post(
+                "/api/anchor/deposit",
+                &json!({ "chatId": chat_id, "amount": amount, "currency": currency }),
+            )
+            .await?;
+        if !response.is_success() {
+            let error = response.error_or("Unable to process deposit.");
+            self.bot
+                .notify(&chat_id, &format!("❌ **Deposit Failed**\n\n{error}"))
+                .await;
+            return Err(NodeError::Failed(
+                response.error_or("Anchor deposit failed"),
+            ));
+        }
+        let result = response.0;
+
+        let credited: Option<Amount> = result
+            .get("creditedXLM")
+            .and_then(|value| serde_json::from_value(value.clone()).ok());
+        let deposit_id = result.get("depositId").cloned().unwrap_or(Value::Null);
+        let message = format!(
+            "✅ **Deposit Successful!**\n\n\
+             **Deposited:** {amount} {currency}\n\
+             **Credited:** {} XLM\n\
+             **Deposit ID:** `{}`\n\n\
+             Use /mybalance to check your updated balance.",
+            credited.map_or_else(|| "—".into(), |xlm| xlm.to_string()),
+            display(&deposit_id),
+        );
+        self.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("depositId".into(), deposit_id);
+        output.insert("fiatAmount".into(), json!(amount));
+        output.insert("currency".into(), json!(currency));
+        output.insert("creditedXLM".into(), json!(credited));
+        Ok(output.into())
+    }
+}
+
+/// `anchor-offramp`: withdraws `config.amount` XLM (default 10) to
+/// `config.fiatCurrency` through the bot's mock anchor.
+#[derive(Debug, Clone)]
+pub struct AnchorOffRamp {
+    bot: BotClient,
+}
+
+impl AnchorOffRamp {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for AnchorOffRamp {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: AnchorOffRampConfig = ctx.parse_config()?;
+        let xlm_amount = match config.amount.trim() {
+            "" => Amount::from_stroops(10 * STROOPS_PER_XLM),
+            text => text
+                .parse::<Amount>()
+                .map_err(|err| NodeError::Config(err.to_string()))?,
+        };
+        let currency = config.fiat_currency;
+
+        let response = self
+            .bot
+            .post(
+                "/api/anchor/withdraw",
+                &json!({ "chatId": chat_id, "xlmAmount": xlm_amount, "currency": currency }),
+            )
+            .await?;
+        if !response.is_success() {
+            let error = response.error_or("Unable to process withdrawal.");
+            self.bot
+                .notify(&chat_id, &format!("❌ **Withdrawal Failed**\n\n{error}"))
+                .await;
+            return Err(NodeError::Failed(
+                response.error_or("Anchor withdrawal failed"),
+            ));
+        }
+        let result = response.0;
+
+        // The anchor rounds its payout already; this only fixes the places.
+        let payout = result
+            .get("fiatPayout")
+            .and_then(|value| serde_json::from_value::<Fiat>(value.clone()).ok())
+            .and_then(|fiat| fiat.rescale(FIAT_DECIMALS, Rounding::HalfUp).ok());
+        let withdrawal_id = result.get("withdrawalId").cloned().unwrap_or(Value::Null);
+        let eta = result.get("eta").cloned().unwrap_or(Value::Null);
+        let message = format!(
+            "✅ **Withdrawal Processed!**\n\n\
+             **Withdrawn:** {xlm_amount} XLM\n\
+             **Payout:** {} {currency}\n\
+             **Withdrawal ID:** `{}`\n\
+             **ETA:** {}\n\n\
+             _Demo: In production, funds would be sent to your bank._",
+            payout.map_or_else(|| "—".into(), |fiat| fiat.to_string()),
+            display(&withdrawal_id),
+            eta.as_str().unwrap_or("5-10 min"),
+        );
+        self.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("withdrawalId".into(), withdrawal_id);
+        output.insert("xlmAmount".into(), json!(xlm_amount));
+        output.insert("fiatPayout".into(), json!(payout));
+        output.insert("currency".into(), json!(currency));
+        output.insert("eta".into(), eta);
+        Ok(output.into())
+    }
+}
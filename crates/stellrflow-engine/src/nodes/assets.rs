@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use stellrflow_amount::Amount;
+use stellrflow_keystore::Keystore;
+use stellrflow_nodes::{ConvertPayConfig, PathStrict, SendAssetConfig, TrustlineConfig};
+use stellrflow_signer::{KeystoreSigner, Signer, SignerError};
+use stellrflow_stellar::{Asset, PathPayment, Slippage, StellarError, SubmissionQueue};
+
+use super::policy::{PolicyCheck, PolicyClient};
+use super::{shorten, BotClient};
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+/// The wallet each Telegram chat signs with.
+#[async_trait]
+pub trait Wallets: Send + Sync {
+    async fn wallet(&self, chat_id: &str) -> Result<Arc<dyn Signer>, SignerError>;
+}
+
+/// The bot's encrypted keystore.
+#[async_trait]
+impl Wallets for Arc<Keystore> {
+    async fn wallet(&self, chat_id: &str) -> Result<Arc<dyn Signer>, SignerError> {
+        Ok(Arc::new(KeystoreSigner::new(self.clone(), chat_id)?))
+    }
+}
+
+/// Signers by chat ID.
+#[async_trait]
+impl Wallets for HashMap<String, Arc<dyn Signer>> {
+    async fn wallet(&self, chat_id: &str) -> Result<Arc<dyn Signer>, SignerError> {
+        self.get(chat_id)
+            .cloned()
+            .ok_or_else(|| SignerError::UnknownAccount(chat_id.to_string()))
+    }
+}
+
+/// What the asset nodes sign and submit with: the chat's wallet, through
+/// the shared submission queue, so they never race the chat's other
+/// transactions for a sequence number, once the spending policy allows it.
+#[derive(Clone)]
+pub struct Stellar {
+    pub(super) queue: Arc<SubmissionQueue>,
+    pub(super) wallets: Arc<dyn Wallets>,
+    pub(super) bot: BotClient,
+    policy: Option<PolicyClient>,
+}
+
+impl Stellar {
+    pub fn new(queue: Arc<SubmissionQueue>, wallets: Arc<dyn Wallets>, bot: BotClient) -> Self {
+        Self {
+            queue,
+            wallets,
+            bot,
+            policy: None,
+        }
+    }
+
+    /// Checks every payment with the spending policy service before signing
+    /// it. Without one, payments are made unchecked, as the bot makes them
+    /// when `POLICY_URL` is not set.
+    pub fn with_policy(mut self, policy: PolicyClient) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Asks the spending policy whether the chat's wallet may spend `amount`
+    /// of `asset`, valued in XLM, on `destination`. Tells the chat and fails
+    /// if it may not, or if the asset cannot be valued.
+    pub(super) async fn check_policy(
+        &self,
+        chat_id: &str,
+        destination: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<PolicyCheck, NodeError> {
+        let Some(policy) = &self.policy else {
+            return Ok(PolicyCheck::unchecked());
+        };
+        let check = match self.queue.horizon().xlm_value(asset, amount).await {
+            Ok(xlm) => policy.check(chat_id, destination, xlm).await,
+            Err(err) => PolicyCheck::deny(format!(
+                "Cannot value {amount} {} in XLM: {err}",
+                asset.code()
+            )),
+        };
+        if !check.allowed {
+            self.bot.notify(chat_id, &check.message()).await;
+            return Err(NodeError::Failed(check.reason));
+        }
+        Ok(check)
+    }
+
+    /// Un-counts a payment the policy allowed but that was not made.
+    pub(super) async fn release(&self, check: &PolicyCheck) {
+        if let Some(policy) = &self.policy {
+            policy.release(check).await;
+        }
+    }
+
+    pub(super) async fn wallet(&self, chat_id: &str) -> Result<Arc<dyn Signer>, NodeError> {
+        self.wallets.wallet(chat_id).await.map_err(|_| {
+            NodeError::Failed("No wallet connected. Connect one via StellrFlow workflow.".into())
+        })
+    }
+}
+
+pub(super) fn parse_asset(text: &str) -> Result<Asset, NodeError> {
+    text.parse()
+        .map_err(|err: StellarError| NodeError::Config(err.to_string()))
+}
+
+/// `trustline`: adds, limits or removes a trustline from the chat's wallet.
+#[derive(Clone)]
+pub struct Trustline {
+    stellar: Stellar,
+}
+
+impl Trustline {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for Trustline {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: TrustlineConfig = ctx.parse_config()?;
+        let asset = parse_asset(&config.asset)?;
+        let limit = match config.limit.trim() {
+            "" => None,
+            text => Some(
+                text.parse::<Amount>()
+                    .map_err(|err| NodeError::Config(err.to_string()))?,
+            ),
+        };
+        let removed = limit == Some(Amount::ZERO);
+
+        let wallet = self.stellar.wallet(&chat_id).await?;
+        let record = match self
+            .stellar
+            .queue
+            .change_trust(wallet.as_ref(), &asset, limit)
+            .await
+        {
+            Ok(record) => record,
+            Err(err) => {
+                self.stellar
+                    .bot
+                    .notify(&chat_id, &format!("❌ **Trustline Failed**\n\n{err}"))
+                    .await;
+                return Err(NodeError::Failed(err.to_string()));
+            }
+        };
+
+        let issuer = asset.issuer().unwrap_or_default();
+        let message = if removed {
+            format!(
+                "✅ **Trustline Removed**\n\n\
+                 **Asset:** {}\n\
+                 **Issuer:** `{}`",
+                asset.code(),
+                shorten(issuer),
+            )
+        } else {
+            format!(
+                "✅ **Trustline Ready!**\n\n\
+                 **Asset:** {}\n\
+                 **Issuer:** `{}`\n\
+                 **Limit:** {}\n\
+                 **Transaction:** `{}`",
+                asset.code(),
+                shorten(issuer),
+                limit.map_or("unlimited".to_string(), |limit| limit.to_string()),
+                shorten(&record.hash),
+            )
+        };
+        self.stellar.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("asset".into(), json!(asset));
+        output.insert("limit".into(), json!(limit));
+        output.insert("removed".into(), json!(removed));
+        output.insert("hash".into(), json!(record.hash));
+        output.insert("ledger".into(), json!(record.ledger));
+        Ok(output.into())
+    }
+}
+
+/// `send-asset`: pays XLM or an issued asset from the chat's wallet, once
+/// both sides' trustlines have been checked.
+#[derive(Clone)]
+pub struct SendAsset {
+    stellar: Stellar,
+}
+
+impl SendAsset {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for SendAsset {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: SendAssetConfig = ctx.parse_config()?;
+        let destination = config.destination.trim().to_string();
+        if destination.is_empty() {
+            return Err(NodeError::Config(
+                "Destination address is required to send an asset".into(),
+            ));
+        }
+        let asset = parse_asset(&config.asset)?;
+        let amount = config
+            .amount
+            .trim()
+            .parse::<Amount>()
+            .map_err(|err| NodeError::Config(err.to_string()))?;
+
+        let wallet = self.stellar.wallet(&chat_id).await?;
+        let check = self
+            .stellar
+            .check_policy(&chat_id, &destination, &asset, amount)
+            .await?;
+        let sent = match self
+            .stellar
+            .queue
+            .send_asset(wallet.as_ref(), &destination, &asset, amount)
+            .await
+        {
+            Ok(sent) => sent,
+            Err(err) => {
+                self.stellar.release(&check).await;
+                self.stellar
+                    .bot
+                    .notify(&chat_id, &format!("❌ **Payment Failed**\n\n{err}"))
+                    .await;
+                return Err(NodeError::Failed(err.to_string()));
+            }
+        };
+
+        let mut message = format!(
+            "✅ **Payment Sent!**\n\n\
+             **Amount:** {amount} {}\n\
+             **To:** `{}`\n",
+            asset.code(),
+            shorten(&destination),
+        );
+        if sent.created_account {
+            message.push_str("**New account created**\n");
+        }
+        message.push_str(&format!("**Transaction:** `{}`", shorten(&sent.hash)));
+        self.stellar.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("destination".into(), json!(destination));
+        output.insert("asset".into(), json!(asset));
+        output.insert("amount".into(), json!(amount));
+        output.insert("hash".into(), json!(sent.hash));
+        output.insert("ledger".into(), json!(sent.ledger));
+        output.insert("createdAccount".into(), json!(sent.created_account));
+        Ok(output.into())
+    }
+}
+
+/// `convert-pay`: pays from the chat's wallet in one asset what the
+/// destination receives in another, over the best path Horizon finds and
+/// within the configured slippage.
+#[derive(Clone)]
+pub struct ConvertPay {
+    stellar: Stellar,
+}
+
+impl ConvertPay {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for ConvertPay {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: ConvertPayConfig = ctx.parse_config()?;
+        let destination = config.destination.trim().to_string();
+        if destination.is_empty() {
+            return Err(NodeError::Config(
+                "Destination address is required to convert and pay".into(),
+            ));
+        }
+        let send_asset = parse_asset(&config.send_asset)?;
+        let dest_asset = parse_asset(&config.dest_asset)?;
+        let amount = config
+            .amount
+            .trim()
+            .parse::<Amount>()
+            .map_err(|err| NodeError::Config(err.to_string()))?;
+        let slippage = match config.slippage.trim() {
+            "" => Slippage::default(),
+            text => text
+                .parse::<Slippage>()
+                .map_err(|err| NodeError::Config(err.to_string()))?,
+        };
+        let payment = match config.strict {
+            PathStrict::Send => PathPayment::strict_send(send_asset, amount, dest_asset),
+            PathStrict::Receive => PathPayment::strict_receive(send_asset, dest_asset, amount),
+        }
+        .with_slippage(slippage);
+
+        let wallet = self.stellar.wallet(&chat_id).await?;
+        // Checked for the most it may send: the quote plus the slippage.
+        let most_sent = match self.stellar.queue.horizon().quote(&payment).await {
+            Ok(quote) => quote.most_sent(),
+            Err(err) => {
+                self.stellar
+                    .bot
+                    .notify(&chat_id, &format!("❌ **Payment Failed**\n\n{err}"))
+                    .await;
+                return Err(NodeError::Failed(err.to_string()));
+            }
+        };
+        let check = self
+            .stellar
+            .check_policy(&chat_id, &destination, &payment.send_asset, most_sent)
+            .await?;
+        let paid = match self
+            .stellar
+            .queue
+            .path_payment(wallet.as_ref(), &destination, &payment)
+            .await
+        {
+            Ok(paid) => paid,
+            Err(err) => {
+                self.stellar.release(&check).await;
+                self.stellar
+                    .bot
+                    .notify(&chat_id, &format!("❌ **Payment Failed**\n\n{err}"))
+                    .await;
+                return Err(NodeError::Failed(err.to_string()));
+            }
+        };
+
+        let (sent, received) = (payment.send_asset.code(), payment.dest_asset.code());
+        let message = format!(
+            "✅ **Converted & Paid!**\n\n\
+             **Sent:** {} {sent}\n\
+             **Received:** {} {received}\n\
+             **Rate:** 1 {sent} = {} {received}\n\
+             **To:** `{}`\n\
+             **Transaction:** `{}`",
+            paid.source_amount,
+            paid.destination_amount,
+            paid.rate(),
+            shorten(&destination),
+            shorten(&paid.hash),
+        );
+        self.stellar.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("destination".into(), json!(destination));
+        output.insert("sendAsset".into(), json!(payment.send_asset));
+        output.insert("destAsset".into(), json!(payment.dest_asset));
+        output.insert("sentAmount".into(), json!(paid.source_amount));
+        output.insert("receivedAmount".into(), json!(paid.destination_amount));
+        output.insert("rate".into(), json!(paid.rate()));
+        output.insert("quotedRate".into(), json!(paid.quote.rate()));
+        output.insert("path".into(), json!(paid.quote.path.path));
+        output.insert("hash".into(), json!(paid.hash));
+        output.insert("ledger".into(), json!(paid.ledger));
+        Ok(output.into())
+    }
+}
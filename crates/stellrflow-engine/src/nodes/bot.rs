@@ -0,0 +1,109 @@
+use serde_json::{json, Value};
+
+use crate::error::NodeError;
+use crate::graph::Payload;
+
+pub const DEFAULT_BOT_URL: &str = "http://localhost:3003";
+pub const DEFAULT_APP_URL: &str = "http://localhost:3000";
+
+/// Client for the Telegram bot's REST API (`bots/telegram-stellar`), the
+/// same endpoints `api-service.ts` calls from the browser.
+#[derive(Debug, Clone)]
+pub struct BotClient {
+    base_url: String,
+    app_url: String,
+    http: reqwest::Client,
+}
+
+impl Default for BotClient {
+    fn default() -> Self {
+        Self::new(DEFAULT_BOT_URL)
+    }
+}
+
+impl BotClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            app_url: DEFAULT_APP_URL.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Origin of the web app, used to build the Freighter connect link.
+    pub fn with_app_url(mut self, app_url: impl Into<String>) -> Self {
+        self.app_url = app_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn app_url(&self) -> &str {
+        &self.app_url
+    }
+
+    pub async fn send_message(
+        &self,
+        chat_id: &str,
+        message: &str,
+        parse_mode: &str,
+    ) -> Result<BotResponse, NodeError> {
+        let body = json!({ "chatId": chat_id, "message": message, "parseMode": parse_mode });
+        self.post("/api/telegram/send", &body).await
+    }
+
+    /// Send a Markdown message, ignoring delivery failures. Used for the
+    /// follow-up notices nodes send after their real work is done.
+    pub async fn notify(&self, chat_id: &str, message: &str) {
+        let _ = self.send_message(chat_id, message, "Markdown").await;
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> Result<BotResponse, NodeError> {
+        let response = self
+            .http
+            .post(format!("{}{path}", self.base_url))
+            .json(body)
+            .send()
+            .await?;
+        Ok(BotResponse(response.json().await?))
+    }
+
+    pub async fn get(&self, path: &str) -> Result<BotResponse, NodeError> {
+        let response = self
+            .http
+            .get(format!("{}{path}", self.base_url))
+            .send()
+            .await?;
+        Ok(BotResponse(response.json().await?))
+    }
+}
+
+/// A decoded bot API response. Every endpoint answers with a JSON object
+/// carrying `success` and, on failure, `error`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotResponse(pub Payload);
+
+impl BotResponse {
+    pub fn is_success(&self) -> bool {
+        self.0.get("success").and_then(Value::as_bool) == Some(true)
+    }
+
+    /// The bot's `error` message, or `fallback` if it did not send one.
+    pub fn error_or(&self, fallback: &str) -> String {
+        self.0
+            .get("error")
+            .and_then(Value::as_str)
+            .unwrap_or(fallback)
+            .to_string()
+    }
+
+    pub fn into_success(self, fallback: &str) -> Result<Payload, NodeError> {
+        if self.is_success() {
+            Ok(self.0)
+        } else {
+            Err(NodeError::Failed(self.error_or(fallback)))
+        }
+    }
+}
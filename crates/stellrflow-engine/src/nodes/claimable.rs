@@ -0,0 +1,265 @@
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use stellrflow_amount::Amount;
+use stellrflow_nodes::interval::parse_interval_ms;
+use stellrflow_nodes::{ClaimClaimableBalanceConfig, CreateClaimableBalanceConfig};
+use stellrflow_stellar::{Claimant, PageQuery, Predicate, StellarError};
+
+use super::assets::{parse_asset, Stellar};
+use super::shorten;
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+/// An empty interval is unset.
+fn parse_delay(key: &str, text: &str) -> Result<Option<Duration>, NodeError> {
+    match text.trim() {
+        "" => Ok(None),
+        text => parse_interval_ms(text)
+            .map(|ms| Some(Duration::from_millis(ms)))
+            .map_err(|err| NodeError::Config(format!("`{key}`: {err}"))),
+    }
+}
+
+/// `create-claimable-balance`: sets funds aside from the chat's wallet for
+/// the claimants to claim within the configured window, and for the
+/// wallet to take back once it expires if `reclaim` is set.
+#[derive(Clone)]
+pub struct CreateClaimableBalance {
+    stellar: Stellar,
+}
+
+impl CreateClaimableBalance {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for CreateClaimableBalance {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: CreateClaimableBalanceConfig = ctx.parse_config()?;
+        if config.claimants.is_empty() {
+            return Err(NodeError::Config(
+                "At least one claimant is required to create a claimable balance".into(),
+            ));
+        }
+        let asset = parse_asset(&config.asset)?;
+        let amount = config
+            .amount
+            .trim()
+            .parse::<Amount>()
+            .map_err(|err| NodeError::Config(err.to_string()))?;
+        let after = parse_delay("claimableAfter", &config.claimable_after)?;
+        let expires = parse_delay("expiresAfter", &config.expires_after)?;
+        let predicate = match (after, expires) {
+            (Some(after), Some(expires)) => {
+                Predicate::after_delay(after).and(Predicate::within(expires))
+            }
+            (Some(after), None) => Predicate::after_delay(after),
+            (None, Some(expires)) => Predicate::within(expires),
+            (None, None) => Predicate::Unconditional,
+        };
+
+        let wallet = self.stellar.wallet(&chat_id).await?;
+        let mut claimants: Vec<Claimant> = config
+            .claimants
+            .iter()
+            .map(|destination| Claimant::new(destination.trim(), predicate.clone()))
+            .collect();
+        if let (true, Some(expires)) = (config.reclaim, expires) {
+            claimants.push(Claimant::new(
+                wallet.public_key(),
+                Predicate::after_delay(expires),
+            ));
+        }
+        // Any claimant may end up with the balance, so each must be one the
+        // wallet may pay it to. It counts towards the limits once: the
+        // other claimants' checks are released straight away, before the
+        // first claimant's is made.
+        for destination in &config.claimants[1..] {
+            let check = self
+                .stellar
+                .check_policy(&chat_id, destination.trim(), &asset, amount)
+                .await?;
+            self.stellar.release(&check).await;
+        }
+        let check = self
+            .stellar
+            .check_policy(&chat_id, config.claimants[0].trim(), &asset, amount)
+            .await?;
+        let created = match self
+            .stellar
+            .queue
+            .create_claimable_balance(wallet.as_ref(), &asset, amount, &claimants)
+            .await
+        {
+            Ok(created) => created,
+            Err(err) => {
+                self.stellar.release(&check).await;
+                self.stellar
+                    .bot
+                    .notify(
+                        &chat_id,
+                        &format!("❌ **Claimable Balance Failed**\n\n{err}"),
+                    )
+                    .await;
+                return Err(NodeError::Failed(err.to_string()));
+            }
+        };
+
+        let mut message = format!(
+            "✅ **Claimable Balance Created!**\n\n\
+             **Amount:** {amount} {}\n\
+             **Claimants:** {}\n",
+            asset.code(),
+            config
+                .claimants
+                .iter()
+                .map(|claimant| format!("`{}`", shorten(claimant.trim())))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        if after.is_some() {
+            message.push_str(&format!(
+                "**Claimable after:** {}\n",
+                config.claimable_after.trim()
+            ));
+        }
+        if expires.is_some() {
+            let then = if config.reclaim {
+                ", then yours again"
+            } else {
+                ""
+            };
+            message.push_str(&format!(
+                "**Expires after:** {}{then}\n",
+                config.expires_after.trim()
+            ));
+        }
+        message.push_str(&format!(
+            "**Balance ID:** `{}`\n**Transaction:** `{}`",
+            shorten(&created.balance_id),
+            shorten(&created.hash),
+        ));
+        self.stellar.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("balanceId".into(), json!(created.balance_id));
+        output.insert(
+            "claimants".into(),
+            json!(claimants
+                .iter()
+                .map(|claimant| &claimant.destination)
+                .collect::<Vec<_>>()),
+        );
+        output.insert("asset".into(), json!(asset));
+        output.insert("amount".into(), json!(amount));
+        output.insert("hash".into(), json!(created.hash));
+        output.insert("ledger".into(), json!(created.ledger));
+        Ok(output.into())
+    }
+}
+
+/// `claim-claimable-balance`: claims one balance into the chat's wallet,
+/// or every balance it can claim now when no ID is set.
+#[derive(Clone)]
+pub struct ClaimClaimableBalance {
+    stellar: Stellar,
+}
+
+impl ClaimClaimableBalance {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for ClaimClaimableBalance {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: ClaimClaimableBalanceConfig = ctx.parse_config()?;
+        let wallet = self.stellar.wallet(&chat_id).await?;
+        let ids = match config.balance_id.trim() {
+            "" => {
+                let now = SystemTime::now();
+                match self
+                    .stellar
+                    .queue
+                    .horizon()
+                    .claimable_balances(wallet.public_key(), &PageQuery::default())
+                    .await
+                {
+                    Ok(page) => page
+                        .records
+                        .into_iter()
+                        .filter(|balance| balance.claimable_by(wallet.public_key(), now))
+                        .map(|balance| balance.id)
+                        .collect(),
+                    Err(err) => return Err(claim_failed(&self.stellar, &chat_id, err).await),
+                }
+            }
+            id => vec![id.to_string()],
+        };
+
+        let mut claimed = Vec::new();
+        for id in &ids {
+            match self.stellar.queue.claim_balance(wallet.as_ref(), id).await {
+                Ok(balance) => claimed.push(balance),
+                Err(err) => return Err(claim_failed(&self.stellar, &chat_id, err).await),
+            }
+        }
+
+        let message = if claimed.is_empty() {
+            "ℹ️ **Nothing to Claim**\n\nNo claimable balances are waiting for your wallet.".into()
+        } else {
+            let mut message = "✅ **Balances Claimed!**\n\n".to_string();
+            for balance in &claimed {
+                message.push_str(&format!(
+                    "**{} {}** from `{}` (`{}`)\n",
+                    balance.amount,
+                    balance.asset.code(),
+                    shorten(&balance.balance_id),
+                    shorten(&balance.hash),
+                ));
+            }
+            message.trim_end().to_string()
+        };
+        self.stellar.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("count".into(), json!(claimed.len()));
+        output.insert(
+            "claimed".into(),
+            claimed
+                .iter()
+                .map(|balance| {
+                    json!({
+                        "balanceId": balance.balance_id,
+                        "asset": balance.asset,
+                        "amount": balance.amount,
+                        "hash": balance.hash,
+                        "ledger": balance.ledger,
+                    })
+                })
+                .collect(),
+        );
+        Ok(output.into())
+    }
+}
+
+async fn claim_failed(stellar: &Stellar, chat_id: &str, err: StellarError) -> NodeError {
+    stellar
+        .bot
+        .notify(chat_id, &format!("❌ **Claim Failed**\n\n{err}"))
+        .await;
+    NodeError::Failed(err.to_string())
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use stellrflow_expr::Expression;
+use stellrflow_nodes::ConditionConfig;
+
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+
+/// `condition`: evaluates `config.expression` against the incoming payload
+/// and routes it along the `true` or `false` handle. Outputs
+/// `{ condition: <bool> }`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Condition;
+
+#[async_trait]
+impl NodeExecutor for Condition {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let config: ConditionConfig = ctx.parse_config()?;
+        let expression = Expression::parse(&config.expression)
+            .map_err(|err| NodeError::Config(format!("Invalid expression: {err}")))?;
+        let result = expression.eval_bool(ctx.input).map_err(|err| {
+            NodeError::Failed(format!("Condition `{}`: {err}", config.expression))
+        })?;
+
+        let mut output = NodeOutput::default();
+        output.value.insert("condition".into(), json!(result));
+        Ok(output.on(result.to_string()))
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use stellrflow_nodes::DelayConfig;
+
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+
+/// `delay`: waits `config.delay` seconds (default 5) before passing the
+/// payload on. Outputs `{ delayed: <ms> }`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Delay;
+
+#[async_trait]
+impl NodeExecutor for Delay {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let config: DelayConfig = ctx.parse_config()?;
+        let millis = config.delay.saturating_mul(1000);
+
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+
+        let mut output = NodeOutput::default();
+        output.value.insert("delayed".into(), json!(millis));
+        Ok(output)
+    }
+}
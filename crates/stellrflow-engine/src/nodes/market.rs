@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use stellrflow_amount::Amount;
+use stellrflow_nodes::interval::parse_interval_ms;
+use stellrflow_nodes::{
+    ManageBuyOfferConfig, ManageSellOfferConfig, PriceDirection, PriceFeed, PriceTriggerConfig,
+};
+use stellrflow_stellar::{
+    Horizon, LimitOrder, Price, PriceCondition, PriceSource, PriceWatch, Side, StellarError,
+};
+
+use super::assets::{parse_asset, Stellar};
+use super::policy::PolicyCheck;
+use super::{shorten, BotClient};
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+fn parse_price(text: &str) -> Result<Price, NodeError> {
+    text.parse()
+        .map_err(|err: StellarError| NodeError::Config(err.to_string()))
+}
+
+fn parse_interval(key: &str, text: &str) -> Result<Duration, NodeError> {
+    parse_interval_ms(text)
+        .map(Duration::from_millis)
+        .map_err(|err| NodeError::Config(format!("`{key}`: {err}")))
+}
+
+/// `price-trigger`: polls the pair's order book, or its latest trades,
+/// until the price crosses the threshold, then tells the chat and starts
+/// the workflow with the price. Fails if the price does not get there
+/// within `timeout`.
+#[derive(Debug, Clone)]
+pub struct PriceTrigger {
+    horizon: Horizon,
+    bot: BotClient,
+}
+
+impl PriceTrigger {
+    pub fn new(horizon: Horizon, bot: BotClient) -> Self {
+        Self { horizon, bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for PriceTrigger {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: PriceTriggerConfig = ctx.parse_config()?;
+        let base = parse_asset(&config.base)?;
+        let counter = parse_asset(&config.counter)?;
+        let threshold = parse_price(&config.price)?;
+        let interval = parse_interval("interval", &config.interval)?;
+        let timeout = parse_interval("timeout", &config.timeout)?;
+        let (condition, direction) = match config.condition {
+            PriceDirection::Above => (PriceCondition::Above(threshold), "above"),
+            PriceDirection::Below => (PriceCondition::Below(threshold), "below"),
+        };
+        let (source, feed) = match config.source {
+            PriceFeed::Mid => (PriceSource::Mid, "mid"),
+            PriceFeed::Bid => (PriceSource::Bid, "bid"),
+            PriceFeed::Ask => (PriceSource::Ask, "ask"),
+            PriceFeed::Last => (PriceSource::Last, "last"),
+        };
+        let watch = PriceWatch::new(base.clone(), counter.clone(), condition)
+            .with_source(source)
+            .with_interval(interval);
+
+        let price = match tokio::time::timeout(timeout, self.horizon.watch_price(&watch)).await {
+            Ok(Ok(price)) => price,
+            Ok(Err(err)) => return Err(NodeError::Failed(err.to_string())),
+            Err(_) => {
+                return Err(NodeError::Failed(format!(
+                    "{} did not go {direction} {threshold} {} within {}",
+                    base.code(),
+                    counter.code(),
+                    config.timeout.trim(),
+                )))
+            }
+        };
+
+        let message = format!(
+            "📈 **Price Alert!**\n\n\
+             **{}:** {price} {}\n\
+             **Condition:** {direction} {threshold} ({feed})",
+            base.code(),
+            counter.code(),
+        );
+        self.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("base".into(), json!(base));
+        output.insert("counter".into(), json!(counter));
+        output.insert("price".into(), json!(price));
+        output.insert("threshold".into(), json!(threshold));
+        output.insert("condition".into(), json!(direction));
+        output.insert("source".into(), json!(feed));
+        Ok(output.into())
+    }
+}
+
+/// `manage-sell-offer`: places, updates or cancels an offer from the
+/// chat's wallet selling a fixed amount of one asset for another.
+#[derive(Clone)]
+pub struct ManageSellOffer {
+    stellar: Stellar,
+}
+
+impl ManageSellOffer {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for ManageSellOffer {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: ManageSellOfferConfig = ctx.parse_config()?;
+        let order = LimitOrder::sell(
+            parse_asset(&config.selling)?,
+            parse_amount(&config.amount)?,
+            parse_asset(&config.buying)?,
+            parse_price(&config.price)?,
+        )
+        .with_offer_id(parse_offer_id(&config.offer_id)?);
+        manage_offer(&self.stellar, &chat_id, &order).await
+    }
+}
+
+/// `manage-buy-offer`: places, updates or cancels an offer from the
+/// chat's wallet buying a fixed amount of one asset with another.
+#[derive(Clone)]
+pub struct ManageBuyOffer {
+    stellar: Stellar,
+}
+
+impl ManageBuyOffer {
+    pub fn new(stellar: Stellar) -> Self {
+        Self { stellar }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for ManageBuyOffer {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: ManageBuyOfferConfig = ctx.parse_config()?;
+        let order = LimitOrder::buy(
+            parse_asset(&config.buying)?,
+            parse_amount(&config.amount)?,
+            parse_asset(&config.selling)?,
+            parse_price(&config.price)?,
+        )
+        .with_offer_id(parse_offer_id(&config.offer_id)?);
+        manage_offer(&self.stellar, &chat_id, &order).await
+    }
+}
+
+fn parse_amount(text: &str) -> Result<Amount, NodeError> {
+    text.trim()
+        .parse::<Amount>()
+        .map_err(|err| NodeError::Config(err.to_string()))
+}
+
+/// Empty is a new offer.
+fn parse_offer_id(text: &str) -> Result<i64, NodeError> {
+    match text.trim() {
+        "" => Ok(0),
+        text => text
+            .parse()
+            .map_err(|_| NodeError::Config(format!("Invalid offer ID `{text}`"))),
+    }
+}
+
+async fn manage_offer(
+    stellar: &Stellar,
+    chat_id: &str,
+    order: &LimitOrder,
+) -> Result<NodeOutput, NodeError> {
+    let wallet = stellar.wallet(chat_id).await?;
+    // What is sold goes to the order book, not to an account: the policy
+    // sees it paid to the issuer of what comes back, or of what is sold
+    // when that is XLM.
+    let check = if order.is_cancel() {
+        PolicyCheck::unchecked()
+    } else {
+        let counterparty = order
+            .buying
+            .issuer()
+            .or(order.selling.issuer())
+            .unwrap_or_default();
+        stellar
+            .check_policy(chat_id, counterparty, &order.selling, order.most_sold())
+            .await?
+    };
+    let placed = match stellar.queue.manage_offer(wallet.as_ref(), order).await {
+        Ok(placed) => placed,
+        Err(err) => {
+            stellar.release(&check).await;
+            stellar
+                .bot
+                .notify(chat_id, &format!("❌ **Offer Failed**\n\n{err}"))
+                .await;
+            return Err(NodeError::Failed(err.to_string()));
+        }
+    };
+
+    let (selling, buying) = (order.selling.code(), order.buying.code());
+    let (side, terms) = match order.side {
+        Side::Sell => (
+            "sell",
+            format!(
+                "**Selling:** {} {selling}\n**Price:** {} {buying} per {selling}\n",
+                order.amount, order.price
+            ),
+        ),
+        Side::Buy => (
+            "buy",
+            format!(
+                "**Buying:** {} {buying}\n**Price:** {} {selling} per {buying}\n",
+                order.amount, order.price
+            ),
+        ),
+    };
+    let mut message = match placed.offer_id {
+        _ if order.is_cancel() => format!(
+            "✅ **Offer Cancelled**\n\n**Offer ID:** {}\n",
+            order.offer_id
+        ),
+        None => format!("✅ **Offer Filled!**\n\n{terms}"),
+        Some(id) if id == order.offer_id => {
+            format!("✅ **Offer Updated!**\n\n{terms}**Offer ID:** {id}\n")
+        }
+        Some(id) => format!("✅ **Offer Placed!**\n\n{terms}**Offer ID:** {id}\n"),
+    };
+    if placed.sold.is_positive() {
+        message.push_str(&format!(
+            "**Traded:** {} {selling} for {} {buying}\n",
+            placed.sold, placed.bought
+        ));
+    }
+    message.push_str(&format!("**Transaction:** `{}`", shorten(&placed.hash)));
+    stellar.bot.notify(chat_id, &message).await;
+
+    let mut output = Payload::new();
+    output.insert("success".into(), Value::Bool(true));
+    output.insert("chatId".into(), json!(chat_id));
+    output.insert("side".into(), json!(side));
+    output.insert("selling".into(), json!(order.selling));
+    output.insert("buying".into(), json!(order.buying));
+    output.insert("price".into(), json!(order.price));
+    output.insert("offerId".into(), json!(placed.offer_id));
+    output.insert("cancelled".into(), json!(order.is_cancel()));
+    output.insert("amount".into(), json!(placed.amount));
+    output.insert("sold".into(), json!(placed.sold));
+    output.insert("bought".into(), json!(placed.bought));
+    output.insert("hash".into(), json!(placed.hash));
+    output.insert("ledger".into(), json!(placed.ledger));
+    Ok(output.into())
+}
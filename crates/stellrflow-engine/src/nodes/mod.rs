@@ -0,0 +1,92 @@
+//! Executors for the built-in node types, ported from `nodeExecutors` in
+//! `frontend/lib/utils/api-service.ts`.
+//!
+//! Everything except `delay` and `condition` talks to the Telegram bot's REST API through a
+//! shared [`BotClient`]; messages sent to the user are kept word-for-word.
+//! `trustline`, `send-asset`, `convert-pay`, the two offer nodes and the
+//! two claimable balance nodes are new: they sign with the chat's wallet and submit through a
+//! [`SubmissionQueue`](stellrflow_stellar::SubmissionQueue) themselves, and only notify the user through the
+//! bot. `price-trigger` reads prices from the queue's Horizon. Those that
+//! spend from the wallet first ask the spending policy service through a
+//! [`PolicyClient`], when [`Stellar`] has one.
+
+mod anchor;
+mod assets;
+mod bot;
+mod claimable;
+mod condition;
+mod delay;
+mod market;
+mod payments;
+mod policy;
+mod stellar;
+mod telegram;
+
+use serde_json::Value;
+use stellrflow_template::shorten;
+
+pub use anchor::{AnchorOffRamp, AnchorOnRamp};
+pub use assets::{ConvertPay, SendAsset, Stellar, Trustline, Wallets};
+pub use bot::{BotClient, BotResponse, DEFAULT_APP_URL, DEFAULT_BOT_URL};
+pub use claimable::{ClaimClaimableBalance, CreateClaimableBalance};
+pub use condition::Condition;
+pub use delay::Delay;
+pub use market::{ManageBuyOffer, ManageSellOffer, PriceTrigger};
+pub use payments::{AutoPay, Multisig};
+pub use policy::{PolicyCheck, PolicyClient};
+pub use stellar::{StellarSdk, WalletIntegration};
+pub use telegram::{TelegramSend, TelegramTrigger};
+
+use crate::executor::ExecutorRegistry;
+
+impl ExecutorRegistry {
+    /// A registry with every built-in node type, talking to `bot`.
+    pub fn builtin(bot: BotClient) -> Self {
+        let mut registry = Self::new();
+        registry
+            .register("telegram-trigger", TelegramTrigger::new(bot.clone()))
+            .register("telegram-send", TelegramSend::new(bot.clone()))
+            .register("stellar-sdk", StellarSdk::new(bot.clone()))
+            .register("wallet-integration", WalletIntegration::new(bot.clone()))
+            .register("anchor-onramp", AnchorOnRamp::new(bot.clone()))
+            .register("anchor-offramp", AnchorOffRamp::new(bot.clone()))
+            .register("autopay", AutoPay::new(bot.clone()))
+            .register("multisig", Multisig::new(bot))
+            .register("delay", Delay)
+            .register("condition", Condition);
+        registry
+    }
+
+    /// Registers `trustline`, `send-asset`, `convert-pay`,
+    /// `manage-sell-offer`, `manage-buy-offer`, `create-claimable-balance`
+    /// and `claim-claimable-balance`, which sign with each chat's wallet
+    /// and submit through `stellar`'s queue, and `price-trigger`, which
+    /// watches prices on the queue's Horizon.
+    pub fn register_stellar(&mut self, stellar: Stellar) -> &mut Self {
+        let horizon = stellar.queue.horizon().clone();
+        let bot = stellar.bot.clone();
+        self.register("trustline", Trustline::new(stellar.clone()))
+            .register("send-asset", SendAsset::new(stellar.clone()))
+            .register("convert-pay", ConvertPay::new(stellar.clone()))
+            .register("manage-sell-offer", ManageSellOffer::new(stellar.clone()))
+            .register("manage-buy-offer", ManageBuyOffer::new(stellar.clone()))
+            .register(
+                "create-claimable-balance",
+                CreateClaimableBalance::new(stellar.clone()),
+            )
+            .register(
+                "claim-claimable-balance",
+                ClaimClaimableBalance::new(stellar),
+            )
+            .register("price-trigger", PriceTrigger::new(horizon, bot))
+    }
+}
+
+/// A JSON value as JavaScript would interpolate it into a template string.
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "undefined".into(),
+        other => other.to_string(),
+    }
+}
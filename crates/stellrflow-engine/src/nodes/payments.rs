@@ -0,0 +1,237 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use chrono::DateTime;
+use serde_json::{json, Value};
+
+use stellrflow_amount::{Amount, STROOPS_PER_XLM};
+use stellrflow_nodes::{AutoPayConfig, MultisigConfig};
+use stellrflow_recurrence::{preview, Preview, Recurrence, Schedule};
+
+use super::{display, shorten, BotClient};
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+const HOUR_MS: u64 = 3_600_000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+/// How many payment dates the AutoPay confirmation lists.
+const LISTED_PAYMENTS: usize = 3;
+
+/// `autopay`: registers a recurring XLM payment with the bot's scheduler.
+#[derive(Debug, Clone)]
+pub struct AutoPay {
+    bot: BotClient,
+}
+
+impl AutoPay {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for AutoPay {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: AutoPayConfig = ctx.parse_config()?;
+        let destination = config.destination.trim().to_string();
+        if destination.is_empty() {
+            return Err(NodeError::Config(
+                "Destination address is required for AutoPay".into(),
+            ));
+        }
+        let amount = match config.amount.trim() {
+            "" => Amount::from_stroops(10 * STROOPS_PER_XLM),
+            text => text
+                .parse::<Amount>()
+                .map_err(|err| NodeError::Config(err.to_string()))?,
+        };
+        let interval = config.interval.as_str();
+        // The bot schedules whole days.
+        let duration_ms = config.duration_ms().map_err(NodeError::Config)?;
+        let duration = duration_ms.div_ceil(DAY_MS);
+        let preview = preview_from_now(interval, amount, duration);
+        if let Some(Preview {
+            payments,
+            total: None,
+            ..
+        }) = preview
+        {
+            return Err(NodeError::Config(format!(
+                "{payments} payments of {amount} XLM add up to more than {} XLM",
+                Amount::MAX
+            )));
+        }
+
+        let body = json!({
+            "chatId": chat_id,
+            "destination": destination,
+            "amount": amount,
+            "interval": interval,
+            "duration": duration,
+        });
+        let response = self.bot.post("/api/autopay/create", &body).await?;
+        if !response.is_success() {
+            let error = response.error_or("Unable to create scheduled payment.");
+            self.bot
+                .notify(&chat_id, &format!("❌ **AutoPay Setup Failed**\n\n{error}"))
+                .await;
+            return Err(NodeError::Failed(response.error_or("AutoPay setup failed")));
+        }
+        let result = response.0;
+
+        let schedule_id = result.get("scheduleId").cloned().unwrap_or(Value::Null);
+        let mut message = format!(
+            "✅ **AutoPay Activated!**\n\n\
+             **Amount:** {amount} XLM\n\
+             **To:** `{}`\n\
+             **Frequency:** {}\n\
+             **Duration:** {duration} days\n",
+            shorten(&destination),
+            preview
+                .as_ref()
+                .map_or(interval, |preview| &preview.description),
+        );
+        if let Some(preview) = &preview {
+            let dates: Vec<String> = preview
+                .next
+                .iter()
+                .filter_map(|at| DateTime::from_timestamp_millis(*at))
+                .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+                .collect();
+            if !dates.is_empty() {
+                message.push_str(&format!("**Next payments:** {}\n", dates.join(", ")));
+            }
+            if let Some(total) = preview.total {
+                message.push_str(&format!(
+                    "**Total:** {total} XLM over {} payments\n",
+                    preview.payments
+                ));
+            }
+        }
+        message.push_str(&format!(
+            "**Schedule ID:** `{}`\n\n\
+             Use /autopay to manage your schedules.",
+            display(&schedule_id),
+        ));
+        self.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("scheduleId".into(), schedule_id);
+        output.insert("destination".into(), json!(destination));
+        output.insert("amount".into(), json!(amount));
+        output.insert("interval".into(), json!(interval));
+        output.insert("duration".into(), json!(duration));
+        output.insert(
+            "nextPayment".into(),
+            result.get("nextPayment").cloned().unwrap_or(Value::Null),
+        );
+        if let Some(preview) = preview {
+            output.insert("payments".into(), json!(preview.payments));
+            output.insert(
+                "totalAmount".into(),
+                json!(preview.total.map(|total| total.to_string())),
+            );
+        }
+        Ok(output.into())
+    }
+}
+
+/// The payments a schedule created now would make over `duration_days`;
+/// `None` when the bot took an interval this crate does not read.
+fn preview_from_now(interval: &str, amount: Amount, duration_days: u64) -> Option<Preview> {
+    let recurrence = Recurrence::parse(interval).ok()?;
+    let start = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|now| i64::try_from(now.as_millis()).ok())?;
+    let end = i64::try_from(duration_days.checked_mul(DAY_MS)?)
+        .ok()
+        .and_then(|ms| start.checked_add(ms))?;
+    let schedule = Schedule {
+        recurrence,
+        start,
+        end,
+        amount,
+    };
+    Some(preview(&schedule, LISTED_PAYMENTS))
+}
+
+/// `multisig`: registers an approval requirement with the bot.
+#[derive(Debug, Clone)]
+pub struct Multisig {
+    bot: BotClient,
+}
+
+impl Multisig {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for Multisig {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.require_chat_id()?;
+        let config: MultisigConfig = ctx.parse_config()?;
+        let threshold = config.threshold;
+        // The bot counts the timeout in whole hours.
+        let timeout = config
+            .timeout_ms()
+            .map_err(NodeError::Config)?
+            .div_ceil(HOUR_MS);
+        let signers = config.signers;
+
+        if signers.len() < threshold as usize {
+            return Err(NodeError::Config(format!(
+                "Need at least {threshold} signers configured"
+            )));
+        }
+
+        let body = json!({
+            "chatId": chat_id,
+            "threshold": threshold,
+            "signers": signers,
+            "timeout": timeout,
+        });
+        let response = self.bot.post("/api/multisig/create", &body).await?;
+        if !response.is_success() {
+            let error = response.error_or("Unable to configure multisig.");
+            self.bot
+                .notify(
+                    &chat_id,
+                    &format!("❌ **Multisig Setup Failed**\n\n{error}"),
+                )
+                .await;
+            return Err(NodeError::Failed(
+                response.error_or("Multisig setup failed"),
+            ));
+        }
+        let result = response.0;
+
+        let multisig_id = result.get("multisigId").cloned().unwrap_or(Value::Null);
+        let message = format!(
+            "✅ **Multisig Configured!**\n\n\
+             **Threshold:** {threshold} of {} signatures required\n\
+             **Approval Timeout:** {timeout} hours\n\
+             **Multisig ID:** `{}`\n\n\
+             Transactions will require {threshold} approvals before execution.",
+            signers.len(),
+            display(&multisig_id),
+        );
+        self.bot.notify(&chat_id, &message).await;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("multisigId".into(), multisig_id);
+        output.insert("threshold".into(), json!(threshold));
+        output.insert("signerCount".into(), json!(signers.len()));
+        output.insert("timeout".into(), json!(timeout));
+        Ok(output.into())
+    }
+}
@@ -0,0 +1,134 @@
+use serde_json::{json, Value};
+
+use stellrflow_amount::Amount;
+
+use super::BotResponse;
+
+/// Client for the spending policy service (`crates/stellrflow-policy`),
+/// asked before any node signs a payment, as `checkPolicy` in
+/// `telegram-bot.ts` does.
+#[derive(Debug, Clone)]
+pub struct PolicyClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+/// What the policy service said about a payment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyCheck {
+    pub allowed: bool,
+    /// `allow`, `deny` or `requireMultisig`.
+    pub outcome: String,
+    pub reason: String,
+    /// The recorded decision, released if the payment is not made.
+    pub decision_id: Option<i64>,
+}
+
+impl PolicyCheck {
+    /// What a chat is held to when no policy service is configured.
+    pub fn unchecked() -> Self {
+        Self {
+            allowed: true,
+            outcome: "allow".into(),
+            reason: "no spending policy configured".into(),
+            decision_id: None,
+        }
+    }
+
+    pub(super) fn deny(reason: String) -> Self {
+        Self {
+            allowed: false,
+            outcome: "deny".into(),
+            reason,
+            decision_id: None,
+        }
+    }
+
+    /// The chat message for a payment the policy did not allow.
+    pub fn message(&self) -> String {
+        if self.outcome == "requireMultisig" {
+            format!("🔐 **Multisig Approval Required**\n\n{}", self.reason)
+        } else {
+            format!(
+                "🚫 **Payment Blocked by Spending Policy**\n\n{}",
+                self.reason
+            )
+        }
+    }
+}
+
+impl PolicyClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Asks whether a workflow may pay `amount` XLM from `chat_id`'s wallet
+    /// to `destination`. A service that cannot be reached denies it.
+    pub async fn check(&self, chat_id: &str, destination: &str, amount: Amount) -> PolicyCheck {
+        let body = json!({
+            "chatId": chat_id,
+            "destination": destination,
+            "amount": amount,
+            "source": "workflow",
+        });
+        let response = match self
+            .http
+            .post(format!("{}/api/policy/check", self.base_url))
+            .json(&body)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => {
+                return PolicyCheck::deny(format!("Spending policy service unavailable: {err}"))
+            }
+        };
+        let status = response.status();
+        let result = match response.json().await {
+            Ok(result) => BotResponse(result),
+            Err(err) => {
+                return PolicyCheck::deny(format!("Spending policy service unavailable: {err}"))
+            }
+        };
+        if !status.is_success() || !result.is_success() {
+            return PolicyCheck::deny(result.error_or("Policy check failed"));
+        }
+        let decision = result.0.get("decision");
+        let field = |key: &str| decision.and_then(|decision| decision.get(key));
+        PolicyCheck {
+            allowed: result.0.get("allowed").and_then(Value::as_bool) == Some(true),
+            outcome: field("outcome")
+                .and_then(Value::as_str)
+                .unwrap_or("deny")
+                .to_string(),
+            reason: field("reason")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            decision_id: field("id").and_then(Value::as_i64),
+        }
+    }
+
+    /// Tells the service an allowed payment was not made, so it stops
+    /// counting towards the wallet's limits.
+    pub async fn release(&self, check: &PolicyCheck) {
+        let Some(id) = check.decision_id else {
+            return;
+        };
+        let _ = self
+            .http
+            .post(format!(
+                "{}/api/policy/decisions/{id}/release",
+                self.base_url
+            ))
+            .send()
+            .await;
+    }
+}
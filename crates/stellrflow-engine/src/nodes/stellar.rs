@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use stellrflow_nodes::{SdkOperation, StellarSdkConfig, WalletIntegrationConfig, WalletProvider};
+
+use super::{display, shorten, BotClient};
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+/// `stellar-sdk`: either switches the chat to chatbot mode or reports the
+/// XLM balance of `config.destination` (falling back to the payload's
+/// `destination`/`address`).
+#[derive(Debug, Clone)]
+pub struct StellarSdk {
+    bot: BotClient,
+}
+
+impl StellarSdk {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for StellarSdk {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.input_str("chatId").ok_or_else(|| {
+            NodeError::Config(
+                "Chat ID required. Connect this block to a Telegram trigger first.".into(),
+            )
+        })?;
+        let config: StellarSdkConfig = ctx.parse_config()?;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+
+        if config.operation == SdkOperation::Chatbot {
+            self.bot.notify(&chat_id, CHATBOT_MESSAGE).await;
+            output.insert("operation".into(), json!("chatbot"));
+            output.insert("chatId".into(), json!(chat_id));
+            output.insert(
+                "message".into(),
+                json!("Stellar AI Chatbot is now active. User can ask questions in Telegram."),
+            );
+            return Ok(output.into());
+        }
+
+        let destination = Some(config.destination.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .or_else(|| ctx.input_str("destination"))
+            .or_else(|| ctx.input_str("address"))
+            .ok_or_else(|| {
+                NodeError::Config("Stellar address is required for balance check".into())
+            })?;
+        let network = config.network.as_str();
+
+        let result = self
+            .bot
+            .get(&format!("/api/stellar/balance/{destination}"))
+            .await?
+            .into_success("Failed to fetch balance")?;
+        let balance = result.get("balance").cloned().unwrap_or(Value::Null);
+
+        let message = format!(
+            "💰 **Balance Check**\n\n\
+             **Address:** `{}`\n\
+             **Balance:** {} XLM\n\n\
+             Network: {network}",
+            shorten(&destination),
+            display(&balance),
+        );
+        self.bot.notify(&chat_id, &message).await;
+
+        let address = result
+            .get("address")
+            .and_then(Value::as_str)
+            .filter(|a| !a.is_empty())
+            .unwrap_or(&destination);
+        output.insert("operation".into(), json!("balance"));
+        output.insert("address".into(), json!(address));
+        output.insert("balance".into(), balance);
+        output.insert("network".into(), json!(network));
+        output.insert("chatId".into(), json!(chat_id));
+        Ok(output.into())
+    }
+}
+
+const CHATBOT_MESSAGE: &str = "🤖 **Stellar AI Chatbot Activated!**\n\n\
+     I can now answer your questions about Stellar!\n\n\
+     **Try asking:**\n\
+     • What is Stellar?\n\
+     • How does Soroban work?\n\
+     • What are Stellar anchors?\n\
+     • Tell me about XLM\n\n\
+     **Commands:**\n\
+     /balance <address> - Check any address balance\n\
+     /help - Show all commands\n\n\
+     _Just type your question and I'll help!_";
+
+/// `wallet-integration`: creates an in-bot wallet (`walletProvider:
+/// "telegram"`) or sends a Freighter connect link.
+#[derive(Debug, Clone)]
+pub struct WalletIntegration {
+    bot: BotClient,
+}
+
+impl WalletIntegration {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for WalletIntegration {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx
+            .input_str("chatId")
+            .or_else(|| ctx.config_str("chatId"))
+            .ok_or_else(|| {
+                NodeError::Config("Connect this block to a Telegram trigger first.".into())
+            })?;
+        let config: WalletIntegrationConfig = ctx.parse_config()?;
+        let network = config.network.as_str();
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+
+        if config.wallet_provider == WalletProvider::Telegram {
+            let created = self
+                .bot
+                .post("/api/wallet/create", &json!({ "chatId": chat_id }))
+                .await?
+                .into_success("Failed to create Telegram wallet")?;
+            let public_key = created
+                .get("publicKey")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let is_new = created.get("isNew").and_then(Value::as_bool) == Some(true);
+
+            let message = if is_new {
+                format!(
+                    "🎉 **Your Stellar Wallet is Ready!**\n\n\
+                     **Address:**\n`{public_key}`\n\n\
+                     📱 **Wallet Commands:**\n\
+                     /mybalance - Check your balance\n\
+                     /mywallet - Show your address\n\
+                     /send <address> <amount> - Send XLM\n\
+                     /fundwallet - Get free testnet XLM\n\
+                     /disconnect - Disconnect wallet\n\n\
+                     Network: {network}\n\n\
+                     _Your wallet is securely stored in the bot._"
+                )
+            } else {
+                format!(
+                    "👛 **Wallet Already Created!**\n\n\
+                     **Address:**\n`{public_key}`\n\n\
+                     Use /mybalance to check your balance."
+                )
+            };
+            self.bot.notify(&chat_id, &message).await;
+
+            output.insert("mode".into(), json!("telegram-wallet"));
+            output.insert("walletProvider".into(), json!("telegram"));
+            output.insert("publicKey".into(), json!(public_key));
+            output.insert("isNew".into(), json!(is_new));
+            output.insert("chatId".into(), json!(chat_id));
+            output.insert("network".into(), json!(network));
+            output.insert(
+                "message".into(),
+                json!(if is_new {
+                    "Telegram wallet created"
+                } else {
+                    "Telegram wallet already exists"
+                }),
+            );
+            return Ok(output.into());
+        }
+
+        let wallet_url = format!(
+            "{}/connect-wallet?chatId={chat_id}&network={network}",
+            self.bot.app_url()
+        );
+        let message = format!(
+            "🦊 <b>Freighter Wallet Integration</b>\n\n\
+             Connect your Freighter browser extension wallet to Stellar:\n\n\
+             👉 <a href=\"{wallet_url}\">Click here to connect</a>\n\n\
+             <b>After connecting you can:</b>\n\
+             • View your wallet balances\n\
+             • Sign and approve transactions\n\
+             • Interact with Stellar dApps\n\n\
+             <b>Requirements:</b>\n\
+             • Freighter extension installed\n\
+             • Open link in browser with Freighter\n\n\
+             Network: {network}\n\n\
+             🔗 Get Freighter: <a href=\"https://freighter.app\">freighter.app</a>"
+        );
+        let _ = self.bot.send_message(&chat_id, &message, "HTML").await;
+
+        output.insert("mode".into(), json!("freighter-wallet"));
+        output.insert("walletProvider".into(), json!("freighter"));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("walletUrl".into(), json!(wallet_url));
+        output.insert("network".into(), json!(network));
+        output.insert(
+            "message".into(),
+            json!("Freighter connection link sent to user"),
+        );
+        Ok(output.into())
+    }
+}
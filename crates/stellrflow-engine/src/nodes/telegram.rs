@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use stellrflow_nodes::TelegramSendConfig;
+
+use super::BotClient;
+use crate::error::NodeError;
+use crate::executor::{NodeContext, NodeExecutor, NodeOutput};
+use crate::graph::Payload;
+
+/// `telegram-trigger`: registers the chat's session with the features the
+/// connected blocks need and greets the user.
+#[derive(Debug, Clone)]
+pub struct TelegramTrigger {
+    bot: BotClient,
+}
+
+impl TelegramTrigger {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for TelegramTrigger {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx.config_str("chatId").ok_or_else(|| {
+            NodeError::Config(
+                "Telegram Chat ID is required. Send /register to the bot to get yours.".into(),
+            )
+        })?;
+        if chat_id.starts_with('@') {
+            return Err(NodeError::Config(
+                "Use your numeric Chat ID, not username. Open the bot in Telegram and send \
+                 /register to get your Chat ID."
+                    .into(),
+            ));
+        }
+
+        let connected = |t: &str| ctx.downstream_types.contains(&t);
+        let has_chatbot = connected("stellar-sdk");
+        let has_wallet = connected("wallet-integration");
+        let has_send = connected("telegram-send");
+
+        let mut features = Vec::new();
+        if has_chatbot {
+            features.push("chatbot");
+        }
+        if has_wallet {
+            features.push("wallet");
+        }
+        if has_send {
+            features.push("telegram-send");
+        }
+
+        // Like the browser, a failed registration is not fatal: the message
+        // below is what tells us whether the bot is reachable.
+        let _ = self
+            .bot
+            .post(
+                "/api/session/register",
+                &json!({ "chatId": chat_id, "features": features }),
+            )
+            .await;
+
+        let message = if ctx.downstream_types.is_empty() {
+            WELCOME_MESSAGE.to_string()
+        } else {
+            let mut enabled = Vec::new();
+            if has_chatbot {
+                enabled.push("✅ Stellar AI Chatbot");
+            }
+            if has_wallet {
+                enabled.push("✅ Wallet Integration");
+            }
+            if has_send {
+                enabled.push("✅ Notifications");
+            }
+            format!(
+                "🚀 **StellrFlow Connected!**\n\n\
+                 Your workflow is now active with:\n{}\n\n\
+                 _Setting up features..._",
+                enabled.join("\n")
+            )
+        };
+        self.bot
+            .send_message(&chat_id, &message, "Markdown")
+            .await?
+            .into_success("Failed to send message. Is the bot running?")?;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("chatId".into(), json!(chat_id));
+        output.insert("features".into(), json!(features));
+        output.insert("message".into(), json!("Connected successfully"));
+        Ok(output.into())
+    }
+}
+
+const WELCOME_MESSAGE: &str = "🎉 **Connected to StellrFlow!**\n\n\
+     Your Telegram is now linked to StellrFlow.\n\n\
+     ⚠️ _No workflow blocks connected yet._\n\n\
+     Add blocks in the workflow builder to enable features:\n\
+     • **Stellar SDK (Chatbot)** - Ask questions about Stellar\n\
+     • **Wallet Integration** - Connect Freighter wallet\n\
+     • **Send Telegram** - Send notifications\n\n\
+     _Powered by Stellar_";
+
+/// `telegram-send`: renders `config.message` as a template against the
+/// incoming payload and sends it to the chat. A variable the payload lacks
+/// fails the node instead of sending a half-filled message.
+///
+/// Unlike the browser version the result does not echo `inputData`; the
+/// engine already forwards it downstream.
+#[derive(Debug, Clone)]
+pub struct TelegramSend {
+    bot: BotClient,
+}
+
+impl TelegramSend {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for TelegramSend {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let chat_id = ctx
+            .chat_id()
+            .ok_or_else(|| NodeError::Config("Telegram Chat ID is required".into()))?;
+
+        let template = ctx.parse_config::<TelegramSendConfig>()?.message;
+        let message = stellrflow_template::render(&template, ctx.input)
+            .map_err(|err| NodeError::Failed(format!("Message template: {err}")))?;
+
+        let text = if message.is_empty() {
+            "Notification from StellrFlow"
+        } else {
+            &message
+        };
+        self.bot
+            .send_message(&chat_id, text, "Markdown")
+            .await?
+            .into_success("Failed to send message")?;
+
+        let mut output = Payload::new();
+        output.insert("success".into(), Value::Bool(true));
+        output.insert("sentTo".into(), json!(chat_id));
+        output.insert("message".into(), json!(message));
+        Ok(output.into())
+    }
+}
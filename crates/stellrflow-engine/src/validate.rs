@@ -0,0 +1,541 @@
+//! Static checks run before a workflow is started.
+//!
+//! [`validate`] never stops at the first problem: it returns every
+//! [`Diagnostic`] it finds, each carrying the IDs of the nodes and edges
+//! involved so the builder can highlight them. Errors block a run;
+//! warnings are informational.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stellrflow_nodes::{NodeSchema, SchemaRegistry};
+
+use crate::graph::Workflow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCode {
+    /// The workflow has no nodes.
+    Empty,
+    /// Two nodes share an ID.
+    DuplicateNode,
+    /// An edge points at a node that does not exist.
+    DanglingEdge,
+    /// No executor is registered for a node's type.
+    UnknownNodeType,
+    /// A set of nodes feed into each other.
+    Cycle,
+    /// The workflow has no trigger node.
+    NoTrigger,
+    /// The workflow has more than one trigger node.
+    MultipleTriggers,
+    /// A node cannot be reached from any trigger.
+    Unreachable,
+    /// A config key the node type requires is missing or empty.
+    MissingConfig,
+    /// A config does not match its node type's schema.
+    InvalidConfig,
+    /// An edge leaves a node with named outputs from a handle it does not
+    /// have.
+    UnknownHandle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edge_ids: Vec<String>,
+    /// The offending key, for [`DiagnosticCode::MissingConfig`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_key: Option<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        severity: Severity,
+        code: DiagnosticCode,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            node_ids: Vec::new(),
+            edge_ids: Vec::new(),
+            config_key: None,
+        }
+    }
+
+    pub(crate) fn error(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    pub(crate) fn warning(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, message)
+    }
+
+    pub(crate) fn nodes<I, S>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.node_ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub(crate) fn edge(mut self, id: &str) -> Self {
+        self.edge_ids.push(id.to_string());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Everything [`validate`] found, in discovery order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validation {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Validation {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| d.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|d| !d.is_error())
+    }
+
+    /// Diagnostics with the given code.
+    pub fn with_code(&self, code: DiagnosticCode) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.code == code)
+    }
+
+    pub(crate) fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// Lists the errors, separated by `; `.
+impl fmt::Display for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, d) in self.errors().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{d}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What the validator needs to know about a node type.
+#[derive(Debug, Clone, Default)]
+pub struct NodeRule {
+    /// Whether the type starts a workflow.
+    pub trigger: bool,
+    /// Config keys that must be present and non-empty.
+    pub required_config: Vec<String>,
+    /// Named output handles; if non-empty, every outgoing edge must leave
+    /// from one of them.
+    pub outputs: Vec<String>,
+    /// If set, configs must normalize against it, and required keys are
+    /// checked on the normalized config.
+    pub schema: Option<NodeSchema>,
+}
+
+impl NodeRule {
+    pub fn trigger(required_config: &[&str]) -> Self {
+        Self {
+            trigger: true,
+            required_config: required_config.iter().map(|k| k.to_string()).collect(),
+            outputs: Vec::new(),
+            schema: None,
+        }
+    }
+
+    pub fn action(required_config: &[&str]) -> Self {
+        Self {
+            trigger: false,
+            ..Self::trigger(required_config)
+        }
+    }
+}
+
+impl From<NodeSchema> for NodeRule {
+    fn from(schema: NodeSchema) -> Self {
+        Self {
+            trigger: schema.is_trigger(),
+            required_config: schema.required.iter().map(|k| k.to_string()).collect(),
+            outputs: schema.outputs.iter().map(|k| k.to_string()).collect(),
+            schema: Some(schema),
+        }
+    }
+}
+
+/// [`NodeRule`]s by `NodeData.type`. Types without a rule are treated as
+/// actions with no required config.
+#[derive(Debug, Clone, Default)]
+pub struct NodeRules {
+    rules: HashMap<String, NodeRule>,
+}
+
+impl NodeRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules for every schema in [`SchemaRegistry::builtin`].
+    pub fn builtin() -> Self {
+        Self::from_schemas(&SchemaRegistry::builtin())
+    }
+
+    pub fn from_schemas(schemas: &SchemaRegistry) -> Self {
+        let mut rules = Self::new();
+        for schema in schemas.iter() {
+            rules.insert(schema.node_type, NodeRule::from(*schema));
+        }
+        rules
+    }
+
+    pub fn insert(&mut self, node_type: impl Into<String>, rule: NodeRule) -> &mut Self {
+        self.rules.insert(node_type.into(), rule);
+        self
+    }
+
+    pub fn get(&self, node_type: &str) -> Option<&NodeRule> {
+        self.rules.get(node_type)
+    }
+
+    pub fn is_trigger(&self, node_type: &str) -> bool {
+        self.get(node_type).is_some_and(|r| r.trigger)
+    }
+}
+
+/// Check `workflow` against `rules`.
+pub fn validate(workflow: &Workflow, rules: &NodeRules) -> Validation {
+    let mut out = Validation::default();
+
+    if workflow.nodes.is_empty() {
+        out.push(Diagnostic::error(
+            DiagnosticCode::Empty,
+            "workflow has no nodes",
+        ));
+        return out;
+    }
+
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for node in &workflow.nodes {
+        if !seen.insert(node.id.as_str()) && !duplicates.contains(&node.id.as_str()) {
+            duplicates.push(node.id.as_str());
+        }
+    }
+    for id in &duplicates {
+        out.push(
+            Diagnostic::error(
+                DiagnosticCode::DuplicateNode,
+                format!("duplicate node id `{id}`"),
+            )
+            .nodes([*id]),
+        );
+    }
+
+    for edge in &workflow.edges {
+        for end in [&edge.source, &edge.target] {
+            if !seen.contains(end.as_str()) {
+                out.push(
+                    Diagnostic::error(
+                        DiagnosticCode::DanglingEdge,
+                        format!("edge `{}` references unknown node `{end}`", edge.id),
+                    )
+                    .edge(&edge.id),
+                );
+            }
+        }
+    }
+
+    // Graph checks below assume node IDs are unique.
+    if !duplicates.is_empty() {
+        return out;
+    }
+
+    check_config(workflow, rules, &mut out);
+    check_handles(workflow, rules, &mut out);
+    check_cycles(workflow, &mut out);
+    check_triggers(workflow, rules, &mut out);
+    out
+}
+
+fn check_config(workflow: &Workflow, rules: &NodeRules, out: &mut Validation) {
+    for node in &workflow.nodes {
+        let Some(rule) = rules.get(&node.data.node_type) else {
+            continue;
+        };
+        let config = match &rule.schema {
+            None => Cow::Borrowed(&node.data.config),
+            Some(schema) => match schema.normalize(&node.data.config, node.data.config_version) {
+                Ok(config) => Cow::Owned(config),
+                Err(err) => {
+                    out.push(
+                        Diagnostic::error(
+                            DiagnosticCode::InvalidConfig,
+                            format!("`{}`: {err}", node.id),
+                        )
+                        .nodes([node.id.as_str()]),
+                    );
+                    continue;
+                }
+            },
+        };
+        for key in &rule.required_config {
+            if is_blank(config.get(key)) {
+                let mut d = Diagnostic::error(
+                    DiagnosticCode::MissingConfig,
+                    format!(
+                        "`{}` ({}) is missing required config `{key}`",
+                        node.id, node.data.node_type
+                    ),
+                )
+                .nodes([node.id.as_str()]);
+                d.config_key = Some(key.clone());
+                out.push(d);
+            }
+        }
+    }
+}
+
+fn check_handles(workflow: &Workflow, rules: &NodeRules, out: &mut Validation) {
+    for edge in &workflow.edges {
+        let Some(source) = workflow.node(&edge.source) else {
+            continue;
+        };
+        let Some(rule) = rules.get(&source.data.node_type) else {
+            continue;
+        };
+        if rule.outputs.is_empty()
+            || edge
+                .source_handle
+                .as_ref()
+                .is_some_and(|h| rule.outputs.contains(h))
+        {
+            continue;
+        }
+        let handle = match &edge.source_handle {
+            Some(handle) => format!("handle `{handle}`"),
+            None => "no handle".to_string(),
+        };
+        out.push(
+            Diagnostic::error(
+                DiagnosticCode::UnknownHandle,
+                format!(
+                    "edge `{}` leaves `{}` ({}) from {handle}; expected one of: {}",
+                    edge.id,
+                    source.id,
+                    source.data.node_type,
+                    rule.outputs.join(", ")
+                ),
+            )
+            .nodes([source.id.as_str()])
+            .edge(&edge.id),
+        );
+    }
+}
+
+fn is_blank(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::String(s)) => s.trim().is_empty(),
+        Some(Value::Array(a)) => a.is_empty(),
+        Some(Value::Object(o)) => o.is_empty(),
+        Some(_) => false,
+    }
+}
+
+/// Reports each strongly connected component that contains a cycle,
+/// including single nodes with an edge to themselves.
+fn check_cycles(workflow: &Workflow, out: &mut Validation) {
+    for component in strongly_connected(workflow) {
+        let self_loop = component.len() == 1
+            && workflow
+                .outgoing(component[0])
+                .any(|e| e.target == component[0]);
+        if component.len() > 1 || self_loop {
+            out.push(
+                Diagnostic::error(
+                    DiagnosticCode::Cycle,
+                    format!(
+                        "workflow contains a cycle through: {}",
+                        component.join(", ")
+                    ),
+                )
+                .nodes(component),
+            );
+        }
+    }
+}
+
+/// Tarjan's algorithm, iterative. Components come out with their nodes in
+/// document order, and are themselves ordered by their first node.
+fn strongly_connected(workflow: &Workflow) -> Vec<Vec<&str>> {
+    let index_of: HashMap<&str, usize> = workflow
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), i))
+        .collect();
+    let n = workflow.nodes.len();
+    let mut successors = vec![Vec::new(); n];
+    for edge in &workflow.edges {
+        if let (Some(&s), Some(&t)) = (
+            index_of.get(edge.source.as_str()),
+            index_of.get(edge.target.as_str()),
+        ) {
+            successors[s].push(t);
+        }
+    }
+
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next = 0;
+    let mut components = Vec::new();
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        // (node, position in its successor list)
+        let mut work = vec![(root, 0)];
+        while let Some(&(v, pos)) = work.last() {
+            if pos == 0 && index[v] == usize::MAX {
+                index[v] = next;
+                low[v] = next;
+                next += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&w) = successors[v].get(pos) {
+                if let Some(top) = work.last_mut() {
+                    top.1 += 1;
+                }
+                if index[w] == usize::MAX {
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+
+    components.sort_unstable_by_key(|c| c[0]);
+    components
+        .into_iter()
+        .map(|c| {
+            c.into_iter()
+                .map(|i| workflow.nodes[i].id.as_str())
+                .collect()
+        })
+        .collect()
+}
+
+fn check_triggers(workflow: &Workflow, rules: &NodeRules, out: &mut Validation) {
+    let triggers: Vec<&str> = workflow
+        .nodes
+        .iter()
+        .filter(|n| rules.is_trigger(&n.data.node_type))
+        .map(|n| n.id.as_str())
+        .collect();
+
+    match triggers.len() {
+        0 => {
+            out.push(Diagnostic::error(
+                DiagnosticCode::NoTrigger,
+                "workflow has no trigger node",
+            ));
+            return;
+        }
+        1 => {}
+        _ => out.push(
+            Diagnostic::warning(
+                DiagnosticCode::MultipleTriggers,
+                format!(
+                    "workflow has {} trigger nodes; each starts its own branch",
+                    triggers.len()
+                ),
+            )
+            .nodes(triggers.iter().copied()),
+        ),
+    }
+
+    let mut reached: HashSet<&str> = triggers.iter().copied().collect();
+    let mut queue: VecDeque<&str> = triggers.into_iter().collect();
+    while let Some(id) = queue.pop_front() {
+        for edge in workflow.outgoing(id) {
+            if reached.insert(edge.target.as_str()) {
+                queue.push_back(edge.target.as_str());
+            }
+        }
+    }
+
+    for node in &workflow.nodes {
+        if !reached.contains(node.id.as_str()) {
+            out.push(
+                Diagnostic::error(
+                    DiagnosticCode::Unreachable,
+                    format!(
+                        "`{}` ({}) is not connected to a trigger",
+                        node.id, node.data.node_type
+                    ),
+                )
+                .nodes([node.id.as_str()]),
+            );
+        }
+    }
+}
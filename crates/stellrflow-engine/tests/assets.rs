@@ -0,0 +1,160 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::json;
+use stellrflow_amount::Amount;
+use stellrflow_engine::NodeStatus;
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::Asset;
+
+use common::{engine, signer, telegram, workflow, xlm};
+
+#[tokio::test]
+async fn trustline_and_send_asset_nodes_use_the_chats_wallet() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice, bob) = (signer(1), Arc::new(signer(2)), signer(3));
+    for account in [&issuer, alice.as_ref(), &bob] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let engine = engine(&horizon, wallets);
+
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[(
+                "trust",
+                "trustline",
+                json!({ "asset": usdc.to_string(), "limit": "500" }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    assert_eq!(report.node_results["trust"]["limit"], "500");
+    assert_eq!(
+        horizon.asset_balance(alice.public_key(), &usdc),
+        Some(Amount::ZERO)
+    );
+
+    horizon
+        .client()
+        .send_asset(&issuer, alice.public_key(), &usdc, xlm("20"))
+        .await
+        .unwrap();
+    let report = engine
+        .run(&workflow(telegram("alice"), &[
+            (
+                "refund",
+                "send-asset",
+                json!({ "destination": issuer.public_key(), "asset": usdc.to_string(), "amount": "5" }),
+            ),
+            (
+                "pay-bob",
+                "send-asset",
+                json!({ "destination": bob.public_key(), "asset": usdc.to_string(), "amount": "5" }),
+            ),
+            (
+                "tip-bob",
+                "send-asset",
+                json!({ "destination": bob.public_key(), "asset": "XLM", "amount": "2.5" }),
+            ),
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(report.status("refund"), Some(NodeStatus::Success));
+    assert_eq!(report.status("tip-bob"), Some(NodeStatus::Success));
+    assert_eq!(report.node_results["refund"]["asset"], usdc.to_string());
+    // Bob has no trustline; the payment is refused before it is signed.
+    assert_eq!(report.status("pay-bob"), Some(NodeStatus::Error));
+    assert!(report.node_errors["pay-bob"].contains("has no trustline to USDC:"));
+    assert_eq!(
+        horizon.asset_balance(alice.public_key(), &usdc),
+        Some(xlm("15"))
+    );
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("102.5")));
+}
+
+#[tokio::test]
+async fn convert_pay_delivers_the_destination_asset() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice, bob) = (signer(1), Arc::new(signer(2)), signer(3));
+    for account in [&issuer, alice.as_ref(), &bob] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    horizon.set_price(&Asset::Native, &usdc, "1/8");
+    horizon
+        .client()
+        .change_trust(&bob, &usdc, None)
+        .await
+        .unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let engine = engine(&horizon, wallets);
+
+    let report = engine
+        .run(&workflow(telegram("alice"), &[(
+            "pay",
+            "convert-pay",
+            json!({ "destination": bob.public_key(), "destAsset": usdc.to_string(), "amount": "2.5" }),
+        )]))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    let pay = &report.node_results["pay"];
+    assert_eq!(pay["sendAsset"], "native");
+    assert_eq!(pay["sentAmount"], "20");
+    assert_eq!(pay["receivedAmount"], "2.5");
+    assert_eq!(pay["rate"], "0.125");
+    assert_eq!(pay["quotedRate"], "0.125");
+    assert_eq!(pay["path"], json!([]));
+    assert_eq!(
+        horizon.asset_balance(bob.public_key(), &usdc),
+        Some(xlm("2.5"))
+    );
+
+    // Nothing trades EURC, so there is no path to it.
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[(
+                "pay",
+                "convert-pay",
+                json!({
+                    "destination": issuer.public_key(),
+                    "sendAsset": "XLM",
+                    "destAsset": format!("EURC:{}", issuer.public_key()),
+                    "amount": "10",
+                    "strict": "send",
+                }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.node_errors["pay"], "no path found from XLM to EURC");
+}
+
+#[tokio::test]
+async fn chats_without_a_wallet_fail() {
+    let horizon = MockHorizon::start().await;
+    let engine = engine(&horizon, HashMap::new());
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[(
+                "send",
+                "send-asset",
+                json!({ "destination": signer(3).public_key(), "asset": "native", "amount": "1" }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        report.node_errors["send"],
+        "No wallet connected. Connect one via StellrFlow workflow."
+    );
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::json;
+use stellrflow_engine::NodeStatus;
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+
+use common::{engine, signer, telegram, workflow, xlm};
+
+#[tokio::test]
+async fn a_balance_waits_for_a_new_account_to_claim_it() {
+    let horizon = MockHorizon::start().await;
+    let (alice, bob) = (Arc::new(signer(2)), Arc::new(signer(3)));
+    horizon.fund(alice.public_key(), xlm("100"));
+    let wallets = HashMap::from([
+        ("alice".to_string(), alice.clone() as Arc<dyn Signer>),
+        ("bob".to_string(), bob.clone() as Arc<dyn Signer>),
+    ]);
+    let engine = engine(&horizon, wallets);
+
+    // Bob has no account yet; Alice gets it back if he never claims it.
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[(
+                "gift",
+                "create-claimable-balance",
+                json!({
+                    "claimants": bob.public_key(),
+                    "amount": 25,
+                    "expiresAfter": "30d",
+                    "reclaim": true,
+                }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    let gift = &report.node_results["gift"];
+    assert_eq!(
+        gift["claimants"],
+        json!([bob.public_key(), alice.public_key()])
+    );
+    assert_eq!(gift["amount"], "25");
+    let balance_id = gift["balanceId"].as_str().unwrap().to_string();
+
+    // Nothing Alice can claim yet.
+    let claim = || ("claim", "claim-claimable-balance", json!({}));
+    let report = engine
+        .run(&workflow(telegram("alice"), &[claim()]))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    assert_eq!(report.node_results["claim"]["count"], 0);
+
+    horizon.fund(bob.public_key(), xlm("5"));
+    let report = engine
+        .run(&workflow(telegram("bob"), &[claim()]))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    let claimed = &report.node_results["claim"];
+    assert_eq!(claimed["count"], 1);
+    assert_eq!(claimed["claimed"][0]["balanceId"], balance_id.as_str());
+    assert_eq!(claimed["claimed"][0]["amount"], "25");
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("29.99999")));
+
+    // Claimed once, it is gone.
+    let report = engine
+        .run(&workflow(
+            telegram("bob"),
+            &[(
+                "claim",
+                "claim-claimable-balance",
+                json!({ "balanceId": balance_id }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("claim"), Some(NodeStatus::Error));
+    assert!(
+        report.node_errors["claim"].contains("not found"),
+        "{}",
+        report.node_errors["claim"]
+    );
+}
@@ -0,0 +1,96 @@
+//! Fixtures for the tests that run the Stellar nodes against a
+//! `MockHorizon`.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use stellrflow_amount::Amount;
+use stellrflow_engine::nodes::{BotClient, PolicyClient, Stellar};
+use stellrflow_engine::{
+    Engine, ExecutorRegistry, NodeContext, NodeError, NodeExecutor, NodeOutput, Workflow,
+};
+use stellrflow_policy::{api, PolicyStore};
+use stellrflow_signer::{LocalSigner, Signer};
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::SubmissionQueue;
+
+/// Hands its `chatId` down, as the Telegram trigger does.
+pub struct Trigger;
+
+#[async_trait]
+impl NodeExecutor for Trigger {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        let mut output = NodeOutput::default();
+        output
+            .value
+            .insert("chatId".into(), json!(ctx.require_chat_id()?));
+        Ok(output)
+    }
+}
+
+pub fn xlm(amount: &str) -> Amount {
+    amount.parse().unwrap()
+}
+
+pub fn signer(n: u8) -> LocalSigner {
+    LocalSigner::from_seed([n; 32])
+}
+
+/// The Stellar nodes signing with `wallets` against `horizon`, and
+/// `telegram-trigger` as [`Trigger`].
+pub fn engine(horizon: &MockHorizon, wallets: HashMap<String, Arc<dyn Signer>>) -> Engine {
+    Engine::new(registry(stellar(horizon, wallets)))
+}
+
+pub fn stellar(horizon: &MockHorizon, wallets: HashMap<String, Arc<dyn Signer>>) -> Stellar {
+    let queue = Arc::new(SubmissionQueue::new(horizon.client()));
+    // Nobody listens there: notices to the chat are dropped.
+    let bot = BotClient::new("http://127.0.0.1:9");
+    Stellar::new(queue, Arc::new(wallets), bot)
+}
+
+/// Serves `store`'s policy endpoints on a free local port.
+pub async fn policy_service(store: Arc<PolicyStore>) -> PolicyClient {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, api::router(store)).await });
+    PolicyClient::new(url)
+}
+
+pub fn registry(stellar: Stellar) -> ExecutorRegistry {
+    let mut registry = ExecutorRegistry::new();
+    registry
+        .register("telegram-trigger", Trigger)
+        .register_stellar(stellar);
+    registry
+}
+
+/// A `telegram-trigger` for `chat_id`, as [`workflow`] takes it.
+pub fn telegram(chat_id: &str) -> (&'static str, Value) {
+    ("telegram-trigger", json!({ "chatId": chat_id }))
+}
+
+/// A trigger node `trigger` of type `ty` feeding each `(id, type, config)`
+/// action.
+pub fn workflow((ty, config): (&str, Value), actions: &[(&str, &str, Value)]) -> Workflow {
+    let mut nodes = vec![node("trigger", ty, &config)];
+    let mut edges = Vec::new();
+    for (id, ty, config) in actions {
+        nodes.push(node(id, ty, config));
+        edges.push(json!({ "id": format!("e-{id}"), "source": "trigger", "target": id }));
+    }
+    serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap()
+}
+
+fn node(id: &str, ty: &str, config: &Value) -> Value {
+    json!({
+        "id": id,
+        "type": "customNode",
+        "position": { "x": 0, "y": 0 },
+        "data": { "label": id, "type": ty, "icon": "", "description": "", "config": config },
+    })
+}
@@ -0,0 +1,320 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use stellrflow_engine::nodes::Condition;
+use stellrflow_engine::{
+    DiagnosticCode, Engine, EngineError, ExecutionEvent, ExecutorRegistry, NodeContext, NodeError,
+    NodeExecutor, NodeOutput, NodeRule, NodeRules, NodeStatus, Workflow,
+};
+
+/// Records each call and outputs `{ <node id>: <input keys> }`.
+#[derive(Clone, Default)]
+struct Echo {
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl NodeExecutor for Echo {
+    async fn execute(&self, ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        self.calls.lock().unwrap().push(ctx.node.id.clone());
+        let mut keys: Vec<&String> = ctx.input.keys().collect();
+        keys.sort();
+        let mut output = NodeOutput::default();
+        output.value.insert(ctx.node.id.clone(), json!(keys));
+        output.value.insert("last".into(), json!(ctx.node.id));
+        Ok(output)
+    }
+}
+
+struct Fail;
+
+#[async_trait]
+impl NodeExecutor for Fail {
+    async fn execute(&self, _ctx: NodeContext<'_>) -> Result<NodeOutput, NodeError> {
+        Err(NodeError::Failed("boom".into()))
+    }
+}
+
+/// `start` is a trigger; `echo` and `fail` are actions.
+fn engine(echo: &Echo) -> Engine {
+    let mut registry = ExecutorRegistry::new();
+    registry
+        .register("start", echo.clone())
+        .register("echo", echo.clone())
+        .register("fail", Fail);
+    let mut rules = NodeRules::new();
+    rules.insert("start", NodeRule::trigger(&[]));
+    Engine::new(registry).with_rules(rules)
+}
+
+/// Builds a workflow from `(id, type)` nodes and `(source, target)` edges.
+fn workflow(nodes: &[(&str, &str)], edges: &[(&str, &str)]) -> Workflow {
+    let nodes: Vec<Value> = nodes
+        .iter()
+        .map(|(id, ty)| {
+            json!({
+                "id": id,
+                "type": "customNode",
+                "position": { "x": 0, "y": 0 },
+                "data": { "label": id, "type": ty, "icon": "", "description": "", "config": {} },
+            })
+        })
+        .collect();
+    let edges: Vec<Value> = edges
+        .iter()
+        .enumerate()
+        .map(|(i, (s, t))| {
+            json!({
+                "id": format!("e{i}"),
+                "source": s,
+                "target": t,
+                "sourceHandle": null,
+                "targetHandle": null,
+                "type": "smoothstep",
+                "animated": true,
+            })
+        })
+        .collect();
+    serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap()
+}
+
+#[tokio::test]
+async fn runs_nodes_in_dependency_order() {
+    let echo = Echo::default();
+    let wf = workflow(
+        &[("c", "echo"), ("b", "echo"), ("a", "start")],
+        &[("a", "b"), ("b", "c")],
+    );
+
+    let report = engine(&echo).run(&wf).await.unwrap();
+
+    assert!(report.is_success());
+    assert_eq!(*echo.calls.lock().unwrap(), ["a", "b", "c"]);
+    assert_eq!(report.node_results["c"]["c"], json!(["a", "b", "last"]));
+}
+
+#[tokio::test]
+async fn fan_in_runs_once_with_merged_payload() {
+    let echo = Echo::default();
+    let wf = workflow(
+        &[
+            ("t", "start"),
+            ("l", "echo"),
+            ("r", "echo"),
+            ("join", "echo"),
+        ],
+        &[("t", "l"), ("t", "r"), ("l", "join"), ("r", "join")],
+    );
+
+    let report = engine(&echo).run(&wf).await.unwrap();
+
+    assert_eq!(*echo.calls.lock().unwrap(), ["t", "l", "r", "join"]);
+    assert_eq!(
+        report.node_results["join"]["join"],
+        json!(["l", "last", "r", "t"])
+    );
+    // The later edge wins on conflicting keys.
+    assert_eq!(report.node_results["join"]["last"], json!("join"));
+}
+
+#[tokio::test]
+async fn failure_stops_only_its_branch() {
+    let echo = Echo::default();
+    let wf = workflow(
+        &[
+            ("t", "start"),
+            ("bad", "fail"),
+            ("after", "echo"),
+            ("ok", "echo"),
+        ],
+        &[("t", "bad"), ("bad", "after"), ("t", "ok")],
+    );
+
+    let report = engine(&echo).run(&wf).await.unwrap();
+
+    assert!(!report.is_success());
+    assert_eq!(report.status("bad"), Some(NodeStatus::Error));
+    assert_eq!(report.node_errors["bad"], "boom");
+    assert_eq!(report.status("after"), Some(NodeStatus::Pending));
+    assert_eq!(report.status("ok"), Some(NodeStatus::Success));
+    assert!(!report.node_results.contains_key("after"));
+}
+
+#[tokio::test]
+async fn every_trigger_starts_a_run() {
+    let echo = Echo::default();
+    let wf = workflow(&[("a", "start"), ("b", "start")], &[]);
+
+    engine(&echo).run(&wf).await.unwrap();
+
+    assert_eq!(*echo.calls.lock().unwrap(), ["a", "b"]);
+}
+
+#[tokio::test]
+async fn emits_status_events() {
+    let echo = Echo::default();
+    let wf = workflow(&[("a", "start"), ("b", "fail")], &[("a", "b")]);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    engine(&echo).run_with(&wf, &tx).await.unwrap();
+    drop(tx);
+
+    let mut events: Vec<ExecutionEvent> = Vec::new();
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+    let statuses: Vec<(&str, NodeStatus)> = events
+        .iter()
+        .map(|e| (e.node_id.as_str(), e.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("a", NodeStatus::Pending),
+            ("b", NodeStatus::Pending),
+            ("a", NodeStatus::Running),
+            ("a", NodeStatus::Success),
+            ("b", NodeStatus::Running),
+            ("b", NodeStatus::Error),
+        ]
+    );
+    assert_eq!(events[5].error.as_deref(), Some("boom"));
+}
+
+#[tokio::test]
+async fn report_serializes_like_the_store() {
+    let echo = Echo::default();
+    let wf = workflow(&[("a", "start")], &[]);
+
+    let report = engine(&echo).run(&wf).await.unwrap();
+
+    assert_eq!(
+        serde_json::to_value(&report).unwrap(),
+        json!({
+            "nodeExecutionState": { "a": "success" },
+            "nodeResults": { "a": { "a": [], "last": "a" } },
+            "nodeErrors": {},
+        })
+    );
+}
+
+#[tokio::test]
+async fn refuses_to_run_invalid_graphs() {
+    let echo = Echo::default();
+    let engine = engine(&echo);
+
+    let invalid = [
+        (
+            workflow(
+                &[("t", "start"), ("a", "echo"), ("b", "echo")],
+                &[("t", "a"), ("a", "b"), ("b", "a")],
+            ),
+            DiagnosticCode::Cycle,
+        ),
+        (
+            workflow(&[("t", "start"), ("d", "discord-trigger")], &[("t", "d")]),
+            DiagnosticCode::UnknownNodeType,
+        ),
+        (
+            workflow(&[("t", "start")], &[("t", "gone")]),
+            DiagnosticCode::DanglingEdge,
+        ),
+        (
+            workflow(&[("t", "start"), ("lone", "echo")], &[]),
+            DiagnosticCode::Unreachable,
+        ),
+        (Workflow::default(), DiagnosticCode::Empty),
+    ];
+
+    for (wf, code) in invalid {
+        match engine.run(&wf).await {
+            Err(EngineError::Invalid(validation)) => {
+                assert!(
+                    validation.errors().any(|d| d.code == code),
+                    "expected {code:?} in {validation:?}"
+                );
+            }
+            other => panic!("expected {code:?}, got {other:?}"),
+        }
+    }
+    assert!(echo.calls.lock().unwrap().is_empty());
+}
+
+/// `t` → `c` (condition on `expression`) → `yes` / `no` by handle, and
+/// `no` → `after`.
+fn branching(expression: &str) -> Workflow {
+    let node = |id: &str, ty: &str, config: Value| json!({ "id": id, "data": { "type": ty, "config": config } });
+    let edge = |id: &str, source: &str, target: &str, handle: Option<&str>| json!({ "id": id, "source": source, "target": target, "sourceHandle": handle });
+    serde_json::from_value(json!({
+        "nodes": [
+            node("t", "start", json!({})),
+            node("c", "condition", json!({ "expression": expression })),
+            node("yes", "echo", json!({})),
+            node("no", "echo", json!({})),
+            node("after", "echo", json!({})),
+        ],
+        "edges": [
+            edge("e0", "t", "c", None),
+            edge("e1", "c", "yes", Some("true")),
+            edge("e2", "c", "no", Some("false")),
+            edge("e3", "no", "after", None),
+        ],
+    }))
+    .unwrap()
+}
+
+fn branching_engine(echo: &Echo) -> Engine {
+    let mut registry = ExecutorRegistry::new();
+    registry
+        .register("start", echo.clone())
+        .register("echo", echo.clone())
+        .register("condition", Condition);
+    let mut rules = NodeRules::builtin();
+    rules.insert("start", NodeRule::trigger(&[]));
+    Engine::new(registry).with_rules(rules)
+}
+
+#[tokio::test]
+async fn conditions_route_along_one_handle() {
+    let echo = Echo::default();
+    let report = branching_engine(&echo)
+        .run(&branching("last == 't'"))
+        .await
+        .unwrap();
+
+    assert!(report.is_success());
+    assert_eq!(*echo.calls.lock().unwrap(), ["t", "yes"]);
+    assert_eq!(report.node_results["c"], json!({ "condition": true }));
+    // The condition's input and output both reach the chosen branch.
+    assert_eq!(
+        report.node_results["yes"]["yes"],
+        json!(["condition", "last", "t"])
+    );
+    assert_eq!(report.status("no"), Some(NodeStatus::Skipped));
+    assert_eq!(report.status("after"), Some(NodeStatus::Skipped));
+
+    let echo = Echo::default();
+    let report = branching_engine(&echo)
+        .run(&branching("len(t) > 0"))
+        .await
+        .unwrap();
+    assert_eq!(*echo.calls.lock().unwrap(), ["t", "no", "after"]);
+    assert_eq!(report.status("yes"), Some(NodeStatus::Skipped));
+}
+
+#[tokio::test]
+async fn condition_errors_stop_both_branches() {
+    let echo = Echo::default();
+    let report = branching_engine(&echo)
+        .run(&branching("missing > 1"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.node_errors["c"],
+        "Condition `missing > 1`: cannot compare null with number 1"
+    );
+    assert_eq!(report.status("yes"), Some(NodeStatus::Pending));
+    assert_eq!(report.status("no"), Some(NodeStatus::Pending));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use stellrflow_engine::NodeStatus;
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{Asset, LimitOrder};
+
+use common::{engine, signer, workflow, xlm};
+
+#[tokio::test]
+async fn a_price_alert_places_an_offer() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice, bob) = (signer(1), Arc::new(signer(2)), signer(3));
+    for account in [&issuer, alice.as_ref(), &bob] {
+        horizon.fund(account.public_key(), xlm("1000"));
+    }
+    let client = horizon.client();
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    client
+        .change_trust(alice.as_ref(), &usdc, None)
+        .await
+        .unwrap();
+    client
+        .send_asset(&issuer, alice.public_key(), &usdc, xlm("20"))
+        .await
+        .unwrap();
+    // Bob asks 0.125 USDC for each of 100 XLM.
+    client.change_trust(&bob, &usdc, None).await.unwrap();
+    let ask = LimitOrder::sell(
+        Asset::Native,
+        xlm("100"),
+        usdc.clone(),
+        "1/8".parse().unwrap(),
+    );
+    client.manage_offer(&bob, &ask).await.unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let engine = engine(&horizon, wallets);
+
+    let trigger = json!({
+        "chatId": "alice",
+        "counter": usdc.to_string(),
+        "condition": "below",
+        "price": "0.13",
+        "source": "ask",
+        "interval": "1s",
+    });
+    let report = engine
+        .run(&workflow(
+            ("price-trigger", trigger.clone()),
+            &[(
+                "bid",
+                "manage-buy-offer",
+                json!({ "buying": "native", "selling": usdc.to_string(), "amount": 80, "price": "0.12" }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    let alert = &report.node_results["trigger"];
+    assert_eq!(
+        (&alert["price"], &alert["threshold"]),
+        (&json!("0.125"), &json!("0.13"))
+    );
+    let bid = &report.node_results["bid"];
+    assert_eq!(bid["side"], "buy");
+    assert_eq!(bid["amount"], "9.6");
+    let offer_id = bid["offerId"].as_i64().unwrap();
+    let offers = client
+        .account_offers(alice.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(offers.records[0].id, offer_id);
+    assert_eq!(offers.records[0].selling, usdc);
+
+    // Cancelled by ID.
+    let report = engine
+        .run(&workflow(
+            ("price-trigger", trigger),
+            &[(
+                "cancel",
+                "manage-sell-offer",
+                json!({
+                    "selling": usdc.to_string(),
+                    "buying": "XLM",
+                    "amount": "0",
+                    "price": "1",
+                    "offerId": offer_id,
+                }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    assert_eq!(report.node_results["cancel"]["cancelled"], true);
+    assert_eq!(report.node_results["cancel"]["offerId"], Value::Null);
+    let offers = client
+        .account_offers(alice.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert!(offers.records.is_empty());
+}
+
+#[tokio::test]
+async fn a_price_alert_gives_up_after_its_timeout() {
+    let horizon = MockHorizon::start().await;
+    let alice = Arc::new(signer(2));
+    horizon.fund(alice.public_key(), xlm("1000"));
+    let usdc = Asset::issued("USDC", signer(1).public_key()).unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let engine = engine(&horizon, wallets);
+
+    let report = engine
+        .run(&workflow(
+            (
+                "price-trigger",
+                json!({
+                    "chatId": "alice",
+                    "counter": usdc.to_string(),
+                    "price": "1",
+                    "interval": "1s",
+                    "timeout": "1s",
+                }),
+            ),
+            &[(
+                "sell",
+                "manage-sell-offer",
+                json!({ "selling": "XLM", "buying": usdc.to_string(), "amount": 10, "price": 1 }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("trigger"), Some(NodeStatus::Error));
+    assert_eq!(
+        report.node_errors["trigger"],
+        "XLM did not go above 1 USDC within 1s"
+    );
+    assert_ne!(report.status("sell"), Some(NodeStatus::Success));
+}
@@ -0,0 +1,315 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use stellrflow_engine::nodes::PolicyClient;
+use stellrflow_engine::{Engine, NodeStatus};
+use stellrflow_policy::{now_ms, Outcome, Policy, PolicyStore};
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::Asset;
+
+use common::{policy_service, registry, signer, stellar, telegram, workflow, xlm};
+
+fn policy(store: &PolicyStore, chat_id: &str, rules: Value) {
+    let policy: Policy = serde_json::from_value(json!({ "rules": rules })).unwrap();
+    store.set_policy(chat_id, &policy, now_ms()).unwrap();
+}
+
+fn send(destination: &str, asset: &str, amount: &str) -> (&'static str, &'static str, Value) {
+    (
+        "pay",
+        "send-asset",
+        json!({ "destination": destination, "asset": asset, "amount": amount }),
+    )
+}
+
+#[tokio::test]
+async fn a_payment_the_policy_denies_is_never_submitted() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice, bob, carol, dave) = (
+        signer(1),
+        Arc::new(signer(2)),
+        signer(3),
+        signer(4),
+        signer(5),
+    );
+    for account in [&issuer, alice.as_ref(), &bob, &carol, &dave] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    horizon.set_price(&Asset::Native, &usdc, "1/8");
+    let client = horizon.client();
+    client
+        .change_trust(alice.as_ref(), &usdc, None)
+        .await
+        .unwrap();
+    client.change_trust(&carol, &usdc, None).await.unwrap();
+    client
+        .send_asset(&issuer, alice.public_key(), &usdc, xlm("10"))
+        .await
+        .unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let store = Arc::new(PolicyStore::open_in_memory().unwrap());
+    policy(
+        &store,
+        "alice",
+        json!([
+            { "type": "denylist", "destinations": [bob.public_key()] },
+            { "type": "maxPerTransaction", "amount": "20" },
+        ]),
+    );
+    let engine = Engine::new(registry(
+        stellar(&horizon, wallets.clone()).with_policy(policy_service(store.clone()).await),
+    ));
+    let sequence = horizon.sequence(alice.public_key());
+
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[send(bob.public_key(), "XLM", "1")],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("pay"), Some(NodeStatus::Error));
+    assert!(report.node_errors["pay"].contains("is on the denylist"));
+    // 3 USDC sells for 24 XLM, over the cap.
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[send(carol.public_key(), &usdc.to_string(), "3")],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("pay"), Some(NodeStatus::Error));
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("100")));
+    let decisions = store.decisions("alice", 10).unwrap();
+    assert_eq!(decisions.len(), 2);
+    assert!(decisions.iter().all(|d| d.outcome == Outcome::Deny));
+    assert_eq!(decisions[0].amount.to_string(), "24");
+
+    // Allowed, it is paid; allowed but then refused, it no longer counts.
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[send(carol.public_key(), &usdc.to_string(), "2")],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    assert_eq!(
+        horizon.asset_balance(carol.public_key(), &usdc),
+        Some(xlm("2"))
+    );
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[send(dave.public_key(), &usdc.to_string(), "1")],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("pay"), Some(NodeStatus::Error));
+    assert!(report.node_errors["pay"].contains("has no trustline to USDC:"));
+    let decisions = store.decisions("alice", 2).unwrap();
+    assert_eq!(decisions[0].outcome, Outcome::Allow);
+    assert!(decisions[0].released);
+    assert!(!decisions[1].released);
+
+    // A service that cannot be reached allows nothing.
+    let engine = Engine::new(registry(
+        stellar(&horizon, wallets).with_policy(PolicyClient::new("http://127.0.0.1:9")),
+    ));
+    let sequence = horizon.sequence(alice.public_key());
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[send(carol.public_key(), "XLM", "1")],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("pay"), Some(NodeStatus::Error));
+    assert!(report.node_errors["pay"].contains("Spending policy service unavailable"));
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+}
+
+#[tokio::test]
+async fn convert_pay_is_checked_for_the_most_it_may_send() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice, bob) = (signer(1), Arc::new(signer(2)), signer(3));
+    for account in [&issuer, alice.as_ref(), &bob] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    horizon.set_price(&Asset::Native, &usdc, "1/8");
+    horizon
+        .client()
+        .change_trust(&bob, &usdc, None)
+        .await
+        .unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let store = Arc::new(PolicyStore::open_in_memory().unwrap());
+    policy(
+        &store,
+        "alice",
+        json!([{ "type": "maxPerTransaction", "amount": "20" }]),
+    );
+    let engine = Engine::new(registry(
+        stellar(&horizon, wallets).with_policy(policy_service(store.clone()).await),
+    ));
+    let convert = |amount: &str| {
+        (
+            "pay",
+            "convert-pay",
+            json!({ "destination": bob.public_key(), "destAsset": usdc.to_string(), "amount": amount }),
+        )
+    };
+    let sequence = horizon.sequence(alice.public_key());
+
+    // 2.5 USDC is quoted at 20 XLM, and may cost 1% more.
+    let report = engine
+        .run(&workflow(telegram("alice"), &[convert("2.5")]))
+        .await
+        .unwrap();
+    assert_eq!(report.status("pay"), Some(NodeStatus::Error));
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+    assert_eq!(
+        store.decisions("alice", 1).unwrap()[0].amount.to_string(),
+        "20.2"
+    );
+
+    let report = engine
+        .run(&workflow(telegram("alice"), &[convert("2")]))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    assert_eq!(
+        horizon.asset_balance(bob.public_key(), &usdc),
+        Some(xlm("2"))
+    );
+}
+
+#[tokio::test]
+async fn offers_are_checked_for_what_they_sell() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice) = (signer(1), Arc::new(signer(2)));
+    for account in [&issuer, alice.as_ref()] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    horizon
+        .client()
+        .change_trust(alice.as_ref(), &usdc, None)
+        .await
+        .unwrap();
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let store = Arc::new(PolicyStore::open_in_memory().unwrap());
+    policy(
+        &store,
+        "alice",
+        json!([{ "type": "maxPerTransaction", "amount": "20" }]),
+    );
+    let engine = Engine::new(registry(
+        stellar(&horizon, wallets).with_policy(policy_service(store.clone()).await),
+    ));
+    let sequence = horizon.sequence(alice.public_key());
+
+    // Buying 3 USDC at 8 XLM each sells up to 24 XLM.
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[(
+                "bid",
+                "manage-buy-offer",
+                json!({ "buying": usdc.to_string(), "selling": "XLM", "amount": "3", "price": "8" }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("bid"), Some(NodeStatus::Error));
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+    let decision = &store.decisions("alice", 1).unwrap()[0];
+    assert_eq!(decision.amount.to_string(), "24");
+    assert_eq!(decision.destination, issuer.public_key());
+
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[(
+                "ask",
+                "manage-sell-offer",
+                json!({ "selling": "XLM", "buying": usdc.to_string(), "amount": "20", "price": "0.125" }),
+            )],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    assert!(report.node_results["ask"]["offerId"].is_i64());
+}
+
+#[tokio::test]
+async fn every_claimant_is_checked_and_the_amount_counted_once() {
+    let horizon = MockHorizon::start().await;
+    let (alice, bob, carol) = (Arc::new(signer(2)), signer(3), signer(4));
+    horizon.fund(alice.public_key(), xlm("100"));
+    let wallets = HashMap::from([("alice".to_string(), alice.clone() as Arc<dyn Signer>)]);
+    let store = Arc::new(PolicyStore::open_in_memory().unwrap());
+    policy(
+        &store,
+        "alice",
+        json!([
+            { "type": "denylist", "destinations": [bob.public_key()] },
+            { "type": "dailyLimit", "amount": "30" },
+        ]),
+    );
+    let engine = Engine::new(registry(
+        stellar(&horizon, wallets).with_policy(policy_service(store.clone()).await),
+    ));
+    let gift = |claimants: Vec<&str>, amount: &str| {
+        (
+            "gift",
+            "create-claimable-balance",
+            json!({ "claimants": claimants, "amount": amount }),
+        )
+    };
+    let sequence = horizon.sequence(alice.public_key());
+
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[gift(vec![carol.public_key(), bob.public_key()], "5")],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("gift"), Some(NodeStatus::Error));
+    assert!(report.node_errors["gift"].contains("is on the denylist"));
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[gift(vec![carol.public_key()], "31")],
+        ))
+        .await
+        .unwrap();
+    assert_eq!(report.status("gift"), Some(NodeStatus::Error));
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+
+    // Two claimants, 20 XLM counted once: 10 more are still allowed today.
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[gift(vec![carol.public_key(), alice.public_key()], "20")],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+    let report = engine
+        .run(&workflow(
+            telegram("alice"),
+            &[gift(vec![carol.public_key()], "10")],
+        ))
+        .await
+        .unwrap();
+    assert!(report.is_success(), "{:?}", report.node_errors);
+}
@@ -0,0 +1,219 @@
+use serde_json::{json, Value};
+use stellrflow_engine::validate::validate;
+use stellrflow_engine::{DiagnosticCode, NodeRules, Severity, Validation, Workflow};
+
+/// Builds a workflow from `(id, type, config)` nodes and `(source, target)`
+/// edges.
+fn workflow(nodes: &[(&str, &str, Value)], edges: &[(&str, &str)]) -> Workflow {
+    let nodes: Vec<Value> = nodes
+        .iter()
+        .map(|(id, ty, config)| json!({ "id": id, "data": { "type": ty, "config": config } }))
+        .collect();
+    let edges: Vec<Value> = edges
+        .iter()
+        .enumerate()
+        .map(|(i, (s, t))| json!({ "id": format!("e{i}"), "source": s, "target": t }))
+        .collect();
+    serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap()
+}
+
+fn trigger(id: &str) -> (&str, &'static str, Value) {
+    (id, "telegram-trigger", json!({ "chatId": "12345" }))
+}
+
+fn send(id: &str) -> (&str, &'static str, Value) {
+    (id, "telegram-send", json!({ "message": "hi" }))
+}
+
+fn check(wf: &Workflow) -> Validation {
+    validate(wf, &NodeRules::builtin())
+}
+
+/// `(code, node ids)` of every diagnostic.
+fn codes(v: &Validation) -> Vec<(DiagnosticCode, Vec<&str>)> {
+    v.diagnostics
+        .iter()
+        .map(|d| (d.code, d.node_ids.iter().map(String::as_str).collect()))
+        .collect()
+}
+
+#[test]
+fn accepts_a_connected_workflow() {
+    let wf = workflow(
+        &[trigger("t"), send("a"), send("b")],
+        &[("t", "a"), ("a", "b")],
+    );
+    assert_eq!(check(&wf), Validation::default());
+}
+
+#[test]
+fn reports_each_cycle_with_its_nodes() {
+    let wf = workflow(
+        &[trigger("t"), send("a"), send("b"), send("c"), send("d")],
+        &[
+            ("t", "a"),
+            ("a", "b"),
+            ("b", "a"),
+            ("t", "c"),
+            ("c", "c"),
+            ("t", "d"),
+        ],
+    );
+    let v = check(&wf);
+
+    assert!(v.has_errors());
+    assert_eq!(
+        codes(&v),
+        [
+            (DiagnosticCode::Cycle, vec!["a", "b"]),
+            (DiagnosticCode::Cycle, vec!["c"]),
+        ]
+    );
+}
+
+#[test]
+fn orphaned_actions_are_unreachable() {
+    // A lone telegram-send used to run as if it were a trigger, and so did
+    // everything hanging off it.
+    let wf = workflow(
+        &[trigger("t"), send("a"), send("lone"), send("child")],
+        &[("t", "a"), ("lone", "child")],
+    );
+
+    assert_eq!(
+        codes(&check(&wf)),
+        [
+            (DiagnosticCode::Unreachable, vec!["lone"]),
+            (DiagnosticCode::Unreachable, vec!["child"]),
+        ]
+    );
+}
+
+#[test]
+fn needs_a_trigger() {
+    let wf = workflow(&[send("a"), send("b")], &[("a", "b")]);
+    let v = check(&wf);
+
+    assert_eq!(codes(&v), [(DiagnosticCode::NoTrigger, vec![])]);
+    assert_eq!(
+        serde_json::to_value(&v.diagnostics[0]).unwrap(),
+        json!({
+            "severity": "error",
+            "code": "no_trigger",
+            "message": "workflow has no trigger node",
+        })
+    );
+}
+
+#[test]
+fn multiple_triggers_are_a_warning() {
+    let wf = workflow(
+        &[trigger("t1"), trigger("t2"), send("a")],
+        &[("t1", "a"), ("t2", "a")],
+    );
+    let v = check(&wf);
+
+    assert!(!v.has_errors());
+    assert_eq!(
+        codes(&v),
+        [(DiagnosticCode::MultipleTriggers, vec!["t1", "t2"])]
+    );
+    assert_eq!(v.diagnostics[0].severity, Severity::Warning);
+}
+
+#[test]
+fn reports_missing_required_config() {
+    let wf = workflow(
+        &[
+            ("t", "telegram-trigger", json!({ "chatId": "  " })),
+            (
+                "pay",
+                "autopay",
+                json!({ "destination": "GABC", "amount": "" }),
+            ),
+            ("ms", "multisig", json!({ "threshold": 2, "signers": [] })),
+        ],
+        &[("t", "pay"), ("pay", "ms")],
+    );
+    let v = check(&wf);
+
+    let missing: Vec<(&str, &str)> = v
+        .with_code(DiagnosticCode::MissingConfig)
+        .map(|d| (d.node_ids[0].as_str(), d.config_key.as_deref().unwrap()))
+        .collect();
+    assert_eq!(
+        missing,
+        [("t", "chatId"), ("pay", "amount"), ("ms", "signers")]
+    );
+}
+
+#[test]
+fn checks_configs_against_their_schemas() {
+    let wf = workflow(
+        &[
+            trigger("t"),
+            // Saved by the old palette: legacy keys, no `configVersion`.
+            (
+                "pay",
+                "autopay",
+                json!({ "destinationAddress": "GABC", "amount": "5", "totalDuration": "7d" }),
+            ),
+            (
+                "ms",
+                "multisig",
+                json!({ "signers": ["GA"], "threshold": 3 }),
+            ),
+        ],
+        &[("t", "pay"), ("pay", "ms")],
+    );
+    let v = check(&wf);
+
+    assert_eq!(codes(&v), [(DiagnosticCode::InvalidConfig, vec!["ms"])]);
+    assert_eq!(
+        v.diagnostics[0].message,
+        "`ms`: invalid `multisig` config: threshold 3 is higher than the number of signers (1)"
+    );
+}
+
+#[test]
+fn reports_structural_errors_with_ids() {
+    let wf = workflow(
+        &[trigger("t"), send("a"), send("a")],
+        &[("t", "a"), ("t", "missing")],
+    );
+    let v = check(&wf);
+
+    assert_eq!(
+        codes(&v),
+        [
+            (DiagnosticCode::DuplicateNode, vec!["a"]),
+            (DiagnosticCode::DanglingEdge, vec![]),
+        ]
+    );
+    assert_eq!(v.diagnostics[1].edge_ids, ["e1"]);
+}
+
+#[test]
+fn condition_edges_must_leave_from_a_branch() {
+    let mut wf = workflow(
+        &[
+            trigger("t"),
+            ("c", "condition", json!({ "expression": "amount > 5" })),
+            send("a"),
+            send("b"),
+        ],
+        &[("t", "c"), ("c", "a"), ("c", "b")],
+    );
+    wf.edges[1].source_handle = Some("true".into());
+    let v = check(&wf);
+
+    assert_eq!(codes(&v), [(DiagnosticCode::UnknownHandle, vec!["c"])]);
+    assert_eq!(v.diagnostics[0].edge_ids, ["e2"]);
+    assert_eq!(
+        v.diagnostics[0].message,
+        "edge `e2` leaves `c` (condition) from no handle; expected one of: true, false"
+    );
+
+    wf.edges[2].source_handle = Some("false".into());
+    assert!(check(&wf).diagnostics.is_empty());
+}
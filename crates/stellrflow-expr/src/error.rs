@@ -0,0 +1,41 @@
+use thiserror::Error;
+
+/// A syntax error, with the 1-based column it was found at.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message} at column {column}")]
+pub struct ParseError {
+    pub message: String,
+    pub column: usize,
+}
+
+impl ParseError {
+    /// An error at byte offset `offset` of `source`.
+    pub(crate) fn at(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        Self {
+            message: message.into(),
+            column: source[..offset].chars().count() + 1,
+        }
+    }
+}
+
+/// Why evaluating a well-formed expression failed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EvalError {
+    /// An operator or function was applied to values it does not accept.
+    #[error("{0}")]
+    Type(String),
+    #[error("division by zero")]
+    DivisionByZero,
+    /// A result does not fit the 96-bit decimal range.
+    #[error("arithmetic overflow")]
+    Overflow,
+    /// A payload number could not be read as a decimal.
+    #[error("number out of range: {0}")]
+    OutOfRange(String),
+    #[error("expected true or false, got {0}")]
+    NotBoolean(&'static str),
+}
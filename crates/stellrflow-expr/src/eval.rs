@@ -0,0 +1,307 @@
+use std::cmp::Ordering;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde_json::Map;
+
+use crate::error::EvalError;
+use crate::parser::{BinaryOp, Expr};
+use crate::value::Value;
+
+type Payload = Map<String, serde_json::Value>;
+
+pub(crate) fn eval(expr: &Expr, payload: &Payload) -> Result<Value, EvalError> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Payload => Value::from_json(&serde_json::Value::Object(payload.clone())),
+        Expr::Var(name) => payload.get(name).map_or(Ok(Value::Null), Value::from_json),
+        Expr::Member(target, name) => member(eval(target, payload)?, name),
+        Expr::Index(target, index) => index_into(eval(target, payload)?, eval(index, payload)?),
+        Expr::List(items) => Ok(Value::List(
+            items
+                .iter()
+                .map(|item| eval(item, payload))
+                .collect::<Result<_, _>>()?,
+        )),
+        Expr::Not(operand) => match eval(operand, payload)? {
+            Value::Bool(b) => Ok(Value::Bool(!b)),
+            other => Err(type_error(format!(
+                "`!` expects a boolean, got {}",
+                describe(&other)
+            ))),
+        },
+        Expr::Neg(operand) => {
+            let value = eval(operand, payload)?;
+            let n = number(&value, "`-`")?;
+            Ok(Value::Number(-n))
+        }
+        Expr::Binary(op @ (BinaryOp::And | BinaryOp::Or), left, right) => {
+            let symbol = if *op == BinaryOp::And { "&&" } else { "||" };
+            let left = boolean(eval(left, payload)?, symbol)?;
+            // Short-circuit: `x != null && x.y > 0` never reads `x.y` of null.
+            if left == (*op == BinaryOp::Or) {
+                return Ok(Value::Bool(left));
+            }
+            Ok(Value::Bool(boolean(eval(right, payload)?, symbol)?))
+        }
+        Expr::Binary(op, left, right) => binary(*op, eval(left, payload)?, eval(right, payload)?),
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, payload))
+                .collect::<Result<Vec<_>, _>>()?;
+            call(name, args)
+        }
+    }
+}
+
+fn type_error(message: String) -> EvalError {
+    EvalError::Type(message)
+}
+
+/// A value as it reads in error messages.
+fn describe(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("string {s:?}"),
+        Value::Number(n) => format!("number {}", n.normalize()),
+        Value::Bool(b) => format!("boolean {b}"),
+        other => other.type_name().to_string(),
+    }
+}
+
+fn boolean(value: Value, op: &str) -> Result<bool, EvalError> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        other => Err(type_error(format!(
+            "`{op}` expects booleans, got {}",
+            describe(&other)
+        ))),
+    }
+}
+
+fn number(value: &Value, what: &str) -> Result<Decimal, EvalError> {
+    value
+        .to_number()
+        .ok_or_else(|| type_error(format!("{what} expects a number, got {}", describe(value))))
+}
+
+fn string<'a>(value: &'a Value, what: &str) -> Result<&'a str, EvalError> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(type_error(format!(
+            "{what} expects a string, got {}",
+            describe(other)
+        ))),
+    }
+}
+
+fn member(target: Value, name: &str) -> Result<Value, EvalError> {
+    match target {
+        Value::Object(mut map) => Ok(map.remove(name).unwrap_or(Value::Null)),
+        Value::Null => Ok(Value::Null),
+        other => Err(type_error(format!(
+            "cannot read `.{name}` of {}",
+            describe(&other)
+        ))),
+    }
+}
+
+fn index_into(target: Value, index: Value) -> Result<Value, EvalError> {
+    match (target, index) {
+        (Value::Null, _) => Ok(Value::Null),
+        (Value::Object(mut map), Value::String(key)) => Ok(map.remove(&key).unwrap_or(Value::Null)),
+        (Value::List(mut items), Value::Number(n)) => {
+            if !n.fract().is_zero() {
+                return Err(type_error(format!(
+                    "list index must be a whole number, got {}",
+                    n.normalize()
+                )));
+            }
+            // Negative indexes count from the end.
+            let len = Decimal::from(items.len());
+            let i = if n.is_sign_negative() { len + n } else { n };
+            if i.is_sign_negative() || i >= len {
+                return Ok(Value::Null);
+            }
+            let i = usize::try_from(i).map_err(|_| EvalError::Overflow)?;
+            Ok(items.swap_remove(i))
+        }
+        (target, index) => Err(type_error(format!(
+            "cannot index {} with {}",
+            target.type_name(),
+            describe(&index)
+        ))),
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value) -> Result<Value, EvalError> {
+    let symbol = match op {
+        BinaryOp::Eq => return Ok(Value::Bool(loose_eq(&left, &right))),
+        BinaryOp::Ne => return Ok(Value::Bool(!loose_eq(&left, &right))),
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            let ordering = compare(&left, &right)?;
+            return Ok(Value::Bool(match op {
+                BinaryOp::Lt => ordering.is_lt(),
+                BinaryOp::Le => ordering.is_le(),
+                BinaryOp::Gt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }));
+        }
+        BinaryOp::Add => {
+            if let Some((a, b)) = numbers(&left, &right) {
+                return a
+                    .checked_add(b)
+                    .map(Value::Number)
+                    .ok_or(EvalError::Overflow);
+            }
+            if matches!(left, Value::String(_)) || matches!(right, Value::String(_)) {
+                return Ok(Value::String(format!("{left}{right}")));
+            }
+            "+"
+        }
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Rem => "%",
+        BinaryOp::And | BinaryOp::Or => unreachable!("logical operators short-circuit"),
+    };
+    let (Some(a), Some(b)) = (left.to_number(), right.to_number()) else {
+        return Err(type_error(format!(
+            "`{symbol}` expects numbers, got {} and {}",
+            describe(&left),
+            describe(&right)
+        )));
+    };
+    let result = match op {
+        BinaryOp::Add => a.checked_add(b),
+        BinaryOp::Sub => a.checked_sub(b),
+        BinaryOp::Mul => a.checked_mul(b),
+        BinaryOp::Div | BinaryOp::Rem if b.is_zero() => return Err(EvalError::DivisionByZero),
+        BinaryOp::Div => a.checked_div(b),
+        _ => a.checked_rem(b),
+    };
+    result.map(Value::Number).ok_or(EvalError::Overflow)
+}
+
+/// Both operands as numbers, when at least one is a number and the other
+/// is a number or a numeric string.
+fn numbers(left: &Value, right: &Value) -> Option<(Decimal, Decimal)> {
+    if !matches!(left, Value::Number(_)) && !matches!(right, Value::Number(_)) {
+        return None;
+    }
+    Some((left.to_number()?, right.to_number()?))
+}
+
+/// `==`: numbers compare by value, also against numeric strings; values
+/// of different types are never equal.
+fn loose_eq(left: &Value, right: &Value) -> bool {
+    if let Some((a, b)) = numbers(left, right) {
+        return a == b;
+    }
+    match (left, right) {
+        (Value::List(a), Value::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| loose_eq(x, y))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((ka, va), (kb, vb))| ka == kb && loose_eq(va, vb))
+        }
+        _ => left == right,
+    }
+}
+
+/// Ordering: numbers by value, strings by text, except that two numeric
+/// strings (as Horizon reports balances) compare as numbers.
+fn compare(left: &Value, right: &Value) -> Result<Ordering, EvalError> {
+    if let Some((a, b)) = numbers(left, right) {
+        return Ok(a.cmp(&b));
+    }
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => match (left.to_number(), right.to_number()) {
+            (Some(a), Some(b)) => Ok(a.cmp(&b)),
+            _ => Ok(a.cmp(b)),
+        },
+        _ => Err(type_error(format!(
+            "cannot compare {} with {}",
+            describe(left),
+            describe(right)
+        ))),
+    }
+}
+
+fn call(name: &str, mut args: Vec<Value>) -> Result<Value, EvalError> {
+    let what = format!("`{name}`");
+    Ok(match name {
+        "len" => {
+            let len = match &args[0] {
+                Value::String(s) => s.chars().count(),
+                Value::List(items) => items.len(),
+                Value::Object(map) => map.len(),
+                other => {
+                    return Err(type_error(format!(
+                        "`len` expects a string, list or object, got {}",
+                        describe(other)
+                    )))
+                }
+            };
+            Value::Number(Decimal::from(len))
+        }
+        "lower" => Value::String(string(&args[0], &what)?.to_lowercase()),
+        "upper" => Value::String(string(&args[0], &what)?.to_uppercase()),
+        "trim" => Value::String(string(&args[0], &what)?.trim().to_string()),
+        "contains" => Value::Bool(match &args[0] {
+            Value::String(s) => s.contains(string(&args[1], &what)?),
+            Value::List(items) => items.iter().any(|item| loose_eq(item, &args[1])),
+            Value::Object(map) => map.contains_key(string(&args[1], &what)?),
+            Value::Null => false,
+            other => {
+                return Err(type_error(format!(
+                    "`contains` expects a string, list or object, got {}",
+                    describe(other)
+                )))
+            }
+        }),
+        "startsWith" => Value::Bool(string(&args[0], &what)?.starts_with(string(&args[1], &what)?)),
+        "endsWith" => Value::Bool(string(&args[0], &what)?.ends_with(string(&args[1], &what)?)),
+        "number" => Value::Number(number(&args[0], &what)?),
+        "string" => Value::String(args[0].to_string()),
+        "abs" => Value::Number(number(&args[0], &what)?.abs()),
+        "round" => {
+            let places = match args.get(1) {
+                None => 0,
+                Some(places) => {
+                    let n = number(places, &what)?;
+                    u32::try_from(n)
+                        .ok()
+                        .filter(|p| n.fract().is_zero() && *p <= 28)
+                        .ok_or_else(|| {
+                            type_error(format!(
+                                "`round` places must be a whole number from 0 to 28, got {}",
+                                n.normalize()
+                            ))
+                        })?
+                }
+            };
+            let n = number(&args[0], &what)?;
+            Value::Number(n.round_dp_with_strategy(places, RoundingStrategy::MidpointAwayFromZero))
+        }
+        "min" | "max" => {
+            // `min(list)` works on the list's items.
+            if let [Value::List(items)] = args.as_mut_slice() {
+                args = std::mem::take(items);
+            }
+            let numbers = args
+                .iter()
+                .map(|arg| number(arg, &what))
+                .collect::<Result<Vec<_>, _>>()?;
+            let pick = if name == "min" {
+                numbers.into_iter().min()
+            } else {
+                numbers.into_iter().max()
+            };
+            Value::Number(pick.ok_or_else(|| type_error(format!("{what} of an empty list")))?)
+        }
+        _ => unreachable!("functions are checked when parsing"),
+    })
+}
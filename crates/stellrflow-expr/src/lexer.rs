@@ -0,0 +1,208 @@
+use rust_decimal::Decimal;
+
+use crate::error::ParseError;
+use crate::value::parse_number;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Number(Decimal),
+    String(String),
+    Ident(String),
+    True,
+    False,
+    Null,
+    Dollar,
+    And,
+    Or,
+    Not,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Dot,
+    Comma,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    End,
+}
+
+impl Token {
+    /// How the token reads in error messages.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            Token::Number(n) => format!("number {n}"),
+            Token::String(s) => format!("string {s:?}"),
+            Token::Ident(name) => format!("`{name}`"),
+            Token::End => "end of expression".into(),
+            other => format!("`{}`", other.symbol()),
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Token::True => "true",
+            Token::False => "false",
+            Token::Null => "null",
+            Token::Dollar => "$",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Not => "!",
+            Token::Eq => "==",
+            Token::Ne => "!=",
+            Token::Lt => "<",
+            Token::Le => "<=",
+            Token::Gt => ">",
+            Token::Ge => ">=",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Star => "*",
+            Token::Slash => "/",
+            Token::Percent => "%",
+            Token::Dot => ".",
+            Token::Comma => ",",
+            Token::LParen => "(",
+            Token::RParen => ")",
+            Token::LBracket => "[",
+            Token::RBracket => "]",
+            Token::Number(_) | Token::String(_) | Token::Ident(_) | Token::End => "",
+        }
+    }
+}
+
+/// Split `source` into tokens paired with their byte offsets. The last
+/// token is always [`Token::End`].
+pub(crate) fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let token = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'0'..=b'9' => {
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                if i + 1 < bytes.len() && bytes[i] == b'.' && bytes[i + 1].is_ascii_digit() {
+                    i += 1;
+                    while i < bytes.len() && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                let text = &source[start..i];
+                let number = parse_number(text)
+                    .ok_or_else(|| ParseError::at(source, start, "number is too large"))?;
+                Token::Number(number)
+            }
+            b'"' | b'\'' => {
+                let (text, end) = string(source, start)?;
+                i = end;
+                Token::String(text)
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                match &source[start..i] {
+                    "true" => Token::True,
+                    "false" => Token::False,
+                    "null" => Token::Null,
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    name => Token::Ident(name.to_string()),
+                }
+            }
+            _ => {
+                let (token, len) = match (c, bytes.get(i + 1)) {
+                    (b'&', Some(b'&')) => (Token::And, 2),
+                    (b'|', Some(b'|')) => (Token::Or, 2),
+                    (b'=', Some(b'=')) => (Token::Eq, 2),
+                    (b'!', Some(b'=')) => (Token::Ne, 2),
+                    (b'<', Some(b'=')) => (Token::Le, 2),
+                    (b'>', Some(b'=')) => (Token::Ge, 2),
+                    (b'!', _) => (Token::Not, 1),
+                    (b'<', _) => (Token::Lt, 1),
+                    (b'>', _) => (Token::Gt, 1),
+                    (b'+', _) => (Token::Plus, 1),
+                    (b'-', _) => (Token::Minus, 1),
+                    (b'*', _) => (Token::Star, 1),
+                    (b'/', _) => (Token::Slash, 1),
+                    (b'%', _) => (Token::Percent, 1),
+                    (b'.', _) => (Token::Dot, 1),
+                    (b',', _) => (Token::Comma, 1),
+                    (b'(', _) => (Token::LParen, 1),
+                    (b')', _) => (Token::RParen, 1),
+                    (b'[', _) => (Token::LBracket, 1),
+                    (b']', _) => (Token::RBracket, 1),
+                    (b'$', _) => (Token::Dollar, 1),
+                    (b'=', _) => {
+                        return Err(ParseError::at(
+                            source,
+                            start,
+                            "unexpected `=`, use `==` to compare",
+                        ))
+                    }
+                    _ => {
+                        let ch = source[start..].chars().next().unwrap_or_default();
+                        return Err(ParseError::at(
+                            source,
+                            start,
+                            format!("unexpected character {ch:?}"),
+                        ));
+                    }
+                };
+                i += len;
+                token
+            }
+        };
+        tokens.push((token, start));
+    }
+    tokens.push((Token::End, source.len()));
+    Ok(tokens)
+}
+
+/// Read a quoted string starting at `start`, returning its text and the
+/// offset just past the closing quote.
+fn string(source: &str, start: usize) -> Result<(String, usize), ParseError> {
+    let mut chars = source[start..].char_indices();
+    let (_, quote) = chars.next().unwrap_or_default();
+    let mut text = String::new();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            c if c == quote => return Ok((text, start + at + 1)),
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some((_, 'n')) => '\n',
+                    Some((_, 't')) => '\t',
+                    Some((_, 'r')) => '\r',
+                    Some((_, c @ ('\\' | '"' | '\''))) => c,
+                    Some((_, other)) => {
+                        return Err(ParseError::at(
+                            source,
+                            start + at,
+                            format!("unknown escape `\\{other}`"),
+                        ))
+                    }
+                    None => break,
+                };
+                text.push(escaped);
+            }
+            c => text.push(c),
+        }
+    }
+    Err(ParseError::at(source, start, "unterminated string"))
+}
@@ -0,0 +1,133 @@
+//! A small, side-effect-free expression language for `condition` nodes.
+//!
+//! Expressions read the payload a node receives (the accumulated
+//! `inputData`) and compute a value:
+//!
+//! ```text
+//! balance < 50
+//! amount * 2 >= limit && network == "testnet"
+//! not contains(lower(message), "stop") or len(signers) >= 2
+//! result.balances[0].asset == "XLM"
+//! $["chat-id"] != null
+//! ```
+//!
+//! - Identifiers are payload keys; `.key` and `[index]` reach into nested
+//!   objects and lists, and `$` is the whole payload. Missing keys read as
+//!   `null`.
+//! - Numbers are exact decimals, so `0.1 + 0.2 == 0.3`. Numeric strings
+//!   (Horizon reports balances as `"100.0000000"`) are read as numbers
+//!   wherever a number is expected, and two of them order as numbers.
+//!   `==` only equates values of different types for a number and a
+//!   numeric string.
+//! - `[a, b]` builds a list.
+//! - `&&`/`and`, `||`/`or`, `!`/`not`, comparisons, `+ - * / %`, and
+//!   `+` to concatenate strings.
+//! - Functions: see [`FUNCTIONS`].
+//!
+//! There are no loops, assignments or calls out of the evaluator, and
+//! input size and nesting depth are capped, so evaluating untrusted
+//! expressions is safe.
+
+mod error;
+mod eval;
+mod lexer;
+mod parser;
+mod value;
+
+pub use error::{EvalError, ParseError};
+pub use value::Value;
+
+use serde_json::Map;
+
+/// Longest accepted source, in bytes.
+pub const MAX_LEN: usize = 4096;
+
+/// Deepest accepted nesting of sub-expressions.
+pub const MAX_DEPTH: usize = 64;
+
+/// Built-in functions and the number of arguments each takes.
+pub const FUNCTIONS: &[(&str, Arity)] = &[
+    ("len", Arity::Exactly(1)),
+    ("lower", Arity::Exactly(1)),
+    ("upper", Arity::Exactly(1)),
+    ("trim", Arity::Exactly(1)),
+    ("contains", Arity::Exactly(2)),
+    ("startsWith", Arity::Exactly(2)),
+    ("endsWith", Arity::Exactly(2)),
+    ("number", Arity::Exactly(1)),
+    ("string", Arity::Exactly(1)),
+    ("abs", Arity::Exactly(1)),
+    ("round", Arity::Between(1, 2)),
+    ("min", Arity::AtLeast(1)),
+    ("max", Arity::AtLeast(1)),
+];
+
+/// How many arguments a function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    Between(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(self, n: usize) -> bool {
+        match self {
+            Arity::Exactly(k) => n == k,
+            Arity::Between(lo, hi) => (lo..=hi).contains(&n),
+            Arity::AtLeast(k) => n >= k,
+        }
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: usize| if n == 1 { "argument" } else { "arguments" };
+        match *self {
+            Arity::Exactly(n) => write!(f, "{n} {}", plural(n)),
+            Arity::Between(lo, hi) => write!(f, "{lo} or {hi} {}", plural(hi)),
+            Arity::AtLeast(n) => write!(f, "at least {n} {}", plural(n)),
+        }
+    }
+}
+
+/// A parsed expression, ready to evaluate against any number of payloads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: parser::Expr,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            source: source.to_string(),
+            root: parser::parse(source)?,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluate against `payload`.
+    pub fn eval(&self, payload: &Map<String, serde_json::Value>) -> Result<Value, EvalError> {
+        eval::eval(&self.root, payload)
+    }
+
+    /// Evaluate against `payload`, requiring a boolean result.
+    pub fn eval_bool(&self, payload: &Map<String, serde_json::Value>) -> Result<bool, EvalError> {
+        match self.eval(payload)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(EvalError::NotBoolean(other.type_name())),
+        }
+    }
+}
+
+impl std::str::FromStr for Expression {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
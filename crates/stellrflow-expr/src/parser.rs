@@ -0,0 +1,305 @@
+use crate::error::ParseError;
+use crate::lexer::{tokenize, Token};
+use crate::value::Value;
+use crate::{FUNCTIONS, MAX_DEPTH, MAX_LEN};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinaryOp {
+    And,
+    Or,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Literal(Value),
+    /// `$`, the whole payload.
+    Payload,
+    /// A top-level payload key.
+    Var(String),
+    Member(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    List(Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(&'static str, Vec<Expr>),
+}
+
+pub(crate) fn parse(source: &str) -> Result<Expr, ParseError> {
+    if source.len() > MAX_LEN {
+        return Err(ParseError::at(
+            source,
+            MAX_LEN,
+            format!("expression is longer than {MAX_LEN} bytes"),
+        ));
+    }
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        source,
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    if parser.peek() == &Token::End {
+        return Err(ParseError::at(source, 0, "expression is empty"));
+    }
+    let expr = parser.expr()?;
+    match parser.peek() {
+        Token::End => Ok(expr),
+        other => Err(parser.error(format!("unexpected {}", other.describe()))),
+    }
+}
+
+/// Recursive descent, loosest binding first:
+/// `or`, `and`, comparison, `+ -`, `* / %`, unary, postfix, primary.
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), ParseError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {what}, found {}", self.peek().describe())))
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::at(self.source, self.offset(), message)
+    }
+
+    /// Count one more level of nesting, failing past [`MAX_DEPTH`].
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error(format!(
+                "expression is nested more than {MAX_DEPTH} levels deep"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run `f` one nesting level deeper.
+    fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        self.nest()?;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Parse a left-associative chain of `operand (op operand)*`.
+    fn chain(
+        &mut self,
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+        op: fn(&Token) -> Option<BinaryOp>,
+    ) -> Result<Expr, ParseError> {
+        let mut left = operand(self)?;
+        let depth = self.depth;
+        while let Some(op) = op(self.peek()) {
+            self.next();
+            self.nest()?;
+            let right = operand(self)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        self.nested(|p| p.chain(Self::and, |t| (t == &Token::Or).then_some(BinaryOp::Or)))
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        self.chain(Self::comparison, |t| {
+            (t == &Token::And).then_some(BinaryOp::And)
+        })
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        let left = self.sum()?;
+        let Some(op) = comparison_op(self.peek()) else {
+            return Ok(left);
+        };
+        self.next();
+        let right = self.nested(Self::sum)?;
+        if comparison_op(self.peek()).is_some() {
+            return Err(self.error("comparisons cannot be chained, combine them with `&&` instead"));
+        }
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        self.chain(Self::product, |t| match t {
+            Token::Plus => Some(BinaryOp::Add),
+            Token::Minus => Some(BinaryOp::Sub),
+            _ => None,
+        })
+    }
+
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        self.chain(Self::unary, |t| match t {
+            Token::Star => Some(BinaryOp::Mul),
+            Token::Slash => Some(BinaryOp::Div),
+            Token::Percent => Some(BinaryOp::Rem),
+            _ => None,
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat(&Token::Not) {
+            return self.nested(|p| Ok(Expr::Not(Box::new(p.unary()?))));
+        }
+        if self.eat(&Token::Minus) {
+            return self.nested(|p| Ok(Expr::Neg(Box::new(p.unary()?))));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+        let depth = self.depth;
+        loop {
+            if self.eat(&Token::Dot) {
+                self.nest()?;
+                match self.next() {
+                    Token::Ident(name) => expr = Expr::Member(Box::new(expr), name),
+                    other => {
+                        self.pos -= usize::from(other != Token::End);
+                        return Err(self.error(format!(
+                            "expected a field name after `.`, found {}",
+                            other.describe()
+                        )));
+                    }
+                }
+            } else if self.eat(&Token::LBracket) {
+                self.nest()?;
+                let index = self.expr()?;
+                self.expect(Token::RBracket, "`]`")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                break;
+            }
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let offset = self.offset();
+        Ok(match self.next() {
+            Token::Number(n) => Expr::Literal(Value::Number(n)),
+            Token::String(s) => Expr::Literal(Value::String(s)),
+            Token::True => Expr::Literal(Value::Bool(true)),
+            Token::False => Expr::Literal(Value::Bool(false)),
+            Token::Null => Expr::Literal(Value::Null),
+            Token::Dollar => Expr::Payload,
+            Token::LBracket => Expr::List(self.items(Token::RBracket, "`,` or `]`")?),
+            Token::LParen => {
+                let inner = self.expr()?;
+                self.expect(Token::RParen, "`)`")?;
+                inner
+            }
+            Token::Ident(name) if self.peek() == &Token::LParen => {
+                self.next();
+                self.call(&name, offset)?
+            }
+            Token::Ident(name) => Expr::Var(name),
+            other => {
+                self.pos -= usize::from(other != Token::End);
+                return Err(self.error(format!("unexpected {}", other.describe())));
+            }
+        })
+    }
+
+    /// Arguments of a call to `name`, whose opening parenthesis has been
+    /// consumed.
+    fn call(&mut self, name: &str, offset: usize) -> Result<Expr, ParseError> {
+        let Some(&(name, arity)) = FUNCTIONS.iter().find(|(f, _)| *f == name) else {
+            return Err(ParseError::at(
+                self.source,
+                offset,
+                format!("unknown function `{name}`"),
+            ));
+        };
+        let args = self.items(Token::RParen, "`,` or `)`")?;
+        if !arity.accepts(args.len()) {
+            return Err(ParseError::at(
+                self.source,
+                offset,
+                format!("`{name}` takes {arity}, got {}", args.len()),
+            ));
+        }
+        Ok(Expr::Call(name, args))
+    }
+
+    /// Comma-separated expressions up to `close`, whose opening bracket
+    /// has been consumed.
+    fn items(&mut self, close: Token, what: &str) -> Result<Vec<Expr>, ParseError> {
+        let mut items = Vec::new();
+        if self.eat(&close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.expr()?);
+            if self.eat(&close) {
+                return Ok(items);
+            }
+            self.expect(Token::Comma, what)?;
+        }
+    }
+}
+
+fn comparison_op(token: &Token) -> Option<BinaryOp> {
+    Some(match token {
+        Token::Eq => BinaryOp::Eq,
+        Token::Ne => BinaryOp::Ne,
+        Token::Lt => BinaryOp::Lt,
+        Token::Le => BinaryOp::Le,
+        Token::Gt => BinaryOp::Gt,
+        Token::Ge => BinaryOp::Ge,
+        _ => return None,
+    })
+}
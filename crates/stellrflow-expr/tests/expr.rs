@@ -0,0 +1,166 @@
+use serde_json::{json, Map, Value as Json};
+use stellrflow_expr::{EvalError, Expression, Value, MAX_DEPTH, MAX_LEN};
+
+fn payload() -> Map<String, Json> {
+    let Json::Object(map) = json!({
+        "balance": "42.5000000",
+        "amount": 10,
+        "network": "testnet",
+        "message": "  Please STOP  ",
+        "signers": ["GA", "GB"],
+        "result": { "balances": [{ "asset": "XLM", "balance": "100.0000000" }] },
+        "chat-id": 7,
+    }) else {
+        unreachable!()
+    };
+    map
+}
+
+fn eval(src: &str) -> Result<Value, EvalError> {
+    Expression::parse(src).unwrap().eval(&payload())
+}
+
+fn truthy(src: &str) -> bool {
+    Expression::parse(src)
+        .unwrap()
+        .eval_bool(&payload())
+        .unwrap()
+}
+
+fn parse_error(src: &str) -> String {
+    Expression::parse(src).unwrap_err().to_string()
+}
+
+#[test]
+fn reads_the_payload() {
+    assert!(truthy("balance < 50"));
+    assert!(truthy("amount * 2 >= 20 && network == 'testnet'"));
+    assert!(truthy("result.balances[0].asset == \"XLM\""));
+    assert!(truthy("result.balances[-1].balance > balance"));
+    assert!(truthy("$[\"chat-id\"] == 7"));
+    assert!(truthy("missing == null && missing.deeper == null"));
+    assert!(truthy("result.balances[5] == null"));
+}
+
+#[test]
+fn follows_precedence() {
+    assert_eq!(eval("1 + 2 * 3").unwrap().to_string(), "7");
+    assert_eq!(eval("(1 + 2) * 3").unwrap().to_string(), "9");
+    assert_eq!(eval("-2 * -3 - 1").unwrap().to_string(), "5");
+    assert_eq!(eval("7 % 4 + 10 / 4").unwrap().to_string(), "5.5");
+    assert!(truthy("true || false && false"));
+    assert!(truthy("not false and !(1 > 2)"));
+}
+
+#[test]
+fn numbers_are_exact() {
+    assert!(truthy("0.1 + 0.2 == 0.3"));
+    assert!(truthy("balance == 42.5"));
+    assert!(truthy("'10' == amount"));
+    assert!(!truthy("'10' == '10.0'"));
+    assert!(!truthy("1 == true"));
+}
+
+#[test]
+fn strings_concatenate_and_compare() {
+    assert_eq!(
+        eval("'Balance: ' + balance + ' XLM'").unwrap(),
+        Value::String("Balance: 42.5000000 XLM".into())
+    );
+    assert!(truthy("'apple' < 'banana'"));
+    assert!(truthy("amount + '5' == 15"));
+}
+
+#[test]
+fn calls_functions() {
+    assert!(truthy("contains(lower(message), 'stop')"));
+    assert!(truthy("trim(message) == 'Please STOP'"));
+    assert!(truthy("len(signers) >= 2 && contains(signers, 'GB')"));
+    assert!(truthy(
+        "startsWith(network, 'test') && endsWith(upper(network), 'NET')"
+    ));
+    assert!(truthy("contains(result, 'balances')"));
+    assert_eq!(eval("round(2.345, 2)").unwrap().to_string(), "2.35");
+    assert_eq!(eval("round(-2.5)").unwrap().to_string(), "-3");
+    assert_eq!(eval("max(1, balance, amount)").unwrap().to_string(), "42.5");
+    assert_eq!(eval("min([3, 1, 2])").unwrap().to_string(), "1");
+    assert_eq!(eval("abs(number('-4'))").unwrap().to_string(), "4");
+    assert_eq!(eval("string(amount) + 1").unwrap().to_string(), "11");
+}
+
+#[test]
+fn short_circuits() {
+    assert!(!truthy("false && missing.x > 1"));
+    assert!(truthy("true || 1 / 0 > 1"));
+}
+
+#[test]
+fn reports_evaluation_errors() {
+    assert_eq!(eval("amount / 0"), Err(EvalError::DivisionByZero));
+    assert_eq!(
+        eval("missing < 5").unwrap_err().to_string(),
+        "cannot compare null with number 5"
+    );
+    assert_eq!(
+        eval("network && true").unwrap_err().to_string(),
+        "`&&` expects booleans, got string \"testnet\""
+    );
+    assert_eq!(
+        eval("network.name").unwrap_err().to_string(),
+        "cannot read `.name` of string \"testnet\""
+    );
+    assert_eq!(
+        eval("79228162514264337593543950335 + 1"),
+        Err(EvalError::Overflow)
+    );
+    assert_eq!(
+        Expression::parse("amount").unwrap().eval_bool(&payload()),
+        Err(EvalError::NotBoolean("number"))
+    );
+}
+
+#[test]
+fn reports_syntax_errors_with_columns() {
+    assert_eq!(parse_error(""), "expression is empty at column 1");
+    assert_eq!(
+        parse_error("balance = 5"),
+        "unexpected `=`, use `==` to compare at column 9"
+    );
+    assert_eq!(
+        parse_error("1 < x < 3"),
+        "comparisons cannot be chained, combine them with `&&` instead at column 7"
+    );
+    assert_eq!(
+        parse_error("exec('rm -rf /')"),
+        "unknown function `exec` at column 1"
+    );
+    assert_eq!(
+        parse_error("len(a, b)"),
+        "`len` takes 1 argument, got 2 at column 1"
+    );
+    assert_eq!(parse_error("'open"), "unterminated string at column 1");
+    assert_eq!(
+        parse_error("(a + b"),
+        "expected `)`, found end of expression at column 7"
+    );
+    assert_eq!(parse_error("a b"), "unexpected `b` at column 3");
+    assert_eq!(parse_error("é"), "unexpected character 'é' at column 1");
+}
+
+#[test]
+fn limits_size_and_depth() {
+    let long = "1".repeat(MAX_LEN + 1);
+    assert!(parse_error(&long).starts_with("expression is longer than 4096 bytes"));
+
+    let deep = format!(
+        "{}1{}",
+        "(".repeat(MAX_DEPTH + 1),
+        ")".repeat(MAX_DEPTH + 1)
+    );
+    assert!(parse_error(&deep).starts_with("expression is nested more than 64 levels deep"));
+    let chain = vec!["1"; MAX_DEPTH + 2].join(" + ");
+    assert!(Expression::parse(&chain).is_err());
+
+    let shallow = format!("{}1{}", "(".repeat(10), ")".repeat(10));
+    assert!(Expression::parse(&shallow).is_ok());
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use stellrflow_engine::{Edge, NodeData};
+
+use crate::error::FormatError;
+use crate::{upgrade, FORMAT_VERSION};
+
+/// A `.stellrflow.json` file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub format: Format,
+    pub format_version: u32,
+    #[serde(default)]
+    pub metadata: Metadata,
+    pub nodes: Vec<WorkflowNode>,
+    #[serde(default)]
+    pub edges: Vec<Edge>,
+}
+
+/// The `format` marker that identifies a workflow file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Format {
+    #[default]
+    #[serde(rename = "stellrflow")]
+    Stellrflow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self {
+            name: "Untitled workflow".into(),
+            description: String::new(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// A node as placed on the canvas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowNode {
+    pub id: String,
+    #[serde(default)]
+    pub position: Position,
+    pub data: NodeData,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Document {
+    pub fn new(metadata: Metadata, nodes: Vec<WorkflowNode>, edges: Vec<Edge>) -> Self {
+        Self {
+            format: Format::Stellrflow,
+            format_version: FORMAT_VERSION,
+            metadata,
+            nodes,
+            edges,
+        }
+    }
+
+    /// Build a document from the builder's ReactFlow `{ nodes, edges }`.
+    pub fn from_graph(metadata: Metadata, graph: Value) -> Result<Self, FormatError> {
+        let mut document: Document = serde_json::from_value(upgrade(graph)?)?;
+        document.metadata = metadata;
+        document.check()?;
+        Ok(document)
+    }
+
+    /// The ReactFlow `{ nodes, edges }` the builder loads.
+    pub fn to_graph(&self) -> Value {
+        let nodes: Vec<Value> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                json!({
+                    "id": node.id,
+                    "type": "customNode",
+                    "position": node.position,
+                    "data": node.data,
+                })
+            })
+            .collect();
+        json!({ "nodes": nodes, "edges": self.edges })
+    }
+
+    /// Structural checks: unique node IDs and edges between existing nodes.
+    /// Whether the graph can run is left to the engine's validator.
+    pub(crate) fn check(&self) -> Result<(), FormatError> {
+        let mut ids = HashSet::new();
+        for node in &self.nodes {
+            if !ids.insert(node.id.as_str()) {
+                return Err(FormatError::Invalid(format!(
+                    "duplicate node ID `{}`",
+                    node.id
+                )));
+            }
+        }
+        for edge in &self.edges {
+            for end in [&edge.source, &edge.target] {
+                if !ids.contains(end.as_str()) {
+                    return Err(FormatError::Invalid(format!(
+                        "edge `{}` refers to missing node `{end}`",
+                        edge.id
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
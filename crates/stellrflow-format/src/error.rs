@@ -0,0 +1,25 @@
+use stellrflow_nodes::SchemaError;
+use thiserror::Error;
+
+/// Why a workflow file could not be read or written.
+#[derive(Debug, Error)]
+pub enum FormatError {
+    /// The JSON is neither a `.stellrflow.json` document nor a builder graph.
+    #[error("not a StellrFlow workflow file")]
+    NotAWorkflow,
+    #[error("format version {found} is newer than this build supports ({supported})")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    /// Duplicate node IDs, edges to missing nodes, and the like.
+    #[error("invalid workflow file: {0}")]
+    Invalid(String),
+    /// A node's config could not be brought to its current schema, so its
+    /// secrets cannot be located reliably.
+    #[error("node `{node_id}`: {source}")]
+    Config {
+        node_id: String,
+        #[source]
+        source: SchemaError,
+    },
+    #[error("malformed JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
@@ -0,0 +1,55 @@
+//! The `.stellrflow.json` file format for sharing workflows.
+//!
+//! A [`Document`] holds a workflow's nodes (with their `NodeData` and canvas
+//! position), its edges (with handles) and some [`Metadata`], tagged with a
+//! `format` marker and a [`FORMAT_VERSION`]:
+//!
+//! ```json
+//! {
+//!   "format": "stellrflow",
+//!   "formatVersion": 1,
+//!   "metadata": { "name": "Balance alerts" },
+//!   "nodes": [{ "id": "t", "position": { "x": 0.0, "y": 0.0 }, "data": { "type": "telegram-trigger", ... } }],
+//!   "edges": [{ "id": "e1", "source": "t", "target": "s", "sourceHandle": null, "targetHandle": null }]
+//! }
+//! ```
+//!
+//! [`serialize`] is the export path: node configs are brought to their
+//! current schema version and anything identifying the author — chat IDs,
+//! Stellar addresses — is blanked, so a file can be shared as is.
+//! [`parse`] is the import path; it runs [`upgrade`] first, which also
+//! accepts the builder's plain `{ nodes, edges }` graph as version 0.
+
+mod document;
+mod error;
+mod secrets;
+mod upgrade;
+
+pub use document::{Document, Format, Metadata, Position, WorkflowNode};
+pub use error::FormatError;
+pub use secrets::Redaction;
+pub use upgrade::upgrade;
+
+use serde_json::Value;
+
+/// The version [`serialize`] writes and [`parse`] upgrades to.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Suffix for exported files.
+pub const FILE_EXTENSION: &str = ".stellrflow.json";
+
+/// Read a workflow file of any supported version.
+pub fn parse(json: &str) -> Result<Document, FormatError> {
+    let value: Value = serde_json::from_str(json)?;
+    let document: Document = serde_json::from_value(upgrade(value)?)?;
+    document.check()?;
+    Ok(document)
+}
+
+/// Export `document` as pretty-printed JSON with its secrets removed.
+pub fn serialize(document: &Document) -> Result<String, FormatError> {
+    let mut document = document.clone();
+    document.check()?;
+    document.redact()?;
+    Ok(serde_json::to_string_pretty(&document)?)
+}
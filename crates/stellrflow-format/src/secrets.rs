@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use stellrflow_nodes::SchemaRegistry;
+
+use crate::document::Document;
+use crate::error::FormatError;
+
+/// Config keys blanked for node types without a schema.
+const UNKNOWN_TYPE_SECRETS: &[&str] = &["chatId"];
+
+/// A config value blanked on export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Redaction {
+    pub node_id: String,
+    pub key: String,
+}
+
+impl Document {
+    /// Bring every config to its current schema version and blank the keys
+    /// its schema lists as secrets, plus any value holding a Stellar
+    /// address. Returns what was blanked.
+    pub fn redact(&mut self) -> Result<Vec<Redaction>, FormatError> {
+        let registry = SchemaRegistry::builtin();
+        let mut redactions = Vec::new();
+        for node in &mut self.nodes {
+            let data = &mut node.data;
+            let secrets = match registry.get(&data.node_type) {
+                Some(schema) => {
+                    data.config = schema
+                        .normalize(&data.config, data.config_version)
+                        .map_err(|source| FormatError::Config {
+                            node_id: node.id.clone(),
+                            source,
+                        })?;
+                    data.config_version = Some(schema.version);
+                    schema.secrets
+                }
+                None => UNKNOWN_TYPE_SECRETS,
+            };
+            for (key, value) in data.config.iter_mut() {
+                if is_blank(value) {
+                    continue;
+                }
+                if secrets.contains(&key.as_str()) || holds_address(value) {
+                    *value = blank(value);
+                    redactions.push(Redaction {
+                        node_id: node.id.clone(),
+                        key: key.clone(),
+                    });
+                }
+            }
+        }
+        Ok(redactions)
+    }
+}
+
+/// An empty value of the same JSON type, so the config still parses.
+fn blank(value: &Value) -> Value {
+    match value {
+        Value::Array(_) => Value::Array(Vec::new()),
+        Value::Object(_) => Value::Object(Map::new()),
+        _ => Value::String(String::new()),
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+fn holds_address(value: &Value) -> bool {
+    match value {
+        Value::String(s) => is_address(s.trim()),
+        Value::Array(items) => items.iter().any(holds_address),
+        _ => false,
+    }
+}
+
+/// A Stellar account (`G…`) or muxed account (`M…`) in strkey form.
+fn is_address(s: &str) -> bool {
+    let len_ok = match s.as_bytes().first() {
+        Some(b'G') => s.len() == 56,
+        Some(b'M') => s.len() == 69,
+        _ => false,
+    };
+    len_ok
+        && s.bytes()
+            .all(|b| b.is_ascii_uppercase() || (b'2'..=b'7').contains(&b))
+}
@@ -0,0 +1,81 @@
+use serde_json::{json, Map, Value};
+
+use crate::document::Metadata;
+use crate::error::FormatError;
+use crate::FORMAT_VERSION;
+
+/// Bring a workflow file to [`FORMAT_VERSION`].
+///
+/// This works on raw JSON so that fields of older versions can be renamed
+/// or moved before the current types see them. A bare builder graph
+/// (`{ nodes, edges }` without a `format` marker) counts as version 0.
+pub fn upgrade(value: Value) -> Result<Value, FormatError> {
+    let Value::Object(document) = value else {
+        return Err(FormatError::NotAWorkflow);
+    };
+    let version = match document.get("format") {
+        None if document.contains_key("nodes") => 0,
+        Some(Value::String(format)) if format == "stellrflow" => {
+            let version = document
+                .get("formatVersion")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| FormatError::Invalid("missing `formatVersion`".into()))?;
+            u32::try_from(version).unwrap_or(u32::MAX)
+        }
+        _ => return Err(FormatError::NotAWorkflow),
+    };
+    if version > FORMAT_VERSION {
+        return Err(FormatError::UnsupportedVersion {
+            found: version,
+            supported: FORMAT_VERSION,
+        });
+    }
+
+    let document = match version {
+        0 => from_graph(&document)?,
+        _ => document,
+    };
+    Ok(Value::Object(document))
+}
+
+/// Version 0 → 1: keep what the file format defines and drop ReactFlow view
+/// state (`selected`, `dragging`, `style`, …).
+fn from_graph(graph: &Map<String, Value>) -> Result<Map<String, Value>, FormatError> {
+    let array = |key: &str| match graph.get(key) {
+        Some(Value::Array(items)) => Ok(items.as_slice()),
+        None if key == "edges" => Ok(&[][..]),
+        _ => Err(FormatError::Invalid(format!("`{key}` must be an array"))),
+    };
+    let field = |item: &Value, key: &str| item.get(key).cloned().unwrap_or(Value::Null);
+
+    let nodes: Vec<Value> = array("nodes")?
+        .iter()
+        .map(|node| {
+            json!({
+                "id": field(node, "id"),
+                "position": node.get("position").cloned().unwrap_or(json!({ "x": 0, "y": 0 })),
+                "data": field(node, "data"),
+            })
+        })
+        .collect();
+    let edges: Vec<Value> = array("edges")?
+        .iter()
+        .map(|edge| {
+            json!({
+                "id": field(edge, "id"),
+                "source": field(edge, "source"),
+                "target": field(edge, "target"),
+                "sourceHandle": field(edge, "sourceHandle"),
+                "targetHandle": field(edge, "targetHandle"),
+            })
+        })
+        .collect();
+
+    let mut document = Map::new();
+    document.insert("format".into(), json!("stellrflow"));
+    document.insert("formatVersion".into(), json!(1));
+    document.insert("metadata".into(), json!(Metadata::default()));
+    document.insert("nodes".into(), nodes.into());
+    document.insert("edges".into(), edges.into());
+    Ok(document)
+}
@@ -0,0 +1,158 @@
+use serde_json::{json, Value};
+use stellrflow_format::{parse, serialize, upgrade, Document, FormatError, Metadata, Redaction};
+
+const ADDRESS: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+
+/// The builder's in-memory graph, ReactFlow view state included.
+fn graph() -> Value {
+    json!({
+        "nodes": [
+            {
+                "id": "t",
+                "type": "customNode",
+                "position": { "x": 100, "y": 50 },
+                "selected": true,
+                "data": {
+                    "type": "telegram-trigger",
+                    "label": "Telegram",
+                    "config": { "chatId": "123456789" },
+                },
+            },
+            {
+                "id": "pay",
+                "type": "customNode",
+                "position": { "x": 100, "y": 200 },
+                "data": {
+                    "type": "autopay",
+                    "label": "AutoPay",
+                    "config": { "destinationAddress": ADDRESS, "amount": "5", "totalDuration": "7d" },
+                },
+            },
+        ],
+        "edges": [{ "id": "e1", "source": "t", "target": "pay", "animated": true }],
+    })
+}
+
+#[test]
+fn exports_without_secrets() {
+    let mut document = Document::from_graph(Metadata::default(), graph()).unwrap();
+    let json = serialize(&document).unwrap();
+    assert!(!json.contains("123456789"));
+    assert!(!json.contains(ADDRESS));
+
+    let exported: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(exported["format"], "stellrflow");
+    assert_eq!(exported["formatVersion"], 1);
+    // Legacy configs are exported in their current shape.
+    assert_eq!(
+        exported["nodes"][1]["data"]["config"],
+        json!({ "destination": "", "amount": "5", "interval": "daily", "duration": "7d" })
+    );
+    assert_eq!(exported["nodes"][1]["data"]["configVersion"], 2);
+
+    let redactions = document.redact().unwrap();
+    assert_eq!(
+        redactions,
+        [
+            Redaction {
+                node_id: "t".into(),
+                key: "chatId".into()
+            },
+            Redaction {
+                node_id: "pay".into(),
+                key: "destination".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn blanks_addresses_outside_declared_secrets() {
+    let graph = json!({
+        "nodes": [{
+            "id": "x",
+            "data": { "type": "custom-plugin", "config": { "payee": ADDRESS, "chatId": 42, "note": "hi" } },
+        }],
+    });
+    let document = Document::from_graph(Metadata::default(), graph).unwrap();
+    let exported = parse(&serialize(&document).unwrap()).unwrap();
+    assert_eq!(
+        Value::Object(exported.nodes[0].data.config.clone()),
+        json!({ "payee": "", "chatId": "", "note": "hi" })
+    );
+}
+
+#[test]
+fn imports_builder_graphs_as_version_zero() {
+    let document = parse(&graph().to_string()).unwrap();
+    assert_eq!(document.metadata.name, "Untitled workflow");
+    assert_eq!(document.nodes[0].position.x, 100.0);
+    assert_eq!(document.edges[0].source_handle, None);
+
+    let upgraded = upgrade(graph()).unwrap();
+    assert!(upgraded["nodes"][0].get("selected").is_none());
+    assert!(upgraded["edges"][0].get("animated").is_none());
+}
+
+#[test]
+fn to_graph_is_what_the_builder_loads() {
+    let document = parse(&graph().to_string()).unwrap();
+    let graph = document.to_graph();
+    assert_eq!(graph["nodes"][0]["type"], "customNode");
+    assert_eq!(
+        graph["nodes"][1]["data"]["config"]["destinationAddress"],
+        ADDRESS
+    );
+    assert_eq!(
+        Document::from_graph(document.metadata.clone(), graph).unwrap(),
+        document
+    );
+}
+
+#[test]
+fn rejects_unknown_and_future_files() {
+    assert!(matches!(
+        parse(r#"{ "format": "n8n", "nodes": [] }"#),
+        Err(FormatError::NotAWorkflow)
+    ));
+    assert!(matches!(parse("[]"), Err(FormatError::NotAWorkflow)));
+    assert!(matches!(
+        parse(r#"{ "format": "stellrflow", "formatVersion": 7, "nodes": [] }"#),
+        Err(FormatError::UnsupportedVersion {
+            found: 7,
+            supported: 1
+        })
+    ));
+}
+
+#[test]
+fn rejects_broken_structure() {
+    let dangling = json!({ "nodes": [{ "id": "a", "data": { "type": "delay" } }], "edges": [{ "id": "e", "source": "a", "target": "b" }] });
+    let err = parse(&dangling.to_string()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid workflow file: edge `e` refers to missing node `b`"
+    );
+
+    let duplicate = json!({ "nodes": [
+        { "id": "a", "data": { "type": "delay" } },
+        { "id": "a", "data": { "type": "delay" } },
+    ] });
+    assert!(matches!(
+        parse(&duplicate.to_string()),
+        Err(FormatError::Invalid(_))
+    ));
+}
+
+#[test]
+fn refuses_to_export_configs_it_cannot_read() {
+    let graph = json!({ "nodes": [{
+        "id": "pay",
+        "data": { "type": "autopay", "config": { "destinationAddress": ADDRESS, "destination": "GOTHER" } },
+    }] });
+    let document = Document::from_graph(Metadata::default(), graph).unwrap();
+    match serialize(&document) {
+        Err(FormatError::Config { node_id, .. }) => assert_eq!(node_id, "pay"),
+        other => panic!("unexpected {other:?}"),
+    }
+}
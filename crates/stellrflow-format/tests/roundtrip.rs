@@ -0,0 +1,148 @@
+use proptest::collection::{btree_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+use stellrflow_engine::{Edge, NodeData};
+use stellrflow_format::{parse, serialize, Document, Metadata, Position, WorkflowNode};
+use stellrflow_nodes::SchemaRegistry;
+
+/// A chat ID or Stellar address planted in a secret config key.
+fn secret() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[1-9][0-9]{8,11}",
+        "G[A-Z2-7]{55}".prop_map(|s| s.to_string()),
+    ]
+}
+
+/// A registered node type with its default config and secrets filled in,
+/// or an unknown type with free-form string values.
+fn node_data() -> impl Strategy<Value = (NodeData, Vec<String>)> {
+    let types: Vec<&'static str> = SchemaRegistry::builtin()
+        .iter()
+        .map(|s| s.node_type)
+        .collect();
+    let known = (proptest::sample::select(types), vec(secret(), 3)).prop_map(|(ty, secrets)| {
+        let registry = SchemaRegistry::builtin();
+        let schema = registry.get(ty).unwrap();
+        let mut config = schema.default_config();
+        let mut planted = Vec::new();
+        for (key, secret) in schema.secrets.iter().zip(&secrets) {
+            let value = match config[*key] {
+                // Enough signers for the default threshold.
+                Value::Array(_) => {
+                    planted.extend(secrets.iter().cloned());
+                    secrets.iter().cloned().map(Value::String).collect()
+                }
+                _ => {
+                    planted.push(secret.clone());
+                    Value::String(secret.clone())
+                }
+            };
+            config.insert(key.to_string(), value);
+        }
+        let data = NodeData {
+            label: schema.label.into(),
+            node_type: ty.into(),
+            icon: schema.icon.into(),
+            description: schema.description.into(),
+            config,
+            config_version: Some(schema.version),
+        };
+        (data, planted)
+    });
+    let unknown = (
+        "[a-z]{3,8}-plugin",
+        btree_map("[a-z][a-zA-Z]{0,7}", "\\PC{0,12}", 0..4),
+    )
+        .prop_map(|(ty, values)| {
+            let config: Map<String, Value> = values
+                .into_iter()
+                .map(|(k, v)| (k, Value::String(v)))
+                .collect();
+            let data = NodeData {
+                node_type: ty,
+                config,
+                ..NodeData::default()
+            };
+            (data, Vec::new())
+        });
+    prop_oneof![known, unknown]
+}
+
+/// A structurally valid document and the secrets planted in it.
+fn document() -> impl Strategy<Value = (Document, Vec<String>)> {
+    let metadata = ("\\PC{1,20}", "\\PC{0,40}", vec("[a-z]{1,8}", 0..3)).prop_map(
+        |(name, description, tags)| Metadata {
+            name,
+            description,
+            tags,
+        },
+    );
+    // Half-pixel steps: positions survive JSON exactly.
+    let coordinate = || (-10_000..10_000i32).prop_map(|v| f64::from(v) / 2.0);
+    let nodes = vec((node_data(), coordinate(), coordinate()), 1..8);
+    (metadata, nodes)
+        .prop_flat_map(|(metadata, nodes)| {
+            let n = nodes.len();
+            let handle = option::of(prop_oneof![Just("true"), Just("false")]);
+            let edges = vec((0..n, 0..n, handle), 0..(n * 2));
+            (Just(metadata), Just(nodes), edges)
+        })
+        .prop_map(|(metadata, nodes, edges)| {
+            let mut planted = Vec::new();
+            let nodes: Vec<WorkflowNode> = nodes
+                .into_iter()
+                .enumerate()
+                .map(|(i, ((data, secrets), x, y))| {
+                    planted.extend(secrets);
+                    WorkflowNode {
+                        id: format!("n{i}"),
+                        position: Position { x, y },
+                        data,
+                    }
+                })
+                .collect();
+            let edges = edges
+                .into_iter()
+                .enumerate()
+                .map(|(i, (source, target, handle))| Edge {
+                    id: format!("e{i}"),
+                    source: format!("n{source}"),
+                    target: format!("n{target}"),
+                    source_handle: handle.map(str::to_string),
+                    target_handle: None,
+                })
+                .collect();
+            (Document::new(metadata, nodes, edges), planted)
+        })
+}
+
+proptest! {
+    #[test]
+    fn export_then_import_keeps_everything_but_secrets((document, _) in document()) {
+        let json = serialize(&document).unwrap();
+        let imported = parse(&json).unwrap();
+
+        let mut expected = document.clone();
+        expected.redact().unwrap();
+        prop_assert_eq!(&imported, &expected);
+        // Exporting an imported file changes nothing.
+        prop_assert_eq!(serialize(&imported).unwrap(), json);
+    }
+
+    #[test]
+    fn exports_never_contain_planted_secrets((document, planted) in document()) {
+        let json = serialize(&document).unwrap();
+        for secret in planted {
+            prop_assert!(!json.contains(&secret), "{} leaked", secret);
+        }
+    }
+
+    #[test]
+    fn builder_graphs_import_losslessly((document, _) in document()) {
+        let graph = document.to_graph();
+        let imported = parse(&graph.to_string()).unwrap();
+        prop_assert_eq!(imported.nodes, document.nodes);
+        prop_assert_eq!(imported.edges, document.edges);
+    }
+}
@@ -0,0 +1,46 @@
+use thiserror::Error;
+
+/// Why a keystore call failed.
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    /// Master keys are written `id:secret`, with a secret of at least
+    /// [`MIN_SECRET_LEN`](crate::MIN_SECRET_LEN) bytes.
+    #[error("invalid master key: {0}")]
+    InvalidMasterKey(String),
+    #[error("invalid key derivation parameters: {0}")]
+    InvalidParams(String),
+    /// The file is the bot's plaintext `wallets.json`; run `migrate` first.
+    #[error("{0} holds plaintext secrets: migrate it with `stellrflow-keystore migrate`")]
+    Plaintext(String),
+    /// `migrate` was pointed at a file that is already encrypted.
+    #[error("{0} is already encrypted")]
+    AlreadyEncrypted(String),
+    #[error("unsupported keystore version {0}")]
+    UnsupportedVersion(u32),
+    #[error("no Telegram wallet for chat {0}")]
+    NotFound(String),
+    /// The wallet was encrypted under a master key that was not supplied.
+    #[error("wallet for chat {chat_id} is encrypted with unknown master key `{key_id}`")]
+    UnknownKey { chat_id: String, key_id: String },
+    /// Wrong master secret, or the entry was modified on disk.
+    #[error("wallet for chat {0} could not be decrypted: wrong master key or tampered entry")]
+    Decrypt(String),
+    #[error("wallet for chat {0} does not have a valid Stellar secret key")]
+    InvalidSecret(String),
+    /// A save left out the secret of a wallet the keystore does not hold.
+    #[error("no secret key for chat {0}'s new wallet")]
+    MissingSecret(String),
+    /// The secret key belongs to a different account than the one recorded.
+    #[error("secret key for chat {chat_id} belongs to {actual}, not {expected}")]
+    KeyMismatch {
+        chat_id: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("corrupt wallet entry for chat {0}")]
+    Corrupt(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid wallet file: {0}")]
+    Json(#[from] serde_json::Error),
+}
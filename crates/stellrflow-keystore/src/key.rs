@@ -0,0 +1,250 @@
+use std::fmt;
+use std::str::FromStr;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::KeystoreError;
+
+/// The shortest master secret accepted, in bytes.
+pub const MIN_SECRET_LEN: usize = 32;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// A server master secret and the ID wallets encrypted under it record.
+///
+/// Written `id:secret`, e.g. `2026-10:…`. The ID names the key in the file
+/// so that a rotated-out key can still be found; it is not secret.
+#[derive(Clone)]
+pub struct MasterKey {
+    id: String,
+    secret: Zeroizing<Vec<u8>>,
+}
+
+impl MasterKey {
+    pub fn new(id: impl Into<String>, secret: impl Into<Vec<u8>>) -> Result<Self, KeystoreError> {
+        let id = id.into();
+        let secret = Zeroizing::new(secret.into());
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(KeystoreError::InvalidMasterKey(format!(
+                "key ID `{id}` must be letters, digits, `-`, `_` or `.`"
+            )));
+        }
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(KeystoreError::InvalidMasterKey(format!(
+                "the secret for `{id}` is {} bytes; use at least {MIN_SECRET_LEN}",
+                secret.len()
+            )));
+        }
+        Ok(MasterKey { id, secret })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl FromStr for MasterKey {
+    type Err = KeystoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, secret) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| KeystoreError::InvalidMasterKey("expected `id:secret`".into()))?;
+        MasterKey::new(id, secret.as_bytes())
+    }
+}
+
+impl fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MasterKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Argon2id cost settings. Each wallet records the ones it was encrypted
+/// with, so raising them only affects wallets sealed afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 19 MiB, two passes, one lane: the OWASP baseline for Argon2id.
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn argon2(self) -> Result<Argon2<'static>, KeystoreError> {
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|e| KeystoreError::InvalidParams(e.to_string()))?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    pub(crate) fn validate(self) -> Result<(), KeystoreError> {
+        self.argon2().map(drop)
+    }
+}
+
+/// The current master key, the ones it replaced, and the cost of sealing
+/// new wallets.
+#[derive(Debug, Clone)]
+pub struct Keyring {
+    current: MasterKey,
+    previous: Vec<MasterKey>,
+    params: KdfParams,
+}
+
+impl Keyring {
+    /// Seals new wallets with `current` and [`KdfParams::default`].
+    pub fn new(current: MasterKey) -> Self {
+        Keyring {
+            current,
+            previous: Vec::new(),
+            params: KdfParams::default(),
+        }
+    }
+
+    /// The keys in `KEYSTORE_MASTER_KEY` and the comma-separated
+    /// `KEYSTORE_PREVIOUS_KEYS`.
+    pub fn from_env() -> Result<Self, KeystoreError> {
+        let current: MasterKey = std::env::var("KEYSTORE_MASTER_KEY")
+            .map_err(|_| KeystoreError::InvalidMasterKey("KEYSTORE_MASTER_KEY is not set".into()))?
+            .parse()?;
+        let previous = std::env::var("KEYSTORE_PREVIOUS_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|key| !key.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<MasterKey>, _>>()?;
+        Ok(Keyring::new(current).with_previous(previous))
+    }
+
+    /// Keys that wallets may still be encrypted under, until a rotation
+    /// moves them to the current one.
+    pub fn with_previous(mut self, keys: impl IntoIterator<Item = MasterKey>) -> Self {
+        self.previous.extend(keys);
+        self
+    }
+
+    pub fn with_params(mut self, params: KdfParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn current(&self) -> &MasterKey {
+        &self.current
+    }
+
+    pub fn params(&self) -> KdfParams {
+        self.params
+    }
+
+    pub(crate) fn find(&self, id: &str) -> Option<&MasterKey> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|key| key.id == id)
+    }
+}
+
+/// A secret encrypted under a key derived from a master key and its own
+/// salt.
+pub(crate) struct Sealed {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Encrypts `plaintext` with XChaCha20-Poly1305 under a fresh salt and
+/// nonce. `aad` is authenticated but not stored.
+pub(crate) fn seal(
+    key: &MasterKey,
+    params: KdfParams,
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Sealed, KeystoreError> {
+    let mut salt = [0u8; SALT_LEN];
+    fill_random(&mut salt)?;
+    let cipher = cipher(key, params, &salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| KeystoreError::InvalidParams("encryption failed".into()))?;
+    Ok(Sealed {
+        salt: salt.to_vec(),
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Decrypts what [`seal`] produced; `None` if the key, the `aad` or the
+/// data do not match.
+pub(crate) fn open(
+    key: &MasterKey,
+    params: KdfParams,
+    aad: &[u8],
+    sealed: &Sealed,
+) -> Result<Option<Zeroizing<Vec<u8>>>, KeystoreError> {
+    let Ok(nonce) = <[u8; 24]>::try_from(sealed.nonce.as_slice()) else {
+        return Ok(None);
+    };
+    let cipher = cipher(key, params, &sealed.salt)?;
+    let plaintext = cipher.decrypt(
+        &XNonce::from(nonce),
+        Payload {
+            msg: &sealed.ciphertext,
+            aad,
+        },
+    );
+    Ok(plaintext.ok().map(Zeroizing::new))
+}
+
+fn cipher(
+    key: &MasterKey,
+    params: KdfParams,
+    salt: &[u8],
+) -> Result<XChaCha20Poly1305, KeystoreError> {
+    let mut derived = Zeroizing::new([0u8; KEY_LEN]);
+    params
+        .argon2()?
+        .hash_password_into(&key.secret, salt, derived.as_mut())
+        .map_err(|e| KeystoreError::InvalidParams(e.to_string()))?;
+    Ok(XChaCha20Poly1305::new(derived.as_ref().into()))
+}
+
+fn fill_random(buf: &mut [u8]) -> Result<(), KeystoreError> {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    OsRng
+        .try_fill_bytes(buf)
+        .map_err(|e| KeystoreError::Io(std::io::Error::other(e.to_string())))
+}
@@ -0,0 +1,408 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tempfile::NamedTempFile;
+use zeroize::Zeroizing;
+
+use crate::error::KeystoreError;
+use crate::key::{self, KdfParams, Keyring, MasterKey, Sealed};
+use crate::wallet::{self, FreighterWallet, TelegramWallet, WalletData, WalletInfo};
+
+/// The on-disk format this crate writes.
+pub const VERSION: u32 = 1;
+
+/// The bot's wallets with every Telegram secret encrypted, backed by a JSON
+/// file.
+///
+/// Each change is written to a temporary file next to the keystore, synced
+/// and renamed over it, so a crash leaves either the old file or the new
+/// one. The file is created readable by its owner only.
+#[derive(Debug)]
+pub struct Keystore {
+    path: PathBuf,
+    keyring: Keyring,
+    file: KeystoreFile,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeystoreFile {
+    version: u32,
+    #[serde(default)]
+    telegram_wallets: BTreeMap<String, SealedWallet>,
+    #[serde(default)]
+    freighter_wallets: BTreeMap<String, FreighterWallet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SealedWallet {
+    public_key: String,
+    created_at: String,
+    key_id: String,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl Keystore {
+    /// Opens the keystore at `path`, or an empty one if there is no file
+    /// yet. A plaintext `wallets.json` is refused; see [`Keystore::migrate`].
+    pub fn open(path: impl Into<PathBuf>, keyring: Keyring) -> Result<Self, KeystoreError> {
+        keyring.params().validate()?;
+        let path = path.into();
+        let file = load(&path)?;
+        Ok(Keystore {
+            path,
+            keyring,
+            file,
+        })
+    }
+
+    /// Encrypts the bot's plaintext `wallets.json` at `path` and writes it
+    /// back in place.
+    ///
+    /// The plaintext is replaced by renaming, so its old blocks are not
+    /// overwritten; keep the disk or volume itself encrypted if that
+    /// matters.
+    pub fn migrate(path: impl Into<PathBuf>, keyring: Keyring) -> Result<Self, KeystoreError> {
+        keyring.params().validate()?;
+        let path = path.into();
+        let data: WalletData = match read(&path)? {
+            None => WalletData::default(),
+            Some(value) if is_encrypted(&value) => {
+                return Err(KeystoreError::AlreadyEncrypted(path.display().to_string()))
+            }
+            Some(value) => serde_json::from_value(value)?,
+        };
+        let mut keystore = Keystore {
+            path,
+            keyring,
+            file: KeystoreFile {
+                version: VERSION,
+                ..KeystoreFile::default()
+            },
+        };
+        keystore.import(data)?;
+        Ok(keystore)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every Telegram wallet, by chat ID, without decrypting anything.
+    pub fn wallets(&self) -> Vec<WalletInfo> {
+        self.file.wallets()
+    }
+
+    pub fn freighter_wallets(&self) -> &BTreeMap<String, FreighterWallet> {
+        &self.file.freighter_wallets
+    }
+
+    /// The decrypted `S…` seed of the chat's Telegram wallet.
+    pub fn secret_key(&self, chat_id: &str) -> Result<Zeroizing<String>, KeystoreError> {
+        let sealed = self
+            .file
+            .telegram_wallets
+            .get(chat_id)
+            .ok_or_else(|| KeystoreError::NotFound(chat_id.to_string()))?;
+        self.unseal(chat_id, sealed)
+    }
+
+    /// Stores `secret_key` as the chat's Telegram wallet, replacing any
+    /// previous one, and returns its public key.
+    pub fn insert(
+        &mut self,
+        chat_id: &str,
+        secret_key: &str,
+        created_at: impl Into<String>,
+    ) -> Result<String, KeystoreError> {
+        let public_key = wallet::account_of(chat_id, secret_key)?;
+        let sealed = self.seal(chat_id, &public_key, created_at.into(), secret_key)?;
+        self.file
+            .telegram_wallets
+            .insert(chat_id.to_string(), sealed);
+        self.save()?;
+        Ok(public_key)
+    }
+
+    /// Forgets the chat's Telegram wallet; `false` if it had none.
+    pub fn remove(&mut self, chat_id: &str) -> Result<bool, KeystoreError> {
+        if self.file.telegram_wallets.remove(chat_id).is_none() {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Everything in the keystore, decrypted, in the bot's format.
+    pub fn export(&self) -> Result<WalletData, KeystoreError> {
+        let telegram_wallets = self
+            .file
+            .telegram_wallets
+            .iter()
+            .map(|(chat_id, sealed)| {
+                let wallet = TelegramWallet {
+                    public_key: sealed.public_key.clone(),
+                    secret_key: self.unseal(chat_id, sealed)?,
+                    created_at: sealed.created_at.clone(),
+                };
+                Ok((chat_id.clone(), wallet))
+            })
+            .collect::<Result<_, KeystoreError>>()?;
+        Ok(WalletData {
+            telegram_wallets,
+            freighter_wallets: self.file.freighter_wallets.clone(),
+        })
+    }
+
+    /// Replaces the contents with `data`, as the bot's `saveWallets()`
+    /// would.
+    ///
+    /// Every secret must belong to the public key beside it. Wallets whose
+    /// public key is already stored keep their existing encryption, so
+    /// saving after one new wallet only derives one key, and may leave
+    /// their secret out, as a bot that loaded [`public`] data does.
+    pub fn import(&mut self, data: WalletData) -> Result<(), KeystoreError> {
+        let mut telegram_wallets = BTreeMap::new();
+        for (chat_id, wallet) in data.telegram_wallets {
+            let existing = self
+                .file
+                .telegram_wallets
+                .get(&chat_id)
+                .filter(|existing| existing.public_key == wallet.public_key);
+            if wallet.secret_key.is_empty() && existing.is_none() {
+                return Err(KeystoreError::MissingSecret(chat_id));
+            }
+            if !wallet.secret_key.is_empty() {
+                let actual = wallet::account_of(&chat_id, &wallet.secret_key)?;
+                if actual != wallet.public_key {
+                    return Err(KeystoreError::KeyMismatch {
+                        chat_id,
+                        expected: wallet.public_key,
+                        actual,
+                    });
+                }
+            }
+            let sealed = match existing {
+                Some(existing) => SealedWallet {
+                    created_at: wallet.created_at,
+                    ..existing.clone()
+                },
+                None => self.seal(
+                    &chat_id,
+                    &wallet.public_key,
+                    wallet.created_at,
+                    &wallet.secret_key,
+                )?,
+            };
+            telegram_wallets.insert(chat_id, sealed);
+        }
+        self.file.telegram_wallets = telegram_wallets;
+        self.file.freighter_wallets = data.freighter_wallets;
+        self.save()
+    }
+
+    /// Re-encrypts every wallet that is not under the current master key
+    /// and cost settings, each with a fresh salt, and returns how many.
+    ///
+    /// Once this succeeds the previous keys are no longer needed.
+    pub fn rotate(&mut self) -> Result<usize, KeystoreError> {
+        let current = self.keyring.current().id().to_string();
+        let params = self.keyring.params();
+        let stale: Vec<String> = self
+            .file
+            .telegram_wallets
+            .iter()
+            .filter(|(_, sealed)| sealed.key_id != current || sealed.kdf != params)
+            .map(|(chat_id, _)| chat_id.clone())
+            .collect();
+        for chat_id in &stale {
+            let sealed = &self.file.telegram_wallets[chat_id];
+            let secret = self.unseal(chat_id, sealed)?;
+            let resealed = self.seal(
+                chat_id,
+                &sealed.public_key,
+                sealed.created_at.clone(),
+                &secret,
+            )?;
+            self.file.telegram_wallets.insert(chat_id.clone(), resealed);
+        }
+        if !stale.is_empty() {
+            self.save()?;
+        }
+        Ok(stale.len())
+    }
+
+    fn seal(
+        &self,
+        chat_id: &str,
+        public_key: &str,
+        created_at: String,
+        secret_key: &str,
+    ) -> Result<SealedWallet, KeystoreError> {
+        let key = self.keyring.current();
+        let params = self.keyring.params();
+        let Sealed {
+            salt,
+            nonce,
+            ciphertext,
+        } = key::seal(
+            key,
+            params,
+            aad(chat_id, public_key).as_bytes(),
+            secret_key.trim().as_bytes(),
+        )?;
+        Ok(SealedWallet {
+            public_key: public_key.to_string(),
+            created_at,
+            key_id: key.id().to_string(),
+            kdf: params,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    fn unseal(
+        &self,
+        chat_id: &str,
+        sealed: &SealedWallet,
+    ) -> Result<Zeroizing<String>, KeystoreError> {
+        let key: &MasterKey =
+            self.keyring
+                .find(&sealed.key_id)
+                .ok_or_else(|| KeystoreError::UnknownKey {
+                    chat_id: chat_id.to_string(),
+                    key_id: sealed.key_id.clone(),
+                })?;
+        let decode = |text: &str| {
+            BASE64
+                .decode(text)
+                .map_err(|_| KeystoreError::Corrupt(chat_id.to_string()))
+        };
+        let parts = Sealed {
+            salt: decode(&sealed.salt)?,
+            nonce: decode(&sealed.nonce)?,
+            ciphertext: decode(&sealed.ciphertext)?,
+        };
+        let plaintext = key::open(
+            key,
+            sealed.kdf,
+            aad(chat_id, &sealed.public_key).as_bytes(),
+            &parts,
+        )?
+        .ok_or_else(|| KeystoreError::Decrypt(chat_id.to_string()))?;
+        let secret = std::str::from_utf8(&plaintext)
+            .map_err(|_| KeystoreError::Corrupt(chat_id.to_string()))?;
+        Ok(Zeroizing::new(secret.to_string()))
+    }
+
+    fn save(&self) -> Result<(), KeystoreError> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(dir)?;
+        let mut tmp = NamedTempFile::new_in(dir)?;
+        serde_json::to_writer_pretty(&mut tmp, &self.file)?;
+        tmp.write_all(b"\n")?;
+        tmp.as_file().sync_all()?;
+        tmp.persist(&self.path).map_err(|e| e.error)?;
+        // Make the rename itself durable.
+        #[cfg(unix)]
+        File::open(dir)?.sync_all()?;
+        Ok(())
+    }
+}
+
+/// The Telegram wallets in the keystore at `path`. This needs no master
+/// key.
+pub fn list(path: impl AsRef<Path>) -> Result<Vec<WalletInfo>, KeystoreError> {
+    Ok(load(path.as_ref())?.wallets())
+}
+
+/// The wallets in the keystore at `path` in the bot's format, with every
+/// Telegram secret left out. This needs no master key.
+pub fn public(path: impl AsRef<Path>) -> Result<WalletData, KeystoreError> {
+    let file = load(path.as_ref())?;
+    let telegram_wallets = file
+        .telegram_wallets
+        .into_iter()
+        .map(|(chat_id, sealed)| {
+            let wallet = TelegramWallet {
+                public_key: sealed.public_key,
+                secret_key: Zeroizing::default(),
+                created_at: sealed.created_at,
+            };
+            (chat_id, wallet)
+        })
+        .collect();
+    Ok(WalletData {
+        telegram_wallets,
+        freighter_wallets: file.freighter_wallets,
+    })
+}
+
+impl KeystoreFile {
+    fn wallets(&self) -> Vec<WalletInfo> {
+        self.telegram_wallets
+            .iter()
+            .map(|(chat_id, sealed)| WalletInfo {
+                chat_id: chat_id.clone(),
+                public_key: sealed.public_key.clone(),
+                created_at: sealed.created_at.clone(),
+                key_id: sealed.key_id.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Ties a ciphertext to its chat and account, so entries cannot be swapped
+/// between chats in the file.
+fn aad(chat_id: &str, public_key: &str) -> String {
+    format!("stellrflow-keystore:v{VERSION}:{chat_id}:{public_key}")
+}
+
+/// The keystore at `path`, or an empty one if there is no file yet.
+fn load(path: &Path) -> Result<KeystoreFile, KeystoreError> {
+    match read(path)? {
+        None => Ok(KeystoreFile {
+            version: VERSION,
+            ..KeystoreFile::default()
+        }),
+        Some(value) if is_encrypted(&value) => parse(value),
+        Some(_) => Err(KeystoreError::Plaintext(path.display().to_string())),
+    }
+}
+
+fn read(path: &Path) -> Result<Option<Value>, KeystoreError> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The bot's plaintext file has no `version`.
+fn is_encrypted(value: &Value) -> bool {
+    value.get("version").is_some()
+}
+
+fn parse(value: Value) -> Result<KeystoreFile, KeystoreError> {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version != u64::from(VERSION) {
+        return Err(KeystoreError::UnsupportedVersion(
+            u32::try_from(version).unwrap_or(u32::MAX),
+        ));
+    }
+    Ok(serde_json::from_value(value)?)
+}
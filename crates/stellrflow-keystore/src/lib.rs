@@ -0,0 +1,36 @@
+//! Encrypted storage for the secrets of the bot's Telegram wallets.
+//!
+//! The bot's `saveWallets()` used to write every Telegram wallet's `S…` seed
+//! to `data/wallets.json` in plaintext. A [`Keystore`] keeps the same file
+//! with each seed encrypted instead:
+//!
+//! - Every wallet has its own random salt. Argon2id stretches the server's
+//!   [`MasterKey`] with that salt into the wallet's key, and the seed is
+//!   sealed with XChaCha20-Poly1305, bound to its chat ID and public key.
+//! - Each wallet records which master key and [`KdfParams`] sealed it. A
+//!   [`Keyring`] holds the current key and any previous ones, and
+//!   [`Keystore::rotate`] re-encrypts everything under the current key.
+//! - Writes go to a temporary file that is synced and renamed over the
+//!   keystore, so a crash never leaves a half-written file.
+//! - [`Keystore::migrate`] converts the bot's existing plaintext
+//!   [`WalletData`] in place, once.
+//!
+//! Public keys, creation times and Freighter links stay readable, so the
+//! keystore can be [`list`]ed, or read as [`public`] data, without the
+//! master key.
+//!
+//! The `stellrflow-keystore` binary wraps this for the bot: `migrate`,
+//! `rotate` and `list` for operators, and `export`/`import` to load and
+//! save the bot's wallets through a pipe when `KEYSTORE_MASTER_KEY` is set.
+//! A bot that leaves signing to `stellrflow-signer` loads with `public`
+//! instead, and never holds the secrets of the wallets it already has.
+
+mod error;
+mod key;
+mod keystore;
+mod wallet;
+
+pub use error::KeystoreError;
+pub use key::{KdfParams, Keyring, MasterKey, MIN_SECRET_LEN};
+pub use keystore::{list, public, Keystore, VERSION};
+pub use wallet::{FreighterWallet, TelegramWallet, WalletData, WalletInfo};
@@ -0,0 +1,86 @@
+//! Operator and bot commands for the Telegram wallet keystore.
+//!
+//! ```text
+//! stellrflow-keystore migrate [FILE]  encrypt the bot's plaintext wallets.json in place
+//! stellrflow-keystore rotate [FILE]   re-encrypt every wallet under the current master key
+//! stellrflow-keystore list [FILE]     chat IDs, public keys and master key IDs
+//! stellrflow-keystore export [FILE]   print the decrypted wallets as the bot's JSON
+//! stellrflow-keystore public [FILE]   print the wallets as the bot's JSON, without secrets
+//! stellrflow-keystore import [FILE]   replace the wallets with the bot's JSON from stdin
+//! ```
+//!
+//! - `FILE` defaults to `WALLETS_FILE`, then `data/wallets.json`.
+//! - `KEYSTORE_MASTER_KEY` (required except for `list` and `public`): the current master
+//!   key, as `id:secret`.
+//! - `KEYSTORE_PREVIOUS_KEYS`: comma-separated `id:secret` keys that wallets
+//!   may still be encrypted under.
+
+use std::env;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use stellrflow_keystore::{Keyring, Keystore, WalletData};
+use zeroize::Zeroizing;
+
+const USAGE: &str = "usage: stellrflow-keystore <migrate|rotate|list|export|public|import> [FILE]";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("stellrflow-keystore: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let command = args.next().ok_or(USAGE)?;
+    let path = args
+        .next()
+        .or_else(|| env::var("WALLETS_FILE").ok())
+        .unwrap_or_else(|| "data/wallets.json".into());
+    if args.next().is_some() {
+        return Err(USAGE.into());
+    }
+
+    match command.as_str() {
+        "migrate" => {
+            let keystore = Keystore::migrate(&path, Keyring::from_env()?)?;
+            println!(
+                "Encrypted {} Telegram wallets in {path}",
+                keystore.wallets().len()
+            );
+        }
+        "rotate" => {
+            let mut keystore = Keystore::open(&path, Keyring::from_env()?)?;
+            let rotated = keystore.rotate()?;
+            println!("Re-encrypted {rotated} Telegram wallets in {path}");
+        }
+        "list" => {
+            for wallet in stellrflow_keystore::list(&path)? {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    wallet.chat_id, wallet.public_key, wallet.key_id, wallet.created_at
+                );
+            }
+        }
+        "export" => {
+            let data = Keystore::open(&path, Keyring::from_env()?)?.export()?;
+            println!("{}", Zeroizing::new(serde_json::to_string(&data)?).as_str());
+        }
+        "public" => {
+            let data = stellrflow_keystore::public(&path)?;
+            println!("{}", serde_json::to_string(&data)?);
+        }
+        "import" => {
+            let mut input = Zeroizing::new(String::new());
+            io::stdin().read_to_string(&mut input)?;
+            let data: WalletData = serde_json::from_str(&input)?;
+            Keystore::open(&path, Keyring::from_env()?)?.import(data)?;
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
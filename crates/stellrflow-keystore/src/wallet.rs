@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
+use zeroize::Zeroizing;
+
+use crate::error::KeystoreError;
+
+/// The bot's `wallets.json`, as `saveWallets()` writes it, keyed by chat ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletData {
+    #[serde(default)]
+    pub telegram_wallets: BTreeMap<String, TelegramWallet>,
+    #[serde(default)]
+    pub freighter_wallets: BTreeMap<String, FreighterWallet>,
+}
+
+/// A wallet the bot created and signs for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelegramWallet {
+    pub public_key: String,
+    /// The `S…` seed. Wiped from memory when dropped. Empty when the bot
+    /// does not hold it, as in [`public`](crate::public) data.
+    #[serde(default, skip_serializing_if = "withheld")]
+    pub secret_key: Zeroizing<String>,
+    /// ISO 8601, as the bot writes it.
+    pub created_at: String,
+}
+
+/// A Freighter address linked to a chat. There is no secret to protect, so
+/// these are stored as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreighterWallet {
+    pub public_key: String,
+    pub network: String,
+    pub connected_at: String,
+}
+
+/// What the keystore tells about a Telegram wallet without decrypting it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletInfo {
+    pub chat_id: String,
+    pub public_key: String,
+    pub created_at: String,
+    /// The master key the secret is encrypted under.
+    pub key_id: String,
+}
+
+fn withheld(secret_key: &Zeroizing<String>) -> bool {
+    secret_key.is_empty()
+}
+
+/// The `G…` account of a `S…` seed.
+pub(crate) fn account_of(chat_id: &str, secret_key: &str) -> Result<String, KeystoreError> {
+    let seed = PrivateKey::from_string(secret_key.trim())
+        .map_err(|_| KeystoreError::InvalidSecret(chat_id.to_string()))?;
+    let seed = Zeroizing::new(seed.0);
+    let public = SigningKey::from_bytes(&seed).verifying_key().to_bytes();
+    Ok(format!("{}", PublicKey(public)))
+}
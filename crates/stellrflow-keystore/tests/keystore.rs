@@ -0,0 +1,329 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Value};
+use stellar_strkey::ed25519::PrivateKey;
+use stellrflow_keystore::{KdfParams, Keyring, Keystore, KeystoreError, MasterKey, WalletData};
+
+/// Cheap enough for debug builds; the real default is far slower.
+const FAST: KdfParams = KdfParams {
+    memory_kib: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+fn key(id: &str) -> MasterKey {
+    format!("{id}:{}", "0123456789abcdef".repeat(2) + id)
+        .parse()
+        .unwrap()
+}
+
+fn keyring(id: &str) -> Keyring {
+    Keyring::new(key(id)).with_params(FAST)
+}
+
+/// A seed and its account.
+fn wallet(n: u8) -> (String, String) {
+    let seed = PrivateKey([n; 32]).to_string().as_str().to_string();
+    let public = ed25519_dalek::SigningKey::from_bytes(&[n; 32])
+        .verifying_key()
+        .to_bytes();
+    let public = stellar_strkey::ed25519::PublicKey(public)
+        .to_string()
+        .as_str()
+        .to_string();
+    (seed, public)
+}
+
+/// What the bot's `saveWallets()` writes.
+fn bot_file(dir: &Path) -> (std::path::PathBuf, Value) {
+    let (seed1, public1) = wallet(1);
+    let (seed2, public2) = wallet(2);
+    let data = json!({
+        "telegramWallets": {
+            "123456789": {
+                "publicKey": public1,
+                "secretKey": seed1,
+                "createdAt": "2026-01-02T03:04:05.000Z"
+            },
+            "-1001234": {
+                "publicKey": public2,
+                "secretKey": seed2,
+                "createdAt": "2026-02-03T04:05:06.000Z"
+            }
+        },
+        "freighterWallets": {
+            "555": {
+                "publicKey": "GFREIGHTER",
+                "network": "testnet",
+                "connectedAt": "2026-03-04T05:06:07.000Z"
+            }
+        }
+    });
+    let path = dir.join("data").join("wallets.json");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, serde_json::to_string_pretty(&data).unwrap()).unwrap();
+    (path, data)
+}
+
+#[test]
+fn migrates_the_bots_plaintext_file_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, data) = bot_file(dir.path());
+
+    assert!(matches!(
+        Keystore::open(&path, keyring("k1")),
+        Err(KeystoreError::Plaintext(_))
+    ));
+    let keystore = Keystore::migrate(&path, keyring("k1")).unwrap();
+    assert_eq!(keystore.wallets().len(), 2);
+
+    let text = fs::read_to_string(&path).unwrap();
+    for n in [1, 2] {
+        assert!(!text.contains(&wallet(n).0), "seed {n} left in plaintext");
+    }
+    let stored: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(stored["version"], 1);
+    assert_eq!(stored["freighterWallets"], data["freighterWallets"]);
+    let entry = &stored["telegramWallets"]["123456789"];
+    assert_eq!(
+        entry["publicKey"],
+        data["telegramWallets"]["123456789"]["publicKey"]
+    );
+    assert_eq!(entry["keyId"], "k1");
+    assert_ne!(
+        entry["salt"], stored["telegramWallets"]["-1001234"]["salt"],
+        "every wallet has its own salt"
+    );
+
+    let reopened = Keystore::open(&path, keyring("k1")).unwrap();
+    let exported = serde_json::to_value(reopened.export().unwrap()).unwrap();
+    assert_eq!(exported, data);
+    assert_eq!(*reopened.secret_key("123456789").unwrap(), wallet(1).0);
+
+    assert!(matches!(
+        Keystore::migrate(&path, keyring("k1")),
+        Err(KeystoreError::AlreadyEncrypted(_))
+    ));
+    let listed = stellrflow_keystore::list(&path).unwrap();
+    assert_eq!(listed, reopened.wallets());
+}
+
+#[test]
+fn secrets_need_the_right_key_and_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, _) = bot_file(dir.path());
+    Keystore::migrate(&path, keyring("k1")).unwrap();
+
+    // Same ID, different secret.
+    let impostor = Keyring::new(MasterKey::new("k1", [7u8; 32]).unwrap()).with_params(FAST);
+    let keystore = Keystore::open(&path, impostor).unwrap();
+    assert!(matches!(
+        keystore.secret_key("123456789"),
+        Err(KeystoreError::Decrypt(chat)) if chat == "123456789"
+    ));
+
+    let keystore = Keystore::open(&path, keyring("k2")).unwrap();
+    assert!(matches!(
+        keystore.secret_key("123456789"),
+        Err(KeystoreError::UnknownKey { key_id, .. }) if key_id == "k1"
+    ));
+    assert!(matches!(
+        keystore.secret_key("42"),
+        Err(KeystoreError::NotFound(_))
+    ));
+
+    // Moving a ciphertext to another chat does not decrypt.
+    let mut stored: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let wallets = &mut stored["telegramWallets"];
+    wallets["-1001234"]["ciphertext"] = wallets["123456789"]["ciphertext"].clone();
+    wallets["-1001234"]["nonce"] = wallets["123456789"]["nonce"].clone();
+    wallets["-1001234"]["salt"] = wallets["123456789"]["salt"].clone();
+    fs::write(&path, stored.to_string()).unwrap();
+    let keystore = Keystore::open(&path, keyring("k1")).unwrap();
+    assert!(keystore.secret_key("123456789").is_ok());
+    assert!(matches!(
+        keystore.secret_key("-1001234"),
+        Err(KeystoreError::Decrypt(_))
+    ));
+}
+
+#[test]
+fn rotation_moves_every_wallet_to_the_current_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, data) = bot_file(dir.path());
+    Keystore::migrate(&path, keyring("k1")).unwrap();
+
+    let mut keystore = Keystore::open(&path, keyring("k2").with_previous([key("k1")])).unwrap();
+    // Readable before rotating, through the previous key.
+    assert_eq!(*keystore.secret_key("-1001234").unwrap(), wallet(2).0);
+    assert_eq!(keystore.rotate().unwrap(), 2);
+    assert_eq!(keystore.rotate().unwrap(), 0);
+    assert!(keystore.wallets().iter().all(|w| w.key_id == "k2"));
+
+    let keystore = Keystore::open(&path, keyring("k2")).unwrap();
+    let exported = serde_json::to_value(keystore.export().unwrap()).unwrap();
+    assert_eq!(exported, data);
+    assert!(matches!(
+        Keystore::open(&path, keyring("k1")).unwrap().export(),
+        Err(KeystoreError::UnknownKey { .. })
+    ));
+
+    // New cost settings count as stale too.
+    let stronger = KdfParams {
+        memory_kib: 128,
+        ..FAST
+    };
+    let mut keystore = Keystore::open(&path, keyring("k2").with_params(stronger)).unwrap();
+    assert_eq!(keystore.rotate().unwrap(), 2);
+}
+
+#[test]
+fn saves_check_secrets_and_keep_unchanged_wallets() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wallets.json");
+    let mut keystore = Keystore::open(&path, keyring("k1")).unwrap();
+    assert!(keystore.wallets().is_empty());
+    assert!(!path.exists(), "nothing is written until a change");
+
+    let (seed1, public1) = wallet(1);
+    assert_eq!(
+        keystore
+            .insert("1", &seed1, "2026-01-01T00:00:00.000Z")
+            .unwrap(),
+        public1
+    );
+    assert!(matches!(
+        keystore.insert("2", "SNOTASEED", "2026-01-01T00:00:00.000Z"),
+        Err(KeystoreError::InvalidSecret(_))
+    ));
+    let before: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+    let mut data: WalletData = keystore.export().unwrap();
+    let (seed3, public3) = wallet(3);
+    data.telegram_wallets.insert(
+        "3".into(),
+        serde_json::from_value(json!({
+            "publicKey": public3,
+            "secretKey": seed3,
+            "createdAt": "2026-01-03T00:00:00.000Z"
+        }))
+        .unwrap(),
+    );
+    keystore.import(data.clone()).unwrap();
+    let after: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(
+        after["telegramWallets"]["1"], before["telegramWallets"]["1"],
+        "an unchanged wallet is not re-encrypted"
+    );
+    assert_eq!(keystore.wallets().len(), 2);
+
+    let mut wrong = data;
+    wrong.telegram_wallets.get_mut("3").unwrap().public_key = public1;
+    assert!(matches!(
+        keystore.import(wrong),
+        Err(KeystoreError::KeyMismatch { chat_id, .. }) if chat_id == "3"
+    ));
+
+    assert!(keystore.remove("3").unwrap());
+    assert!(!keystore.remove("3").unwrap());
+    assert_eq!(
+        Keystore::open(&path, keyring("k1"))
+            .unwrap()
+            .wallets()
+            .len(),
+        1
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o077, 0, "readable by its owner only");
+    }
+    let leftovers = fs::read_dir(dir.path()).unwrap().count();
+    assert_eq!(leftovers, 1, "no temporary files left behind");
+}
+
+#[test]
+fn a_bot_can_load_and_save_without_holding_secrets() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, data) = bot_file(dir.path());
+    Keystore::migrate(&path, keyring("k1")).unwrap();
+
+    let public = stellrflow_keystore::public(&path).unwrap();
+    let json = serde_json::to_value(&public).unwrap();
+    assert_eq!(
+        json["telegramWallets"]["123456789"],
+        json!({
+            "publicKey": data["telegramWallets"]["123456789"]["publicKey"],
+            "createdAt": "2026-01-02T03:04:05.000Z"
+        })
+    );
+    assert_eq!(json["freighterWallets"], data["freighterWallets"]);
+
+    // Saved back with one new wallet, the others keep their secrets.
+    let mut keystore = Keystore::open(&path, keyring("k1")).unwrap();
+    let mut saved: WalletData = serde_json::from_value(json).unwrap();
+    let (seed3, public3) = wallet(3);
+    saved.telegram_wallets.insert(
+        "3".into(),
+        serde_json::from_value(json!({
+            "publicKey": public3,
+            "secretKey": seed3,
+            "createdAt": "2026-01-03T00:00:00.000Z"
+        }))
+        .unwrap(),
+    );
+    keystore.import(saved.clone()).unwrap();
+    assert_eq!(*keystore.secret_key("123456789").unwrap(), wallet(1).0);
+    assert_eq!(*keystore.secret_key("3").unwrap(), seed3);
+
+    // A new wallet cannot be saved without its secret.
+    let mut missing = saved;
+    let (_, public4) = wallet(4);
+    missing.telegram_wallets.insert(
+        "4".into(),
+        serde_json::from_value(json!({
+            "publicKey": public4,
+            "createdAt": "2026-01-04T00:00:00.000Z"
+        }))
+        .unwrap(),
+    );
+    assert!(matches!(
+        keystore.import(missing),
+        Err(KeystoreError::MissingSecret(chat_id)) if chat_id == "4"
+    ));
+}
+
+#[test]
+fn master_keys_are_id_and_secret() {
+    assert_eq!(key("2026-10").id(), "2026-10");
+    for bad in [
+        "no-separator",
+        "k1:short",
+        ":0123456789abcdef0123456789abcdef",
+        "a b:0123456789abcdef0123456789abcdef",
+    ] {
+        assert!(
+            matches!(
+                bad.parse::<MasterKey>(),
+                Err(KeystoreError::InvalidMasterKey(_))
+            ),
+            "{bad:?}"
+        );
+    }
+    // The secret never shows up in debug output.
+    let debug = format!("{:?}", key("k1"));
+    assert!(!debug.contains("0123456789abcdef"), "{debug}");
+
+    let dir = tempfile::tempdir().unwrap();
+    let weak = KdfParams {
+        memory_kib: 1,
+        ..FAST
+    };
+    assert!(matches!(
+        Keystore::open(dir.path().join("w.json"), keyring("k1").with_params(weak)),
+        Err(KeystoreError::InvalidParams(_))
+    ));
+}
@@ -0,0 +1,196 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stellrflow_template::Template;
+
+use crate::schema::{Category, Config, NodeConfig};
+use crate::{check_amount, check_fiat_amount, de};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Testnet,
+    Mainnet,
+}
+
+impl Network {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Network::Testnet => "testnet",
+            Network::Mainnet => "mainnet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SdkOperation {
+    /// Answer Stellar questions in the chat.
+    #[default]
+    Chatbot,
+    /// Report the XLM balance of `destination`.
+    Balance,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StellarSdkConfig {
+    #[serde(default)]
+    pub network: Network,
+    #[serde(default)]
+    pub operation: SdkOperation,
+    /// Address to check in `balance` mode; falls back to the incoming
+    /// payload's `destination` / `address`.
+    #[serde(default)]
+    pub destination: String,
+}
+
+impl NodeConfig for StellarSdkConfig {
+    const NODE_TYPE: &'static str = "stellar-sdk";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Stellar SDK (Chatbot)";
+    const ICON: &'static str = "send";
+    const DESCRIPTION: &'static str =
+        "Chatbot mode: User asks Stellar questions in Telegram, bot answers using SDK";
+    const SECRETS: &'static [&'static str] = &["destination"];
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WalletProvider {
+    /// Link a Freighter browser wallet.
+    #[default]
+    Freighter,
+    /// Create a wallet held by the bot.
+    Telegram,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletIntegrationConfig {
+    #[serde(default)]
+    pub wallet_provider: WalletProvider,
+    #[serde(default)]
+    pub network: Network,
+}
+
+impl NodeConfig for WalletIntegrationConfig {
+    const NODE_TYPE: &'static str = "wallet-integration";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Wallet Integration";
+    const ICON: &'static str = "wallet";
+    const DESCRIPTION: &'static str =
+        "Connect to Freighter browser wallet or create a Telegram-native wallet";
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TelegramSendConfig {
+    /// Defaults to the chat handed down by the trigger.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub chat_id: String,
+    /// A `stellrflow-template` template over the incoming payload.
+    #[serde(default)]
+    pub message: String,
+}
+
+impl NodeConfig for TelegramSendConfig {
+    const NODE_TYPE: &'static str = "telegram-send";
+    const VERSION: u32 = 2;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Send Telegram";
+    const ICON: &'static str = "messageCircle";
+    const DESCRIPTION: &'static str =
+        "Send a message to Telegram. Use {{balance | amount}}, {{address | short}} for templates.";
+    const SECRETS: &'static [&'static str] = &["chatId"];
+
+    /// Version 1 only knew `{balance}` and `{address}`.
+    fn migrate(mut config: Config, _from: u32) -> Result<Config, String> {
+        if let Some(Value::String(message)) = config.get_mut("message") {
+            for key in ["balance", "address"] {
+                *message = message.replace(&format!("{{{key}}}"), &format!("{{{{{key}}}}}"));
+            }
+        }
+        Ok(config)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        Template::parse(&self.message)
+            .map(drop)
+            .map_err(|err| format!("`message`: {err}"))
+    }
+}
+
+/// Shared by the on- and off-ramp nodes; `$check` reads the amount, which
+/// is fiat going in and XLM coming out.
+macro_rules! anchor_config {
+    ($name:ident, $node_type:literal, $label:literal, $icon:literal, $description:literal, $check:ident) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+        #[serde(rename_all = "camelCase", deny_unknown_fields)]
+        pub struct $name {
+            #[serde(default)]
+            pub anchor_url: String,
+            #[serde(default = "usd")]
+            pub fiat_currency: String,
+            #[serde(default = "usdc")]
+            pub asset: String,
+            /// Decimal amount, as a string.
+            #[serde(default, deserialize_with = "de::string_or_number")]
+            pub amount: String,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    anchor_url: String::new(),
+                    fiat_currency: usd(),
+                    asset: usdc(),
+                    amount: String::new(),
+                }
+            }
+        }
+
+        impl NodeConfig for $name {
+            const NODE_TYPE: &'static str = $node_type;
+            const VERSION: u32 = 1;
+            const CATEGORY: Category = Category::Action;
+            const LABEL: &'static str = $label;
+            const ICON: &'static str = $icon;
+            const DESCRIPTION: &'static str = $description;
+            const REQUIRED: &'static [&'static str] = &["amount"];
+
+            fn check(&self) -> Result<(), String> {
+                $check(&self.amount)
+            }
+        }
+    };
+}
+
+fn usd() -> String {
+    "USD".into()
+}
+
+fn usdc() -> String {
+    "USDC".into()
+}
+
+anchor_config!(
+    AnchorOnRampConfig,
+    "anchor-onramp",
+    "Anchor On-Ramp",
+    "arrowDown",
+    "Convert fiat to Stellar assets via anchor",
+    check_fiat_amount
+);
+
+anchor_config!(
+    AnchorOffRampConfig,
+    "anchor-offramp",
+    "Anchor Off-Ramp",
+    "arrowUp",
+    "Convert Stellar assets to fiat via anchor",
+    check_amount
+);
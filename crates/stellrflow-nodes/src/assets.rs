@@ -0,0 +1,216 @@
+//! `trustline`, `send-asset` and `convert-pay`: issued assets, named
+//! `CODE:ISSUER`.
+//!
+//! `/send`, `sendXLM` and the transaction builder only ever paid in XLM.
+//! These nodes let a workflow hold and send any asset, `USDC` from an
+//! anchor included, through the Rust Stellar layer, and pay in one asset
+//! what the destination receives in another.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use stellrflow_amount::Amount;
+
+use crate::schema::{Category, NodeConfig};
+use crate::{check_amount, de};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TrustlineConfig {
+    /// The asset to trust, as `CODE:ISSUER`.
+    #[serde(default)]
+    pub asset: String,
+    /// The most of the asset the wallet will hold, as a decimal string.
+    /// Empty trusts as much as a trustline can hold; `0` removes the
+    /// trustline.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub limit: String,
+}
+
+impl NodeConfig for TrustlineConfig {
+    const NODE_TYPE: &'static str = "trustline";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Trustline";
+    const ICON: &'static str = "link";
+    const DESCRIPTION: &'static str =
+        "Add, limit or remove a trustline so your Telegram wallet can hold an asset";
+    const REQUIRED: &'static [&'static str] = &["asset"];
+
+    fn check(&self) -> Result<(), String> {
+        if let Some(Kind::Native) = check_asset("asset", &self.asset)? {
+            return Err("`asset`: XLM needs no trustline; expected `CODE:ISSUER`".into());
+        }
+        let limit = self.limit.trim();
+        match limit.parse::<Amount>() {
+            _ if limit.is_empty() => Ok(()),
+            Ok(limit) if limit >= Amount::ZERO => Ok(()),
+            _ => Err(format!(
+                "`limit` must be 0 or a positive amount with at most 7 decimals, got `{limit}`"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SendAssetConfig {
+    /// Stellar address that receives the payment.
+    #[serde(default)]
+    pub destination: String,
+    /// `native` (or `XLM`), or `CODE:ISSUER`.
+    #[serde(default)]
+    pub asset: String,
+    /// How much of the asset to send, as a decimal string.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+}
+
+impl NodeConfig for SendAssetConfig {
+    const NODE_TYPE: &'static str = "send-asset";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Send Asset";
+    const ICON: &'static str = "coins";
+    const DESCRIPTION: &'static str =
+        "Send XLM or any issued asset from your Telegram wallet, checking trustlines first";
+    const REQUIRED: &'static [&'static str] = &["destination", "asset", "amount"];
+    const SECRETS: &'static [&'static str] = &["destination"];
+
+    fn check(&self) -> Result<(), String> {
+        check_asset("asset", &self.asset)?;
+        check_amount(&self.amount)
+    }
+}
+
+/// Which end of a `convert-pay` payment is fixed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PathStrict {
+    /// Send exactly `amount` of `sendAsset`.
+    Send,
+    /// Deliver exactly `amount` of `destAsset`.
+    #[default]
+    Receive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ConvertPayConfig {
+    /// Stellar address that receives the payment; it must already exist.
+    #[serde(default)]
+    pub destination: String,
+    /// The asset paid with: `native` (or `XLM`), or `CODE:ISSUER`.
+    #[serde(default = "native")]
+    pub send_asset: String,
+    /// The asset the destination receives.
+    #[serde(default)]
+    pub dest_asset: String,
+    /// How much is sent or received, per `strict`, as a decimal string.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+    #[serde(default)]
+    pub strict: PathStrict,
+    /// How far the payment may stray from the quoted rate, in percent.
+    #[serde(default = "one_percent", deserialize_with = "de::string_or_number")]
+    pub slippage: String,
+}
+
+fn native() -> String {
+    "native".into()
+}
+
+fn one_percent() -> String {
+    "1".into()
+}
+
+impl Default for ConvertPayConfig {
+    fn default() -> Self {
+        Self {
+            destination: String::new(),
+            send_asset: native(),
+            dest_asset: String::new(),
+            amount: String::new(),
+            strict: PathStrict::default(),
+            slippage: one_percent(),
+        }
+    }
+}
+
+impl NodeConfig for ConvertPayConfig {
+    const NODE_TYPE: &'static str = "convert-pay";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Convert & Pay";
+    const ICON: &'static str = "repeat";
+    const DESCRIPTION: &'static str =
+        "Pay in one asset and deliver another, converted through the best path on the SDEX";
+    const REQUIRED: &'static [&'static str] = &["destination", "destAsset", "amount"];
+    const SECRETS: &'static [&'static str] = &["destination"];
+
+    fn check(&self) -> Result<(), String> {
+        check_asset("sendAsset", &self.send_asset)?;
+        check_asset("destAsset", &self.dest_asset)?;
+        check_amount(&self.amount)?;
+        check_slippage(&self.slippage)
+    }
+}
+
+/// A percentage from 0 to 100 with at most two decimals; empty is the
+/// default 1%.
+fn check_slippage(slippage: &str) -> Result<(), String> {
+    let slippage = slippage.trim();
+    let percent = slippage.trim_end_matches('%').trim_end();
+    if slippage.is_empty() {
+        return Ok(());
+    }
+    let (whole, fraction) = percent.split_once('.').unwrap_or((percent, ""));
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    let valid = (1..=3).contains(&whole.len())
+        && fraction.len() <= 2
+        && digits(whole)
+        && digits(fraction)
+        && match whole.parse::<u32>() {
+            Ok(100) => fraction.trim_end_matches('0').is_empty(),
+            Ok(whole) => whole < 100,
+            Err(_) => false,
+        };
+    if !valid {
+        return Err(format!(
+            "`slippage` must be a percentage from 0 to 100 with at most 2 decimals, got `{slippage}`"
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) enum Kind {
+    Native,
+    Issued,
+}
+
+/// An empty asset is left for the required-key check. Otherwise it is
+/// `native` or `XLM`, or a code of 1 to 12 letters and digits, a colon and
+/// the issuer's `G…` address.
+pub(crate) fn check_asset(field: &str, asset: &str) -> Result<Option<Kind>, String> {
+    let asset = asset.trim();
+    if asset.is_empty() {
+        return Ok(None);
+    }
+    if asset.eq_ignore_ascii_case("native") || asset.eq_ignore_ascii_case("xlm") {
+        return Ok(Some(Kind::Native));
+    }
+    let valid = asset.split_once(':').is_some_and(|(code, issuer)| {
+        (1..=12).contains(&code.len())
+            && code.bytes().all(|b| b.is_ascii_alphanumeric())
+            && issuer.len() == 56
+            && issuer.starts_with('G')
+            && issuer
+                .bytes()
+                .all(|b| matches!(b, b'A'..=b'Z' | b'2'..=b'7'))
+    });
+    if !valid {
+        return Err(format!(
+            "`{field}` must be `native` or `CODE:ISSUER`, got `{asset}`"
+        ));
+    }
+    Ok(Some(Kind::Issued))
+}
@@ -0,0 +1,153 @@
+//! `create-claimable-balance` and `claim-claimable-balance`: paying
+//! accounts that may not exist yet.
+//!
+//! A payment needs a funded account on the other end, which a Telegram
+//! user who has just been told about the bot rarely has. A claimable
+//! balance sets the funds aside on the ledger instead, for the claimants
+//! to take once they have an account, and optionally only within a window
+//! of time; the sender can be allowed to take it back once it expires.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::check_asset;
+use crate::interval::parse_interval_ms;
+use crate::payments::positive_interval;
+use crate::schema::{Category, NodeConfig};
+use crate::{check_amount, de};
+
+/// The most claimants one balance can have, the sender included when it
+/// may reclaim.
+const MAX_CLAIMANTS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateClaimableBalanceConfig {
+    /// Stellar addresses that may claim the balance; they need not exist
+    /// yet.
+    #[serde(default, deserialize_with = "de::list")]
+    pub claimants: Vec<String>,
+    /// `native` (or `XLM`), or `CODE:ISSUER`.
+    #[serde(default = "native")]
+    pub asset: String,
+    /// How much of the asset to set aside, as a decimal string.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+    /// How long after creation the claimants must wait, e.g. `1h`; empty
+    /// lets them claim at once.
+    #[serde(default)]
+    pub claimable_after: String,
+    /// How long after creation the claimants may claim, e.g. `30d`; empty
+    /// never expires.
+    #[serde(default)]
+    pub expires_after: String,
+    /// Let the sender take the balance back once it expires.
+    #[serde(default, deserialize_with = "de::flag")]
+    pub reclaim: bool,
+}
+
+fn native() -> String {
+    "native".into()
+}
+
+impl Default for CreateClaimableBalanceConfig {
+    fn default() -> Self {
+        Self {
+            claimants: Vec::new(),
+            asset: native(),
+            amount: String::new(),
+            claimable_after: String::new(),
+            expires_after: String::new(),
+            reclaim: false,
+        }
+    }
+}
+
+impl NodeConfig for CreateClaimableBalanceConfig {
+    const NODE_TYPE: &'static str = "create-claimable-balance";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Claimable Balance";
+    const ICON: &'static str = "gift";
+    const DESCRIPTION: &'static str =
+        "Set XLM or an asset aside from your Telegram wallet for accounts to claim, even before they exist";
+    const REQUIRED: &'static [&'static str] = &["claimants", "asset", "amount"];
+    const SECRETS: &'static [&'static str] = &["claimants"];
+
+    fn check(&self) -> Result<(), String> {
+        let count = self.claimants.len() + usize::from(self.reclaim);
+        if count > MAX_CLAIMANTS {
+            return Err(format!(
+                "at most {MAX_CLAIMANTS} claimants, the sender included when it may reclaim; got {count}"
+            ));
+        }
+        for (i, claimant) in self.claimants.iter().enumerate() {
+            if self.claimants[..i].contains(claimant) {
+                return Err(format!("duplicate claimant `{claimant}`"));
+            }
+        }
+        check_asset("asset", &self.asset)?;
+        check_amount(&self.amount)?;
+
+        let after = optional_interval("claimableAfter", &self.claimable_after)?;
+        let expires = optional_interval("expiresAfter", &self.expires_after)?;
+        match (after, expires) {
+            (Some(after), Some(expires)) if after >= expires => Err(format!(
+                "`claimableAfter` ({}) must be shorter than `expiresAfter` ({})",
+                self.claimable_after.trim(),
+                self.expires_after.trim()
+            )),
+            (_, None) if self.reclaim => {
+                Err("`reclaim` needs `expiresAfter`: the sender reclaims once it expires".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An empty interval is unset; anything else is longer than zero, in
+/// milliseconds.
+fn optional_interval(key: &str, value: &str) -> Result<Option<u64>, String> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    positive_interval(key, value)?;
+    parse_interval_ms(value)
+        .map(Some)
+        .map_err(|err| format!("`{key}`: {err}"))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ClaimClaimableBalanceConfig {
+    /// The balance to claim, as Horizon lists its ID; empty claims every
+    /// balance the wallet can claim now.
+    #[serde(default)]
+    pub balance_id: String,
+}
+
+impl NodeConfig for ClaimClaimableBalanceConfig {
+    const NODE_TYPE: &'static str = "claim-claimable-balance";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Claim Balance";
+    const ICON: &'static str = "download";
+    const DESCRIPTION: &'static str =
+        "Claim balances set aside for your Telegram wallet, one by ID or all that are claimable";
+
+    fn check(&self) -> Result<(), String> {
+        let id = self.balance_id.trim();
+        let hash = match id.len() {
+            0 => return Ok(()),
+            72 if id.starts_with("00000000") => &id[8..],
+            64 => id,
+            _ => "",
+        };
+        if hash.is_empty() || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!(
+                "`balanceId` must be a claimable balance ID of 72 hex digits, got `{id}`"
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SchemaError {
+    #[error("unknown node type `{0}`")]
+    UnknownNodeType(String),
+    #[error("`{node_type}` config version {version} is newer than supported version {current}")]
+    UnsupportedVersion {
+        node_type: String,
+        version: u32,
+        current: u32,
+    },
+    /// The config could not be migrated or does not match the schema.
+    #[error("invalid `{node_type}` config: {message}")]
+    Invalid { node_type: String, message: String },
+}
+
+impl SchemaError {
+    pub(crate) fn invalid(node_type: &str, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            node_type: node_type.to_string(),
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! The interval grammar of `bots/telegram-stellar/src/interval-parser.ts`,
+//! used to check `duration` / `timeout` values. AutoPay's `interval` also
+//! takes calendar rules; see [`stellrflow_recurrence::Recurrence`].
+
+pub use stellrflow_recurrence::parse_interval_ms;
@@ -0,0 +1,68 @@
+//! Typed config schemas for every StellrFlow node type.
+//!
+//! Each node type's `config` is a serde struct implementing [`NodeConfig`]:
+//! it knows its `NodeData.type`, palette entry, schema version, required
+//! keys and how to migrate configs saved by older builders. Unknown keys
+//! are rejected.
+//!
+//! [`SchemaRegistry::builtin`] collects them all. It can normalize any
+//! saved config into canonical form, export JSON Schema per type, and
+//! render the palette in the shape of the frontend's `NODE_TYPES`.
+
+mod actions;
+mod assets;
+mod claimable;
+pub mod de;
+mod error;
+pub mod interval;
+mod logic;
+mod market;
+mod payments;
+mod schema;
+mod triggers;
+
+pub use actions::{
+    AnchorOffRampConfig, AnchorOnRampConfig, Network, SdkOperation, StellarSdkConfig,
+    TelegramSendConfig, WalletIntegrationConfig, WalletProvider,
+};
+pub use assets::{ConvertPayConfig, PathStrict, SendAssetConfig, TrustlineConfig};
+pub use claimable::{ClaimClaimableBalanceConfig, CreateClaimableBalanceConfig};
+pub use error::SchemaError;
+pub use logic::{ConditionConfig, DelayConfig};
+pub use market::{
+    ManageBuyOfferConfig, ManageSellOfferConfig, PriceDirection, PriceFeed, PriceTriggerConfig,
+};
+pub use payments::{AutoPayConfig, MultisigConfig};
+pub use schema::{Category, Config, NodeConfig, NodeSchema, SchemaRegistry};
+pub use triggers::{DiscordTriggerConfig, TelegramTriggerConfig, WhatsappTriggerConfig};
+
+use stellrflow_amount::{Amount, Fiat, FIAT_DECIMALS};
+
+/// An empty amount is left for the required-key check; anything else must
+/// be a positive XLM amount, with at most 7 decimals.
+fn check_amount(amount: &str) -> Result<(), String> {
+    let amount = amount.trim();
+    if amount.is_empty() {
+        return Ok(());
+    }
+    match amount.parse::<Amount>() {
+        Ok(xlm) if xlm.is_positive() => Ok(()),
+        _ => Err(format!(
+            "`amount` must be a positive XLM amount with at most 7 decimals, got `{amount}`"
+        )),
+    }
+}
+
+/// As [`check_amount`], for an amount of fiat in the anchor's cents.
+fn check_fiat_amount(amount: &str) -> Result<(), String> {
+    let amount = amount.trim();
+    if amount.is_empty() {
+        return Ok(());
+    }
+    match Fiat::parse(amount, FIAT_DECIMALS) {
+        Ok(fiat) if fiat.is_positive() => Ok(()),
+        _ => Err(format!(
+            "`amount` must be a positive amount with at most {FIAT_DECIMALS} decimals, got `{amount}`"
+        )),
+    }
+}
@@ -0,0 +1,65 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use stellrflow_expr::Expression;
+
+use crate::de;
+use crate::schema::{Category, NodeConfig};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DelayConfig {
+    /// Seconds to wait.
+    #[serde(default = "five", deserialize_with = "de::int_u64")]
+    pub delay: u64,
+}
+
+fn five() -> u64 {
+    5
+}
+
+impl Default for DelayConfig {
+    fn default() -> Self {
+        Self { delay: five() }
+    }
+}
+
+impl NodeConfig for DelayConfig {
+    const NODE_TYPE: &'static str = "delay";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Logic;
+    const LABEL: &'static str = "Delay";
+    const ICON: &'static str = "clock";
+    const DESCRIPTION: &'static str = "Add a delay in workflow execution";
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ConditionConfig {
+    /// A `stellrflow-expr` expression over the incoming payload, such as
+    /// `balance < 50`. Nodes wired to the `true` or `false` handle run
+    /// depending on its result.
+    #[serde(default)]
+    pub expression: String,
+}
+
+impl NodeConfig for ConditionConfig {
+    const NODE_TYPE: &'static str = "condition";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Logic;
+    const LABEL: &'static str = "Condition";
+    const ICON: &'static str = "gitBranch";
+    const DESCRIPTION: &'static str = "Branch on an expression like `balance < 50`";
+    const REQUIRED: &'static [&'static str] = &["expression"];
+    const OUTPUTS: &'static [&'static str] = &["true", "false"];
+
+    /// An empty expression is left for the required-key check.
+    fn check(&self) -> Result<(), String> {
+        if self.expression.trim().is_empty() {
+            return Ok(());
+        }
+        Expression::parse(&self.expression)
+            .map(drop)
+            .map_err(|err| format!("`expression`: {err}"))
+    }
+}
@@ -0,0 +1,276 @@
+//! `price-trigger`, `manage-sell-offer` and `manage-buy-offer`: trading on
+//! the SDEX.
+//!
+//! A workflow can wait for a pair's price on Horizon's order book to cross
+//! a threshold, and place, update or cancel limit orders from the chat's
+//! wallet. Prices are decimals, or fractions like `1/8` when a decimal
+//! would be inexact.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use stellrflow_amount::Amount;
+
+use crate::assets::{check_asset, Kind};
+use crate::de;
+use crate::payments::positive_interval;
+use crate::schema::{Category, NodeConfig};
+
+/// Which way the price must cross.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceDirection {
+    /// Fire at or above `price`.
+    #[default]
+    Above,
+    /// Fire at or below `price`.
+    Below,
+}
+
+/// Which price of the pair to watch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceFeed {
+    /// Halfway between the best bid and the best ask.
+    #[default]
+    Mid,
+    /// The best offer to buy `base`.
+    Bid,
+    /// The best offer to sell `base`.
+    Ask,
+    /// The close of the latest minute with trades.
+    Last,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PriceTriggerConfig {
+    /// Numeric chat ID to notify and trade for.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub chat_id: String,
+    /// The asset priced: `native` (or `XLM`), or `CODE:ISSUER`.
+    #[serde(default = "native")]
+    pub base: String,
+    /// The asset the price is in.
+    #[serde(default)]
+    pub counter: String,
+    #[serde(default)]
+    pub condition: PriceDirection,
+    /// The threshold, in `counter` per `base`.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub price: String,
+    #[serde(default)]
+    pub source: PriceFeed,
+    /// How often to read the order book, e.g. `30s`.
+    #[serde(default = "thirty_seconds")]
+    pub interval: String,
+    /// How long to wait for the price before failing, e.g. `24h`.
+    #[serde(default = "one_day")]
+    pub timeout: String,
+}
+
+fn native() -> String {
+    "native".into()
+}
+
+fn thirty_seconds() -> String {
+    "30s".into()
+}
+
+fn one_day() -> String {
+    "24h".into()
+}
+
+impl Default for PriceTriggerConfig {
+    fn default() -> Self {
+        Self {
+            chat_id: String::new(),
+            base: native(),
+            counter: String::new(),
+            condition: PriceDirection::default(),
+            price: String::new(),
+            source: PriceFeed::default(),
+            interval: thirty_seconds(),
+            timeout: one_day(),
+        }
+    }
+}
+
+impl NodeConfig for PriceTriggerConfig {
+    const NODE_TYPE: &'static str = "price-trigger";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Trigger;
+    const LABEL: &'static str = "Price Alert";
+    const ICON: &'static str = "trendingUp";
+    const DESCRIPTION: &'static str =
+        "Start the workflow when an asset's SDEX price goes above or below a threshold";
+    const REQUIRED: &'static [&'static str] = &["chatId", "counter", "price"];
+    const SECRETS: &'static [&'static str] = &["chatId"];
+
+    fn check(&self) -> Result<(), String> {
+        if self.chat_id.trim().starts_with('@') {
+            return Err("use the numeric chat ID, not a username".into());
+        }
+        check_asset("base", &self.base)?;
+        check_asset("counter", &self.counter)?;
+        check_pair(&self.base, &self.counter, "base", "counter")?;
+        check_price(&self.price)?;
+        positive_interval("interval", &self.interval)?;
+        positive_interval("timeout", &self.timeout)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ManageSellOfferConfig {
+    /// The asset sold: `native` (or `XLM`), or `CODE:ISSUER`.
+    #[serde(default)]
+    pub selling: String,
+    /// The asset bought.
+    #[serde(default)]
+    pub buying: String,
+    /// How much of `selling` to sell, as a decimal string; `0` cancels the
+    /// offer `offerId`.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+    /// What one unit of `selling` costs in `buying`.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub price: String,
+    /// The offer to update or cancel; empty places a new one.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub offer_id: String,
+}
+
+impl NodeConfig for ManageSellOfferConfig {
+    const NODE_TYPE: &'static str = "manage-sell-offer";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Sell Offer";
+    const ICON: &'static str = "trendingDown";
+    const DESCRIPTION: &'static str =
+        "Place, update or cancel an SDEX offer selling a fixed amount of an asset";
+    const REQUIRED: &'static [&'static str] = &["selling", "buying", "amount", "price"];
+
+    fn check(&self) -> Result<(), String> {
+        check_offer(
+            &self.selling,
+            &self.buying,
+            &self.amount,
+            &self.price,
+            &self.offer_id,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ManageBuyOfferConfig {
+    /// The asset bought: `native` (or `XLM`), or `CODE:ISSUER`.
+    #[serde(default)]
+    pub buying: String,
+    /// The asset paid with.
+    #[serde(default)]
+    pub selling: String,
+    /// How much of `buying` to buy, as a decimal string; `0` cancels the
+    /// offer `offerId`.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+    /// What one unit of `buying` costs in `selling`.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub price: String,
+    /// The offer to update or cancel; empty places a new one.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub offer_id: String,
+}
+
+impl NodeConfig for ManageBuyOfferConfig {
+    const NODE_TYPE: &'static str = "manage-buy-offer";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Buy Offer";
+    const ICON: &'static str = "trendingUp";
+    const DESCRIPTION: &'static str =
+        "Place, update or cancel an SDEX offer buying a fixed amount of an asset";
+    const REQUIRED: &'static [&'static str] = &["buying", "selling", "amount", "price"];
+
+    fn check(&self) -> Result<(), String> {
+        check_offer(
+            &self.selling,
+            &self.buying,
+            &self.amount,
+            &self.price,
+            &self.offer_id,
+        )
+    }
+}
+
+fn check_offer(
+    selling: &str,
+    buying: &str,
+    amount: &str,
+    price: &str,
+    offer_id: &str,
+) -> Result<(), String> {
+    check_asset("selling", selling)?;
+    check_asset("buying", buying)?;
+    check_pair(selling, buying, "selling", "buying")?;
+    check_price(price)?;
+
+    let offer_id = offer_id.trim();
+    let has_offer = match offer_id.parse::<i64>() {
+        _ if offer_id.is_empty() => false,
+        Ok(id) if id > 0 => true,
+        _ => {
+            return Err(format!(
+                "`offerId` must be a positive offer ID, got `{offer_id}`"
+            ))
+        }
+    };
+    let amount = amount.trim();
+    match amount.parse::<Amount>() {
+        _ if amount.is_empty() => Ok(()),
+        Ok(amount) if amount.is_positive() => Ok(()),
+        Ok(Amount::ZERO) if has_offer => Ok(()),
+        Ok(Amount::ZERO) => Err("`amount` 0 cancels an offer; set `offerId` to cancel".into()),
+        _ => Err(format!(
+            "`amount` must be a positive amount with at most 7 decimals, got `{amount}`"
+        )),
+    }
+}
+
+/// Two assets that trade against each other must differ.
+fn check_pair(a: &str, b: &str, a_key: &str, b_key: &str) -> Result<(), String> {
+    let (a, b) = (a.trim(), b.trim());
+    let same = match (check_asset(a_key, a)?, check_asset(b_key, b)?) {
+        (Some(Kind::Native), Some(Kind::Native)) => true,
+        (Some(Kind::Issued), Some(Kind::Issued)) => a == b,
+        _ => false,
+    };
+    if same {
+        return Err(format!("`{a_key}` and `{b_key}` must be different assets"));
+    }
+    Ok(())
+}
+
+/// An empty price is left for the required-key check; anything else is a
+/// positive decimal with at most 7 places, or a fraction `n/d` of positive
+/// 32-bit integers.
+fn check_price(price: &str) -> Result<(), String> {
+    let price = price.trim();
+    if price.is_empty() {
+        return Ok(());
+    }
+    let valid = match price.split_once('/') {
+        Some((n, d)) => [n, d]
+            .iter()
+            .all(|term| term.trim().parse::<i32>().is_ok_and(|term| term > 0)),
+        None => price
+            .parse::<Amount>()
+            .is_ok_and(|price| price.is_positive()),
+    };
+    if !valid {
+        return Err(format!(
+            "`price` must be a positive decimal with at most 7 decimals or a fraction like `1/8`, got `{price}`"
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+//! `autopay` and `multisig`.
+//!
+//! `NODE_TYPES` used to declare both twice. The Actions entries
+//! (`destinationAddress` / `totalDuration`, `signerAddresses` /
+//! `approvalTimeout`) were what the palette created, while `executeAutoPay`
+//! and `executeMultisig` read the Logic entries (`destination` / `duration`
+//! in days, `signers` / `timeout` in hours). Version 2 keeps the Logic key
+//! names with interval strings for every duration, and version 1 configs of
+//! either shape migrate into it.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stellrflow_recurrence::Recurrence;
+
+use crate::interval::parse_interval_ms;
+use crate::schema::{Category, Config, NodeConfig};
+use crate::{check_amount, de};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AutoPayConfig {
+    /// Stellar address that receives each payment.
+    #[serde(default)]
+    pub destination: String,
+    /// XLM per payment, as a decimal string.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+    /// `daily`, `weekly`, `monthly`, an interval such as `3600s` or `1d12h`,
+    /// or a calendar rule such as `weekdays at 09:00 UTC`.
+    #[serde(default = "daily")]
+    pub interval: String,
+    /// How long the schedule runs, e.g. `30d`.
+    #[serde(default = "thirty_days")]
+    pub duration: String,
+}
+
+fn daily() -> String {
+    "daily".into()
+}
+
+fn thirty_days() -> String {
+    "30d".into()
+}
+
+impl Default for AutoPayConfig {
+    fn default() -> Self {
+        Self {
+            destination: String::new(),
+            amount: String::new(),
+            interval: daily(),
+            duration: thirty_days(),
+        }
+    }
+}
+
+impl AutoPayConfig {
+    /// `duration` in milliseconds.
+    pub fn duration_ms(&self) -> Result<u64, String> {
+        parse_interval_ms(&self.duration)
+    }
+}
+
+impl NodeConfig for AutoPayConfig {
+    const NODE_TYPE: &'static str = "autopay";
+    const VERSION: u32 = 2;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "AutoPay";
+    const ICON: &'static str = "repeat";
+    const DESCRIPTION: &'static str =
+        "Set up recurring XLM payments at regular intervals from your Telegram wallet";
+    const REQUIRED: &'static [&'static str] = &["destination", "amount"];
+    const SECRETS: &'static [&'static str] = &["destination"];
+
+    fn migrate(mut config: Config, _from: u32) -> Result<Config, String> {
+        rename(&mut config, "destinationAddress", "destination")?;
+        // The Logic shape counted `duration` in days.
+        with_unit(&mut config, "duration", 'd');
+        rename(&mut config, "totalDuration", "duration")?;
+        Ok(config)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        check_amount(&self.amount)?;
+        Recurrence::parse(&self.interval).map_err(|err| format!("`interval`: {err}"))?;
+        positive_interval("duration", &self.duration)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MultisigConfig {
+    /// Stellar addresses allowed to approve.
+    #[serde(default, deserialize_with = "de::list")]
+    pub signers: Vec<String>,
+    /// Approvals needed before execution.
+    #[serde(default = "two", deserialize_with = "de::int_u32")]
+    pub threshold: u32,
+    /// How long approvals stay open, e.g. `24h`.
+    #[serde(default = "one_day")]
+    pub timeout: String,
+    /// Execute as soon as the threshold is reached.
+    #[serde(default, deserialize_with = "de::flag")]
+    pub auto_execute: bool,
+}
+
+fn two() -> u32 {
+    2
+}
+
+fn one_day() -> String {
+    "24h".into()
+}
+
+impl Default for MultisigConfig {
+    fn default() -> Self {
+        Self {
+            signers: Vec::new(),
+            threshold: two(),
+            timeout: one_day(),
+            auto_execute: false,
+        }
+    }
+}
+
+impl MultisigConfig {
+    /// `timeout` in milliseconds.
+    pub fn timeout_ms(&self) -> Result<u64, String> {
+        parse_interval_ms(&self.timeout)
+    }
+}
+
+impl NodeConfig for MultisigConfig {
+    const NODE_TYPE: &'static str = "multisig";
+    const VERSION: u32 = 2;
+    const CATEGORY: Category = Category::Action;
+    const LABEL: &'static str = "Multisig Approval";
+    const ICON: &'static str = "shield";
+    const DESCRIPTION: &'static str =
+        "Require multiple wallet signers to approve a transaction before execution";
+    const REQUIRED: &'static [&'static str] = &["signers"];
+    const SECRETS: &'static [&'static str] = &["signers"];
+
+    fn migrate(mut config: Config, _from: u32) -> Result<Config, String> {
+        rename(&mut config, "signerAddresses", "signers")?;
+        // The Logic shape counted `timeout` in hours.
+        with_unit(&mut config, "timeout", 'h');
+        rename(&mut config, "approvalTimeout", "timeout")?;
+        Ok(config)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.threshold == 0 {
+            return Err("threshold must be at least 1".into());
+        }
+        if !self.signers.is_empty() && self.threshold as usize > self.signers.len() {
+            return Err(format!(
+                "threshold {} is higher than the number of signers ({})",
+                self.threshold,
+                self.signers.len()
+            ));
+        }
+        for (i, signer) in self.signers.iter().enumerate() {
+            if self.signers[..i].contains(signer) {
+                return Err(format!("duplicate signer `{signer}`"));
+            }
+        }
+        positive_interval("timeout", &self.timeout)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn positive_interval(key: &str, value: &str) -> Result<(), String> {
+    match parse_interval_ms(value) {
+        Ok(0) => Err(format!("`{key}` must be longer than zero")),
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("`{key}`: {err}")),
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Move a legacy key to its canonical name. Fails if both are set to
+/// different values, rather than silently dropping one.
+fn rename(config: &mut Config, from: &str, to: &str) -> Result<(), String> {
+    let Some(old) = config.remove(from) else {
+        return Ok(());
+    };
+    match config.get(to) {
+        Some(new) if !is_blank(new) => {
+            if !is_blank(&old) && &old != new {
+                return Err(format!("both `{from}` and `{to}` are set"));
+            }
+        }
+        _ => {
+            config.insert(to.to_string(), old);
+        }
+    }
+    Ok(())
+}
+
+/// Turn a bare number under `key` into an interval string with `unit`.
+fn with_unit(config: &mut Config, key: &str, unit: char) {
+    let bare = match config.get(key) {
+        Some(Value::Number(n)) => n.to_string(),
+        Some(Value::String(s)) if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => {
+            s.clone()
+        }
+        _ => return,
+    };
+    config.insert(key.to_string(), Value::String(format!("{bare}{unit}")));
+}
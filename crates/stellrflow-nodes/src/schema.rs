@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::error::SchemaError;
+
+/// A node's `config` object.
+pub type Config = Map<String, Value>;
+
+/// Palette sections, as in `NODE_TYPES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Trigger,
+    Action,
+    Logic,
+}
+
+impl Category {
+    /// Key of the section in `NODE_TYPES`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Category::Trigger => "trigger",
+            Category::Action => "action",
+            Category::Logic => "logic",
+        }
+    }
+
+    /// Heading shown in the palette.
+    pub fn title(self) -> &'static str {
+        match self {
+            Category::Trigger => "Triggers",
+            Category::Action => "Actions",
+            Category::Logic => "Logic",
+        }
+    }
+}
+
+/// The typed config of one node type.
+///
+/// Implementors use `#[serde(deny_unknown_fields)]` so stray keys are
+/// rejected, and `Default` yields the config a freshly dropped node gets.
+pub trait NodeConfig: Serialize + DeserializeOwned + JsonSchema + Default {
+    const NODE_TYPE: &'static str;
+    /// Bumped whenever the canonical shape changes; [`migrate`] must accept
+    /// every earlier version.
+    ///
+    /// [`migrate`]: NodeConfig::migrate
+    const VERSION: u32;
+    const CATEGORY: Category;
+    const LABEL: &'static str;
+    const ICON: &'static str;
+    const DESCRIPTION: &'static str;
+    /// Keys that must be non-empty before the node can run.
+    const REQUIRED: &'static [&'static str] = &[];
+    /// Keys that identify a person or account (chat IDs, addresses). They
+    /// are blanked when a workflow is exported for sharing.
+    const SECRETS: &'static [&'static str] = &[];
+    /// Named output handles the node routes its result along. Empty means a
+    /// single unnamed output that always fires.
+    const OUTPUTS: &'static [&'static str] = &[];
+
+    /// Rewrite a config saved at version `from` (< [`VERSION`]) into the
+    /// current shape. Configs with no recorded version are version 1.
+    ///
+    /// [`VERSION`]: NodeConfig::VERSION
+    fn migrate(config: Config, _from: u32) -> Result<Config, String> {
+        Ok(config)
+    }
+
+    /// Checks that do not fit the type system, run after deserializing.
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Migrate `config` from version `from` and parse it.
+    fn parse(config: &Config, from: u32) -> Result<Self, SchemaError> {
+        if from > Self::VERSION {
+            return Err(SchemaError::UnsupportedVersion {
+                node_type: Self::NODE_TYPE.to_string(),
+                version: from,
+                current: Self::VERSION,
+            });
+        }
+        let mut config = config.clone();
+        if from < Self::VERSION {
+            config = Self::migrate(config, from)
+                .map_err(|msg| SchemaError::invalid(Self::NODE_TYPE, msg))?;
+        }
+        let parsed: Self = serde_json::from_value(Value::Object(config))
+            .map_err(|err| SchemaError::invalid(Self::NODE_TYPE, err.to_string()))?;
+        parsed
+            .check()
+            .map_err(|msg| SchemaError::invalid(Self::NODE_TYPE, msg))?;
+        Ok(parsed)
+    }
+
+    /// This config as a canonical JSON object.
+    fn to_config(&self) -> Config {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(config)) => config,
+            _ => unreachable!("node configs serialize to objects"),
+        }
+    }
+}
+
+/// Type-erased [`NodeConfig`] metadata, as stored in a [`SchemaRegistry`].
+#[derive(Clone, Copy)]
+pub struct NodeSchema {
+    pub node_type: &'static str,
+    pub version: u32,
+    pub category: Category,
+    pub label: &'static str,
+    pub icon: &'static str,
+    pub description: &'static str,
+    pub required: &'static [&'static str],
+    pub secrets: &'static [&'static str],
+    pub outputs: &'static [&'static str],
+    normalize: fn(&Config, u32) -> Result<Config, SchemaError>,
+    default_config: fn() -> Config,
+    json_schema: fn() -> Value,
+}
+
+impl NodeSchema {
+    pub fn of<T: NodeConfig>() -> Self {
+        Self {
+            node_type: T::NODE_TYPE,
+            version: T::VERSION,
+            category: T::CATEGORY,
+            label: T::LABEL,
+            icon: T::ICON,
+            description: T::DESCRIPTION,
+            required: T::REQUIRED,
+            secrets: T::SECRETS,
+            outputs: T::OUTPUTS,
+            normalize: |config, from| T::parse(config, from).map(|c| c.to_config()),
+            default_config: || T::default().to_config(),
+            json_schema: || schemars::schema_for!(T).to_value(),
+        }
+    }
+
+    pub fn is_trigger(&self) -> bool {
+        self.category == Category::Trigger
+    }
+
+    /// Migrate `config` from version `from` (`None` = 1) and return it in
+    /// canonical form.
+    pub fn normalize(&self, config: &Config, from: Option<u32>) -> Result<Config, SchemaError> {
+        (self.normalize)(config, from.unwrap_or(1))
+    }
+
+    pub fn default_config(&self) -> Config {
+        (self.default_config)()
+    }
+
+    /// JSON Schema (draft 2020-12) of the canonical config.
+    pub fn json_schema(&self) -> Value {
+        (self.json_schema)()
+    }
+}
+
+impl std::fmt::Debug for NodeSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeSchema")
+            .field("node_type", &self.node_type)
+            .field("version", &self.version)
+            .field("category", &self.category)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Node schemas by `NodeData.type`, in palette order.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaRegistry {
+    schemas: Vec<NodeSchema>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every node type the builder offers.
+    pub fn builtin() -> Self {
+        use crate::{
+            actions::*, assets::*, claimable::*, logic::*, market::*, payments::*, triggers::*,
+        };
+
+        let mut registry = Self::new();
+        registry
+            .register::<TelegramTriggerConfig>()
+            .register::<DiscordTriggerConfig>()
+            .register::<WhatsappTriggerConfig>()
+            .register::<PriceTriggerConfig>()
+            .register::<StellarSdkConfig>()
+            .register::<WalletIntegrationConfig>()
+            .register::<TelegramSendConfig>()
+            .register::<AnchorOnRampConfig>()
+            .register::<AnchorOffRampConfig>()
+            .register::<AutoPayConfig>()
+            .register::<MultisigConfig>()
+            .register::<TrustlineConfig>()
+            .register::<SendAssetConfig>()
+            .register::<ConvertPayConfig>()
+            .register::<ManageSellOfferConfig>()
+            .register::<ManageBuyOfferConfig>()
+            .register::<CreateClaimableBalanceConfig>()
+            .register::<ClaimClaimableBalanceConfig>()
+            .register::<DelayConfig>()
+            .register::<ConditionConfig>();
+        registry
+    }
+
+    /// Register `T`, replacing any schema already registered for its type.
+    pub fn register<T: NodeConfig>(&mut self) -> &mut Self {
+        let schema = NodeSchema::of::<T>();
+        match self
+            .schemas
+            .iter_mut()
+            .find(|s| s.node_type == T::NODE_TYPE)
+        {
+            Some(existing) => *existing = schema,
+            None => self.schemas.push(schema),
+        }
+        self
+    }
+
+    pub fn get(&self, node_type: &str) -> Option<&NodeSchema> {
+        self.schemas.iter().find(|s| s.node_type == node_type)
+    }
+
+    /// Schemas in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &NodeSchema> {
+        self.schemas.iter()
+    }
+
+    /// Canonical form of a `node_type` config saved at version `from`.
+    pub fn normalize(
+        &self,
+        node_type: &str,
+        config: &Config,
+        from: Option<u32>,
+    ) -> Result<Config, SchemaError> {
+        self.get(node_type)
+            .ok_or_else(|| SchemaError::UnknownNodeType(node_type.to_string()))?
+            .normalize(config, from)
+    }
+
+    /// `{ "<type>": { "version": n, "schema": <JSON Schema> }, … }`
+    pub fn json_schemas(&self) -> Value {
+        self.iter()
+            .map(|s| {
+                let entry = json!({ "version": s.version, "schema": s.json_schema() });
+                (s.node_type.to_string(), entry)
+            })
+            .collect::<Map<_, _>>()
+            .into()
+    }
+
+    /// The palette in the shape of the frontend's `NODE_TYPES`, each item
+    /// carrying its default config and `configVersion`.
+    pub fn catalog(&self) -> Value {
+        let mut sections: BTreeMap<Category, Vec<Value>> = BTreeMap::new();
+        for s in self.iter() {
+            sections.entry(s.category).or_default().push(json!({
+                "type": s.node_type,
+                "label": s.label,
+                "icon": s.icon,
+                "description": s.description,
+                "config": s.default_config(),
+                "configVersion": s.version,
+            }));
+        }
+        sections
+            .into_iter()
+            .map(|(category, items)| {
+                let section = json!({ "category": category.title(), "items": items });
+                (category.key().to_string(), section)
+            })
+            .collect::<Map<_, _>>()
+            .into()
+    }
+}
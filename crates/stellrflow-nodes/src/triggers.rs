@@ -0,0 +1,116 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::de;
+use crate::schema::{Category, NodeConfig};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TelegramTriggerConfig {
+    /// Numeric chat ID, as returned by the bot's `/register` command.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub chat_id: String,
+    #[serde(default = "all")]
+    pub message_types: String,
+}
+
+fn all() -> String {
+    "all".into()
+}
+
+impl Default for TelegramTriggerConfig {
+    fn default() -> Self {
+        Self {
+            chat_id: String::new(),
+            message_types: all(),
+        }
+    }
+}
+
+impl NodeConfig for TelegramTriggerConfig {
+    const NODE_TYPE: &'static str = "telegram-trigger";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Trigger;
+    const LABEL: &'static str = "Telegram";
+    const ICON: &'static str = "messageCircle";
+    const DESCRIPTION: &'static str =
+        "Enter your Telegram chat ID and hit Run to receive auth message and start workflow";
+    const REQUIRED: &'static [&'static str] = &["chatId"];
+    const SECRETS: &'static [&'static str] = &["chatId"];
+
+    fn check(&self) -> Result<(), String> {
+        if self.chat_id.trim().starts_with('@') {
+            return Err("use the numeric chat ID, not a username".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DiscordTriggerConfig {
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub server_id: String,
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub channel_id: String,
+    #[serde(default = "message")]
+    pub event_type: String,
+}
+
+fn message() -> String {
+    "message".into()
+}
+
+impl Default for DiscordTriggerConfig {
+    fn default() -> Self {
+        Self {
+            server_id: String::new(),
+            channel_id: String::new(),
+            event_type: message(),
+        }
+    }
+}
+
+impl NodeConfig for DiscordTriggerConfig {
+    const NODE_TYPE: &'static str = "discord-trigger";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Trigger;
+    const LABEL: &'static str = "Discord";
+    const ICON: &'static str = "hash";
+    const DESCRIPTION: &'static str = "Trigger on Discord channel messages";
+    const REQUIRED: &'static [&'static str] = &["serverId", "channelId"];
+    const SECRETS: &'static [&'static str] = &["serverId", "channelId"];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WhatsappTriggerConfig {
+    #[serde(default = "twilio")]
+    pub provider: String,
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub phone_number_id: String,
+}
+
+fn twilio() -> String {
+    "twilio".into()
+}
+
+impl Default for WhatsappTriggerConfig {
+    fn default() -> Self {
+        Self {
+            provider: twilio(),
+            phone_number_id: String::new(),
+        }
+    }
+}
+
+impl NodeConfig for WhatsappTriggerConfig {
+    const NODE_TYPE: &'static str = "whatsapp-trigger";
+    const VERSION: u32 = 1;
+    const CATEGORY: Category = Category::Trigger;
+    const LABEL: &'static str = "WhatsApp";
+    const ICON: &'static str = "phone";
+    const DESCRIPTION: &'static str = "Trigger on incoming WhatsApp messages";
+    const REQUIRED: &'static [&'static str] = &["phoneNumberId"];
+    const SECRETS: &'static [&'static str] = &["phoneNumberId"];
+}
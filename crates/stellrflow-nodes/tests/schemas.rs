@@ -0,0 +1,392 @@
+use serde_json::{json, Value};
+use stellrflow_nodes::interval::parse_interval_ms;
+use stellrflow_nodes::{
+    AutoPayConfig, Config, ConvertPayConfig, CreateClaimableBalanceConfig, ManageSellOfferConfig,
+    MultisigConfig, NodeConfig, PathStrict, PriceDirection, PriceFeed, PriceTriggerConfig,
+    SchemaError, SchemaRegistry,
+};
+
+fn config(value: Value) -> Config {
+    match value {
+        Value::Object(map) => map,
+        _ => panic!("not an object"),
+    }
+}
+
+fn normalize(node_type: &str, value: Value) -> Result<Value, SchemaError> {
+    SchemaRegistry::builtin()
+        .normalize(node_type, &config(value), None)
+        .map(Value::Object)
+}
+
+#[test]
+fn migrates_actions_autopay_shape() {
+    let legacy = json!({
+        "destinationAddress": "GDEST",
+        "amount": "5",
+        "interval": "3600s",
+        "totalDuration": "24h",
+    });
+    assert_eq!(
+        normalize("autopay", legacy).unwrap(),
+        json!({ "destination": "GDEST", "amount": "5", "interval": "3600s", "duration": "24h" })
+    );
+}
+
+#[test]
+fn migrates_logic_autopay_shape() {
+    let legacy =
+        json!({ "destination": "GDEST", "amount": 10, "interval": "daily", "duration": 30 });
+    assert_eq!(
+        normalize("autopay", legacy).unwrap(),
+        json!({ "destination": "GDEST", "amount": "10", "interval": "daily", "duration": "30d" })
+    );
+}
+
+#[test]
+fn migrates_both_multisig_shapes() {
+    let actions = json!({
+        "signerAddresses": "GA, GB\nGC",
+        "approvalTimeout": "300s",
+        "autoExecute": "false",
+    });
+    assert_eq!(
+        normalize("multisig", actions).unwrap(),
+        json!({
+            "signers": ["GA", "GB", "GC"],
+            "threshold": 2,
+            "timeout": "300s",
+            "autoExecute": false,
+        })
+    );
+
+    let logic = json!({ "threshold": "1", "signers": ["GA"], "timeout": 24 });
+    assert_eq!(
+        normalize("multisig", logic).unwrap(),
+        json!({ "signers": ["GA"], "threshold": 1, "timeout": "24h", "autoExecute": false })
+    );
+}
+
+#[test]
+fn canonical_configs_are_not_migrated() {
+    let registry = SchemaRegistry::builtin();
+    // At version 2 a bare number is not a day count.
+    let v2 = config(json!({ "destination": "G", "amount": "1", "duration": "30" }));
+    let normalized = registry.normalize("autopay", &v2, Some(2)).unwrap();
+    assert_eq!(normalized["duration"], "30");
+
+    let legacy_key = config(json!({ "destinationAddress": "G" }));
+    assert!(matches!(
+        registry.normalize("autopay", &legacy_key, Some(2)),
+        Err(SchemaError::Invalid { .. })
+    ));
+}
+
+#[test]
+fn normalizing_is_idempotent() {
+    let registry = SchemaRegistry::builtin();
+    for schema in registry.iter() {
+        let once = schema.normalize(&schema.default_config(), None).unwrap();
+        let twice = schema.normalize(&once, Some(schema.version)).unwrap();
+        assert_eq!(once, twice, "{}", schema.node_type);
+    }
+}
+
+#[test]
+fn rejects_unknown_fields() {
+    let err = normalize("telegram-send", json!({ "chatId": "1", "mesage": "typo" })).unwrap_err();
+    match err {
+        SchemaError::Invalid { node_type, message } => {
+            assert_eq!(node_type, "telegram-send");
+            assert!(message.contains("unknown field `mesage`"), "{message}");
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn rejects_conflicting_legacy_keys() {
+    let err = normalize(
+        "autopay",
+        json!({ "destinationAddress": "GA", "destination": "GB" }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid `autopay` config: both `destinationAddress` and `destination` are set"
+    );
+}
+
+/// Circle's USDC issuer on the public network.
+const ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+#[test]
+fn rejects_bad_values() {
+    for (node_type, value) in [
+        ("autopay", json!({ "amount": "-1" })),
+        ("autopay", json!({ "amount": "0.00000001" })),
+        ("anchor-offramp", json!({ "amount": "0" })),
+        ("anchor-onramp", json!({ "amount": "12.345" })),
+        ("autopay", json!({ "interval": "fortnightly" })),
+        ("multisig", json!({ "signers": ["GA"], "threshold": 2 })),
+        (
+            "multisig",
+            json!({ "signers": ["GA", "GA"], "threshold": 1 }),
+        ),
+        ("stellar-sdk", json!({ "operation": "swap" })),
+        ("trustline", json!({ "asset": "native" })),
+        ("trustline", json!({ "asset": "USDC:GA", "limit": "10" })),
+        (
+            "trustline",
+            json!({ "asset": format!("USDC:{ISSUER}"), "limit": "-1" }),
+        ),
+        ("send-asset", json!({ "asset": "USDC", "amount": "1" })),
+        (
+            "convert-pay",
+            json!({ "destAsset": "USDC:GA", "amount": "1" }),
+        ),
+        (
+            "convert-pay",
+            json!({ "destAsset": format!("USDC:{ISSUER}"), "strict": "both" }),
+        ),
+        (
+            "convert-pay",
+            json!({ "destAsset": format!("USDC:{ISSUER}"), "slippage": "100.5" }),
+        ),
+        ("convert-pay", json!({ "slippage": "0.125" })),
+        ("price-trigger", json!({ "counter": "native" })),
+        ("price-trigger", json!({ "price": "1/0" })),
+        ("price-trigger", json!({ "price": "0.1", "interval": "0s" })),
+        ("price-trigger", json!({ "condition": "crosses" })),
+        (
+            "manage-sell-offer",
+            json!({ "selling": "XLM", "buying": "native" }),
+        ),
+        ("manage-sell-offer", json!({ "price": "-0.5" })),
+        ("manage-sell-offer", json!({ "amount": "0" })),
+        (
+            "manage-buy-offer",
+            json!({ "amount": "5", "offerId": "abc" }),
+        ),
+        (
+            "create-claimable-balance",
+            json!({ "claimants": "GA GA", "amount": "1" }),
+        ),
+        (
+            "create-claimable-balance",
+            json!({ "claimableAfter": "1d", "expiresAfter": "1h" }),
+        ),
+        ("create-claimable-balance", json!({ "reclaim": true })),
+        ("create-claimable-balance", json!({ "expiresAfter": "0s" })),
+        ("claim-claimable-balance", json!({ "balanceId": "abc" })),
+        ("telegram-trigger", json!({ "chatId": "@someone" })),
+    ] {
+        assert!(
+            matches!(
+                normalize(node_type, value.clone()),
+                Err(SchemaError::Invalid { .. })
+            ),
+            "{node_type}: {value}"
+        );
+    }
+}
+
+#[test]
+fn rejects_future_versions_and_unknown_types() {
+    let registry = SchemaRegistry::builtin();
+    assert_eq!(
+        registry.normalize("delay", &Config::new(), Some(9)),
+        Err(SchemaError::UnsupportedVersion {
+            node_type: "delay".into(),
+            version: 9,
+            current: 1,
+        })
+    );
+    assert_eq!(
+        registry.normalize("nope", &Config::new(), None),
+        Err(SchemaError::UnknownNodeType("nope".into()))
+    );
+}
+
+#[test]
+fn typed_parse() {
+    let autopay = AutoPayConfig::parse(
+        &config(json!({ "destination": "G", "amount": "2.5", "duration": 7 })),
+        1,
+    )
+    .unwrap();
+    assert_eq!(autopay.duration_ms(), Ok(7 * 86_400_000));
+
+    let multisig = MultisigConfig::parse(&config(json!({})), 2).unwrap();
+    assert_eq!(multisig, MultisigConfig::default());
+
+    let convert = ConvertPayConfig::parse(
+        &config(json!({ "destAsset": format!("USDC:{ISSUER}"), "amount": 10, "slippage": 0.5 })),
+        1,
+    )
+    .unwrap();
+    assert_eq!(
+        (convert.send_asset.as_str(), convert.strict),
+        ("native", PathStrict::Receive)
+    );
+    assert_eq!(
+        (convert.amount.as_str(), convert.slippage.as_str()),
+        ("10", "0.5")
+    );
+
+    let trigger = PriceTriggerConfig::parse(
+        &config(json!({ "chatId": 42, "counter": format!("USDC:{ISSUER}"), "price": 0.12 })),
+        1,
+    )
+    .unwrap();
+    assert_eq!(
+        (trigger.base.as_str(), trigger.price.as_str()),
+        ("native", "0.12")
+    );
+    assert_eq!(
+        (trigger.condition, trigger.source),
+        (PriceDirection::Above, PriceFeed::Mid)
+    );
+    assert_eq!(
+        (trigger.interval.as_str(), trigger.timeout.as_str()),
+        ("30s", "24h")
+    );
+
+    // A zero amount cancels the offer it names.
+    let cancel = ManageSellOfferConfig::parse(
+        &config(json!({
+            "selling": "native",
+            "buying": format!("USDC:{ISSUER}"),
+            "amount": 0,
+            "price": "1/8",
+            "offerId": 1234,
+        })),
+        1,
+    )
+    .unwrap();
+    assert_eq!(
+        (cancel.amount.as_str(), cancel.offer_id.as_str()),
+        ("0", "1234")
+    );
+
+    let claimable = CreateClaimableBalanceConfig::parse(
+        &config(json!({
+            "claimants": "GA, GB",
+            "amount": 5,
+            "expiresAfter": "30d",
+            "reclaim": "true",
+        })),
+        1,
+    )
+    .unwrap();
+    assert_eq!(claimable.claimants, ["GA", "GB"]);
+    assert_eq!(
+        (claimable.asset.as_str(), claimable.claimable_after.as_str()),
+        ("native", "")
+    );
+    assert!(claimable.reclaim);
+}
+
+#[test]
+fn exports_json_schema() {
+    let schemas = SchemaRegistry::builtin().json_schemas();
+    let autopay = &schemas["autopay"];
+
+    assert_eq!(autopay["version"], 2);
+    assert_eq!(autopay["schema"]["additionalProperties"], false);
+    let properties = autopay["schema"]["properties"].as_object().unwrap();
+    let keys: Vec<&str> = properties.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["amount", "destination", "duration", "interval"]);
+
+    assert_eq!(
+        schemas["stellar-sdk"]["schema"]["$defs"]["SdkOperation"]["oneOf"]
+            .as_array()
+            .map(Vec::len),
+        Some(2)
+    );
+}
+
+#[test]
+fn catalog_lists_each_type_once() {
+    let catalog = SchemaRegistry::builtin().catalog();
+
+    let types: Vec<&str> = ["trigger", "action", "logic"]
+        .iter()
+        .flat_map(|key| catalog[key]["items"].as_array().unwrap())
+        .map(|item| item["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types.iter().filter(|t| **t == "autopay").count(), 1);
+    assert_eq!(types.iter().filter(|t| **t == "multisig").count(), 1);
+    assert_eq!(
+        catalog["logic"]["items"][0]["config"],
+        json!({ "delay": 5 })
+    );
+    assert_eq!(catalog["action"]["category"], "Actions");
+}
+
+#[test]
+fn interval_grammar_matches_the_bot() {
+    assert_eq!(parse_interval_ms("3600s"), Ok(3_600_000));
+    assert_eq!(parse_interval_ms(" 1.5 h "), Ok(5_400_000));
+    assert_eq!(parse_interval_ms("2WEEKS"), Ok(1_209_600_000));
+    assert_eq!(parse_interval_ms("250"), Ok(250));
+    assert_eq!(
+        parse_interval_ms("5y"),
+        Err("Unknown time unit: y".to_string())
+    );
+    assert!(parse_interval_ms(".5h").is_err());
+    assert!(parse_interval_ms("").is_err());
+}
+
+#[test]
+fn autopay_intervals_may_be_calendar_rules() {
+    for interval in ["monthly", "1d12h", "weekdays at 09:00 UTC", "0 9 * * MON"] {
+        let config = normalize("autopay", json!({ "interval": interval })).unwrap();
+        assert_eq!(config["interval"], interval);
+    }
+    let err = normalize("autopay", json!({ "interval": "0 25 * * *" })).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid `autopay` config: `interval`: hour `25` is not 0 to 23"
+    );
+}
+
+#[test]
+fn condition_expressions_are_parsed() {
+    let condition = normalize("condition", json!({ "expression": "balance < 50" })).unwrap();
+    assert_eq!(condition, json!({ "expression": "balance < 50" }));
+    assert_eq!(
+        normalize("condition", json!({ "expression": "balance = 50" }))
+            .unwrap_err()
+            .to_string(),
+        "invalid `condition` config: `expression`: unexpected `=`, use `==` to compare at column 9"
+    );
+
+    let registry = SchemaRegistry::builtin();
+    assert_eq!(
+        registry.get("condition").unwrap().outputs,
+        ["true", "false"]
+    );
+    assert!(registry.get("delay").unwrap().outputs.is_empty());
+}
+
+#[test]
+fn telegram_messages_are_templates() {
+    let legacy = json!({ "message": "Balance of {address}: {balance} XLM" });
+    assert_eq!(
+        normalize("telegram-send", legacy).unwrap(),
+        json!({ "chatId": "", "message": "Balance of {{address}}: {{balance}} XLM" })
+    );
+    assert_eq!(
+        SchemaRegistry::builtin()
+            .normalize(
+                "telegram-send",
+                &config(json!({ "message": "{{ balance | shout }}" })),
+                Some(2),
+            )
+            .unwrap_err()
+            .to_string(),
+        "invalid `telegram-send` config: `message`: unknown filter `shout` (expected one of: \
+         amount, short, date, markdown, html, upper, lower, default) at column 14"
+    );
+}
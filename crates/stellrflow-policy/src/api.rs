@@ -0,0 +1,167 @@
+//! The policy endpoints, backed by a [`PolicyStore`].
+//!
+//! | Method   | Path                                  |                              |
+//! |----------|---------------------------------------|------------------------------|
+//! | `POST`   | `/api/policy/check`                   | decide on a payment          |
+//! | `GET`    | `/api/policy/{chatId}`                | the chat's policy            |
+//! | `PUT`    | `/api/policy/{chatId}`                | replace it                   |
+//! | `DELETE` | `/api/policy/{chatId}`                | remove it                    |
+//! | `GET`    | `/api/policy/{chatId}/decisions`      | the chat's decisions         |
+//! | `POST`   | `/api/policy/decisions/{id}/release`  | un-count an unmade payment   |
+//!
+//! `default` in place of a chat ID is the policy every wallet is held to.
+//! Responses follow `telegram-bot.ts`: `{ success: true, ... }` on success
+//! and `{ success: false, error }` with a 4xx/5xx status otherwise. A denied
+//! payment is still a successful check: it answers `allowed: false` and the
+//! recorded `decision`.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::decision::SpendRequest;
+use crate::error::PolicyError;
+use crate::now_ms;
+use crate::rule::Policy;
+use crate::store::PolicyStore;
+
+type Shared = Arc<PolicyStore>;
+
+/// How many decisions are listed when the request does not say.
+const DEFAULT_DECISIONS: u32 = 50;
+
+/// The policy routes, backed by `store`.
+pub fn router(store: Shared) -> Router {
+    Router::new()
+        .route("/api/policy/check", post(check))
+        .route(
+            "/api/policy/{chat_id}",
+            get(show).put(replace).delete(remove),
+        )
+        .route("/api/policy/{chat_id}/decisions", get(decisions))
+        .route("/api/policy/decisions/{id}/release", post(release))
+        .with_state(store)
+}
+
+async fn check(
+    State(store): State<Shared>,
+    Json(request): Json<SpendRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let now = now_ms();
+    let decision = blocking(store, move |store| store.check(&request, now)).await?;
+    println!(
+        "Policy: {} {} XLM from {} to {} ({}: {})",
+        decision.outcome,
+        decision.amount,
+        decision.chat_id,
+        decision.destination,
+        decision.rule.as_ref().map_or("none", |rule| rule.kind()),
+        decision.reason,
+    );
+    Ok(Json(json!({
+        "success": true,
+        "allowed": decision.allowed(),
+        "decision": decision,
+    })))
+}
+
+async fn show(
+    State(store): State<Shared>,
+    Path(chat_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let id = chat_id.clone();
+    let (policy, updated_at) = blocking(store, move |store| store.policy(&id)).await?;
+    Ok(Json(json!({
+        "success": true,
+        "chatId": chat_id,
+        "rules": policy.rules,
+        "updatedAt": updated_at,
+    })))
+}
+
+async fn replace(
+    State(store): State<Shared>,
+    Path(chat_id): Path<String>,
+    Json(policy): Json<Policy>,
+) -> Result<Json<Value>, ApiError> {
+    let now = now_ms();
+    let (id, rules) = (chat_id.clone(), policy.clone());
+    blocking(store, move |store| store.set_policy(&id, &rules, now)).await?;
+    println!("Policy set for {chat_id}: {} rule(s)", policy.rules.len());
+    Ok(Json(json!({
+        "success": true,
+        "chatId": chat_id,
+        "rules": policy.rules,
+        "updatedAt": now,
+    })))
+}
+
+async fn remove(
+    State(store): State<Shared>,
+    Path(chat_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    blocking(store, move |store| store.remove_policy(&chat_id)).await?;
+    Ok(Json(
+        json!({ "success": true, "message": "Policy removed" }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct DecisionsQuery {
+    limit: Option<u32>,
+}
+
+async fn decisions(
+    State(store): State<Shared>,
+    Path(chat_id): Path<String>,
+    Query(query): Query<DecisionsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_DECISIONS);
+    let decisions = blocking(store, move |store| store.decisions(&chat_id, limit)).await?;
+    Ok(Json(json!({ "success": true, "decisions": decisions })))
+}
+
+async fn release(
+    State(store): State<Shared>,
+    Path(id): Path<i64>,
+) -> Result<Json<Value>, ApiError> {
+    let decision = blocking(store, move |store| store.release(id)).await?;
+    Ok(Json(json!({ "success": true, "decision": decision })))
+}
+
+/// Run a store call on the blocking thread pool.
+async fn blocking<T, F>(store: Shared, f: F) -> Result<T, PolicyError>
+where
+    T: Send + 'static,
+    F: FnOnce(&PolicyStore) -> Result<T, PolicyError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&store))
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+}
+
+/// A failed request: its status and the message sent as `error`.
+struct ApiError(StatusCode, String);
+
+impl From<PolicyError> for ApiError {
+    fn from(err: PolicyError) -> Self {
+        let status = match err {
+            PolicyError::Invalid(_) => StatusCode::BAD_REQUEST,
+            PolicyError::NoPolicy(_) | PolicyError::NotFound(_) => StatusCode::NOT_FOUND,
+            PolicyError::Sqlite(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "success": false, "error": self.1 }))).into_response()
+    }
+}
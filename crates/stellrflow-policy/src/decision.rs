@@ -0,0 +1,168 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use stellrflow_amount::Amount;
+use stellrflow_nodes::de;
+
+use crate::error::PolicyError;
+use crate::rule::Rule;
+
+/// What asked for a payment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// `/send` in a chat.
+    #[default]
+    Manual,
+    /// A workflow's payment node, through `/api/wallet/{chatId}/send`.
+    Workflow,
+    /// A scheduled AutoPay payment.
+    Autopay,
+    /// An anchor withdrawal, paid to the anchor's treasury.
+    Offramp,
+}
+
+/// The decision on a payment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Outcome {
+    /// Sign and submit it.
+    Allow,
+    /// Do not make it.
+    Deny,
+    /// Do not sign it alone; it needs the wallet's multisig approval.
+    RequireMultisig,
+}
+
+impl Source {
+    const NAMES: [(Source, &'static str); 4] = [
+        (Source::Manual, "manual"),
+        (Source::Workflow, "workflow"),
+        (Source::Autopay, "autopay"),
+        (Source::Offramp, "offramp"),
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(source, _)| *source == self)
+            .map_or("", |(_, name)| name)
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == s.trim())
+            .map(|(source, _)| *source)
+            .ok_or_else(|| {
+                format!(
+                    "unknown payment source `{s}` (expected manual, workflow, autopay or offramp)"
+                )
+            })
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Outcome {
+    const NAMES: [(Outcome, &'static str); 3] = [
+        (Outcome::Allow, "allow"),
+        (Outcome::Deny, "deny"),
+        (Outcome::RequireMultisig, "requireMultisig"),
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(outcome, _)| *outcome == self)
+            .map_or("", |(_, name)| name)
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == s.trim())
+            .map(|(outcome, _)| *outcome)
+            .ok_or_else(|| format!("unknown outcome `{s}`"))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A payment about to be signed: `amount` XLM from the chat's wallet to
+/// `destination`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendRequest {
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub chat_id: String,
+    #[serde(default)]
+    pub destination: String,
+    #[serde(default)]
+    pub amount: Decimal,
+    /// `manual` when absent.
+    #[serde(default)]
+    pub source: Source,
+}
+
+impl SpendRequest {
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        if self.chat_id.trim().is_empty()
+            || self.destination.trim().is_empty()
+            || self.amount.is_zero()
+        {
+            return Err(PolicyError::Invalid(
+                "chatId, destination, and amount are required".into(),
+            ));
+        }
+        // Only what a payment can carry: whole stroops, up to an i64 of them.
+        if self.amount.is_sign_negative() || self.amount.to_string().parse::<Amount>().is_err() {
+            return Err(PolicyError::Invalid("Invalid amount".into()));
+        }
+        Ok(())
+    }
+}
+
+/// A recorded decision on a [`SpendRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Decision {
+    pub id: i64,
+    pub chat_id: String,
+    pub destination: String,
+    pub amount: Decimal,
+    pub source: Source,
+    pub outcome: Outcome,
+    /// The rule that fired; `None` when nothing objected.
+    pub rule: Option<Rule>,
+    pub reason: String,
+    /// Unix milliseconds.
+    pub decided_at: i64,
+    /// An allowed payment that was never made, so it no longer counts
+    /// towards the wallet's limits.
+    pub released: bool,
+}
+
+impl Decision {
+    pub fn allowed(&self) -> bool {
+        self.outcome == Outcome::Allow
+    }
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+/// Why a policy call failed.
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    /// The policy or payment request is missing a field or has a malformed
+    /// one.
+    #[error("{0}")]
+    Invalid(String),
+    #[error("No policy for this chat")]
+    NoPolicy(String),
+    #[error("Decision not found")]
+    NotFound(i64),
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
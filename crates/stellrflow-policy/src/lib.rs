@@ -0,0 +1,41 @@
+//! Spending policies, checked before any payment is signed.
+//!
+//! The bot's `/send` and `/api/wallet/{chatId}/send` used to pay any amount
+//! to any address as soon as they were asked. With this service running,
+//! the bot first asks [`PolicyStore::check`] (through `POST
+//! /api/policy/check`) about every payment it is about to sign: manual
+//! sends, workflow payments, AutoPay and anchor withdrawals alike.
+//!
+//! - A [`Policy`] is a list of [`Rule`]s: a cap per payment, rolling daily
+//!   and weekly limits, destination allow- and denylists, time-of-day
+//!   windows and a multisig threshold.
+//! - A payment is held to the `default` policy and its chat's own. The first
+//!   rule that denies it decides; otherwise a `requireMultisig` rule it is
+//!   over sends it for approval; otherwise it is allowed.
+//! - Every [`Decision`] is recorded with the rule that fired and why.
+//!   Allowed payments count towards the limits until the bot
+//!   [releases](PolicyStore::release) one it failed to make.
+//!
+//! [`api::router`] serves the endpoints; the `stellrflow-policy` binary runs
+//! them on `PORT` (default 3006), and the bot checks with it when
+//! `POLICY_URL` is set.
+
+pub mod api;
+mod decision;
+mod error;
+mod rule;
+mod store;
+
+pub use decision::{Decision, Outcome, Source, SpendRequest};
+pub use error::PolicyError;
+pub use rule::{Policy, Rule, Spent, Verdict};
+pub use store::{PolicyStore, DEFAULT_POLICY};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The current time as a Unix timestamp in milliseconds.
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
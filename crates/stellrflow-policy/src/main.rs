@@ -0,0 +1,28 @@
+//! Serves the spending policy API.
+//!
+//! - `PORT` (default 3006): the API listener.
+//! - `STELLRFLOW_POLICY_DB` (default `stellrflow-policy.db`): the SQLite
+//!   file.
+
+use std::env;
+use std::sync::Arc;
+
+use stellrflow_policy::{api, PolicyStore};
+use tower_http::cors::CorsLayer;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let port: u16 = env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3006);
+    let db = env::var("STELLRFLOW_POLICY_DB").unwrap_or_else(|_| "stellrflow-policy.db".into());
+
+    let store = Arc::new(PolicyStore::open(&db)?);
+    // The builder runs on its own origin, like calls to the bot's API.
+    let app = api::router(store).layer(CorsLayer::permissive());
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("StellrFlow spending policy service running on port {port} (database: {db})");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
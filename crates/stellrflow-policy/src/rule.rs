@@ -0,0 +1,316 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Weekday};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::decision::{Outcome, SpendRequest};
+use crate::error::PolicyError;
+
+/// One restriction on a wallet's payments.
+///
+/// Written in JSON with a `type` tag, e.g.
+/// `{ "type": "dailyLimit", "amount": "100" }`. Amounts are in XLM.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Rule {
+    /// No single payment may be larger than `amount`.
+    MaxPerTransaction { amount: Decimal },
+    /// Payments in any 24 hours may not add up to more than `amount`.
+    DailyLimit { amount: Decimal },
+    /// Payments in any 7 days may not add up to more than `amount`.
+    WeeklyLimit { amount: Decimal },
+    /// Payments may only go to these accounts.
+    Allowlist { destinations: Vec<String> },
+    /// Payments may never go to these accounts.
+    Denylist { destinations: Vec<String> },
+    /// Payments may only be made between `start` and `end` (`HH:MM`, end
+    /// excluded; an `end` before `start` runs past midnight), on `days`
+    /// (`mon`…`sun`, every day when empty), in `timezone` (UTC when absent).
+    TimeWindow {
+        start: String,
+        end: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        days: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timezone: Option<String>,
+    },
+    /// Payments larger than `above` need multisig approval instead of being
+    /// signed straight away.
+    RequireMultisig { above: Decimal },
+}
+
+impl Rule {
+    /// The rule's `type` tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Rule::MaxPerTransaction { .. } => "maxPerTransaction",
+            Rule::DailyLimit { .. } => "dailyLimit",
+            Rule::WeeklyLimit { .. } => "weeklyLimit",
+            Rule::Allowlist { .. } => "allowlist",
+            Rule::Denylist { .. } => "denylist",
+            Rule::TimeWindow { .. } => "timeWindow",
+            Rule::RequireMultisig { .. } => "requireMultisig",
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let positive = |amount: &Decimal| {
+            if amount.is_sign_positive() && !amount.is_zero() {
+                Ok(())
+            } else {
+                Err(format!("{}: amount must be positive", self.kind()))
+            }
+        };
+        match self {
+            Rule::MaxPerTransaction { amount }
+            | Rule::DailyLimit { amount }
+            | Rule::WeeklyLimit { amount } => positive(amount),
+            Rule::RequireMultisig { above } => positive(above),
+            Rule::Allowlist { destinations } | Rule::Denylist { destinations } => {
+                if destinations.is_empty() {
+                    return Err(format!("{}: destinations are required", self.kind()));
+                }
+                for destination in destinations {
+                    if stellar_strkey::ed25519::PublicKey::from_string(destination).is_err() {
+                        return Err(format!(
+                            "{}: `{destination}` is not a Stellar account",
+                            self.kind()
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            Rule::TimeWindow {
+                start,
+                end,
+                days,
+                timezone,
+            } => {
+                let (start, end) = (clock(start)?, clock(end)?);
+                if start == end {
+                    return Err("timeWindow: start and end must differ".into());
+                }
+                for day in days {
+                    weekday(day)?;
+                }
+                zone(timezone.as_deref())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// What this rule says about `request`, made at `now` (Unix
+    /// milliseconds) after `spent`. `None` when it has no objection.
+    fn check(&self, request: &SpendRequest, spent: &Spent, now: i64) -> Option<(Outcome, String)> {
+        let amount = request.amount;
+        // A total too large to count is over any limit.
+        let day = spent.day.checked_add(amount);
+        let week = spent.week.checked_add(amount);
+        let deny = |reason: String| Some((Outcome::Deny, reason));
+        match self {
+            Rule::MaxPerTransaction { amount: max } if amount > *max => deny(format!(
+                "{} XLM is over the limit of {} XLM per payment",
+                xlm(amount),
+                xlm(*max)
+            )),
+            Rule::DailyLimit { amount: limit } if day.is_none_or(|day| day > *limit) => {
+                deny(format!(
+                    "{} XLM would bring the last 24 hours to {}, over the daily limit of {} XLM",
+                    xlm(amount),
+                    total(day),
+                    xlm(*limit)
+                ))
+            }
+            Rule::WeeklyLimit { amount: limit } if week.is_none_or(|week| week > *limit) => {
+                deny(format!(
+                    "{} XLM would bring the last 7 days to {}, over the weekly limit of {} XLM",
+                    xlm(amount),
+                    total(week),
+                    xlm(*limit)
+                ))
+            }
+            Rule::Allowlist { destinations } if !destinations.contains(&request.destination) => {
+                deny(format!(
+                    "{} is not on the allowlist",
+                    short(&request.destination)
+                ))
+            }
+            Rule::Denylist { destinations } if destinations.contains(&request.destination) => deny(
+                format!("{} is on the denylist", short(&request.destination)),
+            ),
+            Rule::TimeWindow {
+                start,
+                end,
+                days,
+                timezone,
+            } => {
+                // Rules are validated before they are stored.
+                let (Ok(start), Ok(end), Ok(tz)) =
+                    (clock(start), clock(end), zone(timezone.as_deref()))
+                else {
+                    return deny("the time window is malformed".into());
+                };
+                let local = DateTime::from_timestamp_millis(now)?.with_timezone(&tz);
+                let time = local.time().with_nanosecond(0)?;
+                let in_hours = if start < end {
+                    start <= time && time < end
+                } else {
+                    time >= start || time < end
+                };
+                let on_day = days.is_empty()
+                    || days
+                        .iter()
+                        .any(|day| weekday(day).is_ok_and(|day| day == local.weekday()));
+                if in_hours && on_day {
+                    return None;
+                }
+                deny(format!(
+                    "payments are only allowed {}-{}{} {}",
+                    start.format("%H:%M"),
+                    end.format("%H:%M"),
+                    if days.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" on {}", days.join(", "))
+                    },
+                    tz.name()
+                ))
+            }
+            Rule::RequireMultisig { above } if amount > *above => Some((
+                Outcome::RequireMultisig,
+                format!("payments over {} XLM need multisig approval", xlm(*above)),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+/// How much a wallet has had allowed recently, in XLM.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spent {
+    /// In the last 24 hours.
+    pub day: Decimal,
+    /// In the last 7 days.
+    pub week: Decimal,
+}
+
+/// A wallet's rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    pub rules: Vec<Rule>,
+}
+
+/// What a [`Policy`] says about a payment, and the rule that decided it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    pub outcome: Outcome,
+    /// `None` when the payment was allowed because nothing objected.
+    pub rule: Option<Rule>,
+    pub reason: String,
+}
+
+impl Policy {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules }
+    }
+
+    /// Check that every rule is well formed.
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        self.rules
+            .iter()
+            .try_for_each(Rule::validate)
+            .map_err(PolicyError::Invalid)
+    }
+
+    /// Decide on `request`, made at `now` (Unix milliseconds) by a wallet
+    /// that has already had `spent` allowed.
+    ///
+    /// The first rule that denies the payment decides it. Otherwise a
+    /// `requireMultisig` rule it is over sends it for approval, and
+    /// failing that it is allowed.
+    pub fn evaluate(&self, request: &SpendRequest, spent: &Spent, now: i64) -> Verdict {
+        let mut multisig = None;
+        for rule in &self.rules {
+            match rule.check(request, spent, now) {
+                Some((Outcome::Deny, reason)) => {
+                    return Verdict {
+                        outcome: Outcome::Deny,
+                        rule: Some(rule.clone()),
+                        reason,
+                    }
+                }
+                Some((outcome, reason)) if multisig.is_none() => {
+                    multisig = Some(Verdict {
+                        outcome,
+                        rule: Some(rule.clone()),
+                        reason,
+                    });
+                }
+                _ => {}
+            }
+        }
+        multisig.unwrap_or_else(|| Verdict {
+            outcome: Outcome::Allow,
+            rule: None,
+            reason: "within policy".into(),
+        })
+    }
+}
+
+/// `HH:MM` as a time of day.
+fn clock(text: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .map_err(|_| format!("timeWindow: `{text}` is not a time (HH:MM)"))
+}
+
+fn weekday(text: &str) -> Result<Weekday, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("timeWindow: `{text}` is not a day of the week"))
+}
+
+fn zone(name: Option<&str>) -> Result<Tz, String> {
+    match name {
+        None => Ok(Tz::UTC),
+        Some(name) => name
+            .trim()
+            .parse()
+            .map_err(|_| format!("timeWindow: unknown timezone `{name}`")),
+    }
+}
+
+/// A running total as the bot shows it, or that it overflowed.
+fn total(total: Option<Decimal>) -> String {
+    match total {
+        Some(total) => format!("{} XLM", xlm(total)),
+        None => format!("more than {} XLM", Decimal::MAX),
+    }
+}
+
+/// An amount as the bot shows it: no trailing zeros.
+fn xlm(amount: Decimal) -> Decimal {
+    amount.normalize()
+}
+
+/// `GABC…WXYZ`, as the bot abbreviates accounts.
+fn short(account: &str) -> String {
+    match (
+        account.get(..4),
+        account.get(account.len().saturating_sub(4)..),
+    ) {
+        (Some(head), Some(tail)) if account.len() > 12 => format!("{head}…{tail}"),
+        _ => account.to_string(),
+    }
+}
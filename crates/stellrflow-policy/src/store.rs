@@ -0,0 +1,279 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use rust_decimal::Decimal;
+
+use crate::decision::{Decision, Outcome, SpendRequest};
+use crate::error::PolicyError;
+use crate::rule::{Policy, Spent};
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE policies (
+        chat_id    TEXT    PRIMARY KEY,
+        rules      TEXT    NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    CREATE TABLE decisions (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        chat_id     TEXT    NOT NULL,
+        destination TEXT    NOT NULL,
+        amount      TEXT    NOT NULL,
+        source      TEXT    NOT NULL,
+        outcome     TEXT    NOT NULL,
+        rule        TEXT,
+        reason      TEXT    NOT NULL,
+        decided_at  INTEGER NOT NULL,
+        released    INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX decisions_by_chat ON decisions (chat_id, decided_at);
+"];
+
+const DECISION_COLUMNS: &str =
+    "id, chat_id, destination, amount, source, outcome, rule, reason, decided_at, released";
+
+/// The policy every wallet is held to, on top of its own.
+pub const DEFAULT_POLICY: &str = "default";
+
+const DAY_MS: i64 = 86_400_000;
+const WEEK_MS: i64 = 7 * DAY_MS;
+
+/// Policies and the decisions made under them in a single SQLite database.
+#[derive(Debug)]
+pub struct PolicyStore {
+    conn: Mutex<Connection>,
+}
+
+impl PolicyStore {
+    /// Open (creating if needed) the database at `path` and bring its schema
+    /// up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    /// A private database that lives as long as the store.
+    pub fn open_in_memory() -> Result<Self, PolicyError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, PolicyError> {
+        let applied: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let tx = conn.transaction()?;
+        for (version, migration) in (1..).zip(MIGRATIONS).skip(applied as usize) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", version)?;
+        }
+        tx.commit()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The chat's own policy ([`DEFAULT_POLICY`] for the default one) and
+    /// when it was last set.
+    pub fn policy(&self, chat_id: &str) -> Result<(Policy, i64), PolicyError> {
+        find_policy(&self.conn(), chat_id)?.ok_or_else(|| PolicyError::NoPolicy(chat_id.into()))
+    }
+
+    /// Replace the chat's policy.
+    pub fn set_policy(&self, chat_id: &str, policy: &Policy, now: i64) -> Result<(), PolicyError> {
+        if chat_id.trim().is_empty() {
+            return Err(PolicyError::Invalid("chatId is required".into()));
+        }
+        policy.validate()?;
+        let rules = serde_json::to_string(&policy.rules).expect("rules always serialize");
+        self.conn().execute(
+            "INSERT INTO policies (chat_id, rules, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (chat_id) DO UPDATE SET rules = ?2, updated_at = ?3",
+            params![chat_id, rules, now],
+        )?;
+        Ok(())
+    }
+
+    /// Drop the chat's policy. Its decisions are kept.
+    pub fn remove_policy(&self, chat_id: &str) -> Result<(), PolicyError> {
+        let removed = self
+            .conn()
+            .execute("DELETE FROM policies WHERE chat_id = ?1", [chat_id])?;
+        if removed == 0 {
+            return Err(PolicyError::NoPolicy(chat_id.into()));
+        }
+        Ok(())
+    }
+
+    /// Decide on `request` under the default policy and the chat's own,
+    /// and record the decision. An allowed payment counts towards the
+    /// chat's limits from now on, unless it is [released](Self::release).
+    pub fn check(&self, request: &SpendRequest, now: i64) -> Result<Decision, PolicyError> {
+        request.validate()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut policy = Policy::default();
+        for chat_id in [DEFAULT_POLICY, &request.chat_id] {
+            if let Some((own, _)) = find_policy(&tx, chat_id)? {
+                policy.rules.extend(own.rules);
+            }
+        }
+        let spent = spent(&tx, &request.chat_id, now)?;
+        let verdict = policy.evaluate(request, &spent, now);
+        let rule = verdict
+            .rule
+            .as_ref()
+            .map(|rule| serde_json::to_string(rule).expect("rules always serialize"));
+        tx.execute(
+            "INSERT INTO decisions
+                 (chat_id, destination, amount, source, outcome, rule, reason, decided_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                request.chat_id,
+                request.destination,
+                request.amount.normalize().to_string(),
+                request.source.as_str(),
+                verdict.outcome.as_str(),
+                rule,
+                verdict.reason,
+                now,
+            ],
+        )?;
+        let decision = Decision {
+            id: tx.last_insert_rowid(),
+            chat_id: request.chat_id.clone(),
+            destination: request.destination.clone(),
+            amount: request.amount.normalize(),
+            source: request.source,
+            outcome: verdict.outcome,
+            rule: verdict.rule,
+            reason: verdict.reason,
+            decided_at: now,
+            released: false,
+        };
+        tx.commit()?;
+        Ok(decision)
+    }
+
+    /// Stop counting an allowed payment that was never made towards its
+    /// chat's limits.
+    pub fn release(&self, id: i64) -> Result<Decision, PolicyError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let decision = find_decision(&tx, id)?;
+        if !decision.allowed() {
+            return Err(PolicyError::Invalid(
+                "Only allowed payments can be released".into(),
+            ));
+        }
+        tx.execute("UPDATE decisions SET released = 1 WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(Decision {
+            released: true,
+            ..decision
+        })
+    }
+
+    /// The chat's latest `limit` decisions, newest first.
+    pub fn decisions(&self, chat_id: &str, limit: u32) -> Result<Vec<Decision>, PolicyError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {DECISION_COLUMNS} FROM decisions
+             WHERE chat_id = ?1
+             ORDER BY decided_at DESC, id DESC
+             LIMIT ?2"
+        ))?;
+        let rows = stmt.query_map(params![chat_id, limit], decision)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+fn find_policy(conn: &Connection, chat_id: &str) -> Result<Option<(Policy, i64)>, PolicyError> {
+    Ok(conn
+        .query_row(
+            "SELECT rules, updated_at FROM policies WHERE chat_id = ?1",
+            [chat_id],
+            |row| Ok((json(row, 0)?, row.get(1)?)),
+        )
+        .optional()?
+        .map(|(rules, updated_at)| (Policy::new(rules), updated_at)))
+}
+
+fn find_decision(conn: &Connection, id: i64) -> Result<Decision, PolicyError> {
+    conn.query_row(
+        &format!("SELECT {DECISION_COLUMNS} FROM decisions WHERE id = ?1"),
+        [id],
+        decision,
+    )
+    .optional()?
+    .ok_or(PolicyError::NotFound(id))
+}
+
+/// What the chat has had allowed, and not released, in the windows of the
+/// daily and weekly limits ending at `now`.
+fn spent(conn: &Connection, chat_id: &str, now: i64) -> Result<Spent, PolicyError> {
+    let mut stmt = conn.prepare(
+        "SELECT amount, decided_at FROM decisions
+         WHERE chat_id = ?1 AND outcome = 'allow' AND released = 0 AND decided_at > ?2",
+    )?;
+    let rows = stmt.query_map(params![chat_id, now - WEEK_MS], |row| {
+        Ok((parsed::<Decimal>(row, 0)?, row.get::<_, i64>(1)?))
+    })?;
+    let mut spent = Spent::default();
+    for row in rows {
+        let (amount, decided_at) = row?;
+        // Saturating, so a total past `Decimal::MAX` still breaks every limit.
+        spent.week = spent.week.saturating_add(amount);
+        if decided_at > now - DAY_MS {
+            spent.day = spent.day.saturating_add(amount);
+        }
+    }
+    Ok(spent)
+}
+
+fn decision(row: &Row<'_>) -> rusqlite::Result<Decision> {
+    let rule: Option<String> = row.get(6)?;
+    Ok(Decision {
+        id: row.get(0)?,
+        chat_id: row.get(1)?,
+        destination: row.get(2)?,
+        amount: parsed(row, 3)?,
+        source: parsed(row, 4)?,
+        outcome: parsed::<Outcome>(row, 5)?,
+        rule: match rule {
+            Some(_) => Some(json(row, 6)?),
+            None => None,
+        },
+        reason: row.get(7)?,
+        decided_at: row.get(8)?,
+        released: row.get(9)?,
+    })
+}
+
+/// A text column holding an amount or one of the decision enums.
+fn parsed<T>(row: &Row<'_>, index: usize) -> rusqlite::Result<T>
+where
+    T: FromStr,
+    T::Err: ToString,
+{
+    let text: String = row.get(index)?;
+    text.parse().map_err(|err: T::Err| {
+        rusqlite::Error::FromSqlConversionFailure(index, Type::Text, err.to_string().into())
+    })
+}
+
+/// A text column holding JSON.
+fn json<T: serde::de::DeserializeOwned>(row: &Row<'_>, index: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+    serde_json::from_str(&text)
+        .map_err(|err| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, err.into()))
+}
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use stellrflow_policy::{api, PolicyStore};
+use tower::ServiceExt;
+
+const FRIEND: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+
+fn app() -> Router {
+    api::router(Arc::new(PolicyStore::open_in_memory().unwrap()))
+}
+
+async fn call(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn payments_are_checked_against_the_chats_policy() {
+    let app = app();
+
+    let (status, body) = call(
+        &app,
+        Method::PUT,
+        "/api/policy/42",
+        Some(json!({ "rules": [
+            { "type": "maxPerTransaction", "amount": 10 },
+            { "type": "requireMultisig", "above": "5" },
+        ] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body["rules"][0],
+        json!({ "type": "maxPerTransaction", "amount": "10" })
+    );
+    let (_, body) = call(&app, Method::GET, "/api/policy/42", None).await;
+    assert_eq!(body["rules"].as_array().unwrap().len(), 2);
+
+    // What the bot sends before signing.
+    let check = |amount: Value| {
+        json!({
+            "chatId": 42,
+            "destination": FRIEND,
+            "amount": amount,
+            "source": "workflow",
+        })
+    };
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/policy/check",
+        Some(check(json!(2.5))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["allowed"], true);
+    assert_eq!(body["decision"]["amount"], "2.5");
+    let id = body["decision"]["id"].as_i64().unwrap();
+
+    let (_, body) = call(
+        &app,
+        Method::POST,
+        "/api/policy/check",
+        Some(check(json!("7"))),
+    )
+    .await;
+    assert_eq!(body["allowed"], false);
+    assert_eq!(body["decision"]["outcome"], "requireMultisig");
+    let (_, body) = call(
+        &app,
+        Method::POST,
+        "/api/policy/check",
+        Some(check(json!(11))),
+    )
+    .await;
+    assert_eq!(body["decision"]["outcome"], "deny");
+    assert_eq!(body["decision"]["rule"]["type"], "maxPerTransaction");
+
+    let (_, body) = call(&app, Method::GET, "/api/policy/42/decisions?limit=2", None).await;
+    let outcomes: Vec<&Value> = body["decisions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| &d["outcome"])
+        .collect();
+    assert_eq!(outcomes, [&json!("deny"), &json!("requireMultisig")]);
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        &format!("/api/policy/decisions/{id}/release"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["decision"]["released"], true);
+
+    let (_, body) = call(&app, Method::DELETE, "/api/policy/42", None).await;
+    assert_eq!(
+        body,
+        json!({ "success": true, "message": "Policy removed" })
+    );
+}
+
+#[tokio::test]
+async fn errors_use_the_bots_envelope() {
+    let app = app();
+
+    let (status, body) = call(&app, Method::GET, "/api/policy/42", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body,
+        json!({ "success": false, "error": "No policy for this chat" })
+    );
+
+    let (status, body) = call(
+        &app,
+        Method::PUT,
+        "/api/policy/default",
+        Some(json!({ "rules": [{ "type": "weeklyLimit", "amount": "-1" }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "weeklyLimit: amount must be positive");
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/policy/check",
+        Some(json!({ "chatId": "42", "amount": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "chatId, destination, and amount are required"
+    );
+
+    let (status, body) = call(&app, Method::POST, "/api/policy/decisions/7/release", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Decision not found");
+}
@@ -0,0 +1,264 @@
+use rust_decimal::Decimal;
+use serde_json::json;
+use stellrflow_policy::{
+    Outcome, Policy, PolicyError, PolicyStore, Rule, Source, SpendRequest, Spent, DEFAULT_POLICY,
+};
+
+const CHAT: &str = "123456789";
+const FRIEND: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+const STRANGER: &str = "GAAZI4TCR3TY5OJHCTJC2A4QSY6CJWJH5IAJTGKIN2ER7LBNVKOCCWN7";
+/// 2023-11-14 22:13:20 UTC, a Tuesday.
+const T0: i64 = 1_700_000_000_000;
+const HOUR: i64 = 3_600_000;
+const DAY: i64 = 24 * HOUR;
+
+fn rules(value: serde_json::Value) -> Policy {
+    serde_json::from_value(json!({ "rules": value })).unwrap()
+}
+
+fn pay(destination: &str, amount: &str) -> SpendRequest {
+    SpendRequest {
+        chat_id: CHAT.into(),
+        destination: destination.into(),
+        amount: amount.parse().unwrap(),
+        source: Source::Manual,
+    }
+}
+
+#[test]
+fn limits_count_allowed_payments_in_rolling_windows() {
+    let store = PolicyStore::open_in_memory().unwrap();
+    store
+        .set_policy(
+            CHAT,
+            &rules(json!([
+                { "type": "maxPerTransaction", "amount": "50" },
+                { "type": "dailyLimit", "amount": 100 },
+                { "type": "weeklyLimit", "amount": "250" },
+            ])),
+            T0,
+        )
+        .unwrap();
+
+    let too_big = store.check(&pay(FRIEND, "60"), T0).unwrap();
+    assert_eq!(too_big.outcome, Outcome::Deny);
+    assert_eq!(too_big.rule.as_ref().unwrap().kind(), "maxPerTransaction");
+    assert_eq!(
+        too_big.reason,
+        "60 XLM is over the limit of 50 XLM per payment"
+    );
+
+    for _ in 0..2 {
+        assert!(store.check(&pay(FRIEND, "50"), T0).unwrap().allowed());
+    }
+    // Denied payments did not count; these two did.
+    let over = store.check(&pay(FRIEND, "0.5"), T0 + HOUR).unwrap();
+    assert_eq!(over.outcome, Outcome::Deny);
+    assert_eq!(
+        over.reason,
+        "0.5 XLM would bring the last 24 hours to 100.5 XLM, over the daily limit of 100 XLM"
+    );
+
+    // A day later the daily window has moved on, but the week has not.
+    for day in 1..=3 {
+        let decision = store.check(&pay(FRIEND, "50"), T0 + day * DAY).unwrap();
+        assert!(decision.allowed(), "day {day}");
+    }
+    let weekly = store.check(&pay(FRIEND, "50"), T0 + 3 * DAY).unwrap();
+    assert_eq!(weekly.rule.unwrap().kind(), "weeklyLimit");
+
+    // A payment the bot failed to make stops counting once released.
+    let latest = store.decisions(CHAT, 10).unwrap();
+    let allowed = latest.iter().find(|d| d.allowed()).unwrap();
+    assert!(store.release(allowed.id).unwrap().released);
+    assert!(store
+        .check(&pay(FRIEND, "50"), T0 + 3 * DAY)
+        .unwrap()
+        .allowed());
+    assert!(matches!(
+        store.release(weekly.id),
+        Err(PolicyError::Invalid(_))
+    ));
+    assert!(matches!(
+        store.release(999),
+        Err(PolicyError::NotFound(999))
+    ));
+}
+
+#[test]
+fn destinations_time_windows_and_multisig() {
+    let store = PolicyStore::open_in_memory().unwrap();
+    store
+        .set_policy(
+            DEFAULT_POLICY,
+            &rules(json!([{ "type": "denylist", "destinations": [STRANGER] }])),
+            T0,
+        )
+        .unwrap();
+    store
+        .set_policy(
+            CHAT,
+            &rules(json!([
+                { "type": "requireMultisig", "above": "100" },
+                { "type": "timeWindow", "start": "09:00", "end": "17:00",
+                  "days": ["mon", "tue", "wed", "thu", "fri"], "timezone": "Europe/Berlin" },
+            ])),
+            T0,
+        )
+        .unwrap();
+
+    // The default policy applies to every chat, including ones without
+    // their own.
+    let denied = store.check(&pay(STRANGER, "1"), T0).unwrap();
+    assert_eq!(denied.reason, "GAAZ…CWN7 is on the denylist");
+    let elsewhere = SpendRequest {
+        chat_id: "42".into(),
+        ..pay(STRANGER, "1")
+    };
+    assert_eq!(store.check(&elsewhere, T0).unwrap().outcome, Outcome::Deny);
+
+    // 22:13 UTC is 23:13 in Berlin.
+    let late = store.check(&pay(FRIEND, "1"), T0).unwrap();
+    assert_eq!(
+        late.reason,
+        "payments are only allowed 09:00-17:00 on mon, tue, wed, thu, fri Europe/Berlin"
+    );
+    let morning = T0 + 11 * HOUR; // Wednesday 10:13 in Berlin
+    assert!(store.check(&pay(FRIEND, "100"), morning).unwrap().allowed());
+    let saturday = morning + 3 * DAY;
+    assert_eq!(
+        store.check(&pay(FRIEND, "1"), saturday).unwrap().outcome,
+        Outcome::Deny
+    );
+
+    // Over the threshold needs approval, unless something denies it.
+    let big = store.check(&pay(FRIEND, "100.01"), morning).unwrap();
+    assert_eq!(big.outcome, Outcome::RequireMultisig);
+    assert_eq!(big.reason, "payments over 100 XLM need multisig approval");
+    assert!(!big.allowed());
+    let big_late = store.check(&pay(FRIEND, "500"), T0).unwrap();
+    assert_eq!(big_late.rule.unwrap().kind(), "timeWindow");
+
+    // Overnight windows wrap around midnight.
+    let night = Policy::new(vec![Rule::TimeWindow {
+        start: "22:00".into(),
+        end: "06:00".into(),
+        days: vec![],
+        timezone: None,
+    }]);
+    let spent = Default::default();
+    assert_eq!(
+        night.evaluate(&pay(FRIEND, "1"), &spent, T0).outcome,
+        Outcome::Allow
+    );
+    assert_eq!(
+        night
+            .evaluate(&pay(FRIEND, "1"), &spent, T0 + 8 * HOUR)
+            .outcome,
+        Outcome::Deny
+    );
+}
+
+#[test]
+fn allowlists_and_the_decision_log() {
+    let store = PolicyStore::open_in_memory().unwrap();
+    // No policy at all: everything is allowed, and still recorded.
+    let free = store.check(&pay(STRANGER, "1000"), T0).unwrap();
+    assert!(free.allowed());
+    assert_eq!(free.rule, None);
+    assert_eq!(free.reason, "within policy");
+
+    store
+        .set_policy(
+            CHAT,
+            &rules(json!([{ "type": "allowlist", "destinations": [FRIEND] }])),
+            T0,
+        )
+        .unwrap();
+    let request = SpendRequest {
+        source: Source::Autopay,
+        ..pay(STRANGER, "1")
+    };
+    let denied = store.check(&request, T0 + 1).unwrap();
+    assert_eq!(denied.reason, "GAAZ…CWN7 is not on the allowlist");
+    assert!(store.check(&pay(FRIEND, "1"), T0 + 2).unwrap().allowed());
+
+    let log = store.decisions(CHAT, 10).unwrap();
+    assert_eq!(log.len(), 3);
+    assert_eq!(log[1], denied);
+    assert_eq!(log[1].source, Source::Autopay);
+    assert_eq!(
+        serde_json::to_value(&log[1]).unwrap(),
+        json!({
+            "id": denied.id,
+            "chatId": CHAT,
+            "destination": STRANGER,
+            "amount": "1",
+            "source": "autopay",
+            "outcome": "deny",
+            "rule": { "type": "allowlist", "destinations": [FRIEND] },
+            "reason": "GAAZ…CWN7 is not on the allowlist",
+            "decidedAt": T0 + 1,
+            "released": false,
+        })
+    );
+    assert_eq!(store.decisions(CHAT, 1).unwrap()[0].destination, FRIEND);
+
+    store.remove_policy(CHAT).unwrap();
+    assert!(matches!(store.policy(CHAT), Err(PolicyError::NoPolicy(_))));
+    assert_eq!(store.decisions(CHAT, 10).unwrap().len(), 3);
+}
+
+#[test]
+fn malformed_rules_and_requests_are_rejected() {
+    let store = PolicyStore::open_in_memory().unwrap();
+    for (rule, error) in [
+        (
+            json!({ "type": "dailyLimit", "amount": 0 }),
+            "dailyLimit: amount must be positive",
+        ),
+        (
+            json!({ "type": "denylist", "destinations": ["GNOTANACCOUNT"] }),
+            "denylist: `GNOTANACCOUNT` is not a Stellar account",
+        ),
+        (
+            json!({ "type": "timeWindow", "start": "9am", "end": "17:00" }),
+            "timeWindow: `9am` is not a time (HH:MM)",
+        ),
+        (
+            json!({ "type": "timeWindow", "start": "09:00", "end": "17:00", "timezone": "Mars/Base" }),
+            "timeWindow: unknown timezone `Mars/Base`",
+        ),
+    ] {
+        let err = store
+            .set_policy(CHAT, &rules(json!([rule])), T0)
+            .unwrap_err();
+        assert_eq!(err.to_string(), error);
+    }
+
+    let err = store.check(&pay(FRIEND, "0"), T0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "chatId, destination, and amount are required"
+    );
+    // Anything a payment cannot carry: fractions of a stroop, or more
+    // stroops than an i64 holds.
+    for amount in ["-1", "0.00000001", "79228162514264337593543950335"] {
+        let err = store.check(&pay(FRIEND, amount), T0).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid amount", "{amount}");
+    }
+
+    // Totals past what a Decimal holds break the limits rather than panic.
+    let limits = Policy::new(vec![Rule::DailyLimit {
+        amount: "100".parse().unwrap(),
+    }]);
+    let spent = Spent {
+        day: Decimal::MAX,
+        week: Decimal::MAX,
+    };
+    let verdict = limits.evaluate(&pay(FRIEND, "1"), &spent, T0);
+    assert_eq!(verdict.outcome, Outcome::Deny);
+    assert!(verdict
+        .reason
+        .contains("to more than 79228162514264337593543950335 XLM"));
+}
@@ -0,0 +1,217 @@
+//! Five-field cron expressions: `minute hour day-of-month month
+//! day-of-week`.
+//!
+//! Fields take `*`, numbers, ranges (`1-5`), steps (`*/15`, `10-40/10`),
+//! lists (`1,15`) and, for months and weekdays, three-letter names
+//! (`JAN`, `MON-FRI`). Sunday is `0` or `7`. As in Vixie cron, a day
+//! matches when either day field does if both are restricted. The
+//! `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` shorthands are
+//! accepted, and a `CRON_TZ=Europe/Berlin` prefix sets the zone (UTC
+//! otherwise).
+
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeDelta};
+use chrono_tz::Tz;
+
+use crate::tz;
+
+/// How many days ahead to search; eight years covers every leap day.
+const HORIZON_DAYS: u32 = 8 * 366;
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A parsed cron expression. Each field is a bit set of the values it
+/// allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    pub(crate) minutes: u64,
+    pub(crate) hours: u32,
+    /// Bit 1 to 31.
+    pub(crate) days: u32,
+    /// Bit 1 to 12.
+    pub(crate) months: u16,
+    /// Bit 0 (Sunday) to 6.
+    pub(crate) weekdays: u8,
+    /// Whether the day-of-month and day-of-week fields were restricted,
+    /// rather than `*`.
+    pub(crate) days_restricted: bool,
+    pub(crate) weekdays_restricted: bool,
+    pub(crate) tz: Tz,
+}
+
+/// Whether `input` is written as cron: a shorthand, or five fields whose
+/// first is numeric.
+pub(crate) fn looks_like(input: &str) -> bool {
+    let body = strip_tz(input).map_or(input, |(_, body)| body);
+    if body.starts_with('@') {
+        return true;
+    }
+    let fields: Vec<&str> = body.split_whitespace().collect();
+    fields.len() == 5
+        && fields[0]
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"*,-/".contains(&b))
+}
+
+fn strip_tz(input: &str) -> Option<(&str, &str)> {
+    let upper = input.get(..8)?.to_ascii_uppercase();
+    let rest = if upper.starts_with("CRON_TZ=") {
+        &input[8..]
+    } else if upper.starts_with("TZ=") {
+        &input[3..]
+    } else {
+        return None;
+    };
+    let (zone, body) = rest.split_once(char::is_whitespace)?;
+    Some((zone, body.trim_start()))
+}
+
+impl Cron {
+    pub(crate) fn parse(input: &str) -> Result<Self, String> {
+        let (tz, body) = match strip_tz(input) {
+            Some((zone, body)) => (tz::parse_tz(zone)?, body),
+            None => (Tz::UTC, input),
+        };
+        let expanded = match body.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other if other.starts_with('@') => {
+                return Err(format!(
+                    "unknown shorthand `{body}` (expected @hourly, @daily, @weekly, @monthly or @yearly)"
+                ))
+            }
+            _ => body,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+
+        let weekdays = field("day-of-week", weekday, 0, 7, &WEEKDAYS, 0)?;
+        let cron = Self {
+            minutes: field("minute", minute, 0, 59, &[], 0)?,
+            hours: field("hour", hour, 0, 23, &[], 0)? as u32,
+            days: field("day-of-month", day, 1, 31, &[], 1)? as u32,
+            months: field("month", month, 1, 12, &MONTHS, 1)? as u16,
+            // 7 is Sunday too.
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            days_restricted: !day.starts_with(['*', '?']),
+            weekdays_restricted: !weekday.starts_with(['*', '?']),
+            tz,
+        };
+        if cron.next_after(0).is_none() {
+            return Err(format!("`{body}` never matches a date"));
+        }
+        Ok(cron)
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// The first match later than `after` (Unix ms).
+    pub(crate) fn next_after(&self, after: i64) -> Option<i64> {
+        let from = tz::local(self.tz, after)?;
+        // No offset change is this large, so earlier local times are
+        // certainly not later than `after`.
+        let earliest = from - TimeDelta::hours(3);
+        for day in from.date().iter_days().take(HORIZON_DAYS as usize) {
+            if !self.day_matches(day) {
+                continue;
+            }
+            for hour in bits(u64::from(self.hours)) {
+                for minute in bits(self.minutes) {
+                    let local = day.and_time(NaiveTime::from_hms_opt(hour, minute, 0)?);
+                    if local < earliest {
+                        continue;
+                    }
+                    let at = tz::resolve(self.tz, local);
+                    if at > after {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, day: NaiveDate) -> bool {
+        if self.months & (1 << day.month()) == 0 {
+            return false;
+        }
+        let by_day = self.days & (1 << day.day()) != 0;
+        let by_weekday = self.weekdays & (1 << day.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => by_day || by_weekday,
+            _ => by_day && by_weekday,
+        }
+    }
+}
+
+/// The set bits of `set`, lowest first.
+fn bits(set: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |bit| set & (1 << bit) != 0)
+}
+
+/// One field as a bit set. `names[i]` stands for `first_name + i`.
+fn field(
+    name: &str,
+    text: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name: u32,
+) -> Result<u64, String> {
+    let value = |item: &str| -> Result<u32, String> {
+        let upper = item.to_ascii_uppercase();
+        if let Some(i) = names.iter().position(|n| *n == upper) {
+            return Ok(first_name + i as u32);
+        }
+        item.parse::<u32>()
+            .ok()
+            .filter(|n| (min..=max).contains(n))
+            .ok_or_else(|| format!("{name} `{item}` is not {min} to {max}"))
+    };
+
+    let mut set = 0u64;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("{name} step `{step}` must be a positive number"))?;
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (low, high) = match range {
+            "*" | "?" => (min, max),
+            _ => match range.split_once('-') {
+                Some((low, high)) => (value(low)?, value(high)?),
+                // `5/15` runs from 5 to the end.
+                None if step > 1 || item.contains('/') => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if low > high {
+            return Err(format!("{name} range `{range}` runs backwards"));
+        }
+        for v in (low..=high).step_by(step as usize) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+/// Why a schedule string could not be read. The message is meant for the
+/// person who wrote it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{0}")]
+pub struct ParseError(pub String);
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        Self(message)
+    }
+}
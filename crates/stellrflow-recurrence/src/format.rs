@@ -0,0 +1,445 @@
+//! Writing schedules back out without losing anything: `1h 30m` rather
+//! than a rounded `2h`, and `every Monday at 09:00 UTC` for a rule. Every
+//! text written here parses back to the same occurrences.
+
+use std::fmt::{self, Write};
+
+use chrono::{NaiveTime, Timelike, Weekday};
+use chrono_tz::Tz;
+
+use crate::cron::Cron;
+use crate::keyword;
+use crate::rule::{Freq, Rule, WeekdayNum};
+use crate::span::{Span, DAY_MS, HOUR_MS, MINUTE_MS, SECOND_MS, WEEK_MS};
+
+/// Largest first; `m` is minutes and `mo` months.
+const MILLI_UNITS: [(&str, u64); 6] = [
+    ("w", WEEK_MS),
+    ("d", DAY_MS),
+    ("h", HOUR_MS),
+    ("m", MINUTE_MS),
+    ("s", SECOND_MS),
+    ("ms", 1),
+];
+
+const WEEKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+const WEEKEND: [Weekday; 2] = [Weekday::Sat, Weekday::Sun];
+
+/// At most this many times of day are spelled out for a cron expression;
+/// `*/15 * * * *` stays as it is.
+const MAX_CRON_TIMES: usize = 4;
+
+impl fmt::Display for Span {
+    /// Every unit that is used, largest first: `1h 30m`, `1y 2mo 3d`,
+    /// `1s 500ms`. Parses back with [`Span::parse`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years > 0 {
+            parts.push(format!("{years}y"));
+        }
+        if months > 0 {
+            parts.push(format!("{months}mo"));
+        }
+        let mut rest = self.millis;
+        for (name, unit) in MILLI_UNITS {
+            if rest >= unit {
+                parts.push(format!("{}{name}", rest / unit));
+                rest %= unit;
+            }
+        }
+        if parts.is_empty() {
+            return f.write_str("0ms");
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+impl Span {
+    /// `every day`, `every month`, `every 1h 30m`.
+    pub(crate) fn describe(self) -> String {
+        let word = match (self.months, self.millis) {
+            (0, HOUR_MS) => "hour",
+            (0, DAY_MS) => "day",
+            (0, WEEK_MS) => "week",
+            (1, 0) => "month",
+            (12, 0) => "year",
+            _ => return format!("every {self}"),
+        };
+        format!("every {word}")
+    }
+}
+
+impl Rule {
+    /// The rule as a phrase, or as RRULE text when no phrase says the same.
+    pub(crate) fn describe(&self) -> String {
+        self.phrase().unwrap_or_else(|| self.to_rrule())
+    }
+
+    /// `RRULE:FREQ=…`, after a `DTSTART` line when the rule has a start.
+    pub fn to_rrule(&self) -> String {
+        let mut out = String::new();
+        if let Some(dtstart) = self.dtstart {
+            let at = dtstart.format("%Y%m%dT%H%M%S");
+            match self.tz {
+                Tz::UTC => writeln!(out, "DTSTART:{at}Z"),
+                tz => writeln!(out, "DTSTART;TZID={}:{at}", tz.name()),
+            }
+            .ok();
+        }
+        let freq = match self.freq {
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+            Freq::Yearly => "YEARLY",
+        };
+        out.push_str("RRULE:FREQ=");
+        out.push_str(freq);
+        let mut part = |key: &str, value: String| {
+            if !value.is_empty() {
+                write!(out, ";{key}={value}").ok();
+            }
+        };
+        if self.interval != 1 {
+            part("INTERVAL", self.interval.to_string());
+        }
+        if let Some(count) = self.count {
+            part("COUNT", count.to_string());
+        }
+        if let Some(until) = self.until.and_then(chrono::DateTime::from_timestamp_millis) {
+            part("UNTIL", until.format("%Y%m%dT%H%M%SZ").to_string());
+        }
+        part("BYMONTH", join(&self.by_month));
+        part("BYMONTHDAY", join(&self.by_month_day));
+        part(
+            "BYDAY",
+            self.by_day
+                .iter()
+                .map(|w| match w.nth {
+                    0 => weekday_code(w.weekday).to_string(),
+                    n => format!("{n}{}", weekday_code(w.weekday)),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        let (hours, minutes, seconds) = self.time_parts();
+        part("BYHOUR", join(&hours));
+        part("BYMINUTE", join(&minutes));
+        part("BYSECOND", join(&seconds));
+        part("BYSETPOS", join(&self.by_set_pos));
+        if self.week_start != Weekday::Mon {
+            part("WKST", weekday_code(self.week_start).to_string());
+        }
+        if self.dtstart.is_none() && self.tz != Tz::UTC {
+            part("TZID", self.tz.name().to_string());
+        }
+        out
+    }
+
+    /// `BYHOUR`, `BYMINUTE` and `BYSECOND`, taken from `at` for phrases.
+    fn time_parts(&self) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+        if self.at.is_empty() {
+            return (
+                self.by_hour.clone(),
+                self.by_minute.clone(),
+                self.by_second.clone(),
+            );
+        }
+        let set = |part: fn(&NaiveTime) -> u32| {
+            let mut values: Vec<u32> = self.at.iter().map(part).collect();
+            values.sort();
+            values.dedup();
+            values
+        };
+        (
+            set(NaiveTime::hour),
+            set(NaiveTime::minute),
+            set(NaiveTime::second),
+        )
+    }
+
+    /// The phrase that parses back to this rule, if there is one.
+    fn phrase(&self) -> Option<String> {
+        if self.dtstart.is_some()
+            || self.count.is_some()
+            || self.until.is_some()
+            || !self.by_month.is_empty()
+            || !self.by_set_pos.is_empty()
+            || (self.week_start != Weekday::Mon && self.interval > 1)
+        {
+            return None;
+        }
+        let times = self.phrase_times()?;
+        let n = self.interval;
+        let mut out = match self.freq {
+            Freq::Daily => {
+                if !self.by_day.is_empty() || !self.by_month_day.is_empty() {
+                    return None;
+                }
+                match n {
+                    1 => "every day".to_string(),
+                    n => format!("every {n} days"),
+                }
+            }
+            Freq::Weekly => {
+                if self.by_day.iter().any(|w| w.nth != 0) {
+                    return None;
+                }
+                let days = weekday_list(&self.by_day);
+                match (n, days) {
+                    (1, None) => "every week".to_string(),
+                    (1, Some(days)) if days.starts_with("week") => days,
+                    (1, Some(days)) => format!("every {days}"),
+                    (2, None) => "every other week".to_string(),
+                    (2, Some(days)) => format!("every other week on {days}"),
+                    (n, None) => format!("every {n} weeks"),
+                    (n, Some(days)) => format!("every {n} weeks on {days}"),
+                }
+            }
+            Freq::Monthly => {
+                let days = self.phrase_month_days()?;
+                match (n, days) {
+                    (1, None) => "every month".to_string(),
+                    (1, Some(days)) => format!("the {days} of every month"),
+                    (n, None) => format!("every {n} months"),
+                    (n, Some(days)) => format!("every {n} months on the {days}"),
+                }
+            }
+            Freq::Yearly => return None,
+        };
+        if !times.is_empty() {
+            out.push_str(" at ");
+            out.push_str(&and_list(times.iter().map(|t| clock(*t)).collect()));
+        }
+        match self.tz {
+            // `every 2 months` alone would read back as a duration, which
+            // clamps to short months instead of skipping them.
+            Tz::UTC
+                if times.is_empty() && Span::parse(&out).is_err() && keyword(&out).is_none() => {}
+            Tz::UTC => out.push_str(" UTC"),
+            tz => write!(out, " in {}", tz.name()).ok()?,
+        }
+        Some(out)
+    }
+
+    /// The times of day a phrase would name: empty when the rule keeps the
+    /// start's, `None` when only part of the time is fixed.
+    fn phrase_times(&self) -> Option<Vec<NaiveTime>> {
+        if !self.at.is_empty() {
+            let mut at = self.at.clone();
+            at.sort();
+            at.dedup();
+            return Some(at);
+        }
+        match (
+            self.by_hour.is_empty(),
+            self.by_minute.is_empty(),
+            self.by_second.is_empty(),
+        ) {
+            (true, true, true) => Some(Vec::new()),
+            (false, false, false) => {
+                let mut times = Vec::new();
+                for h in &self.by_hour {
+                    for m in &self.by_minute {
+                        for s in &self.by_second {
+                            times.push(NaiveTime::from_hms_opt(*h, *m, *s)?);
+                        }
+                    }
+                }
+                times.sort();
+                times.dedup();
+                Some(times)
+            }
+            _ => None,
+        }
+    }
+
+    /// `1st and 15th`, `last day` or `last Friday`; `Some(None)` for the
+    /// start's day of the month.
+    fn phrase_month_days(&self) -> Option<Option<String>> {
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true, true) => Some(None),
+            (false, true) => {
+                let mut days = self.by_month_day.clone();
+                days.sort_by_key(|d| if *d < 0 { 32 - d } else { *d });
+                let names = days
+                    .iter()
+                    .map(|d| match d {
+                        -1 => Some("last day".to_string()),
+                        d if *d > 0 => Some(ordinal(*d)),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Some(and_list(names)))
+            }
+            (true, false) => {
+                let names = self
+                    .by_day
+                    .iter()
+                    .map(|w| {
+                        let nth = match w.nth {
+                            1 => "first",
+                            2 => "second",
+                            3 => "third",
+                            4 => "fourth",
+                            5 => "fifth",
+                            -1 => "last",
+                            _ => return None,
+                        };
+                        Some(format!("{nth} {}", weekday_name(w.weekday)))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Some(and_list(names)))
+            }
+            (false, false) => None,
+        }
+    }
+}
+
+impl Cron {
+    /// The expression as a phrase, when one says the same.
+    pub(crate) fn describe(&self) -> Option<String> {
+        self.to_rule()?.phrase()
+    }
+
+    /// The phrase rule this expression matches the same times as, when it
+    /// names a few times of day in every month.
+    fn to_rule(&self) -> Option<Rule> {
+        const ALL_MONTHS: u16 = 0b1_1111_1111_1110;
+        if self.months != ALL_MONTHS {
+            return None;
+        }
+        let mut at = Vec::new();
+        for h in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+            for m in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                if at.len() == MAX_CRON_TIMES {
+                    return None;
+                }
+                at.push(NaiveTime::from_hms_opt(h, m, 0)?);
+            }
+        }
+        let weekdays = |restricted: bool| {
+            [
+                Weekday::Sun,
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+            ]
+            .into_iter()
+            .filter(move |w| !restricted || self.weekdays & (1 << w.num_days_from_sunday()) != 0)
+            .map(|weekday| WeekdayNum { nth: 0, weekday })
+        };
+        let mut rule = match (self.days_restricted, self.weekdays_restricted) {
+            (false, false) => Rule::new(Freq::Daily),
+            (false, true) => {
+                let mut rule = Rule::new(Freq::Weekly);
+                rule.by_day = weekdays(true).collect();
+                rule.by_day
+                    .sort_by_key(|w| w.weekday.num_days_from_monday());
+                rule
+            }
+            (true, false) => {
+                let mut rule = Rule::new(Freq::Monthly);
+                rule.by_month_day = (1..=31).filter(|d| self.days & (1 << d) != 0).collect();
+                rule
+            }
+            (true, true) => return None,
+        };
+        rule.at = at;
+        rule.tz = self.tz;
+        Some(rule)
+    }
+}
+
+/// `Monday, Wednesday and Friday`, `weekdays`, `weekends`; `None` for no
+/// days.
+fn weekday_list(days: &[WeekdayNum]) -> Option<String> {
+    let mut days: Vec<Weekday> = days.iter().map(|w| w.weekday).collect();
+    days.sort_by_key(|d| d.num_days_from_monday());
+    days.dedup();
+    if days.is_empty() {
+        return None;
+    }
+    if days == WEEKDAYS {
+        return Some("weekdays".into());
+    }
+    if days == WEEKEND {
+        return Some("weekends".into());
+    }
+    Some(and_list(
+        days.into_iter()
+            .map(|d| weekday_name(d).to_string())
+            .collect(),
+    ))
+}
+
+/// `a`, `a and b`, `a, b and c`.
+fn and_list(mut items: Vec<String>) -> String {
+    let Some(last) = items.pop() else {
+        return String::new();
+    };
+    if items.is_empty() {
+        return last;
+    }
+    format!("{} and {last}", items.join(", "))
+}
+
+fn ordinal(n: i32) -> String {
+    let suffix = match (n % 100, n % 10) {
+        (11..=13, _) => "th",
+        (_, 1) => "st",
+        (_, 2) => "nd",
+        (_, 3) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// `09:00`, or `09:00:30` when the seconds matter.
+fn clock(time: NaiveTime) -> String {
+    match time.second() {
+        0 => time.format("%H:%M").to_string(),
+        _ => time.format("%H:%M:%S").to_string(),
+    }
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
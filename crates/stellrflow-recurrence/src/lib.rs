@@ -0,0 +1,211 @@
+//! When recurring StellrFlow jobs run.
+//!
+//! A [`Recurrence`] is parsed from one of:
+//!
+//! ```text
+//! 1h, 90 min, 3600000            the bot's `parseIntervalFormat` grammar
+//! 1d12h, 1h 30m, every 2mo 1w    compound durations (`mo` and `y` are calendar months)
+//! daily, weekly, monthly         the AutoPay keywords
+//! weekdays at 09:00 UTC          English calendar phrases
+//! every 1st of month
+//! last friday of the month at 17:00 in Europe/Berlin
+//! 0 9 * * MON-FRI                five-field cron, optionally `CRON_TZ=…`
+//! FREQ=MONTHLY;BYDAY=-1FR        RFC 5545 RRULE, optionally after a DTSTART line
+//! ```
+//!
+//! Occurrences are counted from the moment a schedule starts and never
+//! include it. Durations step from the start (months clamp to shorter
+//! months: Jan 31, Feb 29, Mar 31). Phrases and rules without a time of
+//! day keep the start's, and are evaluated in their time zone, so `at
+//! 09:00 in Europe/Berlin` stays at 09:00 across daylight saving changes.
+//!
+//! Everything is computed from the start and the rule alone, so the same
+//! inputs always give the same occurrences.
+//!
+//! [`Recurrence::describe`] writes a schedule back out in words that parse
+//! to the same occurrences, and [`preview`] lists a payment schedule's
+//! first dates and what it commits to in total.
+
+mod cron;
+mod error;
+mod format;
+mod phrase;
+mod preview;
+mod rrule;
+mod rule;
+mod span;
+mod tz;
+
+pub use cron::Cron;
+pub use error::ParseError;
+pub use preview::{preview, Preview, Schedule};
+pub use rule::{Freq, Rule, WeekdayNum};
+pub use span::{parse_interval_ms, Span};
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// A parsed schedule, keeping the text it was written as.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recurrence {
+    source: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// A fixed step from the start.
+    Every(Span),
+    /// A calendar rule, from an RRULE or a phrase.
+    Rule(Box<Rule>),
+    Cron(Cron),
+}
+
+impl Recurrence {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let text = source.trim();
+        if text.is_empty() {
+            return Err(ParseError("interval is empty".into()));
+        }
+        let kind = if text.to_ascii_lowercase().contains("freq=") {
+            Kind::Rule(Box::new(rrule::parse(text)?))
+        } else if cron::looks_like(text) {
+            Kind::Cron(Cron::parse(text)?)
+        } else if let Some(span) = keyword(text) {
+            Kind::Every(span)
+        } else {
+            match Span::parse(source) {
+                Ok(span) if span.is_zero() => {
+                    return Err(ParseError(format!("interval `{text}` is zero")))
+                }
+                Ok(span) => Kind::Every(span),
+                Err(span_error) => match phrase::parse(text) {
+                    Ok(rule) => Kind::Rule(Box::new(rule)),
+                    // `5x` was meant as a duration, `every fooday` as a phrase.
+                    Err(_) if text.starts_with(|c: char| c.is_ascii_digit()) => {
+                        return Err(ParseError(span_error))
+                    }
+                    Err(phrase_error) => return Err(ParseError(phrase_error)),
+                },
+            }
+        };
+        if let Kind::Rule(rule) = &kind {
+            if rule.next_after(0, 0).is_none() {
+                return Err(ParseError(format!("`{text}` never matches a date")));
+            }
+        }
+        Ok(Self {
+            source: text.to_string(),
+            kind,
+        })
+    }
+
+    /// The text this was parsed from, trimmed.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    /// The first occurrence later than `after` of a schedule that started
+    /// at `start`, both Unix ms. `None` when there are no more.
+    pub fn next_after(&self, start: i64, after: i64) -> Option<i64> {
+        match &self.kind {
+            Kind::Every(span) => span.next_after(start, after),
+            Kind::Rule(rule) => rule.next_after(start, after),
+            Kind::Cron(cron) => cron.next_after(after.max(start)),
+        }
+    }
+
+    /// The schedule in words, e.g. `every 1h 30m` or `every Monday at
+    /// 09:00 UTC`. Parsing it gives the same occurrences.
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            Kind::Every(span) => span.describe(),
+            Kind::Rule(rule) => rule.describe(),
+            Kind::Cron(cron) => cron.describe().unwrap_or_else(|| self.source.clone()),
+        }
+    }
+
+    /// How many occurrences of a schedule that started at `start` fall at
+    /// or before `end`.
+    pub fn count_between(&self, start: i64, end: i64) -> u64 {
+        if let Kind::Every(span) = &self.kind {
+            if let Some(ms) = span.fixed_ms().filter(|ms| *ms > 0) {
+                return u64::try_from(end.saturating_sub(start)).map_or(0, |gap| gap / ms);
+            }
+        }
+        self.occurrences(start).take_while(|at| *at <= end).count() as u64
+    }
+
+    /// Every occurrence of a schedule that started at `start`, in order.
+    pub fn occurrences(&self, start: i64) -> impl Iterator<Item = i64> + '_ {
+        let mut last = start;
+        std::iter::from_fn(move || {
+            last = self.next_after(start, last)?;
+            Some(last)
+        })
+    }
+
+    /// Up to `n` occurrences later than `after`.
+    pub fn upcoming(&self, start: i64, after: i64, n: usize) -> Vec<i64> {
+        let mut last = after;
+        std::iter::from_fn(|| {
+            last = self.next_after(start, last)?;
+            Some(last)
+        })
+        .take(n)
+        .collect()
+    }
+}
+
+/// The single-word cadences AutoPay has always accepted, and their
+/// `every …` forms.
+pub(crate) fn keyword(text: &str) -> Option<Span> {
+    let lower = text.to_ascii_lowercase();
+    let word = lower
+        .strip_prefix("every ")
+        .map(str::trim_start)
+        .unwrap_or(&lower);
+    match word {
+        "hourly" | "hour" => Some(Span::millis(span::HOUR_MS)),
+        "daily" | "day" => Some(Span::millis(span::DAY_MS)),
+        "weekly" | "week" => Some(Span::millis(span::WEEK_MS)),
+        "monthly" | "month" => Some(Span::months(1)),
+        "yearly" | "annually" | "year" => Some(Span::months(12)),
+        _ => None,
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = ParseError;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        source.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.source
+    }
+}
@@ -0,0 +1,350 @@
+//! English schedules: `weekdays at 09:00 UTC`, `every 1st of month`,
+//! `every other week on Friday at 5pm in Europe/Berlin`,
+//! `last friday of the month at 17:00`.
+//!
+//! The shape is `[every] [N days|weeks|months [on]] <days> [at <times>]
+//! [[in] <zone>]`. Without `at`, payments keep the time of day the schedule
+//! was created at. The zone defaults to UTC.
+
+use chrono::{NaiveTime, Weekday};
+
+use crate::rule::{Freq, Rule, WeekdayNum};
+use crate::tz::parse_tz;
+
+const WEEKDAY_NAMES: &[(&str, Weekday)] = &[
+    ("mon", Weekday::Mon),
+    ("tue", Weekday::Tue),
+    ("tues", Weekday::Tue),
+    ("wed", Weekday::Wed),
+    ("thu", Weekday::Thu),
+    ("thur", Weekday::Thu),
+    ("thurs", Weekday::Thu),
+    ("fri", Weekday::Fri),
+    ("sat", Weekday::Sat),
+    ("sun", Weekday::Sun),
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+];
+
+const ORDINAL_WORDS: &[(&str, i32)] = &[
+    ("first", 1),
+    ("second", 2),
+    ("third", 3),
+    ("fourth", 4),
+    ("fifth", 5),
+    ("last", -1),
+];
+
+pub(crate) fn parse(input: &str) -> Result<Rule, String> {
+    let lower = input.to_lowercase();
+    let tokens: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut p = Parser { tokens, pos: 0 };
+
+    let _ = p.eat("every") || p.eat("each");
+    let mut interval = 1;
+    if p.eat("other") {
+        interval = 2;
+    } else if let Some(n) = p.peek().and_then(|t| t.parse::<u32>().ok()) {
+        if p.peek_at(1).is_some_and(|t| unit(t).is_some()) {
+            interval = n.max(1);
+            p.pos += 1;
+        }
+    }
+    let mut freq = p.peek().and_then(unit);
+    if freq.is_some() {
+        p.pos += 1;
+        let _ = p.eat("on");
+    }
+
+    let mut rule = match freq {
+        Some(Freq::Daily) => Rule::new(Freq::Daily),
+        _ => match p.days()? {
+            Some(days) => {
+                if let Some(cadence) = freq.filter(|f| *f != days.freq) {
+                    return Err(format!(
+                        "`{input}` mixes a {} cadence with {} days",
+                        name(cadence),
+                        name(days.freq)
+                    ));
+                }
+                freq = Some(days.freq);
+                days
+            }
+            None => Rule::new(freq.ok_or_else(|| {
+                format!(
+                    "`{input}` is not an interval such as `1h 30m`, a phrase such as \
+                     `weekdays at 09:00 UTC`, a cron expression or an RRULE"
+                )
+            })?),
+        },
+    };
+    rule.interval = interval;
+    rule.freq = freq.unwrap_or(rule.freq);
+
+    if p.eat("at") {
+        rule.at = p.times()?;
+    }
+    if let Some(zone) = p.zone()? {
+        rule.tz = zone;
+    }
+    match p.peek() {
+        Some(extra) => Err(format!("unexpected `{extra}` in `{input}`")),
+        None => Ok(rule),
+    }
+}
+
+fn name(freq: Freq) -> &'static str {
+    match freq {
+        Freq::Daily => "daily",
+        Freq::Weekly => "weekly",
+        Freq::Monthly => "monthly",
+        Freq::Yearly => "yearly",
+    }
+}
+
+fn unit(token: &str) -> Option<Freq> {
+    match token {
+        "day" | "days" => Some(Freq::Daily),
+        "week" | "weeks" => Some(Freq::Weekly),
+        "month" | "months" => Some(Freq::Monthly),
+        _ => None,
+    }
+}
+
+fn weekday(token: &str) -> Option<Weekday> {
+    let token = match token.strip_suffix('s') {
+        Some(singular) if singular.ends_with("day") => singular,
+        _ => token,
+    };
+    WEEKDAY_NAMES
+        .iter()
+        .find(|(name, _)| *name == token)
+        .map(|(_, day)| *day)
+}
+
+/// `1st`, `22nd`, `first`, `last`.
+fn ordinal(token: &str) -> Option<i32> {
+    if let Some((_, n)) = ORDINAL_WORDS.iter().find(|(word, _)| *word == token) {
+        return Some(*n);
+    }
+    let digits = token.len().checked_sub(2)?;
+    let (number, suffix) = token.split_at_checked(digits)?;
+    let n: i32 = number.parse().ok()?;
+    let expected = match (n % 100, n % 10) {
+        (11..=13, _) => "th",
+        (_, 1) => "st",
+        (_, 2) => "nd",
+        (_, 3) => "rd",
+        _ => "th",
+    };
+    (suffix == expected && (1..=31).contains(&n)).then_some(n)
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<&'a str> {
+        self.tokens.get(self.pos + ahead).copied()
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        let found = self.peek() == Some(word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// The day part, or `None` when the next token starts something else.
+    fn days(&mut self) -> Result<Option<Rule>, String> {
+        let Some(token) = self.peek() else {
+            return Ok(None);
+        };
+        let weekly = |days: &[Weekday]| {
+            let mut rule = Rule::new(Freq::Weekly);
+            rule.by_day = days
+                .iter()
+                .map(|&weekday| WeekdayNum { nth: 0, weekday })
+                .collect();
+            rule
+        };
+        match token {
+            "day" | "daily" => {
+                self.pos += 1;
+                return Ok(Some(Rule::new(Freq::Daily)));
+            }
+            "weekday" | "weekdays" => {
+                self.pos += 1;
+                return Ok(Some(weekly(&[
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri,
+                ])));
+            }
+            "weekend" | "weekends" => {
+                self.pos += 1;
+                return Ok(Some(weekly(&[Weekday::Sat, Weekday::Sun])));
+            }
+            _ => {}
+        }
+
+        if weekday(token).is_some() {
+            let mut days = Vec::new();
+            while let Some(day) = self.peek().and_then(weekday) {
+                days.push(day);
+                self.pos += 1;
+                if self.peek() == Some("and") && self.peek_at(1).and_then(weekday).is_some() {
+                    self.pos += 1;
+                }
+            }
+            return Ok(Some(weekly(&days)));
+        }
+
+        let start = self.pos;
+        let _ = self.eat("the");
+        if self.peek().and_then(ordinal).is_none() {
+            self.pos = start;
+            return Ok(None);
+        }
+        let mut rule = Rule::new(Freq::Monthly);
+        loop {
+            let token = self.peek().unwrap_or_default();
+            let n = ordinal(token)
+                .ok_or_else(|| format!("expected a day such as `1st`, got `{token}`"))?;
+            self.pos += 1;
+            if let Some(day) = self.peek().and_then(weekday) {
+                if !(-5..=5).contains(&n) {
+                    return Err(format!("there is no `{token}` {day} in a month"));
+                }
+                rule.by_day.push(WeekdayNum {
+                    nth: n,
+                    weekday: day,
+                });
+                self.pos += 1;
+            } else {
+                let _ = self.eat("day");
+                rule.by_month_day.push(n);
+            }
+            if !(self.eat("and") || self.peek().and_then(ordinal).is_some()) {
+                break;
+            }
+            let _ = self.eat("the");
+        }
+        if !rule.by_day.is_empty() && !rule.by_month_day.is_empty() {
+            return Err(
+                "mix month days such as `1st` or weekdays such as `last friday`, not both".into(),
+            );
+        }
+        if self.eat("of") {
+            let _ = self.eat("the") || self.eat("every") || self.eat("each");
+            if !(self.eat("month") || self.eat("months")) {
+                return Err(format!(
+                    "expected `month` after `of`, got `{}`",
+                    self.peek().unwrap_or("nothing")
+                ));
+            }
+        }
+        Ok(Some(rule))
+    }
+
+    /// `09:00`, `9am`, `9:30 pm`, `noon` or `midnight`, joined by `and`.
+    fn times(&mut self) -> Result<Vec<NaiveTime>, String> {
+        let mut times = Vec::new();
+        loop {
+            let token = self.peek().ok_or("expected a time after `at`")?;
+            self.pos += 1;
+            let time = match token {
+                "noon" | "midday" => NaiveTime::from_hms_opt(12, 0, 0),
+                "midnight" => Some(NaiveTime::MIN),
+                _ => {
+                    let (clock, meridiem) =
+                        match token.strip_suffix("am").or(token.strip_suffix("pm")) {
+                            Some(clock) => (clock, Some(&token[clock.len()..])),
+                            None if matches!(self.peek(), Some("am" | "pm")) => {
+                                self.pos += 1;
+                                (token, self.tokens.get(self.pos - 1).copied())
+                            }
+                            None => (token, None),
+                        };
+                    clock_time(clock, meridiem)
+                }
+            };
+            times
+                .push(time.ok_or_else(|| format!("`{token}` is not a time such as 09:00 or 9am"))?);
+            if !self.eat("and")
+                && !self
+                    .peek()
+                    .is_some_and(|t| t.starts_with(|c: char| c.is_ascii_digit()))
+            {
+                return Ok(times);
+            }
+        }
+    }
+
+    /// `UTC`, `in Europe/Berlin`, or a bare zone name.
+    fn zone(&mut self) -> Result<Option<chrono_tz::Tz>, String> {
+        let explicit = self.eat("in");
+        match self.peek() {
+            Some(name) => {
+                let zone = parse_tz(name);
+                if zone.is_ok() || explicit {
+                    self.pos += 1;
+                }
+                if explicit {
+                    zone.map(Some)
+                } else {
+                    Ok(zone.ok())
+                }
+            }
+            None if explicit => Err("expected a time zone after `in`".into()),
+            None => Ok(None),
+        }
+    }
+}
+
+/// `9`, `09:00` or `17:30:15`, then `am`/`pm` if given.
+fn clock_time(clock: &str, meridiem: Option<&str>) -> Option<NaiveTime> {
+    let mut parts = clock.split(':');
+    let mut hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts
+        .next()
+        .map_or(Some(0), |m| m.parse().ok().filter(|_| m.len() == 2))?;
+    let second: u32 = parts
+        .next()
+        .map_or(Some(0), |s| s.parse().ok().filter(|_| s.len() == 2))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    match meridiem {
+        Some(meridiem) => {
+            if !(1..=12).contains(&hour) {
+                return None;
+            }
+            hour %= 12;
+            if meridiem == "pm" {
+                hour += 12;
+            }
+        }
+        // A bare `9` reads as a count, not a time.
+        None if !clock.contains(':') => return None,
+        None => {}
+    }
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
@@ -0,0 +1,54 @@
+//! What a recurring payment commits to, for confirmation messages.
+
+use stellrflow_amount::Amount;
+
+use crate::Recurrence;
+
+/// A recurring payment: how often, between when, and how much each time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub recurrence: Recurrence,
+    /// When the schedule starts, in Unix ms. It is not itself a payment.
+    pub start: i64,
+    /// Nothing is paid after this, in Unix ms.
+    pub end: i64,
+    /// Paid at each occurrence.
+    pub amount: Amount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    /// How often, in words: `every 1h 30m`, `every Monday at 09:00 UTC`.
+    pub description: String,
+    /// The first payments, in Unix ms.
+    pub next: Vec<i64>,
+    /// How many payments fall between the start and the end.
+    pub payments: u64,
+    /// `amount` times `payments`, or `None` if that is more than an
+    /// [`Amount`] holds.
+    pub total: Option<Amount>,
+}
+
+/// The first `n` payments of `schedule`, and what all of them add up to.
+pub fn preview(schedule: &Schedule, n: usize) -> Preview {
+    let Schedule {
+        recurrence,
+        start,
+        end,
+        amount,
+    } = schedule;
+    let next = recurrence
+        .occurrences(*start)
+        .take_while(|at| at <= end)
+        .take(n)
+        .collect();
+    let payments = recurrence.count_between(*start, *end);
+    Preview {
+        description: recurrence.describe(),
+        next,
+        payments,
+        total: i64::try_from(payments)
+            .ok()
+            .and_then(|payments| amount.checked_mul(payments)),
+    }
+}
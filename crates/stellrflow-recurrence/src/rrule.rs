@@ -0,0 +1,224 @@
+//! `RRULE` text: `FREQ=MONTHLY;BYDAY=-1FR;BYHOUR=17`, optionally after a
+//! `DTSTART` line and with an `RRULE:` prefix.
+//!
+//! Supported parts are `FREQ` (`DAILY`, `WEEKLY`, `MONTHLY`, `YEARLY`),
+//! `INTERVAL`, `COUNT`, `UNTIL`, `BYMONTH`, `BYMONTHDAY`, `BYDAY`,
+//! `BYHOUR`, `BYMINUTE`, `BYSECOND`, `BYSETPOS` and `WKST`, plus `TZID` as
+//! a shorthand for `DTSTART;TZID=…` without a start.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
+
+use crate::rule::{Freq, Rule, WeekdayNum};
+use crate::tz::{parse_tz, resolve};
+
+pub(crate) fn parse(input: &str) -> Result<Rule, String> {
+    let mut dtstart: Option<(NaiveDateTime, Option<Tz>)> = None;
+    let mut rule_text = None;
+    for line in input.split_whitespace() {
+        let upper = line.to_ascii_uppercase();
+        if upper.starts_with("DTSTART") {
+            if dtstart.is_some() {
+                return Err("more than one DTSTART".into());
+            }
+            dtstart = Some(parse_dtstart(line)?);
+        } else if rule_text.is_some() {
+            return Err(format!("unexpected `{line}` after the rule"));
+        } else {
+            let body = match upper.strip_prefix("RRULE:") {
+                Some(_) => &line["RRULE:".len()..],
+                None => line,
+            };
+            rule_text = Some(body);
+        }
+    }
+    let rule_text = rule_text.ok_or("missing RRULE")?;
+
+    let mut rule = None::<Rule>;
+    let mut parts = Vec::new();
+    for part in rule_text.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("expected NAME=VALUE, got `{part}`"))?;
+        let key = key.to_ascii_uppercase();
+        if parts.iter().any(|(k, _): &(String, &str)| *k == key) {
+            return Err(format!("`{key}` is given twice"));
+        }
+        if key == "FREQ" {
+            rule = Some(Rule::new(freq(value)?));
+        }
+        parts.push((key, value));
+    }
+    let mut rule = rule.ok_or("`FREQ` is required")?;
+
+    let mut until = None;
+    let mut tzid = None;
+    for (key, value) in parts {
+        match key.as_str() {
+            "FREQ" => {}
+            "INTERVAL" => rule.interval = number(&key, value, 1, u32::MAX as i64)? as u32,
+            "COUNT" => rule.count = Some(number(&key, value, 1, u32::MAX as i64)? as u32),
+            "UNTIL" => until = Some(value),
+            "BYMONTH" => rule.by_month = list(&key, value, 1, 12)?,
+            "BYMONTHDAY" => rule.by_month_day = signed_list(&key, value, 31)?,
+            "BYHOUR" => rule.by_hour = list(&key, value, 0, 23)?,
+            "BYMINUTE" => rule.by_minute = list(&key, value, 0, 59)?,
+            "BYSECOND" => rule.by_second = list(&key, value, 0, 59)?,
+            "BYSETPOS" => rule.by_set_pos = signed_list(&key, value, 366)?,
+            "BYDAY" => {
+                rule.by_day = value
+                    .split(',')
+                    .map(weekday_num)
+                    .collect::<Result<_, _>>()?
+            }
+            "WKST" => rule.week_start = weekday(value)?,
+            "TZID" => tzid = Some(parse_tz(value)?),
+            "BYWEEKNO" | "BYYEARDAY" => return Err(format!("`{key}` is not supported")),
+            other => return Err(format!("unknown part `{other}`")),
+        }
+    }
+
+    if rule.count.is_some() && until.is_some() {
+        return Err("`COUNT` and `UNTIL` cannot both be given".into());
+    }
+    if rule.by_day.iter().any(|w| w.nth != 0) && matches!(rule.freq, Freq::Daily | Freq::Weekly) {
+        return Err("numbered `BYDAY` entries such as `1MO` need FREQ=MONTHLY or YEARLY".into());
+    }
+    if !rule.by_month_day.is_empty() && rule.freq == Freq::Weekly {
+        return Err("`BYMONTHDAY` cannot be used with FREQ=WEEKLY".into());
+    }
+
+    rule.tz = match (dtstart, tzid) {
+        (Some((_, Some(a))), Some(b)) if a != b => {
+            return Err("`TZID` disagrees with the DTSTART time zone".into())
+        }
+        (Some((_, Some(tz))), _) | (_, Some(tz)) => tz,
+        _ => Tz::UTC,
+    };
+    rule.dtstart = dtstart.map(|(at, _)| at);
+    if let Some(until) = until {
+        let (at, utc) = date_time(until)?;
+        rule.until = Some(resolve(if utc { Tz::UTC } else { rule.tz }, at));
+    }
+    Ok(rule)
+}
+
+/// `DTSTART:20240101T090000Z`, `DTSTART;TZID=Europe/Berlin:20240101T090000`
+/// or `DTSTART;VALUE=DATE:20240101`. The zone is `None` for floating times.
+fn parse_dtstart(line: &str) -> Result<(NaiveDateTime, Option<Tz>), String> {
+    let (params, value) = line
+        .split_once(':')
+        .ok_or_else(|| format!("expected DTSTART:<date>, got `{line}`"))?;
+    let mut tz = None;
+    for param in params.split(';').skip(1) {
+        match param.split_once('=') {
+            Some((key, zone)) if key.eq_ignore_ascii_case("TZID") => tz = Some(parse_tz(zone)?),
+            Some((key, _)) if key.eq_ignore_ascii_case("VALUE") => {}
+            _ => return Err(format!("unknown DTSTART parameter `{param}`")),
+        }
+    }
+    let (at, utc) = date_time(value)?;
+    match (utc, tz) {
+        (true, Some(_)) => Err("a DTSTART ending in `Z` cannot also have a TZID".into()),
+        (true, None) => Ok((at, Some(Tz::UTC))),
+        (false, tz) => Ok((at, tz)),
+    }
+}
+
+/// `20240101`, `20240101T090000` or `20240101T090000Z`; `true` for `Z`.
+fn date_time(value: &str) -> Result<(NaiveDateTime, bool), String> {
+    let invalid = || format!("expected a date like 20240131T090000Z, got `{value}`");
+    let (value, utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let (date, time) = match value.split_once(['T', 't']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+    let date = NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| invalid())?;
+    let time = match time {
+        Some(time) => NaiveTime::parse_from_str(time, "%H%M%S").map_err(|_| invalid())?,
+        None => NaiveTime::MIN,
+    };
+    Ok((date.and_time(time), utc))
+}
+
+fn freq(value: &str) -> Result<Freq, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "DAILY" => Ok(Freq::Daily),
+        "WEEKLY" => Ok(Freq::Weekly),
+        "MONTHLY" => Ok(Freq::Monthly),
+        "YEARLY" => Ok(Freq::Yearly),
+        "HOURLY" | "MINUTELY" | "SECONDLY" => Err(format!(
+            "FREQ={value} is not supported; use an interval such as `every 2h` or a cron expression"
+        )),
+        _ => Err(format!("unknown FREQ `{value}`")),
+    }
+}
+
+fn number(key: &str, value: &str, min: i64, max: i64) -> Result<i64, String> {
+    value
+        .parse::<i64>()
+        .ok()
+        .filter(|n| (min..=max).contains(n))
+        .ok_or_else(|| format!("`{key}` must be {min} to {max}, got `{value}`"))
+}
+
+fn list(key: &str, value: &str, min: i64, max: i64) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(|item| number(key, item, min, max).map(|n| n as u32))
+        .collect()
+}
+
+/// `1` to `max` or `-1` to `-max`.
+fn signed_list(key: &str, value: &str, max: i64) -> Result<Vec<i32>, String> {
+    value
+        .split(',')
+        .map(|item| {
+            number(key, item, -max, max)
+                .ok()
+                .filter(|n| *n != 0)
+                .map(|n| n as i32)
+                .ok_or_else(|| format!("`{key}` must be 1 to {max} or -1 to -{max}, got `{item}`"))
+        })
+        .collect()
+}
+
+fn weekday_num(item: &str) -> Result<WeekdayNum, String> {
+    if !item.is_ascii() {
+        return Err(format!("unknown `BYDAY` entry `{item}`"));
+    }
+    let split = item.len().saturating_sub(2);
+    let (nth, day) = item.split_at(split);
+    let nth = match nth {
+        "" => 0,
+        nth => nth
+            .parse::<i32>()
+            .ok()
+            .filter(|n| *n != 0 && (-53..=53).contains(n))
+            .ok_or_else(|| format!("`BYDAY` entry `{item}` has an invalid number"))?,
+    };
+    Ok(WeekdayNum {
+        nth,
+        weekday: weekday(day)?,
+    })
+}
+
+fn weekday(code: &str) -> Result<Weekday, String> {
+    Ok(match code.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => {
+            return Err(format!(
+                "unknown weekday `{code}` (expected MO, TU, WE, TH, FR, SA or SU)"
+            ))
+        }
+    })
+}
@@ -0,0 +1,346 @@
+//! Calendar rules in the model of RFC 5545 `RRULE`, evaluated in a time
+//! zone.
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Weekday};
+use chrono_tz::Tz;
+
+use crate::tz;
+
+/// How far past the requested time to look before deciding a rule has no
+/// more occurrences (e.g. `BYMONTH=2;BYMONTHDAY=30`).
+const HORIZON_YEARS: i32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A `BYDAY` entry: every such weekday (`MO`), or the nth one in the month
+/// or year (`1MO`, `-1FR` for the last Friday).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekdayNum {
+    /// 0 for every one.
+    pub nth: i32,
+    pub weekday: Weekday,
+}
+
+/// A calendar rule. Build one with [`Recurrence::parse`](crate::Recurrence::parse).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub(crate) freq: Freq,
+    pub(crate) interval: u32,
+    pub(crate) tz: Tz,
+    /// Local start. `None` starts the rule when the schedule starts, which
+    /// also supplies the time of day when the rule names none.
+    pub(crate) dtstart: Option<NaiveDateTime>,
+    /// Last allowed instant, in Unix ms.
+    pub(crate) until: Option<i64>,
+    pub(crate) count: Option<u32>,
+    pub(crate) by_month: Vec<u32>,
+    pub(crate) by_month_day: Vec<i32>,
+    pub(crate) by_day: Vec<WeekdayNum>,
+    pub(crate) by_hour: Vec<u32>,
+    pub(crate) by_minute: Vec<u32>,
+    pub(crate) by_second: Vec<u32>,
+    pub(crate) by_set_pos: Vec<i32>,
+    pub(crate) week_start: Weekday,
+    /// Times of day from a phrase such as `at 09:00 and 17:30`, which
+    /// `BYHOUR` × `BYMINUTE` cannot express. Overrides the `BY*` time parts.
+    pub(crate) at: Vec<NaiveTime>,
+}
+
+impl Rule {
+    pub(crate) fn new(freq: Freq) -> Self {
+        Self {
+            freq,
+            interval: 1,
+            tz: Tz::UTC,
+            dtstart: None,
+            until: None,
+            count: None,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_day: Vec::new(),
+            by_hour: Vec::new(),
+            by_minute: Vec::new(),
+            by_second: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+            at: Vec::new(),
+        }
+    }
+
+    pub fn freq(&self) -> Freq {
+        self.freq
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// The first instance later than both `after` and `start`, for a
+    /// schedule that started at `start` (Unix ms).
+    pub(crate) fn next_after(&self, start: i64, after: i64) -> Option<i64> {
+        let dtstart = match self.dtstart {
+            Some(dtstart) => dtstart,
+            None => tz::local(self.tz, start)?.with_nanosecond(0)?,
+        };
+        let floor = after.max(start);
+        let floor_date = tz::local(self.tz, floor)?.date().max(dtstart.date());
+        let horizon = floor_date.with_year(floor_date.year() + HORIZON_YEARS)?;
+        let times = self.times(dtstart);
+
+        // COUNT is numbered from DTSTART, so counted rules walk every
+        // period. Others start one period before the one holding `floor`.
+        let mut period = match self.count {
+            Some(_) => 0,
+            None => self
+                .periods_between(dtstart.date(), floor_date)
+                .saturating_sub(1),
+        };
+        let mut emitted = 0;
+        loop {
+            let first = self.period_start(dtstart.date(), period)?;
+            if first > horizon {
+                return None;
+            }
+            for local in self.instances(dtstart, first, &times) {
+                let at = tz::resolve(self.tz, local);
+                if self.until.is_some_and(|until| at > until) {
+                    return None;
+                }
+                if let Some(count) = self.count {
+                    if emitted == count {
+                        return None;
+                    }
+                    emitted += 1;
+                }
+                if at > floor {
+                    return Some(at);
+                }
+            }
+            period = period.checked_add(1)?;
+        }
+    }
+
+    /// Whole periods from the one holding `from` to the one holding `to`.
+    fn periods_between(&self, from: NaiveDate, to: NaiveDate) -> u32 {
+        let steps = match self.freq {
+            Freq::Daily => (to - from).num_days(),
+            Freq::Weekly => (week_start(to, self.week_start) - week_start(from, self.week_start))
+                .num_days()
+                .div_euclid(7),
+            Freq::Monthly => month_number(to) - month_number(from),
+            Freq::Yearly => i64::from(to.year() - from.year()),
+        };
+        u32::try_from(steps.max(0) / i64::from(self.interval)).unwrap_or(u32::MAX)
+    }
+
+    /// First day of period `n`, counting DTSTART's as 0.
+    fn period_start(&self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        let steps = n.checked_mul(self.interval)?;
+        match self.freq {
+            Freq::Daily => start.checked_add_signed(TimeDelta::days(i64::from(steps))),
+            Freq::Weekly => week_start(start, self.week_start)
+                .checked_add_signed(TimeDelta::weeks(i64::from(steps))),
+            Freq::Monthly => start.with_day(1)?.checked_add_months(Months::new(steps)),
+            Freq::Yearly => NaiveDate::from_ymd_opt(start.year().checked_add(steps as i32)?, 1, 1),
+        }
+    }
+
+    /// The instances in the period starting on `first`, in order.
+    fn instances(
+        &self,
+        dtstart: NaiveDateTime,
+        first: NaiveDate,
+        times: &[NaiveTime],
+    ) -> Vec<NaiveDateTime> {
+        let mut set: Vec<NaiveDateTime> = self
+            .days(dtstart.date(), first)
+            .into_iter()
+            .flat_map(|day| times.iter().map(move |time| day.and_time(*time)))
+            .collect();
+        if !self.by_set_pos.is_empty() {
+            let len = set.len() as i32;
+            let mut picked: Vec<NaiveDateTime> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|&pos| {
+                    let index = if pos > 0 { pos - 1 } else { len + pos };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|i| set.get(i).copied())
+                })
+                .collect();
+            picked.sort();
+            picked.dedup();
+            set = picked;
+        }
+        set.retain(|at| *at >= dtstart);
+        set
+    }
+
+    fn days(&self, start: NaiveDate, first: NaiveDate) -> Vec<NaiveDate> {
+        let month_ok =
+            |day: &NaiveDate| self.by_month.is_empty() || self.by_month.contains(&day.month());
+        match self.freq {
+            Freq::Daily => {
+                let day = first;
+                let matches = month_ok(&day)
+                    && (self.by_month_day.is_empty() || self.month_day_matches(day))
+                    && (self.by_day.is_empty()
+                        || self.by_day.iter().any(|w| w.weekday == day.weekday()));
+                if matches {
+                    vec![day]
+                } else {
+                    Vec::new()
+                }
+            }
+            Freq::Weekly => first
+                .iter_days()
+                .take(7)
+                .filter(|day| match self.by_day.as_slice() {
+                    [] => day.weekday() == start.weekday(),
+                    by_day => by_day.iter().any(|w| w.weekday == day.weekday()),
+                })
+                .filter(month_ok)
+                .collect(),
+            Freq::Monthly => {
+                if month_ok(&first) {
+                    self.month_days(start, first.year(), first.month())
+                } else {
+                    Vec::new()
+                }
+            }
+            Freq::Yearly => {
+                let year = first.year();
+                if self.by_month.is_empty()
+                    && self.by_month_day.is_empty()
+                    && !self.by_day.is_empty()
+                {
+                    // BYDAY alone counts weekdays through the year.
+                    let last = NaiveDate::from_ymd_opt(year, 12, 31).map_or(365, |d| d.ordinal());
+                    return first
+                        .iter_days()
+                        .take_while(|day| day.year() == year)
+                        .filter(|day| {
+                            self.by_day
+                                .iter()
+                                .any(|w| nth_matches(*w, *day, day.ordinal(), last))
+                        })
+                        .collect();
+                }
+                let months = if !self.by_month.is_empty() {
+                    self.by_month.clone()
+                } else if self.by_month_day.is_empty() && self.by_day.is_empty() {
+                    vec![start.month()]
+                } else {
+                    (1..=12).collect()
+                };
+                let mut days: Vec<NaiveDate> = months
+                    .into_iter()
+                    .flat_map(|month| self.month_days(start, year, month))
+                    .collect();
+                days.sort();
+                days
+            }
+        }
+    }
+
+    /// The days of one month the rule selects.
+    fn month_days(&self, start: NaiveDate, year: i32, month: u32) -> Vec<NaiveDate> {
+        let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+            return Vec::new();
+        };
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            // DTSTART's day, where the month has one.
+            return first.with_day(start.day()).into_iter().collect();
+        }
+        let last = days_in_month(first);
+        first
+            .iter_days()
+            .take(last as usize)
+            .filter(|day| self.by_month_day.is_empty() || self.month_day_matches(*day))
+            .filter(|day| {
+                self.by_day.is_empty()
+                    || self
+                        .by_day
+                        .iter()
+                        .any(|w| nth_matches(*w, *day, day.day(), last))
+            })
+            .collect()
+    }
+
+    fn month_day_matches(&self, day: NaiveDate) -> bool {
+        let last = days_in_month(day) as i32;
+        let d = day.day() as i32;
+        self.by_month_day
+            .iter()
+            .any(|&md| md == d || md == d - last - 1)
+    }
+
+    fn times(&self, dtstart: NaiveDateTime) -> Vec<NaiveTime> {
+        if !self.at.is_empty() {
+            let mut at = self.at.clone();
+            at.sort();
+            at.dedup();
+            return at;
+        }
+        let or = |by: &[u32], default: u32| {
+            if by.is_empty() {
+                vec![default]
+            } else {
+                by.to_vec()
+            }
+        };
+        let hours = or(&self.by_hour, dtstart.hour());
+        let minutes = or(&self.by_minute, dtstart.minute());
+        let seconds = or(&self.by_second, dtstart.second());
+        let mut times: Vec<NaiveTime> = hours
+            .iter()
+            .flat_map(|h| {
+                let seconds = &seconds;
+                minutes.iter().flat_map(move |m| {
+                    seconds
+                        .iter()
+                        .filter_map(move |s| NaiveTime::from_hms_opt(*h, *m, *s))
+                })
+            })
+            .collect();
+        times.sort();
+        times.dedup();
+        times
+    }
+}
+
+/// Whether `day` is the weekday `w` asks for, being the `index`th (1-based)
+/// day of a month or year that has `last` days.
+fn nth_matches(w: WeekdayNum, day: NaiveDate, index: u32, last: u32) -> bool {
+    if day.weekday() != w.weekday {
+        return false;
+    }
+    match w.nth {
+        0 => true,
+        n if n > 0 => (index - 1) / 7 + 1 == n as u32,
+        n => (last - index) / 7 + 1 == n.unsigned_abs(),
+    }
+}
+
+fn week_start(day: NaiveDate, first: Weekday) -> NaiveDate {
+    let back = (7 + day.weekday().num_days_from_monday() - first.num_days_from_monday()) % 7;
+    day - TimeDelta::days(i64::from(back))
+}
+
+fn month_number(day: NaiveDate) -> i64 {
+    i64::from(day.year()) * 12 + i64::from(day.month0())
+}
+
+pub(crate) fn days_in_month(day: NaiveDate) -> u32 {
+    let first = day.with_day(1).unwrap_or(day);
+    first
+        .checked_add_months(Months::new(1))
+        .map_or(31, |next| (next - first).num_days() as u32)
+}
@@ -0,0 +1,251 @@
+//! Fixed intervals: the suffix grammar of `interval-parser.ts` and compound
+//! durations built from it.
+
+use chrono::{DateTime, Months};
+
+pub(crate) const SECOND_MS: u64 = 1_000;
+pub(crate) const MINUTE_MS: u64 = 60 * SECOND_MS;
+pub(crate) const HOUR_MS: u64 = 60 * MINUTE_MS;
+pub(crate) const DAY_MS: u64 = 24 * HOUR_MS;
+pub(crate) const WEEK_MS: u64 = 7 * DAY_MS;
+
+/// `Number.MAX_SAFE_INTEGER`. Larger results lose precision in JavaScript.
+const MAX_SAFE_MS: f64 = 9_007_199_254_740_991.0;
+
+/// The unit table of `parseIntervalFormat`, keyed by lower-case suffix.
+const UNITS: &[(&str, u64)] = &[
+    ("ms", 1),
+    ("s", SECOND_MS),
+    ("sec", SECOND_MS),
+    ("second", SECOND_MS),
+    ("seconds", SECOND_MS),
+    ("m", MINUTE_MS),
+    ("min", MINUTE_MS),
+    ("minute", MINUTE_MS),
+    ("minutes", MINUTE_MS),
+    ("h", HOUR_MS),
+    ("hr", HOUR_MS),
+    ("hour", HOUR_MS),
+    ("hours", HOUR_MS),
+    ("d", DAY_MS),
+    ("day", DAY_MS),
+    ("days", DAY_MS),
+    ("w", WEEK_MS),
+    ("week", WEEK_MS),
+    ("weeks", WEEK_MS),
+];
+
+/// Calendar units, which only compound durations accept.
+const MONTH_UNITS: &[(&str, u32)] = &[
+    ("mo", 1),
+    ("mon", 1),
+    ("month", 1),
+    ("months", 1),
+    ("y", 12),
+    ("yr", 12),
+    ("year", 12),
+    ("years", 12),
+];
+
+/// Parse `"3600s"`, `"1.5h"`, `"30 min"`, … into milliseconds, exactly as
+/// `parseIntervalFormat` in `bots/telegram-stellar/src/interval-parser.ts`
+/// does, error messages included.
+///
+/// Where the TypeScript returns something other than a whole number of
+/// milliseconds this returns an error instead: `NaN` for the unit
+/// `constructor` (an `Object.prototype` key), and imprecise numbers above
+/// `Number.MAX_SAFE_INTEGER`.
+pub fn parse_interval_ms(input: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid interval format: {input}");
+    let trimmed = js_trim(input);
+
+    // /^\d+$/ → parseInt
+    if !trimmed.is_empty() && trimmed.bytes().all(|b| b.is_ascii_digit()) {
+        return safe(trimmed.parse().map_err(|_| invalid())?).ok_or_else(invalid);
+    }
+
+    // /^(\d+\.?\d*)\s*([a-zA-Z]+)$/
+    let (number, rest) = split_number(trimmed).ok_or_else(invalid)?;
+    let unit = rest.trim_start_matches(is_js_space);
+    if unit.is_empty() || !unit.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err(invalid());
+    }
+
+    let unit_lower = unit.to_ascii_lowercase();
+    let multiplier = match UNITS.iter().find(|(name, _)| *name == unit_lower) {
+        Some((_, multiplier)) => *multiplier,
+        // `unitMap["constructor"]` is a function, so the result is NaN.
+        None if unit_lower == "constructor" => return Err(invalid()),
+        None => return Err(format!("Unknown time unit: {unit}")),
+    };
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+    safe((value * multiplier as f64).floor()).ok_or_else(invalid)
+}
+
+fn safe(ms: f64) -> Option<u64> {
+    (ms <= MAX_SAFE_MS).then_some(ms as u64)
+}
+
+/// A leading `\d+\.?\d*`, and what follows it.
+fn split_number(s: &str) -> Option<(&str, &str)> {
+    let int = s.bytes().take_while(u8::is_ascii_digit).count();
+    if int == 0 {
+        return None;
+    }
+    let mut end = int;
+    if s[end..].starts_with('.') {
+        end += 1;
+        end += s[end..].bytes().take_while(u8::is_ascii_digit).count();
+    }
+    Some(s.split_at(end))
+}
+
+/// `String.prototype.trim`, whose whitespace differs from Rust's: it
+/// includes U+FEFF and excludes U+0085.
+fn js_trim(s: &str) -> &str {
+    s.trim_matches(is_js_space)
+}
+
+/// JavaScript's `\s`: WhiteSpace and LineTerminator.
+fn is_js_space(c: char) -> bool {
+    matches!(
+        c,
+        '\t' | '\n' | '\u{000B}' | '\u{000C}' | '\r' | ' ' | '\u{00A0}' | '\u{1680}' | '\u{2000}'
+            ..='\u{200A}'
+                | '\u{2028}'
+                | '\u{2029}'
+                | '\u{202F}'
+                | '\u{205F}'
+                | '\u{3000}'
+                | '\u{FEFF}'
+    )
+}
+
+/// A fixed step between occurrences: whole months, then milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub months: u32,
+    pub millis: u64,
+}
+
+impl Span {
+    pub const fn millis(millis: u64) -> Self {
+        Self { months: 0, millis }
+    }
+
+    pub const fn months(months: u32) -> Self {
+        Self { months, millis: 0 }
+    }
+
+    /// The legacy grammar, or a sequence of `<number><unit>` parts such as
+    /// `1d12h`, `1h 30m` or `2mo 1w`, optionally after `every`.
+    ///
+    /// Anything [`parse_interval_ms`] accepts means the same here.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        if let Ok(millis) = parse_interval_ms(input) {
+            return Ok(Self::millis(millis));
+        }
+        let trimmed = js_trim(input);
+        let body = strip_word(trimmed, "every").unwrap_or(trimmed);
+
+        let mut span = Span::default();
+        let mut rest = body.trim_start_matches(is_js_space);
+        if rest.is_empty() {
+            return Err(format!("Invalid interval format: {input}"));
+        }
+        while !rest.is_empty() {
+            let (number, after) =
+                split_number(rest).ok_or_else(|| format!("Invalid interval format: {input}"))?;
+            let after = after.trim_start_matches(is_js_space);
+            let len = after.bytes().take_while(u8::is_ascii_alphabetic).count();
+            if len == 0 {
+                return Err(format!("Invalid interval format: {input}"));
+            }
+            let (unit, after) = after.split_at(len);
+            let unit = unit.to_ascii_lowercase();
+            let value: f64 = number
+                .parse()
+                .map_err(|_| format!("Invalid interval format: {input}"))?;
+
+            if let Some((_, months)) = MONTH_UNITS.iter().find(|(name, _)| *name == unit) {
+                if value.fract() != 0.0 {
+                    return Err(format!("`{number}{unit}`: months and years must be whole"));
+                }
+                let add = (value as u64)
+                    .checked_mul(u64::from(*months))
+                    .and_then(|m| u32::try_from(m).ok())
+                    .ok_or_else(|| format!("`{number}{unit}` is too long"))?;
+                span.months = span
+                    .months
+                    .checked_add(add)
+                    .ok_or_else(|| format!("Interval too long: {input}"))?;
+            } else if let Some((_, multiplier)) = UNITS.iter().find(|(name, _)| *name == unit) {
+                let add = safe((value * *multiplier as f64).floor())
+                    .ok_or_else(|| format!("`{number}{unit}` is too long"))?;
+                span.millis = span
+                    .millis
+                    .checked_add(add)
+                    .filter(|ms| *ms as f64 <= MAX_SAFE_MS)
+                    .ok_or_else(|| format!("Interval too long: {input}"))?;
+            } else {
+                return Err(format!("Unknown time unit: {unit}"));
+            }
+            rest = after.trim_start_matches(|c: char| is_js_space(c) || c == ',');
+        }
+        Ok(span)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.months == 0 && self.millis == 0
+    }
+
+    /// The length in milliseconds, unless it has calendar months.
+    pub fn fixed_ms(self) -> Option<u64> {
+        (self.months == 0).then_some(self.millis)
+    }
+
+    /// `count` steps after `start` (Unix ms). Months are added as a whole,
+    /// clamping to the end of shorter months, so Jan 31 + 1 month is the
+    /// last day of February and + 2 months is Mar 31.
+    pub fn after(self, start: i64, count: u32) -> Option<i64> {
+        let mut at = start;
+        if self.months > 0 {
+            let months = self.months.checked_mul(count)?;
+            let date = DateTime::from_timestamp_millis(at)?;
+            at = date
+                .checked_add_months(Months::new(months))?
+                .timestamp_millis();
+        }
+        let offset = i64::try_from(self.millis.checked_mul(u64::from(count))?).ok()?;
+        at.checked_add(offset)
+    }
+
+    /// The first step after `start` that is later than `after`.
+    pub(crate) fn next_after(self, start: i64, after: i64) -> Option<i64> {
+        if self.is_zero() {
+            return None;
+        }
+        // No step is longer than this, so it undercounts the steps needed.
+        let longest = i64::from(self.months)
+            .saturating_mul(31 * DAY_MS as i64)
+            .saturating_add(self.millis as i64);
+        let mut count = u32::try_from(after.saturating_sub(start).max(0) / longest)
+            .ok()?
+            .max(1);
+        loop {
+            let at = self.after(start, count)?;
+            if at > after {
+                return Some(at);
+            }
+            count = count.checked_add(1)?;
+        }
+    }
+}
+
+/// `s` without a leading `word` and the whitespace after it.
+fn strip_word<'a>(s: &'a str, word: &str) -> Option<&'a str> {
+    let head = s.get(..word.len())?;
+    let rest = &s[word.len()..];
+    (head.eq_ignore_ascii_case(word) && rest.starts_with(is_js_space))
+        .then(|| rest.trim_start_matches(is_js_space))
+}
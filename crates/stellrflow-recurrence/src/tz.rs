@@ -0,0 +1,43 @@
+//! Local calendar time in a named zone.
+
+use chrono::offset::LocalResult;
+use chrono::{DateTime, NaiveDateTime, Offset, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// `UTC`, `GMT`, `Z`, or an IANA name such as `Europe/Berlin` in any case.
+pub(crate) fn parse_tz(name: &str) -> Result<Tz, String> {
+    match name.to_ascii_uppercase().as_str() {
+        "UTC" | "GMT" | "Z" => Ok(Tz::UTC),
+        _ => chrono_tz::TZ_VARIANTS
+            .iter()
+            .find(|tz| tz.name().eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| format!("unknown time zone `{name}`")),
+    }
+}
+
+/// The instant `local` names in `tz`, in Unix ms.
+///
+/// Following RFC 5545, a time that occurs twice when clocks go back means
+/// the first one, and a time skipped when clocks go forward is read with
+/// the offset from before the jump (02:30 on a spring-forward night is
+/// 03:30 local).
+pub(crate) fn resolve(tz: Tz, local: NaiveDateTime) -> i64 {
+    let instant = match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.with_timezone(&Utc),
+        LocalResult::None => {
+            let before = tz
+                .offset_from_utc_datetime(&(local - TimeDelta::days(1)))
+                .fix();
+            let utc = local - TimeDelta::seconds(i64::from(before.local_minus_utc()));
+            DateTime::from_naive_utc_and_offset(utc, Utc)
+        }
+    };
+    instant.timestamp_millis()
+}
+
+/// The local time in `tz` at `at` (Unix ms).
+pub(crate) fn local(tz: Tz, at: i64) -> Option<NaiveDateTime> {
+    let utc = DateTime::from_timestamp_millis(at)?;
+    Some(utc.with_timezone(&tz).naive_local())
+}
@@ -0,0 +1,306 @@
+use chrono::{DateTime, SecondsFormat};
+use stellrflow_recurrence::Recurrence;
+
+fn ms(iso: &str) -> i64 {
+    DateTime::parse_from_rfc3339(iso)
+        .unwrap()
+        .timestamp_millis()
+}
+
+/// The next `n` occurrences of `rule` for a schedule started at `start`.
+fn next(rule: &str, start: &str, n: usize) -> Vec<String> {
+    let recurrence = Recurrence::parse(rule).unwrap_or_else(|e| panic!("{rule:?}: {e}"));
+    recurrence
+        .occurrences(ms(start))
+        .take(n)
+        .map(|at| {
+            DateTime::from_timestamp_millis(at)
+                .unwrap()
+                .to_rfc3339_opts(SecondsFormat::Secs, true)
+        })
+        .collect()
+}
+
+fn error(rule: &str) -> String {
+    Recurrence::parse(rule).unwrap_err().to_string()
+}
+
+#[test]
+fn phrases_name_days_and_times() {
+    // 2024-03-01 is a Friday.
+    assert_eq!(
+        next("weekdays at 09:00 UTC", "2024-03-01T12:00:00Z", 5),
+        [
+            "2024-03-04T09:00:00Z",
+            "2024-03-05T09:00:00Z",
+            "2024-03-06T09:00:00Z",
+            "2024-03-07T09:00:00Z",
+            "2024-03-08T09:00:00Z",
+        ]
+    );
+    // Without `at`, the time of day the schedule started at.
+    assert_eq!(
+        next("every 1st of month", "2024-01-15T08:30:00Z", 3),
+        [
+            "2024-02-01T08:30:00Z",
+            "2024-03-01T08:30:00Z",
+            "2024-04-01T08:30:00Z",
+        ]
+    );
+    assert_eq!(
+        next(
+            "every other week on Friday at 5pm",
+            "2024-01-01T00:00:00Z",
+            3
+        ),
+        [
+            "2024-01-05T17:00:00Z",
+            "2024-01-19T17:00:00Z",
+            "2024-02-02T17:00:00Z",
+        ]
+    );
+    assert_eq!(
+        next(
+            "the 1st and 15th of the month at noon",
+            "2024-01-10T00:00:00Z",
+            3
+        ),
+        [
+            "2024-01-15T12:00:00Z",
+            "2024-02-01T12:00:00Z",
+            "2024-02-15T12:00:00Z",
+        ]
+    );
+    assert_eq!(
+        next(
+            "every Monday and Thursday at 9am and 6:30 pm",
+            "2024-01-01T10:00:00Z",
+            4
+        ),
+        [
+            "2024-01-01T18:30:00Z",
+            "2024-01-04T09:00:00Z",
+            "2024-01-04T18:30:00Z",
+            "2024-01-08T09:00:00Z",
+        ]
+    );
+    assert_eq!(
+        next("last day of the month at 23:00", "2024-01-31T23:00:00Z", 2),
+        ["2024-02-29T23:00:00Z", "2024-03-31T23:00:00Z"]
+    );
+}
+
+#[test]
+fn local_times_survive_daylight_saving() {
+    // Berlin moves from UTC+1 to UTC+2 on 2024-03-31.
+    assert_eq!(
+        next(
+            "last friday of the month at 17:00 in Europe/Berlin",
+            "2024-03-01T00:00:00Z",
+            2
+        ),
+        ["2024-03-29T16:00:00Z", "2024-04-26T15:00:00Z"]
+    );
+    assert_eq!(
+        next(
+            "every day at 09:00 europe/berlin",
+            "2024-03-30T00:00:00Z",
+            3
+        ),
+        [
+            "2024-03-30T08:00:00Z",
+            "2024-03-31T07:00:00Z",
+            "2024-04-01T07:00:00Z",
+        ]
+    );
+    // 02:30 does not exist on 2024-03-10 in New York; it is read as EST.
+    assert_eq!(
+        next(
+            "CRON_TZ=America/New_York 30 2 * * *",
+            "2024-03-09T00:00:00Z",
+            3
+        ),
+        [
+            "2024-03-09T07:30:00Z",
+            "2024-03-10T07:30:00Z",
+            "2024-03-11T06:30:00Z",
+        ]
+    );
+    // 01:30 happens twice on 2024-11-03; only the first is used.
+    assert_eq!(
+        next(
+            "CRON_TZ=America/New_York 30 1 * * *",
+            "2024-11-02T12:00:00Z",
+            2
+        ),
+        ["2024-11-03T05:30:00Z", "2024-11-04T06:30:00Z"]
+    );
+}
+
+#[test]
+fn cron_expressions() {
+    assert_eq!(
+        next("0 9 * * MON-FRI", "2024-03-01T12:00:00Z", 2),
+        ["2024-03-04T09:00:00Z", "2024-03-05T09:00:00Z"]
+    );
+    assert_eq!(
+        next("*/15 * * * *", "2024-03-01T10:07:00Z", 3),
+        [
+            "2024-03-01T10:15:00Z",
+            "2024-03-01T10:30:00Z",
+            "2024-03-01T10:45:00Z",
+        ]
+    );
+    // With both day fields restricted, either one matching is enough.
+    assert_eq!(
+        next("0 0 13 * FRI", "2024-09-01T00:00:00Z", 7),
+        [
+            "2024-09-06T00:00:00Z",
+            "2024-09-13T00:00:00Z",
+            "2024-09-20T00:00:00Z",
+            "2024-09-27T00:00:00Z",
+            "2024-10-04T00:00:00Z",
+            "2024-10-11T00:00:00Z",
+            "2024-10-13T00:00:00Z",
+        ]
+    );
+    assert_eq!(
+        next("@monthly", "2024-01-15T00:00:00Z", 1),
+        ["2024-02-01T00:00:00Z"]
+    );
+    assert_eq!(
+        next("0 12 * * 7", "2024-03-01T00:00:00Z", 1),
+        ["2024-03-03T12:00:00Z"]
+    );
+
+    assert_eq!(error("61 * * * *"), "minute `61` is not 0 to 59");
+    assert_eq!(error("0 0 30 2 *"), "`0 0 30 2 *` never matches a date");
+    assert_eq!(
+        error("0 0 * * 5-1"),
+        "day-of-week range `5-1` runs backwards"
+    );
+    assert_eq!(
+        error("@fortnightly"),
+        "unknown shorthand `@fortnightly` (expected @hourly, @daily, @weekly, @monthly or @yearly)"
+    );
+    assert_eq!(
+        error("TZ=Mars/Olympus 0 9 * * *"),
+        "unknown time zone `Mars/Olympus`"
+    );
+}
+
+#[test]
+fn rrules() {
+    // The last working day of each month.
+    assert_eq!(
+        next(
+            "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;BYHOUR=17;BYMINUTE=0;BYSECOND=0",
+            "2024-03-01T00:00:00Z",
+            4
+        ),
+        [
+            "2024-03-29T17:00:00Z",
+            "2024-04-30T17:00:00Z",
+            "2024-05-31T17:00:00Z",
+            "2024-06-28T17:00:00Z",
+        ]
+    );
+    assert_eq!(
+        next("RRULE:FREQ=MONTHLY;BYDAY=-1FR", "2024-03-01T09:00:00Z", 2),
+        ["2024-03-29T09:00:00Z", "2024-04-26T09:00:00Z"]
+    );
+    assert_eq!(
+        next(
+            "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29",
+            "2024-03-01T00:00:00Z",
+            2
+        ),
+        ["2028-02-29T00:00:00Z", "2032-02-29T00:00:00Z"]
+    );
+    assert_eq!(
+        next(
+            "DTSTART;TZID=Europe/Berlin:20240325T090000\nRRULE:FREQ=DAILY",
+            "2024-03-30T00:00:00Z",
+            2
+        ),
+        ["2024-03-30T08:00:00Z", "2024-03-31T07:00:00Z"]
+    );
+}
+
+#[test]
+fn rrule_counts_run_from_dtstart() {
+    let rule = "DTSTART:20240101T090000Z\nRRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO;COUNT=3";
+    assert_eq!(
+        next(rule, "2024-01-01T00:00:00Z", 5),
+        [
+            "2024-01-01T09:00:00Z",
+            "2024-01-15T09:00:00Z",
+            "2024-01-29T09:00:00Z",
+        ]
+    );
+    assert_eq!(
+        next(rule, "2024-01-20T00:00:00Z", 5),
+        ["2024-01-29T09:00:00Z"]
+    );
+    assert_eq!(
+        next(
+            "FREQ=DAILY;UNTIL=20240103T000000Z",
+            "2024-01-01T06:00:00Z",
+            5
+        ),
+        ["2024-01-02T06:00:00Z"]
+    );
+}
+
+#[test]
+fn upcoming_is_deterministic() {
+    let recurrence = Recurrence::parse("0 9 * * MON").unwrap();
+    let start = ms("2024-01-01T00:00:00Z");
+    let after = ms("2024-06-01T00:00:00Z");
+    let first = recurrence.upcoming(start, after, 3);
+    assert_eq!(first, recurrence.upcoming(start, after, 3));
+    assert_eq!(first[0], ms("2024-06-03T09:00:00Z"));
+    assert_eq!(first[2] - first[1], 7 * 24 * 3_600_000);
+    assert_eq!(recurrence.next_after(start, first[0] - 1), Some(first[0]));
+}
+
+#[test]
+fn unreadable_rules_say_why() {
+    assert_eq!(
+        error("FREQ=HOURLY"),
+        "FREQ=HOURLY is not supported; use an interval such as `every 2h` or a cron expression"
+    );
+    assert_eq!(
+        error("FREQ=MONTHLY;BYMONTH=2;BYMONTHDAY=30"),
+        "`FREQ=MONTHLY;BYMONTH=2;BYMONTHDAY=30` never matches a date"
+    );
+    assert_eq!(
+        error("FREQ=WEEKLY;BYDAY=1MO"),
+        "numbered `BYDAY` entries such as `1MO` need FREQ=MONTHLY or YEARLY"
+    );
+    assert_eq!(
+        error("FREQ=DAILY;COUNT=2;UNTIL=20240101"),
+        "`COUNT` and `UNTIL` cannot both be given"
+    );
+    assert_eq!(
+        error("FREQ=DAILY;BYWEEKNO=1"),
+        "`BYWEEKNO` is not supported"
+    );
+    assert_eq!(
+        error("every fooday"),
+        "`every fooday` is not an interval such as `1h 30m`, a phrase such as \
+         `weekdays at 09:00 UTC`, a cron expression or an RRULE"
+    );
+    assert_eq!(
+        error("every monday at 25:00"),
+        "`25:00` is not a time such as 09:00 or 9am"
+    );
+    assert_eq!(
+        error("every week on the 1st"),
+        "`every week on the 1st` mixes a weekly cadence with monthly days"
+    );
+    assert_eq!(
+        error("weekdays at 09:00 in Mars/Olympus"),
+        "unknown time zone `mars/olympus`"
+    );
+}
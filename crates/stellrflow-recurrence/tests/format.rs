@@ -0,0 +1,221 @@
+use chrono::DateTime;
+use proptest::prelude::*;
+use stellrflow_amount::Amount;
+use stellrflow_recurrence::{Recurrence, Schedule, Span};
+
+const DAY: i64 = 24 * 3_600_000;
+
+fn ms(iso: &str) -> i64 {
+    DateTime::parse_from_rfc3339(iso)
+        .unwrap()
+        .timestamp_millis()
+}
+
+fn describe(input: &str) -> String {
+    Recurrence::parse(input)
+        .unwrap_or_else(|e| panic!("{input:?}: {e}"))
+        .describe()
+}
+
+#[test]
+fn spans_print_every_unit() {
+    let cases: &[(&str, &str)] = &[
+        ("90 min", "1h 30m"),
+        ("1500", "1s 500ms"),
+        ("1d12h", "1d 12h"),
+        ("14 days", "2w"),
+        ("1y 2mo", "1y 2mo"),
+        ("every 13 months", "1y 1mo"),
+        ("2mo 1w 3d", "2mo 1w 3d"),
+    ];
+    for (input, text) in cases {
+        assert_eq!(Span::parse(input).unwrap().to_string(), *text, "{input:?}");
+    }
+    assert_eq!(Span::default().to_string(), "0ms");
+}
+
+#[test]
+fn schedules_describe_themselves() {
+    let cases: &[(&str, &str)] = &[
+        ("daily", "every day"),
+        ("Weekly", "every week"),
+        ("monthly", "every month"),
+        ("annually", "every year"),
+        ("90 min", "every 1h 30m"),
+        ("1d12h", "every 1d 12h"),
+        ("every monday at 9am utc", "every Monday at 09:00 UTC"),
+        ("weekdays at 09:00", "weekdays at 09:00 UTC"),
+        (
+            "every fri, mon and wed at 17:30",
+            "every Monday, Wednesday and Friday at 17:30 UTC",
+        ),
+        ("every 1st of month", "the 1st of every month"),
+        (
+            "the 15th and 1st of the month at noon in europe/berlin",
+            "the 1st and 15th of every month at 12:00 in Europe/Berlin",
+        ),
+        (
+            "last friday of the month at 5pm",
+            "the last Friday of every month at 17:00 UTC",
+        ),
+        ("every other week on friday", "every other week on Friday"),
+        ("every 2 months", "every 2mo"),
+        // `every 2 months` alone is a duration, which clamps to short
+        // months instead of skipping them.
+        ("FREQ=MONTHLY;INTERVAL=2", "every 2 months UTC"),
+        ("0 9 * * MON", "every Monday at 09:00 UTC"),
+        ("0 9 * * 1-5", "weekdays at 09:00 UTC"),
+        (
+            "30 8 1,15 * *",
+            "the 1st and 15th of every month at 08:30 UTC",
+        ),
+        (
+            "CRON_TZ=America/New_York 0 9,17 * * *",
+            "every day at 09:00 and 17:00 in America/New_York",
+        ),
+        // Nothing shorter says the same.
+        ("*/15 * * * *", "*/15 * * * *"),
+        ("FREQ=YEARLY;BYMONTH=3", "RRULE:FREQ=YEARLY;BYMONTH=3"),
+        (
+            "DTSTART:20240101T090000Z\nRRULE:FREQ=DAILY;COUNT=3",
+            "DTSTART:20240101T090000Z\nRRULE:FREQ=DAILY;COUNT=3",
+        ),
+    ];
+    for (input, text) in cases {
+        assert_eq!(describe(input), *text, "{input:?}");
+    }
+}
+
+#[test]
+fn descriptions_parse_back_to_the_same_occurrences() {
+    let inputs = [
+        "hourly",
+        "daily",
+        "monthly",
+        "yearly",
+        "90 min",
+        "every 2mo 1w",
+        "1y 1mo 1d 1h 1m 1s 1ms",
+        "weekdays at 09:00 UTC",
+        "weekends",
+        "every day at 9am and 6pm in Asia/Tokyo",
+        "every 3 days",
+        "every 3 days at 07:15:30",
+        "every other week on tuesday and thursday",
+        "every 4 weeks",
+        "the last day of the month",
+        "every 2 months on the 31st at 23:59",
+        "the second monday and last friday of the month",
+        "0 9 * * MON",
+        "0 0 31 * *",
+        "15 6,18 * * sat,sun",
+        "CRON_TZ=Europe/London 30 1 * * *",
+        "*/10 * * * *",
+        "0 12 1 1 *",
+        "FREQ=MONTHLY;BYDAY=-1FR;BYHOUR=17;BYMINUTE=0;BYSECOND=0",
+        "FREQ=MONTHLY;BYMONTHDAY=-2",
+        "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO;WKST=SU",
+        "FREQ=DAILY;BYHOUR=9;TZID=Europe/Berlin",
+        "FREQ=MONTHLY;BYDAY=MO,TU;BYSETPOS=1",
+        "DTSTART;TZID=Europe/Paris:20240105T083000\nRRULE:FREQ=WEEKLY;UNTIL=20250101T000000Z",
+    ];
+    let starts = [
+        ms("2024-01-31T10:00:00Z"),
+        ms("2024-03-30T23:45:00Z"),
+        ms("2025-10-26T00:30:00Z"),
+    ];
+    for input in inputs {
+        let original = Recurrence::parse(input).unwrap();
+        let text = original.describe();
+        let again =
+            Recurrence::parse(&text).unwrap_or_else(|e| panic!("{input:?} -> {text:?}: {e}"));
+        for start in starts {
+            assert_eq!(
+                again.occurrences(start).take(40).collect::<Vec<_>>(),
+                original.occurrences(start).take(40).collect::<Vec<_>>(),
+                "{input:?} -> {text:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn previews_count_every_payment_in_the_duration() {
+    let start = ms("2024-03-01T12:00:00Z");
+    let schedule = Schedule {
+        recurrence: Recurrence::parse("weekdays at 09:00 UTC").unwrap(),
+        start,
+        end: start + 14 * DAY,
+        amount: "2.5".parse().unwrap(),
+    };
+    let preview = stellrflow_recurrence::preview(&schedule, 3);
+    assert_eq!(preview.description, "weekdays at 09:00 UTC");
+    assert_eq!(
+        preview.next,
+        [
+            ms("2024-03-04T09:00:00Z"),
+            ms("2024-03-05T09:00:00Z"),
+            ms("2024-03-06T09:00:00Z"),
+        ]
+    );
+    // Mar 4-8 and Mar 11-15 at 09:00, all before Mar 15 12:00.
+    assert_eq!(preview.payments, 10);
+    assert_eq!(preview.total.unwrap().to_string(), "25");
+
+    let schedule = Schedule {
+        recurrence: Recurrence::parse("90 min").unwrap(),
+        start,
+        end: start + DAY,
+        amount: Amount::from_stroops(1_000_001),
+    };
+    let preview = stellrflow_recurrence::preview(&schedule, 100);
+    assert_eq!(preview.description, "every 1h 30m");
+    assert_eq!(preview.payments, 16);
+    assert_eq!(preview.next.len(), 16);
+    assert_eq!(preview.next[15], start + DAY);
+    assert_eq!(preview.total.unwrap().to_string(), "1.6000016");
+
+    // Shorter than the interval: nothing is committed.
+    let schedule = Schedule {
+        recurrence: Recurrence::parse("monthly").unwrap(),
+        start,
+        end: start + 7 * DAY,
+        amount: Amount::ONE,
+    };
+    let preview = stellrflow_recurrence::preview(&schedule, 3);
+    assert!(preview.next.is_empty());
+    assert_eq!((preview.payments, preview.total), (0, Some(Amount::ZERO)));
+
+    // More than an amount holds has no total.
+    let schedule = Schedule {
+        recurrence: Recurrence::parse("daily").unwrap(),
+        start,
+        end: start + 7 * DAY,
+        amount: Amount::MAX,
+    };
+    let preview = stellrflow_recurrence::preview(&schedule, 3);
+    assert_eq!((preview.payments, preview.total), (7, None));
+}
+
+fn span() -> impl Strategy<Value = Span> {
+    prop_oneof![
+        (0u32..1_200).prop_map(|months| Span { months, millis: 0 }),
+        (0u64..1 << 45).prop_map(|millis| Span { months: 0, millis }),
+        (0u32..1_200, 0u64..1 << 45).prop_map(|(months, millis)| Span { months, millis }),
+    ]
+    .prop_filter("not zero", |span| !span.is_zero())
+}
+
+proptest! {
+    #[test]
+    fn spans_parse_back_from_their_text(span in span()) {
+        prop_assert_eq!(Span::parse(&span.to_string()), Ok(span));
+        let original = Recurrence::parse(&format!("every {span}")).unwrap();
+        let again = Recurrence::parse(&original.describe()).unwrap();
+        let start = ms("2024-01-31T10:00:00Z");
+        prop_assert_eq!(
+            again.occurrences(start).take(5).collect::<Vec<_>>(),
+            original.occurrences(start).take(5).collect::<Vec<_>>()
+        );
+    }
+}
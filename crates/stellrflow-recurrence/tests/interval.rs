@@ -0,0 +1,153 @@
+use stellrflow_recurrence::{parse_interval_ms, Kind, Recurrence, Span};
+
+const HOUR: u64 = 3_600_000;
+const DAY: u64 = 24 * HOUR;
+
+/// What `parseIntervalFormat` in `interval-parser.ts` returns for each
+/// input, as printed by Node.
+#[test]
+fn the_legacy_grammar_matches_the_bot() {
+    let ok: &[(&str, u64)] = &[
+        ("0", 0),
+        ("3600000", 3_600_000),
+        ("1h", HOUR),
+        ("1.h", HOUR),
+        ("1. h", HOUR),
+        ("  2 h ", 2 * HOUR),
+        ("\u{FEFF}5m", 300_000),
+        ("1.5H", 5_400_000),
+        ("3   d", 3 * DAY),
+        ("0.0001ms", 0),
+        ("1.9ms", 1),
+        ("30 Minutes", 1_800_000),
+        ("2w", 14 * DAY),
+        ("9007199254740991", 9_007_199_254_740_991),
+    ];
+    for (input, ms) in ok {
+        assert_eq!(parse_interval_ms(input), Ok(*ms), "{input:?}");
+    }
+
+    let err: &[(&str, &str)] = &[
+        ("", "Invalid interval format: "),
+        ("1.", "Invalid interval format: 1."),
+        (".5h", "Invalid interval format: .5h"),
+        ("\u{0085}5m", "Invalid interval format: \u{0085}5m"),
+        ("1e3s", "Invalid interval format: 1e3s"),
+        ("10\u{200B}s", "Invalid interval format: 10\u{200B}s"),
+        ("1_000s", "Invalid interval format: 1_000s"),
+        ("-1h", "Invalid interval format: -1h"),
+        ("1h30m", "Invalid interval format: 1h30m"),
+        ("5y", "Unknown time unit: y"),
+        ("5Fortnights", "Unknown time unit: Fortnights"),
+        // NaN in JavaScript, and past `Number.MAX_SAFE_INTEGER`.
+        ("1constructor", "Invalid interval format: 1constructor"),
+        (
+            "9007199254740993",
+            "Invalid interval format: 9007199254740993",
+        ),
+        (
+            "99999999999999999999",
+            "Invalid interval format: 99999999999999999999",
+        ),
+    ];
+    for (input, message) in err {
+        assert_eq!(
+            parse_interval_ms(input),
+            Err(message.to_string()),
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn compound_durations_add_up() {
+    let cases: &[(&str, Span)] = &[
+        ("1d12h", Span::millis(DAY + 12 * HOUR)),
+        ("1h 30m", Span::millis(HOUR + 30 * 60_000)),
+        ("every 2h, 15 min", Span::millis(2 * HOUR + 15 * 60_000)),
+        ("1.5h 30s", Span::millis(5_430_000)),
+        (
+            "2mo 1w",
+            Span {
+                months: 2,
+                millis: 7 * DAY,
+            },
+        ),
+        ("1y", Span::months(12)),
+        ("every 3 months", Span::months(3)),
+        // The legacy grammar wins wherever it applies.
+        ("every 90 min", Span::millis(90 * 60_000)),
+    ];
+    for (input, span) in cases {
+        assert_eq!(Span::parse(input), Ok(*span), "{input:?}");
+    }
+
+    assert_eq!(
+        Span::parse("1.5mo").unwrap_err(),
+        "`1.5mo`: months and years must be whole"
+    );
+    assert_eq!(Span::parse("1h 5x").unwrap_err(), "Unknown time unit: x");
+    assert_eq!(
+        Span::parse("1h and 5m").unwrap_err(),
+        "Invalid interval format: 1h and 5m"
+    );
+}
+
+#[test]
+fn keywords_and_durations_become_spans() {
+    let span = |input: &str| match Recurrence::parse(input).unwrap().kind() {
+        Kind::Every(span) => *span,
+        other => panic!("{input:?} parsed as {other:?}"),
+    };
+    assert_eq!(span("daily"), Span::millis(DAY));
+    assert_eq!(span("Weekly"), Span::millis(7 * DAY));
+    assert_eq!(span("monthly"), Span::months(1));
+    assert_eq!(span("every month"), Span::months(1));
+    assert_eq!(span("yearly"), Span::months(12));
+    assert_eq!(span("1d12h"), Span::millis(DAY + 12 * HOUR));
+
+    assert_eq!(
+        Recurrence::parse("0").unwrap_err().to_string(),
+        "interval `0` is zero"
+    );
+    assert_eq!(
+        Recurrence::parse("5x").unwrap_err().to_string(),
+        "Unknown time unit: x"
+    );
+    assert_eq!(
+        Recurrence::parse("  ").unwrap_err().to_string(),
+        "interval is empty"
+    );
+}
+
+#[test]
+fn months_clamp_to_shorter_months() {
+    // 2024-01-31T10:00:00Z
+    let start = 1_706_695_200_000;
+    let monthly = Recurrence::parse("monthly").unwrap();
+    let dates: Vec<String> = monthly
+        .occurrences(start)
+        .take(3)
+        .map(|at| {
+            chrono::DateTime::from_timestamp_millis(at)
+                .unwrap()
+                .to_rfc3339()
+        })
+        .collect();
+    assert_eq!(
+        dates,
+        [
+            "2024-02-29T10:00:00+00:00",
+            "2024-03-31T10:00:00+00:00",
+            "2024-04-30T10:00:00+00:00",
+        ]
+    );
+}
+
+#[test]
+fn recurrences_serialize_as_their_text() {
+    let recurrence: Recurrence = serde_json::from_str(r#""  1h 30m ""#).unwrap();
+    assert_eq!(recurrence.source(), "1h 30m");
+    assert_eq!(serde_json::to_string(&recurrence).unwrap(), r#""1h 30m""#);
+    assert!(serde_json::from_str::<Recurrence>(r#""5x""#).is_err());
+}
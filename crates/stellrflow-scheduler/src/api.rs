@@ -0,0 +1,132 @@
+//! The bot's AutoPay endpoints, backed by a [`Scheduler`].
+//!
+//! | Method   | Path                                   |                      |
+//! |----------|----------------------------------------|----------------------|
+//! | `POST`   | `/api/autopay/create`                  | create a schedule    |
+//! | `GET`    | `/api/autopay/{chatId}`                | the chat's schedules |
+//! | `DELETE` | `/api/autopay/{scheduleId}`            | cancel a schedule    |
+//! | `GET`    | `/api/autopay/{scheduleId}/payments`   | payment history      |
+//!
+//! Request and response shapes match `telegram-bot.ts`: `{ success: true,
+//! ... }` on success and `{ success: false, error }` with a 4xx/5xx status
+//! otherwise. Timestamps in schedules and payments are Unix milliseconds.
+//!
+//! A created schedule also comes back with its `frequency` in words, its
+//! first `upcomingPayments`, and how many `payments` the duration holds and
+//! their `totalAmount`, for the confirmation message.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{json, Value};
+
+use crate::error::SchedulerError;
+use crate::schedule::NewSchedule;
+use crate::scheduler::Scheduler;
+
+type Shared = Arc<Scheduler>;
+
+const DAY_MS: i64 = 86_400_000;
+
+/// How many payment dates a new schedule's response lists.
+const UPCOMING_PAYMENTS: usize = 3;
+
+/// The AutoPay routes, backed by `scheduler`.
+pub fn router(scheduler: Shared) -> Router {
+    Router::new()
+        .route("/api/autopay/create", post(create))
+        // Listing takes a chat ID and cancelling a schedule ID, as in the bot.
+        .route("/api/autopay/{id}", get(list).delete(cancel))
+        .route("/api/autopay/{id}/payments", get(payments))
+        .with_state(scheduler)
+}
+
+async fn create(
+    State(scheduler): State<Shared>,
+    Json(request): Json<NewSchedule>,
+) -> Result<Json<Value>, ApiError> {
+    let schedule = scheduler.create(request).await?;
+    let iso = |at: i64| {
+        DateTime::from_timestamp_millis(at)
+            .map(|at| at.to_rfc3339_opts(SecondsFormat::Millis, true))
+    };
+    let next_payment = schedule.next_payment.and_then(iso);
+    let preview = schedule.preview(UPCOMING_PAYMENTS);
+    let upcoming: Vec<String> = preview.next.into_iter().filter_map(iso).collect();
+    let duration = schedule.ends_at - schedule.created_at;
+    let duration = if duration % DAY_MS == 0 {
+        json!(duration / DAY_MS)
+    } else {
+        json!(duration as f64 / DAY_MS as f64)
+    };
+    println!(
+        "AutoPay schedule created: {} for {}",
+        schedule.schedule_id, schedule.chat_id
+    );
+    Ok(Json(json!({
+        "success": true,
+        "scheduleId": schedule.schedule_id,
+        "destination": schedule.destination,
+        "amount": schedule.amount,
+        "interval": schedule.interval,
+        "duration": duration,
+        "misfirePolicy": schedule.misfire_policy,
+        "nextPayment": next_payment,
+        "frequency": preview.description,
+        "upcomingPayments": upcoming,
+        "payments": preview.payments,
+        "totalAmount": preview.total.map(|total| total.to_string()),
+        "message": "AutoPay schedule created successfully",
+    })))
+}
+
+async fn list(
+    State(scheduler): State<Shared>,
+    Path(chat_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let schedules = scheduler.list(chat_id).await?;
+    Ok(Json(json!({ "success": true, "schedules": schedules })))
+}
+
+async fn cancel(
+    State(scheduler): State<Shared>,
+    Path(schedule_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    scheduler.cancel(schedule_id).await?;
+    Ok(Json(
+        json!({ "success": true, "message": "Schedule cancelled" }),
+    ))
+}
+
+async fn payments(
+    State(scheduler): State<Shared>,
+    Path(schedule_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let payments = scheduler.payments(schedule_id).await?;
+    Ok(Json(json!({ "success": true, "payments": payments })))
+}
+
+/// A failed request: its status and the message sent as `error`.
+struct ApiError(StatusCode, String);
+
+impl From<SchedulerError> for ApiError {
+    fn from(err: SchedulerError) -> Self {
+        let status = match err {
+            SchedulerError::Invalid(_) => StatusCode::BAD_REQUEST,
+            SchedulerError::NotFound(_) => StatusCode::NOT_FOUND,
+            SchedulerError::Sqlite(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "success": false, "error": self.1 }))).into_response()
+    }
+}
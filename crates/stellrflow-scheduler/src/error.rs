@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+/// Why a scheduler call failed.
+#[derive(Debug, Error)]
+pub enum SchedulerError {
+    /// The schedule request is missing a field or has a malformed one.
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Schedule not found")]
+    NotFound(String),
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+}
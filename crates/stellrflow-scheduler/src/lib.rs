@@ -0,0 +1,46 @@
+//! Recurring AutoPay payments that survive restarts.
+//!
+//! The bot's `/api/autopay/create` used to keep schedules in memory and
+//! never act on them. This crate owns them instead: [`ScheduleStore`] keeps
+//! schedules and every payment attempt in SQLite, and [`Scheduler`] wakes at
+//! each due time, pays through a [`Payer`] (by default the bot's
+//! `/api/wallet/{chatId}/send`, which signs with the chat's Telegram wallet)
+//! and records the outcome.
+//!
+//! - A schedule pays on the occurrences of its [`Recurrence`], counted from
+//!   the creation time so months do not drift: `daily`, `1d12h`,
+//!   `weekdays at 09:00 UTC`, cron or an RRULE. A schedule completes once
+//!   the next occurrence would fall after its duration.
+//! - Occurrences that fell due while the scheduler was down are handled by
+//!   the schedule's [`MisfirePolicy`].
+//! - A payment is recorded as pending before it is submitted. One still
+//!   pending at startup was interrupted with its outcome unknown; it is
+//!   marked failed and never retried, so a crash cannot pay twice.
+//!
+//! [`api::router`] serves the bot's `/api/autopay` endpoints; the
+//! `stellrflow-scheduler` binary runs them on `PORT` (default 3005) next to
+//! the scheduling loop, and the bot forwards to it when
+//! `AUTOPAY_SCHEDULER_URL` is set.
+
+pub mod api;
+mod error;
+mod payer;
+mod schedule;
+mod scheduler;
+mod store;
+
+pub use error::SchedulerError;
+pub use payer::{BotPayer, Payer};
+pub use schedule::{MisfirePolicy, NewSchedule, Payment, PaymentStatus, Schedule, ScheduleStatus};
+pub use scheduler::{Scheduler, Tick, DEFAULT_GRACE};
+pub use stellrflow_recurrence::Recurrence;
+pub use store::ScheduleStore;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The current time as a Unix timestamp in milliseconds.
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
@@ -0,0 +1,60 @@
+//! Runs the AutoPay scheduler and serves its API.
+//!
+//! - `PORT` (default 3005): the API listener.
+//! - `STELLRFLOW_SCHEDULER_DB` (default `stellrflow-scheduler.db`): the
+//!   SQLite file.
+//! - `STELLAR_BOT_URL` (default `http://localhost:3003`): the bot that signs
+//!   payments and delivers notifications.
+//! - `AUTOPAY_MISFIRE_POLICY` (default `catch-up-once`): `skip`,
+//!   `catch-up-once` or `catch-up-all`, for schedules created without one.
+//! - `AUTOPAY_MISFIRE_GRACE` (default `5m`): how late a payment may be and
+//!   still count as on time.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use stellrflow_engine::nodes::{BotClient, DEFAULT_BOT_URL};
+use stellrflow_nodes::interval::parse_interval_ms;
+use stellrflow_scheduler::{api, BotPayer, MisfirePolicy, ScheduleStore, Scheduler};
+use tower_http::cors::CorsLayer;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let port: u16 = env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3005);
+    let db =
+        env::var("STELLRFLOW_SCHEDULER_DB").unwrap_or_else(|_| "stellrflow-scheduler.db".into());
+    let bot_url = env::var("STELLAR_BOT_URL").unwrap_or_else(|_| DEFAULT_BOT_URL.into());
+    let policy: MisfirePolicy = match env::var("AUTOPAY_MISFIRE_POLICY") {
+        Ok(policy) => policy.parse()?,
+        Err(_) => MisfirePolicy::default(),
+    };
+    let grace = match env::var("AUTOPAY_MISFIRE_GRACE") {
+        Ok(grace) => Duration::from_millis(parse_interval_ms(&grace)?),
+        Err(_) => stellrflow_scheduler::DEFAULT_GRACE,
+    };
+
+    let store = Arc::new(ScheduleStore::open(&db)?);
+    let payer = Arc::new(BotPayer::new(BotClient::new(bot_url)));
+    let scheduler = Arc::new(
+        Scheduler::new(store, payer)
+            .with_misfire_policy(policy)
+            .with_grace(grace),
+    );
+
+    let worker = Arc::clone(&scheduler);
+    let worker = tokio::spawn(async move { worker.run().await });
+
+    // The builder runs on its own origin, like calls to the bot's API.
+    let app = api::router(scheduler).layer(CorsLayer::permissive());
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("StellrFlow AutoPay scheduler running on port {port} (database: {db}, misfire policy: {policy})");
+    tokio::select! {
+        served = axum::serve(listener, app) => served?,
+        stopped = worker => stopped??,
+    }
+    Ok(())
+}
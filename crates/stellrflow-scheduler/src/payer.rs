@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use stellrflow_amount::Amount;
+use stellrflow_engine::nodes::BotClient;
+
+/// Makes the payments and tells the chat about them.
+#[async_trait]
+pub trait Payer: Send + Sync {
+    /// Sign and submit a payment of `amount` XLM from the chat's wallet to
+    /// `destination`. The transaction hash, or a user-facing error.
+    async fn pay(&self, chat_id: &str, destination: &str, amount: Amount)
+        -> Result<String, String>;
+
+    /// Send the chat a Markdown message. Delivery failures are ignored.
+    async fn notify(&self, chat_id: &str, message: &str);
+}
+
+/// Pays through the bot's `/api/wallet/{chatId}/send`, which signs with the
+/// chat's Telegram wallet. Payments are marked as AutoPay for the bot's
+/// spending policy check.
+#[derive(Debug, Clone, Default)]
+pub struct BotPayer {
+    bot: BotClient,
+}
+
+impl BotPayer {
+    pub fn new(bot: BotClient) -> Self {
+        Self { bot }
+    }
+}
+
+#[async_trait]
+impl Payer for BotPayer {
+    async fn pay(
+        &self,
+        chat_id: &str,
+        destination: &str,
+        amount: Amount,
+    ) -> Result<String, String> {
+        let body = json!({
+            "destination": destination,
+            "amount": amount,
+            "source": "autopay",
+        });
+        let result = self
+            .bot
+            .post(&format!("/api/wallet/{chat_id}/send"), &body)
+            .await
+            .and_then(|response| response.into_success("Payment failed"))
+            .map_err(|err| err.to_string())?;
+        Ok(result
+            .get("hash")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    async fn notify(&self, chat_id: &str, message: &str) {
+        self.bot.notify(chat_id, message).await;
+    }
+}
@@ -0,0 +1,341 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use stellrflow_amount::Amount;
+use stellrflow_nodes::de;
+use stellrflow_nodes::interval::parse_interval_ms;
+use stellrflow_recurrence::{Preview, Recurrence};
+
+use crate::error::SchedulerError;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1_000;
+
+/// The shortest gap allowed between payments. Anything shorter would turn a
+/// typo such as `5s` into a drained wallet.
+const MIN_INTERVAL_MS: i64 = 60 * 1_000;
+
+/// How many gaps between the first payments are checked against
+/// [`MIN_INTERVAL_MS`].
+const CHECKED_GAPS: usize = 16;
+
+/// How long a schedule runs when the request names no duration.
+const DEFAULT_DURATION_DAYS: u64 = 30;
+
+/// What to do with occurrences that fell due while the scheduler was not
+/// running, i.e. ones more than the grace period late.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MisfirePolicy {
+    /// Record them as skipped and wait for the next on-time occurrence.
+    Skip,
+    /// Make one payment for the latest, and skip the rest.
+    #[default]
+    CatchUpOnce,
+    /// Pay every one of them, oldest first.
+    CatchUpAll,
+}
+
+impl MisfirePolicy {
+    const NAMES: [(MisfirePolicy, &'static str); 3] = [
+        (MisfirePolicy::Skip, "skip"),
+        (MisfirePolicy::CatchUpOnce, "catch-up-once"),
+        (MisfirePolicy::CatchUpAll, "catch-up-all"),
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(policy, _)| *policy == self)
+            .map_or("", |(_, name)| name)
+    }
+}
+
+impl FromStr for MisfirePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == s.trim())
+            .map(|(policy, _)| *policy)
+            .ok_or_else(|| {
+                format!(
+                    "unknown misfire policy `{s}` (expected skip, catch-up-once or catch-up-all)"
+                )
+            })
+    }
+}
+
+impl fmt::Display for MisfirePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleStatus {
+    Active,
+    /// Every occurrence within the duration has been paid or skipped.
+    Completed,
+    Cancelled,
+}
+
+impl ScheduleStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScheduleStatus::Active => "active",
+            ScheduleStatus::Completed => "completed",
+            ScheduleStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl FromStr for ScheduleStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(ScheduleStatus::Active),
+            "completed" => Ok(ScheduleStatus::Completed),
+            "cancelled" => Ok(ScheduleStatus::Cancelled),
+            other => Err(format!("unknown schedule status `{other}`")),
+        }
+    }
+}
+
+/// A stored schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    /// `AP-<created ms>-<n>`, as the bot numbered them.
+    pub schedule_id: String,
+    pub chat_id: String,
+    pub destination: String,
+    /// XLM per payment.
+    pub amount: Amount,
+    pub interval: Recurrence,
+    pub misfire_policy: MisfirePolicy,
+    /// Unix timestamps in milliseconds.
+    pub created_at: i64,
+    /// No occurrence is due after this.
+    pub ends_at: i64,
+    /// The first occurrence that has not been paid or skipped.
+    pub next_index: u32,
+    /// When occurrence `next_index` is due; `None` once the schedule has
+    /// stopped.
+    pub next_payment: Option<i64>,
+    pub status: ScheduleStatus,
+}
+
+impl Schedule {
+    /// When the occurrence after the one due at `at` is due, if it falls
+    /// within the duration. Pass `created_at` for the first.
+    pub fn due_after(&self, at: i64) -> Option<i64> {
+        self.interval
+            .next_after(self.created_at, at)
+            .filter(|next| *next <= self.ends_at)
+    }
+
+    /// The first `n` payments, and what the whole schedule commits to.
+    pub fn preview(&self, n: usize) -> Preview {
+        stellrflow_recurrence::preview(
+            &stellrflow_recurrence::Schedule {
+                recurrence: self.interval.clone(),
+                start: self.created_at,
+                end: self.ends_at,
+                amount: self.amount,
+            },
+            n,
+        )
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == ScheduleStatus::Active
+    }
+}
+
+/// The body of `POST /api/autopay/create`, as the bot accepted it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSchedule {
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub chat_id: String,
+    #[serde(default)]
+    pub destination: String,
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub amount: String,
+    /// `daily` when empty. Anything [`Recurrence`] reads: `12h`, `1d12h`,
+    /// `weekdays at 09:00 UTC`, cron or an RRULE.
+    #[serde(default)]
+    pub interval: String,
+    /// A number of days, as the bot took it, or an interval such as `2w`.
+    /// 30 days when empty.
+    #[serde(default, deserialize_with = "de::string_or_number")]
+    pub duration: String,
+    /// The scheduler's default when absent.
+    #[serde(default)]
+    pub misfire_policy: Option<MisfirePolicy>,
+}
+
+impl NewSchedule {
+    /// Check the request and lay out the schedule it describes.
+    pub(crate) fn build(
+        &self,
+        schedule_id: String,
+        default_policy: MisfirePolicy,
+        now: i64,
+    ) -> Result<Schedule, SchedulerError> {
+        let invalid = SchedulerError::Invalid;
+        let chat_id = self.chat_id.trim();
+        let destination = self.destination.trim();
+        let amount = self.amount.trim();
+        if chat_id.is_empty() || destination.is_empty() || amount.is_empty() {
+            return Err(invalid(
+                "chatId, destination, and amount are required".into(),
+            ));
+        }
+        let digits = chat_id.strip_prefix('-').unwrap_or(chat_id);
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid(format!(
+                "invalid chat ID `{chat_id}`: use the numeric chat ID from /register"
+            )));
+        }
+        if !is_account_address(destination) {
+            return Err(invalid(format!(
+                "`{destination}` is not a Stellar account address"
+            )));
+        }
+        let amount = match amount.parse::<Amount>() {
+            Ok(xlm) if xlm.is_positive() => xlm,
+            _ => return Err(invalid(format!("Invalid amount `{amount}`"))),
+        };
+
+        let interval = match self.interval.trim() {
+            "" => "daily",
+            text => text,
+        };
+        let interval = Recurrence::parse(interval).map_err(|err| invalid(err.to_string()))?;
+        let occurrences: Vec<i64> = interval.occurrences(now).take(CHECKED_GAPS + 1).collect();
+        if occurrences
+            .windows(2)
+            .any(|w| w[1] - w[0] < MIN_INTERVAL_MS)
+        {
+            return Err(invalid(format!(
+                "interval must be at least 1 minute, got `{interval}`"
+            )));
+        }
+        let duration_ms = duration_ms(&self.duration).map_err(invalid)?;
+        let ends_at = i64::try_from(duration_ms)
+            .ok()
+            .and_then(|ms| now.checked_add(ms))
+            .ok_or_else(|| invalid(format!("duration `{}` is too long", self.duration)))?;
+
+        let mut schedule = Schedule {
+            schedule_id,
+            chat_id: chat_id.to_string(),
+            destination: destination.to_string(),
+            amount,
+            interval,
+            misfire_policy: self.misfire_policy.unwrap_or(default_policy),
+            created_at: now,
+            ends_at,
+            next_index: 0,
+            next_payment: None,
+            status: ScheduleStatus::Active,
+        };
+        schedule.next_payment = schedule.due_after(now);
+        if schedule.next_payment.is_none() {
+            return Err(invalid(format!(
+                "duration `{}` is shorter than the interval `{}`, so nothing would be paid",
+                self.duration, schedule.interval
+            )));
+        }
+        let preview = schedule.preview(0);
+        if preview.total.is_none() {
+            return Err(invalid(format!(
+                "{} payments of {amount} XLM add up to more than {} XLM",
+                preview.payments,
+                Amount::MAX
+            )));
+        }
+        Ok(schedule)
+    }
+}
+
+/// Days when numeric, as the bot's `parseInt(duration) || 30` read them;
+/// otherwise the interval grammar.
+fn duration_ms(duration: &str) -> Result<u64, String> {
+    let duration = duration.trim();
+    if duration.is_empty() {
+        return Ok(DEFAULT_DURATION_DAYS * DAY_MS);
+    }
+    let ms = match duration.parse::<f64>() {
+        Ok(days) if days.is_finite() => (days * DAY_MS as f64) as u64,
+        _ => parse_interval_ms(duration)?,
+    };
+    if ms == 0 {
+        return Err(format!("duration must be positive, got `{duration}`"));
+    }
+    Ok(ms)
+}
+
+/// A `G…` strkey: 56 base32 characters.
+fn is_account_address(address: &str) -> bool {
+    address.len() == 56
+        && address.starts_with('G')
+        && address
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || (b'2'..=b'7').contains(&b))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentStatus {
+    /// Submitted, outcome not yet recorded.
+    Pending,
+    Succeeded,
+    Failed,
+    /// Dropped by the misfire policy.
+    Skipped,
+}
+
+impl PaymentStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Succeeded => "succeeded",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Skipped => "skipped",
+        }
+    }
+}
+
+impl FromStr for PaymentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(PaymentStatus::Pending),
+            "succeeded" => Ok(PaymentStatus::Succeeded),
+            "failed" => Ok(PaymentStatus::Failed),
+            "skipped" => Ok(PaymentStatus::Skipped),
+            other => Err(format!("unknown payment status `{other}`")),
+        }
+    }
+}
+
+/// What happened to one occurrence of a schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payment {
+    pub schedule_id: String,
+    pub occurrence: u32,
+    pub due_at: i64,
+    /// When the scheduler acted on it.
+    pub executed_at: i64,
+    pub status: PaymentStatus,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
+}
@@ -0,0 +1,333 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::DateTime;
+use stellrflow_template::shorten;
+use tokio::sync::Notify;
+
+use crate::error::SchedulerError;
+use crate::now_ms;
+use crate::payer::Payer;
+use crate::schedule::{MisfirePolicy, NewSchedule, Payment, Schedule};
+use crate::store::ScheduleStore;
+
+/// How late an occurrence may be paid before it counts as missed.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// The longest the loop sleeps between checks, so a changed clock or a
+/// schedule written by another process is picked up within a minute.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// The least the loop sleeps after a tick with errors. A schedule that
+/// failed is still due, and would otherwise be retried straight away.
+const ERROR_BACKOFF: Duration = Duration::from_secs(30);
+
+/// What one pass over the due schedules did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tick {
+    pub paid: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// The schedules that could not be processed, by ID, and why. The
+    /// others due were processed all the same.
+    pub errors: Vec<(String, String)>,
+}
+
+/// Pays due occurrences of the schedules in a [`ScheduleStore`].
+pub struct Scheduler {
+    store: Arc<ScheduleStore>,
+    payer: Arc<dyn Payer>,
+    default_policy: MisfirePolicy,
+    grace_ms: i64,
+    wake: Notify,
+}
+
+impl Scheduler {
+    pub fn new(store: Arc<ScheduleStore>, payer: Arc<dyn Payer>) -> Self {
+        Self {
+            store,
+            payer,
+            default_policy: MisfirePolicy::default(),
+            grace_ms: DEFAULT_GRACE.as_millis() as i64,
+            wake: Notify::new(),
+        }
+    }
+
+    /// The policy for schedules created without one.
+    pub fn with_misfire_policy(mut self, policy: MisfirePolicy) -> Self {
+        self.default_policy = policy;
+        self
+    }
+
+    /// How late an occurrence may be and still be paid as on time.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace_ms = i64::try_from(grace.as_millis()).unwrap_or(i64::MAX);
+        self
+    }
+
+    pub fn store(&self) -> &Arc<ScheduleStore> {
+        &self.store
+    }
+
+    /// Store a new schedule and wake the loop to account for it.
+    pub async fn create(&self, request: NewSchedule) -> Result<Schedule, SchedulerError> {
+        let policy = self.default_policy;
+        let now = now_ms();
+        let schedule = self
+            .blocking(move |store| store.create(&request, policy, now))
+            .await?;
+        self.wake.notify_one();
+        Ok(schedule)
+    }
+
+    pub async fn list(&self, chat_id: String) -> Result<Vec<Schedule>, SchedulerError> {
+        self.blocking(move |store| store.list(&chat_id)).await
+    }
+
+    pub async fn cancel(&self, schedule_id: String) -> Result<Schedule, SchedulerError> {
+        self.blocking(move |store| store.cancel(&schedule_id)).await
+    }
+
+    pub async fn payments(&self, schedule_id: String) -> Result<Vec<Payment>, SchedulerError> {
+        self.blocking(move |store| store.payments(&schedule_id))
+            .await
+    }
+
+    /// Fail the payments a previous run left pending and tell their chats.
+    pub async fn recover(&self, now: i64) -> Result<Vec<Payment>, SchedulerError> {
+        let interrupted = self.blocking(move |store| store.recover(now)).await?;
+        for payment in &interrupted {
+            let id = payment.schedule_id.clone();
+            let schedule = self.blocking(move |store| store.get(&id)).await?;
+            let message = format!(
+                "⚠️ **AutoPay Payment Interrupted**\n\n\
+                 **Amount:** {} XLM\n\
+                 **To:** `{}`\n\n\
+                 The scheduler stopped while this payment was being sent, so it may or \
+                 may not have gone through. It will not be retried; check your wallet's \
+                 history.",
+                schedule.amount,
+                shorten(&schedule.destination),
+            );
+            self.payer.notify(&schedule.chat_id, &message).await;
+        }
+        Ok(interrupted)
+    }
+
+    /// Act on every occurrence due at or before `now`. Only fails if the
+    /// due schedules cannot be listed; a schedule that fails is recorded in
+    /// [`Tick::errors`] and left due for the next tick.
+    pub async fn tick(&self, now: i64) -> Result<Tick, SchedulerError> {
+        let mut tick = Tick::default();
+        let due = self.blocking(move |store| store.due(now)).await?;
+        for schedule in due {
+            if let Err(err) = self.process(&schedule, now, &mut tick).await {
+                tick.errors.push((schedule.schedule_id, err.to_string()));
+            }
+        }
+        Ok(tick)
+    }
+
+    /// Recover, then pay schedules as they fall due. Only returns if
+    /// recovery fails; later errors are logged and retried after
+    /// [`ERROR_BACKOFF`].
+    pub async fn run(&self) -> Result<(), SchedulerError> {
+        let interrupted = self.recover(now_ms()).await?;
+        if !interrupted.is_empty() {
+            eprintln!(
+                "AutoPay: marked {} interrupted payment(s) as failed",
+                interrupted.len()
+            );
+        }
+        loop {
+            let errored = match self.tick(now_ms()).await {
+                Ok(tick) => {
+                    if (tick.paid, tick.failed, tick.skipped) != (0, 0, 0) {
+                        println!(
+                            "AutoPay: {} paid, {} failed, {} skipped",
+                            tick.paid, tick.failed, tick.skipped
+                        );
+                    }
+                    for (schedule_id, err) in &tick.errors {
+                        eprintln!("AutoPay: schedule {schedule_id}: {err}");
+                    }
+                    !tick.errors.is_empty()
+                }
+                Err(err) => {
+                    eprintln!("AutoPay: {err}");
+                    true
+                }
+            };
+            let wait = match self.blocking(|store| store.next_due()).await {
+                Ok(Some(at)) => Duration::from_millis(u64::try_from(at - now_ms()).unwrap_or(0)),
+                Ok(None) => MAX_SLEEP,
+                Err(err) => {
+                    eprintln!("AutoPay: {err}");
+                    MAX_SLEEP
+                }
+            };
+            if errored {
+                tokio::time::sleep(wait.clamp(ERROR_BACKOFF, MAX_SLEEP)).await;
+                continue;
+            }
+            tokio::select! {
+                () = tokio::time::sleep(wait.min(MAX_SLEEP)) => {}
+                () = self.wake.notified() => {}
+            }
+        }
+    }
+
+    async fn process(
+        &self,
+        schedule: &Schedule,
+        now: i64,
+        tick: &mut Tick,
+    ) -> Result<(), SchedulerError> {
+        let mut due = Vec::new();
+        let mut next = schedule.next_payment.map(|at| (schedule.next_index, at));
+        while let Some((index, at)) = next.filter(|(_, at)| *at <= now) {
+            due.push((index, at));
+            next = schedule.due_after(at).map(|at| (index + 1, at));
+        }
+
+        let last = due.len().saturating_sub(1);
+        let mut missed = Vec::new();
+        for (i, &occurrence) in due.iter().enumerate() {
+            let on_time = now - occurrence.1 <= self.grace_ms;
+            let pay = on_time
+                || match schedule.misfire_policy {
+                    MisfirePolicy::Skip => false,
+                    MisfirePolicy::CatchUpOnce => i == last,
+                    MisfirePolicy::CatchUpAll => true,
+                };
+            if !pay {
+                missed.push(occurrence);
+                continue;
+            }
+            // Either call stops early if the schedule was cancelled meanwhile.
+            if !self.skip(schedule, &mut missed, now, tick).await?
+                || !self.pay(schedule, occurrence, now, tick).await?
+            {
+                return Ok(());
+            }
+        }
+        self.skip(schedule, &mut missed, now, tick).await?;
+        Ok(())
+    }
+
+    /// Record `missed` as skipped. `false` if the schedule has stopped.
+    async fn skip(
+        &self,
+        schedule: &Schedule,
+        missed: &mut Vec<(u32, i64)>,
+        now: i64,
+        tick: &mut Tick,
+    ) -> Result<bool, SchedulerError> {
+        if missed.is_empty() {
+            return Ok(true);
+        }
+        let occurrences = std::mem::take(missed);
+        let count = occurrences.len();
+        let id = schedule.schedule_id.clone();
+        let Some(updated) = self
+            .blocking(move |store| store.skip(&id, &occurrences, now))
+            .await?
+        else {
+            return Ok(false);
+        };
+        tick.skipped += count;
+
+        let message = format!(
+            "⏭️ **AutoPay Payments Skipped**\n\n\
+             {count} payment(s) of {} XLM to `{}` fell due while the scheduler was \
+             offline and were skipped (misfire policy: {}).\n\n{}",
+            schedule.amount,
+            shorten(&schedule.destination),
+            schedule.misfire_policy,
+            next_line(&updated),
+        );
+        self.payer.notify(&schedule.chat_id, &message).await;
+        Ok(true)
+    }
+
+    /// Claim, make and record one payment. `false` if the schedule has
+    /// stopped.
+    async fn pay(
+        &self,
+        schedule: &Schedule,
+        (occurrence, due_at): (u32, i64),
+        now: i64,
+        tick: &mut Tick,
+    ) -> Result<bool, SchedulerError> {
+        let id = schedule.schedule_id.clone();
+        let Some(updated) = self
+            .blocking(move |store| store.begin(&id, (occurrence, due_at), now))
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        let outcome = self
+            .payer
+            .pay(&schedule.chat_id, &schedule.destination, schedule.amount)
+            .await;
+        let id = schedule.schedule_id.clone();
+        let recorded = outcome.clone();
+        // The chat hears of the payment even if it cannot be recorded.
+        let finished = self
+            .blocking(move |store| store.finish(&id, occurrence, &recorded, now))
+            .await;
+
+        let to = shorten(&schedule.destination);
+        let message = match &outcome {
+            Ok(hash) => {
+                tick.paid += 1;
+                format!(
+                    "✅ **AutoPay Payment Sent**\n\n\
+                     **Amount:** {} XLM\n\
+                     **To:** `{to}`\n\
+                     **Tx:** `{hash}`\n\n{}",
+                    schedule.amount,
+                    next_line(&updated),
+                )
+            }
+            Err(error) => {
+                tick.failed += 1;
+                format!(
+                    "❌ **AutoPay Payment Failed**\n\n\
+                     **Amount:** {} XLM\n\
+                     **To:** `{to}`\n\n\
+                     {error}\n\n{}",
+                    schedule.amount,
+                    next_line(&updated),
+                )
+            }
+        };
+        self.payer.notify(&schedule.chat_id, &message).await;
+        finished?;
+        Ok(true)
+    }
+
+    /// Run a store call on the blocking thread pool.
+    async fn blocking<T, F>(&self, f: F) -> Result<T, SchedulerError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ScheduleStore) -> Result<T, SchedulerError> + Send + 'static,
+    {
+        let store = Arc::clone(&self.store);
+        tokio::task::spawn_blocking(move || f(&store))
+            .await
+            .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+    }
+}
+
+/// When the schedule pays next, or that it has finished.
+fn next_line(schedule: &Schedule) -> String {
+    match schedule
+        .next_payment
+        .and_then(DateTime::from_timestamp_millis)
+    {
+        Some(at) => format!("**Next payment:** {}", at.format("%Y-%m-%d %H:%M UTC")),
+        None => format!("Schedule `{}` is complete.", schedule.schedule_id),
+    }
+}
@@ -0,0 +1,371 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+
+use crate::error::SchedulerError;
+use crate::schedule::{
+    MisfirePolicy, NewSchedule, Payment, PaymentStatus, Schedule, ScheduleStatus,
+};
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE schedules (
+        schedule_id    TEXT    PRIMARY KEY,
+        chat_id        TEXT    NOT NULL,
+        destination    TEXT    NOT NULL,
+        amount         TEXT    NOT NULL,
+        interval       TEXT    NOT NULL,
+        misfire_policy TEXT    NOT NULL,
+        created_at     INTEGER NOT NULL,
+        ends_at        INTEGER NOT NULL,
+        next_index     INTEGER NOT NULL,
+        next_payment   INTEGER,
+        status         TEXT    NOT NULL
+    );
+    CREATE INDEX schedules_by_chat ON schedules (chat_id, created_at);
+    CREATE INDEX schedules_due ON schedules (next_payment) WHERE status = 'active';
+
+    CREATE TABLE payments (
+        schedule_id TEXT    NOT NULL REFERENCES schedules (schedule_id),
+        occurrence  INTEGER NOT NULL,
+        due_at      INTEGER NOT NULL,
+        executed_at INTEGER NOT NULL,
+        status      TEXT    NOT NULL,
+        tx_hash     TEXT,
+        error       TEXT,
+        PRIMARY KEY (schedule_id, occurrence)
+    );
+"];
+
+const SCHEDULE_COLUMNS: &str = "schedule_id, chat_id, destination, amount, interval, \
+     misfire_policy, created_at, ends_at, next_index, next_payment, status";
+
+const PAYMENT_COLUMNS: &str =
+    "schedule_id, occurrence, due_at, executed_at, status, tx_hash, error";
+
+/// Recorded for payments that were pending when the scheduler stopped.
+const INTERRUPTED: &str = "The scheduler stopped before this payment's outcome was \
+     recorded; check the wallet's history before paying it again";
+
+/// Schedules and their payment history in a single SQLite database.
+#[derive(Debug)]
+pub struct ScheduleStore {
+    conn: Mutex<Connection>,
+}
+
+impl ScheduleStore {
+    /// Open (creating if needed) the database at `path` and bring its schema
+    /// up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SchedulerError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    /// A private database that lives as long as the store.
+    pub fn open_in_memory() -> Result<Self, SchedulerError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, SchedulerError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let applied: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let tx = conn.transaction()?;
+        for (version, migration) in (1..).zip(MIGRATIONS).skip(applied as usize) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", version)?;
+        }
+        tx.commit()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // Every write runs in a transaction that is rolled back on drop, so
+        // a panic mid-call leaves nothing half-written.
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Store the schedule `request` describes, created at `now`.
+    pub fn create(
+        &self,
+        request: &NewSchedule,
+        default_policy: MisfirePolicy,
+        now: i64,
+    ) -> Result<Schedule, SchedulerError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let count: i64 = tx.query_row("SELECT COUNT(*) FROM schedules", [], |row| row.get(0))?;
+        let schedule = request.build(format!("AP-{now}-{}", count + 1), default_policy, now)?;
+        tx.execute(
+            &format!("INSERT INTO schedules ({SCHEDULE_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"),
+            params![
+                schedule.schedule_id,
+                schedule.chat_id,
+                schedule.destination,
+                schedule.amount.to_string(),
+                schedule.interval.to_string(),
+                schedule.misfire_policy.as_str(),
+                schedule.created_at,
+                schedule.ends_at,
+                schedule.next_index,
+                schedule.next_payment,
+                schedule.status.as_str(),
+            ],
+        )?;
+        tx.commit()?;
+        Ok(schedule)
+    }
+
+    pub fn get(&self, schedule_id: &str) -> Result<Schedule, SchedulerError> {
+        find(&self.conn(), schedule_id)
+    }
+
+    /// The chat's schedules, oldest first, including stopped ones.
+    pub fn list(&self, chat_id: &str) -> Result<Vec<Schedule>, SchedulerError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SCHEDULE_COLUMNS} FROM schedules
+             WHERE chat_id = ?1
+             ORDER BY created_at, schedule_id"
+        ))?;
+        let rows = stmt.query_map([chat_id], schedule)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Stop an active schedule. Stopped schedules are returned unchanged.
+    pub fn cancel(&self, schedule_id: &str) -> Result<Schedule, SchedulerError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE schedules SET status = 'cancelled', next_payment = NULL
+             WHERE schedule_id = ?1 AND status = 'active'",
+            [schedule_id],
+        )?;
+        let schedule = find(&tx, schedule_id)?;
+        tx.commit()?;
+        Ok(schedule)
+    }
+
+    /// Active schedules with an occurrence due at or before `now`.
+    pub fn due(&self, now: i64) -> Result<Vec<Schedule>, SchedulerError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SCHEDULE_COLUMNS} FROM schedules
+             WHERE status = 'active' AND next_payment <= ?1
+             ORDER BY next_payment, schedule_id"
+        ))?;
+        let rows = stmt.query_map([now], schedule)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// When the earliest active schedule is next due.
+    pub fn next_due(&self) -> Result<Option<i64>, SchedulerError> {
+        Ok(self.conn().query_row(
+            "SELECT MIN(next_payment) FROM schedules WHERE status = 'active'",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Every occurrence the scheduler has acted on, in order.
+    pub fn payments(&self, schedule_id: &str) -> Result<Vec<Payment>, SchedulerError> {
+        let conn = self.conn();
+        find(&conn, schedule_id)?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {PAYMENT_COLUMNS} FROM payments
+             WHERE schedule_id = ?1
+             ORDER BY occurrence"
+        ))?;
+        let rows = stmt.query_map([schedule_id], payment)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Record `occurrences` (consecutive, starting at the schedule's next
+    /// one) as skipped and move past them. `None` if the schedule has been
+    /// stopped or has already moved on.
+    pub fn skip(
+        &self,
+        schedule_id: &str,
+        occurrences: &[(u32, i64)],
+        now: i64,
+    ) -> Result<Option<Schedule>, SchedulerError> {
+        self.claim(schedule_id, occurrences, PaymentStatus::Skipped, now)
+    }
+
+    /// Record `occurrence` as pending and move past it, before the payment
+    /// is submitted. `None` if the schedule has been stopped or has already
+    /// moved on, in which case the payment must not be made.
+    pub fn begin(
+        &self,
+        schedule_id: &str,
+        occurrence: (u32, i64),
+        now: i64,
+    ) -> Result<Option<Schedule>, SchedulerError> {
+        self.claim(schedule_id, &[occurrence], PaymentStatus::Pending, now)
+    }
+
+    /// Record the outcome of a payment started with [`begin`](Self::begin):
+    /// the transaction hash or the error.
+    pub fn finish(
+        &self,
+        schedule_id: &str,
+        occurrence: u32,
+        outcome: &Result<String, String>,
+        now: i64,
+    ) -> Result<(), SchedulerError> {
+        let (status, hash, error) = match outcome {
+            Ok(hash) => (PaymentStatus::Succeeded, Some(hash), None),
+            Err(error) => (PaymentStatus::Failed, None, Some(error)),
+        };
+        self.conn().execute(
+            "UPDATE payments SET status = ?1, tx_hash = ?2, error = ?3, executed_at = ?4
+             WHERE schedule_id = ?5 AND occurrence = ?6 AND status = 'pending'",
+            params![status.as_str(), hash, error, now, schedule_id, occurrence],
+        )?;
+        Ok(())
+    }
+
+    /// Mark payments left pending by a previous run as failed and return
+    /// them. They may or may not have reached the network, so they are
+    /// never retried.
+    pub fn recover(&self, now: i64) -> Result<Vec<Payment>, SchedulerError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let pending: Vec<Payment> = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {PAYMENT_COLUMNS} FROM payments
+                 WHERE status = 'pending'
+                 ORDER BY schedule_id, occurrence"
+            ))?;
+            let rows = stmt.query_map([], payment)?;
+            rows.collect::<Result<_, _>>()?
+        };
+        tx.execute(
+            "UPDATE payments SET status = 'failed', error = ?1, executed_at = ?2
+             WHERE status = 'pending'",
+            params![INTERRUPTED, now],
+        )?;
+        tx.commit()?;
+        Ok(pending
+            .into_iter()
+            .map(|p| Payment {
+                status: PaymentStatus::Failed,
+                error: Some(INTERRUPTED.to_string()),
+                executed_at: now,
+                ..p
+            })
+            .collect())
+    }
+
+    fn claim(
+        &self,
+        schedule_id: &str,
+        occurrences: &[(u32, i64)],
+        status: PaymentStatus,
+        now: i64,
+    ) -> Result<Option<Schedule>, SchedulerError> {
+        let (Some(&(first, _)), Some(&last)) = (occurrences.first(), occurrences.last()) else {
+            return Ok(None);
+        };
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let current = find(&tx, schedule_id)?;
+        if !current.is_active() || current.next_index != first {
+            return Ok(None);
+        }
+        for &(occurrence, due_at) in occurrences {
+            tx.execute(
+                "INSERT INTO payments (schedule_id, occurrence, due_at, executed_at, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![schedule_id, occurrence, due_at, now, status.as_str()],
+            )?;
+        }
+        let schedule = advance(&tx, current, last)?;
+        tx.commit()?;
+        Ok(Some(schedule))
+    }
+}
+
+/// Point the schedule at the occurrence after `last` (its index and due
+/// time), completing it if that one falls after its duration.
+fn advance(
+    tx: &Transaction<'_>,
+    mut schedule: Schedule,
+    (last, last_due): (u32, i64),
+) -> Result<Schedule, SchedulerError> {
+    schedule.next_index = last + 1;
+    schedule.next_payment = schedule.due_after(last_due);
+    if schedule.next_payment.is_none() {
+        schedule.status = ScheduleStatus::Completed;
+    }
+    tx.execute(
+        "UPDATE schedules SET next_index = ?1, next_payment = ?2, status = ?3
+         WHERE schedule_id = ?4",
+        params![
+            schedule.next_index,
+            schedule.next_payment,
+            schedule.status.as_str(),
+            schedule.schedule_id,
+        ],
+    )?;
+    Ok(schedule)
+}
+
+fn find(conn: &Connection, schedule_id: &str) -> Result<Schedule, SchedulerError> {
+    conn.query_row(
+        &format!("SELECT {SCHEDULE_COLUMNS} FROM schedules WHERE schedule_id = ?1"),
+        [schedule_id],
+        schedule,
+    )
+    .optional()?
+    .ok_or_else(|| SchedulerError::NotFound(schedule_id.to_string()))
+}
+
+fn schedule(row: &Row<'_>) -> rusqlite::Result<Schedule> {
+    Ok(Schedule {
+        schedule_id: row.get(0)?,
+        chat_id: row.get(1)?,
+        destination: row.get(2)?,
+        amount: parsed(row, 3)?,
+        interval: parsed(row, 4)?,
+        misfire_policy: parsed(row, 5)?,
+        created_at: row.get(6)?,
+        ends_at: row.get(7)?,
+        next_index: row.get(8)?,
+        next_payment: row.get(9)?,
+        status: parsed(row, 10)?,
+    })
+}
+
+fn payment(row: &Row<'_>) -> rusqlite::Result<Payment> {
+    Ok(Payment {
+        schedule_id: row.get(0)?,
+        occurrence: row.get(1)?,
+        due_at: row.get(2)?,
+        executed_at: row.get(3)?,
+        status: parsed(row, 4)?,
+        tx_hash: row.get(5)?,
+        error: row.get(6)?,
+    })
+}
+
+/// A text column holding the amount, the interval or one of the schedule
+/// enums.
+fn parsed<T>(row: &Row<'_>, index: usize) -> rusqlite::Result<T>
+where
+    T: FromStr,
+    T::Err: ToString,
+{
+    let text: String = row.get(index)?;
+    text.parse().map_err(|err: T::Err| {
+        rusqlite::Error::FromSqlConversionFailure(index, Type::Text, err.to_string().into())
+    })
+}
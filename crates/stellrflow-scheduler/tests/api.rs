@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use stellrflow_engine::nodes::BotClient;
+use stellrflow_scheduler::{api, BotPayer, ScheduleStore, Scheduler};
+use tower::ServiceExt;
+
+fn app() -> Router {
+    let store = Arc::new(ScheduleStore::open_in_memory().unwrap());
+    // Nothing is due during these tests, so the bot is never called.
+    let payer = Arc::new(BotPayer::new(BotClient::new("http://127.0.0.1:9")));
+    api::router(Arc::new(Scheduler::new(store, payer)))
+}
+
+async fn call(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn schedules_round_trip_in_the_bots_shapes() {
+    let app = app();
+
+    // What the engine's `autopay` node sends.
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/autopay/create",
+        Some(json!({
+            "chatId": 42,
+            "destination": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H",
+            "amount": 2.5,
+            "interval": "weekly",
+            "duration": 14,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["success"], true);
+    assert_eq!(body["amount"], "2.5");
+    assert_eq!(body["interval"], "weekly");
+    assert_eq!(body["duration"], 14);
+    assert_eq!(body["misfirePolicy"], "catch-up-once");
+    assert!(body["nextPayment"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(body["frequency"], "every week");
+    assert_eq!(body["upcomingPayments"].as_array().unwrap().len(), 2);
+    assert_eq!(body["upcomingPayments"][0], body["nextPayment"]);
+    assert_eq!(body["payments"], 2);
+    assert_eq!(body["totalAmount"], "5");
+    let id = body["scheduleId"].as_str().unwrap().to_string();
+    assert!(id.starts_with("AP-"));
+
+    let (_, body) = call(&app, Method::GET, "/api/autopay/42", None).await;
+    assert_eq!(body["schedules"][0]["scheduleId"], id);
+    assert_eq!(body["schedules"][0]["status"], "active");
+
+    let (_, body) = call(
+        &app,
+        Method::GET,
+        &format!("/api/autopay/{id}/payments"),
+        None,
+    )
+    .await;
+    assert_eq!(body, json!({ "success": true, "payments": [] }));
+
+    let (status, body) = call(&app, Method::DELETE, &format!("/api/autopay/{id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["message"], "Schedule cancelled");
+    let (_, body) = call(&app, Method::GET, "/api/autopay/42", None).await;
+    assert_eq!(body["schedules"][0]["status"], "cancelled");
+    assert_eq!(body["schedules"][0]["nextPayment"], Value::Null);
+}
+
+#[tokio::test]
+async fn errors_use_the_bots_envelope() {
+    let app = app();
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/autopay/create",
+        Some(json!({ "chatId": "42", "interval": "daily" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body,
+        json!({ "success": false, "error": "chatId, destination, and amount are required" })
+    );
+
+    let (status, body) = call(&app, Method::DELETE, "/api/autopay/AP-1-1", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Schedule not found");
+}
@@ -0,0 +1,378 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use stellrflow_amount::Amount;
+use stellrflow_scheduler::{
+    MisfirePolicy, NewSchedule, Payer, PaymentStatus, Schedule, ScheduleStatus, ScheduleStore,
+    Scheduler, SchedulerError, Tick,
+};
+
+const CHAT: &str = "123456789";
+const DESTINATION: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+/// 2023-11-14 22:13:20 UTC.
+const T0: i64 = 1_700_000_000_000;
+const DAY: i64 = 86_400_000;
+
+/// Records payments instead of making them; fails them all when `error`
+/// is set.
+#[derive(Default)]
+struct FakePayer {
+    paid: Mutex<Vec<String>>,
+    messages: Mutex<Vec<String>>,
+    error: Mutex<Option<String>>,
+}
+
+#[async_trait]
+impl Payer for FakePayer {
+    async fn pay(
+        &self,
+        _chat_id: &str,
+        destination: &str,
+        amount: Amount,
+    ) -> Result<String, String> {
+        if let Some(error) = self.error.lock().unwrap().clone() {
+            return Err(error);
+        }
+        let mut paid = self.paid.lock().unwrap();
+        paid.push(format!("{amount} to {destination}"));
+        Ok(format!("hash{}", paid.len()))
+    }
+
+    async fn notify(&self, _chat_id: &str, message: &str) {
+        self.messages.lock().unwrap().push(message.to_string());
+    }
+}
+
+fn request(interval: &str, duration: &str) -> NewSchedule {
+    NewSchedule {
+        chat_id: CHAT.into(),
+        destination: DESTINATION.into(),
+        amount: "5".into(),
+        interval: interval.into(),
+        duration: duration.into(),
+        misfire_policy: None,
+    }
+}
+
+fn scheduler(store: ScheduleStore) -> (Scheduler, Arc<FakePayer>) {
+    let payer = Arc::new(FakePayer::default());
+    let scheduler = Scheduler::new(Arc::new(store), payer.clone());
+    (scheduler, payer)
+}
+
+fn create(scheduler: &Scheduler, request: &NewSchedule, policy: MisfirePolicy) -> Schedule {
+    scheduler.store().create(request, policy, T0).unwrap()
+}
+
+fn statuses(scheduler: &Scheduler, id: &str) -> Vec<PaymentStatus> {
+    let payments = scheduler.store().payments(id).unwrap();
+    payments.into_iter().map(|p| p.status).collect()
+}
+
+#[tokio::test]
+async fn pays_each_occurrence_until_the_duration_ends() {
+    let (scheduler, payer) = scheduler(ScheduleStore::open_in_memory().unwrap());
+    let schedule = create(&scheduler, &request("daily", "3"), MisfirePolicy::Skip);
+    assert_eq!(schedule.next_payment, Some(T0 + DAY));
+    assert_eq!(schedule.ends_at, T0 + 3 * DAY);
+
+    assert_eq!(scheduler.tick(T0 + DAY - 1).await.unwrap(), Tick::default());
+    for day in 1..=3 {
+        let tick = scheduler.tick(T0 + day * DAY).await.unwrap();
+        assert_eq!(tick.paid, 1, "day {day}");
+        // A second pass at the same time finds nothing left to do.
+        assert_eq!(
+            scheduler.tick(T0 + day * DAY).await.unwrap(),
+            Tick::default()
+        );
+    }
+    assert_eq!(payer.paid.lock().unwrap().len(), 3);
+
+    let done = scheduler.store().get(&schedule.schedule_id).unwrap();
+    assert_eq!(done.status, ScheduleStatus::Completed);
+    assert_eq!(done.next_payment, None);
+    assert_eq!(scheduler.store().next_due().unwrap(), None);
+
+    let payments = scheduler.store().payments(&schedule.schedule_id).unwrap();
+    assert_eq!(payments.len(), 3);
+    assert_eq!(payments[2].due_at, T0 + 3 * DAY);
+    assert_eq!(payments[2].tx_hash.as_deref(), Some("hash3"));
+    let messages = payer.messages.lock().unwrap();
+    assert!(messages[0].contains("**Next payment:** 2023-11-16 22:13 UTC"));
+    assert!(messages[2].contains("is complete"));
+}
+
+#[tokio::test]
+async fn misfire_policies_decide_what_downtime_costs() {
+    let cases = [
+        (MisfirePolicy::Skip, 0, vec![PaymentStatus::Skipped; 3]),
+        (
+            MisfirePolicy::CatchUpOnce,
+            1,
+            vec![
+                PaymentStatus::Skipped,
+                PaymentStatus::Skipped,
+                PaymentStatus::Succeeded,
+            ],
+        ),
+        (
+            MisfirePolicy::CatchUpAll,
+            3,
+            vec![PaymentStatus::Succeeded; 3],
+        ),
+    ];
+    for (policy, paid, expected) in cases {
+        let (scheduler, payer) = scheduler(ScheduleStore::open_in_memory().unwrap());
+        let schedule = create(&scheduler, &request("daily", "30"), policy);
+
+        // Down from before the first payment until half a day after the third.
+        let tick = scheduler.tick(T0 + 3 * DAY + DAY / 2).await.unwrap();
+        assert_eq!((tick.paid, tick.skipped), (paid, 3 - paid), "{policy}");
+        assert_eq!(
+            statuses(&scheduler, &schedule.schedule_id),
+            expected,
+            "{policy}"
+        );
+        assert_eq!(payer.paid.lock().unwrap().len(), paid);
+
+        let after = scheduler.store().get(&schedule.schedule_id).unwrap();
+        assert_eq!(after.next_index, 3);
+        assert_eq!(after.next_payment, Some(T0 + 4 * DAY));
+    }
+}
+
+#[tokio::test]
+async fn on_time_payments_are_made_whatever_the_policy() {
+    let (scheduler, _) = scheduler(ScheduleStore::open_in_memory().unwrap());
+    let scheduler = scheduler.with_grace(Duration::from_secs(60));
+    let schedule = create(&scheduler, &request("daily", "30"), MisfirePolicy::Skip);
+
+    // Back 30 seconds after the third payment was due: the first two were
+    // missed, the third is still on time.
+    let tick = scheduler.tick(T0 + 3 * DAY + 30_000).await.unwrap();
+    assert_eq!((tick.paid, tick.skipped), (1, 2));
+    assert_eq!(
+        statuses(&scheduler, &schedule.schedule_id),
+        [
+            PaymentStatus::Skipped,
+            PaymentStatus::Skipped,
+            PaymentStatus::Succeeded
+        ]
+    );
+}
+
+#[tokio::test]
+async fn failed_payments_are_recorded_and_not_retried() {
+    let (scheduler, payer) = scheduler(ScheduleStore::open_in_memory().unwrap());
+    *payer.error.lock().unwrap() = Some("No wallet found for this chat".into());
+    let schedule = create(
+        &scheduler,
+        &request("weekly", "4w"),
+        MisfirePolicy::CatchUpAll,
+    );
+
+    let tick = scheduler.tick(T0 + 7 * DAY).await.unwrap();
+    assert_eq!(tick.failed, 1);
+    assert_eq!(scheduler.tick(T0 + 7 * DAY).await.unwrap(), Tick::default());
+
+    let payments = scheduler.store().payments(&schedule.schedule_id).unwrap();
+    assert_eq!(payments[0].status, PaymentStatus::Failed);
+    assert_eq!(
+        payments[0].error.as_deref(),
+        Some("No wallet found for this chat")
+    );
+    let after = scheduler.store().get(&schedule.schedule_id).unwrap();
+    assert_eq!(after.status, ScheduleStatus::Active);
+    assert_eq!(after.next_payment, Some(T0 + 14 * DAY));
+    assert!(payer.messages.lock().unwrap()[0].contains("No wallet found for this chat"));
+}
+
+#[tokio::test]
+async fn a_schedule_that_errors_does_not_hold_up_the_others() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("autopay.db");
+    let (scheduler, payer) = scheduler(ScheduleStore::open(&path).unwrap());
+    // Due first, and its payments cannot be written.
+    let broken = scheduler
+        .store()
+        .create(&request("daily", "30"), MisfirePolicy::Skip, T0 - 1)
+        .unwrap();
+    let healthy = create(&scheduler, &request("daily", "30"), MisfirePolicy::Skip);
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute_batch(&format!(
+            "CREATE TRIGGER broken BEFORE INSERT ON payments
+             WHEN NEW.schedule_id = '{}'
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+            broken.schedule_id
+        ))
+        .unwrap();
+
+    let tick = scheduler.tick(T0 + DAY).await.unwrap();
+    assert_eq!(tick.paid, 1);
+    assert_eq!(tick.errors.len(), 1);
+    assert_eq!(tick.errors[0].0, broken.schedule_id);
+    assert!(tick.errors[0].1.contains("disk full"), "{:?}", tick.errors);
+    assert_eq!(
+        statuses(&scheduler, &healthy.schedule_id),
+        [PaymentStatus::Succeeded]
+    );
+    assert!(statuses(&scheduler, &broken.schedule_id).is_empty());
+    assert_eq!(payer.paid.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_payment_that_cannot_be_recorded_is_still_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("autopay.db");
+    let (scheduler, payer) = scheduler(ScheduleStore::open(&path).unwrap());
+    let schedule = create(&scheduler, &request("daily", "30"), MisfirePolicy::Skip);
+    rusqlite::Connection::open(&path)
+        .unwrap()
+        .execute_batch(
+            "CREATE TRIGGER broken BEFORE UPDATE ON payments
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END;",
+        )
+        .unwrap();
+
+    let tick = scheduler.tick(T0 + DAY).await.unwrap();
+    assert_eq!(tick.paid, 1);
+    assert_eq!(tick.errors[0].0, schedule.schedule_id);
+    let messages = payer.messages.lock().unwrap();
+    assert!(messages[0].contains("AutoPay Payment Sent"), "{messages:?}");
+    assert!(messages[0].contains("hash1"));
+}
+
+#[tokio::test]
+async fn interrupted_payments_fail_on_restart_instead_of_paying_twice() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("autopay.db");
+
+    let id = {
+        let store = ScheduleStore::open(&path).unwrap();
+        let schedule = store
+            .create(&request("daily", "30"), MisfirePolicy::CatchUpAll, T0)
+            .unwrap();
+        // The process dies after claiming the payment, before recording
+        // what happened to it.
+        store
+            .begin(&schedule.schedule_id, (0, T0 + DAY), T0 + DAY)
+            .unwrap()
+            .unwrap();
+        schedule.schedule_id
+    };
+
+    let (scheduler, payer) = scheduler(ScheduleStore::open(&path).unwrap());
+    let interrupted = scheduler.recover(T0 + DAY + 5_000).await.unwrap();
+    assert_eq!(interrupted.len(), 1);
+    assert_eq!(interrupted[0].status, PaymentStatus::Failed);
+    assert_eq!(statuses(&scheduler, &id), [PaymentStatus::Failed]);
+    assert!(payer.messages.lock().unwrap()[0].contains("Interrupted"));
+
+    assert_eq!(
+        scheduler.tick(T0 + DAY + 5_000).await.unwrap(),
+        Tick::default()
+    );
+    assert!(payer.paid.lock().unwrap().is_empty());
+    assert!(scheduler.recover(T0 + 2 * DAY).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn cancelled_schedules_stop_paying() {
+    let (scheduler, payer) = scheduler(ScheduleStore::open_in_memory().unwrap());
+    let schedule = create(&scheduler, &request("daily", "30"), MisfirePolicy::Skip);
+
+    let cancelled = scheduler
+        .cancel(schedule.schedule_id.clone())
+        .await
+        .unwrap();
+    assert_eq!(cancelled.status, ScheduleStatus::Cancelled);
+    assert_eq!(scheduler.tick(T0 + 5 * DAY).await.unwrap(), Tick::default());
+    assert!(payer.paid.lock().unwrap().is_empty());
+    assert!(matches!(
+        scheduler.cancel("AP-1-1".into()).await,
+        Err(SchedulerError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn occurrences_follow_the_interval_from_creation() {
+    // 2024-01-31 12:00 UTC: months clamp to their last day without drifting.
+    let jan_31 = 1_706_702_400_000;
+    let feb_29 = 1_709_208_000_000;
+    let mar_31 = 1_711_886_400_000;
+    let (scheduler, _) = scheduler(ScheduleStore::open_in_memory().unwrap());
+    let monthly = scheduler
+        .store()
+        .create(&request("monthly", "90"), MisfirePolicy::Skip, jan_31)
+        .unwrap();
+    assert_eq!(monthly.next_payment, Some(feb_29));
+    scheduler.tick(feb_29).await.unwrap();
+    let monthly = scheduler.store().get(&monthly.schedule_id).unwrap();
+    assert_eq!(monthly.next_payment, Some(mar_31));
+
+    // T0 is a Tuesday evening; the next weekday 09:00 is Wednesday's.
+    let weekdays = create(
+        &scheduler,
+        &request("weekdays at 09:00 UTC", "7"),
+        MisfirePolicy::Skip,
+    );
+    let wednesday_9am = 1_700_038_800_000;
+    assert_eq!(weekdays.next_payment, Some(wednesday_9am));
+    assert_eq!(weekdays.interval.source(), "weekdays at 09:00 UTC");
+    scheduler.tick(wednesday_9am + 2 * DAY).await.unwrap();
+    let payments = scheduler.store().payments(&weekdays.schedule_id).unwrap();
+    let due: Vec<i64> = payments.iter().map(|p| p.due_at).collect();
+    assert_eq!(
+        due,
+        [wednesday_9am, wednesday_9am + DAY, wednesday_9am + 2 * DAY]
+    );
+    // Then Monday's; the weekend is left out.
+    let weekdays = scheduler.store().get(&weekdays.schedule_id).unwrap();
+    assert_eq!(weekdays.next_index, 3);
+    assert_eq!(weekdays.next_payment, Some(wednesday_9am + 5 * DAY));
+}
+
+#[test]
+fn requests_are_checked() {
+    let store = ScheduleStore::open_in_memory().unwrap();
+    let error = |request: NewSchedule| {
+        store
+            .create(&request, MisfirePolicy::Skip, T0)
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert_eq!(
+        error(NewSchedule::default()),
+        "chatId, destination, and amount are required"
+    );
+    assert_eq!(
+        error(NewSchedule {
+            amount: "-1".into(),
+            ..request("daily", "30")
+        }),
+        "Invalid amount `-1`"
+    );
+    assert!(error(NewSchedule {
+        destination: "GABC".into(),
+        ..request("daily", "30")
+    })
+    .contains("not a Stellar account address"));
+    assert!(error(request("5s", "30")).contains("at least 1 minute"));
+    assert_eq!(
+        error(request("FREQ=DAILY;BYSECOND=0,30", "30")),
+        "interval must be at least 1 minute, got `FREQ=DAILY;BYSECOND=0,30`"
+    );
+    assert!(error(request("fortnightly", "30")).starts_with("`fortnightly` is not an interval"));
+    assert!(error(request("daily", "12h")).contains("nothing would be paid"));
+    assert_eq!(
+        error(NewSchedule {
+            amount: "900000000000".into(),
+            ..request("daily", "30")
+        }),
+        "30 payments of 900000000000 XLM add up to more than 922337203685.4775807 XLM"
+    );
+    assert!(store.list(CHAT).unwrap().is_empty());
+}
@@ -0,0 +1,243 @@
+//! Signing by someone outside the process, such as a Freighter user.
+//!
+//! A [`DeferredSigner`] parks each transaction in a [`ParkingLot`], tells a
+//! [`ParkHook`] (which can send the user a link to the builder's
+//! `/send-transaction` page) and waits. When the signed XDR is posted to
+//! `/api/transaction/submit`, [`router`] hands the user's signature back
+//! to the waiting caller, which carries on and submits the transaction
+//! itself.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use crate::envelope::{network_passphrase, Envelope, Signature};
+use crate::error::SignerError;
+use crate::Signer;
+
+/// How long a [`DeferredSigner`] waits for a signature by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// A transaction waiting for an external signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParkedTransaction {
+    /// The transaction hash, in hex.
+    pub hash: String,
+    /// The account whose signature is needed.
+    pub account: String,
+    pub network_passphrase: String,
+    /// The unsigned envelope, for the wallet to sign.
+    pub xdr: String,
+}
+
+/// Told about each transaction as it is parked.
+#[async_trait]
+pub trait ParkHook: Send + Sync {
+    async fn parked(&self, transaction: &ParkedTransaction);
+}
+
+/// Transactions waiting for external signatures, by hash.
+#[derive(Debug, Default)]
+pub struct ParkingLot {
+    waiting: Mutex<HashMap<String, Waiting>>,
+}
+
+#[derive(Debug)]
+struct Waiting {
+    transaction: ParkedTransaction,
+    resume: oneshot::Sender<Signature>,
+}
+
+impl ParkingLot {
+    pub fn new() -> Self {
+        ParkingLot::default()
+    }
+
+    /// Everything still waiting, ordered by hash.
+    pub fn pending(&self) -> Vec<ParkedTransaction> {
+        let mut pending: Vec<_> = self
+            .lock()
+            .values()
+            .map(|waiting| waiting.transaction.clone())
+            .collect();
+        pending.sort_by(|a, b| a.hash.cmp(&b.hash));
+        pending
+    }
+
+    /// Resumes the parked transaction that `signed` is a signed copy of,
+    /// with the parked account's signature from it.
+    pub fn submit(&self, signed: &Envelope) -> Result<ParkedTransaction, SignerError> {
+        let hash = signed.hash_hex();
+        let mut waiting = self.lock();
+        let entry = waiting
+            .get(&hash)
+            .filter(|entry| entry.transaction.network_passphrase == signed.network_passphrase())
+            .ok_or_else(|| SignerError::NotParked(hash.clone()))?;
+        let account = entry.transaction.account.clone();
+        let signature = signed.signature_by(&account).ok_or(SignerError::Unsigned {
+            hash: hash.clone(),
+            account,
+        })?;
+        let entry = waiting.remove(&hash).expect("the entry was just found");
+        // The caller may have given up in the meantime; the signature is
+        // then simply not used.
+        let _ = entry.resume.send(signature);
+        Ok(entry.transaction)
+    }
+
+    fn park(
+        &self,
+        transaction: ParkedTransaction,
+    ) -> Result<oneshot::Receiver<Signature>, SignerError> {
+        let mut waiting = self.lock();
+        if waiting.contains_key(&transaction.hash) {
+            return Err(SignerError::InvalidEnvelope(format!(
+                "transaction {} is already waiting for a signature",
+                transaction.hash
+            )));
+        }
+        let (resume, resumed) = oneshot::channel();
+        waiting.insert(
+            transaction.hash.clone(),
+            Waiting {
+                transaction,
+                resume,
+            },
+        );
+        Ok(resumed)
+    }
+
+    fn abandon(&self, hash: &str) {
+        self.lock().remove(hash);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Waiting>> {
+        self.waiting.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Signs for an account whose key is held elsewhere, by waiting for its
+/// holder to sign.
+#[derive(Clone)]
+pub struct DeferredSigner {
+    account: String,
+    lot: Arc<ParkingLot>,
+    hook: Arc<dyn ParkHook>,
+    timeout: Duration,
+}
+
+impl DeferredSigner {
+    pub fn new(account: impl Into<String>, lot: Arc<ParkingLot>, hook: Arc<dyn ParkHook>) -> Self {
+        DeferredSigner {
+            account: account.into(),
+            lot,
+            hook,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl std::fmt::Debug for DeferredSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeferredSigner")
+            .field("account", &self.account)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Signer for DeferredSigner {
+    fn public_key(&self) -> &str {
+        &self.account
+    }
+
+    /// Parks the transaction and waits until its signed XDR is submitted,
+    /// or fails with [`SignerError::Timeout`].
+    async fn sign(&self, envelope: &Envelope) -> Result<Signature, SignerError> {
+        let transaction = ParkedTransaction {
+            hash: envelope.hash_hex(),
+            account: self.account.clone(),
+            network_passphrase: envelope.network_passphrase().to_string(),
+            xdr: envelope.to_xdr(),
+        };
+        let hash = transaction.hash.clone();
+        let resumed = self.lot.park(transaction.clone())?;
+        self.hook.parked(&transaction).await;
+        match tokio::time::timeout(self.timeout, resumed).await {
+            Ok(Ok(signature)) => Ok(signature),
+            _ => {
+                self.lot.abandon(&hash);
+                Err(SignerError::Timeout(hash))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Submission {
+    signed_xdr: String,
+    /// `testnet` (the default, like the bot), `mainnet` or a passphrase.
+    #[serde(default)]
+    network: Option<String>,
+}
+
+/// `POST /api/transaction/submit`, in the bot's request and response
+/// shapes: `{ signedXdr, network }` resumes the parked transaction and
+/// answers `{ success: true, hash, resumed: true }`. A transaction nobody is
+/// waiting for is a 404, so the bot can submit it as before.
+pub fn router(lot: Arc<ParkingLot>) -> Router {
+    Router::new()
+        .route("/api/transaction/submit", post(submit))
+        .with_state(lot)
+}
+
+async fn submit(
+    State(lot): State<Arc<ParkingLot>>,
+    Json(submission): Json<Submission>,
+) -> Result<Json<Value>, ApiError> {
+    let network = submission.network.as_deref().unwrap_or("testnet");
+    let signed = Envelope::from_xdr(&submission.signed_xdr, network_passphrase(network))?;
+    let transaction = lot.submit(&signed)?;
+    Ok(Json(json!({
+        "success": true,
+        "hash": transaction.hash,
+        "resumed": true,
+    })))
+}
+
+/// A failed request: its status and the message sent as `error`.
+struct ApiError(StatusCode, String);
+
+impl From<SignerError> for ApiError {
+    fn from(err: SignerError) -> Self {
+        let status = match err {
+            SignerError::NotParked(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "success": false, "error": self.1 }))).into_response()
+    }
+}
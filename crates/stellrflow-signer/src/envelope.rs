@@ -0,0 +1,226 @@
+use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+use stellar_strkey::ed25519::PublicKey;
+use stellar_xdr::curr::{
+    DecoratedSignature, Hash, Limits, MuxedAccount, Preconditions, ReadXdr, Transaction,
+    TransactionEnvelope, TransactionExt, TransactionSignaturePayload,
+    TransactionSignaturePayloadTaggedTransaction, WriteXdr,
+};
+
+use crate::error::SignerError;
+
+/// The passphrase of the test network, as `Networks.TESTNET` in the SDK.
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+/// The passphrase of the public network, as `Networks.PUBLIC` in the SDK.
+pub const PUBLIC_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+/// The passphrase for the bot's `testnet`/`mainnet` setting; anything else is
+/// taken as a passphrase itself.
+pub fn network_passphrase(network: &str) -> &str {
+    match network {
+        "testnet" => TESTNET_PASSPHRASE,
+        "mainnet" | "public" => PUBLIC_PASSPHRASE,
+        other => other,
+    }
+}
+
+/// A transaction envelope and the network it is meant for, which together
+/// decide what gets signed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    envelope: TransactionEnvelope,
+    network_passphrase: String,
+}
+
+impl Envelope {
+    pub fn new(envelope: TransactionEnvelope, network_passphrase: impl Into<String>) -> Self {
+        Envelope {
+            envelope,
+            network_passphrase: network_passphrase.into(),
+        }
+    }
+
+    /// Reads base64 XDR, as `TransactionBuilder.toXDR()` and Freighter
+    /// produce it.
+    pub fn from_xdr(xdr: &str, network_passphrase: impl Into<String>) -> Result<Self, SignerError> {
+        let envelope = TransactionEnvelope::from_xdr_base64(xdr.trim(), Limits::none())
+            .map_err(|e| SignerError::InvalidEnvelope(e.to_string()))?;
+        Ok(Envelope::new(envelope, network_passphrase))
+    }
+
+    pub fn to_xdr(&self) -> String {
+        self.envelope
+            .to_xdr_base64(Limits::none())
+            .expect("an envelope that was read or built always writes")
+    }
+
+    pub fn envelope(&self) -> &TransactionEnvelope {
+        &self.envelope
+    }
+
+    pub fn into_envelope(self) -> TransactionEnvelope {
+        self.envelope
+    }
+
+    pub fn network_passphrase(&self) -> &str {
+        &self.network_passphrase
+    }
+
+    /// The transaction hash: what every signer signs, and the ID Horizon
+    /// reports.
+    pub fn hash(&self) -> [u8; 32] {
+        let tagged = match &self.envelope {
+            TransactionEnvelope::TxV0(v0) => {
+                // Pre-protocol-13 envelopes are signed as the equivalent
+                // `Transaction`.
+                let tx = &v0.tx;
+                TransactionSignaturePayloadTaggedTransaction::Tx(Transaction {
+                    source_account: MuxedAccount::Ed25519(tx.source_account_ed25519.clone()),
+                    fee: tx.fee,
+                    seq_num: tx.seq_num.clone(),
+                    cond: match &tx.time_bounds {
+                        Some(bounds) => Preconditions::Time(bounds.clone()),
+                        None => Preconditions::None,
+                    },
+                    memo: tx.memo.clone(),
+                    operations: tx.operations.clone(),
+                    ext: TransactionExt::V0,
+                })
+            }
+            TransactionEnvelope::Tx(v1) => {
+                TransactionSignaturePayloadTaggedTransaction::Tx(v1.tx.clone())
+            }
+            TransactionEnvelope::TxFeeBump(bump) => {
+                TransactionSignaturePayloadTaggedTransaction::TxFeeBump(bump.tx.clone())
+            }
+        };
+        let payload = TransactionSignaturePayload {
+            network_id: Hash(Sha256::digest(self.network_passphrase.as_bytes()).into()),
+            tagged_transaction: tagged,
+        };
+        let bytes = payload
+            .to_xdr(Limits::none())
+            .expect("a signature payload always writes");
+        Sha256::digest(bytes).into()
+    }
+
+    /// [`Envelope::hash`] in lowercase hex.
+    pub fn hash_hex(&self) -> String {
+        hex(&self.hash())
+    }
+
+    pub fn signatures(&self) -> &[DecoratedSignature] {
+        match &self.envelope {
+            TransactionEnvelope::TxV0(v0) => &v0.signatures,
+            TransactionEnvelope::Tx(v1) => &v1.signatures,
+            TransactionEnvelope::TxFeeBump(bump) => &bump.signatures,
+        }
+    }
+
+    /// Appends a signature. Stellar allows at most 20.
+    pub fn add_signature(&mut self, signature: Signature) -> Result<(), SignerError> {
+        let signatures = match &mut self.envelope {
+            TransactionEnvelope::TxV0(v0) => &mut v0.signatures,
+            TransactionEnvelope::Tx(v1) => &mut v1.signatures,
+            TransactionEnvelope::TxFeeBump(bump) => &mut bump.signatures,
+        };
+        let mut all = signatures.to_vec();
+        all.push(signature.into());
+        *signatures = all
+            .try_into()
+            .map_err(|_| SignerError::InvalidEnvelope("more than 20 signatures".into()))?;
+        Ok(())
+    }
+
+    /// The signature in the envelope that `account` made over this
+    /// transaction, if there is one.
+    pub fn signature_by(&self, account: &str) -> Option<Signature> {
+        let hash = self.hash();
+        self.signatures().iter().find_map(|decorated| {
+            let signature = Signature::try_from(decorated).ok()?;
+            signature.verify(account, &hash).ok()?;
+            Some(signature)
+        })
+    }
+
+    /// Has `signer` sign, checks the signature and appends it.
+    pub async fn sign_with(&mut self, signer: &dyn crate::Signer) -> Result<(), SignerError> {
+        let signature = signer.sign(self).await?;
+        signature.verify(signer.public_key(), &self.hash())?;
+        self.add_signature(signature)
+    }
+}
+
+/// An ed25519 signature and the hint that tells Stellar which key made it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    /// The last four bytes of the signing account's public key.
+    pub hint: [u8; 4],
+    pub signature: [u8; 64],
+}
+
+impl Signature {
+    /// Pairs a raw signature with the hint for `account`.
+    pub fn new(account: &str, signature: [u8; 64]) -> Result<Self, SignerError> {
+        let key = account_key(account)?;
+        Ok(Signature {
+            hint: hint(&key),
+            signature,
+        })
+    }
+
+    /// Checks that `account` signed `hash`.
+    pub fn verify(&self, account: &str, hash: &[u8; 32]) -> Result<(), SignerError> {
+        let key = account_key(account)?;
+        let bad = || SignerError::BadSignature(account.to_string());
+        if self.hint != hint(&key) {
+            return Err(bad());
+        }
+        let key = VerifyingKey::from_bytes(&key).map_err(|_| bad())?;
+        key.verify(hash, &Ed25519Signature::from_bytes(&self.signature))
+            .map_err(|_| bad())
+    }
+}
+
+impl From<Signature> for DecoratedSignature {
+    fn from(signature: Signature) -> Self {
+        DecoratedSignature {
+            hint: stellar_xdr::curr::SignatureHint(signature.hint),
+            signature: stellar_xdr::curr::Signature(
+                signature
+                    .signature
+                    .to_vec()
+                    .try_into()
+                    .expect("64 bytes fit a signature"),
+            ),
+        }
+    }
+}
+
+impl TryFrom<&DecoratedSignature> for Signature {
+    type Error = SignerError;
+
+    fn try_from(decorated: &DecoratedSignature) -> Result<Self, Self::Error> {
+        let signature = <[u8; 64]>::try_from(decorated.signature.0.as_slice())
+            .map_err(|_| SignerError::InvalidEnvelope("signature is not 64 bytes".into()))?;
+        Ok(Signature {
+            hint: decorated.hint.0,
+            signature,
+        })
+    }
+}
+
+/// The raw ed25519 key of a `G…` account.
+pub(crate) fn account_key(account: &str) -> Result<[u8; 32], SignerError> {
+    PublicKey::from_string(account)
+        .map(|key| key.0)
+        .map_err(|_| SignerError::InvalidAccount(account.to_string()))
+}
+
+fn hint(key: &[u8; 32]) -> [u8; 4] {
+    [key[28], key[29], key[30], key[31]]
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
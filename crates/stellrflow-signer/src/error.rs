@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+/// Why a signature could not be produced.
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("not a valid Stellar secret key")]
+    InvalidSecret,
+    #[error("not a valid Stellar account: {0}")]
+    InvalidAccount(String),
+    /// The XDR is not a transaction envelope.
+    #[error("invalid transaction envelope: {0}")]
+    InvalidEnvelope(String),
+    /// No signer holds a key for this account.
+    #[error("no signer for account {0}")]
+    UnknownAccount(String),
+    /// The signer answered with a signature that does not verify for its
+    /// account and the transaction.
+    #[error("signature from {0} does not verify")]
+    BadSignature(String),
+    /// The remote signer refused or failed; its message is kept.
+    #[error("remote signer: {0}")]
+    Remote(String),
+    /// No external signature arrived in time for a deferred transaction.
+    #[error("transaction {0} was not signed in time")]
+    Timeout(String),
+    /// The posted transaction is not one waiting for a signature.
+    #[error("no transaction {0} is waiting for a signature")]
+    NotParked(String),
+    /// The posted transaction carries no valid signature from the account
+    /// it was parked for.
+    #[error("transaction {hash} is not signed by {account}")]
+    Unsigned { hash: String, account: String },
+    #[error(transparent)]
+    Keystore(#[from] stellrflow_keystore::KeystoreError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
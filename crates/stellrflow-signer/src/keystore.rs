@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use stellrflow_keystore::{Keyring, Keystore};
+
+use crate::envelope::{Envelope, Signature};
+use crate::error::SignerError;
+use crate::local::LocalSigner;
+use crate::{Signer, Signers};
+
+/// Signs for a chat's Telegram wallet in the encrypted keystore.
+///
+/// The secret is decrypted for each signature and dropped right after, so
+/// it is never held in memory between transactions.
+#[derive(Debug, Clone)]
+pub struct KeystoreSigner {
+    keystore: Arc<Keystore>,
+    chat_id: String,
+    public_key: String,
+}
+
+impl KeystoreSigner {
+    pub fn new(keystore: Arc<Keystore>, chat_id: impl Into<String>) -> Result<Self, SignerError> {
+        let chat_id = chat_id.into();
+        let public_key = keystore
+            .wallets()
+            .into_iter()
+            .find(|wallet| wallet.chat_id == chat_id)
+            .map(|wallet| wallet.public_key)
+            .ok_or_else(|| stellrflow_keystore::KeystoreError::NotFound(chat_id.clone()))?;
+        Ok(KeystoreSigner {
+            keystore,
+            chat_id,
+            public_key,
+        })
+    }
+
+    pub fn chat_id(&self) -> &str {
+        &self.chat_id
+    }
+}
+
+#[async_trait]
+impl Signer for KeystoreSigner {
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    async fn sign(&self, envelope: &Envelope) -> Result<Signature, SignerError> {
+        let hash = envelope.hash();
+        let keystore = Arc::clone(&self.keystore);
+        let chat_id = self.chat_id.clone();
+        // Key derivation is deliberately slow; keep it off the runtime.
+        tokio::task::spawn_blocking(move || {
+            let secret = keystore.secret_key(&chat_id)?;
+            Ok(LocalSigner::from_secret(&secret)?.sign_hash(&hash))
+        })
+        .await
+        .map_err(|e| SignerError::Io(std::io::Error::other(e)))?
+    }
+}
+
+/// The Telegram wallets in a keystore file, by account.
+///
+/// The file is re-read for every lookup, so a wallet created after the
+/// signer started can sign straight away.
+#[derive(Debug, Clone)]
+pub struct KeystoreWallets {
+    path: String,
+    keyring: Keyring,
+}
+
+impl KeystoreWallets {
+    /// Opens the keystore at `path` once, so a wrong file or master key
+    /// fails now rather than on the first signature.
+    pub fn open(path: impl Into<String>, keyring: Keyring) -> Result<Self, SignerError> {
+        let path = path.into();
+        Keystore::open(&path, keyring.clone())?;
+        Ok(KeystoreWallets { path, keyring })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl Signers for KeystoreWallets {
+    async fn signer_for(&self, account: &str) -> Result<Arc<dyn Signer>, SignerError> {
+        let keystore = Keystore::open(&self.path, self.keyring.clone())?;
+        let chat_id = keystore
+            .wallets()
+            .into_iter()
+            .find(|wallet| wallet.public_key == account)
+            .map(|wallet| wallet.chat_id)
+            .ok_or_else(|| SignerError::UnknownAccount(account.to_string()))?;
+        Ok(Arc::new(KeystoreSigner::new(Arc::new(keystore), chat_id)?))
+    }
+}
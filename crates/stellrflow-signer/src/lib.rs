@@ -0,0 +1,73 @@
+//! Transaction signing for StellrFlow, behind one [`Signer`] trait.
+//!
+//! The bot signs in several ways today: `/send` calls
+//! `Keypair.fromSecret(wallet.secretKey)` inline, `sendXLM` takes a raw
+//! secret, and Freighter users are sent a link to `/send-transaction`. Here
+//! each of those is a [`Signer`] that turns an [`Envelope`] into a
+//! [`Signature`] over its transaction hash:
+//!
+//! - [`LocalSigner`]: an `S…` key held in process.
+//! - [`KeystoreSigner`]: a chat's Telegram wallet in the encrypted
+//!   `stellrflow-keystore`, decrypted only while signing.
+//! - [`RemoteSigner`]: another process on a Unix socket, standing in for an
+//!   HSM or KMS; [`remote::serve`] is the other end, and the
+//!   `stellrflow-signer` binary serves the keystore's wallets with it.
+//! - [`DeferredSigner`]: parks the transaction until its holder signs it
+//!   elsewhere (Freighter) and the signed XDR is posted to
+//!   `/api/transaction/submit`; see [`deferred`].
+//!
+//! [`Signers`] finds the signer for an account, as a signing server must:
+//! [`KeystoreWallets`] finds the keystore's Telegram wallets by their
+//! public keys.
+//!
+//! [`Envelope::sign_with`] checks a signer's signature against its account
+//! before adding it, so a misbehaving backend cannot slip in a bad one.
+
+pub mod deferred;
+mod envelope;
+mod error;
+mod keystore;
+mod local;
+#[cfg(unix)]
+pub mod remote;
+
+pub use deferred::{DeferredSigner, ParkHook, ParkedTransaction, ParkingLot};
+pub use envelope::{
+    network_passphrase, Envelope, Signature, PUBLIC_PASSPHRASE, TESTNET_PASSPHRASE,
+};
+pub use error::SignerError;
+pub use keystore::{KeystoreSigner, KeystoreWallets};
+pub use local::LocalSigner;
+#[cfg(unix)]
+pub use remote::{RemoteSigner, RemoteSigners};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+/// Something that can sign transactions for one account.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// The `G…` account this signer signs for.
+    fn public_key(&self) -> &str;
+
+    /// The account's signature over `envelope`'s transaction hash. It is
+    /// not added to the envelope; see [`Envelope::sign_with`].
+    async fn sign(&self, envelope: &Envelope) -> Result<Signature, SignerError>;
+}
+
+/// The keys a signing server holds, by account.
+#[async_trait]
+pub trait Signers: Send + Sync {
+    async fn signer_for(&self, account: &str) -> Result<Arc<dyn Signer>, SignerError>;
+}
+
+#[async_trait]
+impl Signers for HashMap<String, Arc<dyn Signer>> {
+    async fn signer_for(&self, account: &str) -> Result<Arc<dyn Signer>, SignerError> {
+        self.get(account)
+            .cloned()
+            .ok_or_else(|| SignerError::UnknownAccount(account.to_string()))
+    }
+}
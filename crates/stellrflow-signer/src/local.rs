@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use ed25519_dalek::{Signer as _, SigningKey};
+use stellar_strkey::ed25519::{PrivateKey, PublicKey};
+
+use crate::envelope::{Envelope, Signature};
+use crate::error::SignerError;
+use crate::Signer;
+
+/// Signs in process with a key held in memory, as `Keypair.fromSecret` does
+/// in the bot. The key is wiped when the signer is dropped.
+pub struct LocalSigner {
+    key: SigningKey,
+    public_key: String,
+}
+
+impl LocalSigner {
+    /// From an `S…` seed.
+    pub fn from_secret(secret_key: &str) -> Result<Self, SignerError> {
+        let seed =
+            PrivateKey::from_string(secret_key.trim()).map_err(|_| SignerError::InvalidSecret)?;
+        Ok(LocalSigner::from_seed(seed.0))
+    }
+
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let key = SigningKey::from_bytes(&seed);
+        let public_key = PublicKey(key.verifying_key().to_bytes()).to_string();
+        LocalSigner {
+            key,
+            public_key: public_key.as_str().to_string(),
+        }
+    }
+
+    pub(crate) fn sign_hash(&self, hash: &[u8; 32]) -> Signature {
+        Signature::new(&self.public_key, self.key.sign(hash).to_bytes())
+            .expect("the signer's own account is valid")
+    }
+}
+
+impl std::fmt::Debug for LocalSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalSigner")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Signer for LocalSigner {
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    async fn sign(&self, envelope: &Envelope) -> Result<Signature, SignerError> {
+        Ok(self.sign_hash(&envelope.hash()))
+    }
+}
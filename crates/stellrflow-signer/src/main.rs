@@ -0,0 +1,76 @@
+//! Serves the keystore's Telegram wallets as a remote signer.
+//!
+//! - `SIGNER_SOCKET` (default `stellrflow-signer.sock`): the Unix socket to
+//!   listen on, readable and writable by this user only. A stale socket
+//!   is replaced; any other file at that path is left alone.
+//! - `WALLETS_FILE` (default `data/wallets.json`): the encrypted keystore,
+//!   re-read for every request so new wallets can sign straight away.
+//! - `KEYSTORE_MASTER_KEY` and `KEYSTORE_PREVIOUS_KEYS`: as for
+//!   `stellrflow-keystore`.
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    use std::env;
+    use std::sync::Arc;
+
+    let socket = env::var("SIGNER_SOCKET").unwrap_or_else(|_| "stellrflow-signer.sock".into());
+    let path = env::var("WALLETS_FILE").unwrap_or_else(|_| "data/wallets.json".into());
+    let keyring = stellrflow_keystore::Keyring::from_env()?;
+    let wallets = stellrflow_signer::KeystoreWallets::open(&path, keyring)?;
+
+    let listener = socket::bind(&socket)?;
+    println!("StellrFlow signer listening on {socket} (keystore: {path})");
+    stellrflow_signer::remote::serve(listener, Arc::new(wallets)).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("stellrflow-signer needs Unix sockets");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+mod socket {
+    use std::fs::{self, DirBuilder, Permissions};
+    use std::io;
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use std::path::Path;
+
+    use tokio::net::UnixListener;
+
+    /// Listens on `path`, readable and writable by this user only.
+    ///
+    /// Whoever can connect can have any wallet sign, so the socket is bound
+    /// inside a private directory, restricted there and only then moved into
+    /// place: there is no moment, whatever the umask, when others could
+    /// connect. An existing socket at `path` is taken to be stale and
+    /// replaced; anything else there is an error.
+    pub fn bind(path: &str) -> io::Result<UnixListener> {
+        match fs::symlink_metadata(path) {
+            Ok(meta) if !meta.file_type().is_socket() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{path} exists and is not a socket"),
+                ));
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        let private = format!("{path}.{}", std::process::id());
+        DirBuilder::new().mode(0o700).create(&private)?;
+        let staged = Path::new(&private).join("socket");
+        let bound = UnixListener::bind(&staged).and_then(|listener| {
+            fs::set_permissions(&staged, Permissions::from_mode(0o600))?;
+            fs::rename(&staged, path)?;
+            Ok(listener)
+        });
+        // The directory is empty unless binding failed part way.
+        let _ = fs::remove_file(&staged);
+        fs::remove_dir(&private)?;
+        bound
+    }
+}
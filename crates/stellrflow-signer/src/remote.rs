@@ -0,0 +1,226 @@
+//! An out-of-process signer reached over a Unix socket, standing in for an
+//! HSM or a cloud KMS: the keys live in another process, which sees every
+//! transaction it is asked to sign.
+//!
+//! The protocol is one JSON object per line. A request names the account,
+//! the network and the envelope:
+//!
+//! ```json
+//! {"account":"G…","networkPassphrase":"Test SDF Network ; September 2015","envelope":"AAAAAg…"}
+//! ```
+//!
+//! and the answer is `{"signature":"<base64>"}` with the 64-byte ed25519
+//! signature of the transaction hash, or `{"error":"…"}`. The signer hashes
+//! the envelope itself rather than signing a hash it is handed. Neither side
+//! reads a line longer than [`MAX_LINE`].
+
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::envelope::{Envelope, Signature};
+use crate::error::SignerError;
+use crate::{Signer, Signers};
+
+/// How long [`RemoteSigner`] waits for an answer by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The longest line, newline included, either side reads: far more than
+/// an envelope of 100 operations takes.
+pub const MAX_LINE: u64 = 256 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    account: String,
+    network_passphrase: String,
+    envelope: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Asks the signer listening on a Unix socket to sign for `account`.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    socket: PathBuf,
+    account: String,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn new(socket: impl Into<PathBuf>, account: impl Into<String>) -> Self {
+        RemoteSigner {
+            socket: socket.into(),
+            account: account.into(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn call(&self, request: &Request) -> Result<Response, SignerError> {
+        let mut stream = UnixStream::connect(&self.socket).await?;
+        let mut line =
+            serde_json::to_string(request).map_err(|e| SignerError::Remote(e.to_string()))?;
+        line.push('\n');
+        if line.len() as u64 > MAX_LINE {
+            return Err(SignerError::Remote(format!(
+                "request longer than {MAX_LINE} bytes"
+            )));
+        }
+        stream.write_all(line.as_bytes()).await?;
+        let answer = read_line(&mut BufReader::new(stream))
+            .await?
+            .unwrap_or_default();
+        serde_json::from_str(&answer)
+            .map_err(|_| SignerError::Remote(format!("unreadable answer {:?}", answer.trim())))
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> &str {
+        &self.account
+    }
+
+    async fn sign(&self, envelope: &Envelope) -> Result<Signature, SignerError> {
+        let request = Request {
+            account: self.account.clone(),
+            network_passphrase: envelope.network_passphrase().to_string(),
+            envelope: envelope.to_xdr(),
+        };
+        let response = tokio::time::timeout(self.timeout, self.call(&request))
+            .await
+            .map_err(|_| SignerError::Remote("timed out".into()))??;
+        if let Some(error) = response.error {
+            return Err(SignerError::Remote(error));
+        }
+        let bad = || SignerError::BadSignature(self.account.clone());
+        let bytes = BASE64
+            .decode(response.signature.ok_or_else(bad)?)
+            .map_err(|_| bad())?;
+        let signature = Signature::new(
+            &self.account,
+            <[u8; 64]>::try_from(bytes.as_slice()).map_err(|_| bad())?,
+        )?;
+        signature.verify(&self.account, &envelope.hash())?;
+        Ok(signature)
+    }
+}
+
+/// The signer listening on a Unix socket, for any account: it refuses
+/// those it holds no key for when asked to sign.
+#[derive(Debug, Clone)]
+pub struct RemoteSigners {
+    socket: PathBuf,
+}
+
+impl RemoteSigners {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        RemoteSigners {
+            socket: socket.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Signers for RemoteSigners {
+    async fn signer_for(&self, account: &str) -> Result<Arc<dyn Signer>, SignerError> {
+        Ok(Arc::new(RemoteSigner::new(self.socket.clone(), account)))
+    }
+}
+
+/// Answers signing requests on `listener` with `signers` until accepting
+/// fails.
+pub async fn serve(listener: UnixListener, signers: Arc<dyn Signers>) -> io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let signers = Arc::clone(&signers);
+        tokio::spawn(async move {
+            // A client that hangs up mid-request only loses its own answer.
+            let _ = connection(stream, signers.as_ref()).await;
+        });
+    }
+}
+
+async fn connection(stream: UnixStream, signers: &dyn Signers) -> io::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut read = BufReader::new(read);
+    loop {
+        let line = match read_line(&mut read).await {
+            Ok(Some(line)) => line,
+            Ok(None) => return Ok(()),
+            // Answered, then hung up on: the rest of the line is not read.
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
+                let response = Response {
+                    signature: None,
+                    error: Some(e.to_string()),
+                };
+                return reply(&mut write, &response).await;
+            }
+            Err(e) => return Err(e),
+        };
+        let response = match answer(&line, signers).await {
+            Ok(signature) => Response {
+                signature: Some(BASE64.encode(signature.signature)),
+                error: None,
+            },
+            Err(e) => Response {
+                signature: None,
+                error: Some(e.to_string()),
+            },
+        };
+        reply(&mut write, &response).await?;
+    }
+}
+
+async fn reply(write: &mut OwnedWriteHalf, response: &Response) -> io::Result<()> {
+    let mut out = serde_json::to_string(response).map_err(io::Error::other)?;
+    out.push('\n');
+    write.write_all(out.as_bytes()).await
+}
+
+/// The next line, or `None` once the other side is done; a line longer
+/// than [`MAX_LINE`] is `InvalidData`.
+async fn read_line(read: &mut (impl AsyncBufRead + Unpin)) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if read.take(MAX_LINE + 1).read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    if line.len() as u64 > MAX_LINE {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("line longer than {MAX_LINE} bytes"),
+        ));
+    }
+    Ok(Some(line))
+}
+
+async fn answer(line: &str, signers: &dyn Signers) -> Result<Signature, SignerError> {
+    let request: Request = serde_json::from_str(line)
+        .map_err(|e| SignerError::Remote(format!("invalid request: {e}")))?;
+    let envelope = Envelope::from_xdr(&request.envelope, request.network_passphrase)?;
+    let signer = signers.signer_for(&request.account).await?;
+    if signer.public_key() != request.account {
+        return Err(SignerError::UnknownAccount(request.account));
+    }
+    signer.sign(&envelope).await
+}
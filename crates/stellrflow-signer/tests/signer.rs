@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    Asset, Limits, Memo, MuxedAccount, Operation, OperationBody, PaymentOp, Preconditions,
+    SequenceNumber, Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope,
+    Uint256, WriteXdr,
+};
+use stellrflow_keystore::{KdfParams, Keyring, Keystore, MasterKey};
+use stellrflow_signer::remote;
+use stellrflow_signer::{
+    deferred, DeferredSigner, Envelope, KeystoreSigner, LocalSigner, ParkHook, ParkedTransaction,
+    ParkingLot, RemoteSigner, Signer, SignerError, TESTNET_PASSPHRASE,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+
+fn seed(n: u8) -> String {
+    stellar_strkey::ed25519::PrivateKey([n; 32])
+        .to_string()
+        .as_str()
+        .to_string()
+}
+
+/// A 1 XLM payment from `source` to itself.
+fn payment(source: &str) -> Envelope {
+    let key = stellar_strkey::ed25519::PublicKey::from_string(source)
+        .unwrap()
+        .0;
+    let account = MuxedAccount::Ed25519(Uint256(key));
+    let tx = Transaction {
+        source_account: account.clone(),
+        fee: 100,
+        seq_num: SequenceNumber(42),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![Operation {
+            source_account: None,
+            body: OperationBody::Payment(PaymentOp {
+                destination: account,
+                asset: Asset::Native,
+                amount: 10_000_000,
+            }),
+        }]
+        .try_into()
+        .unwrap(),
+        ext: TransactionExt::V0,
+    };
+    Envelope::new(
+        TransactionEnvelope::Tx(TransactionV1Envelope {
+            tx,
+            signatures: Default::default(),
+        }),
+        TESTNET_PASSPHRASE,
+    )
+}
+
+#[tokio::test]
+async fn local_signatures_cover_the_network_and_transaction() {
+    let signer = LocalSigner::from_secret(&seed(1)).unwrap();
+    let mut envelope = payment(signer.public_key());
+
+    // sha256(network ID ‖ ENVELOPE_TYPE_TX ‖ transaction)
+    let TransactionEnvelope::Tx(v1) = envelope.envelope() else {
+        unreachable!()
+    };
+    let mut payload = Sha256::digest(TESTNET_PASSPHRASE).to_vec();
+    payload.extend([0, 0, 0, 2]);
+    payload.extend(v1.tx.to_xdr(Limits::none()).unwrap());
+    let expected: [u8; 32] = Sha256::digest(payload).into();
+    assert_eq!(envelope.hash(), expected);
+
+    envelope.sign_with(&signer).await.unwrap();
+    assert_eq!(envelope.signatures().len(), 1);
+    assert!(envelope.signature_by(signer.public_key()).is_some());
+
+    let xdr = envelope.to_xdr();
+    let read = Envelope::from_xdr(&xdr, TESTNET_PASSPHRASE).unwrap();
+    assert_eq!(read, envelope);
+    // Signed for testnet, so not valid on the public network.
+    let public =
+        Envelope::from_xdr(&xdr, "Public Global Stellar Network ; September 2015").unwrap();
+    assert!(public.signature_by(signer.public_key()).is_none());
+
+    assert!(matches!(
+        LocalSigner::from_secret("SBAD"),
+        Err(SignerError::InvalidSecret)
+    ));
+    assert!(matches!(
+        Envelope::from_xdr("not xdr", TESTNET_PASSPHRASE),
+        Err(SignerError::InvalidEnvelope(_))
+    ));
+}
+
+#[tokio::test]
+async fn keystore_signers_decrypt_only_to_sign() {
+    let dir = tempfile::tempdir().unwrap();
+    let keyring = Keyring::new(MasterKey::new("k1", [9u8; 32]).unwrap()).with_params(KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    });
+    let mut keystore = Keystore::open(dir.path().join("wallets.json"), keyring).unwrap();
+    let account = keystore
+        .insert("123", &seed(2), "2026-01-01T00:00:00.000Z")
+        .unwrap();
+    let keystore = Arc::new(keystore);
+
+    let signer = KeystoreSigner::new(Arc::clone(&keystore), "123").unwrap();
+    assert_eq!(signer.public_key(), account);
+    let mut envelope = payment(&account);
+    envelope.sign_with(&signer).await.unwrap();
+    let local = LocalSigner::from_secret(&seed(2)).unwrap();
+    assert_eq!(
+        envelope.signature_by(&account),
+        Some(local.sign(&envelope).await.unwrap())
+    );
+
+    assert!(matches!(
+        KeystoreSigner::new(keystore, "999"),
+        Err(SignerError::Keystore(_))
+    ));
+}
+
+/// Claims an account but signs with another key.
+struct Liar {
+    claims: String,
+    key: LocalSigner,
+}
+
+#[async_trait]
+impl Signer for Liar {
+    fn public_key(&self) -> &str {
+        &self.claims
+    }
+
+    async fn sign(&self, envelope: &Envelope) -> Result<stellrflow_signer::Signature, SignerError> {
+        self.key.sign(envelope).await
+    }
+}
+
+#[tokio::test]
+async fn remote_signers_answer_over_a_unix_socket() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("signer.sock");
+    let local: Arc<dyn Signer> = Arc::new(LocalSigner::from_secret(&seed(4)).unwrap());
+    let account = local.public_key().to_string();
+    let signers: HashMap<String, Arc<dyn Signer>> =
+        HashMap::from([(account.clone(), Arc::clone(&local))]);
+    let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+    tokio::spawn(remote::serve(listener, Arc::new(signers)));
+
+    let remote = RemoteSigner::new(&socket, &account);
+    let mut envelope = payment(&account);
+    envelope.sign_with(&remote).await.unwrap();
+    assert_eq!(
+        envelope.signature_by(&account),
+        Some(local.sign(&envelope).await.unwrap())
+    );
+
+    let stranger = LocalSigner::from_secret(&seed(5)).unwrap();
+    let remote = RemoteSigner::new(&socket, stranger.public_key());
+    let err = remote
+        .sign(&payment(stranger.public_key()))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, SignerError::Remote(message) if message.contains("no signer for account")),
+        "{err}"
+    );
+
+    // A server that signs with the wrong key is caught.
+    let liar: Arc<dyn Signer> = Arc::new(Liar {
+        claims: account.clone(),
+        key: stranger,
+    });
+    let signers: HashMap<String, Arc<dyn Signer>> = HashMap::from([(account.clone(), liar)]);
+    let socket = dir.path().join("liar.sock");
+    let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+    tokio::spawn(remote::serve(listener, Arc::new(signers)));
+    let remote = RemoteSigner::new(&socket, &account);
+    let err = remote.sign(&payment(&account)).await.unwrap_err();
+    assert!(matches!(err, SignerError::BadSignature(_)), "{err}");
+
+    // A request without end is cut off rather than read into memory.
+    let (read, mut write) = tokio::net::UnixStream::connect(&socket)
+        .await
+        .unwrap()
+        .into_split();
+    tokio::spawn(async move {
+        let line = vec![b'a'; remote::MAX_LINE as usize + 10];
+        let _ = write.write_all(&line).await;
+    });
+    let mut answer = String::new();
+    BufReader::new(read).read_line(&mut answer).await.unwrap();
+    let answer: Value = serde_json::from_str(&answer).unwrap();
+    assert_eq!(answer["error"], "line longer than 262144 bytes");
+
+    let missing = RemoteSigner::new(dir.path().join("missing.sock"), &account);
+    assert!(matches!(
+        missing.sign(&payment(&account)).await,
+        Err(SignerError::Io(_))
+    ));
+}
+
+/// Hands each parked transaction to the test, as the bot would send a
+/// Freighter link.
+struct Notify(mpsc::UnboundedSender<ParkedTransaction>);
+
+#[async_trait]
+impl ParkHook for Notify {
+    async fn parked(&self, transaction: &ParkedTransaction) {
+        self.0.send(transaction.clone()).unwrap();
+    }
+}
+
+async fn post(app: axum::Router, body: Value) -> (StatusCode, Value) {
+    let response = app
+        .oneshot(
+            Request::post("/api/transaction/submit")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn deferred_signers_resume_when_the_signed_xdr_is_posted() {
+    let freighter = LocalSigner::from_secret(&seed(6)).unwrap();
+    let account = freighter.public_key().to_string();
+    let lot = Arc::new(ParkingLot::new());
+    let (tx, mut parked) = mpsc::unbounded_channel();
+    let signer = DeferredSigner::new(&account, Arc::clone(&lot), Arc::new(Notify(tx)));
+    let app = deferred::router(Arc::clone(&lot));
+
+    let mut envelope = payment(&account);
+    let waiting = tokio::spawn({
+        let signer = signer.clone();
+        let mut envelope = envelope.clone();
+        async move { envelope.sign_with(&signer).await.map(|()| envelope) }
+    });
+    let transaction = parked.recv().await.unwrap();
+    assert_eq!(transaction.hash, envelope.hash_hex());
+    assert_eq!(transaction.account, account);
+    assert_eq!(lot.pending(), std::slice::from_ref(&transaction));
+
+    // Posting it unsigned, or for another network, does not resume it.
+    let (status, body) = post(app.clone(), json!({ "signedXdr": transaction.xdr })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+    let (status, _) = post(
+        app.clone(),
+        json!({ "signedXdr": transaction.xdr, "network": "mainnet" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // What Freighter sends back: the same transaction, signed.
+    envelope.sign_with(&freighter).await.unwrap();
+    let (status, body) = post(
+        app.clone(),
+        json!({ "signedXdr": envelope.to_xdr(), "chatId": "123", "network": "testnet" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body,
+        json!({ "success": true, "hash": transaction.hash, "resumed": true })
+    );
+
+    let resumed = waiting.await.unwrap().unwrap();
+    assert_eq!(resumed, envelope);
+    assert!(lot.pending().is_empty());
+    let (status, _) = post(app, json!({ "signedXdr": envelope.to_xdr() })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deferred_signers_give_up_after_their_timeout() {
+    let account = LocalSigner::from_secret(&seed(7))
+        .unwrap()
+        .public_key()
+        .to_string();
+    let lot = Arc::new(ParkingLot::new());
+    let (tx, _parked) = mpsc::unbounded_channel();
+    let signer = DeferredSigner::new(&account, Arc::clone(&lot), Arc::new(Notify(tx)))
+        .with_timeout(Duration::from_millis(20));
+    let envelope = payment(&account);
+    assert!(matches!(
+        signer.sign(&envelope).await,
+        Err(SignerError::Timeout(hash)) if hash == envelope.hash_hex()
+    ));
+    assert!(lot.pending().is_empty());
+}
@@ -0,0 +1,167 @@
+//! Assets as wallets and anchors name them: `native`, or `CODE:ISSUER`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use stellar_xdr::curr::{self as xdr, AlphaNum12, AlphaNum4, AssetCode12, AssetCode4};
+
+use crate::error::StellarError;
+use crate::send::account_id;
+
+/// XLM, or an asset issued by an account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Asset {
+    Native,
+    /// A code of 1 to 12 letters and digits, such as `USDC`, and the
+    /// account that issued it.
+    Issued {
+        code: String,
+        issuer: String,
+    },
+}
+
+impl Asset {
+    pub fn issued(code: &str, issuer: &str) -> Result<Self, StellarError> {
+        let valid_code =
+            (1..=12).contains(&code.len()) && code.bytes().all(|byte| byte.is_ascii_alphanumeric());
+        if !valid_code || account_id(issuer).is_err() {
+            return Err(StellarError::InvalidAsset(format!("{code}:{issuer}")));
+        }
+        Ok(Asset::Issued {
+            code: code.to_string(),
+            issuer: issuer.to_string(),
+        })
+    }
+
+    pub fn is_native(&self) -> bool {
+        matches!(self, Asset::Native)
+    }
+
+    /// `XLM`, or the issued asset's code.
+    pub fn code(&self) -> &str {
+        match self {
+            Asset::Native => "XLM",
+            Asset::Issued { code, .. } => code,
+        }
+    }
+
+    pub fn issuer(&self) -> Option<&str> {
+        match self {
+            Asset::Native => None,
+            Asset::Issued { issuer, .. } => Some(issuer),
+        }
+    }
+
+    /// Horizon's `asset_type`: `native`, `credit_alphanum4` or
+    /// `credit_alphanum12`.
+    pub(crate) fn horizon_type(&self) -> &'static str {
+        match self {
+            Asset::Native => "native",
+            Asset::Issued { code, .. } if code.len() <= 4 => "credit_alphanum4",
+            Asset::Issued { .. } => "credit_alphanum12",
+        }
+    }
+
+    /// An asset as Horizon's records spell it out: `native`, or a
+    /// `credit_alphanum…` type with its code and issuer.
+    pub(crate) fn from_horizon(
+        asset_type: &str,
+        code: Option<&str>,
+        issuer: Option<&str>,
+    ) -> Option<Self> {
+        match (asset_type, code, issuer) {
+            ("native", _, _) => Some(Asset::Native),
+            ("credit_alphanum4" | "credit_alphanum12", Some(code), Some(issuer)) => {
+                Some(Asset::Issued {
+                    code: code.to_string(),
+                    issuer: issuer.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_xdr(&self) -> Result<xdr::Asset, StellarError> {
+        Ok(match self.credit()? {
+            None => xdr::Asset::Native,
+            Some(Credit::AlphaNum4(credit)) => xdr::Asset::CreditAlphanum4(credit),
+            Some(Credit::AlphaNum12(credit)) => xdr::Asset::CreditAlphanum12(credit),
+        })
+    }
+
+    /// The asset as a trustline's `line`; XLM has none.
+    pub(crate) fn to_trust_line(&self) -> Result<xdr::ChangeTrustAsset, StellarError> {
+        match self.credit()? {
+            None => Err(StellarError::InvalidAsset(self.to_string())),
+            Some(Credit::AlphaNum4(credit)) => Ok(xdr::ChangeTrustAsset::CreditAlphanum4(credit)),
+            Some(Credit::AlphaNum12(credit)) => Ok(xdr::ChangeTrustAsset::CreditAlphanum12(credit)),
+        }
+    }
+
+    fn credit(&self) -> Result<Option<Credit>, StellarError> {
+        let Asset::Issued { code, issuer } = self else {
+            return Ok(None);
+        };
+        let issuer = account_id(issuer)?;
+        Ok(Some(if code.len() <= 4 {
+            let mut bytes = [0; 4];
+            bytes[..code.len()].copy_from_slice(code.as_bytes());
+            Credit::AlphaNum4(AlphaNum4 {
+                asset_code: AssetCode4(bytes),
+                issuer,
+            })
+        } else {
+            let mut bytes = [0; 12];
+            bytes[..code.len()].copy_from_slice(code.as_bytes());
+            Credit::AlphaNum12(AlphaNum12 {
+                asset_code: AssetCode12(bytes),
+                issuer,
+            })
+        }))
+    }
+}
+
+enum Credit {
+    AlphaNum4(AlphaNum4),
+    AlphaNum12(AlphaNum12),
+}
+
+/// `native` or `XLM` (in any case) for XLM, otherwise `CODE:ISSUER`.
+impl FromStr for Asset {
+    type Err = StellarError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("native") || text.eq_ignore_ascii_case("xlm") {
+            return Ok(Asset::Native);
+        }
+        match text.split_once(':') {
+            Some((code, issuer)) => Asset::issued(code, issuer),
+            None => Err(StellarError::InvalidAsset(text.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Asset::Native => f.write_str("native"),
+            Asset::Issued { code, issuer } => write!(f, "{code}:{issuer}"),
+        }
+    }
+}
+
+impl Serialize for Asset {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Asset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,505 @@
+//! Claimable balances: payments set aside on the ledger until one of their
+//! claimants takes them, under the conditions of a [`Predicate`].
+//!
+//! Unlike a payment, a claimable balance needs no account on the receiving
+//! end: a Telegram user without a funded account can be paid now and claim
+//! once they have one.
+
+use std::ops::Not;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use stellar_xdr::curr::{
+    self as xdr, AccountId, ClaimClaimableBalanceOp, ClaimPredicate, ClaimableBalanceId,
+    ClaimantV0, CreateClaimableBalanceOp, CreateClaimableBalanceResult, FeeBumpTransactionInnerTx,
+    Hash, HashIdPreimage, HashIdPreimageOperationId, Limits, Operation, OperationBody,
+    OperationResult, OperationResultTr, ReadXdr, SequenceNumber, TransactionEnvelope, WriteXdr,
+};
+use stellrflow_amount::Amount;
+use stellrflow_signer::Signer;
+
+use crate::asset::Asset;
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::resources::{ClaimableBalance, TransactionRecord};
+use crate::send::account_id;
+
+/// How deep a predicate may nest, counting its leaves, as the network
+/// allows.
+pub const MAX_PREDICATE_DEPTH: usize = 4;
+
+/// The most claimants one balance can have.
+pub const MAX_CLAIMANTS: usize = 10;
+
+/// When a claimant may claim a balance. Times are in seconds: since the
+/// Unix epoch for [`BeforeAbsolute`](Predicate::BeforeAbsolute), and since
+/// the balance was created for [`BeforeRelative`](Predicate::BeforeRelative),
+/// which the network turns into an absolute time on creation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Predicate {
+    /// At any time.
+    Unconditional,
+    BeforeAbsolute(i64),
+    BeforeRelative(i64),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    /// Before `time`.
+    pub fn before(time: SystemTime) -> Self {
+        Predicate::BeforeAbsolute(epoch_seconds(time))
+    }
+
+    /// At or after `time`.
+    pub fn after(time: SystemTime) -> Self {
+        !Predicate::before(time)
+    }
+
+    /// Within `duration` of the balance's creation.
+    pub fn within(duration: Duration) -> Self {
+        Predicate::BeforeRelative(seconds(duration))
+    }
+
+    /// Once `duration` has passed since the balance's creation.
+    pub fn after_delay(duration: Duration) -> Self {
+        !Predicate::within(duration)
+    }
+
+    /// When both `self` and `other` allow it.
+    pub fn and(self, other: Predicate) -> Self {
+        Predicate::And(Box::new(self), Box::new(other))
+    }
+
+    /// When either `self` or `other` allows it.
+    pub fn or(self, other: Predicate) -> Self {
+        Predicate::Or(Box::new(self), Box::new(other))
+    }
+
+    /// How deep it nests; a lone condition is 1.
+    pub fn depth(&self) -> usize {
+        match self {
+            Predicate::Unconditional
+            | Predicate::BeforeAbsolute(_)
+            | Predicate::BeforeRelative(_) => 1,
+            Predicate::And(a, b) | Predicate::Or(a, b) => 1 + a.depth().max(b.depth()),
+            Predicate::Not(inner) => 1 + inner.depth(),
+        }
+    }
+
+    /// Whether it allows a claim at `at`, for a balance created at
+    /// `created`, both in seconds since the Unix epoch.
+    pub fn allows(&self, created: i64, at: i64) -> bool {
+        match self {
+            Predicate::Unconditional => true,
+            Predicate::BeforeAbsolute(time) => at < *time,
+            Predicate::BeforeRelative(delay) => at < created.saturating_add(*delay),
+            Predicate::And(a, b) => a.allows(created, at) && b.allows(created, at),
+            Predicate::Or(a, b) => a.allows(created, at) || b.allows(created, at),
+            Predicate::Not(inner) => !inner.allows(created, at),
+        }
+    }
+
+    /// The predicate as the ledger keeps it, for a balance created at
+    /// `created`: relative times made absolute.
+    pub fn resolve(&self, created: i64) -> Predicate {
+        match self {
+            Predicate::BeforeRelative(delay) => {
+                Predicate::BeforeAbsolute(created.saturating_add(*delay))
+            }
+            Predicate::And(a, b) => a.resolve(created).and(b.resolve(created)),
+            Predicate::Or(a, b) => a.resolve(created).or(b.resolve(created)),
+            Predicate::Not(inner) => !inner.resolve(created),
+            other => other.clone(),
+        }
+    }
+
+    /// Its XDR, once found no deeper than [`MAX_PREDICATE_DEPTH`] and with
+    /// no negative times.
+    pub fn to_xdr(&self) -> Result<ClaimPredicate, StellarError> {
+        if self.depth() > MAX_PREDICATE_DEPTH {
+            return Err(StellarError::InvalidPredicate(format!(
+                "nested {} deep, more than {MAX_PREDICATE_DEPTH}",
+                self.depth()
+            )));
+        }
+        self.encode()
+    }
+
+    fn encode(&self) -> Result<ClaimPredicate, StellarError> {
+        let pair = |a: &Predicate, b: &Predicate| -> Result<_, StellarError> {
+            Ok([a.encode()?, b.encode()?]
+                .to_vec()
+                .try_into()
+                .expect("two predicates fit"))
+        };
+        Ok(match self {
+            Predicate::Unconditional => ClaimPredicate::Unconditional,
+            Predicate::BeforeAbsolute(time) | Predicate::BeforeRelative(time) if *time < 0 => {
+                return Err(StellarError::InvalidPredicate(format!(
+                    "negative time {time}"
+                )))
+            }
+            Predicate::BeforeAbsolute(time) => ClaimPredicate::BeforeAbsoluteTime(*time),
+            Predicate::BeforeRelative(delay) => ClaimPredicate::BeforeRelativeTime(*delay),
+            Predicate::And(a, b) => ClaimPredicate::And(pair(a, b)?),
+            Predicate::Or(a, b) => ClaimPredicate::Or(pair(a, b)?),
+            Predicate::Not(inner) => ClaimPredicate::Not(Some(Box::new(inner.encode()?))),
+        })
+    }
+
+    /// Reads a predicate from XDR; `and` and `or` need two predicates, and
+    /// `not` one.
+    pub fn from_xdr(predicate: &ClaimPredicate) -> Result<Self, StellarError> {
+        let pair = |predicates: &[ClaimPredicate]| match predicates {
+            [a, b] => Ok((Predicate::from_xdr(a)?, Predicate::from_xdr(b)?)),
+            _ => Err(StellarError::InvalidPredicate(format!(
+                "{} predicates where two are combined",
+                predicates.len()
+            ))),
+        };
+        Ok(match predicate {
+            ClaimPredicate::Unconditional => Predicate::Unconditional,
+            ClaimPredicate::BeforeAbsoluteTime(time) => Predicate::BeforeAbsolute(*time),
+            ClaimPredicate::BeforeRelativeTime(delay) => Predicate::BeforeRelative(*delay),
+            ClaimPredicate::And(predicates) => {
+                let (a, b) = pair(predicates)?;
+                a.and(b)
+            }
+            ClaimPredicate::Or(predicates) => {
+                let (a, b) = pair(predicates)?;
+                a.or(b)
+            }
+            ClaimPredicate::Not(Some(inner)) => !Predicate::from_xdr(inner)?,
+            ClaimPredicate::Not(None) => {
+                return Err(StellarError::InvalidPredicate("`not` of nothing".into()))
+            }
+        })
+    }
+}
+
+impl Not for Predicate {
+    type Output = Predicate;
+
+    /// When `self` does not allow it.
+    fn not(self) -> Predicate {
+        Predicate::Not(Box::new(self))
+    }
+}
+
+/// A predicate as Horizon writes it: `{"unconditional": true}`,
+/// `{"abs_before": "…", "abs_before_epoch": "…"}`, `{"rel_before": "…"}`,
+/// `{"and": […]}`, `{"or": […]}` or `{"not": {…}}`.
+impl<'de> Deserialize<'de> for Predicate {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct Raw {
+            #[serde(default)]
+            unconditional: bool,
+            #[serde(default)]
+            abs_before_epoch: Option<String>,
+            #[serde(default)]
+            rel_before: Option<String>,
+            #[serde(default)]
+            and: Option<Vec<Predicate>>,
+            #[serde(default)]
+            or: Option<Vec<Predicate>>,
+            #[serde(default)]
+            not: Option<Box<Predicate>>,
+        }
+
+        let seconds = |text: String| {
+            text.parse::<i64>()
+                .map_err(|_| D::Error::custom(format!("invalid time `{text}`")))
+        };
+        let pair = |predicates: Vec<Predicate>| match <[Predicate; 2]>::try_from(predicates) {
+            Ok([a, b]) => Ok((a, b)),
+            Err(predicates) => Err(D::Error::custom(format!(
+                "{} predicates where two are combined",
+                predicates.len()
+            ))),
+        };
+        let raw = Raw::deserialize(d)?;
+        if raw.unconditional {
+            return Ok(Predicate::Unconditional);
+        }
+        if let Some(time) = raw.abs_before_epoch {
+            return Ok(Predicate::BeforeAbsolute(seconds(time)?));
+        }
+        if let Some(delay) = raw.rel_before {
+            return Ok(Predicate::BeforeRelative(seconds(delay)?));
+        }
+        if let Some(predicates) = raw.and {
+            let (a, b) = pair(predicates)?;
+            return Ok(a.and(b));
+        }
+        if let Some(predicates) = raw.or {
+            let (a, b) = pair(predicates)?;
+            return Ok(a.or(b));
+        }
+        if let Some(inner) = raw.not {
+            return Ok(Predicate::Not(inner));
+        }
+        Err(D::Error::custom("expected a claim predicate"))
+    }
+}
+
+/// Who may claim a balance, and when.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct Claimant {
+    pub destination: String,
+    pub predicate: Predicate,
+}
+
+impl Claimant {
+    pub fn new(destination: impl Into<String>, predicate: Predicate) -> Self {
+        Claimant {
+            destination: destination.into(),
+            predicate,
+        }
+    }
+
+    /// `destination` may claim at any time.
+    pub fn unconditional(destination: impl Into<String>) -> Self {
+        Claimant::new(destination, Predicate::Unconditional)
+    }
+
+    pub(crate) fn to_xdr(&self) -> Result<xdr::Claimant, StellarError> {
+        Ok(xdr::Claimant::ClaimantTypeV0(ClaimantV0 {
+            destination: account_id(&self.destination)?,
+            predicate: self.predicate.to_xdr()?,
+        }))
+    }
+}
+
+/// What [`Horizon::create_claimable_balance`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceCreated {
+    pub hash: String,
+    pub ledger: u32,
+    /// The new balance's ID, as Horizon lists it.
+    pub balance_id: String,
+}
+
+impl BalanceCreated {
+    /// Reads the balance's ID from the transaction's result or, if the
+    /// result cannot be read, works it out from the transaction.
+    pub(crate) fn new(record: TransactionRecord) -> Result<Self, StellarError> {
+        let created = record
+            .operation_results()
+            .into_iter()
+            .flatten()
+            .find_map(|result| match result {
+                OperationResult::OpInner(OperationResultTr::CreateClaimableBalance(
+                    CreateClaimableBalanceResult::Success(id),
+                )) => Some(id),
+                _ => None,
+            });
+        let id = match created {
+            Some(id) => id,
+            None => created_balance_id(&record.envelope_xdr).ok_or_else(|| {
+                StellarError::InvalidBalanceId(format!("in transaction {}", record.hash))
+            })?,
+        };
+        Ok(BalanceCreated {
+            hash: record.hash,
+            ledger: record.ledger,
+            balance_id: balance_id_hex(&id),
+        })
+    }
+}
+
+/// What [`Horizon::claim_balance`] took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceClaimed {
+    pub hash: String,
+    pub ledger: u32,
+    pub balance_id: String,
+    pub asset: Asset,
+    pub amount: Amount,
+}
+
+impl BalanceClaimed {
+    pub(crate) fn new(record: TransactionRecord, balance: ClaimableBalance) -> Self {
+        BalanceClaimed {
+            hash: record.hash,
+            ledger: record.ledger,
+            balance_id: balance.id,
+            asset: balance.asset,
+            amount: balance.amount,
+        }
+    }
+}
+
+impl Horizon {
+    /// Sets `amount` of `asset` aside from `source`'s account for
+    /// `claimants` to claim, once the account is found to hold it. The
+    /// account keeps a base reserve per claimant until the balance is
+    /// claimed.
+    pub async fn create_claimable_balance(
+        &self,
+        source: &dyn Signer,
+        asset: &Asset,
+        amount: Amount,
+        claimants: &[Claimant],
+    ) -> Result<BalanceCreated, StellarError> {
+        let operation = self
+            .claimable_operation(source.public_key(), asset, amount, claimants)
+            .await?;
+        let record = self.sign_and_submit(source, vec![operation]).await?;
+        BalanceCreated::new(record)
+    }
+
+    /// Claims the balance `balance_id` into `source`'s account, once the
+    /// account is found to be a claimant allowed to claim it now and to
+    /// have room for it.
+    pub async fn claim_balance(
+        &self,
+        source: &dyn Signer,
+        balance_id: &str,
+    ) -> Result<BalanceClaimed, StellarError> {
+        let (operation, balance) = self
+            .claim_operation(source.public_key(), balance_id)
+            .await?;
+        let record = self.sign_and_submit(source, vec![operation]).await?;
+        Ok(BalanceClaimed::new(record, balance))
+    }
+
+    /// The checked operation for
+    /// [`create_claimable_balance`](Self::create_claimable_balance).
+    pub(crate) async fn claimable_operation(
+        &self,
+        source: &str,
+        asset: &Asset,
+        amount: Amount,
+        claimants: &[Claimant],
+    ) -> Result<Operation, StellarError> {
+        if !amount.is_positive() {
+            return Err(StellarError::InvalidAmount(amount));
+        }
+        check_claimants(claimants)?;
+        let claimants = claimants
+            .iter()
+            .map(Claimant::to_xdr)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.check_sender(source, asset, amount).await?;
+        Ok(Operation {
+            source_account: None,
+            body: OperationBody::CreateClaimableBalance(CreateClaimableBalanceOp {
+                asset: asset.to_xdr()?,
+                amount: amount.stroops(),
+                claimants: claimants.try_into().expect("at most 10 claimants"),
+            }),
+        })
+    }
+
+    /// The checked operation for [`claim_balance`](Self::claim_balance),
+    /// and the balance it claims.
+    pub(crate) async fn claim_operation(
+        &self,
+        source: &str,
+        balance_id: &str,
+    ) -> Result<(Operation, ClaimableBalance), StellarError> {
+        let id = parse_balance_id(balance_id)?;
+        let balance = self.claimable_balance(&balance_id_hex(&id)).await?;
+        if !balance.claimable_by(source, SystemTime::now()) {
+            return Err(StellarError::CannotClaim {
+                account: source.to_string(),
+                balance_id: balance.id,
+            });
+        }
+        self.check_receiver(source, &balance.asset, balance.amount)
+            .await?;
+        let operation = Operation {
+            source_account: None,
+            body: OperationBody::ClaimClaimableBalance(ClaimClaimableBalanceOp { balance_id: id }),
+        };
+        Ok((operation, balance))
+    }
+}
+
+/// One to [`MAX_CLAIMANTS`] different accounts.
+fn check_claimants(claimants: &[Claimant]) -> Result<(), StellarError> {
+    if claimants.is_empty() || claimants.len() > MAX_CLAIMANTS {
+        return Err(StellarError::InvalidClaimants(format!(
+            "{} claimants, expected 1 to {MAX_CLAIMANTS}",
+            claimants.len()
+        )));
+    }
+    for (index, claimant) in claimants.iter().enumerate() {
+        if claimants[..index]
+            .iter()
+            .any(|earlier| earlier.destination == claimant.destination)
+        {
+            return Err(StellarError::InvalidClaimants(format!(
+                "{} is listed twice",
+                claimant.destination
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// A balance ID as Horizon writes it: the hex of its XDR, type and hash.
+/// The hash alone is taken as a version 0 ID.
+pub(crate) fn parse_balance_id(text: &str) -> Result<ClaimableBalanceId, StellarError> {
+    let invalid = || StellarError::InvalidBalanceId(text.to_string());
+    let hex = text.trim().to_ascii_lowercase();
+    let hash = match hex.len() {
+        72 if hex.starts_with("00000000") => &hex[8..],
+        64 => &hex[..],
+        _ => return Err(invalid()),
+    };
+    let mut bytes = [0; 32];
+    for (byte, pair) in bytes.iter_mut().zip(hash.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    Ok(ClaimableBalanceId::ClaimableBalanceIdTypeV0(Hash(bytes)))
+}
+
+pub(crate) fn balance_id_hex(id: &ClaimableBalanceId) -> String {
+    let bytes = id.to_xdr(Limits::none()).expect("a balance ID encodes");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// The ID of the balance created by operation `index` (from 0) of the
+/// transaction from `source` at `sequence`.
+pub(crate) fn balance_id(source: &AccountId, sequence: i64, index: u32) -> ClaimableBalanceId {
+    let preimage = HashIdPreimage::OpId(HashIdPreimageOperationId {
+        source_account: source.clone(),
+        seq_num: SequenceNumber(sequence),
+        op_num: index,
+    });
+    let bytes = preimage.to_xdr(Limits::none()).expect("a preimage encodes");
+    ClaimableBalanceId::ClaimableBalanceIdTypeV0(Hash(Sha256::digest(bytes).into()))
+}
+
+/// The ID of the first balance a transaction's envelope creates.
+fn created_balance_id(envelope_xdr: &str) -> Option<ClaimableBalanceId> {
+    let tx = match TransactionEnvelope::from_xdr_base64(envelope_xdr, Limits::none()).ok()? {
+        TransactionEnvelope::Tx(v1) => v1.tx,
+        TransactionEnvelope::TxFeeBump(bump) => match bump.tx.inner_tx {
+            FeeBumpTransactionInnerTx::Tx(inner) => inner.tx,
+        },
+        TransactionEnvelope::TxV0(_) => return None,
+    };
+    let index = tx
+        .operations
+        .iter()
+        .position(|op| matches!(op.body, OperationBody::CreateClaimableBalance(_)))?;
+    let source = tx.source_account.account_id();
+    Some(balance_id(&source, tx.seq_num.0, index as u32))
+}
+
+pub(crate) fn epoch_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, seconds)
+}
+
+fn seconds(duration: Duration) -> i64 {
+    i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+}
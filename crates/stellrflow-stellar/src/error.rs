@@ -0,0 +1,169 @@
+use serde::Deserialize;
+use stellrflow_amount::Amount;
+use stellrflow_signer::SignerError;
+use thiserror::Error;
+
+use crate::asset::Asset;
+
+/// Why a Horizon call or a payment failed.
+#[derive(Debug, Error)]
+pub enum StellarError {
+    #[error("Horizon request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Horizon answered with a problem document: a rejected submission, a
+    /// malformed request or a server error.
+    #[error("{}", .0.message())]
+    Horizon(Box<Problem>),
+    /// The account, transaction or other resource does not exist.
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("invalid account {0}")]
+    InvalidAccount(String),
+    #[error("invalid amount {0}: payments must be positive")]
+    InvalidAmount(Amount),
+    /// Sending to an account that does not exist creates it, which takes
+    /// at least [`MIN_STARTING_BALANCE`](crate::MIN_STARTING_BALANCE).
+    #[error("Minimum 1 XLM required to create new account")]
+    BelowMinimum,
+    #[error("invalid asset `{0}`: expected `CODE:ISSUER`")]
+    InvalidAsset(String),
+    /// An account cannot hold an issued asset without a trustline to it.
+    #[error("{account} has no trustline to {asset}")]
+    NoTrustline { account: String, asset: Asset },
+    /// The issuer requires authorization and has not given (or has
+    /// revoked) it for the account's trustline.
+    #[error("{account} is not authorized by the issuer to hold {asset}")]
+    NotAuthorized { account: String, asset: Asset },
+    #[error("{account} holds only {balance} of {asset}")]
+    Underfunded {
+        account: String,
+        asset: Asset,
+        balance: Amount,
+    },
+    /// The payment would take the destination over its trustline's limit.
+    #[error("{account} can receive at most {room} more of {asset}")]
+    LineFull {
+        account: String,
+        asset: Asset,
+        room: Amount,
+    },
+    /// A trustline's limit cannot go below what it holds; removing one,
+    /// with a limit of 0, needs it empty.
+    #[error("the trustline to {asset} holds {balance}, more than a limit of {limit}")]
+    LimitBelowBalance {
+        asset: Asset,
+        balance: Amount,
+        limit: Amount,
+    },
+    /// The order books offer no way to convert one asset into the other.
+    #[error("no path found from {} to {}", .from.code(), .to.code())]
+    NoPath { from: Asset, to: Asset },
+    #[error("invalid slippage `{0}`: expected a percentage from 0 to 100 with at most 2 decimals")]
+    InvalidSlippage(String),
+    #[error("invalid price `{0}`: expected a positive decimal or fraction such as `1/8`")]
+    InvalidPrice(String),
+    #[error("invalid claim predicate: {0}")]
+    InvalidPredicate(String),
+    #[error("invalid claimants: {0}")]
+    InvalidClaimants(String),
+    #[error("invalid claimable balance ID `{0}`")]
+    InvalidBalanceId(String),
+    /// The account is not one of the balance's claimants, or its
+    /// predicate does not allow a claim yet (or any more).
+    #[error("{account} cannot claim balance {balance_id} now")]
+    CannotClaim { account: String, balance_id: String },
+    #[error("invalid sponsorship: {0}")]
+    InvalidSponsorship(String),
+    /// Only the account sponsoring an entry can revoke its sponsorship.
+    #[error("{sponsor} does not sponsor {entry}")]
+    NotSponsor { sponsor: String, entry: String },
+    #[error("a transaction carries at most 100 operations, not {0}")]
+    TooManyOperations(usize),
+    #[error("only v1 transactions can be fee bumped")]
+    Unbumpable,
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+}
+
+/// An RFC 7807 problem document, as Horizon reports errors.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Problem {
+    #[serde(default)]
+    pub status: u16,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub detail: String,
+    #[serde(default)]
+    pub extras: Option<ProblemExtras>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ProblemExtras {
+    #[serde(default)]
+    pub result_codes: Option<ResultCodes>,
+    #[serde(default)]
+    pub envelope_xdr: Option<String>,
+    #[serde(default)]
+    pub result_xdr: Option<String>,
+}
+
+/// Why Stellar rejected a transaction: `tx_failed` with a code per
+/// operation, or a transaction-level code such as `tx_bad_seq`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, Deserialize)]
+pub struct ResultCodes {
+    pub transaction: String,
+    /// For a fee bump rejected as `tx_fee_bump_inner_failed`, why the
+    /// transaction inside it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_transaction: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operations: Vec<String>,
+}
+
+impl ResultCodes {
+    /// The code that says what went wrong: the inner transaction's for a
+    /// fee bump whose inner transaction failed.
+    pub fn code(&self) -> &str {
+        self.inner_transaction
+            .as_deref()
+            .unwrap_or(&self.transaction)
+    }
+}
+
+impl Problem {
+    pub fn result_codes(&self) -> Option<&ResultCodes> {
+        self.extras.as_ref()?.result_codes.as_ref()
+    }
+
+    /// The result codes as the bot shows them
+    /// (`{"transaction":"tx_failed","operations":["op_underfunded"]}`), or
+    /// the problem's title and detail.
+    pub fn message(&self) -> String {
+        match self.result_codes() {
+            Some(codes) => serde_json::to_string(codes).expect("result codes always serialize"),
+            None if self.detail.is_empty() => self.title.clone(),
+            None => format!("{}: {}", self.title, self.detail),
+        }
+    }
+}
+
+impl StellarError {
+    /// The result codes of a rejected submission.
+    pub fn result_codes(&self) -> Option<&ResultCodes> {
+        match self {
+            StellarError::Horizon(problem) => problem.result_codes(),
+            _ => None,
+        }
+    }
+
+    /// Whether a submission went unanswered: Horizon's 504, or no response
+    /// in time. The transaction may still make a ledger.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            StellarError::Http(err) => err.is_timeout(),
+            StellarError::Horizon(problem) => problem.status == 504,
+            _ => false,
+        }
+    }
+}
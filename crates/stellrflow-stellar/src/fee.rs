@@ -0,0 +1,131 @@
+//! What to offer per operation when the network is busy.
+
+use stellar_xdr::curr::{
+    FeeBumpTransaction, FeeBumpTransactionEnvelope, FeeBumpTransactionExt,
+    FeeBumpTransactionInnerTx, TransactionEnvelope,
+};
+use stellrflow_signer::{Envelope, Signer};
+
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::resources::FeeStats;
+use crate::send::account_id;
+use crate::BASE_FEE;
+
+/// A fee bump replacing a transaction that is still waiting for a ledger
+/// must offer this many times its fee, as stellar-core requires.
+const BUMP_FACTOR: u32 = 10;
+
+/// Picks the fee per operation from `/fee_stats`: a percentile of what
+/// recent ledgers charged, never below the base fee or above a cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeStrategy {
+    /// Which percentile of recent fees to offer; 90 outbids nine in ten
+    /// recent transactions.
+    pub percentile: u8,
+    /// The most to offer per operation, in stroops, however busy the
+    /// network is. It caps fee bumps too.
+    pub max_fee: u32,
+}
+
+impl Default for FeeStrategy {
+    fn default() -> Self {
+        FeeStrategy {
+            percentile: 90,
+            max_fee: 10_000,
+        }
+    }
+}
+
+impl FeeStrategy {
+    /// The fee per operation to offer, in stroops.
+    pub fn fee(&self, stats: &FeeStats) -> u32 {
+        let wanted = stats
+            .fee_charged
+            .percentile(self.percentile)
+            .max(stats.last_ledger_base_fee);
+        let wanted = u32::try_from(wanted).unwrap_or(u32::MAX);
+        wanted.min(self.max_fee).max(BASE_FEE)
+    }
+
+    /// The fee per operation for a fee bump of a transaction stuck at
+    /// `stuck` per operation: at least ten times it, or `None` if the cap
+    /// is below that and the network would refuse the bump.
+    pub fn bump_fee(&self, stats: &FeeStats, stuck: u32) -> Option<u32> {
+        let least = stuck.saturating_mul(BUMP_FACTOR);
+        (least <= self.max_fee).then(|| self.fee(stats).max(least))
+    }
+}
+
+impl Horizon {
+    /// The fee per operation `strategy` picks from the current fee stats.
+    pub async fn suggested_fee(&self, strategy: &FeeStrategy) -> Result<u32, StellarError> {
+        Ok(strategy.fee(&self.fee_stats().await?))
+    }
+
+    /// Wraps `envelope` in a fee bump offering `fee` per operation, paid
+    /// and signed by `sponsor`. A fee bump is itself re-bumped around the
+    /// transaction inside it; v0 transactions cannot be bumped.
+    pub async fn fee_bump(
+        &self,
+        envelope: &Envelope,
+        sponsor: &dyn Signer,
+        fee: u32,
+    ) -> Result<Envelope, StellarError> {
+        let inner = match envelope.envelope() {
+            TransactionEnvelope::Tx(v1) => v1.clone(),
+            TransactionEnvelope::TxFeeBump(bump) => {
+                let FeeBumpTransactionInnerTx::Tx(inner) = &bump.tx.inner_tx;
+                inner.clone()
+            }
+            TransactionEnvelope::TxV0(_) => return Err(StellarError::Unbumpable),
+        };
+        // The bump counts as one more operation.
+        let operations = inner.tx.operations.len() as i64 + 1;
+        let bump = FeeBumpTransaction {
+            fee_source: account_id(sponsor.public_key())?.into(),
+            fee: i64::from(fee) * operations,
+            inner_tx: FeeBumpTransactionInnerTx::Tx(inner),
+            ext: FeeBumpTransactionExt::V0,
+        };
+        let mut bumped = Envelope::new(
+            TransactionEnvelope::TxFeeBump(FeeBumpTransactionEnvelope {
+                tx: bump,
+                signatures: Default::default(),
+            }),
+            self.network_passphrase(),
+        );
+        bumped.sign_with(sponsor).await?;
+        Ok(bumped)
+    }
+}
+
+/// The fee per operation `envelope` offers; a fee bump's counts itself as
+/// an operation.
+pub(crate) fn fee_per_operation(envelope: &Envelope) -> u32 {
+    let (fee, operations) = match envelope.envelope() {
+        TransactionEnvelope::TxV0(v0) => (i64::from(v0.tx.fee), v0.tx.operations.len()),
+        TransactionEnvelope::Tx(v1) => (i64::from(v1.tx.fee), v1.tx.operations.len()),
+        TransactionEnvelope::TxFeeBump(bump) => {
+            let FeeBumpTransactionInnerTx::Tx(inner) = &bump.tx.inner_tx;
+            (bump.tx.fee, inner.tx.operations.len() + 1)
+        }
+    };
+    u32::try_from(fee / operations.max(1) as i64).unwrap_or(u32::MAX)
+}
+
+/// The hash of the transaction itself, inside any fee bump: what shows
+/// whether it made a ledger, however it was wrapped.
+pub(crate) fn inner_hash(envelope: &Envelope) -> String {
+    match envelope.envelope() {
+        TransactionEnvelope::TxFeeBump(bump) => {
+            let FeeBumpTransactionInnerTx::Tx(inner) = &bump.tx.inner_tx;
+            Envelope::new(
+                TransactionEnvelope::Tx(inner.clone()),
+                envelope.network_passphrase(),
+            )
+            .hash_hex()
+        }
+        _ => envelope.hash_hex(),
+    }
+}
@@ -0,0 +1,300 @@
+use reqwest::{Client, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use stellrflow_signer::{network_passphrase, Envelope};
+
+use crate::error::{Problem, StellarError};
+use crate::resources::{
+    Account, Balance, ClaimableBalance, FeeStats, OfferRecord, OperationRecord, Order, Page,
+    PageQuery, TransactionRecord,
+};
+
+/// Horizon on the test network, the bot's default.
+pub const TESTNET_URL: &str = "https://horizon-testnet.stellar.org";
+/// Horizon on the public network.
+pub const PUBLIC_URL: &str = "https://horizon.stellar.org";
+
+/// A Horizon server and the network it serves.
+#[derive(Debug, Clone)]
+pub struct Horizon {
+    http: Client,
+    url: String,
+    network_passphrase: String,
+}
+
+impl Horizon {
+    pub fn new(url: impl Into<String>, network_passphrase: impl Into<String>) -> Self {
+        Horizon {
+            http: Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            network_passphrase: network_passphrase.into(),
+        }
+    }
+
+    /// SDF's Horizon for the bot's `STELLAR_NETWORK` setting: `testnet`, or
+    /// `mainnet` for the public network.
+    pub fn for_network(network: &str) -> Self {
+        let url = match network {
+            "testnet" => TESTNET_URL,
+            _ => PUBLIC_URL,
+        };
+        Horizon::new(url, network_passphrase(network))
+    }
+
+    /// Sends requests through `http`, e.g. one with a timeout or proxy.
+    pub fn with_http(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn network_passphrase(&self) -> &str {
+        &self.network_passphrase
+    }
+
+    /// Loads an account, as `loadAccount` does.
+    pub async fn account(&self, id: &str) -> Result<Account, StellarError> {
+        self.get(&format!("/accounts/{id}"), None, || format!("account {id}"))
+            .await
+    }
+
+    /// Whether the account has been created (funded) yet.
+    pub async fn account_exists(&self, id: &str) -> Result<bool, StellarError> {
+        match self.account(id).await {
+            Ok(_) => Ok(true),
+            Err(StellarError::NotFound(_)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// What the account holds, XLM first.
+    pub async fn balances(&self, id: &str) -> Result<Vec<Balance>, StellarError> {
+        Ok(self.account(id).await?.balances)
+    }
+
+    pub async fn transaction(&self, hash: &str) -> Result<TransactionRecord, StellarError> {
+        self.get(&format!("/transactions/{hash}"), None, || {
+            format!("transaction {hash}")
+        })
+        .await
+    }
+
+    /// Transactions the account took part in.
+    pub async fn account_transactions(
+        &self,
+        id: &str,
+        query: &PageQuery,
+    ) -> Result<Page<TransactionRecord>, StellarError> {
+        self.page(&format!("/accounts/{id}/transactions"), query, id)
+            .await
+    }
+
+    /// Operations the account took part in.
+    pub async fn account_operations(
+        &self,
+        id: &str,
+        query: &PageQuery,
+    ) -> Result<Page<OperationRecord>, StellarError> {
+        self.page(&format!("/accounts/{id}/operations"), query, id)
+            .await
+    }
+
+    /// The operations that moved funds to or from the account: payments,
+    /// `create_account`, path payments and merges.
+    pub async fn account_payments(
+        &self,
+        id: &str,
+        query: &PageQuery,
+    ) -> Result<Page<OperationRecord>, StellarError> {
+        self.page(&format!("/accounts/{id}/payments"), query, id)
+            .await
+    }
+
+    /// The offers the account has on the order books.
+    pub async fn account_offers(
+        &self,
+        id: &str,
+        query: &PageQuery,
+    ) -> Result<Page<OfferRecord>, StellarError> {
+        self.page(&format!("/accounts/{id}/offers"), query, id)
+            .await
+    }
+
+    /// The claimable balances `claimant` is one of the claimants of,
+    /// whether or not it can claim them yet.
+    pub async fn claimable_balances(
+        &self,
+        claimant: &str,
+        query: &PageQuery,
+    ) -> Result<Page<ClaimableBalance>, StellarError> {
+        let path = format!("/claimable_balances?claimant={claimant}");
+        self.page(&path, query, claimant).await
+    }
+
+    pub async fn claimable_balance(&self, id: &str) -> Result<ClaimableBalance, StellarError> {
+        self.get(&format!("/claimable_balances/{id}"), None, || {
+            format!("claimable balance {id}")
+        })
+        .await
+    }
+
+    /// The operations in a transaction, in order.
+    pub async fn transaction_operations(
+        &self,
+        hash: &str,
+        query: &PageQuery,
+    ) -> Result<Page<OperationRecord>, StellarError> {
+        let path = format!("/transactions/{hash}/operations");
+        let raw: RawPage<OperationRecord> = self
+            .get(&path, Some(query), || format!("transaction {hash}"))
+            .await?;
+        Ok(page(raw))
+    }
+
+    pub async fn fee_stats(&self) -> Result<FeeStats, StellarError> {
+        self.get("/fee_stats", None, || "fee stats".into()).await
+    }
+
+    /// Submits a signed transaction and waits for it to make a ledger. A
+    /// rejected transaction is [`StellarError::Horizon`] with its
+    /// [result codes](StellarError::result_codes).
+    pub async fn submit(&self, envelope: &Envelope) -> Result<TransactionRecord, StellarError> {
+        let response = self
+            .http
+            .post(format!("{}/transactions", self.url))
+            .form(&[("tx", envelope.to_xdr())])
+            .send()
+            .await?;
+        read(response, || format!("transaction {}", envelope.hash_hex())).await
+    }
+
+    /// The records of a collection that comes in one page, such as paths.
+    pub(crate) async fn records<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &(impl Serialize + ?Sized),
+        what: impl FnOnce() -> String,
+    ) -> Result<Vec<T>, StellarError> {
+        let raw: RawRecords<T> = self.fetch(path, query, what).await?;
+        Ok(raw.embedded.records)
+    }
+
+    /// A resource read with `query`, such as an order book.
+    pub(crate) async fn fetch<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &(impl Serialize + ?Sized),
+        what: impl FnOnce() -> String,
+    ) -> Result<T, StellarError> {
+        let response = self
+            .http
+            .get(format!("{}{path}", self.url))
+            .query(query)
+            .send()
+            .await?;
+        read(response, what).await
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: Option<&PageQuery>,
+        what: impl FnOnce() -> String,
+    ) -> Result<T, StellarError> {
+        let mut request = self.http.get(format!("{}{path}", self.url));
+        if let Some(query) = query {
+            request = request.query(query);
+        }
+        let response = request.send().await?;
+        read(response, what).await
+    }
+
+    pub(crate) async fn page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &PageQuery,
+        account: &str,
+    ) -> Result<Page<T>, StellarError> {
+        let raw: RawPage<T> = self
+            .get(path, Some(query), || format!("account {account}"))
+            .await?;
+        Ok(page(raw))
+    }
+}
+
+fn page<T>(raw: RawPage<T>) -> Page<T> {
+    let records = raw.embedded.records;
+    let next = match (&raw.links.next, records.is_empty()) {
+        (Some(link), false) => next_query(&link.href),
+        _ => None,
+    };
+    Page { records, next }
+}
+
+/// Reads a resource, or the problem Horizon answered with instead.
+async fn read<T: DeserializeOwned>(
+    response: Response,
+    what: impl FnOnce() -> String,
+) -> Result<T, StellarError> {
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Err(StellarError::NotFound(what()));
+    }
+    if !status.is_success() {
+        let mut problem: Problem = response.json().await.unwrap_or_default();
+        if problem.title.is_empty() {
+            problem.title = status.to_string();
+        }
+        problem.status = status.as_u16();
+        return Err(StellarError::Horizon(Box::new(problem)));
+    }
+    Ok(response.json().await?)
+}
+
+/// The query of a page's `next` link.
+fn next_query(href: &str) -> Option<PageQuery> {
+    let url = Url::parse(href).ok()?;
+    let mut query = PageQuery::default();
+    for (name, value) in url.query_pairs() {
+        match &*name {
+            "cursor" => query.cursor = Some(value.into_owned()),
+            "limit" => query.limit = value.parse().ok(),
+            "order" if value == "desc" => query.order = Some(Order::Desc),
+            "order" => query.order = Some(Order::Asc),
+            _ => {}
+        }
+    }
+    Some(query)
+}
+
+#[derive(Deserialize)]
+struct RawPage<T> {
+    #[serde(rename = "_links")]
+    links: Links,
+    #[serde(rename = "_embedded")]
+    embedded: Embedded<T>,
+}
+
+#[derive(Deserialize)]
+struct RawRecords<T> {
+    #[serde(rename = "_embedded")]
+    embedded: Embedded<T>,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    next: Option<Link>,
+}
+
+#[derive(Deserialize)]
+struct Link {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Embedded<T> {
+    records: Vec<T>,
+}
@@ -0,0 +1,107 @@
+//! A typed async client for Stellar's Horizon API.
+//!
+//! `anchor/stellarService.ts` and `telegram-bot.ts` each build their own
+//! `Horizon.Server` and repeat the same steps around it: load the source
+//! account, check whether the destination exists, and pick `payment` or
+//! `createAccount`. [`Horizon`] does those calls once, typed:
+//!
+//! - accounts and their [`Balance`]s, [`Horizon::account_exists`];
+//! - transactions, operations and payments, a [`Page`] at a time;
+//! - [`Horizon::fee_stats`] and [`Horizon::submit`].
+//!
+//! [`Horizon::send_native`] is the bot's `/send`: it pays an existing
+//! account, or creates a new one when given at least
+//! [`MIN_STARTING_BALANCE`], and signs with any
+//! [`Signer`](stellrflow_signer::Signer).
+//!
+//! Issued assets are named `CODE:ISSUER` ([`Asset`]).
+//! [`Horizon::change_trust`] adds, limits and removes trustlines, and
+//! [`Horizon::send_asset`] pays in any asset once
+//! [`Horizon::check_payment`] has found both sides with an authorized
+//! trustline, enough of the asset and room under the limit.
+//!
+//! [`Horizon::path_payment`] converts on the way: it asks
+//! `/paths/strict-send` or `/paths/strict-receive` for the best
+//! [`PaymentPath`], bounds it by a [`Slippage`], and reports the rate the
+//! payment actually got ([`PathPaid`]).
+//!
+//! [`Horizon::orderbook`] and [`Horizon::trade_aggregations`] read the
+//! SDEX for a pair of assets, priced as a [`Price`];
+//! [`Horizon::watch_price`] polls one until it crosses a threshold, and
+//! [`Horizon::manage_offer`] places, updates and cancels the
+//! [`LimitOrder`]s of `manage_sell_offer` and `manage_buy_offer`.
+//!
+//! [`Horizon::create_claimable_balance`] sets funds aside for
+//! [`Claimant`]s, each with a [`Predicate`] of when it may claim, so
+//! accounts that do not exist yet can be paid;
+//! [`Horizon::claimable_balances`] lists what an account can claim and
+//! [`Horizon::claim_balance`] takes it.
+//!
+//! [`Horizon::sponsor_account`] has one account hold the reserves of
+//! another's new entries: it creates the account, with nothing if need
+//! be, and opens its trustlines between `begin_sponsoring_future_reserves`
+//! and `end_sponsoring_future_reserves`, signed by both. That is how the
+//! treasury opens Telegram wallets on mainnet, where there is no Friendbot.
+//! [`Horizon::sponsored_reserves`] counts the reserves a sponsor holds,
+//! [`Horizon::sponsored_accounts`] lists whom for, and
+//! [`Horizon::revoke_sponsorship`] hands a [`SponsoredEntry`]'s reserve
+//! back to its owner.
+//!
+//! Sends from one account that may overlap, such as the anchor treasury's,
+//! go through a [`SubmissionQueue`]: it keeps each account's sequence
+//! number, submits one transaction at a time at the fee a [`FeeStrategy`]
+//! picks, and can have a sponsor fee-bump transactions that get stuck.
+//!
+//! With the `mock` feature, `mock::MockHorizon` serves the same endpoints
+//! from an in-memory ledger, for tests that should not touch the testnet.
+
+mod asset;
+mod claimable;
+mod error;
+mod fee;
+mod horizon;
+mod market;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod path;
+mod queue;
+mod resources;
+mod send;
+mod sponsor;
+mod trust;
+
+pub use asset::Asset;
+pub use claimable::{
+    BalanceClaimed, BalanceCreated, Claimant, Predicate, MAX_CLAIMANTS, MAX_PREDICATE_DEPTH,
+};
+pub use error::{Problem, ProblemExtras, ResultCodes, StellarError};
+pub use fee::FeeStrategy;
+pub use horizon::{Horizon, PUBLIC_URL, TESTNET_URL};
+pub use market::{
+    LimitOrder, OfferPlaced, Price, PriceCondition, PriceSource, PriceWatch, Resolution, Side,
+};
+pub use path::{PathPaid, PathPayment, Quote, Slippage, Strict};
+pub use queue::{Backoff, SubmissionQueue};
+pub use resources::{
+    Account, AccountFlags, AccountSigner, Balance, ClaimableBalance, FeeDistribution, FeeStats,
+    InnerTransaction, Level, OfferRecord, OperationRecord, Order, Orderbook, Page, PageQuery,
+    PaymentPath, Thresholds, TradeAggregation, TransactionRecord,
+};
+pub use send::{FeeBump, Sent};
+pub use sponsor::{Sponsored, SponsoredEntry, Sponsorship};
+
+use stellrflow_amount::Amount;
+
+/// The least a new account can be created with: two base reserves of
+/// 0.5 XLM.
+pub const MIN_STARTING_BALANCE: Amount = Amount::ONE;
+
+/// The fee offered per operation, in stroops, as the SDK's `BASE_FEE`.
+pub const BASE_FEE: u32 = 100;
+
+/// How long a built transaction stays valid, in seconds, as the bot's
+/// `.setTimeout(30)`.
+pub const TX_TIMEOUT: u64 = 30;
+
+/// The most operations a transaction can carry.
+pub const MAX_OPERATIONS: usize = 100;
@@ -0,0 +1,590 @@
+//! The order books: prices between two assets, waiting for one to cross a
+//! threshold, and offers that buy or sell at a limit price.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use stellar_xdr::curr::{
+    self as xdr, ClaimAtom, ManageBuyOfferOp, ManageBuyOfferResult, ManageOfferSuccessResult,
+    ManageOfferSuccessResultOffer, ManageSellOfferOp, ManageSellOfferResult, Operation,
+    OperationBody, OperationResult, OperationResultTr,
+};
+use stellrflow_amount::{Amount, STROOPS_PER_XLM};
+use stellrflow_signer::Signer;
+
+use crate::asset::Asset;
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::path::asset_query;
+use crate::resources::{OfferRecord, Orderbook, TradeAggregation, TransactionRecord};
+
+/// The largest term of a price.
+const MAX_TERM: i128 = i32::MAX as i128;
+
+/// A price as Stellar keeps it: a fraction of two positive 32-bit
+/// integers, in lowest terms.
+///
+/// It reads a decimal (`"0.125"`) or a fraction (`"1/8"`), and writes a
+/// decimal rounded to seven places. A decimal too precise for 32-bit terms
+/// becomes the nearest fraction that fits, as the SDK's `best_r` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Price {
+    n: i32,
+    d: i32,
+}
+
+impl Price {
+    /// `numerator / denominator`, if both are positive.
+    pub fn new(numerator: i32, denominator: i32) -> Option<Self> {
+        Price::approximate(numerator.into(), denominator.into())
+    }
+
+    pub fn numerator(self) -> i32 {
+        self.n
+    }
+
+    pub fn denominator(self) -> i32 {
+        self.d
+    }
+
+    /// The same rate seen from the other asset.
+    pub fn inverse(self) -> Self {
+        Price {
+            n: self.d,
+            d: self.n,
+        }
+    }
+
+    /// Halfway between two prices, as near as 32-bit terms allow.
+    pub fn midpoint(self, other: Price) -> Price {
+        let n = i128::from(self.n) * i128::from(other.d) + i128::from(other.n) * i128::from(self.d);
+        let d = 2 * i128::from(self.d) * i128::from(other.d);
+        Price::approximate(n, d).expect("a price between two prices")
+    }
+
+    /// What `amount` of the asset priced costs at this price.
+    pub(crate) fn times(self, amount: Amount, round_up: bool) -> Amount {
+        let scaled = i128::from(amount.stroops()) * i128::from(self.n);
+        let (quotient, remainder) = (scaled / i128::from(self.d), scaled % i128::from(self.d));
+        let stroops = quotient + i128::from(round_up && remainder != 0);
+        i64::try_from(stroops).map_or(Amount::MAX, Amount::from_stroops)
+    }
+
+    pub(crate) fn to_xdr(self) -> xdr::Price {
+        xdr::Price {
+            n: self.n,
+            d: self.d,
+        }
+    }
+
+    /// The fraction of 32-bit terms nearest `n / d`: itself in lowest
+    /// terms if they fit, otherwise the last continued-fraction convergent
+    /// that does. `None` if `n / d` is not positive or is out of reach.
+    fn approximate(n: i128, d: i128) -> Option<Self> {
+        if n <= 0 || d <= 0 {
+            return None;
+        }
+        let divisor = gcd(n, d);
+        let (n, d) = (n / divisor, d / divisor);
+        if n <= MAX_TERM && d <= MAX_TERM {
+            return Some(Price {
+                n: n as i32,
+                d: d as i32,
+            });
+        }
+        let (mut h, mut previous_h) = (1, 0);
+        let (mut k, mut previous_k) = (0, 1);
+        let (mut numerator, mut denominator) = (n, d);
+        while denominator != 0 {
+            let whole = numerator / denominator;
+            let (next_h, next_k) = (whole * h + previous_h, whole * k + previous_k);
+            if next_h > MAX_TERM || next_k > MAX_TERM {
+                break;
+            }
+            (previous_h, h) = (h, next_h);
+            (previous_k, k) = (k, next_k);
+            (numerator, denominator) = (denominator, numerator % denominator);
+        }
+        (h > 0 && k > 0).then_some(Price {
+            n: h as i32,
+            d: k as i32,
+        })
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Ord for Price {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (i64::from(self.n) * i64::from(other.d)).cmp(&(i64::from(other.n) * i64::from(self.d)))
+    }
+}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Price {
+    type Err = StellarError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let invalid = || StellarError::InvalidPrice(text.to_string());
+        let (n, d) = match text.split_once('/') {
+            Some((n, d)) => {
+                let term = |term: &str| {
+                    let term = term.trim();
+                    if term.is_empty() || !term.bytes().all(|b| b.is_ascii_digit()) {
+                        return Err(invalid());
+                    }
+                    term.parse::<i128>().map_err(|_| invalid())
+                };
+                (term(n)?, term(d)?)
+            }
+            None => {
+                let amount: Amount = text.parse().map_err(|_| invalid())?;
+                (amount.stroops().into(), STROOPS_PER_XLM.into())
+            }
+        };
+        Price::approximate(n, d).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scaled = i128::from(self.n) * i128::from(STROOPS_PER_XLM);
+        let stroops = (2 * scaled + i128::from(self.d)) / (2 * i128::from(self.d));
+        let amount = i64::try_from(stroops).map_or(Amount::MAX, Amount::from_stroops);
+        write!(f, "{amount}")
+    }
+}
+
+impl Serialize for Price {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A decimal or fraction, or Horizon's `price_r`: `{ "n": 1, "d": 8 }`,
+/// capitalized in trade aggregations.
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Terms {
+                #[serde(alias = "N")]
+                n: i64,
+                #[serde(alias = "D")]
+                d: i64,
+            },
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+            Raw::Terms { n, d } => Price::approximate(n.into(), d.into())
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid price {n}/{d}"))),
+        }
+    }
+}
+
+/// How wide a bucket of [`Horizon::trade_aggregations`] is; Horizon takes
+/// no others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    #[default]
+    Minute,
+    FiveMinutes,
+    FifteenMinutes,
+    Hour,
+    Day,
+    Week,
+}
+
+impl Resolution {
+    pub fn millis(self) -> u64 {
+        match self {
+            Resolution::Minute => 60_000,
+            Resolution::FiveMinutes => 300_000,
+            Resolution::FifteenMinutes => 900_000,
+            Resolution::Hour => 3_600_000,
+            Resolution::Day => 86_400_000,
+            Resolution::Week => 604_800_000,
+        }
+    }
+}
+
+/// Which price of a pair to read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PriceSource {
+    /// The best offer to buy the base asset.
+    Bid,
+    /// The best offer to sell it.
+    Ask,
+    /// Halfway between the two.
+    #[default]
+    Mid,
+    /// The close of the latest minute that had trades.
+    Last,
+}
+
+/// A price to wait for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceCondition {
+    /// At or above the price.
+    Above(Price),
+    /// At or below the price.
+    Below(Price),
+}
+
+impl PriceCondition {
+    pub fn threshold(self) -> Price {
+        match self {
+            PriceCondition::Above(price) | PriceCondition::Below(price) => price,
+        }
+    }
+
+    pub fn is_met(self, price: Price) -> bool {
+        match self {
+            PriceCondition::Above(threshold) => price >= threshold,
+            PriceCondition::Below(threshold) => price <= threshold,
+        }
+    }
+}
+
+/// A pair's price to poll until it meets a condition, for
+/// [`Horizon::watch_price`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceWatch {
+    pub base: Asset,
+    /// The asset the price is in.
+    pub counter: Asset,
+    pub condition: PriceCondition,
+    pub source: PriceSource,
+    /// How long to wait between polls.
+    pub interval: Duration,
+}
+
+impl PriceWatch {
+    /// Watches the mid price every 30 seconds.
+    pub fn new(base: Asset, counter: Asset, condition: PriceCondition) -> Self {
+        PriceWatch {
+            base,
+            counter,
+            condition,
+            source: PriceSource::default(),
+            interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Which side of the book an offer is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// `manage_sell_offer`: a fixed amount of the asset sold.
+    Sell,
+    /// `manage_buy_offer`: a fixed amount of the asset bought.
+    Buy,
+}
+
+/// An offer to place, update or cancel with [`Horizon::manage_offer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitOrder {
+    pub side: Side,
+    pub selling: Asset,
+    pub buying: Asset,
+    /// How much of `selling` to sell under [`Side::Sell`], or of `buying`
+    /// to buy under [`Side::Buy`]. Zero cancels the offer.
+    pub amount: Amount,
+    /// What one unit of `selling` costs in `buying` under [`Side::Sell`],
+    /// or one unit of `buying` in `selling` under [`Side::Buy`].
+    pub price: Price,
+    /// The offer to update or cancel; 0 places a new one.
+    pub offer_id: i64,
+}
+
+impl LimitOrder {
+    /// Sells `amount` of `selling` for `buying` at `price` or better.
+    pub fn sell(selling: Asset, amount: Amount, buying: Asset, price: Price) -> Self {
+        LimitOrder {
+            side: Side::Sell,
+            selling,
+            buying,
+            amount,
+            price,
+            offer_id: 0,
+        }
+    }
+
+    /// Buys `amount` of `buying` with `selling` at `price` or better.
+    pub fn buy(buying: Asset, amount: Amount, selling: Asset, price: Price) -> Self {
+        LimitOrder {
+            side: Side::Buy,
+            selling,
+            buying,
+            amount,
+            price,
+            offer_id: 0,
+        }
+    }
+
+    /// Takes `offer` off the book.
+    pub fn cancel(offer: &OfferRecord) -> Self {
+        LimitOrder::sell(
+            offer.selling.clone(),
+            Amount::ZERO,
+            offer.buying.clone(),
+            offer.price,
+        )
+        .with_offer_id(offer.id)
+    }
+
+    /// Updates, or with no amount cancels, the offer `offer_id`.
+    pub fn with_offer_id(mut self, offer_id: i64) -> Self {
+        self.offer_id = offer_id;
+        self
+    }
+
+    pub fn is_cancel(&self) -> bool {
+        self.amount == Amount::ZERO
+    }
+
+    /// The most of `selling` the order can sell.
+    pub fn most_sold(&self) -> Amount {
+        self.bounds().0
+    }
+
+    /// The most of `selling` the order can sell, and the least of `buying`
+    /// it buys with it.
+    fn bounds(&self) -> (Amount, Amount) {
+        match self.side {
+            Side::Sell => (self.amount, self.price.times(self.amount, false)),
+            Side::Buy => (self.price.times(self.amount, true), self.amount),
+        }
+    }
+}
+
+/// What [`Horizon::manage_offer`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferPlaced {
+    pub hash: String,
+    pub ledger: u32,
+    /// The offer left on the book; `None` once it is cancelled, or when
+    /// offers already there filled it at once.
+    pub offer_id: Option<i64>,
+    /// How much of the asset sold it has left to sell.
+    pub amount: Amount,
+    /// What the offers it crossed took of the asset sold, and gave of the
+    /// asset bought.
+    pub sold: Amount,
+    pub bought: Amount,
+}
+
+impl OfferPlaced {
+    /// Reads the offer from the transaction's result. If the result cannot
+    /// be read, the order stands in for it: an update keeps its id, and a
+    /// new offer's is unknown.
+    pub(crate) fn new(record: TransactionRecord, order: &LimitOrder) -> Self {
+        let (offer_id, amount, sold, bought) = match offer_result(&record) {
+            Some(result) => {
+                let (offer_id, amount) = match result.offer {
+                    ManageOfferSuccessResultOffer::Created(offer)
+                    | ManageOfferSuccessResultOffer::Updated(offer) => {
+                        (Some(offer.offer_id), Amount::from_stroops(offer.amount))
+                    }
+                    ManageOfferSuccessResultOffer::Deleted => (None, Amount::ZERO),
+                };
+                let (sold, bought) = result.offers_claimed.iter().fold((0, 0), |sum, claim| {
+                    // The offers claimed sold what this one bought.
+                    let (their_sold, their_bought) = match claim {
+                        ClaimAtom::V0(atom) => (atom.amount_sold, atom.amount_bought),
+                        ClaimAtom::OrderBook(atom) => (atom.amount_sold, atom.amount_bought),
+                        ClaimAtom::LiquidityPool(atom) => (atom.amount_sold, atom.amount_bought),
+                    };
+                    (sum.0 + their_bought, sum.1 + their_sold)
+                });
+                (
+                    offer_id,
+                    amount,
+                    Amount::from_stroops(sold),
+                    Amount::from_stroops(bought),
+                )
+            }
+            None => {
+                let offer_id =
+                    (order.offer_id != 0 && !order.is_cancel()).then_some(order.offer_id);
+                (offer_id, order.bounds().0, Amount::ZERO, Amount::ZERO)
+            }
+        };
+        OfferPlaced {
+            hash: record.hash,
+            ledger: record.ledger,
+            offer_id,
+            amount,
+            sold,
+            bought,
+        }
+    }
+}
+
+/// The result of the first offer operation in a successful transaction.
+fn offer_result(record: &TransactionRecord) -> Option<ManageOfferSuccessResult> {
+    record
+        .operation_results()?
+        .into_iter()
+        .find_map(|operation| match operation {
+            OperationResult::OpInner(OperationResultTr::ManageSellOffer(
+                ManageSellOfferResult::Success(success),
+            ))
+            | OperationResult::OpInner(OperationResultTr::ManageBuyOffer(
+                ManageBuyOfferResult::Success(success),
+            )) => Some(success),
+            _ => None,
+        })
+}
+
+impl Horizon {
+    /// The offers between `base` and `counter`, at most `limit` price
+    /// levels a side, from `/order_book`.
+    pub async fn orderbook(
+        &self,
+        base: &Asset,
+        counter: &Asset,
+        limit: u32,
+    ) -> Result<Orderbook, StellarError> {
+        let mut query = asset_query("selling", base);
+        query.extend(asset_query("buying", counter));
+        query.push(("limit".into(), limit.to_string()));
+        self.fetch("/order_book", &query, || {
+            format!("order book of {base} in {counter}")
+        })
+        .await
+    }
+
+    /// The latest `limit` buckets of trades between `base` and `counter`,
+    /// newest first, from `/trade_aggregations`. Buckets without trades
+    /// are left out.
+    pub async fn trade_aggregations(
+        &self,
+        base: &Asset,
+        counter: &Asset,
+        resolution: Resolution,
+        limit: u32,
+    ) -> Result<Vec<TradeAggregation>, StellarError> {
+        let mut query = asset_query("base", base);
+        query.extend(asset_query("counter", counter));
+        query.push(("resolution".into(), resolution.millis().to_string()));
+        query.push(("order".into(), "desc".into()));
+        query.push(("limit".into(), limit.to_string()));
+        self.records("/trade_aggregations", &query, || {
+            format!("trades of {base} in {counter}")
+        })
+        .await
+    }
+
+    /// `base`'s price in `counter`, or `None` when that side of the book
+    /// is empty or, for [`PriceSource::Last`], nothing has traded.
+    pub async fn price(
+        &self,
+        base: &Asset,
+        counter: &Asset,
+        source: PriceSource,
+    ) -> Result<Option<Price>, StellarError> {
+        if source == PriceSource::Last {
+            let latest = self
+                .trade_aggregations(base, counter, Resolution::Minute, 1)
+                .await?;
+            return Ok(latest.first().map(|bucket| bucket.close));
+        }
+        let book = self.orderbook(base, counter, 1).await?;
+        Ok(match source {
+            PriceSource::Bid => book.best_bid(),
+            PriceSource::Ask => book.best_ask(),
+            _ => book.mid(),
+        })
+    }
+
+    /// Polls the price `watch` names until it meets the condition, and
+    /// answers it. A failed poll ends the watch.
+    pub async fn watch_price(&self, watch: &PriceWatch) -> Result<Price, StellarError> {
+        loop {
+            let price = self
+                .price(&watch.base, &watch.counter, watch.source)
+                .await?;
+            if let Some(price) = price.filter(|&price| watch.condition.is_met(price)) {
+                return Ok(price);
+            }
+            tokio::time::sleep(watch.interval).await;
+        }
+    }
+
+    /// Places, updates or cancels an offer from `source`'s account, once
+    /// the account is found to hold what the offer may sell and to have
+    /// room for what it may buy.
+    pub async fn manage_offer(
+        &self,
+        source: &dyn Signer,
+        order: &LimitOrder,
+    ) -> Result<OfferPlaced, StellarError> {
+        let operation = self.offer_operation(source.public_key(), order).await?;
+        let record = self.sign_and_submit(source, vec![operation]).await?;
+        Ok(OfferPlaced::new(record, order))
+    }
+
+    /// The checked operation for [`manage_offer`](Self::manage_offer).
+    pub(crate) async fn offer_operation(
+        &self,
+        source: &str,
+        order: &LimitOrder,
+    ) -> Result<Operation, StellarError> {
+        if order.amount < Amount::ZERO || (order.is_cancel() && order.offer_id == 0) {
+            return Err(StellarError::InvalidAmount(order.amount));
+        }
+        if !order.is_cancel() {
+            let (sold, bought) = order.bounds();
+            self.check_sender(source, &order.selling, sold).await?;
+            self.check_receiver(source, &order.buying, bought).await?;
+        }
+        let (selling, buying) = (order.selling.to_xdr()?, order.buying.to_xdr()?);
+        let body = match order.side {
+            Side::Sell => OperationBody::ManageSellOffer(ManageSellOfferOp {
+                selling,
+                buying,
+                amount: order.amount.stroops(),
+                price: order.price.to_xdr(),
+                offer_id: order.offer_id,
+            }),
+            Side::Buy => OperationBody::ManageBuyOffer(ManageBuyOfferOp {
+                selling,
+                buying,
+                buy_amount: order.amount.stroops(),
+                price: order.price.to_xdr(),
+                offer_id: order.offer_id,
+            }),
+        };
+        Ok(Operation {
+            source_account: None,
+            body,
+        })
+    }
+}
@@ -0,0 +1,2209 @@
+//! An in-memory Horizon for tests, behind the `mock` feature.
+//!
+//! [`MockHorizon`] serves the endpoints [`Horizon`] calls from a ledger of
+//! accounts holding XLM and trustlines, checking submissions the way
+//! Stellar does: the source account and sequence number, the time bounds
+//! and fee, a signature from every source account, then each operation
+//! against balances, trustlines and the base reserve. A rejected
+//! transaction gets the same problem document and result codes as from the
+//! real Horizon; one that fails in an operation (`tx_failed`) still uses up
+//! its sequence number and fee.
+//!
+//! Path payments convert at prices set with [`MockHorizon::set_price`], as
+//! if every order book were deep enough for any amount; paths go straight
+//! from one asset to the other or through one asset in between.
+//!
+//! Offers rest on the book without crossing, holding a base reserve and
+//! their liabilities, and `/order_book` lists them unless a recorded
+//! response was set with [`MockHorizon::set_orderbook`].
+//! `/trade_aggregations` answers what [`MockHorizon::set_trade_aggregations`]
+//! recorded, or nothing.
+//!
+//! Claimable balances are kept by ID, with their predicates' relative times
+//! made absolute when created; their creator holds a base reserve for each
+//! claimant until one of them claims the balance.
+//!
+//! Between `begin_sponsoring_future_reserves` and
+//! `end_sponsoring_future_reserves`, the sponsor holds the reserves of the
+//! accounts and trustlines the sponsored account gets, until it revokes
+//! the sponsorship; `/accounts?sponsor=` lists whom it sponsors.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Form, Json, Router};
+use chrono::{DateTime, SecondsFormat};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stellar_xdr::curr::{
+    self as xdr, AccountId, BeginSponsoringFutureReservesResult, ChangeTrustAsset,
+    ChangeTrustResult, ClaimClaimableBalanceResult, CreateAccountResult,
+    CreateClaimableBalanceResult, EndSponsoringFutureReservesResult, FeeBumpTransactionInnerTx,
+    Hash, InnerTransactionResult, InnerTransactionResultExt, InnerTransactionResultPair,
+    InnerTransactionResultResult, LedgerKey, Limits, ManageBuyOfferResult,
+    ManageOfferSuccessResult, ManageOfferSuccessResultOffer, ManageSellOfferResult, Memo,
+    MuxedAccount, OfferEntry, OfferEntryExt, OperationBody, OperationResult, OperationResultTr,
+    PathPaymentStrictReceiveResult, PathPaymentStrictReceiveResultSuccess,
+    PathPaymentStrictSendResult, PathPaymentStrictSendResultSuccess, PaymentResult, Preconditions,
+    RevokeSponsorshipOp, RevokeSponsorshipResult, SimplePaymentResult, TransactionEnvelope,
+    TransactionResult, TransactionResultExt, TransactionResultResult, TrustLineAsset, WriteXdr,
+};
+use stellrflow_amount::{Amount, Rate};
+use stellrflow_signer::{Envelope, TESTNET_PASSPHRASE};
+use tokio::task::JoinHandle;
+
+use crate::claimable::{balance_id, balance_id_hex};
+use crate::resources::{FeeDistribution, FeeStats};
+use crate::send::account_id;
+use crate::{Asset, Claimant, Horizon, Predicate, Price, BASE_FEE};
+
+/// Half an XLM, in stroops; an account must keep two of them.
+const BASE_RESERVE: i64 = 5_000_000;
+
+/// A Horizon on a local port, serving the test network until dropped.
+pub struct MockHorizon {
+    url: String,
+    ledger: Arc<Mutex<Ledger>>,
+    server: JoinHandle<()>,
+}
+
+impl MockHorizon {
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind a local port");
+        let url = format!("http://{}", listener.local_addr().expect("a bound address"));
+        let ledger = Arc::new(Mutex::new(Ledger::new(url.clone())));
+        let app = router(ledger.clone());
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app)
+                .await
+                .expect("serve mock Horizon");
+        });
+        MockHorizon {
+            url,
+            ledger,
+            server,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A client for this server, on the test network.
+    pub fn client(&self) -> Horizon {
+        Horizon::new(&self.url, TESTNET_PASSPHRASE)
+    }
+
+    /// Adds `xlm` to the account, creating it if needed, as Friendbot does.
+    pub fn fund(&self, account: &str, xlm: Amount) {
+        assert!(xlm.is_positive(), "a positive XLM amount");
+        let stroops = xlm.stroops();
+        let mut ledger = self.lock();
+        let created_sequence = ledger.created_sequence();
+        ledger
+            .accounts
+            .entry(account.to_string())
+            .or_insert(MockAccount {
+                sequence: created_sequence,
+                ..MockAccount::default()
+            })
+            .balance += stroops;
+    }
+
+    /// Whether new trustlines to `issuer`'s assets need its authorization
+    /// before they can hold them.
+    pub fn set_auth_required(&self, issuer: &str, required: bool) {
+        self.lock()
+            .accounts
+            .get_mut(issuer)
+            .expect("a funded issuer")
+            .auth_required = required;
+    }
+
+    /// Authorizes the account's trustline to `asset`, or revokes it, as
+    /// the issuer's `set_trust_line_flags` does.
+    pub fn set_authorized(&self, account: &str, asset: &Asset, authorized: bool) {
+        self.lock()
+            .accounts
+            .get_mut(account)
+            .and_then(|account| account.trustlines.get_mut(asset))
+            .expect("an existing trustline")
+            .authorized = authorized;
+    }
+
+    /// The account's balance of `asset`, if it exists and, for an issued
+    /// asset, has a trustline to it.
+    pub fn asset_balance(&self, account: &str, asset: &Asset) -> Option<Amount> {
+        let ledger = self.lock();
+        let account = ledger.accounts.get(account)?;
+        let stroops = match asset {
+            Asset::Native => account.balance,
+            Asset::Issued { .. } => account.trustlines.get(asset)?.balance,
+        };
+        Some(Amount::from_stroops(stroops))
+    }
+
+    /// The account's XLM balance, if it exists.
+    pub fn balance(&self, account: &str) -> Option<Amount> {
+        let ledger = self.lock();
+        ledger
+            .accounts
+            .get(account)
+            .map(|account| Amount::from_stroops(account.balance))
+    }
+
+    /// The account's current sequence number, if it exists.
+    pub fn sequence(&self, account: &str) -> Option<i64> {
+        self.lock()
+            .accounts
+            .get(account)
+            .map(|account| account.sequence)
+    }
+
+    /// Makes one unit of `from` buy `price` (a decimal or a fraction such
+    /// as `1/8`) of `to`, and one unit of `to` buy its inverse of `from`.
+    pub fn set_price(&self, from: &Asset, to: &Asset, price: &str) {
+        let price: Rate = price.parse().expect("a positive price");
+        let (numerator, denominator) = (price.numerator(), price.denominator());
+        let mut ledger = self.lock();
+        let prices = &mut ledger.market.prices;
+        prices.insert((from.clone(), to.clone()), (numerator, denominator));
+        prices.insert((to.clone(), from.clone()), (denominator, numerator));
+    }
+
+    /// Answers `/order_book` for `base` in `counter` with `book`, a
+    /// response recorded from Horizon, instead of the offers in the ledger.
+    pub fn set_orderbook(&self, base: &Asset, counter: &Asset, book: Value) {
+        self.lock()
+            .orderbooks
+            .insert((base.clone(), counter.clone()), book);
+    }
+
+    /// Answers `/trade_aggregations` for `base` in `counter` with
+    /// `trades`, a response recorded from Horizon, whatever the query.
+    pub fn set_trade_aggregations(&self, base: &Asset, counter: &Asset, trades: Value) {
+        self.lock()
+            .trade_aggregations
+            .insert((base.clone(), counter.clone()), trades);
+    }
+
+    /// Rejects the next submission with transaction result `code`, such as
+    /// `tx_too_late`, without applying it.
+    pub fn reject_next(&self, code: &str) {
+        self.lock()
+            .faults
+            .push_back(Fault::Reject(code.to_string()));
+    }
+
+    /// Applies the next submission as usual, then answers it with Horizon's
+    /// 504 timeout, as when a transaction makes a ledger too late for the
+    /// request that sent it.
+    pub fn time_out_next(&self) {
+        self.lock().faults.push_back(Fault::TimeOut);
+    }
+
+    /// Congests the network: transactions offering less than `fee` per
+    /// operation time out without making a ledger, and the rest are charged
+    /// `fee`. [`BASE_FEE`] ends the surge.
+    pub fn set_surge_fee(&self, fee: u32) {
+        self.lock().surge_fee = fee.max(BASE_FEE);
+    }
+
+    /// What `/fee_stats` answers; every fee is the surge fee until set.
+    pub fn set_fee_stats(&self, stats: FeeStats) {
+        self.lock().fee_stats = Some(stats);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Ledger> {
+        self.ledger.lock().expect("mock ledger poisoned")
+    }
+}
+
+impl Drop for MockHorizon {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+type Shared = Arc<Mutex<Ledger>>;
+
+fn router(ledger: Shared) -> Router {
+    Router::new()
+        .route("/accounts", get(accounts))
+        .route("/accounts/{id}", get(account))
+        .route("/accounts/{id}/transactions", get(account_transactions))
+        .route("/accounts/{id}/operations", get(account_operations))
+        .route("/accounts/{id}/payments", get(account_payments))
+        .route("/accounts/{id}/offers", get(account_offers))
+        .route("/transactions", post(submit))
+        .route("/transactions/{hash}", get(transaction))
+        .route(
+            "/transactions/{hash}/operations",
+            get(transaction_operations),
+        )
+        .route("/paths/strict-send", get(strict_send_paths))
+        .route("/paths/strict-receive", get(strict_receive_paths))
+        .route("/order_book", get(orderbook))
+        .route("/trade_aggregations", get(trade_aggregations))
+        .route("/claimable_balances", get(claimable_balances))
+        .route("/claimable_balances/{id}", get(claimable_balance))
+        .route("/fee_stats", get(fee_stats))
+        .with_state(ledger)
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockAccount {
+    /// In stroops.
+    balance: i64,
+    sequence: i64,
+    trustlines: BTreeMap<Asset, MockTrustline>,
+    /// By offer ID.
+    offers: BTreeMap<i64, MockOffer>,
+    /// Whether new trustlines to the assets it issues start unauthorized.
+    auth_required: bool,
+    /// The base reserves it holds for entries that are not its own, such
+    /// as the claimable balances it created.
+    sponsoring: usize,
+    /// The account holding its own two base reserves, if it was created
+    /// sponsored.
+    sponsor: Option<String>,
+}
+
+impl MockAccount {
+    /// What the account can send while keeping its minimum balance (two
+    /// base reserves, and one more for each trustline, offer and entry it
+    /// sponsors, less those another account sponsors for it) and what its
+    /// offers may sell.
+    fn spendable(&self) -> i64 {
+        let reserves = 2 + (self.subentries() + self.sponsoring) as i64 - self.sponsored() as i64;
+        self.balance - reserves * BASE_RESERVE - self.selling_liabilities(&Asset::Native)
+    }
+
+    /// The base reserves other accounts hold for it.
+    fn sponsored(&self) -> usize {
+        let lines = self
+            .trustlines
+            .values()
+            .filter(|line| line.sponsor.is_some())
+            .count();
+        2 * usize::from(self.sponsor.is_some()) + lines
+    }
+
+    fn subentries(&self) -> usize {
+        self.trustlines.len() + self.offers.len()
+    }
+
+    /// How much of `asset` its offers may sell.
+    fn selling_liabilities(&self, asset: &Asset) -> i64 {
+        self.offers
+            .values()
+            .filter(|offer| offer.selling == *asset)
+            .map(|offer| offer.amount)
+            .sum()
+    }
+
+    /// How much of `asset` its offers may buy.
+    fn buying_liabilities(&self, asset: &Asset) -> i64 {
+        self.offers
+            .values()
+            .filter(|offer| offer.buying == *asset)
+            .map(|offer| offer.buys())
+            .sum()
+    }
+}
+
+/// An offer resting on the book.
+#[derive(Debug, Clone)]
+struct MockOffer {
+    selling: Asset,
+    buying: Asset,
+    /// Of `selling`, in stroops.
+    amount: i64,
+    /// Of `buying` per unit of `selling`.
+    price: Price,
+}
+
+impl MockOffer {
+    /// What it buys if it all sells, in stroops.
+    fn buys(&self) -> i64 {
+        self.price
+            .times(Amount::from_stroops(self.amount), false)
+            .stroops()
+    }
+}
+
+/// A claimable balance waiting for one of its claimants.
+#[derive(Debug, Clone)]
+struct MockBalance {
+    asset: Asset,
+    /// In stroops.
+    amount: i64,
+    /// With the times in their predicates made absolute.
+    claimants: Vec<Claimant>,
+    /// The account holding its reserves.
+    sponsor: String,
+    last_modified_ledger: u32,
+    paging_token: i64,
+}
+
+#[derive(Debug, Clone)]
+struct MockTrustline {
+    /// In stroops, as is the limit.
+    balance: i64,
+    limit: i64,
+    authorized: bool,
+    /// The account holding its base reserve, if not its owner.
+    sponsor: Option<String>,
+}
+
+/// What happens to a submission instead of the usual answer.
+enum Fault {
+    Reject(String),
+    TimeOut,
+}
+
+/// A transaction or operation as Horizon lists it.
+struct Record {
+    paging_token: i64,
+    /// The accounts it involved, whose collections list it.
+    accounts: Vec<String>,
+    /// Whether `/payments` lists it.
+    payment: bool,
+    json: Value,
+}
+
+impl Record {
+    fn involves(&self, account: &str) -> bool {
+        self.accounts.iter().any(|a| a == account)
+    }
+}
+
+/// Prices between assets, as order books that fill any amount.
+#[derive(Debug, Default)]
+struct Market {
+    /// How much of the second asset one unit of the first buys, as a
+    /// numerator and denominator.
+    prices: BTreeMap<(Asset, Asset), (u64, u64)>,
+}
+
+impl Market {
+    fn price(&self, from: &Asset, to: &Asset) -> Option<(u64, u64)> {
+        if from == to {
+            return Some((1, 1));
+        }
+        self.prices.get(&(from.clone(), to.clone())).copied()
+    }
+
+    /// The assets in between on each way from `from` to `to`: none when
+    /// there is a price between them, or one that both have a price with.
+    fn paths(&self, from: &Asset, to: &Asset) -> Vec<Vec<Asset>> {
+        let mut paths = Vec::new();
+        if self.price(from, to).is_some() {
+            paths.push(Vec::new());
+        }
+        for (start, through) in self.prices.keys() {
+            if start == from && through != to && self.price(through, to).is_some() {
+                paths.push(vec![through.clone()]);
+            }
+        }
+        paths
+    }
+
+    /// What `amount` of the first asset in `hops` buys of the last, rounded
+    /// down at each step; `None` where there is no price.
+    fn send(&self, hops: &[Asset], amount: i64) -> Option<i64> {
+        hops.windows(2).try_fold(amount, |amount, pair| {
+            let (numerator, denominator) = self.price(&pair[0], &pair[1])?;
+            let bought = i128::from(amount) * i128::from(numerator) / i128::from(denominator);
+            i64::try_from(bought).ok()
+        })
+    }
+
+    /// What of the first asset in `hops` buys `amount` of the last, rounded
+    /// up at each step.
+    fn receive(&self, hops: &[Asset], amount: i64) -> Option<i64> {
+        hops.windows(2).try_rfold(amount, |amount, pair| {
+            let (numerator, denominator) = self.price(&pair[0], &pair[1])?;
+            let cost = i128::from(amount) * i128::from(denominator);
+            let cost = (cost + i128::from(numerator) - 1) / i128::from(numerator);
+            i64::try_from(cost).ok()
+        })
+    }
+}
+
+struct Ledger {
+    url: String,
+    /// The last closed ledger; every successful submission closes one.
+    sequence: u32,
+    accounts: BTreeMap<String, MockAccount>,
+    transactions: Vec<Record>,
+    operations: Vec<Record>,
+    market: Market,
+    /// The ID of the last offer placed.
+    last_offer_id: i64,
+    /// Claimable balances, by ID.
+    balances: BTreeMap<String, MockBalance>,
+    /// Recorded `/order_book` responses, by base and counter asset.
+    orderbooks: BTreeMap<(Asset, Asset), Value>,
+    /// Recorded `/trade_aggregations` responses, by base and counter asset.
+    trade_aggregations: BTreeMap<(Asset, Asset), Value>,
+    fee_stats: Option<FeeStats>,
+    /// For the next submissions, in order.
+    faults: VecDeque<Fault>,
+    /// The least fee per operation that makes a ledger, and what each
+    /// operation is charged.
+    surge_fee: u32,
+}
+
+impl Ledger {
+    fn new(url: String) -> Self {
+        Ledger {
+            url,
+            sequence: 1,
+            accounts: BTreeMap::new(),
+            transactions: Vec::new(),
+            operations: Vec::new(),
+            market: Market::default(),
+            last_offer_id: 0,
+            balances: BTreeMap::new(),
+            orderbooks: BTreeMap::new(),
+            trade_aggregations: BTreeMap::new(),
+            fee_stats: None,
+            faults: VecDeque::new(),
+            surge_fee: BASE_FEE,
+        }
+    }
+
+    /// The sequence number of an account created now, as Stellar numbers
+    /// them: the ledger shifted into the high 32 bits.
+    fn created_sequence(&self) -> i64 {
+        i64::from(self.sequence + 1) << 32
+    }
+
+    fn submit(&mut self, xdr: &str) -> Result<Value, Rejection> {
+        let envelope = Envelope::from_xdr(xdr, TESTNET_PASSPHRASE).map_err(|_| malformed())?;
+        let (v1, bump) = match envelope.envelope() {
+            TransactionEnvelope::Tx(v1) => (v1.clone(), None),
+            TransactionEnvelope::TxFeeBump(bump) => {
+                let FeeBumpTransactionInnerTx::Tx(inner) = &bump.tx.inner_tx;
+                (inner.clone(), Some(&bump.tx))
+            }
+            TransactionEnvelope::TxV0(_) => return Err(malformed()),
+        };
+        // A fee bump reports its inner transaction's failures as
+        // `tx_fee_bump_inner_failed`.
+        let reject = |code: &str, operations: &[&str]| match bump {
+            Some(_) => failed_inner(xdr, code, operations),
+            None => failed(xdr, code, operations),
+        };
+        let inner = Envelope::new(TransactionEnvelope::Tx(v1.clone()), TESTNET_PASSPHRASE);
+        let tx = &v1.tx;
+        let operations = tx.operations.len() as i64;
+        let inner_rate = i64::from(tx.fee) / operations.max(1);
+
+        // Who pays the fee, what they offer per operation, and for how many
+        // operations; a fee bump counts as one.
+        let (fee_source, rate, charged_operations) = match bump {
+            Some(bump) => {
+                let fee_source = address(&bump.fee_source);
+                if !self.accounts.contains_key(&fee_source) {
+                    return Err(failed(xdr, "tx_no_account", &[]));
+                }
+                let rate = bump.fee / (operations + 1);
+                if rate < i64::from(BASE_FEE) || rate < inner_rate {
+                    return Err(failed(xdr, "tx_insufficient_fee", &[]));
+                }
+                if envelope.signature_by(&fee_source).is_none() {
+                    return Err(failed(xdr, "tx_bad_auth", &[]));
+                }
+                (fee_source, rate, operations + 1)
+            }
+            None => (address(&tx.source_account), inner_rate, operations),
+        };
+
+        let source = address(&tx.source_account);
+        let Some(account) = self.accounts.get(&source) else {
+            return Err(reject("tx_no_account", &[]));
+        };
+        if tx.seq_num.0 != account.sequence + 1 {
+            return Err(reject("tx_bad_seq", &[]));
+        }
+        let now = unix_now();
+        if let Preconditions::Time(bounds) = &tx.cond {
+            if now < bounds.min_time.0 {
+                return Err(reject("tx_too_early", &[]));
+            }
+            if bounds.max_time.0 != 0 && now > bounds.max_time.0 {
+                return Err(reject("tx_too_late", &[]));
+            }
+        }
+        if bump.is_none() && rate < i64::from(BASE_FEE) {
+            return Err(reject("tx_insufficient_fee", &[]));
+        }
+        let signers = std::iter::once(source.clone()).chain(
+            tx.operations
+                .iter()
+                .filter_map(|op| op.source_account.as_ref().map(address)),
+        );
+        for signer in signers {
+            if inner.signature_by(&signer).is_none() {
+                return Err(reject("tx_bad_auth", &[]));
+            }
+        }
+        // Every transaction in a ledger pays its lowest accepted fee.
+        let fee = i64::from(self.surge_fee) * charged_operations;
+        if self.accounts[&fee_source].balance < fee {
+            return Err(failed(xdr, "tx_insufficient_balance", &[]));
+        }
+        // Outbid, it waits in the queue until Horizon gives up on it.
+        if rate < i64::from(self.surge_fee) {
+            return Err(timeout());
+        }
+
+        // From here on the fee and sequence number are spent, even if an
+        // operation fails.
+        self.accounts
+            .get_mut(&fee_source)
+            .expect("checked above")
+            .balance -= fee;
+        self.accounts
+            .get_mut(&source)
+            .expect("checked above")
+            .sequence = tx.seq_num.0;
+
+        let mut pending = Pending {
+            accounts: self.accounts.clone(),
+            balances: self.balances.clone(),
+            last_offer_id: self.last_offer_id,
+            market: &self.market,
+            created_sequence: self.created_sequence(),
+            ledger: self.sequence + 1,
+            close_time: now as i64,
+            tx_source: tx.source_account.clone().account_id(),
+            tx_sequence: tx.seq_num.0,
+            sponsorships: BTreeMap::new(),
+        };
+        let mut codes = Vec::new();
+        let mut applied = Vec::new();
+        for (index, op) in tx.operations.iter().enumerate() {
+            let op_source = op.source_account.as_ref().map_or(source.clone(), address);
+            match apply(&mut pending, index as u32, &op_source, &op.body) {
+                Ok(effect) => {
+                    codes.push("op_success");
+                    applied.push((op_source, effect));
+                }
+                Err(code) => {
+                    codes.push(code);
+                    return Err(reject("tx_failed", &codes));
+                }
+            }
+        }
+        // Every sponsorship begun must end within the transaction.
+        if !pending.sponsorships.is_empty() {
+            return Err(reject("tx_bad_sponsorship", &[]));
+        }
+        let Pending {
+            accounts,
+            balances,
+            last_offer_id,
+            ..
+        } = pending;
+        self.accounts = accounts;
+        self.balances = balances;
+        self.last_offer_id = last_offer_id;
+        self.sequence += 1;
+
+        let hash = envelope.hash_hex();
+        let created_at = DateTime::from_timestamp(now as i64, 0)
+            .expect("now is a valid time")
+            .to_rfc3339_opts(SecondsFormat::Secs, true);
+        let token = transaction_token(self.sequence);
+        let (memo_type, memo) = match &tx.memo {
+            Memo::None => ("none", None),
+            Memo::Text(text) => ("text", Some(text.to_string())),
+            Memo::Id(id) => ("id", Some(id.to_string())),
+            Memo::Hash(_) => ("hash", None),
+            Memo::Return(_) => ("return", None),
+        };
+        let mut record = json!({
+            "id": hash,
+            "paging_token": token.to_string(),
+            "successful": true,
+            "hash": hash,
+            "ledger": self.sequence,
+            "created_at": created_at,
+            "source_account": source,
+            "source_account_sequence": tx.seq_num.0.to_string(),
+            "fee_account": fee_source,
+            "fee_charged": fee.to_string(),
+            "max_fee": bump.map_or(i64::from(tx.fee), |bump| bump.fee).to_string(),
+            "operation_count": tx.operations.len(),
+            "memo_type": memo_type,
+            "memo": memo,
+            "envelope_xdr": xdr,
+            "result_xdr": result_xdr(
+                fee,
+                bump.map(|_| inner.hash()),
+                applied.iter().map(|(_, effect)| effect.result.clone()),
+            ),
+        });
+        if bump.is_some() {
+            record["inner_transaction"] = json!({
+                "hash": inner.hash_hex(),
+                "max_fee": tx.fee.to_string(),
+                "signatures": [],
+            });
+            record["fee_bump_transaction"] = json!({ "hash": hash, "signatures": [] });
+        }
+
+        let mut involved = vec![source.clone(), fee_source];
+        for (index, (op_source, effect)) in applied.into_iter().enumerate() {
+            let op_token = token + index as i64 + 1;
+            let mut json = json!({
+                "id": op_token.to_string(),
+                "paging_token": op_token.to_string(),
+                "transaction_successful": true,
+                "source_account": op_source,
+                "type": effect.kind,
+                "type_i": effect.type_i,
+                "created_at": created_at,
+                "transaction_hash": hash,
+            });
+            json.as_object_mut()
+                .expect("an object")
+                .extend(effect.details);
+            let accounts = vec![op_source, effect.counterparty];
+            involved.extend(accounts.iter().cloned());
+            self.operations.push(Record {
+                paging_token: op_token,
+                accounts,
+                payment: matches!(
+                    effect.kind,
+                    "payment"
+                        | "create_account"
+                        | "path_payment_strict_send"
+                        | "path_payment_strict_receive"
+                ),
+                json,
+            });
+        }
+        involved.sort();
+        involved.dedup();
+        self.transactions.push(Record {
+            paging_token: token,
+            accounts: involved,
+            payment: false,
+            json: record.clone(),
+        });
+        Ok(record)
+    }
+
+    /// A page of `records`, linking to the next one.
+    fn page<'a>(
+        &self,
+        path: &str,
+        records: impl Iterator<Item = &'a Record>,
+        query: PageParams,
+    ) -> Value {
+        let desc = query.order.as_deref() == Some("desc");
+        let limit = query.limit.unwrap_or(10).clamp(1, 200);
+        let cursor: Option<i64> = query.cursor.as_deref().and_then(|c| c.parse().ok());
+        let mut records: Vec<&Record> = records
+            .filter(|record| match cursor {
+                None => true,
+                Some(cursor) if desc => record.paging_token < cursor,
+                Some(cursor) => record.paging_token > cursor,
+            })
+            .collect();
+        if desc {
+            records.reverse();
+        }
+        records.truncate(limit);
+        let next_cursor = records
+            .last()
+            .map(|record| record.paging_token.to_string())
+            .or(query.cursor)
+            .unwrap_or_default();
+        let order = if desc { "desc" } else { "asc" };
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let href = |cursor: &str| {
+            format!(
+                "{}{path}{separator}cursor={cursor}&limit={limit}&order={order}",
+                self.url
+            )
+        };
+        json!({
+            "_links": {
+                "self": { "href": href("") },
+                "next": { "href": href(&next_cursor) },
+            },
+            "_embedded": {
+                "records": records.iter().map(|record| &record.json).collect::<Vec<_>>(),
+            },
+        })
+    }
+
+    fn fee_stats(&self) -> FeeStats {
+        self.fee_stats.clone().unwrap_or_else(|| {
+            let base = u64::from(self.surge_fee);
+            let flat = FeeDistribution {
+                max: base,
+                min: base,
+                mode: base,
+                p10: base,
+                p20: base,
+                p30: base,
+                p40: base,
+                p50: base,
+                p60: base,
+                p70: base,
+                p80: base,
+                p90: base,
+                p95: base,
+                p99: base,
+            };
+            FeeStats {
+                last_ledger: self.sequence,
+                last_ledger_base_fee: base,
+                ledger_capacity_usage: 0.0,
+                fee_charged: flat,
+                max_fee: flat,
+            }
+        })
+    }
+}
+
+/// What an applied operation did, for its record.
+struct Effect {
+    kind: &'static str,
+    type_i: u32,
+    /// The other account it involved.
+    counterparty: String,
+    details: serde_json::Map<String, Value>,
+    result: OperationResultTr,
+}
+
+/// A successful transaction's result: the fee charged and each
+/// operation's result, inside a fee bump's for a bumped transaction.
+fn result_xdr(
+    fee: i64,
+    inner_hash: Option<[u8; 32]>,
+    results: impl Iterator<Item = OperationResultTr>,
+) -> String {
+    let results: Vec<OperationResult> = results.map(OperationResult::OpInner).collect();
+    let results = results.try_into().expect("at most 100 operations");
+    let result = match inner_hash {
+        None => TransactionResultResult::TxSuccess(results),
+        Some(hash) => TransactionResultResult::TxFeeBumpInnerSuccess(InnerTransactionResultPair {
+            transaction_hash: Hash(hash),
+            result: InnerTransactionResult {
+                fee_charged: 0,
+                result: InnerTransactionResultResult::TxSuccess(results),
+                ext: InnerTransactionResultExt::V0,
+            },
+        }),
+    };
+    TransactionResult {
+        fee_charged: fee,
+        result,
+        ext: TransactionResultExt::V0,
+    }
+    .to_xdr_base64(Limits::none())
+    .expect("a result encodes")
+}
+
+/// What a transaction changes, kept apart from the ledger until every one
+/// of its operations has succeeded.
+struct Pending<'a> {
+    accounts: BTreeMap<String, MockAccount>,
+    balances: BTreeMap<String, MockBalance>,
+    last_offer_id: i64,
+    market: &'a Market,
+    /// The sequence number of an account created now.
+    created_sequence: i64,
+    /// The ledger it closes in, and when, in seconds since the Unix epoch.
+    ledger: u32,
+    close_time: i64,
+    /// Its source account and sequence number, which the IDs of the
+    /// claimable balances it creates derive from.
+    tx_source: AccountId,
+    tx_sequence: i64,
+    /// The sponsor of each account whose future reserves are being
+    /// sponsored, until the account ends it.
+    sponsorships: BTreeMap<String, String>,
+}
+
+/// The paging token of the transaction in `ledger`; its operations' follow
+/// it.
+fn transaction_token(ledger: u32) -> i64 {
+    i64::from(ledger) << 32 | 1 << 12
+}
+
+/// Applies operation `index` of a transaction to `pending`, or answers its
+/// failure code.
+fn apply(
+    pending: &mut Pending,
+    index: u32,
+    source: &str,
+    body: &OperationBody,
+) -> Result<Effect, &'static str> {
+    let Pending {
+        accounts,
+        balances,
+        last_offer_id,
+        market,
+        created_sequence,
+        ledger,
+        close_time,
+        tx_source,
+        tx_sequence,
+        sponsorships,
+    } = pending;
+    match body {
+        OperationBody::Payment(payment) => {
+            if payment.amount <= 0 {
+                return Err("op_malformed");
+            }
+            let destination = address(&payment.destination);
+            if !accounts.contains_key(&destination) {
+                return Err("op_no_destination");
+            }
+            let asset = asset(&payment.asset);
+            debit(accounts, source, &asset, payment.amount)?;
+            credit(accounts, &destination, &asset, payment.amount)?;
+            let mut json = json!({
+                "from": source,
+                "to": destination,
+                "amount": Amount::from_stroops(payment.amount).to_fixed(),
+            });
+            json.as_object_mut()
+                .expect("an object")
+                .extend(asset_fields("", &asset));
+            Ok(Effect {
+                kind: "payment",
+                type_i: 1,
+                details: details(json),
+                counterparty: destination,
+                result: OperationResultTr::Payment(PaymentResult::Success),
+            })
+        }
+        OperationBody::PathPaymentStrictSend(payment) => {
+            if payment.send_amount <= 0 || payment.dest_min <= 0 {
+                return Err("op_malformed");
+            }
+            let destination = address(&payment.destination);
+            let (send_asset, dest_asset) = (asset(&payment.send_asset), asset(&payment.dest_asset));
+            let hops = hops(&send_asset, &payment.path, &dest_asset);
+            let received = market
+                .send(&hops, payment.send_amount)
+                .ok_or("op_too_few_offers")?;
+            if received < payment.dest_min {
+                return Err("op_under_dest_min");
+            }
+            path_pay(
+                accounts,
+                source,
+                &destination,
+                &hops,
+                payment.send_amount,
+                received,
+            )?;
+            let mut json = path_json(source, &destination, &hops, payment.send_amount, received);
+            json["destination_min"] = json!(Amount::from_stroops(payment.dest_min).to_fixed());
+            let last = paid(&payment.destination, &payment.dest_asset, received);
+            Ok(Effect {
+                kind: "path_payment_strict_send",
+                type_i: 13,
+                details: details(json),
+                counterparty: destination,
+                result: OperationResultTr::PathPaymentStrictSend(
+                    PathPaymentStrictSendResult::Success(PathPaymentStrictSendResultSuccess {
+                        offers: Default::default(),
+                        last,
+                    }),
+                ),
+            })
+        }
+        OperationBody::PathPaymentStrictReceive(payment) => {
+            if payment.dest_amount <= 0 || payment.send_max <= 0 {
+                return Err("op_malformed");
+            }
+            let destination = address(&payment.destination);
+            let (send_asset, dest_asset) = (asset(&payment.send_asset), asset(&payment.dest_asset));
+            let hops = hops(&send_asset, &payment.path, &dest_asset);
+            let sent = market
+                .receive(&hops, payment.dest_amount)
+                .ok_or("op_too_few_offers")?;
+            if sent > payment.send_max {
+                return Err("op_over_source_max");
+            }
+            path_pay(
+                accounts,
+                source,
+                &destination,
+                &hops,
+                sent,
+                payment.dest_amount,
+            )?;
+            let mut json = path_json(source, &destination, &hops, sent, payment.dest_amount);
+            json["source_max"] = json!(Amount::from_stroops(payment.send_max).to_fixed());
+            let last = paid(
+                &payment.destination,
+                &payment.dest_asset,
+                payment.dest_amount,
+            );
+            Ok(Effect {
+                kind: "path_payment_strict_receive",
+                type_i: 2,
+                details: details(json),
+                counterparty: destination,
+                result: OperationResultTr::PathPaymentStrictReceive(
+                    PathPaymentStrictReceiveResult::Success(
+                        PathPaymentStrictReceiveResultSuccess {
+                            offers: Default::default(),
+                            last,
+                        },
+                    ),
+                ),
+            })
+        }
+        OperationBody::CreateAccount(create) => {
+            if create.starting_balance < 0 {
+                return Err("op_malformed");
+            }
+            let destination = create.destination.to_string();
+            if accounts.contains_key(&destination) {
+                return Err("op_already_exists");
+            }
+            // A sponsor holds the new account's two base reserves, so it
+            // may start with nothing.
+            let sponsor = sponsorships.get(&destination).cloned();
+            match &sponsor {
+                Some(sponsor) => {
+                    let sponsor = accounts.get_mut(sponsor).ok_or("op_low_reserve")?;
+                    sponsor.sponsoring += 2;
+                    if sponsor.spendable() < 0 {
+                        return Err("op_low_reserve");
+                    }
+                }
+                None if create.starting_balance < 2 * BASE_RESERVE => return Err("op_low_reserve"),
+                None => {}
+            }
+            debit(accounts, source, &Asset::Native, create.starting_balance)?;
+            accounts.insert(
+                destination.clone(),
+                MockAccount {
+                    balance: create.starting_balance,
+                    sequence: *created_sequence,
+                    sponsor,
+                    ..MockAccount::default()
+                },
+            );
+            Ok(Effect {
+                kind: "create_account",
+                type_i: 0,
+                details: details(json!({
+                    "starting_balance": Amount::from_stroops(create.starting_balance).to_fixed(),
+                    "funder": source,
+                    "account": destination,
+                })),
+                counterparty: destination,
+                result: OperationResultTr::CreateAccount(CreateAccountResult::Success),
+            })
+        }
+        OperationBody::ChangeTrust(change) => {
+            let asset = match &change.line {
+                ChangeTrustAsset::CreditAlphanum4(credit) => {
+                    asset(&xdr::Asset::CreditAlphanum4(credit.clone()))
+                }
+                ChangeTrustAsset::CreditAlphanum12(credit) => {
+                    asset(&xdr::Asset::CreditAlphanum12(credit.clone()))
+                }
+                ChangeTrustAsset::Native => return Err("op_malformed"),
+                ChangeTrustAsset::PoolShare(_) => return Err("op_not_supported"),
+            };
+            let sponsor = sponsorships.get(source).map(String::as_str);
+            change_trust(accounts, source, &asset, change.limit, sponsor)?;
+            let issuer = asset.issuer().expect("trustlines are to issued assets");
+            let mut json = json!({
+                "limit": Amount::from_stroops(change.limit).to_fixed(),
+                "trustor": source,
+                "trustee": issuer,
+            });
+            json.as_object_mut()
+                .expect("an object")
+                .extend(asset_fields("", &asset));
+            Ok(Effect {
+                kind: "change_trust",
+                type_i: 6,
+                details: details(json),
+                counterparty: issuer.to_string(),
+                result: OperationResultTr::ChangeTrust(ChangeTrustResult::Success),
+            })
+        }
+        OperationBody::ManageSellOffer(op) => {
+            let (selling, buying) = (asset(&op.selling), asset(&op.buying));
+            let price = Price::new(op.price.n, op.price.d).ok_or("op_malformed")?;
+            let offer = MockOffer {
+                selling: selling.clone(),
+                buying: buying.clone(),
+                amount: op.amount,
+                price,
+            };
+            let placed = manage_offer(accounts, last_offer_id, source, offer, op.offer_id)?;
+            Ok(Effect {
+                kind: "manage_sell_offer",
+                type_i: 3,
+                details: offer_json(&selling, &buying, op.amount, price, op.offer_id),
+                counterparty: source.to_string(),
+                result: OperationResultTr::ManageSellOffer(ManageSellOfferResult::Success(placed)),
+            })
+        }
+        OperationBody::ManageBuyOffer(op) => {
+            let (selling, buying) = (asset(&op.selling), asset(&op.buying));
+            // Kept as the offer to sell what buys `buy_amount`.
+            let price = Price::new(op.price.n, op.price.d).ok_or("op_malformed")?;
+            let offer = MockOffer {
+                selling: selling.clone(),
+                buying: buying.clone(),
+                amount: price
+                    .times(Amount::from_stroops(op.buy_amount), true)
+                    .stroops(),
+                price: price.inverse(),
+            };
+            let placed = manage_offer(accounts, last_offer_id, source, offer, op.offer_id)?;
+            Ok(Effect {
+                kind: "manage_buy_offer",
+                type_i: 12,
+                details: offer_json(&selling, &buying, op.buy_amount, price, op.offer_id),
+                counterparty: source.to_string(),
+                result: OperationResultTr::ManageBuyOffer(ManageBuyOfferResult::Success(placed)),
+            })
+        }
+        OperationBody::CreateClaimableBalance(op) => {
+            if op.amount <= 0 {
+                return Err("op_malformed");
+            }
+            let mut claimants: Vec<Claimant> = Vec::new();
+            for xdr::Claimant::ClaimantTypeV0(claimant) in op.claimants.iter() {
+                let destination = claimant.destination.to_string();
+                let predicate =
+                    Predicate::from_xdr(&claimant.predicate).map_err(|_| "op_malformed")?;
+                if predicate.to_xdr().is_err()
+                    || claimants.iter().any(|c| c.destination == destination)
+                {
+                    return Err("op_malformed");
+                }
+                claimants.push(Claimant::new(destination, predicate.resolve(*close_time)));
+            }
+            if claimants.is_empty() {
+                return Err("op_malformed");
+            }
+            // The creator holds a base reserve for each claimant.
+            let account = accounts.get_mut(source).ok_or("op_no_source_account")?;
+            account.sponsoring += claimants.len();
+            if account.spendable() < 0 {
+                return Err("op_low_reserve");
+            }
+            let asset = asset(&op.asset);
+            debit(accounts, source, &asset, op.amount).map_err(|code| match code {
+                "op_src_no_trust" => "op_no_trust",
+                "op_src_not_authorized" => "op_not_authorized",
+                code => code,
+            })?;
+            let id = balance_id(tx_source, *tx_sequence, index);
+            let hex = balance_id_hex(&id);
+            let json = json!({
+                "asset": asset.to_string(),
+                "amount": Amount::from_stroops(op.amount).to_fixed(),
+                "claimants": claimants.iter().map(claimant_json).collect::<Vec<_>>(),
+            });
+            let counterparty = claimants[0].destination.clone();
+            balances.insert(
+                hex,
+                MockBalance {
+                    asset,
+                    amount: op.amount,
+                    claimants,
+                    sponsor: source.to_string(),
+                    last_modified_ledger: *ledger,
+                    paging_token: transaction_token(*ledger) + i64::from(index) + 1,
+                },
+            );
+            Ok(Effect {
+                kind: "create_claimable_balance",
+                type_i: 14,
+                details: details(json),
+                counterparty,
+                result: OperationResultTr::CreateClaimableBalance(
+                    CreateClaimableBalanceResult::Success(id),
+                ),
+            })
+        }
+        OperationBody::ClaimClaimableBalance(op) => {
+            let id = balance_id_hex(&op.balance_id);
+            let balance = balances.get(&id).ok_or("op_does_not_exist")?;
+            let allowed = balance.claimants.iter().any(|claimant| {
+                claimant.destination == source
+                    && claimant.predicate.allows(*close_time, *close_time)
+            });
+            if !allowed {
+                return Err("op_cannot_claim");
+            }
+            credit(accounts, source, &balance.asset, balance.amount)?;
+            let balance = balances.remove(&id).expect("found above");
+            if let Some(sponsor) = accounts.get_mut(&balance.sponsor) {
+                sponsor.sponsoring -= balance.claimants.len();
+            }
+            Ok(Effect {
+                kind: "claim_claimable_balance",
+                type_i: 15,
+                details: details(json!({ "balance_id": id, "claimant": source })),
+                counterparty: balance.sponsor,
+                result: OperationResultTr::ClaimClaimableBalance(
+                    ClaimClaimableBalanceResult::Success,
+                ),
+            })
+        }
+        OperationBody::BeginSponsoringFutureReserves(op) => {
+            let sponsored = op.sponsored_id.to_string();
+            if sponsored == source {
+                return Err("op_malformed");
+            }
+            if sponsorships.contains_key(&sponsored) {
+                return Err("op_already_sponsored");
+            }
+            // A sponsored account cannot sponsor, nor a sponsor be
+            // sponsored.
+            if sponsorships.contains_key(source) || sponsorships.values().any(|s| *s == sponsored) {
+                return Err("op_recursive");
+            }
+            sponsorships.insert(sponsored.clone(), source.to_string());
+            Ok(Effect {
+                kind: "begin_sponsoring_future_reserves",
+                type_i: 16,
+                details: details(json!({ "sponsored_id": sponsored })),
+                counterparty: sponsored,
+                result: OperationResultTr::BeginSponsoringFutureReserves(
+                    BeginSponsoringFutureReservesResult::Success,
+                ),
+            })
+        }
+        OperationBody::EndSponsoringFutureReserves => {
+            let sponsor = sponsorships.remove(source).ok_or("op_not_sponsored")?;
+            Ok(Effect {
+                kind: "end_sponsoring_future_reserves",
+                type_i: 17,
+                details: details(json!({ "begin_sponsor": sponsor })),
+                counterparty: sponsor,
+                result: OperationResultTr::EndSponsoringFutureReserves(
+                    EndSponsoringFutureReservesResult::Success,
+                ),
+            })
+        }
+        OperationBody::RevokeSponsorship(RevokeSponsorshipOp::LedgerEntry(key)) => {
+            let (owner, line, json) = match key {
+                LedgerKey::Account(key) => {
+                    let owner = key.account_id.to_string();
+                    let json = json!({ "account_id": owner });
+                    (owner, None, json)
+                }
+                LedgerKey::Trustline(key) => {
+                    let line = match &key.asset {
+                        TrustLineAsset::CreditAlphanum4(credit) => {
+                            asset(&xdr::Asset::CreditAlphanum4(credit.clone()))
+                        }
+                        TrustLineAsset::CreditAlphanum12(credit) => {
+                            asset(&xdr::Asset::CreditAlphanum12(credit.clone()))
+                        }
+                        _ => return Err("op_malformed"),
+                    };
+                    let owner = key.account_id.to_string();
+                    let json = json!({
+                        "trustline_account_id": owner,
+                        "trustline_asset": line.to_string(),
+                    });
+                    (owner, Some(line), json)
+                }
+                _ => return Err("op_not_supported"),
+            };
+            let account = accounts.get_mut(&owner).ok_or("op_does_not_exist")?;
+            let sponsor = match &line {
+                None => &mut account.sponsor,
+                Some(line) => {
+                    &mut account
+                        .trustlines
+                        .get_mut(line)
+                        .ok_or("op_does_not_exist")?
+                        .sponsor
+                }
+            };
+            if sponsor.as_deref() != Some(source) {
+                return Err("op_not_sponsor");
+            }
+            *sponsor = None;
+            // Its owner holds the reserve from now on.
+            if account.spendable() < 0 {
+                return Err("op_low_reserve");
+            }
+            let released = if line.is_some() { 1 } else { 2 };
+            accounts
+                .get_mut(source)
+                .ok_or("op_no_source_account")?
+                .sponsoring -= released;
+            Ok(Effect {
+                kind: "revoke_sponsorship",
+                type_i: 18,
+                details: details(json),
+                counterparty: owner,
+                result: OperationResultTr::RevokeSponsorship(RevokeSponsorshipResult::Success),
+            })
+        }
+        _ => Err("op_not_supported"),
+    }
+}
+
+/// A claimant as Horizon lists it.
+fn claimant_json(claimant: &Claimant) -> Value {
+    json!({
+        "destination": claimant.destination,
+        "predicate": predicate_json(&claimant.predicate),
+    })
+}
+
+fn predicate_json(predicate: &Predicate) -> Value {
+    match predicate {
+        Predicate::Unconditional => json!({ "unconditional": true }),
+        Predicate::BeforeAbsolute(time) => json!({
+            "abs_before": DateTime::from_timestamp(*time, 0)
+                .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            "abs_before_epoch": time.to_string(),
+        }),
+        Predicate::BeforeRelative(delay) => json!({ "rel_before": delay.to_string() }),
+        Predicate::And(a, b) => json!({ "and": [predicate_json(a), predicate_json(b)] }),
+        Predicate::Or(a, b) => json!({ "or": [predicate_json(a), predicate_json(b)] }),
+        Predicate::Not(inner) => json!({ "not": predicate_json(inner) }),
+    }
+}
+
+/// Takes `amount` of `asset` from `source`: XLM above its reserve, or an
+/// issued asset from its trustline. The issuer destroys what it receives
+/// and creates what it sends from nothing.
+fn debit(
+    accounts: &mut BTreeMap<String, MockAccount>,
+    source: &str,
+    asset: &Asset,
+    amount: i64,
+) -> Result<(), &'static str> {
+    let from = accounts.get_mut(source).ok_or("op_no_source_account")?;
+    if asset.is_native() {
+        if from.spendable() < amount {
+            return Err("op_underfunded");
+        }
+        from.balance -= amount;
+        return Ok(());
+    }
+    if asset.issuer() == Some(source) {
+        return Ok(());
+    }
+    let selling = from.selling_liabilities(asset);
+    let line = from.trustlines.get_mut(asset).ok_or("op_src_no_trust")?;
+    if !line.authorized {
+        return Err("op_src_not_authorized");
+    }
+    if line.balance - selling < amount {
+        return Err("op_underfunded");
+    }
+    line.balance -= amount;
+    Ok(())
+}
+
+/// Gives `amount` of `asset` to `destination`, within its trustline's
+/// limit less what its offers may buy.
+fn credit(
+    accounts: &mut BTreeMap<String, MockAccount>,
+    destination: &str,
+    asset: &Asset,
+    amount: i64,
+) -> Result<(), &'static str> {
+    let to = accounts.get_mut(destination).ok_or("op_no_destination")?;
+    if asset.is_native() {
+        to.balance += amount;
+        return Ok(());
+    }
+    if asset.issuer() == Some(destination) {
+        return Ok(());
+    }
+    let buying = to.buying_liabilities(asset);
+    let line = to.trustlines.get_mut(asset).ok_or("op_no_trust")?;
+    if !line.authorized {
+        return Err("op_not_authorized");
+    }
+    if line.limit - line.balance - buying < amount {
+        return Err("op_line_full");
+    }
+    line.balance += amount;
+    Ok(())
+}
+
+/// A path payment's assets from end to end.
+fn hops(send_asset: &Asset, path: &[xdr::Asset], dest_asset: &Asset) -> Vec<Asset> {
+    std::iter::once(send_asset.clone())
+        .chain(path.iter().map(asset))
+        .chain(std::iter::once(dest_asset.clone()))
+        .collect()
+}
+
+/// Debits `sent` of the first of `hops` and credits `received` of the last.
+fn path_pay(
+    accounts: &mut BTreeMap<String, MockAccount>,
+    source: &str,
+    destination: &str,
+    hops: &[Asset],
+    sent: i64,
+    received: i64,
+) -> Result<(), &'static str> {
+    if !accounts.contains_key(destination) {
+        return Err("op_no_destination");
+    }
+    debit(accounts, source, &hops[0], sent)?;
+    credit(accounts, destination, &hops[hops.len() - 1], received)
+}
+
+/// A path payment's record fields.
+fn path_json(source: &str, destination: &str, hops: &[Asset], sent: i64, received: i64) -> Value {
+    let mut json = json!({
+        "from": source,
+        "to": destination,
+        "amount": Amount::from_stroops(received).to_fixed(),
+        "source_amount": Amount::from_stroops(sent).to_fixed(),
+        "path": hops[1..hops.len() - 1]
+            .iter()
+            .map(|asset| Value::Object(asset_fields("", asset)))
+            .collect::<Vec<_>>(),
+    });
+    let fields = json.as_object_mut().expect("an object");
+    fields.extend(asset_fields("", &hops[hops.len() - 1]));
+    fields.extend(asset_fields("source_", &hops[0]));
+    json
+}
+
+/// The payment at the end of a path, for its result.
+fn paid(destination: &MuxedAccount, asset: &xdr::Asset, amount: i64) -> SimplePaymentResult {
+    SimplePaymentResult {
+        destination: destination.clone().account_id(),
+        asset: asset.clone(),
+        amount,
+    }
+}
+
+/// Places `offer` as a new offer, or puts it in place of `source`'s offer
+/// `offer_id`, or with no amount removes that offer. It rests on the book
+/// as long as the account keeps the reserve, can sell all of it and has
+/// room for what it buys.
+fn manage_offer(
+    accounts: &mut BTreeMap<String, MockAccount>,
+    last_offer_id: &mut i64,
+    source: &str,
+    offer: MockOffer,
+    offer_id: i64,
+) -> Result<ManageOfferSuccessResult, &'static str> {
+    if offer.amount < 0 || offer.selling == offer.buying || offer_id < 0 {
+        return Err("op_malformed");
+    }
+    if offer.amount == 0 && offer_id == 0 {
+        return Err("op_malformed");
+    }
+    for (asset, no_issuer) in [
+        (&offer.selling, "op_sell_no_issuer"),
+        (&offer.buying, "op_buy_no_issuer"),
+    ] {
+        if let Some(issuer) = asset.issuer() {
+            accounts.get(issuer).ok_or(no_issuer)?;
+        }
+    }
+    let account = accounts.get_mut(source).ok_or("op_no_source_account")?;
+    if offer_id != 0 && account.offers.remove(&offer_id).is_none() {
+        return Err("op_offer_not_found");
+    }
+    let success = |offer| ManageOfferSuccessResult {
+        offers_claimed: Default::default(),
+        offer,
+    };
+    if offer.amount == 0 {
+        return Ok(success(ManageOfferSuccessResultOffer::Deleted));
+    }
+
+    let lines = [
+        (&offer.selling, "op_sell_no_trust", "op_sell_not_authorized"),
+        (&offer.buying, "op_buy_no_trust", "op_buy_not_authorized"),
+    ];
+    for (asset, no_trust, not_authorized) in lines {
+        if asset.is_native() || asset.issuer() == Some(source) {
+            continue;
+        }
+        let line = account.trustlines.get(asset).ok_or(no_trust)?;
+        if !line.authorized {
+            return Err(not_authorized);
+        }
+    }
+    if offer_id == 0 && account.spendable() < BASE_RESERVE {
+        return Err("op_low_reserve");
+    }
+    let id = match offer_id {
+        0 => {
+            *last_offer_id += 1;
+            *last_offer_id
+        }
+        id => id,
+    };
+    account.offers.insert(id, offer.clone());
+    let underfunded = match &offer.selling {
+        _ if offer.selling.issuer() == Some(source) => false,
+        Asset::Native => account.spendable() < 0,
+        selling => account.trustlines[selling].balance < account.selling_liabilities(selling),
+    };
+    if underfunded {
+        return Err("op_underfunded");
+    }
+    if let Some(line) = account.trustlines.get(&offer.buying) {
+        if line.limit - line.balance < account.buying_liabilities(&offer.buying) {
+            return Err("op_line_full");
+        }
+    }
+
+    let entry = OfferEntry {
+        seller_id: account_id(source).expect("a funded account"),
+        offer_id: id,
+        selling: offer.selling.to_xdr().expect("an asset from XDR"),
+        buying: offer.buying.to_xdr().expect("an asset from XDR"),
+        amount: offer.amount,
+        price: offer.price.to_xdr(),
+        flags: 0,
+        ext: OfferEntryExt::V0,
+    };
+    Ok(success(match offer_id {
+        0 => ManageOfferSuccessResultOffer::Created(entry),
+        _ => ManageOfferSuccessResultOffer::Updated(entry),
+    }))
+}
+
+/// An offer operation's record fields.
+fn offer_json(
+    selling: &Asset,
+    buying: &Asset,
+    amount: i64,
+    price: Price,
+    offer_id: i64,
+) -> serde_json::Map<String, Value> {
+    let mut fields = details(json!({
+        "amount": Amount::from_stroops(amount).to_fixed(),
+        "price": price_fixed(price),
+        "price_r": { "n": price.numerator(), "d": price.denominator() },
+        "offer_id": offer_id.to_string(),
+    }));
+    fields.extend(asset_fields("selling_", selling));
+    fields.extend(asset_fields("buying_", buying));
+    fields
+}
+
+/// A price with seven decimals, as Horizon writes it.
+fn price_fixed(price: Price) -> String {
+    price
+        .to_string()
+        .parse::<Amount>()
+        .expect("prices print as amounts")
+        .to_fixed()
+}
+
+/// `asset_type`, `asset_code` and `asset_issuer`, with `prefix` before
+/// each as in `source_asset_type`.
+fn asset_fields(prefix: &str, asset: &Asset) -> serde_json::Map<String, Value> {
+    let mut fields = serde_json::Map::new();
+    fields.insert(format!("{prefix}asset_type"), json!(asset.horizon_type()));
+    if let Asset::Issued { code, issuer } = asset {
+        fields.insert(format!("{prefix}asset_code"), json!(code));
+        fields.insert(format!("{prefix}asset_issuer"), json!(issuer));
+    }
+    fields
+}
+
+/// Adds, limits or (with a limit of 0) removes `source`'s trustline; a new
+/// one's reserve is held by `sponsor`, if the account is being sponsored.
+fn change_trust(
+    accounts: &mut BTreeMap<String, MockAccount>,
+    source: &str,
+    asset: &Asset,
+    limit: i64,
+    sponsor: Option<&str>,
+) -> Result<(), &'static str> {
+    let issuer = asset.issuer().expect("an issued asset");
+    if limit < 0 {
+        return Err("op_malformed");
+    }
+    if source == issuer {
+        return Err("op_self_not_allowed");
+    }
+    let auth_required = accounts.get(issuer).ok_or("op_no_issuer")?.auth_required;
+    let account = accounts.get_mut(source).ok_or("op_no_source_account")?;
+    match account.trustlines.get_mut(asset) {
+        Some(line) if limit < line.balance => Err("op_invalid_limit"),
+        Some(_) if limit == 0 => {
+            let line = account.trustlines.remove(asset).expect("found above");
+            if let Some(sponsor) = line.sponsor.and_then(|sponsor| accounts.get_mut(&sponsor)) {
+                sponsor.sponsoring -= 1;
+            }
+            Ok(())
+        }
+        Some(line) => {
+            line.limit = limit;
+            Ok(())
+        }
+        None if limit == 0 => Err("op_invalid_limit"),
+        None => {
+            // The new trustline raises the reserve by one base reserve,
+            // its own or its sponsor's.
+            if sponsor.is_none() && account.spendable() < BASE_RESERVE {
+                return Err("op_low_reserve");
+            }
+            account.trustlines.insert(
+                asset.clone(),
+                MockTrustline {
+                    balance: 0,
+                    limit,
+                    authorized: !auth_required,
+                    sponsor: sponsor.map(str::to_string),
+                },
+            );
+            if let Some(sponsor) = sponsor {
+                let sponsor = accounts.get_mut(sponsor).ok_or("op_low_reserve")?;
+                sponsor.sponsoring += 1;
+                if sponsor.spendable() < 0 {
+                    return Err("op_low_reserve");
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// An asset as a transaction names it.
+fn asset(asset: &xdr::Asset) -> Asset {
+    let (code, issuer) = match asset {
+        xdr::Asset::Native => return Asset::Native,
+        xdr::Asset::CreditAlphanum4(credit) => (&credit.asset_code.0[..], &credit.issuer),
+        xdr::Asset::CreditAlphanum12(credit) => (&credit.asset_code.0[..], &credit.issuer),
+    };
+    // Codes are padded with zero bytes.
+    let end = code.iter().position(|&b| b == 0).unwrap_or(code.len());
+    Asset::Issued {
+        code: String::from_utf8_lossy(&code[..end]).into_owned(),
+        issuer: issuer.to_string(),
+    }
+}
+
+async fn account(State(ledger): State<Shared>, Path(id): Path<String>) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    match ledger.accounts.get(&id) {
+        Some(account) => Json(account_json(&id, account)).into_response(),
+        None => not_found().into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountsParams {
+    sponsor: Option<String>,
+}
+
+/// `/accounts?sponsor=`: the accounts whose account or trustlines the
+/// sponsor holds the reserves of, in order of their IDs.
+async fn accounts(
+    State(ledger): State<Shared>,
+    Query(params): Query<AccountsParams>,
+    Query(query): Query<PageParams>,
+) -> Response {
+    let Some(sponsor) = params.sponsor else {
+        return bad_request().into_response();
+    };
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let path = format!("/accounts?sponsor={sponsor}");
+    let accounts: Vec<Record> = ledger
+        .accounts
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, account))| {
+            account.sponsor.as_deref() == Some(&sponsor)
+                || account
+                    .trustlines
+                    .values()
+                    .any(|line| line.sponsor.as_deref() == Some(&sponsor))
+        })
+        .map(|(index, (id, account))| Record {
+            paging_token: index as i64 + 1,
+            accounts: vec![id.clone()],
+            payment: false,
+            json: account_json(id, account),
+        })
+        .collect();
+    Json(ledger.page(&path, accounts.iter(), query)).into_response()
+}
+
+fn account_json(id: &str, account: &MockAccount) -> Value {
+    let liabilities = |asset: &Asset| {
+        (
+            Amount::from_stroops(account.buying_liabilities(asset)).to_fixed(),
+            Amount::from_stroops(account.selling_liabilities(asset)).to_fixed(),
+        )
+    };
+    let (buying, selling) = liabilities(&Asset::Native);
+    let mut balances = vec![json!({
+        "balance": Amount::from_stroops(account.balance).to_fixed(),
+        "buying_liabilities": buying,
+        "selling_liabilities": selling,
+        "asset_type": "native",
+    })];
+    for (asset, line) in &account.trustlines {
+        let (buying, selling) = liabilities(asset);
+        balances.push(json!({
+            "balance": Amount::from_stroops(line.balance).to_fixed(),
+            "limit": Amount::from_stroops(line.limit).to_fixed(),
+            "buying_liabilities": buying,
+            "selling_liabilities": selling,
+            "is_authorized": line.authorized,
+            "asset_type": asset.horizon_type(),
+            "asset_code": asset.code(),
+            "asset_issuer": asset.issuer(),
+            "sponsor": line.sponsor,
+        }));
+    }
+    json!({
+        "id": id,
+        "account_id": id,
+        "sequence": account.sequence.to_string(),
+        "subentry_count": account.subentries(),
+        "thresholds": { "low_threshold": 0, "med_threshold": 0, "high_threshold": 0 },
+        "flags": {
+            "auth_required": account.auth_required,
+            "auth_revocable": false,
+            "auth_immutable": false,
+            "auth_clawback_enabled": false,
+        },
+        "signers": [{ "key": id, "weight": 1, "type": "ed25519_public_key" }],
+        "num_sponsoring": account.sponsoring,
+        "num_sponsored": account.sponsored(),
+        "sponsor": account.sponsor,
+        "balances": balances,
+        "paging_token": id,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct PageParams {
+    cursor: Option<String>,
+    limit: Option<usize>,
+    order: Option<String>,
+}
+
+async fn account_transactions(
+    State(ledger): State<Shared>,
+    Path(id): Path<String>,
+    Query(query): Query<PageParams>,
+) -> Json<Value> {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let path = format!("/accounts/{id}/transactions");
+    let transactions = ledger.transactions.iter().filter(|tx| tx.involves(&id));
+    Json(ledger.page(&path, transactions, query))
+}
+
+async fn account_operations(
+    State(ledger): State<Shared>,
+    Path(id): Path<String>,
+    Query(query): Query<PageParams>,
+) -> Json<Value> {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let path = format!("/accounts/{id}/operations");
+    let operations = ledger.operations.iter().filter(|op| op.involves(&id));
+    Json(ledger.page(&path, operations, query))
+}
+
+async fn account_payments(
+    State(ledger): State<Shared>,
+    Path(id): Path<String>,
+    Query(query): Query<PageParams>,
+) -> Json<Value> {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let path = format!("/accounts/{id}/payments");
+    let payments = ledger
+        .operations
+        .iter()
+        .filter(|op| op.payment && op.involves(&id));
+    Json(ledger.page(&path, payments, query))
+}
+
+async fn account_offers(
+    State(ledger): State<Shared>,
+    Path(id): Path<String>,
+    Query(query): Query<PageParams>,
+) -> Json<Value> {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let path = format!("/accounts/{id}/offers");
+    let offers: Vec<Record> = ledger
+        .accounts
+        .get(&id)
+        .into_iter()
+        .flat_map(|account| &account.offers)
+        .map(|(&offer_id, offer)| {
+            let mut json = json!({
+                "id": offer_id.to_string(),
+                "paging_token": offer_id.to_string(),
+                "seller": id,
+                "selling": Value::Object(asset_fields("", &offer.selling)),
+                "buying": Value::Object(asset_fields("", &offer.buying)),
+                "amount": Amount::from_stroops(offer.amount).to_fixed(),
+                "last_modified_ledger": ledger.sequence,
+            });
+            json.as_object_mut()
+                .expect("an object")
+                .extend(price_fields(offer.price));
+            Record {
+                paging_token: offer_id,
+                accounts: vec![id.clone()],
+                payment: false,
+                json,
+            }
+        })
+        .collect();
+    Json(ledger.page(&path, offers.iter(), query))
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaimableParams {
+    claimant: Option<String>,
+}
+
+async fn claimable_balances(
+    State(ledger): State<Shared>,
+    Query(params): Query<ClaimableParams>,
+    Query(query): Query<PageParams>,
+) -> Response {
+    let Some(claimant) = params.claimant else {
+        return bad_request().into_response();
+    };
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let path = format!("/claimable_balances?claimant={claimant}");
+    let mut balances: Vec<Record> = ledger
+        .balances
+        .iter()
+        .filter(|(_, balance)| {
+            balance
+                .claimants
+                .iter()
+                .any(|listed| listed.destination == claimant)
+        })
+        .map(|(id, balance)| Record {
+            paging_token: balance.paging_token,
+            accounts: vec![claimant.clone()],
+            payment: false,
+            json: balance_json(id, balance),
+        })
+        .collect();
+    balances.sort_by_key(|record| record.paging_token);
+    Json(ledger.page(&path, balances.iter(), query)).into_response()
+}
+
+async fn claimable_balance(State(ledger): State<Shared>, Path(id): Path<String>) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    match ledger.balances.get(&id) {
+        Some(balance) => Json(balance_json(&id, balance)).into_response(),
+        None => not_found().into_response(),
+    }
+}
+
+fn balance_json(id: &str, balance: &MockBalance) -> Value {
+    json!({
+        "id": id,
+        "paging_token": balance.paging_token.to_string(),
+        "asset": balance.asset.to_string(),
+        "amount": Amount::from_stroops(balance.amount).to_fixed(),
+        "sponsor": balance.sponsor,
+        "last_modified_ledger": balance.last_modified_ledger,
+        "claimants": balance.claimants.iter().map(claimant_json).collect::<Vec<_>>(),
+        "flags": { "clawback_enabled": false },
+    })
+}
+
+async fn transaction(State(ledger): State<Shared>, Path(hash): Path<String>) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    match ledger
+        .transactions
+        .iter()
+        .find(|tx| tx.json["hash"] == hash || tx.json["inner_transaction"]["hash"] == hash)
+    {
+        Some(tx) => Json(tx.json.clone()).into_response(),
+        None => not_found().into_response(),
+    }
+}
+
+/// The operations of a transaction, found by its hash or, for a fee bump,
+/// its inner transaction's.
+async fn transaction_operations(
+    State(ledger): State<Shared>,
+    Path(hash): Path<String>,
+    Query(query): Query<PageParams>,
+) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let Some(tx) = ledger
+        .transactions
+        .iter()
+        .find(|tx| tx.json["hash"] == hash || tx.json["inner_transaction"]["hash"] == hash)
+    else {
+        return not_found().into_response();
+    };
+    let path = format!("/transactions/{hash}/operations");
+    let operations = ledger
+        .operations
+        .iter()
+        .filter(|op| op.json["transaction_hash"] == tx.json["hash"]);
+    Json(ledger.page(&path, operations, query)).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct StrictSendParams {
+    source_asset_type: String,
+    source_asset_code: Option<String>,
+    source_asset_issuer: Option<String>,
+    source_amount: String,
+    destination_assets: String,
+}
+
+async fn strict_send_paths(
+    State(ledger): State<Shared>,
+    Query(query): Query<StrictSendParams>,
+) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let source = query_asset(
+        &query.source_asset_type,
+        &query.source_asset_code,
+        &query.source_asset_issuer,
+    );
+    let (Some(source), Ok(amount), Some(destinations)) = (
+        source,
+        query.source_amount.parse::<Amount>(),
+        asset_list(&query.destination_assets),
+    ) else {
+        return bad_request().into_response();
+    };
+    let mut records = Vec::new();
+    for destination in &destinations {
+        for path in ledger.market.paths(&source, destination) {
+            let hops = [vec![source.clone()], path, vec![destination.clone()]].concat();
+            if let Some(received) = ledger.market.send(&hops, amount.stroops()) {
+                records.push(path_record(&hops, amount.stroops(), received));
+            }
+        }
+    }
+    Json(json!({ "_embedded": { "records": records } })).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct StrictReceiveParams {
+    source_assets: String,
+    destination_asset_type: String,
+    destination_asset_code: Option<String>,
+    destination_asset_issuer: Option<String>,
+    destination_amount: String,
+}
+
+async fn strict_receive_paths(
+    State(ledger): State<Shared>,
+    Query(query): Query<StrictReceiveParams>,
+) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let destination = query_asset(
+        &query.destination_asset_type,
+        &query.destination_asset_code,
+        &query.destination_asset_issuer,
+    );
+    let (Some(destination), Ok(amount), Some(sources)) = (
+        destination,
+        query.destination_amount.parse::<Amount>(),
+        asset_list(&query.source_assets),
+    ) else {
+        return bad_request().into_response();
+    };
+    let mut records = Vec::new();
+    for source in &sources {
+        for path in ledger.market.paths(source, &destination) {
+            let hops = [vec![source.clone()], path, vec![destination.clone()]].concat();
+            if let Some(sent) = ledger.market.receive(&hops, amount.stroops()) {
+                records.push(path_record(&hops, sent, amount.stroops()));
+            }
+        }
+    }
+    Json(json!({ "_embedded": { "records": records } })).into_response()
+}
+
+/// A path as `/paths` lists it.
+fn path_record(hops: &[Asset], sent: i64, received: i64) -> Value {
+    let mut json = json!({
+        "source_amount": Amount::from_stroops(sent).to_fixed(),
+        "destination_amount": Amount::from_stroops(received).to_fixed(),
+        "path": hops[1..hops.len() - 1]
+            .iter()
+            .map(|asset| Value::Object(asset_fields("", asset)))
+            .collect::<Vec<_>>(),
+    });
+    let fields = json.as_object_mut().expect("an object");
+    fields.extend(asset_fields("source_", &hops[0]));
+    fields.extend(asset_fields("destination_", &hops[hops.len() - 1]));
+    json
+}
+
+/// An asset given as `{side}_asset_type`, `_code` and `_issuer`.
+fn query_asset(asset_type: &str, code: &Option<String>, issuer: &Option<String>) -> Option<Asset> {
+    match (asset_type, code, issuer) {
+        ("native", _, _) => Some(Asset::Native),
+        (_, Some(code), Some(issuer)) => Asset::issued(code, issuer).ok(),
+        _ => None,
+    }
+}
+
+/// A comma-separated list of `native` and `CODE:ISSUER`.
+fn asset_list(list: &str) -> Option<Vec<Asset>> {
+    list.split(',').map(|asset| asset.parse().ok()).collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderbookParams {
+    selling_asset_type: String,
+    selling_asset_code: Option<String>,
+    selling_asset_issuer: Option<String>,
+    buying_asset_type: String,
+    buying_asset_code: Option<String>,
+    buying_asset_issuer: Option<String>,
+    limit: Option<usize>,
+}
+
+/// The recorded book for the pair, or the offers between its assets:
+/// those selling the base asset as asks, and those buying it as bids,
+/// priced in the counter asset.
+async fn orderbook(State(ledger): State<Shared>, Query(query): Query<OrderbookParams>) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let base = query_asset(
+        &query.selling_asset_type,
+        &query.selling_asset_code,
+        &query.selling_asset_issuer,
+    );
+    let counter = query_asset(
+        &query.buying_asset_type,
+        &query.buying_asset_code,
+        &query.buying_asset_issuer,
+    );
+    let (Some(base), Some(counter)) = (base, counter) else {
+        return bad_request().into_response();
+    };
+    if let Some(book) = ledger.orderbooks.get(&(base.clone(), counter.clone())) {
+        return Json(book.clone()).into_response();
+    }
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 200);
+    let side = |selling: &Asset, buying: &Asset, price: fn(Price) -> Price| {
+        let mut levels: BTreeMap<Price, i64> = BTreeMap::new();
+        let offers = ledger
+            .accounts
+            .values()
+            .flat_map(|account| account.offers.values());
+        for offer in offers.filter(|offer| offer.selling == *selling && offer.buying == *buying) {
+            *levels.entry(price(offer.price)).or_default() += offer.amount;
+        }
+        levels
+    };
+    let level = |(price, amount): (Price, i64)| {
+        let mut json = json!({ "amount": Amount::from_stroops(amount).to_fixed() });
+        json.as_object_mut()
+            .expect("an object")
+            .extend(price_fields(price));
+        json
+    };
+    let asks = side(&base, &counter, |price| price);
+    let bids = side(&counter, &base, Price::inverse);
+    Json(json!({
+        "bids": bids.into_iter().rev().take(limit).map(level).collect::<Vec<_>>(),
+        "asks": asks.into_iter().take(limit).map(level).collect::<Vec<_>>(),
+        "base": Value::Object(asset_fields("", &base)),
+        "counter": Value::Object(asset_fields("", &counter)),
+    }))
+    .into_response()
+}
+
+/// `price_r` and `price`, as order books and offers give them.
+fn price_fields(price: Price) -> serde_json::Map<String, Value> {
+    details(json!({
+        "price_r": { "n": price.numerator(), "d": price.denominator() },
+        "price": price_fixed(price),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct TradeAggregationParams {
+    base_asset_type: String,
+    base_asset_code: Option<String>,
+    base_asset_issuer: Option<String>,
+    counter_asset_type: String,
+    counter_asset_code: Option<String>,
+    counter_asset_issuer: Option<String>,
+    resolution: u64,
+}
+
+async fn trade_aggregations(
+    State(ledger): State<Shared>,
+    Query(query): Query<TradeAggregationParams>,
+) -> Response {
+    let ledger = ledger.lock().expect("mock ledger poisoned");
+    let base = query_asset(
+        &query.base_asset_type,
+        &query.base_asset_code,
+        &query.base_asset_issuer,
+    );
+    let counter = query_asset(
+        &query.counter_asset_type,
+        &query.counter_asset_code,
+        &query.counter_asset_issuer,
+    );
+    let (Some(base), Some(counter), true) = (base, counter, query.resolution > 0) else {
+        return bad_request().into_response();
+    };
+    match ledger.trade_aggregations.get(&(base, counter)) {
+        Some(trades) => Json(trades.clone()).into_response(),
+        None => Json(json!({ "_embedded": { "records": [] } })).into_response(),
+    }
+}
+
+async fn fee_stats(State(ledger): State<Shared>) -> Json<FeeStats> {
+    Json(ledger.lock().expect("mock ledger poisoned").fee_stats())
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitForm {
+    tx: String,
+}
+
+async fn submit(State(ledger): State<Shared>, Form(form): Form<SubmitForm>) -> Response {
+    let mut ledger = ledger.lock().expect("mock ledger poisoned");
+    match ledger.faults.pop_front() {
+        Some(Fault::Reject(code)) => failed(&form.tx, &code, &[]).into_response(),
+        Some(Fault::TimeOut) => {
+            let _ = ledger.submit(&form.tx);
+            timeout().into_response()
+        }
+        None => match ledger.submit(&form.tx) {
+            Ok(record) => Json(record).into_response(),
+            Err(problem) => problem.into_response(),
+        },
+    }
+}
+
+fn address(account: &MuxedAccount) -> String {
+    account.clone().account_id().to_string()
+}
+
+fn details(value: Value) -> serde_json::Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!("details are built as objects"),
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// A problem document and its status.
+type Rejection = (StatusCode, Json<Value>);
+
+fn problem(status: StatusCode, kind: &str, title: &str, detail: &str, extras: Value) -> Rejection {
+    let mut body = json!({
+        "type": format!("https://stellar.org/horizon-errors/{kind}"),
+        "title": title,
+        "status": status.as_u16(),
+        "detail": detail,
+    });
+    if !extras.is_null() {
+        body["extras"] = extras;
+    }
+    (status, Json(body))
+}
+
+fn not_found() -> Rejection {
+    problem(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        "Resource Missing",
+        "The resource at the url requested was not found.",
+        Value::Null,
+    )
+}
+
+fn bad_request() -> Rejection {
+    problem(
+        StatusCode::BAD_REQUEST,
+        "bad_request",
+        "Bad Request",
+        "The request you sent was invalid in some way.",
+        Value::Null,
+    )
+}
+
+fn malformed() -> Rejection {
+    problem(
+        StatusCode::BAD_REQUEST,
+        "transaction_malformed",
+        "Transaction Malformed",
+        "Horizon could not decode the transaction envelope in this request.",
+        Value::Null,
+    )
+}
+
+fn timeout() -> Rejection {
+    problem(
+        StatusCode::GATEWAY_TIMEOUT,
+        "timeout",
+        "Timeout",
+        "Your request timed out before completing. Please try your request again. \
+         If you are submitting a transaction make sure you are sending exactly the same \
+         transaction (with the same sequence number).",
+        Value::Null,
+    )
+}
+
+/// A fee bump whose transaction failed with `code`.
+fn failed_inner(xdr: &str, code: &str, operations: &[&str]) -> Rejection {
+    let (status, Json(mut body)) = failed(xdr, "tx_fee_bump_inner_failed", operations);
+    body["extras"]["result_codes"]["inner_transaction"] = json!(code);
+    (status, Json(body))
+}
+
+fn failed(xdr: &str, code: &str, operations: &[&str]) -> Rejection {
+    let mut result_codes = json!({ "transaction": code });
+    if !operations.is_empty() {
+        result_codes["operations"] = json!(operations);
+    }
+    problem(
+        StatusCode::BAD_REQUEST,
+        "transaction_failed",
+        "Transaction Failed",
+        "The transaction failed when submitted to the stellar network. \
+         The `extras.result_codes` field on this response contains further details.",
+        json!({ "envelope_xdr": xdr, "result_codes": result_codes }),
+    )
+}
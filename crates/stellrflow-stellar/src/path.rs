@@ -0,0 +1,420 @@
+//! Path payments: paying in one asset what the destination receives in
+//! another, converted through the order books on the way.
+
+use std::fmt;
+use std::str::FromStr;
+
+use stellar_xdr::curr::{
+    Operation, OperationBody, PathPaymentStrictReceiveOp, PathPaymentStrictSendOp, VecM,
+};
+use stellrflow_amount::{Amount, STROOPS_PER_XLM};
+use stellrflow_signer::Signer;
+
+use crate::asset::Asset;
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::resources::{PageQuery, PaymentPath, TransactionRecord};
+use crate::send::account_id;
+
+/// The most assets a path can go through between the two ends.
+const MAX_PATH: usize = 5;
+
+/// Basis points in 100%.
+const FULL: u32 = 10_000;
+
+/// How far a path payment may stray from its quote, in basis points.
+///
+/// It reads and writes a percentage with at most two decimals: `"0.5"` or
+/// `"0.5%"` is 50 basis points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Slippage(u32);
+
+impl Slippage {
+    /// `bps` hundredths of a percent, up to 100%.
+    pub fn from_bps(bps: u32) -> Option<Self> {
+        (bps <= FULL).then_some(Slippage(bps))
+    }
+
+    pub fn bps(self) -> u32 {
+        self.0
+    }
+
+    /// The least to accept in place of `amount`, rounded down.
+    pub fn at_least(self, amount: Amount) -> Amount {
+        scale(amount, FULL - self.0, false)
+    }
+
+    /// The most to give in place of `amount`, rounded up.
+    pub fn at_most(self, amount: Amount) -> Amount {
+        scale(amount, FULL + self.0, true)
+    }
+}
+
+/// 1%, the bot's `PAY_SLIPPAGE_PERCENT` default.
+impl Default for Slippage {
+    fn default() -> Self {
+        Slippage(100)
+    }
+}
+
+impl FromStr for Slippage {
+    type Err = StellarError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || StellarError::InvalidSlippage(text.trim().to_string());
+        let percent = text.trim().trim_end_matches('%').trim_end();
+        let (whole, fraction) = percent.split_once('.').unwrap_or((percent, ""));
+        let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || whole.len() > 3 || fraction.len() > 2 {
+            return Err(invalid());
+        }
+        if !digits(whole) || !digits(fraction) {
+            return Err(invalid());
+        }
+        let whole: u32 = whole.parse().map_err(|_| invalid())?;
+        let fraction: u32 = format!("{fraction:0<2}").parse().map_err(|_| invalid())?;
+        Slippage::from_bps(whole * 100 + fraction).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Slippage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 % 100 {
+            0 => write!(f, "{}%", self.0 / 100),
+            cents if cents % 10 == 0 => write!(f, "{}.{}%", self.0 / 100, cents / 10),
+            cents => write!(f, "{}.{cents:02}%", self.0 / 100),
+        }
+    }
+}
+
+/// Which end of a path payment is fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strict {
+    /// Send exactly the amount; the destination receives at least the
+    /// quote less the slippage.
+    Send,
+    /// The destination receives exactly the amount; the source sends at
+    /// most the quote plus the slippage.
+    Receive,
+}
+
+/// A payment converted from one asset into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPayment {
+    pub send_asset: Asset,
+    pub dest_asset: Asset,
+    pub strict: Strict,
+    /// What is sent under [`Strict::Send`], or received under
+    /// [`Strict::Receive`].
+    pub amount: Amount,
+    pub slippage: Slippage,
+}
+
+impl PathPayment {
+    /// Sends exactly `amount` of `send_asset`, delivered as `dest_asset`.
+    pub fn strict_send(send_asset: Asset, amount: Amount, dest_asset: Asset) -> Self {
+        PathPayment {
+            send_asset,
+            dest_asset,
+            strict: Strict::Send,
+            amount,
+            slippage: Slippage::default(),
+        }
+    }
+
+    /// Delivers exactly `amount` of `dest_asset`, paid in `send_asset`.
+    pub fn strict_receive(send_asset: Asset, dest_asset: Asset, amount: Amount) -> Self {
+        PathPayment {
+            send_asset,
+            dest_asset,
+            strict: Strict::Receive,
+            amount,
+            slippage: Slippage::default(),
+        }
+    }
+
+    pub fn with_slippage(mut self, slippage: Slippage) -> Self {
+        self.slippage = slippage;
+        self
+    }
+}
+
+/// The best path Horizon found for a [`PathPayment`], and the bound that
+/// keeps the payment within its slippage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    pub path: PaymentPath,
+    pub strict: Strict,
+    /// The least the destination accepts (`dest_min`) under
+    /// [`Strict::Send`], or the most the source gives (`send_max`) under
+    /// [`Strict::Receive`].
+    pub limit: Amount,
+}
+
+impl Quote {
+    /// How much of the destination asset one unit of the source asset
+    /// buys on this path.
+    pub fn rate(&self) -> Amount {
+        rate(self.path.source_amount, self.path.destination_amount)
+    }
+
+    /// The most the source may send: the amount it sends under
+    /// [`Strict::Send`], or `send_max` under [`Strict::Receive`].
+    pub fn most_sent(&self) -> Amount {
+        match self.strict {
+            Strict::Send => self.path.source_amount,
+            Strict::Receive => self.limit,
+        }
+    }
+}
+
+/// What [`Horizon::path_payment`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPaid {
+    pub hash: String,
+    pub ledger: u32,
+    /// What the source actually sent.
+    pub source_amount: Amount,
+    /// What the destination actually received.
+    pub destination_amount: Amount,
+    pub quote: Quote,
+}
+
+impl PathPaid {
+    /// The realized rate: how much of the destination asset each unit sent
+    /// bought.
+    pub fn rate(&self) -> Amount {
+        rate(self.source_amount, self.destination_amount)
+    }
+}
+
+impl Horizon {
+    /// Paths that turn `source_amount` of `source_asset` into any of
+    /// `destination_assets`, from `/paths/strict-send`.
+    pub async fn strict_send_paths(
+        &self,
+        source_asset: &Asset,
+        source_amount: Amount,
+        destination_assets: &[Asset],
+    ) -> Result<Vec<PaymentPath>, StellarError> {
+        let mut query = asset_query("source", source_asset);
+        query.push(("source_amount".into(), source_amount.to_fixed()));
+        query.push(("destination_assets".into(), asset_list(destination_assets)));
+        self.records("/paths/strict-send", &query, || {
+            format!("paths from {source_asset}")
+        })
+        .await
+    }
+
+    /// Paths that deliver `destination_amount` of `destination_asset`
+    /// from any of `source_assets`, from `/paths/strict-receive`.
+    pub async fn strict_receive_paths(
+        &self,
+        source_assets: &[Asset],
+        destination_asset: &Asset,
+        destination_amount: Amount,
+    ) -> Result<Vec<PaymentPath>, StellarError> {
+        let mut query = asset_query("destination", destination_asset);
+        query.push(("destination_amount".into(), destination_amount.to_fixed()));
+        query.push(("source_assets".into(), asset_list(source_assets)));
+        self.records("/paths/strict-receive", &query, || {
+            format!("paths to {destination_asset}")
+        })
+        .await
+    }
+
+    /// The best path for `payment`: the one that delivers the most for a
+    /// strict send, or costs the least for a strict receive.
+    pub async fn quote(&self, payment: &PathPayment) -> Result<Quote, StellarError> {
+        if !payment.amount.is_positive() {
+            return Err(StellarError::InvalidAmount(payment.amount));
+        }
+        let (send, dest) = (&payment.send_asset, &payment.dest_asset);
+        let paths = match payment.strict {
+            Strict::Send => {
+                self.strict_send_paths(send, payment.amount, std::slice::from_ref(dest))
+                    .await?
+            }
+            Strict::Receive => {
+                self.strict_receive_paths(std::slice::from_ref(send), dest, payment.amount)
+                    .await?
+            }
+        };
+        let paths = paths.into_iter().filter(|path| {
+            path.source_asset == *send
+                && path.destination_asset == *dest
+                && path.path.len() <= MAX_PATH
+        });
+        let best = match payment.strict {
+            Strict::Send => paths.max_by_key(|path| path.destination_amount),
+            Strict::Receive => paths.min_by_key(|path| path.source_amount),
+        };
+        let path = best.ok_or_else(|| StellarError::NoPath {
+            from: send.clone(),
+            to: dest.clone(),
+        })?;
+        let limit = match payment.strict {
+            // A `dest_min` of zero is malformed.
+            Strict::Send => payment
+                .slippage
+                .at_least(path.destination_amount)
+                .max(Amount::from_stroops(1)),
+            Strict::Receive => payment.slippage.at_most(path.source_amount),
+        };
+        Ok(Quote {
+            path,
+            strict: payment.strict,
+            limit,
+        })
+    }
+
+    /// What `amount` of `asset` sells for in XLM over the best path from
+    /// `/paths/strict-send`: `amount` itself when `asset` is XLM.
+    pub async fn xlm_value(&self, asset: &Asset, amount: Amount) -> Result<Amount, StellarError> {
+        if asset.is_native() {
+            return Ok(amount);
+        }
+        let payment = PathPayment::strict_send(asset.clone(), amount, Asset::Native);
+        Ok(self.quote(&payment).await?.path.destination_amount)
+    }
+
+    /// Quotes `payment` and sends it from `source`'s account to
+    /// `destination`, which must already exist, once the source is found to
+    /// hold what it may send and the destination to have room for what it
+    /// may receive.
+    pub async fn path_payment(
+        &self,
+        source: &dyn Signer,
+        destination: &str,
+        payment: &PathPayment,
+    ) -> Result<PathPaid, StellarError> {
+        let (operation, quote) = self
+            .path_operation(source.public_key(), destination, payment)
+            .await?;
+        let record = self.sign_and_submit(source, vec![operation]).await?;
+        Ok(self.path_paid(record, quote).await)
+    }
+
+    /// The checked operation for [`path_payment`](Self::path_payment), and
+    /// the quote it was built from.
+    pub(crate) async fn path_operation(
+        &self,
+        source: &str,
+        destination: &str,
+        payment: &PathPayment,
+    ) -> Result<(Operation, Quote), StellarError> {
+        let destination_id = account_id(destination)?;
+        if !self.account_exists(destination).await? {
+            return Err(StellarError::NotFound(format!("account {destination}")));
+        }
+        let quote = self.quote(payment).await?;
+        let (most_sent, least_received) = match quote.strict {
+            Strict::Send => (payment.amount, quote.limit),
+            Strict::Receive => (quote.limit, payment.amount),
+        };
+        self.check_sender(source, &payment.send_asset, most_sent)
+            .await?;
+        self.check_receiver(destination, &payment.dest_asset, least_received)
+            .await?;
+
+        let send_asset = payment.send_asset.to_xdr()?;
+        let dest_asset = payment.dest_asset.to_xdr()?;
+        let path: VecM<_, 5> = quote
+            .path
+            .path
+            .iter()
+            .map(Asset::to_xdr)
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .expect("quoted paths have at most five assets");
+        let body = match quote.strict {
+            Strict::Send => OperationBody::PathPaymentStrictSend(PathPaymentStrictSendOp {
+                send_asset,
+                send_amount: payment.amount.stroops(),
+                destination: destination_id.into(),
+                dest_asset,
+                dest_min: quote.limit.stroops(),
+                path,
+            }),
+            Strict::Receive => {
+                OperationBody::PathPaymentStrictReceive(PathPaymentStrictReceiveOp {
+                    send_asset,
+                    send_max: quote.limit.stroops(),
+                    destination: destination_id.into(),
+                    dest_asset,
+                    dest_amount: payment.amount.stroops(),
+                    path,
+                })
+            }
+        };
+        let operation = Operation {
+            source_account: None,
+            body,
+        };
+        Ok((operation, quote))
+    }
+
+    /// What a submitted path payment sent and delivered, from its operation
+    /// record. If that cannot be read, the quote stands in for the end that
+    /// was not fixed.
+    pub(crate) async fn path_paid(&self, record: TransactionRecord, quote: Quote) -> PathPaid {
+        let operations = self
+            .transaction_operations(&record.hash, &PageQuery::default())
+            .await;
+        let realized = operations.ok().and_then(|page| {
+            let operation = page
+                .records
+                .into_iter()
+                .find(|op| op.kind.starts_with("path_payment_strict_"))?;
+            let sent = operation.detail("source_amount")?.parse().ok()?;
+            let received = operation.detail("amount")?.parse().ok()?;
+            Some((sent, received))
+        });
+        let (source_amount, destination_amount) =
+            realized.unwrap_or((quote.path.source_amount, quote.path.destination_amount));
+        PathPaid {
+            hash: record.hash,
+            ledger: record.ledger,
+            source_amount,
+            destination_amount,
+            quote,
+        }
+    }
+}
+
+/// `amount` times `bps` basis points.
+fn scale(amount: Amount, bps: u32, round_up: bool) -> Amount {
+    let scaled = i128::from(amount.stroops()) * i128::from(bps);
+    let (quotient, remainder) = (scaled / i128::from(FULL), scaled % i128::from(FULL));
+    let stroops = quotient + i128::from(round_up && remainder != 0);
+    i64::try_from(stroops).map_or(Amount::MAX, Amount::from_stroops)
+}
+
+/// `received` per unit of `sent`, rounded down.
+fn rate(sent: Amount, received: Amount) -> Amount {
+    if !sent.is_positive() {
+        return Amount::ZERO;
+    }
+    let stroops =
+        i128::from(received.stroops()) * i128::from(STROOPS_PER_XLM) / i128::from(sent.stroops());
+    i64::try_from(stroops).map_or(Amount::MAX, Amount::from_stroops)
+}
+
+/// `{side}_asset_type`, `{side}_asset_code` and `{side}_asset_issuer`.
+pub(crate) fn asset_query(side: &str, asset: &Asset) -> Vec<(String, String)> {
+    let mut query = vec![(format!("{side}_asset_type"), asset.horizon_type().into())];
+    if let Asset::Issued { code, issuer } = asset {
+        query.push((format!("{side}_asset_code"), code.clone()));
+        query.push((format!("{side}_asset_issuer"), issuer.clone()));
+    }
+    query
+}
+
+/// Assets as `destination_assets` and `source_assets` list them.
+fn asset_list(assets: &[Asset]) -> String {
+    assets
+        .iter()
+        .map(Asset::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
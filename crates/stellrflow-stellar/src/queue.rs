@@ -0,0 +1,395 @@
+//! One submission at a time per account, with a locally kept sequence
+//! number.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use stellar_xdr::curr::Operation;
+use stellrflow_amount::Amount;
+use stellrflow_signer::{Envelope, Signer};
+
+use crate::asset::Asset;
+use crate::claimable::{BalanceClaimed, BalanceCreated, Claimant};
+use crate::error::StellarError;
+use crate::fee::{fee_per_operation, inner_hash, FeeStrategy};
+use crate::horizon::Horizon;
+use crate::market::{LimitOrder, OfferPlaced};
+use crate::path::{PathPaid, PathPayment};
+use crate::resources::TransactionRecord;
+use crate::send::Sent;
+use crate::sponsor::{Sponsored, SponsoredEntry, Sponsorship};
+
+/// How often, and how patiently, a submission is retried after
+/// `tx_too_late`, `tx_insufficient_fee` or a timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// Submissions in all, counting the first.
+    pub attempts: u32,
+    /// The wait before the first retry; it doubles for each one after.
+    pub initial: Duration,
+    /// The longest wait between retries.
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            attempts: 5,
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(8),
+        }
+    }
+}
+
+impl Backoff {
+    /// The wait before retry `retry`, counting from 1.
+    fn delay(&self, retry: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+/// Submits transactions for any number of accounts, one at a time per
+/// account and in the order they were queued.
+///
+/// Each account's sequence number is loaded once and then counted up
+/// locally, so AutoPay, `/send` and anchor credits from one treasury no
+/// longer race each other's `loadAccount`. A `tx_bad_seq` (another wallet
+/// spent a number) reloads it. `tx_too_late` and timeouts are retried with
+/// [`Backoff`]; a timed-out transaction is looked up before anything
+/// replaces it, so a payment that did make a ledger is not sent twice.
+///
+/// Each transaction offers the fee its [`FeeStrategy`] picks from
+/// `/fee_stats`. With a fee sponsor, one that times out or offers too
+/// little is wrapped in a fee bump the sponsor pays for, which keeps its
+/// sequence number and so can only make a ledger once.
+pub struct SubmissionQueue {
+    horizon: Horizon,
+    backoff: Backoff,
+    fees: FeeStrategy,
+    sponsor: Option<Arc<dyn Signer>>,
+    lanes: Mutex<HashMap<String, Arc<Lane>>>,
+}
+
+/// One account's queue.
+#[derive(Debug, Default)]
+struct Lane {
+    /// The sequence number of the account's last transaction, once known.
+    /// Holding the lock is holding the head of the queue.
+    sequence: tokio::sync::Mutex<Option<i64>>,
+    depth: AtomicUsize,
+}
+
+/// A place in a [`Lane`], counted in its depth until dropped.
+struct Place<'a>(&'a Lane);
+
+impl Drop for Place<'_> {
+    fn drop(&mut self) {
+        self.0.depth.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl SubmissionQueue {
+    pub fn new(horizon: Horizon) -> Self {
+        SubmissionQueue {
+            horizon,
+            backoff: Backoff::default(),
+            fees: FeeStrategy::default(),
+            sponsor: None,
+            lanes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn with_fees(mut self, fees: FeeStrategy) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Bumps the fee of stuck transactions with fee bumps paid by
+    /// `sponsor`, such as the treasury.
+    pub fn with_fee_sponsor(mut self, sponsor: Arc<dyn Signer>) -> Self {
+        self.sponsor = Some(sponsor);
+        self
+    }
+
+    pub fn horizon(&self) -> &Horizon {
+        &self.horizon
+    }
+
+    /// How the queue picks the fee each transaction offers.
+    pub fn fees(&self) -> FeeStrategy {
+        self.fees
+    }
+
+    /// How many submissions from `account` are waiting or in flight.
+    pub fn depth(&self, account: &str) -> usize {
+        self.lanes()
+            .get(account)
+            .map_or(0, |lane| lane.depth.load(Ordering::SeqCst))
+    }
+
+    /// [`Horizon::send_native`], through the queue and at the fee its
+    /// [`FeeStrategy`] picks.
+    pub async fn send_native(
+        &self,
+        source: &dyn Signer,
+        destination: &str,
+        amount: Amount,
+    ) -> Result<Sent, StellarError> {
+        let (operation, created_account) = self.horizon.native_payment(destination, amount).await?;
+        let record = self.submit(source, vec![operation]).await?;
+        Ok(Sent::new(record, created_account))
+    }
+
+    /// [`Horizon::send_asset`], through the queue.
+    pub async fn send_asset(
+        &self,
+        source: &dyn Signer,
+        destination: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<Sent, StellarError> {
+        let (operation, created_account) = self
+            .horizon
+            .asset_payment(source.public_key(), destination, asset, amount)
+            .await?;
+        let record = self.submit(source, vec![operation]).await?;
+        Ok(Sent::new(record, created_account))
+    }
+
+    /// [`Horizon::change_trust`], through the queue.
+    pub async fn change_trust(
+        &self,
+        source: &dyn Signer,
+        asset: &Asset,
+        limit: Option<Amount>,
+    ) -> Result<TransactionRecord, StellarError> {
+        let operation = self
+            .horizon
+            .trust(source.public_key(), asset, limit)
+            .await?;
+        self.submit(source, vec![operation]).await
+    }
+
+    /// [`Horizon::path_payment`], through the queue.
+    pub async fn path_payment(
+        &self,
+        source: &dyn Signer,
+        destination: &str,
+        payment: &PathPayment,
+    ) -> Result<PathPaid, StellarError> {
+        let (operation, quote) = self
+            .horizon
+            .path_operation(source.public_key(), destination, payment)
+            .await?;
+        let record = self.submit(source, vec![operation]).await?;
+        Ok(self.horizon.path_paid(record, quote).await)
+    }
+
+    /// [`Horizon::manage_offer`], through the queue.
+    pub async fn manage_offer(
+        &self,
+        source: &dyn Signer,
+        order: &LimitOrder,
+    ) -> Result<OfferPlaced, StellarError> {
+        let operation = self
+            .horizon
+            .offer_operation(source.public_key(), order)
+            .await?;
+        let record = self.submit(source, vec![operation]).await?;
+        Ok(OfferPlaced::new(record, order))
+    }
+
+    /// [`Horizon::create_claimable_balance`], through the queue.
+    pub async fn create_claimable_balance(
+        &self,
+        source: &dyn Signer,
+        asset: &Asset,
+        amount: Amount,
+        claimants: &[Claimant],
+    ) -> Result<BalanceCreated, StellarError> {
+        let operation = self
+            .horizon
+            .claimable_operation(source.public_key(), asset, amount, claimants)
+            .await?;
+        let record = self.submit(source, vec![operation]).await?;
+        BalanceCreated::new(record)
+    }
+
+    /// [`Horizon::claim_balance`], through the queue.
+    pub async fn claim_balance(
+        &self,
+        source: &dyn Signer,
+        balance_id: &str,
+    ) -> Result<BalanceClaimed, StellarError> {
+        let (operation, balance) = self
+            .horizon
+            .claim_operation(source.public_key(), balance_id)
+            .await?;
+        let record = self.submit(source, vec![operation]).await?;
+        Ok(BalanceClaimed::new(record, balance))
+    }
+
+    /// [`Horizon::sponsor_account`], through the queue of `sponsor`'s
+    /// transactions.
+    pub async fn sponsor_account(
+        &self,
+        sponsor: &dyn Signer,
+        account: &dyn Signer,
+        sponsorship: &Sponsorship,
+    ) -> Result<Sponsored, StellarError> {
+        let (operations, planned) = self
+            .horizon
+            .sponsorship_operations(sponsor.public_key(), account.public_key(), sponsorship)
+            .await?;
+        let record = self.submit_with(sponsor, &[account], operations).await?;
+        Ok(Sponsored::new(record, account.public_key(), planned))
+    }
+
+    /// [`Horizon::revoke_sponsorship`], through the queue.
+    pub async fn revoke_sponsorship(
+        &self,
+        sponsor: &dyn Signer,
+        entry: &SponsoredEntry,
+    ) -> Result<TransactionRecord, StellarError> {
+        let operation = self
+            .horizon
+            .revoke_operation(sponsor.public_key(), entry)
+            .await?;
+        self.submit(sponsor, vec![operation]).await
+    }
+
+    /// Signs `operations` as `source`'s next transaction once every earlier
+    /// one from the account is done, and submits it.
+    pub async fn submit(
+        &self,
+        source: &dyn Signer,
+        operations: Vec<Operation>,
+    ) -> Result<TransactionRecord, StellarError> {
+        self.submit_with(source, &[], operations).await
+    }
+
+    /// As [`submit`](Self::submit), also signed by `cosigners`: the source
+    /// accounts of operations that are not `source`'s. Only `source`'s
+    /// sequence number is kept.
+    pub async fn submit_with(
+        &self,
+        source: &dyn Signer,
+        cosigners: &[&dyn Signer],
+        operations: Vec<Operation>,
+    ) -> Result<TransactionRecord, StellarError> {
+        let account = source.public_key();
+        let lane = self.lane(account);
+        lane.depth.fetch_add(1, Ordering::SeqCst);
+        let _place = Place(&lane);
+        let mut last = lane.sequence.lock().await;
+
+        // A transaction whose submission timed out, by sequence number and
+        // hash: it may have made a ledger after all.
+        let mut unconfirmed: Option<(i64, String)> = None;
+        // Resubmitted as is, or bumped, rather than rebuilt.
+        let mut pending = None;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let (sequence, envelope) = match pending.take() {
+                Some(pending) => pending,
+                None => {
+                    let current = match *last {
+                        Some(sequence) => sequence,
+                        None => self.horizon.account(account).await?.sequence,
+                    };
+                    *last = Some(current);
+                    let fee = self.horizon.suggested_fee(&self.fees).await?;
+                    let envelope = self
+                        .horizon
+                        .sign(source, cosigners, current + 1, fee, operations.clone())
+                        .await?;
+                    (current + 1, envelope)
+                }
+            };
+
+            let err = match self.horizon.submit(&envelope).await {
+                Ok(record) => {
+                    *last = Some(sequence);
+                    return Ok(record);
+                }
+                Err(err) => err,
+            };
+            let code = err.result_codes().map(|codes| codes.code().to_string());
+            let wait = match code.as_deref() {
+                // It made a ledger and spent the sequence number, but an
+                // operation failed.
+                Some("tx_failed") => {
+                    *last = Some(sequence);
+                    return Err(err);
+                }
+                Some("tx_bad_seq") => {
+                    if let Some((sequence, hash)) = &unconfirmed {
+                        if let Ok(record) = self.horizon.transaction(hash).await {
+                            *last = Some(*sequence);
+                            return Ok(record);
+                        }
+                    }
+                    *last = None;
+                    false
+                }
+                Some("tx_too_late") => true,
+                // Stuck: bump it if a sponsor will pay. Otherwise a timed-out
+                // transaction is resent as is, and one offering too little
+                // is rebuilt at the current fee.
+                Some("tx_insufficient_fee") => {
+                    pending = self.bump(&envelope).await?.map(|bumped| (sequence, bumped));
+                    true
+                }
+                _ if err.is_timeout() => {
+                    unconfirmed = Some((sequence, inner_hash(&envelope)));
+                    let bumped = self.bump(&envelope).await?;
+                    pending = Some((sequence, bumped.unwrap_or(envelope)));
+                    true
+                }
+                _ => return Err(err),
+            };
+            if attempt >= self.backoff.attempts {
+                return Err(err);
+            }
+            if wait {
+                tokio::time::sleep(self.backoff.delay(attempt)).await;
+            }
+        }
+    }
+
+    /// `envelope` in a fee bump paid by the sponsor, if there is one and the
+    /// fee cap leaves room to outbid it.
+    async fn bump(&self, envelope: &Envelope) -> Result<Option<Envelope>, StellarError> {
+        let Some(sponsor) = &self.sponsor else {
+            return Ok(None);
+        };
+        let stats = self.horizon.fee_stats().await?;
+        let Some(fee) = self.fees.bump_fee(&stats, fee_per_operation(envelope)) else {
+            return Ok(None);
+        };
+        let bumped = self
+            .horizon
+            .fee_bump(envelope, sponsor.as_ref(), fee)
+            .await?;
+        Ok(Some(bumped))
+    }
+
+    fn lane(&self, account: &str) -> Arc<Lane> {
+        self.lanes().entry(account.to_string()).or_default().clone()
+    }
+
+    fn lanes(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Lane>>> {
+        self.lanes.lock().expect("submission queue poisoned")
+    }
+}
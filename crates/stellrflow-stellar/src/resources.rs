@@ -0,0 +1,593 @@
+//! Horizon's resources, with its string-encoded numbers read as numbers.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use stellar_xdr::curr::{
+    InnerTransactionResultResult, Limits, OperationResult, ReadXdr, TransactionResult,
+    TransactionResultResult,
+};
+use stellrflow_amount::Amount;
+
+use crate::asset::Asset;
+use crate::claimable::{epoch_seconds, Claimant};
+use crate::market::Price;
+use crate::sponsor::SponsoredEntry;
+
+/// An account and what it holds, from `/accounts/{id}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Account {
+    #[serde(rename = "account_id")]
+    pub id: String,
+    /// The sequence number of the account's last transaction; the next one
+    /// must use this plus one.
+    #[serde(deserialize_with = "number")]
+    pub sequence: i64,
+    #[serde(default)]
+    pub subentry_count: u32,
+    pub balances: Vec<Balance>,
+    #[serde(default)]
+    pub signers: Vec<AccountSigner>,
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    #[serde(default)]
+    pub flags: AccountFlags,
+    #[serde(default)]
+    pub num_sponsoring: u32,
+    #[serde(default)]
+    pub num_sponsored: u32,
+    /// The account holding the account's own two base reserves, if any.
+    #[serde(default)]
+    pub sponsor: Option<String>,
+}
+
+impl Account {
+    /// The account's XLM balance.
+    pub fn native_balance(&self) -> Amount {
+        self.balances
+            .iter()
+            .find(|balance| balance.is_native())
+            .map_or(Amount::ZERO, |balance| balance.balance)
+    }
+
+    /// The account's balance of `asset`: for an issued asset, its trustline,
+    /// if it has one.
+    pub fn balance_of(&self, asset: &Asset) -> Option<&Balance> {
+        self.balances
+            .iter()
+            .find(|balance| balance.asset().as_ref() == Some(asset))
+    }
+
+    /// The account's entries whose reserves `sponsor` holds.
+    pub fn sponsored_by(&self, sponsor: &str) -> Vec<SponsoredEntry> {
+        let mut entries = Vec::new();
+        if self.sponsor.as_deref() == Some(sponsor) {
+            entries.push(SponsoredEntry::Account(self.id.clone()));
+        }
+        for balance in &self.balances {
+            if balance.sponsor.as_deref() != Some(sponsor) {
+                continue;
+            }
+            if let Some(asset) = balance.asset() {
+                entries.push(SponsoredEntry::Trustline {
+                    account: self.id.clone(),
+                    asset,
+                });
+            }
+        }
+        entries
+    }
+}
+
+/// One asset an account holds: XLM, or a trustline to an issued asset.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Balance {
+    pub balance: Amount,
+    /// `native`, `credit_alphanum4`, `credit_alphanum12` or `liquidity_pool_shares`.
+    pub asset_type: String,
+    #[serde(default)]
+    pub asset_code: Option<String>,
+    #[serde(default)]
+    pub asset_issuer: Option<String>,
+    /// The trustline's limit; absent for XLM.
+    #[serde(default)]
+    pub limit: Option<Amount>,
+    #[serde(default)]
+    pub buying_liabilities: Option<Amount>,
+    #[serde(default)]
+    pub selling_liabilities: Option<Amount>,
+    /// Whether the issuer lets the trustline hold and send its asset;
+    /// absent for XLM.
+    #[serde(default)]
+    pub is_authorized: Option<bool>,
+    /// The account holding the trustline's base reserve, if not its own.
+    #[serde(default)]
+    pub sponsor: Option<String>,
+}
+
+impl Balance {
+    pub fn is_native(&self) -> bool {
+        self.asset_type == "native"
+    }
+
+    /// The asset held; `None` for liquidity pool shares.
+    pub fn asset(&self) -> Option<Asset> {
+        Asset::from_horizon(
+            &self.asset_type,
+            self.asset_code.as_deref(),
+            self.asset_issuer.as_deref(),
+        )
+    }
+}
+
+/// How an account issues its assets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct AccountFlags {
+    /// New trustlines to its assets cannot hold them until it authorizes
+    /// them.
+    #[serde(default)]
+    pub auth_required: bool,
+    #[serde(default)]
+    pub auth_revocable: bool,
+    #[serde(default)]
+    pub auth_immutable: bool,
+    #[serde(default)]
+    pub auth_clawback_enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccountSigner {
+    pub key: String,
+    pub weight: u32,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Thresholds {
+    pub low_threshold: u8,
+    pub med_threshold: u8,
+    pub high_threshold: u8,
+}
+
+/// A transaction in the ledger, from `/transactions/{hash}` or a
+/// successful submission.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TransactionRecord {
+    pub hash: String,
+    pub ledger: u32,
+    #[serde(default)]
+    pub successful: bool,
+    #[serde(default)]
+    pub paging_token: String,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub source_account: String,
+    /// The account that paid the fee: the source, or a fee bump's sponsor.
+    #[serde(default)]
+    pub fee_account: String,
+    /// In stroops.
+    #[serde(default, deserialize_with = "number")]
+    pub fee_charged: u64,
+    /// The most the transaction offered, in stroops; for a fee bump, what
+    /// the bump offered.
+    #[serde(default, deserialize_with = "number")]
+    pub max_fee: u64,
+    /// The transaction a fee bump wrapped; `hash` is then the bump's.
+    #[serde(default)]
+    pub inner_transaction: Option<InnerTransaction>,
+    #[serde(default)]
+    pub operation_count: u32,
+    #[serde(default)]
+    pub memo_type: Option<String>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub envelope_xdr: String,
+    #[serde(default)]
+    pub result_xdr: String,
+}
+
+impl TransactionRecord {
+    /// The results of its operations, read from `result_xdr`; `None` if it
+    /// cannot be read or the transaction failed.
+    pub(crate) fn operation_results(&self) -> Option<Vec<OperationResult>> {
+        let result = TransactionResult::from_xdr_base64(&self.result_xdr, Limits::none()).ok()?;
+        let operations = match result.result {
+            TransactionResultResult::TxSuccess(operations) => operations,
+            TransactionResultResult::TxFeeBumpInnerSuccess(pair) => match pair.result.result {
+                InnerTransactionResultResult::TxSuccess(operations) => operations,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(operations.into())
+    }
+}
+
+/// The transaction inside a fee bump.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct InnerTransaction {
+    pub hash: String,
+    /// In stroops.
+    #[serde(default, deserialize_with = "number")]
+    pub max_fee: u64,
+}
+
+/// An operation, from `/operations` or `/payments`. The fields that
+/// depend on its `type` (`from`, `to`, `amount` for a payment; `account`,
+/// `funder`, `starting_balance` for `create_account`; …) are in `details`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OperationRecord {
+    pub id: String,
+    pub paging_token: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub source_account: String,
+    #[serde(default)]
+    pub transaction_hash: String,
+    #[serde(default)]
+    pub transaction_successful: bool,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(flatten)]
+    pub details: Map<String, Value>,
+}
+
+impl OperationRecord {
+    /// A type-specific field that holds text, such as `to` or `amount`.
+    pub fn detail(&self, name: &str) -> Option<&str> {
+        self.details.get(name).and_then(Value::as_str)
+    }
+}
+
+/// A way to convert one asset into another through the order books, from
+/// `/paths/strict-send` or `/paths/strict-receive`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawPath")]
+pub struct PaymentPath {
+    pub source_asset: Asset,
+    pub source_amount: Amount,
+    pub destination_asset: Asset,
+    pub destination_amount: Amount,
+    /// The assets it goes through in between, at most five.
+    pub path: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct RawPath {
+    source_asset_type: String,
+    #[serde(default)]
+    source_asset_code: Option<String>,
+    #[serde(default)]
+    source_asset_issuer: Option<String>,
+    source_amount: Amount,
+    destination_asset_type: String,
+    #[serde(default)]
+    destination_asset_code: Option<String>,
+    #[serde(default)]
+    destination_asset_issuer: Option<String>,
+    destination_amount: Amount,
+    #[serde(default)]
+    path: Vec<RawAsset>,
+}
+
+#[derive(Deserialize)]
+struct RawAsset {
+    asset_type: String,
+    #[serde(default)]
+    asset_code: Option<String>,
+    #[serde(default)]
+    asset_issuer: Option<String>,
+}
+
+impl RawAsset {
+    fn asset(&self) -> Result<Asset, String> {
+        asset(&self.asset_type, &self.asset_code, &self.asset_issuer)
+    }
+}
+
+impl TryFrom<RawPath> for PaymentPath {
+    type Error = String;
+
+    fn try_from(raw: RawPath) -> Result<Self, Self::Error> {
+        Ok(PaymentPath {
+            source_asset: asset(
+                &raw.source_asset_type,
+                &raw.source_asset_code,
+                &raw.source_asset_issuer,
+            )?,
+            source_amount: raw.source_amount,
+            destination_asset: asset(
+                &raw.destination_asset_type,
+                &raw.destination_asset_code,
+                &raw.destination_asset_issuer,
+            )?,
+            destination_amount: raw.destination_amount,
+            path: raw
+                .path
+                .iter()
+                .map(RawAsset::asset)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn asset(
+    asset_type: &str,
+    code: &Option<String>,
+    issuer: &Option<String>,
+) -> Result<Asset, String> {
+    Asset::from_horizon(asset_type, code.as_deref(), issuer.as_deref())
+        .ok_or_else(|| format!("unexpected asset type `{asset_type}`"))
+}
+
+/// The offers between two assets, from `/order_book`. Both sides are
+/// priced in the counter asset per unit of the base asset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Orderbook {
+    /// Offers to buy the base asset, highest price first. Their amounts
+    /// are in the counter asset.
+    pub bids: Vec<Level>,
+    /// Offers to sell the base asset, lowest price first. Their amounts
+    /// are in the base asset.
+    pub asks: Vec<Level>,
+}
+
+impl Orderbook {
+    pub fn best_bid(&self) -> Option<Price> {
+        self.bids.first().map(|level| level.price)
+    }
+
+    pub fn best_ask(&self) -> Option<Price> {
+        self.asks.first().map(|level| level.price)
+    }
+
+    /// Halfway between the best bid and the best ask; `None` unless both
+    /// sides have offers.
+    pub fn mid(&self) -> Option<Price> {
+        Some(self.best_bid()?.midpoint(self.best_ask()?))
+    }
+}
+
+/// The offers at one price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Level {
+    #[serde(rename = "price_r")]
+    pub price: Price,
+    pub amount: Amount,
+}
+
+/// The trades between two assets in one bucket of time, from
+/// `/trade_aggregations`. Prices are in the counter asset per unit of the
+/// base asset.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TradeAggregation {
+    /// When the bucket starts, in milliseconds since the Unix epoch.
+    #[serde(deserialize_with = "number")]
+    pub timestamp: i64,
+    #[serde(deserialize_with = "number")]
+    pub trade_count: u64,
+    pub base_volume: Amount,
+    pub counter_volume: Amount,
+    #[serde(rename = "open_r")]
+    pub open: Price,
+    #[serde(rename = "high_r")]
+    pub high: Price,
+    #[serde(rename = "low_r")]
+    pub low: Price,
+    #[serde(rename = "close_r")]
+    pub close: Price,
+}
+
+/// An account's offer on the order books, from `/accounts/{id}/offers`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawOffer")]
+pub struct OfferRecord {
+    pub id: i64,
+    pub paging_token: String,
+    pub seller: String,
+    pub selling: Asset,
+    pub buying: Asset,
+    /// How much of `selling` is left to sell.
+    pub amount: Amount,
+    /// What one unit of `selling` costs in `buying`.
+    pub price: Price,
+    pub last_modified_ledger: u32,
+}
+
+#[derive(Deserialize)]
+struct RawOffer {
+    #[serde(deserialize_with = "number")]
+    id: i64,
+    #[serde(default)]
+    paging_token: String,
+    seller: String,
+    selling: RawAsset,
+    buying: RawAsset,
+    amount: Amount,
+    price_r: Price,
+    #[serde(default)]
+    last_modified_ledger: u32,
+}
+
+impl TryFrom<RawOffer> for OfferRecord {
+    type Error = String;
+
+    fn try_from(raw: RawOffer) -> Result<Self, Self::Error> {
+        Ok(OfferRecord {
+            id: raw.id,
+            paging_token: raw.paging_token,
+            seller: raw.seller,
+            selling: raw.selling.asset()?,
+            buying: raw.buying.asset()?,
+            amount: raw.amount,
+            price: raw.price_r,
+            last_modified_ledger: raw.last_modified_ledger,
+        })
+    }
+}
+
+/// Funds set aside for their claimants, from `/claimable_balances`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ClaimableBalance {
+    /// The balance's ID: the hex of its XDR, as claims name it.
+    pub id: String,
+    pub asset: Asset,
+    pub amount: Amount,
+    /// The account holding the reserves for it: its creator, unless
+    /// someone else sponsored it.
+    #[serde(default)]
+    pub sponsor: Option<String>,
+    pub claimants: Vec<Claimant>,
+    #[serde(default)]
+    pub last_modified_ledger: u32,
+    #[serde(default)]
+    pub paging_token: String,
+}
+
+impl ClaimableBalance {
+    /// Whether `account` is a claimant allowed to claim it at `at`. The
+    /// ledger keeps predicates with absolute times only.
+    pub fn claimable_by(&self, account: &str, at: SystemTime) -> bool {
+        let at = epoch_seconds(at);
+        self.claimants
+            .iter()
+            .any(|claimant| claimant.destination == account && claimant.predicate.allows(at, at))
+    }
+}
+
+/// What recent ledgers charged, from `/fee_stats`. Fees are in stroops.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeStats {
+    #[serde(deserialize_with = "number")]
+    pub last_ledger: u32,
+    #[serde(deserialize_with = "number")]
+    pub last_ledger_base_fee: u64,
+    /// How full recent ledgers were, from 0 to 1.
+    #[serde(deserialize_with = "number")]
+    pub ledger_capacity_usage: f64,
+    /// What transactions were charged.
+    pub fee_charged: FeeDistribution,
+    /// What transactions offered.
+    pub max_fee: FeeDistribution,
+}
+
+/// Percentiles of the fees in recent ledgers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeDistribution {
+    #[serde(deserialize_with = "number")]
+    pub max: u64,
+    #[serde(deserialize_with = "number")]
+    pub min: u64,
+    #[serde(deserialize_with = "number")]
+    pub mode: u64,
+    #[serde(deserialize_with = "number")]
+    pub p10: u64,
+    #[serde(deserialize_with = "number")]
+    pub p20: u64,
+    #[serde(deserialize_with = "number")]
+    pub p30: u64,
+    #[serde(deserialize_with = "number")]
+    pub p40: u64,
+    #[serde(deserialize_with = "number")]
+    pub p50: u64,
+    #[serde(deserialize_with = "number")]
+    pub p60: u64,
+    #[serde(deserialize_with = "number")]
+    pub p70: u64,
+    #[serde(deserialize_with = "number")]
+    pub p80: u64,
+    #[serde(deserialize_with = "number")]
+    pub p90: u64,
+    #[serde(deserialize_with = "number")]
+    pub p95: u64,
+    #[serde(deserialize_with = "number")]
+    pub p99: u64,
+}
+
+impl FeeDistribution {
+    /// The fee at the `percentile`th percentile, rounded up to one Horizon
+    /// reports: 75 reads `p80`, and anything over 99 reads `max`.
+    pub fn percentile(&self, percentile: u8) -> u64 {
+        match percentile {
+            0..=10 => self.p10,
+            11..=20 => self.p20,
+            21..=30 => self.p30,
+            31..=40 => self.p40,
+            41..=50 => self.p50,
+            51..=60 => self.p60,
+            61..=70 => self.p70,
+            71..=80 => self.p80,
+            81..=90 => self.p90,
+            91..=95 => self.p95,
+            96..=99 => self.p99,
+            _ => self.max,
+        }
+    }
+}
+
+/// One page of a collection, oldest or newest first as requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub records: Vec<T>,
+    /// Where the next page starts; `None` on an empty page.
+    pub next: Option<PageQuery>,
+}
+
+/// Which page of a collection to fetch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageQuery {
+    /// Start after this paging token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    /// Horizon's default is 10, its maximum 200.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+}
+
+impl PageQuery {
+    /// The newest `limit` records first.
+    pub fn latest(limit: u32) -> Self {
+        PageQuery {
+            cursor: None,
+            limit: Some(limit),
+            order: Some(Order::Desc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// A number Horizon sends as a string, also accepting a JSON number.
+fn number<'de, D, T>(d: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Text(String),
+        Number(serde_json::Number),
+    }
+    let text = match Raw::deserialize(d)? {
+        Raw::Text(text) => text,
+        Raw::Number(number) => number.to_string(),
+    };
+    text.parse().map_err(serde::de::Error::custom)
+}
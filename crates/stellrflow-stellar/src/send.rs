@@ -0,0 +1,218 @@
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use stellar_xdr::curr::{
+    AccountId, Asset, CreateAccountOp, Memo, MuxedAccount, Operation, OperationBody, PaymentOp,
+    Preconditions, SequenceNumber, TimeBounds, TimePoint, Transaction, TransactionEnvelope,
+    TransactionExt, TransactionV1Envelope,
+};
+use stellrflow_amount::Amount;
+use stellrflow_signer::{Envelope, Signer};
+
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::resources::TransactionRecord;
+use crate::{BASE_FEE, MIN_STARTING_BALANCE, TX_TIMEOUT};
+
+/// What [`Horizon::send_native`] or [`Horizon::send_asset`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sent {
+    /// The transaction's hash; the fee bump's if it was bumped.
+    pub hash: String,
+    pub ledger: u32,
+    /// Whether the destination did not exist and was created with the
+    /// payment as its starting balance.
+    pub created_account: bool,
+    /// The fee the transaction offered per operation, in stroops.
+    pub fee: u32,
+    /// What the network charged, in stroops, to the source or the sponsor.
+    pub fee_charged: u64,
+    /// Set when the transaction got stuck and a sponsor bumped its fee.
+    pub fee_bump: Option<FeeBump>,
+}
+
+/// How a stuck transaction was bumped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeBump {
+    /// The account that paid the fee.
+    pub fee_source: String,
+    /// The fee the bump offered per operation, counting itself as one, in
+    /// stroops.
+    pub fee: u32,
+    /// The hash of the transaction inside the bump.
+    pub inner_hash: String,
+}
+
+impl Sent {
+    pub(crate) fn new(record: TransactionRecord, created_account: bool) -> Self {
+        let operations = u64::from(record.operation_count.max(1));
+        let per_operation =
+            |fee: u64, operations| u32::try_from(fee / operations).unwrap_or(u32::MAX);
+        let (fee, fee_bump) = match record.inner_transaction {
+            Some(inner) => (
+                per_operation(inner.max_fee, operations),
+                Some(FeeBump {
+                    fee_source: record.fee_account,
+                    fee: per_operation(record.max_fee, operations + 1),
+                    inner_hash: inner.hash,
+                }),
+            ),
+            None => (per_operation(record.max_fee, operations), None),
+        };
+        Sent {
+            hash: record.hash,
+            ledger: record.ledger,
+            created_account,
+            fee,
+            fee_charged: record.fee_charged,
+            fee_bump,
+        }
+    }
+}
+
+impl Horizon {
+    /// Sends `amount` XLM from `source`'s account to `destination`, as the
+    /// bot's `/send` does: a `payment` if the destination exists, otherwise a
+    /// `createAccount` of at least [`MIN_STARTING_BALANCE`].
+    ///
+    /// It offers [`BASE_FEE`] and reads the source's sequence number just
+    /// before submitting, so it can stall when the network is busy and two
+    /// sends from one account at once can collide; see
+    /// [`SubmissionQueue`](crate::SubmissionQueue).
+    pub async fn send_native(
+        &self,
+        source: &dyn Signer,
+        destination: &str,
+        amount: Amount,
+    ) -> Result<Sent, StellarError> {
+        let (operation, created_account) = self.native_payment(destination, amount).await?;
+        let record = self.sign_and_submit(source, vec![operation]).await?;
+        Ok(Sent::new(record, created_account))
+    }
+
+    /// Signs `operations` as `source`'s next transaction at [`BASE_FEE`]
+    /// and submits it.
+    pub(crate) async fn sign_and_submit(
+        &self,
+        source: &dyn Signer,
+        operations: Vec<Operation>,
+    ) -> Result<TransactionRecord, StellarError> {
+        self.sign_and_submit_with(source, &[], operations).await
+    }
+
+    /// As [`sign_and_submit`](Self::sign_and_submit), also signed by
+    /// `cosigners`: the source accounts of operations that are not
+    /// `source`'s.
+    pub(crate) async fn sign_and_submit_with(
+        &self,
+        source: &dyn Signer,
+        cosigners: &[&dyn Signer],
+        operations: Vec<Operation>,
+    ) -> Result<TransactionRecord, StellarError> {
+        let account = self.account(source.public_key()).await?;
+        let envelope = self
+            .sign(
+                source,
+                cosigners,
+                account.sequence + 1,
+                BASE_FEE,
+                operations,
+            )
+            .await?;
+        self.submit(&envelope).await
+    }
+
+    /// The operation that sends `amount` XLM to `destination`, and whether
+    /// it creates the account.
+    pub(crate) async fn native_payment(
+        &self,
+        destination: &str,
+        amount: Amount,
+    ) -> Result<(Operation, bool), StellarError> {
+        if !amount.is_positive() {
+            return Err(StellarError::InvalidAmount(amount));
+        }
+        let destination_id = account_id(destination)?;
+        let created_account = !self.account_exists(destination).await?;
+        let body = if created_account {
+            if amount < MIN_STARTING_BALANCE {
+                return Err(StellarError::BelowMinimum);
+            }
+            OperationBody::CreateAccount(CreateAccountOp {
+                destination: destination_id,
+                starting_balance: amount.stroops(),
+            })
+        } else {
+            OperationBody::Payment(PaymentOp {
+                destination: destination_id.into(),
+                asset: Asset::Native,
+                amount: amount.stroops(),
+            })
+        };
+        let operation = Operation {
+            source_account: None,
+            body,
+        };
+        Ok((operation, created_account))
+    }
+
+    /// A transaction of `operations` at `sequence` offering `fee` for each,
+    /// signed by `source`.
+    pub(crate) async fn sign(
+        &self,
+        source: &dyn Signer,
+        cosigners: &[&dyn Signer],
+        sequence: i64,
+        fee: u32,
+        operations: Vec<Operation>,
+    ) -> Result<Envelope, StellarError> {
+        let transaction = transaction(source.public_key(), sequence, fee, operations)?;
+        let mut envelope = Envelope::new(
+            TransactionEnvelope::Tx(TransactionV1Envelope {
+                tx: transaction,
+                signatures: Default::default(),
+            }),
+            self.network_passphrase(),
+        );
+        envelope.sign_with(source).await?;
+        for cosigner in cosigners {
+            envelope.sign_with(*cosigner).await?;
+        }
+        Ok(envelope)
+    }
+}
+
+pub(crate) fn account_id(account: &str) -> Result<AccountId, StellarError> {
+    AccountId::from_str(account).map_err(|_| StellarError::InvalidAccount(account.to_string()))
+}
+
+/// A transaction from `source` offering `fee` for each of its (at most
+/// [`MAX_OPERATIONS`](crate::MAX_OPERATIONS)) operations and valid for [`TX_TIMEOUT`] seconds from now.
+pub(crate) fn transaction(
+    source: &str,
+    sequence: i64,
+    fee: u32,
+    operations: Vec<Operation>,
+) -> Result<Transaction, StellarError> {
+    let source_account: MuxedAccount = account_id(source)?.into();
+    let count = operations.len();
+    let fee = fee.saturating_mul(count as u32);
+    let max_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        + Duration::from_secs(TX_TIMEOUT);
+    Ok(Transaction {
+        source_account,
+        fee,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::Time(TimeBounds {
+            min_time: TimePoint(0),
+            max_time: TimePoint(max_time.as_secs()),
+        }),
+        memo: Memo::None,
+        operations: operations
+            .try_into()
+            .map_err(|_| StellarError::TooManyOperations(count))?,
+        ext: TransactionExt::V0,
+    })
+}
@@ -0,0 +1,318 @@
+//! Sponsored reserves: one account holding the base reserves of another's
+//! entries, so the treasury can open accounts for Telegram wallets on a
+//! network without Friendbot.
+
+use std::fmt;
+
+use stellar_xdr::curr::{
+    self as xdr, BeginSponsoringFutureReservesOp, ChangeTrustOp, CreateAccountOp, LedgerKey,
+    LedgerKeyAccount, LedgerKeyTrustLine, Operation, OperationBody, RevokeSponsorshipOp,
+    TrustLineAsset,
+};
+use stellrflow_amount::Amount;
+use stellrflow_signer::Signer;
+
+use crate::asset::Asset;
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::resources::{Account, Page, PageQuery, TransactionRecord};
+use crate::send::account_id;
+use crate::MAX_OPERATIONS;
+
+/// The entries a sponsor takes the reserves of for one account: the
+/// account itself, if it does not exist yet, and trustlines to `trustlines`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sponsorship {
+    /// What the new account starts with; it needs nothing for its own
+    /// reserves, which the sponsor holds.
+    pub starting_balance: Amount,
+    pub trustlines: Vec<Asset>,
+}
+
+impl Sponsorship {
+    pub fn new() -> Self {
+        Sponsorship::default()
+    }
+
+    pub fn with_starting_balance(mut self, starting_balance: Amount) -> Self {
+        self.starting_balance = starting_balance;
+        self
+    }
+
+    /// Also opens a trustline to `asset`, as much as it can hold.
+    pub fn with_trustline(mut self, asset: Asset) -> Self {
+        self.trustlines.push(asset);
+        self
+    }
+}
+
+/// What [`Horizon::sponsor_account`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sponsored {
+    pub hash: String,
+    pub ledger: u32,
+    pub account: String,
+    /// Whether the account did not exist and was created.
+    pub created_account: bool,
+    /// The trustlines opened.
+    pub trustlines: Vec<Asset>,
+}
+
+impl Sponsored {
+    pub(crate) fn new(record: TransactionRecord, account: &str, planned: Planned) -> Self {
+        Sponsored {
+            hash: record.hash,
+            ledger: record.ledger,
+            account: account.to_string(),
+            created_account: planned.created_account,
+            trustlines: planned.trustlines,
+        }
+    }
+
+    /// The base reserves the sponsor took on: two for a new account and
+    /// one per trustline.
+    pub fn reserves(&self) -> u32 {
+        2 * u32::from(self.created_account) + self.trustlines.len() as u32
+    }
+}
+
+/// A ledger entry whose reserve an account sponsors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SponsoredEntry {
+    /// The account itself, two base reserves.
+    Account(String),
+    Trustline {
+        account: String,
+        asset: Asset,
+    },
+}
+
+impl SponsoredEntry {
+    /// The account the entry belongs to.
+    pub fn account(&self) -> &str {
+        match self {
+            SponsoredEntry::Account(account) | SponsoredEntry::Trustline { account, .. } => account,
+        }
+    }
+
+    fn to_ledger_key(&self) -> Result<LedgerKey, StellarError> {
+        let account_id = account_id(self.account())?;
+        Ok(match self {
+            SponsoredEntry::Account(_) => LedgerKey::Account(LedgerKeyAccount { account_id }),
+            SponsoredEntry::Trustline { asset, .. } => {
+                let asset = match asset.to_xdr()? {
+                    xdr::Asset::CreditAlphanum4(credit) => TrustLineAsset::CreditAlphanum4(credit),
+                    xdr::Asset::CreditAlphanum12(credit) => {
+                        TrustLineAsset::CreditAlphanum12(credit)
+                    }
+                    xdr::Asset::Native => {
+                        return Err(StellarError::InvalidAsset(asset.to_string()))
+                    }
+                };
+                LedgerKey::Trustline(LedgerKeyTrustLine { account_id, asset })
+            }
+        })
+    }
+}
+
+impl fmt::Display for SponsoredEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SponsoredEntry::Account(account) => write!(f, "account {account}"),
+            SponsoredEntry::Trustline { account, asset } => {
+                write!(f, "{account}'s trustline to {asset}")
+            }
+        }
+    }
+}
+
+/// The entries a checked [`Sponsorship`] creates.
+pub(crate) struct Planned {
+    created_account: bool,
+    trustlines: Vec<Asset>,
+}
+
+impl Horizon {
+    /// Has `sponsor` hold the reserves of `account`'s new entries: creates
+    /// the account if it does not exist yet, with
+    /// [`starting_balance`](Sponsorship::starting_balance) (which may be
+    /// zero), and opens its trustlines, all between a
+    /// `begin_sponsoring_future_reserves` and an `end_sponsoring_future_reserves`
+    /// in one transaction from `sponsor`. Both accounts sign it.
+    pub async fn sponsor_account(
+        &self,
+        sponsor: &dyn Signer,
+        account: &dyn Signer,
+        sponsorship: &Sponsorship,
+    ) -> Result<Sponsored, StellarError> {
+        let (operations, planned) = self
+            .sponsorship_operations(sponsor.public_key(), account.public_key(), sponsorship)
+            .await?;
+        let record = self
+            .sign_and_submit_with(sponsor, &[account], operations)
+            .await?;
+        Ok(Sponsored::new(record, account.public_key(), planned))
+    }
+
+    /// How many base reserves `sponsor` holds for entries that are not its
+    /// own: the accounts and trustlines it sponsors, and the claimants of
+    /// the claimable balances it created.
+    pub async fn sponsored_reserves(&self, sponsor: &str) -> Result<u32, StellarError> {
+        Ok(self.account(sponsor).await?.num_sponsoring)
+    }
+
+    /// The accounts `sponsor` sponsors the account or a trustline of;
+    /// [`Account::sponsored_by`] tells which.
+    pub async fn sponsored_accounts(
+        &self,
+        sponsor: &str,
+        query: &PageQuery,
+    ) -> Result<Page<Account>, StellarError> {
+        let path = format!("/accounts?sponsor={sponsor}");
+        self.page(&path, query, sponsor).await
+    }
+
+    /// Stops `sponsor` sponsoring `entry`, once it is found to: its owner
+    /// holds the reserve from then on, and the revocation fails with
+    /// `op_low_reserve` if it cannot.
+    pub async fn revoke_sponsorship(
+        &self,
+        sponsor: &dyn Signer,
+        entry: &SponsoredEntry,
+    ) -> Result<TransactionRecord, StellarError> {
+        let operation = self.revoke_operation(sponsor.public_key(), entry).await?;
+        self.sign_and_submit(sponsor, vec![operation]).await
+    }
+
+    /// The sandwich for [`sponsor_account`](Self::sponsor_account), once
+    /// the sponsorship is checked against the account.
+    pub(crate) async fn sponsorship_operations(
+        &self,
+        sponsor: &str,
+        account: &str,
+        sponsorship: &Sponsorship,
+    ) -> Result<(Vec<Operation>, Planned), StellarError> {
+        account_id(sponsor)?;
+        let sponsored_id = account_id(account)?;
+        if sponsor == account {
+            return Err(StellarError::InvalidSponsorship(
+                "an account cannot sponsor itself".into(),
+            ));
+        }
+        if sponsorship.starting_balance < Amount::ZERO {
+            return Err(StellarError::InvalidAmount(sponsorship.starting_balance));
+        }
+        let existing = match self.account(account).await {
+            Ok(existing) => Some(existing),
+            Err(StellarError::NotFound(_)) => None,
+            Err(err) => return Err(err),
+        };
+        if existing.is_some() && sponsorship.starting_balance.is_positive() {
+            return Err(StellarError::InvalidSponsorship(format!(
+                "{account} already exists, so it has no starting balance"
+            )));
+        }
+        // Begin, create, a trust per line, end: all in one transaction.
+        let count = sponsorship.trustlines.len() + 2 + usize::from(existing.is_none());
+        if count > MAX_OPERATIONS {
+            return Err(StellarError::InvalidSponsorship(format!(
+                "{} trustlines take {count} operations, more than a transaction's {MAX_OPERATIONS}",
+                sponsorship.trustlines.len()
+            )));
+        }
+        let mut lines = Vec::new();
+        for (i, asset) in sponsorship.trustlines.iter().enumerate() {
+            if sponsorship.trustlines[..i].contains(asset) {
+                return Err(StellarError::InvalidSponsorship(format!(
+                    "the trustline to {asset} is listed twice"
+                )));
+            }
+            if asset.issuer() == Some(account) {
+                return Err(StellarError::InvalidSponsorship(format!(
+                    "{account} issues {asset} and needs no trustline to it"
+                )));
+            }
+            if let Some(existing) = &existing {
+                if existing.balance_of(asset).is_some() {
+                    return Err(StellarError::InvalidSponsorship(format!(
+                        "{account} already has a trustline to {asset}"
+                    )));
+                }
+            }
+            lines.push(asset.to_trust_line()?);
+        }
+        if existing.is_some() && lines.is_empty() {
+            return Err(StellarError::InvalidSponsorship(format!(
+                "{account} already exists and no trustlines were asked for"
+            )));
+        }
+
+        let as_account = Some(sponsored_id.clone().into());
+        let mut operations = vec![Operation {
+            source_account: None,
+            body: OperationBody::BeginSponsoringFutureReserves(BeginSponsoringFutureReservesOp {
+                sponsored_id: sponsored_id.clone(),
+            }),
+        }];
+        if existing.is_none() {
+            operations.push(Operation {
+                source_account: None,
+                body: OperationBody::CreateAccount(CreateAccountOp {
+                    destination: sponsored_id,
+                    starting_balance: sponsorship.starting_balance.stroops(),
+                }),
+            });
+        }
+        for line in lines {
+            operations.push(Operation {
+                source_account: as_account.clone(),
+                body: OperationBody::ChangeTrust(ChangeTrustOp {
+                    line,
+                    limit: Amount::MAX.stroops(),
+                }),
+            });
+        }
+        operations.push(Operation {
+            source_account: as_account,
+            body: OperationBody::EndSponsoringFutureReserves,
+        });
+        let planned = Planned {
+            created_account: existing.is_none(),
+            trustlines: sponsorship.trustlines.clone(),
+        };
+        Ok((operations, planned))
+    }
+
+    /// The `revoke_sponsorship` operation for
+    /// [`revoke_sponsorship`](Self::revoke_sponsorship), once `sponsor` is
+    /// found to sponsor `entry`.
+    pub(crate) async fn revoke_operation(
+        &self,
+        sponsor: &str,
+        entry: &SponsoredEntry,
+    ) -> Result<Operation, StellarError> {
+        let key = entry.to_ledger_key()?;
+        let account = self.account(entry.account()).await?;
+        let current = match entry {
+            SponsoredEntry::Account(_) => account.sponsor.as_deref(),
+            SponsoredEntry::Trustline { asset, .. } => account
+                .balance_of(asset)
+                .ok_or_else(|| StellarError::NoTrustline {
+                    account: account.id.clone(),
+                    asset: asset.clone(),
+                })?
+                .sponsor
+                .as_deref(),
+        };
+        if current != Some(sponsor) {
+            return Err(StellarError::NotSponsor {
+                sponsor: sponsor.to_string(),
+                entry: entry.to_string(),
+            });
+        }
+        Ok(Operation {
+            source_account: None,
+            body: OperationBody::RevokeSponsorship(RevokeSponsorshipOp::LedgerEntry(key)),
+        })
+    }
+}
@@ -0,0 +1,197 @@
+//! Trustlines, and payments in issued assets.
+
+use stellar_xdr::curr::{ChangeTrustOp, Operation, OperationBody, PaymentOp};
+use stellrflow_amount::Amount;
+use stellrflow_signer::Signer;
+
+use crate::asset::Asset;
+use crate::error::StellarError;
+use crate::horizon::Horizon;
+use crate::resources::{Account, Balance, TransactionRecord};
+use crate::send::{account_id, Sent};
+
+impl Horizon {
+    /// Sends `amount` of `asset` from `source`'s account to `destination`.
+    /// XLM is sent as by [`send_native`](Self::send_native); an issued
+    /// asset is first checked with [`check_payment`](Self::check_payment).
+    pub async fn send_asset(
+        &self,
+        source: &dyn Signer,
+        destination: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<Sent, StellarError> {
+        let (operation, created_account) = self
+            .asset_payment(source.public_key(), destination, asset, amount)
+            .await?;
+        let record = self.sign_and_submit(source, vec![operation]).await?;
+        Ok(Sent::new(record, created_account))
+    }
+
+    /// Adds a trustline from `source`'s account to `asset`, or changes its
+    /// limit: `None` trusts as much as a trustline can hold, and a limit of
+    /// zero removes it.
+    pub async fn change_trust(
+        &self,
+        source: &dyn Signer,
+        asset: &Asset,
+        limit: Option<Amount>,
+    ) -> Result<TransactionRecord, StellarError> {
+        let operation = self.trust(source.public_key(), asset, limit).await?;
+        self.sign_and_submit(source, vec![operation]).await
+    }
+
+    /// Checks, before anything is signed, that `source` can send `amount`
+    /// of an issued asset and `destination` can receive it: each needs an
+    /// authorized trustline (the issuer needs none), the source enough of
+    /// the asset and the destination enough room under its limit. Payments
+    /// in XLM have nothing to check beyond a positive amount.
+    pub async fn check_payment(
+        &self,
+        source: &str,
+        destination: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<(), StellarError> {
+        if !amount.is_positive() {
+            return Err(StellarError::InvalidAmount(amount));
+        }
+        self.check_sender(source, asset, amount).await?;
+        self.check_receiver(destination, asset, amount).await
+    }
+
+    /// The sending half of [`check_payment`](Self::check_payment): `source`
+    /// holds at least `amount` of `asset` on an authorized trustline.
+    pub(crate) async fn check_sender(
+        &self,
+        source: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<(), StellarError> {
+        if asset.is_native() || asset.issuer() == Some(source) {
+            return Ok(());
+        }
+        let account = self.account(source).await?;
+        let line = trustline(&account, asset)?;
+        if line.balance < amount {
+            return Err(StellarError::Underfunded {
+                account: source.to_string(),
+                asset: asset.clone(),
+                balance: line.balance,
+            });
+        }
+        Ok(())
+    }
+
+    /// The receiving half of [`check_payment`](Self::check_payment):
+    /// `destination` has an authorized trustline to `asset` with room for
+    /// `amount` more.
+    pub(crate) async fn check_receiver(
+        &self,
+        destination: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<(), StellarError> {
+        if asset.is_native() || asset.issuer() == Some(destination) {
+            return Ok(());
+        }
+        let account = self.account(destination).await?;
+        let line = trustline(&account, asset)?;
+        let room = line
+            .limit
+            .unwrap_or(Amount::MAX)
+            .checked_sub(line.balance)
+            .unwrap_or(Amount::ZERO);
+        if room < amount {
+            return Err(StellarError::LineFull {
+                account: destination.to_string(),
+                asset: asset.clone(),
+                room,
+            });
+        }
+        Ok(())
+    }
+
+    /// The operation that sends `amount` of `asset` to `destination`, and
+    /// whether it creates the account.
+    pub(crate) async fn asset_payment(
+        &self,
+        source: &str,
+        destination: &str,
+        asset: &Asset,
+        amount: Amount,
+    ) -> Result<(Operation, bool), StellarError> {
+        if asset.is_native() {
+            return self.native_payment(destination, amount).await;
+        }
+        let destination_id = account_id(destination)?;
+        self.check_payment(source, destination, asset, amount)
+            .await?;
+        let operation = Operation {
+            source_account: None,
+            body: OperationBody::Payment(PaymentOp {
+                destination: destination_id.into(),
+                asset: asset.to_xdr()?,
+                amount: amount.stroops(),
+            }),
+        };
+        Ok((operation, false))
+    }
+
+    /// The `change_trust` operation for [`change_trust`](Self::change_trust),
+    /// once the limit is checked against the trustline's balance.
+    pub(crate) async fn trust(
+        &self,
+        source: &str,
+        asset: &Asset,
+        limit: Option<Amount>,
+    ) -> Result<Operation, StellarError> {
+        let line = asset.to_trust_line()?;
+        let limit = limit.unwrap_or(Amount::MAX);
+        if limit < Amount::ZERO {
+            return Err(StellarError::InvalidAmount(limit));
+        }
+        let account = self.account(source).await?;
+        match account.balance_of(asset) {
+            None if limit == Amount::ZERO => {
+                return Err(StellarError::NoTrustline {
+                    account: source.to_string(),
+                    asset: asset.clone(),
+                })
+            }
+            Some(held) if held.balance > limit => {
+                return Err(StellarError::LimitBelowBalance {
+                    asset: asset.clone(),
+                    balance: held.balance,
+                    limit,
+                })
+            }
+            _ => {}
+        }
+        Ok(Operation {
+            source_account: None,
+            body: OperationBody::ChangeTrust(ChangeTrustOp {
+                line,
+                limit: limit.stroops(),
+            }),
+        })
+    }
+}
+
+/// `account`'s trustline to `asset` among its balances, if the issuer lets
+/// it use the asset.
+fn trustline<'a>(account: &'a Account, asset: &Asset) -> Result<&'a Balance, StellarError> {
+    let line = account
+        .balance_of(asset)
+        .ok_or_else(|| StellarError::NoTrustline {
+            account: account.id.clone(),
+            asset: asset.clone(),
+        })?;
+    if line.is_authorized == Some(false) {
+        return Err(StellarError::NotAuthorized {
+            account: account.id.clone(),
+            asset: asset.clone(),
+        });
+    }
+    Ok(line)
+}
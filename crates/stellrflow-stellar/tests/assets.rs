@@ -0,0 +1,210 @@
+mod common;
+
+use stellrflow_amount::Amount;
+use stellrflow_signer::{LocalSigner, Signer};
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{Asset, StellarError, SubmissionQueue};
+
+use common::{signer, xlm};
+
+fn usdc(issuer: &LocalSigner) -> Asset {
+    Asset::issued("USDC", issuer.public_key()).unwrap()
+}
+
+#[test]
+fn assets_are_named_code_colon_issuer() {
+    let issuer = signer(1).public_key().to_string();
+    assert_eq!("native".parse::<Asset>().unwrap(), Asset::Native);
+    assert_eq!(" XLM ".parse::<Asset>().unwrap(), Asset::Native);
+
+    let usdc: Asset = format!("USDC:{issuer}").parse().unwrap();
+    assert_eq!(usdc.code(), "USDC");
+    assert_eq!(usdc.issuer(), Some(issuer.as_str()));
+    assert_eq!(usdc.to_string(), format!("USDC:{issuer}"));
+    let long: Asset = format!("STELLRFLOW12:{issuer}").parse().unwrap();
+    assert_eq!(
+        serde_json::to_value(&long).unwrap(),
+        format!("STELLRFLOW12:{issuer}")
+    );
+
+    for bad in [
+        "USDC".to_string(),
+        format!(":{issuer}"),
+        format!("USD-C:{issuer}"),
+        format!("STELLRFLOW123:{issuer}"),
+        "USDC:GABC".to_string(),
+    ] {
+        assert!(
+            matches!(bad.parse::<Asset>(), Err(StellarError::InvalidAsset(_))),
+            "{bad}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn trustlines_are_added_limited_and_removed() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice) = (signer(1), signer(2));
+    horizon.fund(issuer.public_key(), xlm("100"));
+    horizon.fund(alice.public_key(), xlm("10"));
+    let usdc = usdc(&issuer);
+
+    client.change_trust(&alice, &usdc, None).await.unwrap();
+    let account = client.account(alice.public_key()).await.unwrap();
+    let line = account.balance_of(&usdc).unwrap();
+    assert_eq!(
+        (line.balance, line.limit),
+        (Amount::ZERO, Some(Amount::MAX))
+    );
+    assert_eq!(line.is_authorized, Some(true));
+    assert_eq!(account.subentry_count, 1);
+
+    // The issuer creates the asset by sending it.
+    client
+        .send_asset(&issuer, alice.public_key(), &usdc, xlm("50"))
+        .await
+        .unwrap();
+    assert_eq!(
+        horizon.asset_balance(alice.public_key(), &usdc),
+        Some(xlm("50"))
+    );
+
+    let err = client
+        .change_trust(&alice, &usdc, Some(xlm("20")))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, StellarError::LimitBelowBalance { balance, .. } if balance == xlm("50")),
+        "{err}"
+    );
+    client
+        .change_trust(&alice, &usdc, Some(xlm("60")))
+        .await
+        .unwrap();
+    let account = client.account(alice.public_key()).await.unwrap();
+    assert_eq!(account.balance_of(&usdc).unwrap().limit, Some(xlm("60")));
+
+    // Removing it takes an empty trustline; sending the asset back to its
+    // issuer destroys it.
+    let err = client
+        .change_trust(&alice, &usdc, Some(Amount::ZERO))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, StellarError::LimitBelowBalance { .. }),
+        "{err}"
+    );
+    client
+        .send_asset(&alice, issuer.public_key(), &usdc, xlm("50"))
+        .await
+        .unwrap();
+    client
+        .change_trust(&alice, &usdc, Some(Amount::ZERO))
+        .await
+        .unwrap();
+    assert_eq!(horizon.asset_balance(alice.public_key(), &usdc), None);
+    let err = client
+        .change_trust(&alice, &usdc, Some(Amount::ZERO))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NoTrustline { .. }), "{err}");
+
+    // XLM needs no trustline, and each one takes a base reserve.
+    let err = client
+        .change_trust(&alice, &Asset::Native, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidAsset(_)), "{err}");
+    let poor = signer(3);
+    horizon.fund(poor.public_key(), xlm("1"));
+    let err = client.change_trust(&poor, &usdc, None).await.unwrap_err();
+    assert_eq!(err.result_codes().unwrap().operations, ["op_low_reserve"]);
+}
+
+#[tokio::test]
+async fn payments_are_checked_before_signing() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice, bob) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &alice, &bob] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = usdc(&issuer);
+    client.change_trust(&alice, &usdc, None).await.unwrap();
+    client
+        .send_asset(&issuer, alice.public_key(), &usdc, xlm("30"))
+        .await
+        .unwrap();
+    let sequence = horizon.sequence(alice.public_key());
+
+    let err = client
+        .send_asset(&alice, bob.public_key(), &usdc, xlm("10"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, StellarError::NoTrustline { account, .. } if account == bob.public_key()),
+        "{err}"
+    );
+    // Nothing was submitted.
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+
+    // An issuer that requires authorization approves each trustline.
+    horizon.set_auth_required(issuer.public_key(), true);
+    client
+        .change_trust(&bob, &usdc, Some(xlm("15")))
+        .await
+        .unwrap();
+    let err = client
+        .send_asset(&alice, bob.public_key(), &usdc, xlm("10"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NotAuthorized { .. }), "{err}");
+    horizon.set_authorized(bob.public_key(), &usdc, true);
+
+    let err = client
+        .send_asset(&alice, bob.public_key(), &usdc, xlm("20"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, StellarError::LineFull { room, .. } if room == xlm("15")),
+        "{err}"
+    );
+    let err = client
+        .send_asset(&bob, alice.public_key(), &usdc, xlm("1"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::Underfunded { .. }), "{err}");
+
+    let queue = SubmissionQueue::new(horizon.client());
+    let sent = queue
+        .send_asset(&alice, bob.public_key(), &usdc, xlm("10"))
+        .await
+        .unwrap();
+    assert!(!sent.created_account);
+    assert_eq!(
+        horizon.asset_balance(bob.public_key(), &usdc),
+        Some(xlm("10"))
+    );
+    assert_eq!(
+        horizon.asset_balance(alice.public_key(), &usdc),
+        Some(xlm("20"))
+    );
+
+    // A revoked trustline can no longer send.
+    horizon.set_authorized(alice.public_key(), &usdc, false);
+    let err = queue
+        .send_asset(&alice, bob.public_key(), &usdc, xlm("1"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NotAuthorized { .. }), "{err}");
+
+    // XLM is sent as by `send_native`, creating accounts as needed.
+    let carol = signer(4);
+    let sent = queue
+        .send_asset(&alice, carol.public_key(), &Asset::Native, xlm("2"))
+        .await
+        .unwrap();
+    assert!(sent.created_account);
+    assert_eq!(horizon.balance(carol.public_key()), Some(xlm("2")));
+}
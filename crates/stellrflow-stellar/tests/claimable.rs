@@ -0,0 +1,265 @@
+mod common;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use stellar_xdr::curr::{ClaimPredicate, Limits, WriteXdr};
+use stellrflow_amount::Amount;
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{
+    Asset, ClaimableBalance, Claimant, Predicate, StellarError, SubmissionQueue,
+};
+
+use common::{signer, xlm};
+
+/// 2026-01-01T00:00:00Z.
+const NEW_YEAR: i64 = 1_767_225_600;
+
+fn base64(predicate: &Predicate) -> String {
+    predicate
+        .to_xdr()
+        .unwrap()
+        .to_xdr_base64(Limits::none())
+        .unwrap()
+}
+
+#[test]
+fn predicates_encode_as_xdr() {
+    let new_year = UNIX_EPOCH + Duration::from_secs(NEW_YEAR as u64);
+    let hour = Duration::from_secs(3600);
+    // Worked out by hand from the XDR definitions.
+    let expected = [
+        (Predicate::Unconditional, "AAAAAA=="),
+        (Predicate::within(hour), "AAAABQAAAAAAAA4Q"),
+        (Predicate::before(new_year), "AAAABAAAAABpVbkA"),
+        (Predicate::after_delay(hour), "AAAAAwAAAAEAAAAFAAAAAAAADhA="),
+        (
+            Predicate::after_delay(hour).and(Predicate::before(new_year)),
+            "AAAAAQAAAAIAAAADAAAAAQAAAAUAAAAAAAAOEAAAAAQAAAAAaVW5AA==",
+        ),
+        (
+            Predicate::Unconditional.or(Predicate::after(new_year)),
+            "AAAAAgAAAAIAAAAAAAAAAwAAAAEAAAAEAAAAAGlVuQA=",
+        ),
+    ];
+    for (predicate, encoded) in expected {
+        assert_eq!(base64(&predicate), encoded, "{predicate:?}");
+        let decoded = Predicate::from_xdr(&predicate.to_xdr().unwrap()).unwrap();
+        assert_eq!(decoded, predicate);
+    }
+
+    // Four deep is the most the network takes.
+    let deep = !Predicate::within(hour)
+        .and(Predicate::Unconditional)
+        .or(Predicate::Unconditional);
+    assert_eq!(deep.depth(), 4);
+    assert!(deep.to_xdr().is_ok());
+    let err = (!deep).to_xdr().unwrap_err();
+    assert!(matches!(err, StellarError::InvalidPredicate(_)), "{err}");
+    let err = Predicate::BeforeRelative(-1).to_xdr().unwrap_err();
+    assert!(matches!(err, StellarError::InvalidPredicate(_)), "{err}");
+    let err = Predicate::from_xdr(&ClaimPredicate::Not(None)).unwrap_err();
+    assert!(matches!(err, StellarError::InvalidPredicate(_)), "{err}");
+}
+
+#[test]
+fn predicates_decide_when_a_claim_is_allowed() {
+    let created = NEW_YEAR;
+    let window = Predicate::after_delay(Duration::from_secs(60))
+        .and(Predicate::within(Duration::from_secs(3600)));
+    assert!(!window.allows(created, created + 59));
+    assert!(window.allows(created, created + 60));
+    assert!(!window.allows(created, created + 3600));
+
+    // On the ledger, relative times become absolute.
+    let resolved = window.resolve(created);
+    assert_eq!(
+        resolved,
+        Predicate::Not(Box::new(Predicate::BeforeAbsolute(created + 60)))
+            .and(Predicate::BeforeAbsolute(created + 3600))
+    );
+    assert!(resolved.allows(0, created + 60));
+
+    let at = UNIX_EPOCH + Duration::from_secs(NEW_YEAR as u64);
+    assert!(Predicate::after(at).allows(0, NEW_YEAR));
+    assert!(!Predicate::before(at).allows(0, NEW_YEAR));
+}
+
+#[test]
+fn horizon_balances_parse() {
+    let balance: ClaimableBalance = serde_json::from_value(serde_json::json!({
+        "id": "00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be",
+        "asset": "native",
+        "amount": "10.0000000",
+        "sponsor": "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN",
+        "last_modified_ledger": 28411995,
+        "claimants": [
+            {
+                "destination": "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN",
+                "predicate": { "not": {
+                    "abs_before": "2026-01-01T00:00:00Z",
+                    "abs_before_epoch": "1767225600",
+                } },
+            },
+            {
+                "destination": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H",
+                "predicate": { "or": [
+                    { "unconditional": true },
+                    { "rel_before": "3600" },
+                ] },
+            },
+        ],
+        "flags": { "clawback_enabled": false },
+        "paging_token": "28411995-00000000da0d57da7d4850e7fc10d2a9d0ebc731f7afb40574c03395b17d49149b91f5be",
+    }))
+    .unwrap();
+    assert_eq!(balance.asset, Asset::Native);
+    assert_eq!(balance.amount, xlm("10"));
+    assert_eq!(
+        balance.claimants[0].predicate,
+        !Predicate::BeforeAbsolute(NEW_YEAR)
+    );
+    assert_eq!(
+        balance.claimants[1].predicate,
+        Predicate::Unconditional.or(Predicate::BeforeRelative(3600))
+    );
+    let issuer = &balance.claimants[0].destination;
+    let at = UNIX_EPOCH + Duration::from_secs(NEW_YEAR as u64);
+    assert!(!balance.claimable_by(issuer, at - Duration::from_secs(1)));
+    assert!(balance.claimable_by(issuer, at));
+    assert!(!balance.claimable_by(signer(1).public_key(), at));
+}
+
+#[tokio::test]
+async fn balances_wait_until_claimed() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (alice, bob) = (signer(2), signer(3));
+    horizon.fund(alice.public_key(), xlm("100"));
+
+    // Bob has no account yet; Alice can take hers back after a day.
+    let claimants = [
+        Claimant::unconditional(bob.public_key()),
+        Claimant::new(
+            alice.public_key(),
+            Predicate::after_delay(Duration::from_secs(86_400)),
+        ),
+    ];
+    let queue = SubmissionQueue::new(horizon.client());
+    let created = queue
+        .create_claimable_balance(&alice, &Asset::Native, xlm("20"), &claimants)
+        .await
+        .unwrap();
+    assert_eq!(created.balance_id.len(), 72);
+    assert!(created.balance_id.starts_with("00000000"));
+    // The balance leaves the account, and holds a reserve per claimant.
+    let account = client.account(alice.public_key()).await.unwrap();
+    assert_eq!(account.num_sponsoring, 2);
+    assert_eq!(horizon.balance(alice.public_key()), Some(xlm("79.99999")));
+
+    let listed = client
+        .claimable_balances(bob.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(listed.records.len(), 1);
+    let balance = &listed.records[0];
+    assert_eq!(balance.id, created.balance_id);
+    assert_eq!(balance.amount, xlm("20"));
+    assert_eq!(balance.sponsor.as_deref(), Some(alice.public_key()));
+    assert!(balance.claimable_by(bob.public_key(), SystemTime::now()));
+    assert!(!balance.claimable_by(alice.public_key(), SystemTime::now()));
+    let Predicate::Not(inner) = &balance.claimants[1].predicate else {
+        panic!("{:?}", balance.claimants[1].predicate);
+    };
+    assert!(matches!(**inner, Predicate::BeforeAbsolute(_)));
+
+    // Once funded, Bob claims it.
+    horizon.fund(bob.public_key(), xlm("5"));
+    let claimed = client
+        .claim_balance(&bob, &created.balance_id)
+        .await
+        .unwrap();
+    assert_eq!((claimed.asset, claimed.amount), (Asset::Native, xlm("20")));
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("24.99999")));
+    let listed = client
+        .claimable_balances(bob.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert!(listed.records.is_empty());
+    let account = client.account(alice.public_key()).await.unwrap();
+    assert_eq!(account.num_sponsoring, 0);
+    let err = client
+        .claimable_balance(&created.balance_id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NotFound(_)), "{err}");
+}
+
+#[tokio::test]
+async fn balances_are_checked_before_signing() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice, bob) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &alice, &bob] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let sequence = horizon.sequence(alice.public_key());
+
+    let to_bob = [Claimant::unconditional(bob.public_key())];
+    let err = client
+        .create_claimable_balance(&alice, &Asset::Native, Amount::ZERO, &to_bob)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidAmount(_)), "{err}");
+    let err = client
+        .create_claimable_balance(&alice, &Asset::Native, xlm("1"), &[])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidClaimants(_)), "{err}");
+    let twice = [to_bob[0].clone(), to_bob[0].clone()];
+    let err = client
+        .create_claimable_balance(&alice, &Asset::Native, xlm("1"), &twice)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidClaimants(_)), "{err}");
+    let err = client
+        .create_claimable_balance(&alice, &usdc, xlm("1"), &to_bob)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NoTrustline { .. }), "{err}");
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+
+    // Bob cannot claim before the hour is up, nor Alice at all; an
+    // issued asset needs a trustline to claim.
+    let later = [Claimant::new(
+        bob.public_key(),
+        Predicate::after_delay(Duration::from_secs(3600)),
+    )];
+    let created = client
+        .create_claimable_balance(&alice, &Asset::Native, xlm("1"), &later)
+        .await
+        .unwrap();
+    for claimer in [&bob, &alice] {
+        let err = client
+            .claim_balance(claimer, &created.balance_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StellarError::CannotClaim { .. }), "{err}");
+    }
+    let created = client
+        .create_claimable_balance(&issuer, &usdc, xlm("1"), &to_bob)
+        .await
+        .unwrap();
+    let err = client
+        .claim_balance(&bob, &created.balance_id)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NoTrustline { .. }), "{err}");
+
+    let err = client.claim_balance(&bob, "nonsense").await.unwrap_err();
+    assert!(matches!(err, StellarError::InvalidBalanceId(_)), "{err}");
+    let unknown = format!("00000000{}", "ab".repeat(32));
+    let err = client.claim_balance(&bob, &unknown).await.unwrap_err();
+    assert!(matches!(err, StellarError::NotFound(_)), "{err}");
+}
@@ -0,0 +1,14 @@
+//! Fixtures shared by the tests against a `MockHorizon`.
+
+#![allow(dead_code)]
+
+use stellrflow_amount::Amount;
+use stellrflow_signer::LocalSigner;
+
+pub fn xlm(amount: &str) -> Amount {
+    amount.parse().unwrap()
+}
+
+pub fn signer(n: u8) -> LocalSigner {
+    LocalSigner::from_seed([n; 32])
+}
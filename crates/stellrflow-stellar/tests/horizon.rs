@@ -0,0 +1,269 @@
+mod common;
+
+use serde_json::json;
+use stellrflow_signer::{Envelope, Signer, TESTNET_PASSPHRASE};
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{
+    Account, FeeDistribution, FeeStats, Horizon, Order, PageQuery, StellarError, TransactionRecord,
+};
+
+use common::{signer, xlm};
+
+#[tokio::test]
+async fn send_native_pays_existing_accounts_and_creates_new_ones() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (alice, bob, carol) = (signer(1), signer(2), signer(3));
+    horizon.fund(alice.public_key(), xlm("100"));
+    horizon.fund(bob.public_key(), xlm("10"));
+    let start = horizon.sequence(alice.public_key()).unwrap();
+
+    let paid = client
+        .send_native(&alice, bob.public_key(), xlm("5"))
+        .await
+        .unwrap();
+    assert!(!paid.created_account);
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("15")));
+
+    let created = client
+        .send_native(&alice, carol.public_key(), xlm("2.5"))
+        .await
+        .unwrap();
+    assert!(created.created_account);
+    assert!(created.ledger > paid.ledger);
+    assert_eq!(horizon.balance(carol.public_key()), Some(xlm("2.5")));
+
+    // Two payments and two base fees of 100 stroops.
+    let account = client.account(alice.public_key()).await.unwrap();
+    assert_eq!(account.native_balance(), xlm("92.4999800"));
+    assert_eq!(account.sequence, start + 2);
+
+    // A new account needs the minimum balance; nothing is submitted.
+    let err = client
+        .send_native(&alice, signer(4).public_key(), xlm("0.9999999"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::BelowMinimum));
+    assert_eq!(
+        err.to_string(),
+        "Minimum 1 XLM required to create new account"
+    );
+    assert_eq!(horizon.sequence(alice.public_key()), Some(start + 2));
+
+    // Payments come back newest first, a page at a time.
+    let page = client
+        .account_payments(alice.public_key(), &PageQuery::latest(1))
+        .await
+        .unwrap();
+    let newest = &page.records[0];
+    assert_eq!(newest.kind, "create_account");
+    assert_eq!(newest.transaction_hash, created.hash);
+    assert_eq!(newest.detail("starting_balance"), Some("2.5000000"));
+    let next = page.next.unwrap();
+    assert_eq!(next.order, Some(Order::Desc));
+    let page = client
+        .account_payments(alice.public_key(), &next)
+        .await
+        .unwrap();
+    assert_eq!(page.records[0].kind, "payment");
+    assert_eq!(page.records[0].detail("to"), Some(bob.public_key()));
+    assert_eq!(page.records[0].detail("amount"), Some("5.0000000"));
+    let end = client
+        .account_payments(alice.public_key(), &page.next.unwrap())
+        .await
+        .unwrap();
+    assert!(end.records.is_empty());
+    assert_eq!(end.next, None);
+
+    let transactions = client
+        .account_transactions(bob.public_key(), &PageQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(transactions.records.len(), 1);
+    let record = client.transaction(&paid.hash).await.unwrap();
+    assert_eq!(record, transactions.records[0]);
+    assert!(record.successful);
+    assert_eq!(record.source_account, alice.public_key());
+    assert_eq!(record.fee_charged, 100);
+    let operations = client
+        .account_operations(carol.public_key(), &PageQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        operations.records[0].detail("funder"),
+        Some(alice.public_key())
+    );
+}
+
+#[tokio::test]
+async fn rejected_transactions_carry_horizons_result_codes() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (alice, bob) = (signer(1), signer(2));
+    horizon.fund(alice.public_key(), xlm("10"));
+    horizon.fund(bob.public_key(), xlm("1"));
+
+    // Alice must keep two base reserves.
+    let err = client
+        .send_native(&alice, bob.public_key(), xlm("9.5"))
+        .await
+        .unwrap_err();
+    let codes = err.result_codes().unwrap();
+    assert_eq!(codes.transaction, "tx_failed");
+    assert_eq!(codes.operations, ["op_underfunded"]);
+    assert_eq!(
+        err.to_string(),
+        r#"{"transaction":"tx_failed","operations":["op_underfunded"]}"#
+    );
+    // The fee and sequence number were still spent.
+    assert_eq!(horizon.balance(alice.public_key()), Some(xlm("9.9999900")));
+
+    // Replaying a transaction is caught by its sequence number.
+    let sent = client
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    let record = client.transaction(&sent.hash).await.unwrap();
+    let replay = Envelope::from_xdr(&record.envelope_xdr, TESTNET_PASSPHRASE).unwrap();
+    let err = client.submit(&replay).await.unwrap_err();
+    assert_eq!(err.result_codes().unwrap().transaction, "tx_bad_seq");
+    let StellarError::Horizon(problem) = err else {
+        panic!("expected a problem document");
+    };
+    assert_eq!(problem.status, 400);
+    assert_eq!(problem.title, "Transaction Failed");
+
+    // An account that was never funded cannot send.
+    let err = client
+        .send_native(&signer(9), bob.public_key(), xlm("1"))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("account {} not found", signer(9).public_key())
+    );
+
+    for (destination, amount, error) in [
+        ("GNOTANACCOUNT", "1", "invalid account GNOTANACCOUNT"),
+        (
+            bob.public_key(),
+            "0",
+            "invalid amount 0: payments must be positive",
+        ),
+        (
+            bob.public_key(),
+            "-1",
+            "invalid amount -1: payments must be positive",
+        ),
+    ] {
+        let err = client
+            .send_native(&alice, destination, xlm(amount))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), error);
+    }
+}
+
+#[tokio::test]
+async fn accounts_transactions_and_fee_stats() {
+    let horizon = MockHorizon::start().await;
+    let client = Horizon::new(format!("{}/", horizon.url()), TESTNET_PASSPHRASE);
+    let alice = signer(1);
+
+    assert!(!client.account_exists(alice.public_key()).await.unwrap());
+    let missing = "0".repeat(64);
+    assert!(matches!(
+        client.transaction(&missing).await,
+        Err(StellarError::NotFound(what)) if what == format!("transaction {missing}")
+    ));
+    horizon.fund(alice.public_key(), xlm("1"));
+    assert!(client.account_exists(alice.public_key()).await.unwrap());
+    let balances = client.balances(alice.public_key()).await.unwrap();
+    assert_eq!(balances.len(), 1);
+    assert!(balances[0].is_native());
+
+    let stats = client.fee_stats().await.unwrap();
+    assert_eq!(stats.last_ledger_base_fee, 100);
+    assert_eq!(stats.fee_charged.p50, 100);
+    let busy = FeeStats {
+        ledger_capacity_usage: 0.97,
+        ..stats.clone()
+    };
+    horizon.set_fee_stats(FeeStats {
+        max_fee: FeeDistribution {
+            p90: 5_000,
+            ..busy.max_fee
+        },
+        ..busy
+    });
+    let stats = client.fee_stats().await.unwrap();
+    assert_eq!(stats.ledger_capacity_usage, 0.97);
+    assert_eq!(stats.max_fee.p90, 5_000);
+
+    let testnet = Horizon::for_network("testnet");
+    assert_eq!(testnet.url(), "https://horizon-testnet.stellar.org");
+    assert_eq!(testnet.network_passphrase(), TESTNET_PASSPHRASE);
+    assert_eq!(
+        Horizon::for_network("mainnet").network_passphrase(),
+        "Public Global Stellar Network ; September 2015"
+    );
+}
+
+#[test]
+fn resources_read_horizons_string_numbers() {
+    // Trimmed from horizon-testnet.stellar.org.
+    let account: Account = serde_json::from_value(json!({
+        "id": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H",
+        "account_id": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H",
+        "sequence": "4300967772160",
+        "subentry_count": 1,
+        "last_modified_ledger": 1001,
+        "thresholds": { "low_threshold": 0, "med_threshold": 0, "high_threshold": 0 },
+        "flags": { "auth_required": false, "auth_revocable": false },
+        "balances": [
+            {
+                "balance": "120.0000000",
+                "limit": "922337203685.4775807",
+                "buying_liabilities": "0.0000000",
+                "selling_liabilities": "0.0000000",
+                "asset_type": "credit_alphanum4",
+                "asset_code": "USDC",
+                "asset_issuer": "GBBD47IF6LWK7P7MDEVSCWR7DPUWV3NY3DTQEVFL4NAT4AQH3ZLLFLA5"
+            },
+            {
+                "balance": "9999.9999900",
+                "buying_liabilities": "0.0000000",
+                "selling_liabilities": "0.0000000",
+                "asset_type": "native"
+            }
+        ],
+        "signers": [{
+            "weight": 1,
+            "key": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H",
+            "type": "ed25519_public_key"
+        }],
+        "num_sponsoring": 0,
+        "num_sponsored": 0,
+        "paging_token": "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H"
+    }))
+    .unwrap();
+    assert_eq!(account.sequence, 4_300_967_772_160);
+    assert_eq!(account.native_balance(), xlm("9999.99999"));
+    let usdc = &account.balances[0];
+    assert_eq!(usdc.asset_code.as_deref(), Some("USDC"));
+    assert_eq!(usdc.limit, Some(xlm("922337203685.4775807")));
+
+    let submitted: TransactionRecord = serde_json::from_value(json!({
+        "hash": "3389e9f0f1a65f19736cacf544c2e825313e8447f569233bb8db39aa607c8889",
+        "ledger": 7840,
+        "envelope_xdr": "AAAA",
+        "result_xdr": "AAAA",
+    }))
+    .unwrap();
+    assert_eq!(submitted.ledger, 7840);
+    assert_eq!(submitted.fee_charged, 0);
+    assert_eq!(
+        serde_json::to_value(PageQuery::latest(20)).unwrap(),
+        json!({ "limit": 20, "order": "desc" })
+    );
+}
@@ -0,0 +1,273 @@
+mod common;
+
+use std::time::Duration;
+
+use serde_json::Value;
+use stellrflow_amount::Amount;
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{
+    Asset, LimitOrder, Orderbook, Price, PriceCondition, PriceSource, PriceWatch, Resolution,
+    StellarError, SubmissionQueue, TradeAggregation,
+};
+
+use common::{signer, xlm};
+
+const USDC_ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+fn price(price: &str) -> Price {
+    price.parse().unwrap()
+}
+
+fn fixture(name: &str) -> Value {
+    let text = match name {
+        "orderbook" => include_str!("fixtures/orderbook_xlm_usdc.json"),
+        "trade_aggregations" => include_str!("fixtures/trade_aggregations_xlm_usdc.json"),
+        _ => unreachable!("no fixture {name}"),
+    };
+    serde_json::from_str(text).unwrap()
+}
+
+#[test]
+fn prices_are_exact_fractions() {
+    let eighth = price("1/8");
+    assert_eq!((eighth.numerator(), eighth.denominator()), (1, 8));
+    assert_eq!(price("0.125"), eighth);
+    assert_eq!(price(" 2/16 "), eighth);
+    assert_eq!(eighth.to_string(), "0.125");
+    assert_eq!(eighth.inverse(), price("8"));
+    assert_eq!(price("1/3").to_string(), "0.3333333");
+    assert_eq!(price("2/3").to_string(), "0.6666667");
+
+    assert!(price("0.1183") > price("0.1181"));
+    assert!(price("1/3") < price("0.3333334"));
+    assert_eq!(price("0.1181").midpoint(price("0.1183")), price("0.1182"));
+    assert_eq!(Price::new(3, 0), None);
+    assert_eq!(Price::new(-1, 2), None);
+
+    for bad in ["", "0", "-1", "1/0", "0.00000001", "a/b", "1/2/3"] {
+        assert!(
+            matches!(bad.parse::<Price>(), Err(StellarError::InvalidPrice(_))),
+            "{bad}"
+        );
+    }
+
+    // Either as Horizon writes it or as text.
+    let parsed: Price = serde_json::from_str(r#"{"n": 59, "d": 500}"#).unwrap();
+    assert_eq!(parsed, price("0.118"));
+    let parsed: Price = serde_json::from_str(r#"{"N": 148, "D": 1250}"#).unwrap();
+    assert_eq!(parsed, price("0.1184"));
+    let parsed: Price = serde_json::from_str(r#""1/8""#).unwrap();
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), r#""0.125""#);
+}
+
+#[test]
+fn recorded_responses_parse() {
+    let book: Orderbook = serde_json::from_value(fixture("orderbook")).unwrap();
+    assert_eq!(book.best_bid(), Some(price("0.1181")));
+    assert_eq!(book.best_ask(), Some(price("0.1183")));
+    assert_eq!(book.mid(), Some(price("0.1182")));
+    assert_eq!(book.asks[1].amount, xlm("150000"));
+    assert_eq!(Orderbook::default().mid(), None);
+
+    let records = &fixture("trade_aggregations")["_embedded"]["records"];
+    let buckets: Vec<TradeAggregation> = serde_json::from_value(records.clone()).unwrap();
+    assert_eq!(buckets[0].timestamp, 1_760_611_860_000);
+    assert_eq!(buckets[0].trade_count, 14);
+    assert_eq!(buckets[0].base_volume, xlm("8120.4417931"));
+    assert_eq!(buckets[0].close, price("0.1184"));
+    assert_eq!(buckets[1].open, price("0.1182"));
+}
+
+#[tokio::test]
+async fn prices_come_from_the_book_or_the_last_trade() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let usdc = Asset::issued("USDC", USDC_ISSUER).unwrap();
+    assert_eq!(
+        client
+            .price(&Asset::Native, &usdc, PriceSource::Mid)
+            .await
+            .unwrap(),
+        None
+    );
+
+    horizon.set_orderbook(&Asset::Native, &usdc, fixture("orderbook"));
+    horizon.set_trade_aggregations(&Asset::Native, &usdc, fixture("trade_aggregations"));
+    let expected = [
+        (PriceSource::Bid, "0.1181"),
+        (PriceSource::Ask, "0.1183"),
+        (PriceSource::Mid, "0.1182"),
+        (PriceSource::Last, "0.1184"),
+    ];
+    for (source, expected) in expected {
+        let read = client.price(&Asset::Native, &usdc, source).await.unwrap();
+        assert_eq!(read, Some(price(expected)), "{source:?}");
+    }
+    let buckets = client
+        .trade_aggregations(&Asset::Native, &usdc, Resolution::Minute, 2)
+        .await
+        .unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0].counter_volume, xlm("960.6538402"));
+}
+
+#[tokio::test]
+async fn a_watch_waits_for_its_price() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let usdc = Asset::issued("USDC", USDC_ISSUER).unwrap();
+    horizon.set_orderbook(&Asset::Native, &usdc, fixture("orderbook"));
+
+    let watch = PriceWatch::new(
+        Asset::Native,
+        usdc.clone(),
+        PriceCondition::Above(price("0.12")),
+    )
+    .with_interval(Duration::from_millis(10));
+    assert_eq!(watch.source, PriceSource::Mid);
+    let watching = tokio::spawn(async move { client.watch_price(&watch).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!watching.is_finished());
+
+    // The market moves up past the threshold.
+    let mut book = fixture("orderbook");
+    book["bids"][0]["price_r"] = serde_json::json!({ "n": 121, "d": 1000 });
+    book["asks"][0]["price_r"] = serde_json::json!({ "n": 123, "d": 1000 });
+    horizon.set_orderbook(&Asset::Native, &usdc, book);
+    let fired = tokio::time::timeout(Duration::from_secs(5), watching)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(fired, price("0.122"));
+
+    let below = PriceCondition::Below(price("0.122"));
+    assert!(below.is_met(fired) && !below.is_met(price("0.1221")));
+}
+
+#[tokio::test]
+async fn offers_rest_on_the_book_until_cancelled() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice, bob) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &alice, &bob] {
+        horizon.fund(account.public_key(), xlm("1000"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    for account in [&alice, &bob] {
+        client.change_trust(account, &usdc, None).await.unwrap();
+    }
+    client
+        .send_asset(&issuer, bob.public_key(), &usdc, xlm("50"))
+        .await
+        .unwrap();
+
+    // Alice asks 0.125 USDC for each of 100 XLM; Bob bids 8.5 XLM for
+    // each of 20 USDC.
+    let queue = SubmissionQueue::new(horizon.client());
+    let ask = queue
+        .manage_offer(
+            &alice,
+            &LimitOrder::sell(Asset::Native, xlm("100"), usdc.clone(), price("1/8")),
+        )
+        .await
+        .unwrap();
+    let ask_id = ask.offer_id.unwrap();
+    assert_eq!(ask.amount, xlm("100"));
+    assert_eq!((ask.sold, ask.bought), (Amount::ZERO, Amount::ZERO));
+    let bid = client
+        .manage_offer(
+            &bob,
+            &LimitOrder::buy(Asset::Native, xlm("170"), usdc.clone(), price("2/17")),
+        )
+        .await
+        .unwrap();
+    assert_eq!(bid.amount, xlm("20"));
+
+    let book = client.orderbook(&Asset::Native, &usdc, 20).await.unwrap();
+    assert_eq!(book.best_ask(), Some(price("0.125")));
+    assert_eq!(book.best_bid(), Some(price("2/17")));
+    assert_eq!(book.bids[0].amount, xlm("20"));
+
+    // The offer holds a reserve and what it sells.
+    let account = client.account(alice.public_key()).await.unwrap();
+    assert_eq!(account.subentry_count, 2);
+    let native = &account.balances[0];
+    assert_eq!(native.selling_liabilities, Some(xlm("100")));
+    let offers = client
+        .account_offers(alice.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(offers.records.len(), 1);
+    let offer = &offers.records[0];
+    assert_eq!((offer.id, offer.price), (ask_id, price("0.125")));
+    assert_eq!((&offer.selling, &offer.buying), (&Asset::Native, &usdc));
+
+    // Updating keeps the id.
+    let update = LimitOrder::sell(Asset::Native, xlm("40"), usdc.clone(), price("0.13"))
+        .with_offer_id(ask_id);
+    let updated = queue.manage_offer(&alice, &update).await.unwrap();
+    assert_eq!(
+        (updated.offer_id, updated.amount),
+        (Some(ask_id), xlm("40"))
+    );
+
+    let cancelled = queue
+        .manage_offer(&alice, &LimitOrder::cancel(offer))
+        .await
+        .unwrap();
+    assert_eq!(cancelled.offer_id, None);
+    let offers = client
+        .account_offers(alice.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert!(offers.records.is_empty());
+    let book = client.orderbook(&Asset::Native, &usdc, 20).await.unwrap();
+    assert!(book.asks.is_empty());
+    assert_eq!(
+        client
+            .account(alice.public_key())
+            .await
+            .unwrap()
+            .subentry_count,
+        1
+    );
+
+    // Cancelling it again finds nothing.
+    let err = queue
+        .manage_offer(&alice, &LimitOrder::cancel(offer))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.result_codes().unwrap().operations,
+        ["op_offer_not_found"]
+    );
+}
+
+#[tokio::test]
+async fn offers_are_checked_before_signing() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice) = (signer(1), signer(2));
+    for account in [&issuer, &alice] {
+        horizon.fund(account.public_key(), xlm("1000"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let sequence = horizon.sequence(alice.public_key());
+
+    // No trustline for what it buys, then more than the account holds.
+    let order = LimitOrder::sell(Asset::Native, xlm("10"), usdc.clone(), price("0.125"));
+    let err = client.manage_offer(&alice, &order).await.unwrap_err();
+    assert!(matches!(err, StellarError::NoTrustline { .. }), "{err}");
+    client.change_trust(&alice, &usdc, None).await.unwrap();
+    let sequence = sequence.map(|sequence| sequence + 1);
+    let order = LimitOrder::buy(Asset::Native, xlm("100"), usdc.clone(), price("8"));
+    let err = client.manage_offer(&alice, &order).await.unwrap_err();
+    assert!(matches!(err, StellarError::Underfunded { .. }), "{err}");
+
+    let cancel = LimitOrder::sell(Asset::Native, Amount::ZERO, usdc, price("1"));
+    let err = client.manage_offer(&alice, &cancel).await.unwrap_err();
+    assert!(matches!(err, StellarError::InvalidAmount(_)), "{err}");
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+}
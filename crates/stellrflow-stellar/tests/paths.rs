@@ -0,0 +1,174 @@
+mod common;
+
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{Asset, PathPayment, Slippage, StellarError, Strict, SubmissionQueue};
+
+use common::{signer, xlm};
+
+#[test]
+fn slippage_is_a_percentage() {
+    let half: Slippage = "0.5".parse().unwrap();
+    assert_eq!(half.bps(), 50);
+    assert_eq!(" 0.5 % ".parse::<Slippage>().unwrap(), half);
+    assert_eq!(half.to_string(), "0.5%");
+    assert_eq!(Slippage::default().to_string(), "1%");
+    assert_eq!("2.25%".parse::<Slippage>().unwrap().bps(), 225);
+    assert_eq!("100".parse::<Slippage>().unwrap().bps(), 10_000);
+
+    // Bounds round so they never accept less, or give more, than the
+    // slippage allows.
+    assert_eq!(half.at_least(xlm("10")), xlm("9.95"));
+    assert_eq!(half.at_most(xlm("10")), xlm("10.05"));
+    assert_eq!(half.at_least(xlm("0.0000003")), xlm("0.0000002"));
+    assert_eq!(half.at_most(xlm("0.0000003")), xlm("0.0000004"));
+
+    for bad in ["", "%", "-1", "0.125", "100.01", "1e2", "one"] {
+        assert!(
+            matches!(
+                bad.parse::<Slippage>(),
+                Err(StellarError::InvalidSlippage(_))
+            ),
+            "{bad}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn strict_receive_takes_the_cheapest_path() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice, bob) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &alice, &bob] {
+        horizon.fund(account.public_key(), xlm("1000"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let eurc = Asset::issued("EURC", issuer.public_key()).unwrap();
+    client.change_trust(&bob, &usdc, None).await.unwrap();
+    // 1 XLM buys 0.125 USDC straight, or 0.14 through EURC.
+    horizon.set_price(&Asset::Native, &usdc, "0.125");
+    horizon.set_price(&Asset::Native, &eurc, "0.2");
+    horizon.set_price(&eurc, &usdc, "0.7");
+
+    let payment = PathPayment::strict_receive(Asset::Native, usdc.clone(), xlm("10"));
+    let quote = client.quote(&payment).await.unwrap();
+    assert_eq!(quote.path.path, [eurc]);
+    assert_eq!(quote.path.source_amount, xlm("71.4285715"));
+    assert_eq!(quote.limit, xlm("72.1428573"));
+    assert_eq!(quote.rate(), xlm("0.1399999"));
+    assert_eq!(quote.most_sent(), quote.limit);
+    // Selling USDC for XLM is best done straight.
+    assert_eq!(client.xlm_value(&usdc, xlm("10")).await.unwrap(), xlm("80"));
+    assert_eq!(
+        client.xlm_value(&Asset::Native, xlm("10")).await.unwrap(),
+        xlm("10")
+    );
+
+    let before = horizon.balance(alice.public_key()).unwrap();
+    let paid = client
+        .path_payment(&alice, bob.public_key(), &payment)
+        .await
+        .unwrap();
+    assert_eq!(paid.destination_amount, xlm("10"));
+    assert_eq!(paid.source_amount, xlm("71.4285715"));
+    assert_eq!(paid.rate(), xlm("0.1399999"));
+    assert_eq!(
+        horizon.asset_balance(bob.public_key(), &usdc),
+        Some(xlm("10"))
+    );
+    let spent = before
+        .checked_sub(horizon.balance(alice.public_key()).unwrap())
+        .unwrap();
+    assert_eq!(
+        spent,
+        xlm("71.4285715").checked_add(xlm("0.00001")).unwrap()
+    );
+
+    // The operation is listed with the amounts it moved.
+    let operations = client
+        .account_payments(bob.public_key(), &Default::default())
+        .await
+        .unwrap();
+    let record = operations.records.last().unwrap();
+    assert_eq!(record.kind, "path_payment_strict_receive");
+    assert_eq!(record.detail("source_amount"), Some("71.4285715"));
+    assert_eq!(record.detail("source_max"), Some("72.1428573"));
+}
+
+#[tokio::test]
+async fn strict_send_is_bounded_and_checked_before_signing() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, alice, bob) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &alice, &bob] {
+        horizon.fund(account.public_key(), xlm("1000"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let yen = Asset::issued("YEN", issuer.public_key()).unwrap();
+    horizon.set_price(&usdc, &Asset::Native, "8");
+    client.change_trust(&alice, &usdc, None).await.unwrap();
+    client
+        .send_asset(&issuer, alice.public_key(), &usdc, xlm("20"))
+        .await
+        .unwrap();
+
+    let payment = PathPayment::strict_send(usdc.clone(), xlm("5"), Asset::Native)
+        .with_slippage("2".parse().unwrap());
+    let quote = client.quote(&payment).await.unwrap();
+    assert_eq!(quote.strict, Strict::Send);
+    assert!(quote.path.path.is_empty());
+    assert_eq!(quote.path.destination_amount, xlm("40"));
+    assert_eq!(quote.limit, xlm("39.2"));
+
+    let queue = SubmissionQueue::new(horizon.client());
+    let paid = queue
+        .path_payment(&alice, bob.public_key(), &payment)
+        .await
+        .unwrap();
+    assert_eq!(
+        (paid.source_amount, paid.destination_amount),
+        (xlm("5"), xlm("40"))
+    );
+    assert_eq!(paid.rate(), xlm("8"));
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("1040")));
+    assert_eq!(
+        horizon.asset_balance(alice.public_key(), &usdc),
+        Some(xlm("15"))
+    );
+
+    // Refused before anything is signed: more than the source holds, no
+    // trustline on the receiving end, and no market at all.
+    let sequence = horizon.sequence(alice.public_key());
+    let err = queue
+        .path_payment(
+            &alice,
+            bob.public_key(),
+            &PathPayment::strict_send(usdc.clone(), xlm("50"), Asset::Native),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::Underfunded { .. }), "{err}");
+    let err = queue
+        .path_payment(
+            &alice,
+            bob.public_key(),
+            &PathPayment::strict_send(Asset::Native, xlm("8"), usdc.clone()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NoTrustline { .. }), "{err}");
+    let err = client
+        .quote(&PathPayment::strict_send(
+            usdc.clone(),
+            xlm("1"),
+            yen.clone(),
+        ))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, StellarError::NoPath { to, .. } if *to == yen),
+        "{err}"
+    );
+    assert_eq!(err.to_string(), "no path found from USDC to YEN");
+    assert_eq!(horizon.sequence(alice.public_key()), sequence);
+}
@@ -0,0 +1,306 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{
+    Backoff, FeeDistribution, FeeStats, FeeStrategy, SubmissionQueue, BASE_FEE,
+};
+
+use common::{signer, xlm};
+
+fn queue(horizon: &MockHorizon, attempts: u32) -> SubmissionQueue {
+    SubmissionQueue::new(horizon.client()).with_backoff(Backoff {
+        attempts,
+        initial: Duration::from_millis(1),
+        max: Duration::from_millis(5),
+    })
+}
+
+#[tokio::test]
+async fn concurrent_sends_from_one_account_share_its_sequence() {
+    let horizon = MockHorizon::start().await;
+    let treasury = Arc::new(signer(1));
+    horizon.fund(treasury.public_key(), xlm("1000"));
+    let start = horizon.sequence(treasury.public_key()).unwrap();
+
+    // Without the queue, sends that load the account at once collide.
+    let client = horizon.client();
+    let (bob, carol) = (signer(2), signer(3));
+    horizon.fund(bob.public_key(), xlm("1"));
+    horizon.fund(carol.public_key(), xlm("1"));
+    let (a, b) = tokio::join!(
+        client.send_native(treasury.as_ref(), bob.public_key(), xlm("1")),
+        client.send_native(treasury.as_ref(), carol.public_key(), xlm("1")),
+    );
+    let collided = [a, b]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect::<Vec<_>>();
+    for err in &collided {
+        assert_eq!(err.result_codes().unwrap().transaction, "tx_bad_seq");
+    }
+    let direct = 2 - collided.len() as i64;
+
+    let queue = Arc::new(queue(&horizon, 3));
+    let deposits: Vec<_> = (10..30)
+        .map(|n| {
+            let (queue, treasury) = (queue.clone(), treasury.clone());
+            tokio::spawn(async move {
+                let customer = signer(n);
+                queue
+                    .send_native(treasury.as_ref(), customer.public_key(), xlm("2"))
+                    .await
+            })
+        })
+        .collect();
+    for deposit in deposits {
+        assert!(deposit.await.unwrap().unwrap().created_account);
+    }
+
+    assert_eq!(queue.depth(treasury.public_key()), 0);
+    assert_eq!(queue.depth(bob.public_key()), 0);
+    assert_eq!(
+        horizon.sequence(treasury.public_key()),
+        Some(start + direct + 20)
+    );
+    for n in 10..30 {
+        assert_eq!(horizon.balance(signer(n).public_key()), Some(xlm("2")));
+    }
+}
+
+#[tokio::test]
+async fn resyncs_the_sequence_after_tx_bad_seq() {
+    let horizon = MockHorizon::start().await;
+    let (alice, bob) = (signer(1), signer(2));
+    horizon.fund(alice.public_key(), xlm("100"));
+    horizon.fund(bob.public_key(), xlm("10"));
+    let queue = queue(&horizon, 3);
+
+    queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    // Another wallet for the same account spends the next number.
+    horizon
+        .client()
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("13")));
+
+    // Failed operations still spend the sequence number, and the queue
+    // counts it without reloading.
+    let err = queue
+        .send_native(&alice, bob.public_key(), xlm("500"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.result_codes().unwrap().operations, ["op_underfunded"]);
+    queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("14")));
+
+    // Other rejections are not retried.
+    horizon.reject_next("tx_bad_auth");
+    let err = queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.result_codes().unwrap().transaction, "tx_bad_auth");
+    queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("15")));
+}
+
+#[tokio::test]
+async fn retries_too_late_and_timeouts_without_paying_twice() {
+    let horizon = MockHorizon::start().await;
+    let (alice, bob) = (signer(1), signer(2));
+    horizon.fund(alice.public_key(), xlm("100"));
+    horizon.fund(bob.public_key(), xlm("10"));
+    let queue = queue(&horizon, 3);
+
+    horizon.reject_next("tx_too_late");
+    horizon.reject_next("tx_too_late");
+    queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("11")));
+
+    // The payment made a ledger but the request timed out: resending it is
+    // refused, and the queue finds it instead of paying again.
+    horizon.time_out_next();
+    let sent = queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("12")));
+    let record = horizon.client().transaction(&sent.hash).await.unwrap();
+    assert_eq!(record.ledger, sent.ledger);
+    queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("13")));
+
+    // It gives up after the configured attempts.
+    for _ in 0..3 {
+        horizon.reject_next("tx_too_late");
+    }
+    let err = queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.result_codes().unwrap().transaction, "tx_too_late");
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("13")));
+    assert_eq!(queue.depth(alice.public_key()), 0);
+}
+
+/// Fee stats where recent ledgers charged `p10 + 10n` at the nth decile.
+fn fee_stats(p10: u64) -> FeeStats {
+    let decile = |n: u64| p10 + 10 * (n - 1);
+    let fees = FeeDistribution {
+        max: 5_000,
+        min: p10,
+        mode: p10,
+        p10: decile(1),
+        p20: decile(2),
+        p30: decile(3),
+        p40: decile(4),
+        p50: decile(5),
+        p60: decile(6),
+        p70: decile(7),
+        p80: decile(8),
+        p90: decile(9),
+        p95: 1_000,
+        p99: 2_000,
+    };
+    FeeStats {
+        last_ledger: 1,
+        last_ledger_base_fee: 100,
+        ledger_capacity_usage: 0.97,
+        fee_charged: fees,
+        max_fee: fees,
+    }
+}
+
+#[test]
+fn fee_strategy_picks_a_capped_percentile() {
+    let stats = fee_stats(300);
+    let strategy = |percentile, max_fee| FeeStrategy {
+        percentile,
+        max_fee,
+    };
+    assert_eq!(strategy(10, 10_000).fee(&stats), 300);
+    assert_eq!(strategy(75, 10_000).fee(&stats), 370);
+    assert_eq!(strategy(99, 10_000).fee(&stats), 2_000);
+    assert_eq!(strategy(100, 10_000).fee(&stats), 5_000);
+    assert_eq!(strategy(99, 1_500).fee(&stats), 1_500);
+    // Never below the base fee, even with a lower cap.
+    assert_eq!(strategy(10, 10_000).fee(&fee_stats(0)), BASE_FEE);
+    assert_eq!(strategy(10, 50).fee(&stats), BASE_FEE);
+    assert_eq!(FeeStrategy::default().fee(&stats), 380);
+
+    // A bump outbids the stuck transaction tenfold; a cap below that
+    // leaves nothing the network would take.
+    assert_eq!(strategy(90, 10_000).bump_fee(&stats, 100), Some(1_000));
+    assert_eq!(strategy(90, 10_000).bump_fee(&stats, 20), Some(380));
+    assert_eq!(strategy(90, 1_000).bump_fee(&stats, 100), Some(1_000));
+    assert_eq!(strategy(90, 600).bump_fee(&stats, 100), None);
+    assert_eq!(strategy(90, 600).bump_fee(&stats, 600), None);
+}
+
+#[tokio::test]
+async fn stuck_transactions_are_fee_bumped_by_the_sponsor() {
+    let horizon = MockHorizon::start().await;
+    let (treasury, alice, bob) = (Arc::new(signer(1)), signer(2), signer(3));
+    horizon.fund(treasury.public_key(), xlm("100"));
+    horizon.fund(alice.public_key(), xlm("100"));
+    horizon.fund(bob.public_key(), xlm("10"));
+
+    // Fresh fee stats: the queue offers what the ledgers charge.
+    horizon.set_surge_fee(1_000);
+    let queue = queue(&horizon, 3);
+    let sent = queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(
+        (sent.fee, sent.fee_charged, sent.fee_bump),
+        (1_000, 1_000, None)
+    );
+    assert_eq!(horizon.balance(alice.public_key()), Some(xlm("98.9999")));
+
+    // Stale ones: the payment offers their p90 of 180 and times out until
+    // the treasury bumps it.
+    horizon.set_fee_stats(fee_stats(100));
+    let err = queue
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("11")));
+
+    let sponsored = queue.with_fee_sponsor(treasury.clone());
+    let sent = sponsored
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap();
+    assert_eq!(sent.fee, 180);
+    let bump = sent.fee_bump.unwrap();
+    assert_eq!(
+        (bump.fee_source.as_str(), bump.fee),
+        (treasury.public_key(), 1_800)
+    );
+    // Two operations' worth: the payment and the bump.
+    assert_eq!(sent.fee_charged, 2_000);
+    assert_eq!(horizon.balance(bob.public_key()), Some(xlm("12")));
+    assert_eq!(horizon.balance(alice.public_key()), Some(xlm("97.9999")));
+    assert_eq!(horizon.balance(treasury.public_key()), Some(xlm("99.9998")));
+    let record = horizon
+        .client()
+        .transaction(&bump.inner_hash)
+        .await
+        .unwrap();
+    assert_eq!(record.hash, sent.hash);
+    assert_eq!(record.fee_account, treasury.public_key());
+
+    // The cap leaves no room to outbid the surge: it gives up.
+    let capped = SubmissionQueue::new(horizon.client())
+        .with_backoff(Backoff {
+            attempts: 3,
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(5),
+        })
+        .with_fees(FeeStrategy {
+            percentile: 90,
+            max_fee: 500,
+        })
+        .with_fee_sponsor(treasury.clone());
+    let err = capped
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap_err();
+    assert!(err.is_timeout(), "{err}");
+
+    // A fee bump from an unfunded sponsor is refused as such.
+    horizon.set_surge_fee(BASE_FEE);
+    let stranger = SubmissionQueue::new(horizon.client()).with_fee_sponsor(Arc::new(signer(9)));
+    horizon.reject_next("tx_insufficient_fee");
+    let err = stranger
+        .send_native(&alice, bob.public_key(), xlm("1"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.result_codes().unwrap().transaction, "tx_no_account");
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use stellrflow_amount::Amount;
+use stellrflow_signer::Signer;
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{Asset, SponsoredEntry, Sponsorship, StellarError, SubmissionQueue};
+
+use common::{signer, xlm};
+
+#[tokio::test]
+async fn a_treasury_opens_wallets_it_holds_the_reserves_of() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, treasury, wallet) = (signer(1), signer(2), signer(3));
+    horizon.fund(issuer.public_key(), xlm("100"));
+    horizon.fund(treasury.public_key(), xlm("100"));
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let eurc = Asset::issued("EURC", issuer.public_key()).unwrap();
+
+    // The wallet does not exist; it starts with nothing and a trustline.
+    let queue = SubmissionQueue::new(horizon.client());
+    let sponsored = queue
+        .sponsor_account(
+            &treasury,
+            &wallet,
+            &Sponsorship::new().with_trustline(usdc.clone()),
+        )
+        .await
+        .unwrap();
+    assert!(sponsored.created_account);
+    assert_eq!(sponsored.trustlines, std::slice::from_ref(&usdc));
+    assert_eq!(sponsored.reserves(), 3);
+    let account = client.account(wallet.public_key()).await.unwrap();
+    assert_eq!(account.native_balance(), Amount::ZERO);
+    assert_eq!(account.num_sponsored, 3);
+    assert_eq!(account.sponsor.as_deref(), Some(treasury.public_key()));
+    assert_eq!(
+        account.balance_of(&usdc).unwrap().sponsor.as_deref(),
+        Some(treasury.public_key())
+    );
+    // Four operations' fees, and three base reserves held.
+    assert_eq!(
+        horizon.balance(treasury.public_key()),
+        Some(xlm("99.99996"))
+    );
+    assert_eq!(
+        client
+            .sponsored_reserves(treasury.public_key())
+            .await
+            .unwrap(),
+        3
+    );
+
+    // An existing account only gets trustlines.
+    let sponsored = client
+        .sponsor_account(
+            &treasury,
+            &wallet,
+            &Sponsorship::new().with_trustline(eurc.clone()),
+        )
+        .await
+        .unwrap();
+    assert!(!sponsored.created_account);
+    assert_eq!(sponsored.reserves(), 1);
+    assert_eq!(
+        client
+            .sponsored_reserves(treasury.public_key())
+            .await
+            .unwrap(),
+        4
+    );
+    let listed = client
+        .sponsored_accounts(treasury.public_key(), &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(listed.records.len(), 1);
+    let entries = listed.records[0].sponsored_by(treasury.public_key());
+    assert_eq!(
+        entries,
+        [
+            SponsoredEntry::Account(wallet.public_key().to_string()),
+            SponsoredEntry::Trustline {
+                account: wallet.public_key().to_string(),
+                asset: eurc.clone(),
+            },
+            SponsoredEntry::Trustline {
+                account: wallet.public_key().to_string(),
+                asset: usdc.clone(),
+            },
+        ]
+    );
+
+    // Revoked, the wallet holds the reserve itself, which it cannot yet.
+    let err = client
+        .revoke_sponsorship(&treasury, &entries[0])
+        .await
+        .unwrap_err();
+    assert_eq!(err.result_codes().unwrap().operations, ["op_low_reserve"]);
+    horizon.fund(wallet.public_key(), xlm("1"));
+    queue
+        .revoke_sponsorship(&treasury, &entries[0])
+        .await
+        .unwrap();
+    let account = client.account(wallet.public_key()).await.unwrap();
+    assert_eq!((account.sponsor, account.num_sponsored), (None, 2));
+    assert_eq!(
+        client
+            .sponsored_reserves(treasury.public_key())
+            .await
+            .unwrap(),
+        2
+    );
+    let err = client
+        .revoke_sponsorship(&treasury, &entries[0])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NotSponsor { .. }), "{err}");
+
+    // A treasury that cannot cover the reserves opens nothing.
+    let (poor, other) = (signer(4), signer(5));
+    horizon.fund(poor.public_key(), xlm("1.5"));
+    let err = client
+        .sponsor_account(&poor, &other, &Sponsorship::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.result_codes().unwrap().operations,
+        ["op_success", "op_low_reserve"]
+    );
+    assert_eq!(horizon.balance(other.public_key()), None);
+}
+
+#[tokio::test]
+async fn sponsorships_are_checked_before_signing() {
+    let horizon = MockHorizon::start().await;
+    let client = horizon.client();
+    let (issuer, treasury, wallet) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &treasury, &wallet] {
+        horizon.fund(account.public_key(), xlm("100"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let newcomer = signer(4);
+    let sequence = horizon.sequence(treasury.public_key());
+
+    let invalid = [
+        (&treasury, Sponsorship::new()),
+        (
+            &newcomer,
+            Sponsorship::new()
+                .with_trustline(usdc.clone())
+                .with_trustline(usdc.clone()),
+        ),
+        (&issuer, Sponsorship::new().with_trustline(usdc.clone())),
+        // Wallet exists: there is no starting balance, and something to
+        // sponsor is needed.
+        (&wallet, Sponsorship::new()),
+        (
+            &wallet,
+            Sponsorship::new()
+                .with_starting_balance(xlm("1"))
+                .with_trustline(usdc.clone()),
+        ),
+    ];
+    for (account, sponsorship) in &invalid {
+        let err = client
+            .sponsor_account(&treasury, *account, sponsorship)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, StellarError::InvalidSponsorship(_)),
+            "{sponsorship:?}: {err}"
+        );
+    }
+    // Begin, create, 98 trusts and end are more than one transaction holds.
+    let crowded = (0..98).fold(Sponsorship::new(), |sponsorship, i| {
+        sponsorship.with_trustline(Asset::issued(&format!("T{i}"), issuer.public_key()).unwrap())
+    });
+    let err = client
+        .sponsor_account(&treasury, &newcomer, &crowded)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, StellarError::InvalidSponsorship(why) if why.contains("101 operations")),
+        "{err}"
+    );
+    let err = client
+        .sponsor_account(
+            &treasury,
+            &newcomer,
+            &Sponsorship::new().with_starting_balance(xlm("-1")),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidAmount(_)), "{err}");
+    let err = client
+        .sponsor_account(
+            &treasury,
+            &newcomer,
+            &Sponsorship::new().with_trustline(Asset::Native),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidAsset(_)), "{err}");
+    client.change_trust(&wallet, &usdc, None).await.unwrap();
+    let err = client
+        .sponsor_account(
+            &treasury,
+            &wallet,
+            &Sponsorship::new().with_trustline(usdc.clone()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::InvalidSponsorship(_)), "{err}");
+    assert_eq!(horizon.sequence(treasury.public_key()), sequence);
+
+    // Only a sponsor can revoke, and only what exists.
+    let line = SponsoredEntry::Trustline {
+        account: wallet.public_key().to_string(),
+        asset: usdc.clone(),
+    };
+    let err = client
+        .revoke_sponsorship(&treasury, &line)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NotSponsor { .. }), "{err}");
+    let missing = SponsoredEntry::Trustline {
+        account: treasury.public_key().to_string(),
+        asset: usdc,
+    };
+    let err = client
+        .revoke_sponsorship(&treasury, &missing)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NoTrustline { .. }), "{err}");
+    let unknown = SponsoredEntry::Account(newcomer.public_key().to_string());
+    let err = client
+        .revoke_sponsorship(&treasury, &unknown)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StellarError::NotFound(_)), "{err}");
+    assert_eq!(horizon.sequence(treasury.public_key()), sequence);
+}
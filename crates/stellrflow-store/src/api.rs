@@ -0,0 +1,140 @@
+//! REST endpoints for the builder, mounted under `/api/workflows`.
+//!
+//! | Method   | Path                                         |                            |
+//! |----------|----------------------------------------------|----------------------------|
+//! | `GET`    | `/api/workflows/{chatId}`                    | list the chat's workflows  |
+//! | `POST`   | `/api/workflows/{chatId}`                    | create from a draft        |
+//! | `GET`    | `/api/workflows/{chatId}/{id}`               | latest version             |
+//! | `PUT`    | `/api/workflows/{chatId}/{id}`               | save a new version         |
+//! | `DELETE` | `/api/workflows/{chatId}/{id}`               | delete with history        |
+//! | `GET`    | `/api/workflows/{chatId}/{id}/versions`      | version history            |
+//! | `GET`    | `/api/workflows/{chatId}/{id}/versions/{v}`  | one saved version          |
+//!
+//! Responses follow the bot's API: `{ success: true, ... }` on success and
+//! `{ success: false, error }` with a 4xx/5xx status otherwise.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use crate::error::StoreError;
+use crate::repo::{WorkflowDraft, WorkflowRepository};
+
+type Repo = Arc<dyn WorkflowRepository>;
+
+/// The workflow routes, backed by `repo`.
+pub fn router(repo: Repo) -> Router {
+    Router::new()
+        .route("/api/workflows/{chat_id}", get(list).post(create))
+        .route(
+            "/api/workflows/{chat_id}/{id}",
+            get(fetch).put(update).delete(remove),
+        )
+        .route("/api/workflows/{chat_id}/{id}/versions", get(versions))
+        .route(
+            "/api/workflows/{chat_id}/{id}/versions/{version}",
+            get(version),
+        )
+        .with_state(repo)
+}
+
+async fn list(
+    State(repo): State<Repo>,
+    Path(chat_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let workflows = blocking(repo, move |repo| repo.list(&chat_id)).await?;
+    Ok(Json(json!({ "success": true, "workflows": workflows })))
+}
+
+async fn create(
+    State(repo): State<Repo>,
+    Path(chat_id): Path<String>,
+    Json(draft): Json<WorkflowDraft>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let workflow = blocking(repo, move |repo| repo.create(&chat_id, &draft)).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "success": true, "workflow": workflow })),
+    ))
+}
+
+async fn fetch(
+    State(repo): State<Repo>,
+    Path((chat_id, id)): Path<(String, i64)>,
+) -> Result<Json<Value>, ApiError> {
+    let workflow = blocking(repo, move |repo| repo.get(&chat_id, id)).await?;
+    Ok(Json(json!({ "success": true, "workflow": workflow })))
+}
+
+async fn update(
+    State(repo): State<Repo>,
+    Path((chat_id, id)): Path<(String, i64)>,
+    Json(draft): Json<WorkflowDraft>,
+) -> Result<Json<Value>, ApiError> {
+    let workflow = blocking(repo, move |repo| repo.update(&chat_id, id, &draft)).await?;
+    Ok(Json(json!({ "success": true, "workflow": workflow })))
+}
+
+async fn remove(
+    State(repo): State<Repo>,
+    Path((chat_id, id)): Path<(String, i64)>,
+) -> Result<Json<Value>, ApiError> {
+    blocking(repo, move |repo| repo.delete(&chat_id, id)).await?;
+    Ok(Json(
+        json!({ "success": true, "message": "Workflow deleted" }),
+    ))
+}
+
+async fn versions(
+    State(repo): State<Repo>,
+    Path((chat_id, id)): Path<(String, i64)>,
+) -> Result<Json<Value>, ApiError> {
+    let versions = blocking(repo, move |repo| repo.versions(&chat_id, id)).await?;
+    Ok(Json(json!({ "success": true, "versions": versions })))
+}
+
+async fn version(
+    State(repo): State<Repo>,
+    Path((chat_id, id, version)): Path<(String, i64, u32)>,
+) -> Result<Json<Value>, ApiError> {
+    let version = blocking(repo, move |repo| repo.version(&chat_id, id, version)).await?;
+    Ok(Json(json!({ "success": true, "version": version })))
+}
+
+/// Run a repository call on the blocking thread pool.
+async fn blocking<T, F>(repo: Repo, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn WorkflowRepository) -> Result<T, StoreError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(repo.as_ref()))
+        .await
+        .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+        .map_err(ApiError::from)
+}
+
+/// A failed request: its status and the message sent as `error`.
+struct ApiError(StatusCode, String);
+
+impl From<StoreError> for ApiError {
+    fn from(err: StoreError) -> Self {
+        let status = match err {
+            StoreError::InvalidOwner(_) | StoreError::Invalid(_) => StatusCode::BAD_REQUEST,
+            StoreError::NotFound(_) | StoreError::VersionNotFound { .. } => StatusCode::NOT_FOUND,
+            StoreError::Conflict { .. } => StatusCode::CONFLICT,
+            StoreError::Sqlite(_) | StoreError::Json(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "success": false, "error": self.1 }))).into_response()
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+/// Why a repository call failed.
+#[derive(Debug, Error)]
+pub enum StoreError {
+    /// Owners are Telegram chat IDs, which are (possibly negative) integers.
+    #[error("invalid owner chat ID `{0}`: use the numeric chat ID from /register")]
+    InvalidOwner(String),
+    /// The draft has no name, or its graph is not a `{ nodes, edges }`
+    /// document the engine can read.
+    #[error("invalid workflow: {0}")]
+    Invalid(String),
+    /// No such workflow for this owner. Workflows owned by another chat are
+    /// reported the same way.
+    #[error("workflow {0} not found")]
+    NotFound(i64),
+    #[error("workflow {id} has no version {version}")]
+    VersionNotFound { id: i64, version: u32 },
+    /// The update was based on an older version than the one stored.
+    #[error("workflow {id} is at version {current}, but the update was based on version {base}")]
+    Conflict { id: i64, base: u32, current: u32 },
+    #[error("database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("stored graph is corrupt: {0}")]
+    Json(#[from] serde_json::Error),
+}
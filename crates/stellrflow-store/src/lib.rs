@@ -0,0 +1,23 @@
+//! Saved workflows for the StellrFlow builder.
+//!
+//! Workflows belong to the Telegram chat that owns them (the numeric chat ID
+//! the bot's `/register` command hands out) and keep every saved revision:
+//! each save appends a version, so the builder can reload the latest graph
+//! or go back to an earlier one.
+//!
+//! [`WorkflowRepository`] is the storage interface and [`SqliteRepository`]
+//! the default implementation. [`api::router`] exposes a repository over
+//! REST; the `stellrflow-store` binary serves it on `PORT` (default 3004)
+//! with the database at `STELLRFLOW_DB` (default `stellrflow.db`).
+
+pub mod api;
+mod error;
+mod repo;
+mod sqlite;
+
+pub use error::StoreError;
+pub use repo::{
+    StoredWorkflow, VersionInfo, WorkflowDraft, WorkflowRepository, WorkflowSummary,
+    WorkflowVersion,
+};
+pub use sqlite::SqliteRepository;
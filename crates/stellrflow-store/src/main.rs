@@ -0,0 +1,28 @@
+//! Serves the workflow API for the builder.
+//!
+//! `PORT` (default 3004) and `STELLRFLOW_DB` (default `stellrflow.db`)
+//! configure the listener and the SQLite file.
+
+use std::env;
+use std::sync::Arc;
+
+use stellrflow_store::{api, SqliteRepository};
+use tower_http::cors::CorsLayer;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let port: u16 = env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3004);
+    let db = env::var("STELLRFLOW_DB").unwrap_or_else(|_| "stellrflow.db".into());
+
+    let repo = Arc::new(SqliteRepository::open(&db)?);
+    // The builder runs on its own origin, like calls to the bot's API.
+    let app = api::router(repo).layer(CorsLayer::permissive());
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("StellrFlow workflow store running on port {port} (database: {db})");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
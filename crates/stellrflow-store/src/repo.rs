@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stellrflow_engine::Workflow;
+
+use crate::error::StoreError;
+
+/// Storage for workflows and their version history.
+///
+/// Every call is scoped to `owner`, a Telegram chat ID; a workflow owned by
+/// another chat behaves as if it did not exist. Implementations are
+/// blocking; [`crate::api`] calls them off the async runtime.
+pub trait WorkflowRepository: Send + Sync {
+    /// Store a new workflow as version 1.
+    fn create(&self, owner: &str, draft: &WorkflowDraft) -> Result<StoredWorkflow, StoreError>;
+
+    /// The owner's workflows, most recently updated first.
+    fn list(&self, owner: &str) -> Result<Vec<WorkflowSummary>, StoreError>;
+
+    /// The latest version of a workflow.
+    fn get(&self, owner: &str, id: i64) -> Result<StoredWorkflow, StoreError>;
+
+    /// Save `draft` as the next version. A draft identical to the latest
+    /// version is not stored again.
+    fn update(
+        &self,
+        owner: &str,
+        id: i64,
+        draft: &WorkflowDraft,
+    ) -> Result<StoredWorkflow, StoreError>;
+
+    /// Delete a workflow along with its history.
+    fn delete(&self, owner: &str, id: i64) -> Result<(), StoreError>;
+
+    /// Every saved version, newest first.
+    fn versions(&self, owner: &str, id: i64) -> Result<Vec<VersionInfo>, StoreError>;
+
+    /// One saved version.
+    fn version(&self, owner: &str, id: i64, version: u32) -> Result<WorkflowVersion, StoreError>;
+}
+
+/// What the builder sends when saving.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowDraft {
+    #[serde(default = "untitled")]
+    pub name: String,
+    /// The ReactFlow `{ nodes, edges }` document, stored verbatim so node
+    /// positions and styling survive a reload.
+    pub graph: Value,
+    /// The version the builder loaded before editing. When set, the update
+    /// is rejected if someone saved in between.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_version: Option<u32>,
+}
+
+fn untitled() -> String {
+    "Untitled workflow".into()
+}
+
+impl WorkflowDraft {
+    pub fn new(name: impl Into<String>, graph: Value) -> Self {
+        Self {
+            name: name.into(),
+            graph,
+            base_version: None,
+        }
+    }
+
+    /// Require that the stored workflow is still at `version`.
+    pub fn based_on(mut self, version: u32) -> Self {
+        self.base_version = Some(version);
+        self
+    }
+
+    /// Check the draft before storing it. Graphs only have to be readable:
+    /// a half-built workflow with no trigger is still worth saving.
+    pub(crate) fn check(&self) -> Result<(), StoreError> {
+        if self.name.trim().is_empty() {
+            return Err(StoreError::Invalid("name must not be empty".into()));
+        }
+        Workflow::deserialize(&self.graph)
+            .map(drop)
+            .map_err(|err| StoreError::Invalid(format!("graph: {err}")))
+    }
+}
+
+/// A workflow without its graph, as listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowSummary {
+    pub id: i64,
+    pub owner_chat_id: String,
+    pub name: String,
+    /// The latest version number.
+    pub version: u32,
+    /// Milliseconds since the Unix epoch, like `Date.now()`.
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// The latest version of a workflow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredWorkflow {
+    #[serde(flatten)]
+    pub summary: WorkflowSummary,
+    pub graph: Value,
+}
+
+/// One entry in a workflow's history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub version: u32,
+    pub name: String,
+    pub saved_at: i64,
+}
+
+/// A saved version with its graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowVersion {
+    #[serde(flatten)]
+    pub info: VersionInfo,
+    pub graph: Value,
+}
+
+/// Telegram chat IDs are integers; group chats are negative.
+pub(crate) fn check_owner(owner: &str) -> Result<(), StoreError> {
+    let digits = owner.strip_prefix('-').unwrap_or(owner);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(StoreError::InvalidOwner(owner.to_string()));
+    }
+    Ok(())
+}
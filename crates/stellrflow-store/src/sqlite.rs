@@ -0,0 +1,275 @@
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use serde_json::Value;
+
+use crate::error::StoreError;
+use crate::repo::{
+    check_owner, StoredWorkflow, VersionInfo, WorkflowDraft, WorkflowRepository, WorkflowSummary,
+    WorkflowVersion,
+};
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE workflows (
+        id            INTEGER PRIMARY KEY AUTOINCREMENT,
+        owner_chat_id TEXT    NOT NULL,
+        name          TEXT    NOT NULL,
+        version       INTEGER NOT NULL,
+        created_at    INTEGER NOT NULL,
+        updated_at    INTEGER NOT NULL
+    );
+    CREATE INDEX workflows_by_owner ON workflows (owner_chat_id, updated_at);
+
+    CREATE TABLE workflow_versions (
+        workflow_id INTEGER NOT NULL REFERENCES workflows (id) ON DELETE CASCADE,
+        version     INTEGER NOT NULL,
+        name        TEXT    NOT NULL,
+        graph       TEXT    NOT NULL,
+        saved_at    INTEGER NOT NULL,
+        PRIMARY KEY (workflow_id, version)
+    );
+"];
+
+const SUMMARY_COLUMNS: &str = "id, owner_chat_id, name, version, created_at, updated_at";
+
+/// [`WorkflowRepository`] backed by a single SQLite database.
+#[derive(Debug)]
+pub struct SqliteRepository {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteRepository {
+    /// Open (creating if needed) the database at `path` and bring its schema
+    /// up to date.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::init(conn)
+    }
+
+    /// A private database that lives as long as the repository.
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, StoreError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let applied: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let tx = conn.transaction()?;
+        for (version, migration) in (1..).zip(MIGRATIONS).skip(applied as usize) {
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", version)?;
+        }
+        tx.commit()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // A panic mid-call leaves no partial writes behind: every write runs
+        // in a transaction that is rolled back on drop.
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl WorkflowRepository for SqliteRepository {
+    fn create(&self, owner: &str, draft: &WorkflowDraft) -> Result<StoredWorkflow, StoreError> {
+        check_owner(owner)?;
+        draft.check()?;
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let now = now_ms();
+        tx.execute(
+            "INSERT INTO workflows (owner_chat_id, name, version, created_at, updated_at)
+             VALUES (?1, ?2, 1, ?3, ?3)",
+            params![owner, draft.name, now],
+        )?;
+        let id = tx.last_insert_rowid();
+        insert_version(&tx, id, 1, draft, now)?;
+        let stored = latest(&tx, owner, id)?;
+        tx.commit()?;
+        Ok(stored)
+    }
+
+    fn list(&self, owner: &str) -> Result<Vec<WorkflowSummary>, StoreError> {
+        check_owner(owner)?;
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SUMMARY_COLUMNS} FROM workflows
+             WHERE owner_chat_id = ?1
+             ORDER BY updated_at DESC, id DESC"
+        ))?;
+        let rows = stmt.query_map([owner], summary)?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn get(&self, owner: &str, id: i64) -> Result<StoredWorkflow, StoreError> {
+        check_owner(owner)?;
+        latest(&self.conn(), owner, id)
+    }
+
+    fn update(
+        &self,
+        owner: &str,
+        id: i64,
+        draft: &WorkflowDraft,
+    ) -> Result<StoredWorkflow, StoreError> {
+        check_owner(owner)?;
+        draft.check()?;
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let current = latest(&tx, owner, id)?;
+        let version = current.summary.version;
+        if let Some(base) = draft.base_version {
+            if base != version {
+                return Err(StoreError::Conflict {
+                    id,
+                    base,
+                    current: version,
+                });
+            }
+        }
+        if current.summary.name == draft.name && current.graph == draft.graph {
+            return Ok(current);
+        }
+
+        let now = now_ms();
+        insert_version(&tx, id, version + 1, draft, now)?;
+        tx.execute(
+            "UPDATE workflows SET name = ?1, version = ?2, updated_at = ?3 WHERE id = ?4",
+            params![draft.name, version + 1, now, id],
+        )?;
+        let stored = latest(&tx, owner, id)?;
+        tx.commit()?;
+        Ok(stored)
+    }
+
+    fn delete(&self, owner: &str, id: i64) -> Result<(), StoreError> {
+        check_owner(owner)?;
+        let deleted = self.conn().execute(
+            "DELETE FROM workflows WHERE id = ?1 AND owner_chat_id = ?2",
+            params![id, owner],
+        )?;
+        if deleted == 0 {
+            return Err(StoreError::NotFound(id));
+        }
+        Ok(())
+    }
+
+    fn versions(&self, owner: &str, id: i64) -> Result<Vec<VersionInfo>, StoreError> {
+        check_owner(owner)?;
+        let conn = self.conn();
+        find(&conn, owner, id)?;
+        let mut stmt = conn.prepare(
+            "SELECT version, name, saved_at FROM workflow_versions
+             WHERE workflow_id = ?1
+             ORDER BY version DESC",
+        )?;
+        let rows = stmt.query_map([id], |row| {
+            Ok(VersionInfo {
+                version: row.get(0)?,
+                name: row.get(1)?,
+                saved_at: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn version(&self, owner: &str, id: i64, version: u32) -> Result<WorkflowVersion, StoreError> {
+        check_owner(owner)?;
+        let conn = self.conn();
+        find(&conn, owner, id)?;
+        let row = conn
+            .query_row(
+                "SELECT name, graph, saved_at FROM workflow_versions
+                 WHERE workflow_id = ?1 AND version = ?2",
+                params![id, version],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let (name, graph, saved_at) = row.ok_or(StoreError::VersionNotFound { id, version })?;
+        Ok(WorkflowVersion {
+            info: VersionInfo {
+                version,
+                name,
+                saved_at,
+            },
+            graph: serde_json::from_str(&graph)?,
+        })
+    }
+}
+
+fn insert_version(
+    tx: &Transaction<'_>,
+    id: i64,
+    version: u32,
+    draft: &WorkflowDraft,
+    now: i64,
+) -> Result<(), StoreError> {
+    tx.execute(
+        "INSERT INTO workflow_versions (workflow_id, version, name, graph, saved_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            id,
+            version,
+            draft.name,
+            serde_json::to_string(&draft.graph)?,
+            now
+        ],
+    )?;
+    Ok(())
+}
+
+fn summary(row: &Row<'_>) -> rusqlite::Result<WorkflowSummary> {
+    Ok(WorkflowSummary {
+        id: row.get(0)?,
+        owner_chat_id: row.get(1)?,
+        name: row.get(2)?,
+        version: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+/// The workflow's summary, if `owner` owns it.
+fn find(conn: &Connection, owner: &str, id: i64) -> Result<WorkflowSummary, StoreError> {
+    conn.query_row(
+        &format!("SELECT {SUMMARY_COLUMNS} FROM workflows WHERE id = ?1 AND owner_chat_id = ?2"),
+        params![id, owner],
+        summary,
+    )
+    .optional()?
+    .ok_or(StoreError::NotFound(id))
+}
+
+fn latest(conn: &Connection, owner: &str, id: i64) -> Result<StoredWorkflow, StoreError> {
+    let summary = find(conn, owner, id)?;
+    let graph: String = conn.query_row(
+        "SELECT graph FROM workflow_versions WHERE workflow_id = ?1 AND version = ?2",
+        params![id, summary.version],
+        |row| row.get(0),
+    )?;
+    let graph: Value = serde_json::from_str(&graph)?;
+    Ok(StoredWorkflow { summary, graph })
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use stellrflow_store::{api, SqliteRepository};
+use tower::ServiceExt;
+
+fn app() -> Router {
+    api::router(Arc::new(SqliteRepository::open_in_memory().unwrap()))
+}
+
+async fn call(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+fn graph(label: &str) -> Value {
+    json!({
+        "nodes": [{ "id": "t", "data": { "type": "telegram-trigger", "label": label } }],
+        "edges": [],
+    })
+}
+
+#[tokio::test]
+async fn crud_round_trip() {
+    let app = app();
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/workflows/42",
+        Some(json!({ "name": "Alerts", "graph": graph("a") })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["success"], true);
+    let id = body["workflow"]["id"].as_i64().unwrap();
+    assert_eq!(body["workflow"]["ownerChatId"], "42");
+    assert_eq!(body["workflow"]["version"], 1);
+
+    let (status, body) = call(
+        &app,
+        Method::PUT,
+        &format!("/api/workflows/42/{id}"),
+        Some(json!({ "name": "Alerts", "graph": graph("b"), "baseVersion": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["workflow"]["version"], 2);
+
+    let (_, body) = call(&app, Method::GET, "/api/workflows/42", None).await;
+    assert_eq!(body["workflows"][0]["id"], id);
+    assert!(body["workflows"][0].get("graph").is_none());
+
+    let (_, body) = call(&app, Method::GET, &format!("/api/workflows/42/{id}"), None).await;
+    assert_eq!(body["workflow"]["graph"], graph("b"));
+
+    let (_, body) = call(
+        &app,
+        Method::GET,
+        &format!("/api/workflows/42/{id}/versions"),
+        None,
+    )
+    .await;
+    assert_eq!(body["versions"][1]["version"], 1);
+    let (_, body) = call(
+        &app,
+        Method::GET,
+        &format!("/api/workflows/42/{id}/versions/1"),
+        None,
+    )
+    .await;
+    assert_eq!(body["version"]["graph"], graph("a"));
+
+    let (status, _) = call(
+        &app,
+        Method::DELETE,
+        &format!("/api/workflows/42/{id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = call(&app, Method::GET, &format!("/api/workflows/42/{id}"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body,
+        json!({ "success": false, "error": format!("workflow {id} not found") })
+    );
+}
+
+#[tokio::test]
+async fn maps_errors_to_statuses() {
+    let app = app();
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/workflows/@me",
+        Some(json!({ "graph": graph("a") })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["success"], false);
+
+    let (_, body) = call(
+        &app,
+        Method::POST,
+        "/api/workflows/42",
+        Some(json!({ "graph": graph("a") })),
+    )
+    .await;
+    assert_eq!(body["workflow"]["name"], "Untitled workflow");
+    let id = body["workflow"]["id"].as_i64().unwrap();
+
+    call(
+        &app,
+        Method::PUT,
+        &format!("/api/workflows/42/{id}"),
+        Some(json!({ "graph": graph("b"), "baseVersion": 1 })),
+    )
+    .await;
+    let (status, body) = call(
+        &app,
+        Method::PUT,
+        &format!("/api/workflows/42/{id}"),
+        Some(json!({ "graph": graph("c"), "baseVersion": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["error"],
+        format!("workflow {id} is at version 2, but the update was based on version 1")
+    );
+}
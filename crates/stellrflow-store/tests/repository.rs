@@ -0,0 +1,227 @@
+use serde_json::{json, Value};
+use stellrflow_store::{SqliteRepository, StoreError, WorkflowDraft, WorkflowRepository};
+
+const OWNER: &str = "123456789";
+
+fn graph(message: &str) -> Value {
+    json!({
+        "nodes": [
+            {
+                "id": "t",
+                "type": "custom",
+                "position": { "x": 10, "y": 20 },
+                "data": { "type": "telegram-trigger", "label": "Telegram", "config": { "chatId": OWNER } },
+            },
+            {
+                "id": "s",
+                "type": "custom",
+                "position": { "x": 10, "y": 140 },
+                "data": { "type": "telegram-send", "label": "Send", "config": { "message": message } },
+            },
+        ],
+        "edges": [{ "id": "e1", "source": "t", "target": "s", "animated": true }],
+    })
+}
+
+fn repo() -> SqliteRepository {
+    SqliteRepository::open_in_memory().unwrap()
+}
+
+#[test]
+fn keeps_every_saved_version() {
+    let repo = repo();
+    let created = repo
+        .create(OWNER, &WorkflowDraft::new("Alerts", graph("v1")))
+        .unwrap();
+    let id = created.summary.id;
+    assert_eq!(created.summary.version, 1);
+    // ReactFlow fields the engine ignores are stored verbatim.
+    assert_eq!(created.graph, graph("v1"));
+
+    repo.update(OWNER, id, &WorkflowDraft::new("Alerts", graph("v2")))
+        .unwrap();
+    let latest = repo
+        .update(OWNER, id, &WorkflowDraft::new("Price alerts", graph("v3")))
+        .unwrap();
+    assert_eq!(latest.summary.version, 3);
+    assert_eq!(latest.summary.name, "Price alerts");
+    assert_eq!(repo.get(OWNER, id).unwrap(), latest);
+
+    let history: Vec<(u32, String)> = repo
+        .versions(OWNER, id)
+        .unwrap()
+        .into_iter()
+        .map(|v| (v.version, v.name))
+        .collect();
+    assert_eq!(
+        history,
+        [
+            (3, "Price alerts".to_string()),
+            (2, "Alerts".to_string()),
+            (1, "Alerts".to_string()),
+        ]
+    );
+    assert_eq!(repo.version(OWNER, id, 2).unwrap().graph, graph("v2"));
+    assert!(matches!(
+        repo.version(OWNER, id, 4),
+        Err(StoreError::VersionNotFound { version: 4, .. })
+    ));
+}
+
+#[test]
+fn unchanged_saves_do_not_add_versions() {
+    let repo = repo();
+    let id = repo
+        .create(OWNER, &WorkflowDraft::new("Alerts", graph("hi")))
+        .unwrap()
+        .summary
+        .id;
+    let again = repo
+        .update(OWNER, id, &WorkflowDraft::new("Alerts", graph("hi")))
+        .unwrap();
+    assert_eq!(again.summary.version, 1);
+    assert_eq!(repo.versions(OWNER, id).unwrap().len(), 1);
+}
+
+#[test]
+fn rejects_updates_based_on_a_stale_version() {
+    let repo = repo();
+    let id = repo
+        .create(OWNER, &WorkflowDraft::new("Alerts", graph("a")))
+        .unwrap()
+        .summary
+        .id;
+    repo.update(
+        OWNER,
+        id,
+        &WorkflowDraft::new("Alerts", graph("b")).based_on(1),
+    )
+    .unwrap();
+
+    let err = repo
+        .update(
+            OWNER,
+            id,
+            &WorkflowDraft::new("Alerts", graph("c")).based_on(1),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        StoreError::Conflict {
+            base: 1,
+            current: 2,
+            ..
+        }
+    ));
+    assert_eq!(repo.get(OWNER, id).unwrap().graph, graph("b"));
+}
+
+#[test]
+fn workflows_are_scoped_to_their_owner() {
+    let repo = repo();
+    let id = repo
+        .create(OWNER, &WorkflowDraft::new("Mine", graph("a")))
+        .unwrap()
+        .summary
+        .id;
+    repo.create("-100200", &WorkflowDraft::new("Group", graph("b")))
+        .unwrap();
+
+    let other = "987654321";
+    assert!(repo.list(other).unwrap().is_empty());
+    assert!(matches!(repo.get(other, id), Err(StoreError::NotFound(_))));
+    assert!(matches!(
+        repo.update(other, id, &WorkflowDraft::new("Stolen", graph("x"))),
+        Err(StoreError::NotFound(_))
+    ));
+    assert!(matches!(
+        repo.delete(other, id),
+        Err(StoreError::NotFound(_))
+    ));
+
+    let names: Vec<String> = repo
+        .list(OWNER)
+        .unwrap()
+        .into_iter()
+        .map(|w| w.name)
+        .collect();
+    assert_eq!(names, ["Mine"]);
+}
+
+#[test]
+fn lists_most_recently_updated_first() {
+    let repo = repo();
+    let first = repo
+        .create(OWNER, &WorkflowDraft::new("First", graph("a")))
+        .unwrap();
+    repo.create(OWNER, &WorkflowDraft::new("Second", graph("b")))
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    repo.update(
+        OWNER,
+        first.summary.id,
+        &WorkflowDraft::new("First", graph("c")),
+    )
+    .unwrap();
+
+    let names: Vec<String> = repo
+        .list(OWNER)
+        .unwrap()
+        .into_iter()
+        .map(|w| w.name)
+        .collect();
+    assert_eq!(names, ["First", "Second"]);
+}
+
+#[test]
+fn delete_removes_history() {
+    let repo = repo();
+    let id = repo
+        .create(OWNER, &WorkflowDraft::new("Alerts", graph("a")))
+        .unwrap()
+        .summary
+        .id;
+    repo.delete(OWNER, id).unwrap();
+    assert!(matches!(repo.get(OWNER, id), Err(StoreError::NotFound(_))));
+    assert!(matches!(
+        repo.versions(OWNER, id),
+        Err(StoreError::NotFound(_))
+    ));
+}
+
+#[test]
+fn validates_owner_and_draft() {
+    let repo = repo();
+    assert!(matches!(
+        repo.create("@someone", &WorkflowDraft::new("A", graph("a"))),
+        Err(StoreError::InvalidOwner(_))
+    ));
+    assert!(matches!(
+        repo.create(OWNER, &WorkflowDraft::new("  ", graph("a"))),
+        Err(StoreError::Invalid(_))
+    ));
+    assert!(matches!(
+        repo.create(OWNER, &WorkflowDraft::new("A", json!({ "nodes": "nope" }))),
+        Err(StoreError::Invalid(_))
+    ));
+}
+
+#[test]
+fn survives_reopening() {
+    let dir = std::env::temp_dir().join(format!("stellrflow-store-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("workflows.db");
+
+    let id = {
+        let repo = SqliteRepository::open(&path).unwrap();
+        repo.create(OWNER, &WorkflowDraft::new("Alerts", graph("a")))
+            .unwrap()
+            .summary
+            .id
+    };
+    let repo = SqliteRepository::open(&path).unwrap();
+    assert_eq!(repo.get(OWNER, id).unwrap().graph, graph("a"));
+
+    drop(repo);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use stellrflow_signer::{Signer, SignerError, Signers};
+
+/// Whom the service signs for: accounts it holds a key for, then the
+/// Telegram wallets.
+#[derive(Default)]
+pub struct Accounts {
+    keys: HashMap<String, Arc<dyn Signer>>,
+    wallets: Option<Arc<dyn Signers>>,
+}
+
+impl Accounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signs for `signer`'s account with it, such as the treasury's key.
+    pub fn with_key(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.keys.insert(signer.public_key().to_string(), signer);
+        self
+    }
+
+    /// Signs for any other account through `wallets`.
+    pub fn with_wallets(mut self, wallets: Arc<dyn Signers>) -> Self {
+        self.wallets = Some(wallets);
+        self
+    }
+}
+
+#[async_trait]
+impl Signers for Accounts {
+    async fn signer_for(&self, account: &str) -> Result<Arc<dyn Signer>, SignerError> {
+        if let Some(signer) = self.keys.get(account) {
+            return Ok(Arc::clone(signer));
+        }
+        match &self.wallets {
+            Some(wallets) => wallets.signer_for(account).await,
+            None => Err(SignerError::UnknownAccount(account.to_string())),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! The submission endpoints, backed by a [`Submitter`].
+//!
+//! | Method | Path                       |                                           |
+//! |--------|----------------------------|-------------------------------------------|
+//! | `POST` | `/api/submit/send`         | send XLM, creating the account if need be |
+//! | `POST` | `/api/submit/quote`        | the best path for a path payment          |
+//! | `POST` | `/api/submit/path-payment` | deliver an asset, paid over that path     |
+//! | `GET`  | `/api/submit/fee`          | the fee per operation to offer now        |
+//!
+//! Path payments are strict-receive, as for `/pay`: the destination gets
+//! exactly `amount` of `destAsset`, paid in `sendAsset` (XLM unless given)
+//! and at most `slippage` (a percentage, 1% unless given) above the quote.
+//! The quote's `mostSent` is what a payment is checked against the policy
+//! for.
+//!
+//! A request names the `source` account by its public key; the service
+//! signs for it if it is one of its [`Accounts`](crate::Accounts). Every
+//! transaction offers the fee the queue's
+//! [`FeeStrategy`](stellrflow_stellar::FeeStrategy) picks, and `fee` is
+//! the same for transactions built elsewhere, such as Freighter's.
+//! Responses follow `telegram-bot.ts`: `{ success: true, ... }` on success
+//! and `{ success: false, error }` with a 4xx/5xx status otherwise, with
+//! Horizon's `resultCodes` when it rejected the transaction.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stellrflow_amount::Amount;
+use stellrflow_signer::{SignerError, Signers};
+use stellrflow_stellar::{
+    Asset, PathPaid, PathPayment, Quote, ResultCodes, Sent, Slippage, StellarError,
+};
+
+use crate::Submitter;
+
+type Shared = Arc<Submitter>;
+
+/// The submission routes, backed by `submitter`.
+pub fn router(submitter: Shared) -> Router {
+    Router::new()
+        .route("/api/submit/send", post(send))
+        .route("/api/submit/quote", post(quote))
+        .route("/api/submit/path-payment", post(path_payment))
+        .route("/api/submit/fee", get(fee))
+        .with_state(submitter)
+}
+
+#[derive(Debug, Deserialize)]
+struct SendRequest {
+    source: String,
+    destination: String,
+    amount: Amount,
+}
+
+async fn send(
+    State(submitter): State<Shared>,
+    Json(request): Json<SendRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let destination = request.destination.trim();
+    let source = submitter
+        .accounts
+        .signer_for(request.source.trim())
+        .await
+        .map_err(StellarError::from)?;
+    let sent = submitter
+        .queue
+        .send_native(source.as_ref(), destination, request.amount)
+        .await?;
+    println!(
+        "Submitted {}: {} XLM from {} to {} (fee {})",
+        sent.hash,
+        request.amount,
+        source.public_key(),
+        destination,
+        sent.fee_charged,
+    );
+    Ok(Json(sent_json(&sent)))
+}
+
+/// A strict-receive path payment. Assets and the slippage are parsed here,
+/// so that a bad one is reported like any other refusal.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PathRequest {
+    send_asset: Option<String>,
+    dest_asset: String,
+    amount: Amount,
+    slippage: Option<String>,
+}
+
+impl PathRequest {
+    fn payment(&self) -> Result<PathPayment, StellarError> {
+        let send_asset = match &self.send_asset {
+            Some(asset) => asset.parse()?,
+            None => Asset::Native,
+        };
+        let slippage = match &self.slippage {
+            Some(slippage) => slippage.parse()?,
+            None => Slippage::default(),
+        };
+        Ok(
+            PathPayment::strict_receive(send_asset, self.dest_asset.parse()?, self.amount)
+                .with_slippage(slippage),
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PathPaymentRequest {
+    source: String,
+    destination: String,
+    #[serde(flatten)]
+    payment: PathRequest,
+}
+
+async fn quote(
+    State(submitter): State<Shared>,
+    Json(request): Json<PathRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let payment = request.payment()?;
+    let quote = submitter.queue.horizon().quote(&payment).await?;
+    Ok(Json(quote_json(&payment, &quote)))
+}
+
+async fn path_payment(
+    State(submitter): State<Shared>,
+    Json(request): Json<PathPaymentRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let payment = request.payment.payment()?;
+    let destination = request.destination.trim();
+    let source = submitter
+        .accounts
+        .signer_for(request.source.trim())
+        .await
+        .map_err(StellarError::from)?;
+    let paid = submitter
+        .queue
+        .path_payment(source.as_ref(), destination, &payment)
+        .await?;
+    println!(
+        "Submitted {}: {} {} for {} {} from {} to {}",
+        paid.hash,
+        paid.destination_amount,
+        payment.dest_asset.code(),
+        paid.source_amount,
+        payment.send_asset.code(),
+        source.public_key(),
+        destination,
+    );
+    Ok(Json(path_paid_json(&paid)))
+}
+
+async fn fee(State(submitter): State<Shared>) -> Result<Json<Value>, ApiError> {
+    let strategy = submitter.queue.fees();
+    let fee = submitter.queue.horizon().suggested_fee(&strategy).await?;
+    Ok(Json(json!({
+        "success": true,
+        "fee": fee,
+        "maxFee": strategy.max_fee,
+    })))
+}
+
+/// What a payment did, as the bot reads it.
+fn sent_json(sent: &Sent) -> Value {
+    json!({
+        "success": true,
+        "hash": sent.hash,
+        "ledger": sent.ledger,
+        "createdAccount": sent.created_account,
+        "fee": sent.fee,
+        "feeCharged": sent.fee_charged,
+        "feeBump": sent.fee_bump.as_ref().map(|bump| json!({
+            "feeSource": bump.fee_source,
+            "fee": bump.fee,
+            "innerHash": bump.inner_hash,
+        })),
+    })
+}
+
+fn quote_json(payment: &PathPayment, quote: &Quote) -> Value {
+    json!({
+        "success": true,
+        "sendAsset": payment.send_asset,
+        "destAsset": payment.dest_asset,
+        "sourceAmount": quote.path.source_amount,
+        "destinationAmount": quote.path.destination_amount,
+        "mostSent": quote.most_sent(),
+        "rate": quote.rate(),
+        "path": quote.path.path,
+    })
+}
+
+/// What a path payment did, with the rate it got and the one it was
+/// quoted.
+fn path_paid_json(paid: &PathPaid) -> Value {
+    json!({
+        "success": true,
+        "hash": paid.hash,
+        "ledger": paid.ledger,
+        "sourceAmount": paid.source_amount,
+        "destinationAmount": paid.destination_amount,
+        "rate": paid.rate(),
+        "quotedRate": paid.quote.rate(),
+        "path": paid.quote.path.path,
+    })
+}
+
+/// A failed request: its status, the message sent as `error` and Horizon's
+/// result codes, if it rejected the transaction.
+struct ApiError(StatusCode, String, Option<ResultCodes>);
+
+impl From<StellarError> for ApiError {
+    fn from(err: StellarError) -> Self {
+        let status = match &err {
+            StellarError::Signer(SignerError::UnknownAccount(_)) => StatusCode::FORBIDDEN,
+            StellarError::NotFound(_) => StatusCode::NOT_FOUND,
+            StellarError::Horizon(problem) if problem.status < 500 => StatusCode::BAD_REQUEST,
+            StellarError::Http(_) | StellarError::Horizon(_) | StellarError::Signer(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let codes = err.result_codes().cloned();
+        Self(status, err.to_string(), codes)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = json!({ "success": false, "error": self.1 });
+        if let Some(codes) = self.2 {
+            body["resultCodes"] = json!(codes);
+        }
+        (self.0, Json(body)).into_response()
+    }
+}
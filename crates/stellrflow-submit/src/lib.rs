@@ -0,0 +1,38 @@
+//! The bot's Stellar transactions, through one [`SubmissionQueue`].
+//!
+//! `telegram-bot.ts` and `anchor/stellarService.ts` used to load the source
+//! account before every `/send`, `/pay`, workflow payment and anchor
+//! transfer, so
+//! two payments from one wallet, or two credits from the anchor treasury,
+//! could spend the same sequence number and fail with `tx_bad_seq`. They
+//! now ask this service, which holds the one queue they all go through:
+//! it keeps each account's sequence number and submits its transactions
+//! one at a time. `/pay` also gets its quote here rather than working out
+//! paths and slippage itself.
+//!
+//! [`Accounts`] are whom it signs for: keys of its own, such as the
+//! treasury, and the Telegram wallets through the keystore or the signing
+//! server.
+//!
+//! [`api::router`] serves the endpoints; the `stellrflow-submit` binary
+//! runs them on `PORT` (default 3007), and the bot sends through it at
+//! `SUBMIT_URL`.
+
+mod accounts;
+pub mod api;
+
+pub use accounts::Accounts;
+
+use stellrflow_stellar::SubmissionQueue;
+
+/// The queue and whom it signs for, shared by the endpoints.
+pub struct Submitter {
+    pub queue: SubmissionQueue,
+    pub accounts: Accounts,
+}
+
+impl Submitter {
+    pub fn new(queue: SubmissionQueue, accounts: Accounts) -> Self {
+        Submitter { queue, accounts }
+    }
+}
@@ -0,0 +1,81 @@
+//! Serves the submission API.
+//!
+//! - `PORT` (default 3007): the API listener, on `127.0.0.1` only: whoever
+//!   can reach it can spend from every account it signs for.
+//! - `STELLAR_NETWORK` (default `testnet`) and `HORIZON_URL`: as for the
+//!   bot.
+//! - `ANCHOR_TREASURY_SECRET`: the anchor treasury's key, for deposits.
+//!   The treasury also pays for fee bumps of transactions that get stuck.
+//! - `FEE_PERCENTILE` (default 90) and `MAX_FEE` (default 10000 stroops):
+//!   which percentile of recent fees to offer per operation, and the most
+//!   to offer however busy the network is.
+//! - `SIGNER_SOCKET`: the `stellrflow-signer` that signs for the Telegram
+//!   wallets. Without it they are signed from the keystore at
+//!   `WALLETS_FILE` (default `data/wallets.json`) when
+//!   `KEYSTORE_MASTER_KEY` is set, as for `stellrflow-keystore`.
+
+use std::env;
+use std::sync::Arc;
+
+use stellrflow_keystore::Keyring;
+use stellrflow_signer::{network_passphrase, KeystoreWallets, LocalSigner};
+use stellrflow_stellar::{FeeStrategy, Horizon, SubmissionQueue};
+use stellrflow_submit::{api, Accounts, Submitter};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let port: u16 = env::var("PORT")
+        .ok()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(3007);
+    let network = env::var("STELLAR_NETWORK").unwrap_or_else(|_| "testnet".into());
+    let horizon = match env::var("HORIZON_URL") {
+        Ok(url) => Horizon::new(url, network_passphrase(&network)),
+        Err(_) => Horizon::for_network(&network),
+    };
+
+    let defaults = FeeStrategy::default();
+    let fees = FeeStrategy {
+        percentile: match env::var("FEE_PERCENTILE") {
+            Ok(percentile) => percentile.parse()?,
+            Err(_) => defaults.percentile,
+        },
+        max_fee: match env::var("MAX_FEE") {
+            Ok(max_fee) => max_fee.parse()?,
+            Err(_) => defaults.max_fee,
+        },
+    };
+    let mut queue = SubmissionQueue::new(horizon).with_fees(fees);
+
+    let mut accounts = Accounts::new();
+    if let Ok(secret) = env::var("ANCHOR_TREASURY_SECRET") {
+        let treasury = Arc::new(LocalSigner::from_secret(&secret)?);
+        queue = queue.with_fee_sponsor(treasury.clone());
+        accounts = accounts.with_key(treasury);
+    }
+    let wallets = match (env::var("SIGNER_SOCKET"), env::var("KEYSTORE_MASTER_KEY")) {
+        #[cfg(unix)]
+        (Ok(socket), _) => {
+            accounts = accounts.with_wallets(Arc::new(stellrflow_signer::RemoteSigners::new(
+                socket.clone(),
+            )));
+            format!("signer at {socket}")
+        }
+        (_, Ok(_)) => {
+            let path = env::var("WALLETS_FILE").unwrap_or_else(|_| "data/wallets.json".into());
+            let keystore = KeystoreWallets::open(&path, Keyring::from_env()?)?;
+            accounts = accounts.with_wallets(Arc::new(keystore));
+            format!("keystore at {path}")
+        }
+        _ => "none".to_string(),
+    };
+
+    let submitter = Arc::new(Submitter::new(queue, accounts));
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    println!(
+        "StellrFlow submission service running on port {port} (network: {network}, wallets: {wallets}, fees: p{} up to {} stroops)",
+        fees.percentile, fees.max_fee,
+    );
+    axum::serve(listener, api::router(submitter)).await?;
+    Ok(())
+}
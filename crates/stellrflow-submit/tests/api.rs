@@ -0,0 +1,295 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use stellrflow_amount::Amount;
+use stellrflow_signer::{LocalSigner, Signer};
+use stellrflow_stellar::mock::MockHorizon;
+use stellrflow_stellar::{Asset, Backoff, FeeDistribution, FeeStats, FeeStrategy, SubmissionQueue};
+use stellrflow_submit::{api, Accounts, Submitter};
+use tower::ServiceExt;
+
+fn xlm(amount: &str) -> Amount {
+    amount.parse().unwrap()
+}
+
+fn signer(n: u8) -> LocalSigner {
+    LocalSigner::from_seed([n; 32])
+}
+
+/// The service against `horizon`, signing for `keys`.
+fn app(horizon: &MockHorizon, keys: &[u8]) -> Router {
+    serve(queue(horizon), keys)
+}
+
+fn queue(horizon: &MockHorizon) -> SubmissionQueue {
+    SubmissionQueue::new(horizon.client()).with_backoff(Backoff {
+        attempts: 3,
+        initial: Duration::from_millis(1),
+        max: Duration::from_millis(5),
+    })
+}
+
+/// The service sending through `queue`, signing for `keys`.
+fn serve(queue: SubmissionQueue, keys: &[u8]) -> Router {
+    let accounts = keys.iter().fold(Accounts::new(), |accounts, &n| {
+        accounts.with_key(Arc::new(signer(n)))
+    });
+    api::router(Arc::new(Submitter::new(queue, accounts)))
+}
+
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    respond(app, request).await
+}
+
+async fn post(app: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    respond(app, request).await
+}
+
+async fn respond(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+/// Fee stats whose p90 is `p10 + 80`.
+fn fee_stats(p10: u64) -> FeeStats {
+    let decile = |n: u64| p10 + 10 * (n - 1);
+    let fees = FeeDistribution {
+        max: 5_000,
+        min: p10,
+        mode: p10,
+        p10: decile(1),
+        p20: decile(2),
+        p30: decile(3),
+        p40: decile(4),
+        p50: decile(5),
+        p60: decile(6),
+        p70: decile(7),
+        p80: decile(8),
+        p90: decile(9),
+        p95: 1_000,
+        p99: 2_000,
+    };
+    FeeStats {
+        last_ledger: 1,
+        last_ledger_base_fee: 100,
+        ledger_capacity_usage: 0.97,
+        fee_charged: fees,
+        max_fee: fees,
+    }
+}
+
+fn send(source: u8, destination: u8, amount: &str) -> Value {
+    json!({
+        "source": signer(source).public_key(),
+        "destination": signer(destination).public_key(),
+        "amount": amount,
+    })
+}
+
+#[tokio::test]
+async fn sends_from_one_account_at_once_take_turns() {
+    let horizon = MockHorizon::start().await;
+    let treasury = signer(1);
+    horizon.fund(treasury.public_key(), xlm("1000"));
+    horizon.fund(signer(2).public_key(), xlm("10"));
+    let start = horizon.sequence(treasury.public_key()).unwrap();
+    let app = app(&horizon, &[1]);
+
+    // Anchor credits and a /send from the treasury, all at once.
+    let (paid, created, credited) = tokio::join!(
+        post(&app, "/api/submit/send", send(1, 2, "5")),
+        post(&app, "/api/submit/send", send(1, 3, "2")),
+        post(&app, "/api/submit/send", send(1, 4, "2.5")),
+    );
+    for (status, body) in [&paid, &created, &credited] {
+        assert_eq!(*status, StatusCode::OK, "{body}");
+        assert_eq!(body["success"], true);
+    }
+    assert_eq!(paid.1["createdAccount"], false);
+    assert_eq!(created.1["createdAccount"], true);
+    assert!(paid.1["hash"].as_str().is_some_and(|hash| hash.len() == 64));
+    assert_eq!(paid.1["feeBump"], Value::Null);
+
+    assert_eq!(horizon.sequence(treasury.public_key()), Some(start + 3));
+    assert_eq!(horizon.balance(signer(2).public_key()), Some(xlm("15")));
+    assert_eq!(horizon.balance(signer(4).public_key()), Some(xlm("2.5")));
+}
+
+#[tokio::test]
+async fn refuses_accounts_it_does_not_sign_for() {
+    let horizon = MockHorizon::start().await;
+    horizon.fund(signer(2).public_key(), xlm("100"));
+    let app = app(&horizon, &[1]);
+
+    let (status, body) = post(&app, "/api/submit/send", send(2, 1, "1")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["success"], false);
+    assert!(body["error"].as_str().unwrap().contains("no signer"));
+}
+
+#[tokio::test]
+async fn reports_why_a_payment_was_refused() {
+    let horizon = MockHorizon::start().await;
+    horizon.fund(signer(1).public_key(), xlm("100"));
+    let app = app(&horizon, &[1]);
+
+    let (status, body) = post(&app, "/api/submit/send", send(1, 2, "0.5")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "Minimum 1 XLM required to create new account"
+    );
+
+    horizon.fund(signer(2).public_key(), xlm("1"));
+    horizon.reject_next("tx_insufficient_balance");
+    let (status, body) = post(&app, "/api/submit/send", send(1, 2, "1")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(
+        body["resultCodes"]["transaction"],
+        "tx_insufficient_balance"
+    );
+}
+
+#[tokio::test]
+async fn suggests_the_fee_its_strategy_picks() {
+    let horizon = MockHorizon::start().await;
+    horizon.set_fee_stats(fee_stats(300));
+
+    let (status, body) = get(&app(&horizon, &[]), "/api/submit/fee").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        (body["fee"].as_u64(), body["maxFee"].as_u64()),
+        (Some(380), Some(10_000))
+    );
+
+    let capped = serve(
+        queue(&horizon).with_fees(FeeStrategy {
+            percentile: 99,
+            max_fee: 1_500,
+        }),
+        &[],
+    );
+    let (_, body) = get(&capped, "/api/submit/fee").await;
+    assert_eq!(body["fee"], 1_500);
+}
+
+#[tokio::test]
+async fn stuck_payments_are_fee_bumped_by_the_treasury() {
+    let horizon = MockHorizon::start().await;
+    let treasury = Arc::new(signer(1));
+    horizon.fund(treasury.public_key(), xlm("100"));
+    horizon.fund(signer(2).public_key(), xlm("100"));
+    horizon.fund(signer(3).public_key(), xlm("10"));
+    // The ledgers charge 1000 per operation but the stats say 180.
+    horizon.set_surge_fee(1_000);
+    horizon.set_fee_stats(fee_stats(100));
+    let app = serve(queue(&horizon).with_fee_sponsor(treasury.clone()), &[1, 2]);
+
+    let (status, body) = post(&app, "/api/submit/send", send(2, 3, "1")).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["fee"], 180);
+    assert_eq!(body["feeCharged"], 2_000);
+    assert_eq!(body["feeBump"]["feeSource"], treasury.public_key());
+    assert_eq!(body["feeBump"]["fee"], 1_800);
+    assert_eq!(horizon.balance(signer(3).public_key()), Some(xlm("11")));
+    assert_eq!(horizon.balance(treasury.public_key()), Some(xlm("99.9998")));
+}
+
+#[tokio::test]
+async fn pays_in_an_asset_over_the_quoted_path() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice, bob) = (signer(1), signer(2), signer(3));
+    for account in [&issuer, &alice, &bob] {
+        horizon.fund(account.public_key(), xlm("1000"));
+    }
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let eurc = Asset::issued("EURC", issuer.public_key()).unwrap();
+    horizon
+        .client()
+        .change_trust(&bob, &usdc, None)
+        .await
+        .unwrap();
+    // 1 XLM buys 0.125 USDC straight, or 0.14 through EURC.
+    horizon.set_price(&Asset::Native, &usdc, "0.125");
+    horizon.set_price(&Asset::Native, &eurc, "0.2");
+    horizon.set_price(&eurc, &usdc, "0.7");
+    let app = app(&horizon, &[2]);
+    let pay = json!({ "destAsset": usdc.to_string(), "amount": "10" });
+
+    let (status, quote) = post(&app, "/api/submit/quote", pay.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{quote}");
+    assert_eq!(quote["sendAsset"], "native");
+    assert_eq!(quote["sourceAmount"], "71.4285715");
+    assert_eq!(quote["mostSent"], "72.1428573");
+    assert_eq!(quote["rate"], "0.1399999");
+    assert_eq!(quote["path"], json!([eurc.to_string()]));
+
+    let mut payment = pay.clone();
+    payment["source"] = json!(alice.public_key());
+    payment["destination"] = json!(bob.public_key());
+    let (status, paid) = post(&app, "/api/submit/path-payment", payment).await;
+    assert_eq!(status, StatusCode::OK, "{paid}");
+    assert_eq!(paid["destinationAmount"], "10");
+    assert_eq!(paid["sourceAmount"], "71.4285715");
+    assert_eq!(paid["quotedRate"], "0.1399999");
+    assert!(paid["hash"].as_str().is_some_and(|hash| hash.len() == 64));
+    assert_eq!(
+        horizon.asset_balance(bob.public_key(), &usdc),
+        Some(xlm("10"))
+    );
+
+    // A tighter slippage shrinks what the payment may send.
+    let mut tight = pay;
+    tight["slippage"] = json!("0.5%");
+    let (_, quote) = post(&app, "/api/submit/quote", tight).await;
+    assert_eq!(quote["mostSent"], "71.7857144");
+}
+
+#[tokio::test]
+async fn reports_why_a_path_payment_cannot_be_made() {
+    let horizon = MockHorizon::start().await;
+    let (issuer, alice) = (signer(1), signer(2));
+    horizon.fund(issuer.public_key(), xlm("1000"));
+    horizon.fund(alice.public_key(), xlm("1000"));
+    let usdc = Asset::issued("USDC", issuer.public_key()).unwrap();
+    let app = app(&horizon, &[2]);
+
+    let quote = |dest_asset: &str, slippage: &str| json!({ "destAsset": dest_asset, "amount": "10", "slippage": slippage });
+    let (status, body) = post(&app, "/api/submit/quote", quote("USDC", "1")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "invalid asset `USDC`: expected `CODE:ISSUER`"
+    );
+    let (status, body) = post(&app, "/api/submit/quote", quote(&usdc.to_string(), "101")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid slippage"));
+    let (status, body) = post(&app, "/api/submit/quote", quote(&usdc.to_string(), "1")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "no path found from XLM to USDC");
+
+    // Path payments cannot create the destination.
+    horizon.set_price(&Asset::Native, &usdc, "0.125");
+    let mut payment = quote(&usdc.to_string(), "1");
+    payment["source"] = json!(alice.public_key());
+    payment["destination"] = json!(signer(3).public_key());
+    let (status, body) = post(&app, "/api/submit/path-payment", payment).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+}
@@ -0,0 +1,42 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TemplateError {
+    /// A malformed placeholder, with the 1-based column it starts at.
+    #[error("{message} at column {column}")]
+    Syntax { message: String, column: usize },
+    /// A placeholder path that the payload does not contain. `available`
+    /// lists the keys that were present where the lookup failed.
+    #[error("unknown variable `{path}`{}", hint(available))]
+    UnknownVariable {
+        path: String,
+        available: Vec<String>,
+    },
+    /// A filter could not handle its input.
+    #[error("`{filter}` filter: {message}")]
+    Filter {
+        filter: &'static str,
+        message: String,
+    },
+}
+
+impl TemplateError {
+    pub(crate) fn syntax(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        Self::Syntax {
+            message: message.into(),
+            column: source[..offset].chars().count() + 1,
+        }
+    }
+}
+
+fn hint(available: &[String]) -> String {
+    if available.is_empty() {
+        String::new()
+    } else {
+        format!(" (available: {})", available.join(", "))
+    }
+}
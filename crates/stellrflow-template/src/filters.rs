@@ -0,0 +1,215 @@
+use std::str::FromStr;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde_json::Value;
+
+use crate::error::TemplateError;
+
+/// Filter names and what they do, for help text.
+pub const FILTERS: &[(&str, &str)] = &[
+    (
+        "amount",
+        "Group thousands and drop trailing zeros; `amount(2)` fixes the decimals",
+    ),
+    ("short", "Shorten an address to `GABCDEFG...STUVWXYZ`"),
+    (
+        "date",
+        "Format a Unix timestamp in ms or an RFC 3339 date; `date(\"%d %b\")` takes a strftime pattern",
+    ),
+    ("markdown", "Escape Telegram Markdown"),
+    ("html", "Escape HTML"),
+    ("upper", "Upper-case"),
+    ("lower", "Lower-case"),
+    (
+        "default",
+        "`default(\"text\")` replaces a missing or null value",
+    ),
+];
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+
+/// A literal filter argument.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Arg {
+    Str(String),
+    Int(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Filter {
+    Amount(Option<u32>),
+    Short,
+    Date(String),
+    Markdown,
+    Html,
+    Upper,
+    Lower,
+    Default(String),
+}
+
+impl Filter {
+    pub(crate) fn new(name: &str, args: Vec<Arg>) -> Result<Self, String> {
+        let filter = match (name, args.as_slice()) {
+            ("amount", []) => Filter::Amount(None),
+            ("amount", [Arg::Int(places)]) if *places <= 18 => Filter::Amount(Some(*places)),
+            ("amount", _) => return Err("`amount` takes a number of decimals from 0 to 18".into()),
+            ("date", []) => Filter::Date(DEFAULT_DATE_FORMAT.into()),
+            ("date", [Arg::Str(format)]) => {
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("invalid date format {format:?}"));
+                }
+                Filter::Date(format.clone())
+            }
+            ("date", _) => return Err("`date` takes one quoted format".into()),
+            ("default", [Arg::Str(fallback)]) => Filter::Default(fallback.clone()),
+            ("default", _) => return Err("`default` takes one quoted fallback".into()),
+            ("short", []) => Filter::Short,
+            ("markdown", []) => Filter::Markdown,
+            ("html", []) => Filter::Html,
+            ("upper", []) => Filter::Upper,
+            ("lower", []) => Filter::Lower,
+            (name, _) if FILTERS.iter().any(|(f, _)| *f == name) => {
+                return Err(format!("`{name}` takes no arguments"))
+            }
+            (name, _) => {
+                let known: Vec<&str> = FILTERS.iter().map(|(f, _)| *f).collect();
+                return Err(format!(
+                    "unknown filter `{name}` (expected one of: {})",
+                    known.join(", ")
+                ));
+            }
+        };
+        Ok(filter)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Filter::Amount(_) => "amount",
+            Filter::Short => "short",
+            Filter::Date(_) => "date",
+            Filter::Markdown => "markdown",
+            Filter::Html => "html",
+            Filter::Upper => "upper",
+            Filter::Lower => "lower",
+            Filter::Default(_) => "default",
+        }
+    }
+
+    pub(crate) fn apply(&self, value: Value) -> Result<Value, TemplateError> {
+        let fail = |message: String| TemplateError::Filter {
+            filter: self.name(),
+            message,
+        };
+        let text = crate::text(&value);
+        Ok(Value::String(match self {
+            Filter::Amount(places) => {
+                let number = decimal(&value)
+                    .ok_or_else(|| fail(format!("expected a number, got {value}")))?;
+                amount(number, *places)
+            }
+            Filter::Short => shorten(&text),
+            Filter::Date(format) => {
+                let date = datetime(&value).ok_or_else(|| {
+                    fail(format!(
+                        "expected a timestamp in ms or an RFC 3339 date, got {value}"
+                    ))
+                })?;
+                date.format(format).to_string()
+            }
+            Filter::Markdown => escape(&text, |c| matches!(c, '_' | '*' | '`' | '[')),
+            Filter::Html => {
+                let mut out = String::with_capacity(text.len());
+                for c in text.chars() {
+                    match c {
+                        '&' => out.push_str("&amp;"),
+                        '<' => out.push_str("&lt;"),
+                        '>' => out.push_str("&gt;"),
+                        '"' => out.push_str("&quot;"),
+                        c => out.push(c),
+                    }
+                }
+                out
+            }
+            Filter::Upper => text.to_uppercase(),
+            Filter::Lower => text.to_lowercase(),
+            // Only reached for present values.
+            Filter::Default(_) => return Ok(value),
+        }))
+    }
+}
+
+/// `G1234567...89ABCDEF`, as shown in Telegram messages.
+pub fn shorten(address: &str) -> String {
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() <= 16 {
+        return address.to_string();
+    }
+    let head: String = chars[..8].iter().collect();
+    let tail: String = chars[chars.len() - 8..].iter().collect();
+    format!("{head}...{tail}")
+}
+
+fn decimal(value: &Value) -> Option<Decimal> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
+}
+
+/// `1234.5` → `1,234.5`, or `1,234.50` with two places.
+fn amount(number: Decimal, places: Option<u32>) -> String {
+    let number = match places {
+        Some(places) => {
+            let mut rounded =
+                number.round_dp_with_strategy(places, RoundingStrategy::MidpointAwayFromZero);
+            rounded.rescale(places);
+            rounded
+        }
+        None => number.normalize(),
+    };
+    let text = number.abs().to_string();
+    let (int, frac) = text.split_once('.').unwrap_or((&text, ""));
+
+    let mut out = String::new();
+    if number.is_sign_negative() && !number.is_zero() {
+        out.push('-');
+    }
+    for (i, digit) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    if !frac.is_empty() {
+        out.push('.');
+        out.push_str(frac);
+    }
+    out
+}
+
+fn datetime(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(n) => DateTime::from_timestamp_millis(n.as_i64()?),
+        Value::String(s) => DateTime::parse_from_rfc3339(s.trim())
+            .ok()
+            .map(|date| date.with_timezone(&Utc)),
+        _ => None,
+    }
+}
+
+fn escape(text: &str, special: impl Fn(char) -> bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
@@ -0,0 +1,154 @@
+//! Message templates for `telegram-send` and other notification nodes.
+//!
+//! A template is plain text with `{{ … }}` placeholders that are resolved
+//! against the payload a node receives (the merged outputs of everything
+//! upstream):
+//!
+//! ```text
+//! ✅ Sent {{ amount | amount(2) }} XLM to {{ destination | short }}
+//! Tx: {{ stellarTxHash }} at {{ createdAt | date("%H:%M") }}
+//! First balance: {{ result.balances.0.balance | amount }}
+//! Memo: {{ memo | default("none") | markdown }}
+//! ```
+//!
+//! - A path is dot-separated; numeric segments index into lists.
+//! - Filters run left to right; see [`FILTERS`].
+//! - A path that does not resolve is an error naming the missing variable,
+//!   unless a `default` filter supplies a fallback.
+//! - `{{ "{{" }}` writes literal braces.
+//!
+//! Templates are parsed once, so syntax errors and unknown filters surface
+//! when a workflow is validated rather than when the message is sent.
+
+mod error;
+mod filters;
+mod parse;
+
+pub use error::TemplateError;
+pub use filters::{shorten, FILTERS};
+
+use serde_json::{Map, Value};
+
+use crate::filters::Filter;
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Placeholder {
+    operand: Operand,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Path(Vec<String>),
+    Literal(String),
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        parse::parse(source).map(|segments| Self { segments })
+    }
+
+    /// Every variable path the template reads, in order of appearance.
+    pub fn variables(&self) -> impl Iterator<Item = String> + '_ {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Placeholder(Placeholder {
+                operand: Operand::Path(path),
+                ..
+            }) => Some(path.join(".")),
+            _ => None,
+        })
+    }
+
+    pub fn render(&self, payload: &Map<String, Value>) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Placeholder(placeholder) => {
+                    out.push_str(&placeholder.render(payload)?);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl std::str::FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+/// Parse and render `source` in one go.
+pub fn render(source: &str, payload: &Map<String, Value>) -> Result<String, TemplateError> {
+    Template::parse(source)?.render(payload)
+}
+
+impl Placeholder {
+    fn render(&self, payload: &Map<String, Value>) -> Result<String, TemplateError> {
+        let mut value = match &self.operand {
+            Operand::Literal(text) => Ok(Value::String(text.clone())),
+            Operand::Path(path) => resolve(payload, path),
+        };
+        for filter in &self.filters {
+            value = match (filter, value) {
+                (Filter::Default(fallback), Err(_) | Ok(Value::Null)) => {
+                    Ok(Value::String(fallback.clone()))
+                }
+                (filter, Ok(v)) => Ok(filter.apply(v)?),
+                (_, Err(err)) => return Err(err),
+            };
+        }
+        value.map(|v| text(&v))
+    }
+}
+
+/// Walk `path` from the payload root.
+fn resolve(payload: &Map<String, Value>, path: &[String]) -> Result<Value, TemplateError> {
+    let keys = |value: &Value| match value {
+        Value::Object(map) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    let mut current = payload
+        .get(&path[0])
+        .ok_or_else(|| TemplateError::UnknownVariable {
+            path: path[0].clone(),
+            available: payload.keys().cloned().collect(),
+        })?;
+    for (i, key) in path.iter().enumerate().skip(1) {
+        let next = match current {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        current = next.ok_or_else(|| TemplateError::UnknownVariable {
+            path: path[..=i].join("."),
+            available: keys(current),
+        })?;
+    }
+    Ok(current.clone())
+}
+
+/// How a value reads in a message. `null` is empty; lists and objects are
+/// compact JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::TemplateError;
+use crate::filters::{Arg, Filter};
+use crate::{Operand, Placeholder, Segment};
+
+pub(crate) fn parse(source: &str) -> Result<Vec<Segment>, TemplateError> {
+    let mut segments = Vec::new();
+    let mut rest = 0;
+    while let Some(found) = source[rest..].find("{{") {
+        let open = rest + found;
+        if open > rest {
+            segments.push(Segment::Text(source[rest..open].to_string()));
+        }
+        let mut scanner = Scanner {
+            source,
+            pos: open + 2,
+            open,
+        };
+        segments.push(Segment::Placeholder(scanner.placeholder()?));
+        rest = scanner.pos;
+    }
+    if rest < source.len() {
+        segments.push(Segment::Text(source[rest..].to_string()));
+    }
+    Ok(segments)
+}
+
+/// Reads one placeholder, starting just inside its `{{`.
+struct Scanner<'a> {
+    source: &'a str,
+    pos: usize,
+    /// Offset of the `{{`, for errors about the placeholder as a whole.
+    open: usize,
+}
+
+impl Scanner<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.source.len() - trimmed.len();
+    }
+
+    fn error(&self, message: impl Into<String>) -> TemplateError {
+        TemplateError::syntax(self.source, self.pos, message)
+    }
+
+    fn unclosed(&self) -> TemplateError {
+        TemplateError::syntax(self.source, self.open, "unclosed `{{`")
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn placeholder(&mut self) -> Result<Placeholder, TemplateError> {
+        self.skip_whitespace();
+        let operand = match self.peek() {
+            None => return Err(self.unclosed()),
+            Some('"' | '\'') => Operand::Literal(self.string()?),
+            Some(_) if self.rest().starts_with("}}") => {
+                return Err(TemplateError::syntax(
+                    self.source,
+                    self.open,
+                    "empty placeholder",
+                ))
+            }
+            Some(_) => Operand::Path(self.path()?),
+        };
+
+        let mut filters = Vec::new();
+        loop {
+            if self.eat("}}") {
+                return Ok(Placeholder { operand, filters });
+            }
+            if self.peek().is_none() {
+                return Err(self.unclosed());
+            }
+            if !self.eat("|") {
+                let found = self.peek().unwrap_or_default();
+                return Err(self.error(format!("expected `|` or `}}}}`, found {found:?}")));
+            }
+            filters.push(self.filter()?);
+        }
+    }
+
+    fn word(&mut self) -> &str {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.source[start..self.pos]
+    }
+
+    fn path(&mut self) -> Result<Vec<String>, TemplateError> {
+        let mut path = Vec::new();
+        loop {
+            let segment = self.word();
+            if segment.is_empty() {
+                return Err(match self.peek() {
+                    None => self.unclosed(),
+                    Some(c) => self.error(format!("expected a variable name, found {c:?}")),
+                });
+            }
+            path.push(segment.to_string());
+            if !self.rest().starts_with('.') {
+                return Ok(path);
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn filter(&mut self) -> Result<Filter, TemplateError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let name = self.word().to_string();
+        if name.is_empty() {
+            return Err(self.error("expected a filter name after `|`"));
+        }
+        let mut args = Vec::new();
+        if self.eat("(") && !self.eat(")") {
+            loop {
+                args.push(self.arg()?);
+                if self.eat(")") {
+                    break;
+                }
+                if !self.eat(",") {
+                    return Err(self.error("expected `,` or `)`"));
+                }
+            }
+        }
+        Filter::new(&name, args)
+            .map_err(|message| TemplateError::syntax(self.source, start, message))
+    }
+
+    fn arg(&mut self) -> Result<Arg, TemplateError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"' | '\'') => Ok(Arg::Str(self.string()?)),
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                let digits = self.word();
+                digits.parse().map(Arg::Int).map_err(|_| {
+                    TemplateError::syntax(self.source, start, "expected a whole number")
+                })
+            }
+            None => Err(self.unclosed()),
+            Some(c) => Err(self.error(format!("expected a quoted string or number, found {c:?}"))),
+        }
+    }
+
+    /// A quoted string with `\"`, `\'`, `\\` and `\n` escapes.
+    fn string(&mut self) -> Result<String, TemplateError> {
+        let start = self.pos;
+        let mut chars = self.rest().char_indices();
+        let (_, quote) = chars.next().unwrap_or_default();
+        let mut text = String::new();
+        while let Some((at, c)) = chars.next() {
+            match c {
+                c if c == quote => {
+                    self.pos += at + 1;
+                    return Ok(text);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => text.push('\n'),
+                    Some((_, c)) => text.push(c),
+                    None => break,
+                },
+                c => text.push(c),
+            }
+        }
+        Err(TemplateError::syntax(
+            self.source,
+            start,
+            "unterminated string",
+        ))
+    }
+}
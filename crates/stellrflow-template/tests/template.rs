@@ -0,0 +1,167 @@
+use serde_json::{json, Map, Value};
+use stellrflow_template::{render, shorten, Template, TemplateError};
+
+const ADDRESS: &str = "GBRPYHIL2CI3FNQ4BXLFMNDLFJUNPU2HY3ZMFSHONUCEOASW7QC7OX2H";
+
+fn payload() -> Map<String, Value> {
+    let Value::Object(map) = json!({
+        "balance": "12345.6700000",
+        "amount": 2.5,
+        "destination": ADDRESS,
+        "scheduleId": "sched_1",
+        "createdAt": 1_700_000_000_000_i64,
+        "settledAt": "2024-03-01T09:30:00+02:00",
+        "memo": "rent_march *paid*",
+        "note": null,
+        "result": { "balances": [{ "asset": "XLM", "balance": "100.0000000" }] },
+    }) else {
+        unreachable!()
+    };
+    map
+}
+
+fn ok(source: &str) -> String {
+    render(source, &payload()).unwrap()
+}
+
+fn err(source: &str) -> String {
+    render(source, &payload()).unwrap_err().to_string()
+}
+
+#[test]
+fn substitutes_paths() {
+    assert_eq!(ok("Schedule {{scheduleId}} ok"), "Schedule sched_1 ok");
+    assert_eq!(ok("{{ result.balances.0.asset }}"), "XLM");
+    assert_eq!(ok("{{amount}} / {{note}}"), "2.5 / ");
+    assert_eq!(
+        ok("{{ result.balances }}"),
+        r#"[{"asset":"XLM","balance":"100.0000000"}]"#
+    );
+    assert_eq!(ok("no placeholders }} {here}"), "no placeholders }} {here}");
+    assert_eq!(ok(r#"{{ "{{" }}literal}}"#), "{{literal}}");
+}
+
+#[test]
+fn formats_amounts() {
+    assert_eq!(ok("{{ balance | amount }}"), "12,345.67");
+    assert_eq!(ok("{{ balance | amount(2) }}"), "12,345.67");
+    assert_eq!(ok("{{ amount | amount(0) }}"), "3");
+    assert_eq!(ok("{{ result.balances.0.balance | amount(1) }}"), "100.0");
+    assert_eq!(
+        render(
+            "{{ x | amount }}",
+            &json!({ "x": "-1234567.891" }).as_object().unwrap().clone()
+        )
+        .unwrap(),
+        "-1,234,567.891"
+    );
+}
+
+#[test]
+fn shortens_addresses() {
+    assert_eq!(ok("{{ destination | short }}"), "GBRPYHIL...7QC7OX2H");
+    assert_eq!(
+        shorten(ADDRESS),
+        format!("{}...{}", &ADDRESS[..8], &ADDRESS[48..])
+    );
+    assert_eq!(shorten("GSHORT"), "GSHORT");
+}
+
+#[test]
+fn formats_dates() {
+    assert_eq!(ok("{{ createdAt | date }}"), "2023-11-14 22:13 UTC");
+    assert_eq!(
+        ok(r#"{{ settledAt | date("%d %b %H:%M") }}"#),
+        "01 Mar 07:30"
+    );
+}
+
+#[test]
+fn escapes_markup() {
+    assert_eq!(ok("{{ memo | markdown }}"), r"rent\_march \*paid\*");
+    let html = json!({ "x": "<b>\"a&b\"</b>" });
+    assert_eq!(
+        render("{{ x | html }}", html.as_object().unwrap()).unwrap(),
+        "&lt;b&gt;&quot;a&amp;b&quot;&lt;/b&gt;"
+    );
+    assert_eq!(ok("{{ scheduleId | upper }}"), "SCHED_1");
+}
+
+#[test]
+fn defaults_cover_missing_values() {
+    assert_eq!(ok(r#"{{ stellarTxHash | default("pending") }}"#), "pending");
+    assert_eq!(ok(r#"{{ note | default("-") }}"#), "-");
+    assert_eq!(ok(r#"{{ fiatPayout | default("0") | amount(2) }}"#), "0.00");
+    assert_eq!(ok(r#"{{ scheduleId | default("x") }}"#), "sched_1");
+}
+
+#[test]
+fn reports_unknown_variables() {
+    assert_eq!(
+        render(
+            "Tx {{ stellarTxHash }}",
+            &json!({ "chatId": 1, "amount": 2 })
+                .as_object()
+                .unwrap()
+                .clone()
+        )
+        .unwrap_err(),
+        TemplateError::UnknownVariable {
+            path: "stellarTxHash".into(),
+            available: vec!["amount".into(), "chatId".into()],
+        }
+    );
+    assert_eq!(
+        err("{{ result.balances.3.asset }}"),
+        "unknown variable `result.balances.3`"
+    );
+    assert_eq!(
+        err("{{ result.total }}"),
+        "unknown variable `result.total` (available: balances)"
+    );
+    assert_eq!(
+        err("{{ missing | upper }}").split(" (").next().unwrap(),
+        "unknown variable `missing`"
+    );
+}
+
+#[test]
+fn reports_syntax_errors() {
+    let syntax = |source: &str| Template::parse(source).unwrap_err().to_string();
+    assert_eq!(syntax("Hi {{ name"), "unclosed `{{` at column 4");
+    assert_eq!(syntax("{{ }}"), "empty placeholder at column 1");
+    assert_eq!(
+        syntax("{{ amount | shout }}"),
+        "unknown filter `shout` (expected one of: amount, short, date, markdown, html, upper, lower, default) at column 13"
+    );
+    assert_eq!(
+        syntax("{{ amount | amount(\"2\") }}"),
+        "`amount` takes a number of decimals from 0 to 18 at column 13"
+    );
+    assert_eq!(
+        syntax(r#"{{ d | date("%Q") }}"#),
+        "invalid date format \"%Q\" at column 8"
+    );
+    assert_eq!(
+        syntax("{{ a b }}"),
+        "expected `|` or `}}`, found 'b' at column 6"
+    );
+}
+
+#[test]
+fn filter_errors_name_the_filter() {
+    assert_eq!(
+        err("{{ scheduleId | amount }}"),
+        "`amount` filter: expected a number, got \"sched_1\""
+    );
+    assert_eq!(
+        err("{{ memo | date }}"),
+        "`date` filter: expected a timestamp in ms or an RFC 3339 date, got \"rent_march *paid*\""
+    );
+}
+
+#[test]
+fn lists_variables() {
+    let template = Template::parse("{{ a.b }} {{ 'x' }} {{ c | default('') }}").unwrap();
+    assert_eq!(template.variables().collect::<Vec<_>>(), ["a.b", "c"]);
+}
@@ -23,9 +23,13 @@ export function NavBar() {
 
   const { saveWorkflow, loadWorkflow } = useWorkflowStore();
 
-  const handleSave = () => {
-    saveWorkflow();
-    toast.success("Workflow saved successfully");
+  const handleSave = async () => {
+    try {
+      await saveWorkflow();
+      toast.success("Workflow saved successfully");
+    } catch (error: any) {
+      toast.error(error.message || "Failed to save workflow");
+    }
   };
 
   const handleRun = () => {
@@ -4,7 +4,7 @@ import { Handle, Position, NodeProps } from "@reactflow/core";
 import { motion } from "framer-motion";
 import { NodeData, useWorkflowStore } from "@/lib/stores/workflow-store";
 import { getIconByName } from "@/lib/utils/icons";
-import { Loader2, CheckCircle2, AlertCircle } from "lucide-react";
+import { Loader2, CheckCircle2, AlertCircle, MinusCircle } from "lucide-react";
 
 export function CustomNode({ data, id, selected }: NodeProps<NodeData>) {
     const Icon = getIconByName(data.icon);
@@ -23,6 +23,8 @@ export function CustomNode({ data, id, selected }: NodeProps<NodeData>) {
                 return <CheckCircle2 className="h-4 w-4 text-green-500" />;
             case "error":
                 return <AlertCircle className="h-4 w-4 text-red-500" />;
+            case "skipped":
+                return <MinusCircle className="h-4 w-4 text-muted-foreground" />;
             default:
                 return null;
         }
@@ -69,14 +71,36 @@ export function CustomNode({ data, id, selected }: NodeProps<NodeData>) {
                 isConnectable={true}
             />
 
-            {/* Output handle on the right */}
-            <Handle 
-                type="source" 
-                position={Position.Right} 
-                id="out"
-                className="!w-3 !h-3 !bg-primary !border-2 !border-background hover:!bg-accent !-right-1.5"
-                isConnectable={true}
-            />
+            {data.type === "condition" ? (
+                <>
+                    {/* One output per branch; edges keep the handle id */}
+                    <Handle
+                        type="source"
+                        position={Position.Right}
+                        id="true"
+                        style={{ top: "35%" }}
+                        className="!w-3 !h-3 !bg-green-500 !border-2 !border-background hover:!bg-accent !-right-1.5"
+                        isConnectable={true}
+                    />
+                    <Handle
+                        type="source"
+                        position={Position.Right}
+                        id="false"
+                        style={{ top: "70%" }}
+                        className="!w-3 !h-3 !bg-red-500 !border-2 !border-background hover:!bg-accent !-right-1.5"
+                        isConnectable={true}
+                    />
+                </>
+            ) : (
+                /* Output handle on the right */
+                <Handle 
+                    type="source" 
+                    position={Position.Right} 
+                    id="out"
+                    className="!w-3 !h-3 !bg-primary !border-2 !border-background hover:!bg-accent !-right-1.5"
+                    isConnectable={true}
+                />
+            )}
         </motion.div>
     );
 }
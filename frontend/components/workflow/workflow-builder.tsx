@@ -18,6 +18,7 @@ import { NodeTypesSidebar } from "./node-types-sidebar";
 import { PropertiesPanel } from "./properties-panel";
 import { CustomNode } from "./nodes/custom-node";
 import { Button } from "@/components/ui/button";
+import { toast } from "sonner";
 import { Play, Square, Save, Download } from "lucide-react";
 import "@reactflow/core/dist/style.css";
 
@@ -159,8 +160,13 @@ function WorkflowCanvas() {
         stopWorkflow();
     }, [stopWorkflow]);
 
-    const handleSaveWorkflow = useCallback(() => {
-        saveWorkflow();
+    const handleSaveWorkflow = useCallback(async () => {
+        try {
+            await saveWorkflow();
+            toast.success("Workflow saved successfully");
+        } catch (error: any) {
+            toast.error(error.message || "Failed to save workflow");
+        }
     }, [saveWorkflow]);
 
     const handleLoadWorkflow = useCallback(() => {
@@ -23,6 +23,8 @@ export type NodeData = {
   icon: string;
   description: string;
   config: Record<string, any>;
+  /** Schema version of `config`; absent on configs saved before versioning. */
+  configVersion?: number;
 };
 
 type WorkflowState = {
@@ -33,10 +35,15 @@ type WorkflowState = {
   isWorkflowRunning: boolean;
   nodeExecutionState: Record<
     string,
-    "pending" | "running" | "success" | "error"
+    "pending" | "running" | "success" | "error" | "skipped"
   >;
   nodeResults: Record<string, any>;
 
+  // The stored workflow being edited, once it has been saved or loaded
+  workflowId: number | null;
+  workflowOwner: string | null;
+  workflowVersion: number | null;
+
   setNodes: (nodes: Node<NodeData>[]) => void;
   onNodesChange: (changes: NodeChange[]) => void;
   addNode: (nodeType: string, position: XYPosition) => void;
@@ -61,8 +68,8 @@ type WorkflowState = {
   stopWorkflow: () => Promise<void>;
   executeNode: (nodeId: string, inputData?: any) => Promise<any>;
 
-  saveWorkflow: () => void;
-  loadWorkflow: () => void;
+  saveWorkflow: () => Promise<void>;
+  loadWorkflow: () => Promise<void>;
 };
 
 export const NODE_TYPES = {
@@ -131,11 +138,12 @@ export const NODE_TYPES = {
         type: "telegram-send",
         label: "Send Telegram",
         icon: "messageCircle",
-        description: "Send a message to Telegram. Use {balance}, {address} for templates.",
+        description: "Send a message to Telegram. Use {{balance | amount}}, {{address | short}} for templates.",
         config: {
           chatId: "",
           message: "",
         },
+        configVersion: 2,
       },
       {
         type: "anchor-onramp",
@@ -167,11 +175,12 @@ export const NODE_TYPES = {
         icon: "repeat",
         description: "Set up recurring XLM payments at regular intervals from your Telegram wallet",
         config: {
-          destinationAddress: "",
+          destination: "",
           amount: "",
-          interval: "3600s",
-          totalDuration: "24h",
+          interval: "daily",
+          duration: "30d",
         },
+        configVersion: 2,
       },
       {
         type: "multisig",
@@ -179,10 +188,12 @@ export const NODE_TYPES = {
         icon: "shield",
         description: "Require multiple wallet signers to approve a transaction before execution",
         config: {
-          signerAddresses: "",
-          approvalTimeout: "300s",
-          autoExecute: "false",
+          signers: [],
+          threshold: 2,
+          timeout: "24h",
+          autoExecute: false,
         },
+        configVersion: 2,
       },
     ],
   },
@@ -198,27 +209,12 @@ export const NODE_TYPES = {
         config: { delay: 5 },
       },
       {
-        type: "autopay",
-        label: "AutoPay",
-        icon: "repeat",
-        description: "Set up recurring scheduled payments",
-        config: {
-          destination: "",
-          amount: "",
-          interval: "daily",
-          duration: 30,
-        },
-      },
-      {
-        type: "multisig",
-        label: "Multisig",
-        icon: "users",
-        description: "Require multiple approvals before execution",
-        config: {
-          threshold: 2,
-          signers: [],
-          timeout: 24,
-        },
+        type: "condition",
+        label: "Condition",
+        icon: "gitBranch",
+        description: "Branch on an expression like `balance < 50`",
+        config: { expression: "" },
+        configVersion: 1,
       },
     ],
   },
@@ -234,6 +230,16 @@ export const getNodeDataFromType = (nodeType: string): NodeData | null => {
   return null;
 };
 
+// Remembers which stored workflow to reopen when the builder loads
+const LAST_WORKFLOW_KEY = "stellrflow:lastWorkflow";
+
+// Workflows are stored under the chat ID of their Telegram trigger
+const getOwnerChatId = (nodes: Node<NodeData>[]): string | null => {
+  const trigger = nodes.find((n) => n.data?.type === "telegram-trigger");
+  const chatId = String(trigger?.data?.config?.chatId ?? "").trim();
+  return chatId || null;
+};
+
 export const useWorkflowStore = create<WorkflowState>((set, get) => ({
   nodes: [],
   edges: [],
@@ -242,6 +248,9 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
   isWorkflowRunning: false,
   nodeExecutionState: {},
   nodeResults: {},
+  workflowId: null,
+  workflowOwner: null,
+  workflowVersion: null,
 
   setNodes: (nodes) => set({ nodes }),
   onNodesChange: (changes) => {
@@ -683,6 +692,20 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
           break;
         }
 
+        case "condition": {
+          // Expressions are evaluated by stellrflow-engine, which routes the
+          // payload along the `true` or `false` handle.
+          set((state) => ({
+            nodeExecutionState: {
+              ...state.nodeExecutionState,
+              [nodeId]: "error",
+            },
+          }));
+          throw new Error(
+            "Condition nodes are evaluated by the server-side engine; run this workflow there"
+          );
+        }
+
         default: {
           if (
             ["discord-trigger", "whatsapp-trigger"].includes(
@@ -714,6 +737,64 @@ export const useWorkflowStore = create<WorkflowState>((set, get) => ({
     }
   },
 
-  saveWorkflow: () => { },
-  loadWorkflow: () => { },
+  saveWorkflow: async () => {
+    const { nodes, edges, workflowId, workflowOwner, workflowVersion } = get();
+    const chatId = getOwnerChatId(nodes);
+    if (!chatId) {
+      throw new Error(
+        "Add a Telegram Trigger with your Chat ID before saving. Send /register to the bot to get yours."
+      );
+    }
+
+    const { workflowApi } = await import("@/lib/utils/api-service");
+    const graph = {
+      nodes: nodes.map(({ selected, dragging, ...node }: any) => node),
+      edges: edges.map(({ selected, ...edge }: any) => edge),
+    };
+
+    // A new trigger chat ID means a new owner, so start a new workflow
+    const saved =
+      workflowId !== null && workflowOwner === chatId
+        ? await workflowApi.update(chatId, workflowId, {
+            graph,
+            baseVersion: workflowVersion ?? undefined,
+          })
+        : await workflowApi.create(chatId, { graph });
+
+    set({
+      workflowId: saved.id,
+      workflowOwner: chatId,
+      workflowVersion: saved.version,
+    });
+    localStorage.setItem(
+      LAST_WORKFLOW_KEY,
+      JSON.stringify({ chatId, id: saved.id })
+    );
+  },
+
+  loadWorkflow: async () => {
+    if (typeof window === "undefined") return;
+    const last = localStorage.getItem(LAST_WORKFLOW_KEY);
+    if (!last) return;
+
+    try {
+      const { chatId, id } = JSON.parse(last);
+      const { workflowApi } = await import("@/lib/utils/api-service");
+      const saved = await workflowApi.get(chatId, id);
+
+      set({
+        nodes: saved.graph.nodes,
+        edges: saved.graph.edges,
+        selectedNode: null,
+        selectedEdge: null,
+        nodeExecutionState: {},
+        nodeResults: {},
+        workflowId: saved.id,
+        workflowOwner: saved.ownerChatId,
+        workflowVersion: saved.version,
+      });
+    } catch (error) {
+      console.error("Failed to load workflow:", error);
+    }
+  },
 }));
@@ -301,6 +301,27 @@ export const nodeExecutors = {
       throw new Error("Telegram Chat ID is required");
     }
 
+    // `{{ path.to.field }}` placeholders. Formatting filters (`| amount`,
+    // `| short`, …) are applied by the server-side engine; here only
+    // `default(...)` is honoured and the raw value is used.
+    message = message.replace(
+      /\{\{\s*([\w-]+(?:\.[\w-]+)*)\s*(\|[^}]*)?\}\}/g,
+      (_match: string, path: string, filters?: string) => {
+        let value: any = inputData;
+        for (const key of path.split(".")) {
+          value = value?.[key];
+        }
+        const fallback = filters?.match(/default\(\s*["']([^"']*)["']\s*\)/);
+        if ((value === undefined || value === null) && fallback) {
+          value = fallback[1];
+        }
+        if (value === undefined) {
+          throw new Error(`Message template: unknown variable \`${path}\``);
+        }
+        return value === null ? "" : typeof value === "object" ? JSON.stringify(value) : String(value);
+      }
+    );
+    // Messages saved before templates only knew these two.
     if (inputData?.balance) {
       message = message.replace(/\{balance\}/g, String(inputData.balance));
     }
@@ -526,7 +547,7 @@ export const nodeExecutors = {
     const message =
       `✅ **Deposit Successful!**\n\n` +
       `**Deposited:** ${amount} ${currency}\n` +
-      `**Credited:** ${result.creditedXLM?.toFixed(4) || "—"} XLM\n` +
+      `**Credited:** ${result.creditedXLM ?? "—"} XLM\n` +
       `**Deposit ID:** \`${result.depositId}\`\n\n` +
       `Use /mybalance to check your updated balance.`;
 
@@ -573,7 +594,7 @@ export const nodeExecutors = {
     const message =
       `✅ **Withdrawal Processed!**\n\n` +
       `**Withdrawn:** ${xlmAmount} XLM\n` +
-      `**Payout:** ${result.fiatPayout?.toFixed(2) || "—"} ${currency}\n` +
+      `**Payout:** ${result.fiatPayout ?? "—"} ${currency}\n` +
       `**Withdrawal ID:** \`${result.withdrawalId}\`\n` +
       `**ETA:** ${result.eta || "5-10 min"}\n\n` +
       `_Demo: In production, funds would be sent to your bank._`;
@@ -601,7 +622,8 @@ export const nodeExecutors = {
     const destination = config.destination?.trim();
     const amount = parseFloat(config.amount) || 10;
     const interval = config.interval || "daily";
-    const duration = parseInt(config.duration) || 30;
+    // Sent as written ("30d", "2w"): the scheduler reads the interval.
+    const duration = String(config.duration ?? "").trim() || "30d";
 
     if (!destination) {
       throw new Error("Destination address is required for AutoPay");
@@ -624,17 +646,25 @@ export const nodeExecutors = {
       throw new Error(result.error || "AutoPay setup failed");
     }
 
-    // Notify user
-    const intervalText = interval === "daily" ? "every day" :
+    // Notify user. The scheduler describes the schedule exactly; older bots
+    // only echo the interval back.
+    const intervalText = result.frequency || (interval === "daily" ? "every day" :
       interval === "weekly" ? "every week" :
-        interval === "monthly" ? "every month" : interval;
+        interval === "monthly" ? "every month" : interval);
+    const upcoming: string[] = (result.upcomingPayments || []).map((at: string) =>
+      new Date(at).toISOString().slice(0, 16).replace("T", " ") + " UTC"
+    );
 
     const message =
       `✅ **AutoPay Activated!**\n\n` +
       `**Amount:** ${amount} XLM\n` +
       `**To:** \`${destination.slice(0, 8)}...${destination.slice(-8)}\`\n` +
       `**Frequency:** ${intervalText}\n` +
-      `**Duration:** ${duration} days\n` +
+      `**Duration:** ${typeof result.duration === "number" ? `${result.duration} days` : duration}\n` +
+      (upcoming.length ? `**Next payments:** ${upcoming.join(", ")}\n` : "") +
+      (result.totalAmount !== undefined
+        ? `**Total:** ${result.totalAmount} XLM over ${result.payments} payments\n`
+        : "") +
       `**Schedule ID:** \`${result.scheduleId}\`\n\n` +
       `Use /autopay to manage your schedules.`;
 
@@ -649,6 +679,9 @@ export const nodeExecutors = {
       interval,
       duration,
       nextPayment: result.nextPayment,
+      upcomingPayments: result.upcomingPayments,
+      payments: result.payments,
+      totalAmount: result.totalAmount,
     };
   },
 
@@ -661,7 +694,8 @@ export const nodeExecutors = {
 
     const threshold = parseInt(config.threshold) || 2;
     const signers = config.signers || [];
-    const timeout = parseInt(config.timeout) || 24;
+    // Sent as written ("24h", "2d"): the bot reads the interval.
+    const timeout = String(config.timeout ?? "").trim() || "24h";
 
     if (signers.length < threshold) {
       throw new Error(`Need at least ${threshold} signers configured`);
@@ -688,7 +722,7 @@ export const nodeExecutors = {
     const message =
       `✅ **Multisig Configured!**\n\n` +
       `**Threshold:** ${threshold} of ${signers.length} signatures required\n` +
-      `**Approval Timeout:** ${timeout} hours\n` +
+      `**Approval Timeout:** ${typeof result.timeout === "number" ? `${result.timeout} hours` : timeout}\n` +
       `**Multisig ID:** \`${result.multisigId}\`\n\n` +
       `Transactions will require ${threshold} approvals before execution.`;
 
@@ -704,3 +738,68 @@ export const nodeExecutors = {
     };
   },
 };
+
+// Workflow storage - uses the stellrflow-store service (crates/stellrflow-store)
+const WORKFLOW_STORE_URL =
+  (typeof process !== "undefined" &&
+    (process as any).env?.NEXT_PUBLIC_WORKFLOW_STORE_URL) ||
+  "http://localhost:3004";
+
+export type WorkflowDraft = {
+  name?: string;
+  graph: { nodes: any[]; edges: any[] };
+  baseVersion?: number;
+};
+
+export type SavedWorkflow = {
+  id: number;
+  ownerChatId: string;
+  name: string;
+  version: number;
+  createdAt: number;
+  updatedAt: number;
+  graph: { nodes: any[]; edges: any[] };
+};
+
+const workflowRequest = async (path: string, init?: RequestInit) => {
+  const response = await fetch(`${WORKFLOW_STORE_URL}/api/workflows/${path}`, {
+    ...init,
+    headers: { "Content-Type": "application/json" },
+  });
+  const result = await response.json();
+  if (!result.success) {
+    throw new Error(result.error || "Workflow storage request failed");
+  }
+  return result;
+};
+
+export const workflowApi = {
+  list: async (chatId: string): Promise<Omit<SavedWorkflow, "graph">[]> =>
+    (await workflowRequest(chatId)).workflows,
+
+  get: async (chatId: string, id: number): Promise<SavedWorkflow> =>
+    (await workflowRequest(`${chatId}/${id}`)).workflow,
+
+  create: async (chatId: string, draft: WorkflowDraft): Promise<SavedWorkflow> =>
+    (
+      await workflowRequest(chatId, {
+        method: "POST",
+        body: JSON.stringify(draft),
+      })
+    ).workflow,
+
+  update: async (
+    chatId: string,
+    id: number,
+    draft: WorkflowDraft
+  ): Promise<SavedWorkflow> =>
+    (
+      await workflowRequest(`${chatId}/${id}`, {
+        method: "PUT",
+        body: JSON.stringify(draft),
+      })
+    ).workflow,
+
+  remove: async (chatId: string, id: number) =>
+    workflowRequest(`${chatId}/${id}`, { method: "DELETE" }),
+};
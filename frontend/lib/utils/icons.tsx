@@ -1,7 +1,7 @@
 "use client";
 
 import React from 'react';
-import { Webhook, Clock, FormInput, Mail, Globe, Filter, Repeat, Workflow, MessageCircle, DivideIcon as LucideIcon } from 'lucide-react';
+import { Webhook, Clock, FormInput, Mail, Globe, Filter, Repeat, Workflow, MessageCircle, GitBranch, DivideIcon as LucideIcon } from 'lucide-react';
 
 type IconMap = {
   [key: string]: React.ReactNode;
@@ -17,6 +17,7 @@ const icons: IconMap = {
   repeat: <Repeat className="h-4 w-4" />,
   workflow: <Workflow className="h-4 w-4" />,
   messageCircle: <MessageCircle className="h-4 w-4" />,
+  gitBranch: <GitBranch className="h-4 w-4" />,
 };
 
 export function getIconByName(name: string): React.ReactNode {